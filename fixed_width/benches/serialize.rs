@@ -0,0 +1,33 @@
+//! Times serializing a record of 100 padded fields. Run with `cargo bench --bench serialize`.
+
+use fixed_width::{FieldSet, Serializer};
+use serde::Serialize;
+use std::{hint::black_box, time::Instant};
+
+const RECORDS: u32 = 200_000;
+
+fn main() {
+    let fields = FieldSet::Seq(
+        (0..100)
+            .map(|i| FieldSet::new_field(i * 10..i * 10 + 10).justify("right"))
+            .collect(),
+    )
+    .flatten();
+    let record: Vec<&str> = (0..100).map(|_| "abc").collect();
+    let mut out = Vec::with_capacity(1000);
+
+    let start = Instant::now();
+    for _ in 0..RECORDS {
+        out.clear();
+        let mut ser = Serializer::with_flattened(&mut out, &fields);
+        black_box(&record).serialize(&mut ser).unwrap();
+        black_box(&out);
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "100 field record: {:?} per record over {} records",
+        elapsed / RECORDS,
+        RECORDS
+    );
+}
@@ -0,0 +1,142 @@
+use crate::{FieldSet, Justify};
+
+/// Builds a `FieldSet` of consecutive fields from their widths, working out the range of each
+/// field from the end of the one before it. Created by `FieldSet::builder`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Justify};
+///
+/// let fields = FieldSet::builder()
+///     .field("id", 10)
+///     .field("state", 2)
+///     .filler(3)
+///     .field("amount", 8)
+///     .pad_with('0')
+///     .justify(Justify::Right)
+///     .build();
+///
+/// assert_eq!(fields, FieldSet::Seq(vec![
+///     FieldSet::new_field(0..10).name("id"),
+///     FieldSet::new_field(10..12).name("state"),
+///     FieldSet::new_literal(12..15, ""),
+///     FieldSet::new_field(15..23).name("amount").pad_with('0').justify(Justify::Right),
+/// ]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FieldSetBuilder {
+    fields: Vec<FieldSet>,
+    offset: usize,
+}
+
+impl FieldSetBuilder {
+    /// Adds a named field of the given width, starting where the previous field ended.
+    pub fn field<T: Into<String>>(self, name: T, width: usize) -> Self {
+        let start = self.offset;
+        self.push(FieldSet::new_field(start..start + width).name(name), width)
+    }
+
+    /// Adds an unnamed field of the given width, for records that are read by position.
+    pub fn unnamed(self, width: usize) -> Self {
+        let start = self.offset;
+        self.push(FieldSet::new_field(start..start + width), width)
+    }
+
+    /// Adds a gap of the given width, such as unused filler between fields. A gap is written as
+    /// padding and skipped when reading, so it does not take a value.
+    pub fn filler(self, width: usize) -> Self {
+        let start = self.offset;
+        self.push(FieldSet::new_literal(start..start + width, ""), width)
+    }
+
+    /// Sets the padding character of the field that was added last.
+    /// (Panics if no field has been added)
+    pub fn pad_with(self, val: char) -> Self {
+        self.map_last("pad_with", |fs| fs.pad_with(val))
+    }
+
+    /// Sets the justification of the field that was added last.
+    /// (Panics if no field has been added)
+    pub fn justify<T: Into<Justify>>(self, val: T) -> Self {
+        let val = val.into();
+        self.map_last("justify", |fs| fs.justify(val))
+    }
+
+    /// The offset at which the next field will start, which is the width of the fields so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Builds the `FieldSet`.
+    pub fn build(self) -> FieldSet {
+        FieldSet::Seq(self.fields)
+    }
+
+    fn push(mut self, field: FieldSet, width: usize) -> Self {
+        self.fields.push(field);
+        self.offset += width;
+        self
+    }
+
+    fn map_last<F: FnOnce(FieldSet) -> FieldSet>(mut self, setting: &str, f: F) -> Self {
+        match self.fields.pop() {
+            Some(field) => {
+                self.fields.push(f(field));
+                self
+            }
+            None => panic!("Setting {} before adding a field is not feasible.", setting),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builder() {
+        let fields = FieldSet::builder()
+            .field("id", 10)
+            .filler(2)
+            .unnamed(8)
+            .justify("right")
+            .field("notes", 30)
+            .pad_with('.')
+            .build();
+
+        assert_eq!(
+            fields,
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..10).name("id"),
+                FieldSet::new_literal(10..12, ""),
+                FieldSet::new_field(12..20).justify(Justify::Right),
+                FieldSet::new_field(20..50).name("notes").pad_with('.'),
+            ])
+        );
+        assert_eq!(fields.total_width(), 50);
+    }
+
+    #[test]
+    fn builder_filler_round_trip() {
+        let fields = FieldSet::builder()
+            .field("a", 3)
+            .filler(2)
+            .field("b", 3)
+            .build();
+
+        let mut w = crate::Writer::from_memory();
+        crate::to_writer_with_fields(&mut w, &("abc", "def"), fields.clone()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "abc  def");
+
+        let values: (String, String) = crate::from_str_with_fields("abcXXdef", fields).unwrap();
+        assert_eq!(values, ("abc".to_string(), "def".to_string()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn builder_setting_before_field() {
+        let _ = FieldSet::builder().pad_with('0');
+    }
+}
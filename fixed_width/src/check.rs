@@ -0,0 +1,488 @@
+use crate::{error::Error, LineBreak, Result};
+use std::{
+    fmt, fs,
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+const BYTE_ORDER_MARKS: [(&[u8], &str); 3] = [
+    (b"\xEF\xBB\xBF", "UTF-8"),
+    (b"\xFF\xFE", "UTF-16LE"),
+    (b"\xFE\xFF", "UTF-16BE"),
+];
+
+/// Linebreaks that may end a file of records without linebreaks, longest first.
+const STRAY_LINEBREAKS: [&[u8]; 2] = [b"\r\n", b"\n"];
+
+/// Checks that the size of the file at the given path fits records of the given width separated
+/// by the given linebreak, without parsing it. See `FileCheck` for what is checked, and to allow
+/// for header lines.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{check_file, FileIssue, LineBreak};
+/// use std::fs;
+///
+/// let path = std::env::temp_dir().join("fixed_width_check_file_doc.txt");
+/// fs::write(&path, "aaaa\r\nbbbb\r\ncc").unwrap();
+///
+/// let report = check_file(&path, 4, LineBreak::CRLF).unwrap();
+///
+/// assert_eq!(report.records, 2);
+/// assert_eq!(report.trailing_bytes, 2);
+/// assert_eq!(report.issues, vec![FileIssue::IncompleteRecord { index: 2, bytes: 2 }]);
+/// # fs::remove_file(&path).unwrap();
+/// ```
+pub fn check_file<P: AsRef<Path>>(
+    path: P,
+    width: usize,
+    linebreak: LineBreak,
+) -> Result<FileReport> {
+    FileCheck::new(width, linebreak).check_path(path)
+}
+
+/// A problem with the layout of a file found by a `FileCheck`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FileIssue {
+    /// The file starts with a byte order mark for the named encoding, ie `UTF-8`. Records are
+    /// counted from after it, but a `Reader` reads it as part of the first record unless
+    /// `Reader::skip_bom` is set.
+    ByteOrderMark(&'static str),
+    /// The file ended before all of its header lines.
+    IncompleteHeader {
+        /// The number of header lines expected.
+        expected: usize,
+        /// The number of header lines found.
+        found: usize,
+    },
+    /// The file ends part way through a record.
+    IncompleteRecord {
+        /// The zero based index of the incomplete record.
+        index: u64,
+        /// The number of bytes of the incomplete record.
+        bytes: u64,
+    },
+    /// The file ends with a linebreak, although its records are not separated by linebreaks.
+    UnexpectedLinebreak,
+    /// The bytes where a linebreak should be, between two records, are not the linebreak.
+    LinebreakMismatch {
+        /// The offset in bytes of the linebreak from the start of the file.
+        offset: u64,
+        /// The bytes of the expected linebreak.
+        expected: Vec<u8>,
+        /// The bytes that were found instead.
+        found: Vec<u8>,
+    },
+}
+
+impl fmt::Display for FileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileIssue::ByteOrderMark(encoding) => {
+                write!(f, "file starts with a {} byte order mark", encoding)
+            }
+            FileIssue::IncompleteHeader { expected, found } => {
+                write!(f, "file ended after {} of {} header lines", found, expected)
+            }
+            FileIssue::IncompleteRecord { index, bytes } => write!(
+                f,
+                "file ends part way through record {}, with {} bytes left over",
+                index, bytes
+            ),
+            FileIssue::UnexpectedLinebreak => {
+                write!(f, "file ends with a linebreak, but records have none")
+            }
+            FileIssue::LinebreakMismatch {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected linebreak {:?} at byte {}, found {:?}",
+                String::from_utf8_lossy(expected),
+                offset,
+                String::from_utf8_lossy(found)
+            ),
+        }
+    }
+}
+
+/// What a `FileCheck` found out about a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    /// The length of the file in bytes.
+    pub len: u64,
+    /// The number of bytes before the first record, taken up by a byte order mark and header
+    /// lines. Can be given to `Reader::data_offset`.
+    pub data_offset: u64,
+    /// The number of complete records.
+    pub records: u64,
+    /// The number of bytes after the last complete record that do not make up a record, apart
+    /// from a linebreak after it.
+    pub trailing_bytes: u64,
+    /// Whether the file ends with `\n`.
+    pub ends_with_linebreak: bool,
+    /// The problems found with the file.
+    pub issues: Vec<FileIssue>,
+}
+
+impl FileReport {
+    /// Whether no issues were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A quick check that a file is made up of records of a given width separated by a given
+/// linebreak, made from the length of the file and a few reads from its start and end, without
+/// parsing it. Catches most files read with the wrong width or linebreak before any records are
+/// parsed. Each of these is reported as a separate `FileIssue`:
+///
+/// * the file ends part way through a record;
+/// * the file ends with a linebreak although records are not separated by linebreaks;
+/// * the bytes between the first two records, or the last two, are not the linebreak;
+/// * the file starts with a byte order mark.
+///
+/// The last record does not need a linebreak after it.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FileCheck, FileIssue, LineBreak};
+/// use std::io::Cursor;
+///
+/// let data = "id  name\naaaa\nbbbb\n";
+/// let report = FileCheck::new(4, LineBreak::Newline)
+///     .header_lines(1)
+///     .check(Cursor::new(data))
+///     .unwrap();
+///
+/// assert!(report.is_valid());
+/// assert_eq!(report.records, 2);
+/// assert_eq!(report.data_offset, 9);
+/// assert!(report.ends_with_linebreak);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCheck {
+    width: usize,
+    linebreak: LineBreak,
+    header_lines: usize,
+}
+
+impl FileCheck {
+    /// Creates a new check for records of the given width, not counting the linebreak.
+    pub fn new(width: usize, linebreak: LineBreak) -> Self {
+        Self {
+            width,
+            linebreak,
+            header_lines: 0,
+        }
+    }
+
+    /// Sets the number of lines before the first record, each ending with `\n`, which are
+    /// skipped over. Defaults to 0.
+    pub fn header_lines(mut self, lines: usize) -> Self {
+        self.header_lines = lines;
+        self
+    }
+
+    /// Checks the file at the given path.
+    pub fn check_path<P: AsRef<Path>>(&self, path: P) -> Result<FileReport> {
+        self.check(fs::File::open(path)?)
+    }
+
+    /// Checks the given data. Fails with `Error::InvalidWidth` for a width of 0, or if the data
+    /// cannot be read.
+    pub fn check<R: Read + Seek>(&self, mut rdr: R) -> Result<FileReport> {
+        if self.width == 0 {
+            return Err(Error::InvalidWidth {
+                width: 0,
+                required: 1,
+            });
+        }
+
+        let len = rdr.seek(SeekFrom::End(0))?;
+        rdr.seek(SeekFrom::Start(0))?;
+
+        let mut report = FileReport {
+            len,
+            data_offset: 0,
+            records: 0,
+            trailing_bytes: 0,
+            ends_with_linebreak: false,
+            issues: Vec::new(),
+        };
+
+        self.read_head(&mut rdr, &mut report)?;
+
+        let start = report.data_offset;
+        let data_len = len - start;
+        let tail = read_at(&mut rdr, len - data_len.min(2), 2)?;
+        report.ends_with_linebreak = tail.ends_with(b"\n");
+
+        let linebreak = self.linebreak.as_bytes();
+        let width = self.width as u64;
+        let stride = width + linebreak.len() as u64;
+
+        if linebreak.is_empty() {
+            // A trailing `\r\n` or `\n` that leaves whole records is a stray linebreak.
+            let stray = STRAY_LINEBREAKS.iter().find(|lb| {
+                tail.ends_with(lb) && data_len >= width && (data_len - lb.len() as u64) % width == 0
+            });
+
+            match stray {
+                Some(lb) => {
+                    report.records = (data_len - lb.len() as u64) / width;
+                    report.issues.push(FileIssue::UnexpectedLinebreak);
+                }
+                None => self.count_records(&mut report, data_len, stride),
+            }
+            return Ok(report);
+        }
+
+        self.count_records(&mut report, data_len, stride);
+
+        // The linebreaks after the first record and before the last.
+        let mut offsets = vec![start + width];
+        if report.records > 2 {
+            offsets.push(start + (report.records - 1) * stride - linebreak.len() as u64);
+        }
+
+        for offset in offsets {
+            if offset + linebreak.len() as u64 > len {
+                continue;
+            }
+
+            let found = read_at(&mut rdr, offset, linebreak.len())?;
+            if found != linebreak {
+                report.issues.push(FileIssue::LinebreakMismatch {
+                    offset,
+                    expected: linebreak.to_vec(),
+                    found,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Skips over any byte order mark and header lines, reporting them.
+    fn read_head<R: Read>(&self, rdr: &mut R, report: &mut FileReport) -> Result<()> {
+        let mut rdr = io::BufReader::new(rdr);
+
+        let head = rdr.fill_buf()?;
+        let bom = BYTE_ORDER_MARKS
+            .iter()
+            .find(|(bom, _)| head.starts_with(bom));
+
+        if let Some((bom, encoding)) = bom {
+            rdr.consume(bom.len());
+            report.data_offset += bom.len() as u64;
+            report.issues.push(FileIssue::ByteOrderMark(encoding));
+        }
+
+        let mut line = Vec::new();
+        for found in 0..self.header_lines {
+            line.clear();
+            let read = rdr.read_until(b'\n', &mut line)?;
+            report.data_offset += read as u64;
+
+            if !line.ends_with(b"\n") {
+                report.issues.push(FileIssue::IncompleteHeader {
+                    expected: self.header_lines,
+                    found,
+                });
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts the records in `data_len` bytes, reporting any incomplete record at the end.
+    fn count_records(&self, report: &mut FileReport, data_len: u64, stride: u64) {
+        let linebreak = self.linebreak.byte_width() as u64;
+
+        if data_len % stride == 0 {
+            report.records = data_len / stride;
+        } else if (data_len + linebreak) % stride == 0 {
+            // The last record has no linebreak after it.
+            report.records = (data_len + linebreak) / stride;
+        } else {
+            report.records = data_len / stride;
+            report.trailing_bytes = data_len % stride;
+            report.issues.push(FileIssue::IncompleteRecord {
+                index: report.records,
+                bytes: report.trailing_bytes,
+            });
+        }
+    }
+}
+
+/// Reads up to `len` bytes from the given offset.
+fn read_at<R: Read + Seek>(rdr: &mut R, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(len);
+    rdr.seek(SeekFrom::Start(offset))?;
+    rdr.take(len as u64).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn check(data: &str, width: usize, linebreak: LineBreak) -> FileReport {
+        FileCheck::new(width, linebreak)
+            .check(Cursor::new(data))
+            .unwrap()
+    }
+
+    #[test]
+    fn whole_records() {
+        for (data, linebreak) in [
+            ("aaaabbbbcccc", LineBreak::None),
+            ("aaaa\nbbbb\ncccc", LineBreak::Newline),
+            ("aaaa\nbbbb\ncccc\n", LineBreak::Newline),
+            ("aaaa\r\nbbbb\r\ncccc", LineBreak::CRLF),
+            ("aaaa\r\nbbbb\r\ncccc\r\n", LineBreak::CRLF),
+        ] {
+            let report = check(data, 4, linebreak);
+
+            assert!(report.is_valid(), "{:?}: {:?}", data, report.issues);
+            assert_eq!(report.records, 3);
+            assert_eq!(report.trailing_bytes, 0);
+            assert_eq!(report.ends_with_linebreak, data.ends_with('\n'));
+            assert_eq!(report.len, data.len() as u64);
+        }
+    }
+
+    #[test]
+    fn empty_file() {
+        let report = check("", 4, LineBreak::CRLF);
+
+        assert!(report.is_valid());
+        assert_eq!(report.records, 0);
+        assert!(!report.ends_with_linebreak);
+    }
+
+    #[test]
+    fn incomplete_record() {
+        let report = check("aaaa\nbbbb\ncc", 4, LineBreak::Newline);
+
+        assert_eq!(report.records, 2);
+        assert_eq!(report.trailing_bytes, 2);
+        assert_eq!(
+            report.issues,
+            vec![FileIssue::IncompleteRecord { index: 2, bytes: 2 }]
+        );
+
+        let report = check("aaaabbbbcc", 4, LineBreak::None);
+        assert_eq!(
+            report.issues,
+            vec![FileIssue::IncompleteRecord { index: 2, bytes: 2 }]
+        );
+    }
+
+    #[test]
+    fn unexpected_trailing_linebreak() {
+        for data in ["aaaabbbb\n", "aaaabbbb\r\n"] {
+            let report = check(data, 4, LineBreak::None);
+
+            assert_eq!(report.records, 2);
+            assert_eq!(report.trailing_bytes, 0);
+            assert!(report.ends_with_linebreak);
+            assert_eq!(report.issues, vec![FileIssue::UnexpectedLinebreak]);
+        }
+
+        // A last byte of `\n` that is part of a whole record is data.
+        let report = check("aaaabbb\n", 4, LineBreak::None);
+        assert!(report.is_valid());
+        assert!(report.ends_with_linebreak);
+    }
+
+    #[test]
+    fn wrong_linebreak() {
+        let report = check("aaaa\r\nbbbb\r\ncccc\r\n", 4, LineBreak::Newline);
+
+        assert_eq!(
+            report.issues[0],
+            FileIssue::IncompleteRecord { index: 3, bytes: 3 }
+        );
+        assert_eq!(
+            report.issues[1],
+            FileIssue::LinebreakMismatch {
+                offset: 4,
+                expected: b"\n".to_vec(),
+                found: b"\r".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_width() {
+        // Records of 5 bytes read as records of 4 happen to fill whole records, but the
+        // linebreaks are out of place.
+        let report = check("aaaaa\nbbbbb\nccccc\nddddd\neeeee", 4, LineBreak::Newline);
+
+        assert_eq!(report.records, 6);
+        assert_eq!(report.issues.len(), 2);
+        assert!(matches!(
+            report.issues[0],
+            FileIssue::LinebreakMismatch { offset: 4, .. }
+        ));
+        assert!(matches!(
+            report.issues[1],
+            FileIssue::LinebreakMismatch { offset: 24, .. }
+        ));
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let report = check("\u{feff}aaaa\nbbbb", 4, LineBreak::Newline);
+
+        assert_eq!(report.data_offset, 3);
+        assert_eq!(report.records, 2);
+        assert_eq!(report.issues, vec![FileIssue::ByteOrderMark("UTF-8")]);
+        assert_eq!(
+            report.issues[0].to_string(),
+            "file starts with a UTF-8 byte order mark"
+        );
+    }
+
+    #[test]
+    fn header_lines() {
+        let report = FileCheck::new(4, LineBreak::CRLF)
+            .header_lines(2)
+            .check(Cursor::new("HEADER ONE\r\nTWO\r\naaaa\r\nbbbb"))
+            .unwrap();
+
+        assert!(report.is_valid());
+        assert_eq!(report.data_offset, 17);
+        assert_eq!(report.records, 2);
+
+        let report = FileCheck::new(4, LineBreak::CRLF)
+            .header_lines(2)
+            .check(Cursor::new("HEADER ONE\r\n"))
+            .unwrap();
+
+        assert_eq!(
+            report.issues,
+            vec![FileIssue::IncompleteHeader {
+                expected: 2,
+                found: 1
+            }]
+        );
+        assert_eq!(report.records, 0);
+    }
+
+    #[test]
+    fn zero_width() {
+        assert!(matches!(
+            FileCheck::new(0, LineBreak::None).check(Cursor::new("aaaa")),
+            Err(Error::InvalidWidth { width: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,386 @@
+use crate::{
+    ser, DeserializeError, Deserializer, Error, FieldConfig, FieldSerializeReason, FieldSet,
+    Reader, Result, Writer,
+};
+use serde::Deserialize;
+use std::io::{self, BufReader, Read, Write};
+
+/// Counts of the work done by `to_csv` or `from_csv`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CsvStats {
+    /// The number of records converted, not counting the header row.
+    pub records: u64,
+}
+
+/// Converts fixed width records to CSV. A header row of field names, or ranges for fields without
+/// a name, is written first, followed by a row per record. Values are trimmed as when
+/// deserializing into a `String`, and quoted when they hold a comma, a quote or a linebreak.
+/// Nested fields are flattened into columns and literal fields are left out.
+///
+/// An error deserializing a record is returned as an `Error::Record` with the record's index.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Reader};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6).name("name"),
+///     FieldSet::new_field(6..10).name("age"),
+/// ]);
+///
+/// let mut reader = Reader::from_string("Carl  0042Jo,Bo 0007").width(10);
+/// let mut csv = Vec::new();
+/// let stats = fixed_width::to_csv(&mut reader, &fields, &mut csv).unwrap();
+///
+/// assert_eq!(stats.records, 2);
+/// assert_eq!(String::from_utf8(csv).unwrap(), "name,age\nCarl,0042\n\"Jo,Bo\",0007\n");
+/// ```
+pub fn to_csv<R: Read, W: Write>(
+    reader: &mut Reader<R>,
+    fields: &FieldSet,
+    out: W,
+) -> Result<CsvStats> {
+    let columns = columns(fields);
+    let header: Vec<String> = columns.iter().map(FieldConfig::describe).collect();
+
+    let mut out = io::BufWriter::new(out);
+    let mut stats = CsvStats::default();
+    write_row(&mut out, &header)?;
+
+    while let Some(record) = reader.next_record() {
+        let mut de = Deserializer::with_flattened_ref(record?, &columns);
+        let row = Vec::<String>::deserialize(&mut de).map_err(|e| Error::Record {
+            index: stats.records,
+            source: Box::new(Error::from(e)),
+        })?;
+        write_row(&mut out, &row)?;
+        stats.records += 1;
+    }
+
+    out.flush()?;
+    Ok(stats)
+}
+
+/// Converts CSV to fixed width records, the inverse of `to_csv`. The first row is taken to be a
+/// header and is skipped. Every other row must have a value for each field that is not a literal,
+/// in the order of the fields. Values are padded to the width of their fields. A value wider than
+/// its field is a `FieldSerializeReason::TooWide` error rather than being cut short, and a value
+/// holding a linebreak is a `FieldSerializeReason::Unencodable` error, as it would split the
+/// record. Literal fields are written with their values. Records are separated by the linebreak
+/// of the writer.
+///
+/// An error in a row is returned as an `Error::Record` with the index of the record, not counting
+/// the header row.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Writer};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6).name("name"),
+///     FieldSet::new_field(6..10).name("age"),
+/// ]);
+///
+/// let csv = "name,age\nCarl,0042\n\"Jo,Bo\",0007\n";
+/// let mut writer = Writer::from_memory();
+/// let stats = fixed_width::from_csv(csv.as_bytes(), &fields, &mut writer).unwrap();
+///
+/// assert_eq!(stats.records, 2);
+/// assert_eq!(String::from(writer), "Carl  0042Jo,Bo 0007");
+/// ```
+pub fn from_csv<R: Read, W: Write>(
+    input: R,
+    fields: &FieldSet,
+    writer: &mut Writer<W>,
+) -> Result<CsvStats> {
+    let columns = columns(fields);
+    let width = columns.len();
+    let mut rows = CsvRows::new(input);
+    let mut stats = CsvStats::default();
+
+    if let Some(header) = rows.next() {
+        header?;
+    }
+
+    for row in rows {
+        let record_error = |e: Error| Error::Record {
+            index: stats.records,
+            source: Box::new(e),
+        };
+
+        let row = row.map_err(record_error)?;
+        if row.len() != width {
+            return Err(record_error(Error::from(DeserializeError::Message(
+                format!("expected {} values, got {}", width, row.len()),
+            ))));
+        }
+        for (value, conf) in row.iter().zip(&columns) {
+            check_value(value, conf).map_err(record_error)?;
+        }
+
+        if stats.records > 0 {
+            writer.write_linebreak()?;
+        }
+        ser::to_writer_with_fields(writer, &row, fields.clone()).map_err(record_error)?;
+        stats.records += 1;
+    }
+
+    writer.flush()?;
+    Ok(stats)
+}
+
+/// The flattened fields that hold values, leaving out literals.
+fn columns(fields: &FieldSet) -> Vec<FieldConfig> {
+    fields
+        .iter_flat()
+        .filter(|conf| conf.literal.is_none())
+        .cloned()
+        .collect()
+}
+
+/// Fails for a value that cannot be written to its field as it is.
+fn check_value(value: &str, conf: &FieldConfig) -> Result<()> {
+    if let Some(char) = value.chars().find(|c| matches!(c, '\r' | '\n')) {
+        return Err(ser::field_error(
+            conf,
+            FieldSerializeReason::Unencodable { char },
+        ));
+    }
+
+    if !(conf.to_end || conf.capture_only || conf.write_as_blank) && value.len() > conf.width() {
+        return Err(ser::field_error(
+            conf,
+            FieldSerializeReason::TooWide {
+                actual: value.len(),
+            },
+        ));
+    }
+    Ok(())
+}
+
+fn write_row<W: Write>(out: &mut W, row: &[String]) -> Result<()> {
+    for (i, value) in row.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+
+        if value.contains(&[',', '"', '\r', '\n'][..]) {
+            write!(out, "\"{}\"", value.replace('"', "\"\""))?;
+        } else {
+            out.write_all(value.as_bytes())?;
+        }
+    }
+
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// An iterator of the rows of CSV data. Empty lines are skipped.
+struct CsvRows<R: Read> {
+    bytes: std::iter::Peekable<io::Bytes<BufReader<R>>>,
+}
+
+impl<R: Read> CsvRows<R> {
+    fn new(input: R) -> Self {
+        Self {
+            bytes: BufReader::new(input).bytes().peekable(),
+        }
+    }
+
+    fn next_row(&mut self) -> Result<Option<Vec<String>>> {
+        let mut row = Vec::new();
+        let mut field = Vec::new();
+        let mut in_quotes = false;
+        let mut quoted = false;
+
+        loop {
+            let b = match self.bytes.next() {
+                Some(b) => b?,
+                None if in_quotes => {
+                    return Err(Error::from(DeserializeError::Message(
+                        "unterminated quoted value".to_string(),
+                    )))
+                }
+                None if row.is_empty() && field.is_empty() && !quoted => return Ok(None),
+                None => {
+                    row.push(String::from_utf8(field).map_err(Error::FormatError)?);
+                    return Ok(Some(row));
+                }
+            };
+
+            if in_quotes {
+                match b {
+                    b'"' if matches!(self.bytes.peek(), Some(Ok(b'"'))) => {
+                        self.bytes.next();
+                        field.push(b'"');
+                    }
+                    b'"' => in_quotes = false,
+                    b => field.push(b),
+                }
+                continue;
+            }
+
+            match b {
+                b'"' if field.is_empty() => {
+                    in_quotes = true;
+                    quoted = true;
+                }
+                b',' => {
+                    row.push(
+                        String::from_utf8(std::mem::take(&mut field))
+                            .map_err(Error::FormatError)?,
+                    );
+                    quoted = false;
+                }
+                b'\n' => {
+                    if field.last() == Some(&b'\r') {
+                        field.pop();
+                    }
+                    if row.is_empty() && field.is_empty() && !quoted {
+                        continue;
+                    }
+                    row.push(String::from_utf8(field).map_err(Error::FormatError)?);
+                    return Ok(Some(row));
+                }
+                b => field.push(b),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for CsvRows<R> {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_row().transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Justify, LineBreak};
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..11).name("name"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(11..14)
+                    .name("qty")
+                    .justify(Justify::Right),
+                FieldSet::new_field(14..20)
+                    .name("amount")
+                    .justify(Justify::Right)
+                    .pad_with('0'),
+            ]),
+            FieldSet::new_field(20..30),
+        ])
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let data = [
+            "D",
+            "Smith, Jo ",
+            " 12",
+            "012.50",
+            "say \"hi\"  ",
+            "\n",
+            "D",
+            "Brown     ",
+            "  7",
+            "001234",
+            "          ",
+        ]
+        .concat();
+        let mut reader = Reader::from_string(data.as_str())
+            .width(30)
+            .linebreak(LineBreak::Newline);
+
+        let mut csv = Vec::new();
+        let stats = to_csv(&mut reader, &fields(), &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+
+        assert_eq!(stats.records, 2);
+        assert_eq!(
+            csv,
+            "name,qty,amount,20..30\n\"Smith, Jo\",12,012.50,\"say \"\"hi\"\"\"\nBrown,7,001234,\n"
+        );
+
+        let mut writer = Writer::from_memory().linebreak(LineBreak::Newline);
+        let stats = from_csv(csv.as_bytes(), &fields(), &mut writer).unwrap();
+        assert_eq!(stats.records, 2);
+        assert_eq!(String::from(writer), data);
+    }
+
+    #[test]
+    fn csv_parsing() {
+        let csv = "a,b,c,d\r\n\r\n\"multi,line\",1,\"\",x\r\nlong name,,2,\n";
+        let mut writer = Writer::from_memory().linebreak(LineBreak::Newline);
+        from_csv(csv.as_bytes(), &fields(), &mut writer).unwrap();
+
+        assert_eq!(
+            String::from(writer),
+            "Dmulti,line  1000000x         \nDlong name    000002          "
+        );
+    }
+
+    #[test]
+    fn csv_errors() {
+        let mut writer = Writer::from_memory();
+        let err = from_csv("h\na,1,2,x\nb,1,2\n".as_bytes(), &fields(), &mut writer).unwrap_err();
+        assert_eq!(err.to_string(), "record 1, expected 4 values, got 3");
+        match err {
+            Error::Record {
+                index: 1,
+                ref source,
+            } => {
+                assert_eq!(source.to_string(), "expected 4 values, got 3")
+            }
+            ref other => panic!("expected a record error, got {:?}", other),
+        }
+
+        let mut writer = Writer::from_memory();
+        let err = from_csv("h\n\"a,1,2,x\n".as_bytes(), &fields(), &mut writer).unwrap_err();
+        assert!(matches!(err, Error::Record { index: 0, .. }));
+
+        let mut writer = Writer::from_memory();
+        let csv = "h\n\"multi\r\nline\",1,2,x\n";
+        let err = from_csv(csv.as_bytes(), &fields(), &mut writer).unwrap_err();
+        match err {
+            Error::Record {
+                index: 0,
+                ref source,
+            } => {
+                assert_eq!(
+                    source.to_string(),
+                    "field 'name' (1..11): char '\\r' cannot be written to the field"
+                )
+            }
+            ref other => panic!("expected a record error, got {:?}", other),
+        }
+
+        let mut writer = Writer::from_memory();
+        let csv = "h\na,1,2,x\nlong name overflowing,1,2,x\n";
+        let err = from_csv(csv.as_bytes(), &fields(), &mut writer).unwrap_err();
+        match err {
+            Error::Record {
+                index: 1,
+                ref source,
+            } => assert_eq!(
+                source.to_string(),
+                "field 'name' (1..11): value of 21 bytes is too wide for the field"
+            ),
+            ref other => panic!("expected a record error, got {:?}", other),
+        }
+
+        let mut record = b"D\xff".to_vec();
+        record.resize(30, b' ');
+        let mut reader = Reader::from_bytes(record).width(30);
+        let err = to_csv(&mut reader, &fields(), io::sink()).unwrap_err();
+        assert!(matches!(err, Error::Record { index: 0, .. }));
+    }
+}
@@ -1,9 +1,14 @@
-use crate::{error, FieldSet, FixedWidth};
+use crate::{
+    error, DecimalSeparator, FieldConfig, FieldSet, FixedWidth, Flags, Justify, Sign, RANGE_KEY_LEN,
+};
 use serde::{
     self,
     de::{self, Deserialize, Error, IntoDeserializer, Visitor},
 };
-use std::{convert, error::Error as StdError, fmt, iter, num, result::Result, str, vec};
+use std::{
+    borrow::Cow, convert, error::Error as StdError, fmt, iter, num, ops::Range, result::Result,
+    slice, str, vec,
+};
 
 /// Deserializes a `&str` into the given type that implements `FixedWidth` and `Deserialize`.
 ///
@@ -76,7 +81,161 @@ pub fn from_bytes<'de, T>(b: &'de [u8]) -> Result<T, error::Error>
 where
     T: FixedWidth + Deserialize<'de>,
 {
-    from_bytes_with_fields(b, T::fields())
+    match T::flattened_fields() {
+        Some(fields) => deserialize_all(&mut Deserializer::with_flattened_ref(b, fields)),
+        None => from_bytes_with_fields(b, T::fields()),
+    }
+}
+
+/// Deserializes a `&str` like `from_str`, but also returns an error for any fields of nested
+/// structs that were not deserialized, or any data after the last field other than spaces.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, FixedWidth};
+///
+/// #[derive(Deserialize)]
+/// struct Record {
+///     pub name: String,
+/// }
+///
+/// impl FixedWidth for Record {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..4).name("name"),
+///             FieldSet::new_field(4..8).name("room"),
+///         ])
+///     }
+/// }
+///
+/// let err = fixed_width::from_str_strict::<Record>("Carl1234").err().unwrap();
+/// assert_eq!(err.to_string(), "fields were not deserialized: room");
+/// ```
+pub fn from_str_strict<'de, T>(s: &'de str) -> Result<T, error::Error>
+where
+    T: FixedWidth + Deserialize<'de>,
+{
+    from_bytes_strict(s.as_bytes())
+}
+
+/// Deserializes a `&[u8]` like `from_bytes`, but also returns an error for any fields of nested
+/// structs that were not deserialized, or any data after the last field other than spaces.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, FixedWidth};
+///
+/// #[derive(Deserialize)]
+/// struct Record {
+///     pub name: String,
+///     pub room: usize,
+/// }
+///
+/// impl FixedWidth for Record {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..4),
+///             FieldSet::new_field(4..8),
+///         ])
+///     }
+/// }
+///
+/// assert!(fixed_width::from_bytes_strict::<Record>(b"Carl1234    ").is_ok());
+/// assert!(fixed_width::from_bytes_strict::<Record>(b"Carl12345678").is_err());
+/// ```
+pub fn from_bytes_strict<'de, T>(b: &'de [u8]) -> Result<T, error::Error>
+where
+    T: FixedWidth + Deserialize<'de>,
+{
+    let mut de = Deserializer::new(b, T::fields()).strict(true);
+    let value = T::deserialize(&mut de)?;
+    de.finish()?;
+    Ok(value)
+}
+
+/// Deserializes a `&str` like `from_str`, but matches the fields of a struct to its members by
+/// name rather than by position, as `Deserializer::by_name` does.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, FixedWidth};
+///
+/// #[derive(Deserialize)]
+/// struct Person {
+///     pub height: usize,
+///     pub age: usize,
+/// }
+///
+/// impl FixedWidth for Person {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..3).name("age"),
+///             FieldSet::new_field(3..6).name("height"),
+///         ])
+///     }
+/// }
+///
+/// // By position, the age is read as the height.
+/// let person: Person = fixed_width::from_str(" 42180").unwrap();
+/// assert_eq!((person.height, person.age), (42, 180));
+///
+/// let person: Person = fixed_width::from_str_by_name(" 42180").unwrap();
+/// assert_eq!((person.height, person.age), (180, 42));
+/// ```
+pub fn from_str_by_name<'de, T>(s: &'de str) -> Result<T, error::Error>
+where
+    T: FixedWidth + Deserialize<'de>,
+{
+    from_bytes_by_name(s.as_bytes())
+}
+
+/// Deserializes a `&[u8]` like `from_bytes`, but matches the fields of a struct to its members
+/// by name rather than by position, as `Deserializer::by_name` does. Members are matched to the
+/// names of the fields, as renamed by `#[serde(rename)]`, and fields no member is named after
+/// are skipped. A member with no field of its name is missing, as it would be from a map.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, FixedWidth};
+///
+/// #[derive(Deserialize)]
+/// struct Record {
+///     pub room: usize,
+///     #[serde(rename = "name")]
+///     pub person: String,
+/// }
+///
+/// impl FixedWidth for Record {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..4).name("name"),
+///             FieldSet::new_field(4..8).name("floor"),
+///             FieldSet::new_field(8..12).name("room"),
+///         ])
+///     }
+/// }
+///
+/// let record: Record = fixed_width::from_bytes_by_name(b"Carl   31234").unwrap();
+/// assert_eq!(record.person, "Carl");
+/// assert_eq!(record.room, 1234);
+/// ```
+pub fn from_bytes_by_name<'de, T>(b: &'de [u8]) -> Result<T, error::Error>
+where
+    T: FixedWidth + Deserialize<'de>,
+{
+    let de = match T::flattened_fields() {
+        Some(fields) => Deserializer::with_flattened_ref(b, fields),
+        None => Deserializer::new(b, T::fields()),
+    };
+    T::deserialize(&mut de.by_name(true)).map_err(convert::Into::into)
 }
 
 /// Deserializes `&str` data to the given writer using the provided `Field`s.
@@ -104,7 +263,10 @@ where
     from_bytes_with_fields(s.as_bytes(), fields)
 }
 
-/// Deserializes `&[u8]` data to the given writer using the provided `Field`s.
+/// Deserializes `&[u8]` data to the given writer using the provided `Field`s. Returns a
+/// `DeserializeError::UnconsumedFields` if the value does not take every field, as when a struct
+/// has fewer members than the layout has fields. To read just the leading fields of a layout,
+/// use a `Deserializer` or `Reader` with `allow_trailing_fields` set.
 ///
 /// ### Example
 ///
@@ -126,19 +288,163 @@ pub fn from_bytes_with_fields<'de, T>(bytes: &'de [u8], fields: FieldSet) -> Res
 where
     T: Deserialize<'de>,
 {
-    let mut de = Deserializer::new(bytes, fields);
-    T::deserialize(&mut de).map_err(convert::Into::into)
+    deserialize_all(&mut Deserializer::new(bytes, fields))
+}
+
+/// Deserializes `&str` data using a borrowed set of `Field`s, as `from_str_with_fields` does
+/// but without taking ownership of the fields, so the same fields can be used for every record
+/// without cloning them.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, from_str_with_fields_ref};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4),
+///     FieldSet::new_field(4..8),
+/// ]);
+///
+/// for s in &["1234abcd", "5678efgh"] {
+///     let (numbers, letters): (u32, String) = from_str_with_fields_ref(s, &fields).unwrap();
+///     assert_eq!(format!("{}{}", numbers, letters), *s);
+/// }
+/// ```
+pub fn from_str_with_fields_ref<'de, T>(
+    s: &'de str,
+    fields: &'de FieldSet,
+) -> Result<T, error::Error>
+where
+    T: Deserialize<'de>,
+{
+    from_bytes_with_fields_ref(s.as_bytes(), fields)
+}
+
+/// Deserializes `&[u8]` data using a borrowed set of `Field`s, as `from_bytes_with_fields` does
+/// but without taking ownership of the fields, so the same fields can be used for every record
+/// without cloning them.
+///
+/// ### Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use fixed_width::{FieldSet, from_bytes_with_fields_ref};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("numbers"),
+///     FieldSet::new_field(4..8).name("letters"),
+/// ]);
+///
+/// for bytes in &[b"1234abcd", b"5678efgh"] {
+///     let h: HashMap<String, String> = from_bytes_with_fields_ref(*bytes, &fields).unwrap();
+///     assert_eq!(h["numbers"].as_bytes(), &bytes[..4]);
+///     assert_eq!(h["letters"].as_bytes(), &bytes[4..]);
+/// }
+/// ```
+pub fn from_bytes_with_fields_ref<'de, T>(
+    bytes: &'de [u8],
+    fields: &'de FieldSet,
+) -> Result<T, error::Error>
+where
+    T: Deserialize<'de>,
+{
+    deserialize_all(&mut Deserializer::with_fields(bytes, fields))
+}
+
+/// Deserializes a value, checking that it took every field unless the deserializer allows
+/// trailing fields.
+pub(crate) fn deserialize_all<'de, T>(de: &mut Deserializer<'de>) -> Result<T, error::Error>
+where
+    T: Deserialize<'de>,
+{
+    let value = T::deserialize(&mut *de)?;
+    de.check_trailing_fields()?;
+    Ok(value)
+}
+
+/// Deserializes `&[u8]` data using a borrowed set of `Field`s. Any `&str` or `&[u8]` fields of
+/// `T` borrow directly from `bytes` rather than being copied, so the result cannot outlive the
+/// input.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, from_bytes_borrowed};
+///
+/// #[derive(Deserialize)]
+/// struct Record<'a> {
+///     name: &'a str,
+///     room: usize,
+/// }
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4),
+///     FieldSet::new_field(4..8),
+/// ]);
+/// let bytes = b"Carl1234";
+///
+/// let record: Record = from_bytes_borrowed(bytes, &fields).unwrap();
+/// assert_eq!(record.name, "Carl");
+/// assert_eq!(record.name.as_ptr(), bytes.as_ptr());
+/// ```
+pub fn from_bytes_borrowed<'de, T>(bytes: &'de [u8], fields: &FieldSet) -> Result<T, error::Error>
+where
+    T: Deserialize<'de>,
+{
+    from_bytes_with_fields(bytes, fields.clone())
+}
+
+/// An error in a single field of a record, as collected by `Deserializer::deserialize_collecting`.
+#[derive(Debug)]
+pub struct FieldError {
+    /// The name of the field, or its range if it has no name.
+    pub name: String,
+    /// The byte range of the field.
+    pub range: Range<usize>,
+    /// The raw bytes of the field.
+    pub value: Vec<u8>,
+    /// The error that occurred.
+    pub source: DeserializeError,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "field {} with value {:?}: {}",
+            self.name,
+            String::from_utf8_lossy(&self.value),
+            self.source
+        )
+    }
+}
+
+impl StdError for FieldError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.source()
+    }
 }
 
 /// Errors that occur during deserialization.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DeserializeError {
     /// General error message as a `String`.
     Message(String),
     /// The desired type is unsupported by this deserializer.
     Unsupported(String),
-    /// The number of `Field`s given were less than the number of values to be deserialized.
+    /// The record was too short to hold a field, or a value was to be deserialized from a nested
+    /// field set.
     UnexpectedEndOfRecord,
+    /// More values were to be deserialized than there are fields, at the top level of the
+    /// layout or within a nested field set.
+    UnexpectedEndOfFields {
+        /// The position of the value there was no field for, from 1.
+        requested: usize,
+        /// The number of fields, not counting literals.
+        defined: usize,
+    },
     /// The bytes given were not valid UTF-8.
     InvalidUtf8(str::Utf8Error),
     /// A boolean value could not be parsed for this field.
@@ -147,6 +453,41 @@ pub enum DeserializeError {
     ParseIntError(num::ParseIntError),
     /// A float value could not be parsed for this field.
     ParseFloatError(num::ParseFloatError),
+    /// A strict literal field did not hold its expected value.
+    LiteralMismatch {
+        /// The literal value of the field.
+        expected: String,
+        /// The data found in the field.
+        found: String,
+    },
+    /// Fields were left over after deserializing a value. Holds the name, or range, of each.
+    UnconsumedFields(Vec<String>),
+    /// The record has data beyond the end of its last field.
+    TrailingData {
+        /// The width of the record's fields.
+        width: usize,
+        /// The length of the record.
+        len: usize,
+    },
+    /// An error occurred while deserializing the given field.
+    Field {
+        /// The name of the field, or its range if it has no name.
+        name: String,
+        /// The byte range of the field in the record.
+        range: Range<usize>,
+        /// The error that occurred.
+        source: Box<DeserializeError>,
+    },
+    /// The field held a code that stands for none of the variants set by `FieldSet::variants`.
+    UnknownCode(String),
+    /// A flag field set by `FieldSet::flags` held a character that is neither of its flag
+    /// characters nor a space.
+    InvalidFlag {
+        /// The character found.
+        found: char,
+        /// The position of the flag in the field, from 0.
+        index: usize,
+    },
     /// Will never implemente
     WontImplement,
 }
@@ -158,15 +499,22 @@ impl serde::de::Error for DeserializeError {
 }
 
 impl StdError for DeserializeError {
-    fn cause(&self) -> Option<&dyn StdError> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             DeserializeError::Message(_e) => None,
             DeserializeError::Unsupported(_e) => None,
             DeserializeError::UnexpectedEndOfRecord => None,
-            DeserializeError::InvalidUtf8(e) => Some(e),
-            DeserializeError::ParseBoolError(e) => Some(e),
-            DeserializeError::ParseIntError(e) => Some(e),
-            DeserializeError::ParseFloatError(e) => Some(e),
+            DeserializeError::UnexpectedEndOfFields { .. } => None,
+            DeserializeError::InvalidUtf8(e) => e.source(),
+            DeserializeError::ParseBoolError(e) => e.source(),
+            DeserializeError::ParseIntError(e) => e.source(),
+            DeserializeError::ParseFloatError(e) => e.source(),
+            DeserializeError::LiteralMismatch { .. } => None,
+            DeserializeError::UnconsumedFields(_) => None,
+            DeserializeError::TrailingData { .. } => None,
+            DeserializeError::Field { ref source, .. } => source.source(),
+            DeserializeError::UnknownCode(_) => None,
+            DeserializeError::InvalidFlag { .. } => None,
             DeserializeError::WontImplement => None,
         }
     }
@@ -180,15 +528,55 @@ impl fmt::Display for DeserializeError {
             DeserializeError::UnexpectedEndOfRecord => {
                 write!(f, "byte length of record was less than defined length")
             }
+            DeserializeError::UnexpectedEndOfFields { requested, defined } => write!(
+                f,
+                "value {} was requested, but only {} fields are defined",
+                requested, defined
+            ),
             DeserializeError::InvalidUtf8(ref e) => write!(f, "{}", e),
             DeserializeError::ParseBoolError(ref e) => write!(f, "{}", e),
             DeserializeError::ParseIntError(ref e) => write!(f, "{}", e),
             DeserializeError::ParseFloatError(ref e) => write!(f, "{}", e),
+            DeserializeError::LiteralMismatch {
+                ref expected,
+                ref found,
+            } => write!(f, "expected literal {:?}, found {:?}", expected, found),
+            DeserializeError::UnconsumedFields(ref fields) => {
+                write!(f, "fields were not deserialized: {}", fields.join(", "))
+            }
+            DeserializeError::TrailingData { width, len } => write!(
+                f,
+                "record of {} bytes has data after the end of its fields at byte {}",
+                len, width
+            ),
+            DeserializeError::Field {
+                ref name,
+                ref range,
+                ref source,
+            } => write!(
+                f,
+                "field '{}' ({}..{}): {}",
+                name, range.start, range.end, source
+            ),
+            DeserializeError::UnknownCode(ref code) => write!(f, "unknown code {:?}", code),
+            DeserializeError::InvalidFlag { found, index } => {
+                write!(f, "invalid flag {:?} at position {}", found, index)
+            }
             DeserializeError::WontImplement => write!(f, "This will never be implemented."),
         }
     }
 }
 
+impl From<FieldError> for DeserializeError {
+    fn from(e: FieldError) -> Self {
+        DeserializeError::Field {
+            name: e.name,
+            range: e.range,
+            source: Box::new(e.source),
+        }
+    }
+}
+
 impl From<str::Utf8Error> for DeserializeError {
     fn from(e: str::Utf8Error) -> Self {
         DeserializeError::InvalidUtf8(e)
@@ -213,11 +601,133 @@ impl From<num::ParseFloatError> for DeserializeError {
     }
 }
 
+/// The fields left to deserialize, either owned by the `Deserializer` or borrowed from the
+/// caller so they are not cloned for every record.
+enum Fields<'r> {
+    Owned(iter::Peekable<vec::IntoIter<FieldSet>>),
+    Borrowed(iter::Peekable<slice::Iter<'r, FieldSet>>),
+    Flat(iter::Peekable<slice::Iter<'r, FieldConfig>>),
+}
+
+/// The next field of `Fields`, without taking it.
+enum Peeked<'a> {
+    Item(&'a FieldConfig),
+    Seq,
+}
+
+/// A field taken from `Fields`, where a nested field set becomes `Fields` of its own.
+enum Taken<'r> {
+    Item(Cow<'r, FieldConfig>),
+    Seq(Fields<'r>),
+}
+
+impl<'r> Fields<'r> {
+    fn borrowed(fields: &'r FieldSet) -> Self {
+        match fields {
+            FieldSet::Item(_) => Fields::Borrowed(slice::from_ref(fields).iter().peekable()),
+            FieldSet::Seq(seq) => Fields::Borrowed(seq.iter().peekable()),
+        }
+    }
+
+    fn peek(&mut self) -> Option<Peeked<'_>> {
+        let field = match self {
+            Fields::Owned(fields) => fields.peek(),
+            Fields::Borrowed(fields) => fields.peek().copied(),
+            Fields::Flat(fields) => return fields.peek().map(|conf| Peeked::Item(conf)),
+        };
+
+        match field? {
+            FieldSet::Item(conf) => Some(Peeked::Item(conf)),
+            FieldSet::Seq(_) => Some(Peeked::Seq),
+        }
+    }
+
+    /// Peeks at the next field, if it is a single field borrowed for the whole of `'r`.
+    fn peek_borrowed(&mut self) -> Option<&'r FieldConfig> {
+        match self {
+            Fields::Owned(_) => None,
+            Fields::Borrowed(fields) => match fields.peek()? {
+                FieldSet::Item(conf) => Some(conf),
+                FieldSet::Seq(_) => None,
+            },
+            Fields::Flat(fields) => fields.peek().copied(),
+        }
+    }
+
+    fn next(&mut self) -> Option<Taken<'r>> {
+        match self {
+            Fields::Owned(fields) => fields.next().map(|field| match field {
+                FieldSet::Item(conf) => Taken::Item(Cow::Owned(conf)),
+                FieldSet::Seq(seq) => Taken::Seq(Fields::Owned(seq.into_iter().peekable())),
+            }),
+            Fields::Borrowed(fields) => fields.next().map(|field| match field {
+                FieldSet::Item(conf) => Taken::Item(Cow::Borrowed(conf)),
+                FieldSet::Seq(seq) => Taken::Seq(Fields::Borrowed(seq.iter().peekable())),
+            }),
+            Fields::Flat(fields) => fields.next().map(|conf| Taken::Item(Cow::Borrowed(conf))),
+        }
+    }
+
+    /// Takes the next field if it is a nested field set, returning its fields.
+    fn next_if_seq(&mut self) -> Option<Fields<'r>> {
+        match self.peek()? {
+            Peeked::Seq => match self.next() {
+                Some(Taken::Seq(fields)) => Some(fields),
+                _ => None,
+            },
+            Peeked::Item(_) => None,
+        }
+    }
+
+    /// Fields holding just the given field.
+    fn single(conf: Cow<'r, FieldConfig>) -> Self {
+        match conf {
+            Cow::Borrowed(conf) => Fields::Flat(slice::from_ref(conf).iter().peekable()),
+            Cow::Owned(conf) => Fields::Owned(vec![FieldSet::Item(conf)].into_iter().peekable()),
+        }
+    }
+
+    /// Takes the rest of the fields, flattening any nested field sets.
+    fn take_rest(&mut self, taken: &mut Vec<Cow<'r, FieldConfig>>) {
+        while let Some(field) = self.next() {
+            match field {
+                Taken::Item(conf) => taken.push(conf),
+                Taken::Seq(mut seq) => seq.take_rest(taken),
+            }
+        }
+    }
+
+    /// Takes the rest of the fields, describing each one that is not a literal.
+    fn describe_rest(&mut self) -> Vec<String> {
+        let mut described = Vec::new();
+
+        while let Some(field) = self.next() {
+            match field {
+                Taken::Item(conf) if conf.literal.is_none() => described.push(conf.describe()),
+                Taken::Item(_) => {}
+                Taken::Seq(mut seq) => described.extend(seq.describe_rest()),
+            }
+        }
+
+        described
+    }
+}
+
 /// A deserialized for fixed width data. Reads from the given bytes using the provided field
 /// definitions to determine how many bytes to read for each deserialized value.
 pub struct Deserializer<'r> {
-    fields: iter::Peekable<vec::IntoIter<FieldSet>>,
+    fields: Fields<'r>,
     input: &'r [u8],
+    record_end: Option<usize>,
+    strict: bool,
+    by_name: bool,
+    allow_trailing_fields: bool,
+    /// Whether text is read as ISO-8859-1 rather than UTF-8, for a reader that transcodes.
+    latin1: bool,
+    /// The number of values taken from the fields at this level.
+    taken: usize,
+    last: Option<Cow<'r, FieldConfig>>,
+    errors: Option<Vec<FieldError>>,
 }
 
 impl<'r> Deserializer<'r> {
@@ -247,82 +757,726 @@ impl<'r> Deserializer<'r> {
     /// assert_eq!(h.get("8..10").unwrap(), "99");
     /// ```
     pub fn new(input: &'r [u8], fields: FieldSet) -> Self {
-        Self {
-            fields: fields.into_iter().peekable(),
+        let record_end = fields.record_end();
+        Self::from_fields(
             input,
-        }
+            Fields::Owned(fields.into_iter().peekable()),
+            record_end,
+        )
     }
 
-    /// Gets a reference to the underlying input bytes.
+    /// Creates a new Deserializer from the given bytes and borrowed field definitions, so the
+    /// same fields can be used for every record without cloning them.
     ///
     /// ### Example
     ///
     /// ```rust
-    /// use fixed_width::{FieldSet, Deserializer, Reader};
+    /// use fixed_width::{FieldSet, Deserializer};
+    /// use serde::Deserialize;
     ///
-    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3)]);
-    /// let de = Deserializer::new(b"foobar", fields);
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(4..6), FieldSet::new_field(6..8)]),
+    /// ]);
     ///
-    /// assert_eq!(de.get_ref(), b"foobar");
+    /// for input in &[&b"1234abcd"[..], b"5678efgh"] {
+    ///     let mut de = Deserializer::with_fields(input, &fields);
+    ///     let (number, (a, b)): (u32, (String, String)) = Deserialize::deserialize(&mut de).unwrap();
+    ///     assert_eq!(format!("{}{}{}", number, a, b).as_bytes(), *input);
+    /// }
     /// ```
-    pub fn get_ref(&self) -> &[u8] {
-        self.input
-    }
-
-    fn peek_field(&mut self) -> Option<&FieldSet> {
-        self.fields.peek()
+    pub fn with_fields(input: &'r [u8], fields: &'r FieldSet) -> Self {
+        Self::from_fields(input, Fields::borrowed(fields), fields.record_end())
     }
 
-    fn skip_field(&mut self) {
-        self.fields.next();
-    }
-
-    fn peek_bytes(&mut self) -> Result<&'r [u8], DeserializeError> {
-        let field = match self.fields.peek() {
-            Some(FieldSet::Item(conf)) => conf,
-            Some(_) => return Err(DeserializeError::UnexpectedEndOfRecord),
-            None => return Err(DeserializeError::UnexpectedEndOfRecord),
-        };
-
-        match self.input.get(field.range.clone()) {
-            Some(bytes) => Ok(bytes),
-            None => Err(DeserializeError::UnexpectedEndOfRecord),
-        }
+    /// Creates a new Deserializer from the given bytes and fields that have already been
+    /// flattened, such as by `FieldSet::flatten`. Flattening once and reusing the fields for every
+    /// record saves walking the `FieldSet` each time. As the fields are flat, the record cannot
+    /// hold nested structs, tuples or sequences with fields of their own.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde;
+    /// use fixed_width::{FieldSet, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(4..8)]),
+    /// ])
+    /// .flatten();
+    ///
+    /// for input in &[&b"1234abcd"[..], b"5678efgh"] {
+    ///     let mut de = Deserializer::with_flattened(input, &fields);
+    ///     let record: Vec<String> = Vec::deserialize(&mut de).unwrap();
+    ///     assert_eq!(record.concat().as_bytes(), *input);
+    /// }
+    /// ```
+    pub fn with_flattened(input: &'r [u8], fields: &[FieldConfig]) -> Self {
+        let owned = fields
+            .iter()
+            .cloned()
+            .map(FieldSet::Item)
+            .collect::<Vec<_>>();
+        Self::from_fields(
+            input,
+            Fields::Owned(owned.into_iter().peekable()),
+            flat_record_end(fields),
+        )
     }
 
-    fn next_bytes(&mut self) -> Result<&'r [u8], DeserializeError> {
-        let field = match self.fields.next() {
-            Some(FieldSet::Item(conf)) => conf,
-            Some(_) => return Err(DeserializeError::UnexpectedEndOfRecord),
-            None => return Err(DeserializeError::UnexpectedEndOfRecord),
-        };
+    /// Creates a new Deserializer from the given bytes and borrowed fields that have already
+    /// been flattened, as `with_flattened` does but without cloning the fields. The fields must
+    /// live as long as the input.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(4..8)]),
+    /// ])
+    /// .flatten();
+    ///
+    /// for input in &[&b"1234abcd"[..], b"5678efgh"] {
+    ///     let mut de = Deserializer::with_flattened_ref(input, &fields);
+    ///     let record: Vec<String> = Vec::deserialize(&mut de).unwrap();
+    ///     assert_eq!(record.concat().as_bytes(), *input);
+    /// }
+    /// ```
+    pub fn with_flattened_ref(input: &'r [u8], fields: &'r [FieldConfig]) -> Self {
+        Self::from_fields(
+            input,
+            Fields::Flat(fields.iter().peekable()),
+            flat_record_end(fields),
+        )
+    }
 
-        match self.input.get(field.range) {
-            Some(bytes) => Ok(bytes),
-            None => Err(DeserializeError::UnexpectedEndOfRecord),
+    fn from_fields(input: &'r [u8], fields: Fields<'r>, record_end: Option<usize>) -> Self {
+        Self {
+            fields,
+            input,
+            record_end,
+            strict: false,
+            by_name: false,
+            allow_trailing_fields: false,
+            latin1: false,
+            taken: 0,
+            last: None,
+            errors: None,
+        }
+    }
+
+    /// Sets whether nested field sets, such as those of nested structs, must be fully
+    /// deserialized. Any fields left over result in a `DeserializeError::UnconsumedFields`. Use
+    /// `finish` to check the top level fields once the value has been deserialized.
+    pub fn strict(mut self, val: bool) -> Self {
+        self.strict = val;
+        self
+    }
+
+    /// Sets whether structs are deserialized by the names of their members, rather than by
+    /// position. Each member gets the value of the field with its name, as renamed by
+    /// `#[serde(rename)]` or `#[serde(alias)]`, wherever that field is in the layout, so the
+    /// order of the members does not matter and a struct can pick out just the fields it needs.
+    /// Fields that no member is named after are skipped. A member with no field of its name is
+    /// missing, which is an error unless it is an `Option` or has `#[serde(default)]`, so a
+    /// member that is a nested struct needs `#[serde(flatten)]`, which deserializes it from every
+    /// field keyed by name. Nested field sets are looked through, so their fields are found by
+    /// name too.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Deserializer, FieldSet};
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Person {
+    ///     age: u8,
+    ///     name: String,
+    /// }
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("name"),
+    ///     FieldSet::new_field(10..13).name("city"),
+    ///     FieldSet::new_field(13..15).name("age"),
+    /// ]);
+    ///
+    /// let mut de = Deserializer::new(b"0001Carl  NYC42", fields).by_name(true);
+    /// let person = Person::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(person.name, "Carl");
+    /// assert_eq!(person.age, 42);
+    /// ```
+    pub fn by_name(mut self, val: bool) -> Self {
+        self.by_name = val;
+        self
+    }
+
+    /// Sets whether fields may be left over once a value has been deserialized, such as when a
+    /// struct reads just the leading fields of a longer layout. By default, `finish` returns a
+    /// `DeserializeError::UnconsumedFields` for any that are, as do `from_bytes_with_fields` and
+    /// the other `from_*` functions, which deserialize without this set.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field(4..8),
+    /// ]);
+    ///
+    /// let mut de = Deserializer::new(b"1234abcd", fields).allow_trailing_fields(true);
+    /// let (number,): (u32,) = Deserialize::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!(number, 1234);
+    /// assert!(de.finish().is_ok());
+    /// ```
+    pub fn allow_trailing_fields(mut self, val: bool) -> Self {
+        self.allow_trailing_fields = val;
+        self
+    }
+
+    /// Sets whether text is read as ISO-8859-1, one char for each byte, rather than UTF-8.
+    /// Defaults to `false`. Fields read as bytes are not affected.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..6)]);
+    /// let mut de = Deserializer::new(b"Caf\xE9\xE9 ", fields).latin1(true);
+    /// let (word, letter): (String, char) = Deserialize::deserialize(&mut de).unwrap();
+    ///
+    /// assert_eq!((word.as_str(), letter), ("Café", 'é'));
+    /// ```
+    pub fn latin1(mut self, val: bool) -> Self {
+        self.latin1 = val;
+        self
+    }
+
+    /// Checks that every field was deserialized, unless `allow_trailing_fields` is set, and that
+    /// the input has no data after the last field other than spaces. Call once the value has
+    /// been deserialized.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{DeserializeError, FieldSet, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field(4..8),
+    /// ]);
+    ///
+    /// let mut de = Deserializer::new(b"1234abcd9", fields);
+    /// let _ = <(String, String)>::deserialize(&mut de).unwrap();
+    ///
+    /// match de.finish() {
+    ///     Err(DeserializeError::TrailingData { width, len }) => {
+    ///         assert_eq!(width, 8);
+    ///         assert_eq!(len, 9);
+    ///     }
+    ///     _ => panic!("expected trailing data"),
+    /// }
+    /// ```
+    pub fn finish(&mut self) -> Result<(), DeserializeError> {
+        self.check_trailing_fields()?;
+
+        if let Some(width) = self.record_end {
+            let trailing = self.input.get(width..).unwrap_or_default();
+            if trailing.iter().any(|&b| b != b' ') {
+                return Err(DeserializeError::TrailingData {
+                    width,
+                    len: self.input.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deserializes a value, attempting every field rather than stopping at the first error.
+    /// Fields that fail to parse as a string, number, `bool` or `char` are recorded and replaced
+    /// by an empty string, zero, `false` or a space so that the remaining fields can be tried.
+    /// Errors raised by a field type's own `Deserialize` implementation, such as an unknown enum
+    /// variant, cannot be replaced and end the pass, but are still returned along with the errors
+    /// found before them.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Deserializer};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("amount"),
+    ///     FieldSet::new_field(4..8).name("name"),
+    ///     FieldSet::new_field(8..12).name("count"),
+    /// ]);
+    ///
+    /// let mut de = Deserializer::new(b"12x4Carl3.50", fields);
+    /// let errors = de.deserialize_collecting::<(u32, String, u8)>().unwrap_err();
+    ///
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].name, "amount");
+    /// assert_eq!(errors[1].name, "count");
+    /// assert_eq!(errors[1].value, b"3.50");
+    /// ```
+    pub fn deserialize_collecting<T>(&mut self) -> Result<T, Vec<FieldError>>
+    where
+        T: Deserialize<'r>,
+    {
+        self.errors = Some(Vec::new());
+        let res = T::deserialize(&mut *self);
+        let mut errors = self.errors.take().unwrap_or_default();
+
+        match res {
+            Ok(value) if errors.is_empty() => Ok(value),
+            Ok(_) => Err(errors),
+            Err(e) => {
+                errors.push(self.field_error(e));
+                Err(errors)
+            }
+        }
+    }
+
+    /// Records the error of the last field if errors are being collected, returning `None` in
+    /// place of the value so the field can be replaced. Otherwise returns the error.
+    fn recover<T>(
+        &mut self,
+        res: Result<T, DeserializeError>,
+    ) -> Result<Option<T>, DeserializeError> {
+        match res {
+            Ok(value) => Ok(Some(value)),
+            Err(e @ DeserializeError::UnexpectedEndOfRecord) => Err(e),
+            Err(e @ DeserializeError::UnexpectedEndOfFields { .. }) => Err(e),
+            Err(e) if self.errors.is_some() => {
+                let error = self.field_error(e);
+                self.errors.get_or_insert_with(Vec::new).push(error);
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Builds a `FieldError` for the given error from the last field that was read.
+    fn field_error(&self, source: DeserializeError) -> FieldError {
+        match self.last {
+            Some(ref conf) => {
+                let range = if conf.capture_only {
+                    0..self.input.len()
+                } else if conf.to_end {
+                    conf.range.start..self.input.len().max(conf.range.start)
+                } else {
+                    conf.range.clone()
+                };
+
+                FieldError {
+                    name: conf.describe(),
+                    value: self.input.get(range.clone()).unwrap_or_default().to_vec(),
+                    range,
+                    source,
+                }
+            }
+            None => FieldError {
+                name: String::new(),
+                range: 0..0,
+                value: Vec::new(),
+                source,
+            },
+        }
+    }
+
+    fn check_trailing_fields(&mut self) -> Result<(), DeserializeError> {
+        if self.allow_trailing_fields {
+            return Ok(());
+        }
+        self.check_unconsumed()
+    }
+
+    fn check_unconsumed(&mut self) -> Result<(), DeserializeError> {
+        self.skip_literals()?;
+
+        let unconsumed = self.fields.describe_rest();
+
+        if unconsumed.is_empty() {
+            Ok(())
+        } else {
+            Err(DeserializeError::UnconsumedFields(unconsumed))
+        }
+    }
+
+    /// Gets a reference to the underlying input bytes.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Deserializer, Reader};
+    ///
+    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3)]);
+    /// let de = Deserializer::new(b"foobar", fields);
+    ///
+    /// assert_eq!(de.get_ref(), b"foobar");
+    /// ```
+    pub fn get_ref(&self) -> &[u8] {
+        self.input
+    }
+
+    fn peek_field(&mut self) -> Option<Peeked<'_>> {
+        self.fields.peek()
+    }
+
+    fn skip_field(&mut self) {
+        if self.fields.next().is_some() {
+            self.taken += 1;
+        }
+    }
+
+    /// Skips over any literal fields that come next, as they do not hold a value. Strict literal
+    /// fields are checked against the input.
+    fn skip_literals(&mut self) -> Result<(), DeserializeError> {
+        while let Some(Peeked::Item(conf)) = self.fields.peek() {
+            let literal = match conf.literal {
+                Some(ref literal) => literal,
+                None => break,
+            };
+
+            if conf.strict {
+                check_literal(conf, literal, self.input)?;
+            }
+
+            self.fields.next();
+        }
+
+        Ok(())
+    }
+
+    /// Takes the rest of the fields at this level, flattening nested field sets, and checks any
+    /// strict literals among them. Returns the fields that are not literals.
+    fn take_rest(&mut self) -> Result<Vec<Cow<'r, FieldConfig>>, DeserializeError> {
+        let mut rest = Vec::new();
+        self.fields.take_rest(&mut rest);
+
+        let mut values = Vec::with_capacity(rest.len());
+        for conf in rest {
+            match conf.literal {
+                Some(ref literal) if conf.strict => check_literal(&conf, literal, self.input)?,
+                Some(_) => {}
+                None => values.push(conf),
+            }
+        }
+        Ok(values)
+    }
+
+    /// Deserializes a struct or map by the names of its fields rather than by position, as set
+    /// by `by_name`. Takes the rest of the fields at this level and visits them as a map keyed by
+    /// their names, so serde matches them to members by name, including any `rename` or `alias`.
+    /// Fields no member is named after are ignored, and members with no field are missing.
+    fn visit_by_name<V: Visitor<'r>>(&mut self, visitor: V) -> Result<V::Value, DeserializeError> {
+        let fields = self.take_rest()?;
+        visitor.visit_map(ByName {
+            de: self,
+            fields: fields.into_iter(),
+            value: None,
+        })
+    }
+
+    fn peek_bytes(&mut self) -> Result<Cow<'r, [u8]>, DeserializeError> {
+        self.skip_literals()?;
+
+        match self.fields.peek() {
+            Some(Peeked::Item(conf)) => field_bytes(conf, self.input),
+            Some(_) => Err(DeserializeError::UnexpectedEndOfRecord),
+            None => Err(self.end_of_fields()),
         }
     }
 
-    fn peek_str(&mut self) -> Result<&'r str, DeserializeError> {
-        Ok(str::from_utf8(self.peek_bytes()?)?.trim())
+    fn next_bytes(&mut self) -> Result<Cow<'r, [u8]>, DeserializeError> {
+        self.skip_literals()?;
+
+        match self.fields.next() {
+            Some(Taken::Item(conf)) => {
+                let bytes = field_bytes(&conf, self.input);
+                self.taken += 1;
+                self.last = Some(conf);
+                bytes
+            }
+            Some(_) => Err(DeserializeError::UnexpectedEndOfRecord),
+            None => Err(self.end_of_fields()),
+        }
     }
 
-    fn next_str(&mut self) -> Result<&'r str, DeserializeError> {
-        Ok(str::from_utf8(self.next_bytes()?)?.trim())
+    /// The flags of the next field, if it is a flag field with `len` flags, or any number of flags
+    /// if `len` is not known.
+    fn next_flags(&mut self, len: Option<usize>) -> Result<Option<Flags>, DeserializeError> {
+        self.skip_literals()?;
+
+        match self.fields.peek() {
+            Some(Peeked::Item(FieldConfig {
+                flags: Some(flags), ..
+            })) if len.is_none_or(|len| len == flags.count) => Ok(Some(*flags)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Visits the next field, a flag field, as a sequence of its flags.
+    fn visit_flags<V: Visitor<'r>>(
+        &mut self,
+        flags: Flags,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        let bytes = self.next_bytes()?;
+        visitor.visit_seq(FlagSeq {
+            bytes: bytes.iter().take(flags.count).enumerate(),
+            flags,
+        })
+    }
+
+    /// Whether the next field is handed to the visitor as untrimmed bytes.
+    fn next_is_bytes(&mut self) -> Result<bool, DeserializeError> {
+        self.skip_literals()?;
+
+        match self.fields.peek() {
+            Some(Peeked::Item(conf)) => Ok(conf.bytes || conf.capture_only),
+            _ => Ok(false),
+        }
+    }
+
+    fn peek_str(&mut self) -> Result<Cow<'r, str>, DeserializeError> {
+        let bytes = self.peek_bytes()?;
+        let s = self.to_str(bytes)?;
+        match self.fields.peek() {
+            Some(Peeked::Item(conf)) => Ok(trim_centered(s, conf)),
+            _ => Ok(s),
+        }
+    }
+
+    fn next_str(&mut self) -> Result<Cow<'r, str>, DeserializeError> {
+        let bytes = self.next_bytes()?;
+        let s = self.to_str(bytes)?;
+        match self.last {
+            Some(ref conf) => Ok(trim_centered(s, conf)),
+            None => Ok(s),
+        }
+    }
+
+    fn to_str(&self, bytes: Cow<'r, [u8]>) -> Result<Cow<'r, str>, DeserializeError> {
+        if self.latin1 && !bytes.is_ascii() {
+            return Ok(Cow::Owned(latin1_to_string(&bytes).trim().to_string()));
+        }
+        bytes_to_str(bytes)
+    }
+
+    /// Gets the next field as text, changing its case if the field asks for that on read, and
+    /// reading a code as the name of the variant it stands for.
+    fn next_text(&mut self) -> Result<Cow<'r, str>, DeserializeError> {
+        let s = self.next_str()?;
+        let s = match self.last {
+            Some(ref conf) if conf.case_on_read => match s {
+                Cow::Borrowed(s) => conf.case.apply(s),
+                Cow::Owned(s) => Cow::Owned(conf.case.apply(&s).into_owned()),
+            },
+            _ => s,
+        };
+
+        let variant = match self.last {
+            Some(Cow::Borrowed(FieldConfig {
+                variants: Some(ref codes),
+                ..
+            })) => codes.variant(&s).map(Cow::Borrowed),
+            Some(Cow::Owned(FieldConfig {
+                variants: Some(ref codes),
+                ..
+            })) => codes.variant(&s).map(|v| Cow::Owned(v.to_string())),
+            _ => return Ok(s),
+        };
+        variant.ok_or_else(|| DeserializeError::UnknownCode(s.into_owned()))
+    }
+
+    /// Gets the next field as a number, moving a trailing sign to the front for a field with
+    /// `Sign::TrailingChar`, and reading a blank field with `blank_when_zero` as zero.
+    fn next_number(&mut self) -> Result<Cow<'r, str>, DeserializeError> {
+        let s = self.next_str()?;
+        let conf = match self.last {
+            Some(ref conf) => conf,
+            None => return Ok(s),
+        };
+
+        if conf.blank_when_zero && s.is_empty() {
+            return Ok(Cow::Borrowed("0"));
+        }
+
+        let s = match s {
+            _ if conf.sign != Sign::TrailingChar => s,
+            _ if s.ends_with('-') => Cow::Owned(format!("-{}", s[..s.len() - 1].trim_end())),
+            Cow::Borrowed(s) => Cow::Borrowed(s.strip_suffix('+').unwrap_or(s).trim_end()),
+            Cow::Owned(s) => Cow::Owned(s.strip_suffix('+').unwrap_or(&s).trim_end().to_string()),
+        };
+
+        Ok(match conf.decimal_separator {
+            DecimalSeparator::Comma if s.contains(['.', ',']) => Cow::Owned(
+                s.chars()
+                    .filter(|&c| c != '.')
+                    .map(|c| if c == ',' { '.' } else { c })
+                    .collect(),
+            ),
+            DecimalSeparator::Implied => match conf.decimals {
+                Some(decimals) if decimals > 0 && !s.is_empty() && !s.contains('.') => {
+                    Cow::Owned(imply_point(&s, decimals))
+                }
+                _ => s,
+            },
+            _ => s,
+        })
+    }
+
+    /// Whether the next field is a number with a number of decimals, which is handed to the
+    /// visitor as text rather than as a float, so exact decimal types can read it.
+    fn next_is_decimal(&mut self) -> Result<bool, DeserializeError> {
+        self.skip_literals()?;
+
+        match self.fields.peek() {
+            Some(Peeked::Item(conf)) => Ok(conf.decimals.is_some() && conf.variants.is_none()),
+            _ => Ok(false),
+        }
     }
 
     fn done(&mut self) -> bool {
         self.fields.peek().is_none()
     }
+
+    /// The error for a value requested after every field at this level was taken.
+    fn end_of_fields(&self) -> DeserializeError {
+        DeserializeError::UnexpectedEndOfFields {
+            requested: self.taken + 1,
+            defined: self.taken,
+        }
+    }
+}
+
+/// Puts the decimal point back into a number written without one, so `-5` with 2 decimals is
+/// read as `-0.05`.
+fn imply_point(s: &str, decimals: usize) -> String {
+    let (sign, digits) = match s.strip_prefix(['-', '+']) {
+        Some(digits) => (&s[..1], digits),
+        None => ("", s),
+    };
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (int, frac) = digits.split_at(digits.len() - decimals);
+    match int.trim_start_matches('0') {
+        "" => format!("{}0.{}", sign, frac),
+        int => format!("{}{}.{}", sign, int, frac),
+    }
+}
+
+/// Checks that a strict literal field holds its literal value, ignoring padding.
+fn check_literal(conf: &FieldConfig, literal: &str, input: &[u8]) -> Result<(), DeserializeError> {
+    let bytes = conf
+        .slice(input)
+        .ok_or(DeserializeError::UnexpectedEndOfRecord)?;
+    let found = String::from_utf8_lossy(bytes);
+    let pad: &[char] = &[conf.pad_with];
+
+    let matches = match conf.justify {
+        Justify::Left => found.trim_end_matches(pad) == literal.trim_end_matches(pad),
+        Justify::Right => found.trim_start_matches(pad) == literal.trim_start_matches(pad),
+        Justify::Center => found.trim_matches(pad) == literal.trim_matches(pad),
+    };
+
+    if !matches {
+        return Err(DeserializeError::LiteralMismatch {
+            expected: literal.to_string(),
+            found: found.into_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// The end of the last of the given fields, or `None` if one runs to the end of the record.
+fn flat_record_end(fields: &[FieldConfig]) -> Option<usize> {
+    fields.iter().try_fold(0, |end, conf| {
+        (!conf.to_end).then(|| end.max(conf.range.end))
+    })
+}
+
+/// Gets the bytes of the given field from the input, substituting the field's default value if
+/// the field is blank, or the output of its `deserialize_with` function.
+pub(crate) fn field_bytes<'r>(
+    conf: &FieldConfig,
+    input: &'r [u8],
+) -> Result<Cow<'r, [u8]>, DeserializeError> {
+    let bytes = conf
+        .slice(input)
+        .ok_or(DeserializeError::UnexpectedEndOfRecord)?;
+
+    match (&conf.default, &conf.deserialize_with) {
+        (Some(default), _) if conf.is_blank(bytes) => Ok(Cow::Owned(default.as_bytes().to_vec())),
+        (_, Some(f)) => Ok(Cow::Owned((f.0)(bytes)?.into_bytes())),
+        _ => Ok(Cow::Borrowed(bytes)),
+    }
+}
+
+/// Gets the text of the given field from the input, trimmed as `bytes_to_str` and
+/// `trim_centered` do.
+pub(crate) fn field_str<'r>(
+    conf: &FieldConfig,
+    input: &'r [u8],
+) -> Result<Cow<'r, str>, DeserializeError> {
+    let s = bytes_to_str(field_bytes(conf, input)?)?;
+    Ok(trim_centered(s, conf))
+}
+
+/// Trims the pad char of a centered field from both sides of its text, along with whitespace.
+/// Other fields are only trimmed of whitespace, so that a pad char such as `0` stays part of
+/// the value.
+pub(crate) fn trim_centered<'r>(s: Cow<'r, str>, conf: &FieldConfig) -> Cow<'r, str> {
+    if conf.justify != Justify::Center || conf.pad_with.is_whitespace() {
+        return s;
+    }
+
+    let pad = conf.pad_with;
+    let is_pad = |c: char| c == pad || c.is_whitespace();
+    match s {
+        Cow::Borrowed(s) => Cow::Borrowed(s.trim_matches(is_pad)),
+        Cow::Owned(s) => Cow::Owned(s.trim_matches(is_pad).to_string()),
+    }
+}
+
+/// Reads ISO-8859-1 text, in which each byte is the char of the same code point.
+pub(crate) fn latin1_to_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| char::from(b)).collect()
+}
+
+pub(crate) fn bytes_to_str(bytes: Cow<[u8]>) -> Result<Cow<str>, DeserializeError> {
+    match bytes {
+        Cow::Borrowed(b) => Ok(Cow::Borrowed(str::from_utf8(b)?.trim())),
+        Cow::Owned(b) => {
+            let s = String::from_utf8(b).map_err(|e| e.utf8_error())?;
+            Ok(Cow::Owned(s.trim().to_string()))
+        }
+    }
 }
 
 macro_rules! deserialize_int {
     ($de_fn:ident, $visit_fn:ident) => {
         fn $de_fn<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
             let i = self
-                .next_str()?
-                .parse()
-                .map_err(DeserializeError::ParseIntError)?;
+                .next_number()
+                .and_then(|s| s.parse().map_err(DeserializeError::ParseIntError));
+            let i = self.recover(i)?.unwrap_or_default();
 
             visitor.$visit_fn(i)
         }
@@ -333,78 +1487,106 @@ impl<'a, 'de: 'a> serde::Deserializer<'de> for &'a mut Deserializer<'de> {
     type Error = DeserializeError;
 
     fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let s = self.next_str()?;
-        if s.len() > 1 {
-            Err(DeserializeError::Message(format!(
-                "expected bool field to be 1 byte, got {}",
-                s.len()
-            )))
-        } else {
-            let c = s.chars().next().unwrap_or('0');
-            if c == '0' {
-                visitor.visit_bool(false)
+        let b = self.next_str().and_then(|s| {
+            if s.len() > 1 {
+                Err(DeserializeError::Message(format!(
+                    "expected bool field to be 1 byte, got {}",
+                    s.len()
+                )))
             } else {
-                visitor.visit_bool(true)
+                Ok(s.chars().next().unwrap_or('0') != '0')
             }
-        }
+        });
+
+        visitor.visit_bool(self.recover(b)?.unwrap_or_default())
     }
 
     deserialize_int!(deserialize_i8, visit_i8);
     deserialize_int!(deserialize_i16, visit_i16);
     deserialize_int!(deserialize_i32, visit_i32);
     deserialize_int!(deserialize_i64, visit_i64);
+    deserialize_int!(deserialize_i128, visit_i128);
     deserialize_int!(deserialize_u8, visit_u8);
     deserialize_int!(deserialize_u16, visit_u16);
     deserialize_int!(deserialize_u32, visit_u32);
     deserialize_int!(deserialize_u64, visit_u64);
+    deserialize_int!(deserialize_u128, visit_u128);
 
     fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let f = self
-            .next_str()?
-            .parse()
-            .map_err(DeserializeError::ParseFloatError)?;
+            .next_number()
+            .and_then(|s| s.parse().map_err(DeserializeError::ParseFloatError));
 
-        visitor.visit_f32(f)
+        visitor.visit_f32(self.recover(f)?.unwrap_or_default())
     }
 
     fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let f = self
-            .next_str()?
-            .parse()
-            .map_err(DeserializeError::ParseFloatError)?;
+            .next_number()
+            .and_then(|s| s.parse().map_err(DeserializeError::ParseFloatError));
 
-        visitor.visit_f64(f)
+        visitor.visit_f64(self.recover(f)?.unwrap_or_default())
     }
 
     fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        self.next_str().and_then(|s| visitor.visit_borrowed_str(s))
+        if self.next_is_bytes()? {
+            return self.deserialize_bytes(visitor);
+        }
+
+        let s = match self.next_is_decimal()? {
+            true => self.next_number(),
+            false => self.next_text(),
+        };
+        match self.recover(s)? {
+            Some(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Some(Cow::Owned(s)) => visitor.visit_string(s),
+            None => visitor.visit_str(""),
+        }
     }
 
     fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        self.next_str().and_then(|s| visitor.visit_borrowed_str(s))
+        if self.next_is_bytes()? {
+            return self.deserialize_byte_buf(visitor);
+        }
+
+        // Owned data is asked for, so the input is only lent to the visitor, which lets owned
+        // values be read out of a buffer that is about to be reused.
+        let s = match self.next_is_decimal()? {
+            true => self.next_number(),
+            false => self.next_text(),
+        };
+        match self.recover(s)? {
+            Some(Cow::Borrowed(s)) => visitor.visit_str(s),
+            Some(Cow::Owned(s)) => visitor.visit_string(s),
+            None => visitor.visit_str(""),
+        }
     }
 
     fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let s = self.next_str()?;
-        if s.len() > 1 {
-            Err(DeserializeError::Message(format!(
-                "expected bool field to be 1 byte, got {}",
-                s.len()
-            )))
-        } else {
-            let c = s.chars().next().unwrap_or(' ');
-            visitor.visit_char(c)
-        }
+        let c = self.next_text().and_then(|s| {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (c, None) => Ok(c.unwrap_or(' ')),
+                _ => Err(DeserializeError::Message(format!(
+                    "expected char field to hold 1 char, got {}",
+                    s.chars().count()
+                ))),
+            }
+        });
+
+        visitor.visit_char(self.recover(c)?.unwrap_or(' '))
     }
 
     fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        self.next_bytes()
-            .and_then(|b| visitor.visit_borrowed_bytes(b))
+        match self.next_bytes()? {
+            Cow::Borrowed(b) => visitor.visit_borrowed_bytes(b),
+            Cow::Owned(b) => visitor.visit_byte_buf(b),
+        }
     }
 
     fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         self.next_bytes()
-            .and_then(|b| visitor.visit_byte_buf(b.to_vec()))
+            .and_then(|b| visitor.visit_byte_buf(b.into_owned()))
     }
 
     fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
@@ -439,7 +1621,23 @@ impl<'a, 'de: 'a> serde::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 
     fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(self)
+        // A raw bytes field can be read into a sequence such as `Vec<u8>`.
+        if self.next_is_bytes()? {
+            let bytes = self.next_bytes()?;
+            let mut seq =
+                de::value::SeqDeserializer::<_, DeserializeError>::new(bytes.iter().copied());
+            let value = visitor.visit_seq(&mut seq)?;
+            seq.end()?;
+            return Ok(value);
+        }
+
+        if let Some(flags) = self.next_flags(None)? {
+            return self.visit_flags(flags, visitor);
+        }
+
+        let value = visitor.visit_seq(&mut *self)?;
+        self.skip_literals()?;
+        Ok(value)
     }
 
     fn deserialize_struct<V: Visitor<'de>>(
@@ -448,15 +1646,27 @@ impl<'a, 'de: 'a> serde::Deserializer<'de> for &'a mut Deserializer<'de> {
         _fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(self)
+        if self.by_name {
+            return self.visit_by_name(visitor);
+        }
+
+        let value = visitor.visit_seq(&mut *self)?;
+        self.skip_literals()?;
+        Ok(value)
     }
 
     fn deserialize_tuple<V: Visitor<'de>>(
         self,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(self)
+        if let Some(flags) = self.next_flags(Some(len))? {
+            return self.visit_flags(flags, visitor);
+        }
+
+        let value = visitor.visit_seq(&mut *self)?;
+        self.skip_literals()?;
+        Ok(value)
     }
 
     fn deserialize_tuple_struct<V: Visitor<'de>>(
@@ -465,11 +1675,19 @@ impl<'a, 'de: 'a> serde::Deserializer<'de> for &'a mut Deserializer<'de> {
         _len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(self)
+        let value = visitor.visit_seq(&mut *self)?;
+        self.skip_literals()?;
+        Ok(value)
     }
 
     fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        visitor.visit_map(self)
+        if self.by_name {
+            return self.visit_by_name(visitor);
+        }
+
+        let value = visitor.visit_map(&mut *self)?;
+        self.skip_literals()?;
+        Ok(value)
     }
 
     fn deserialize_enum<V: Visitor<'de>>(
@@ -485,17 +1703,51 @@ impl<'a, 'de: 'a> serde::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_str(visitor)
     }
 
-    // Not supported.
-    fn deserialize_ignored_any<V: Visitor<'de>>(
-        self,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error> {
-        Err(DeserializeError::WontImplement)
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.skip_literals()?;
+        self.skip_field();
+        visitor.visit_unit()
     }
 
-    // FixedWidth is not self describing format should avoid this method.
-    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
-        Err(DeserializeError::WontImplement)
+    /// Guesses the type of the field from its data, as fixed width data is not self describing.
+    /// Blank fields are visited as `None`, and integers and floats as `i64`, `u64` or `f64`.
+    /// Anything else is visited as a string, and raw bytes fields as bytes. A field with a number
+    /// of decimals is visited as the text of its number, so no precision is lost to a float.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.next_is_bytes()? {
+            return self.deserialize_bytes(visitor);
+        }
+        if self.next_is_decimal()? {
+            return match self.next_number()? {
+                s if s.is_empty() => visitor.visit_none(),
+                Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            };
+        }
+
+        let s = self.next_str()?;
+        if s.is_empty() {
+            return visitor.visit_none();
+        }
+        if let Ok(i) = s.parse() {
+            return visitor.visit_i64(i);
+        }
+        if let Ok(u) = s.parse() {
+            return visitor.visit_u64(u);
+        }
+
+        // Only plain numbers are floats, not words such as `inf` or `NaN`.
+        let numeric = s
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E'));
+        if let (true, Ok(f)) = (numeric, s.parse()) {
+            return visitor.visit_f64(f);
+        }
+
+        match s {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
     }
 }
 
@@ -506,13 +1758,31 @@ impl<'a, 'de: 'a> de::SeqAccess<'de> for &'a mut Deserializer<'de> {
         &mut self,
         seed: S,
     ) -> Result<Option<S::Value>, Self::Error> {
-        match self.fields.peek() {
-            Some(FieldSet::Item(_)) => seed.deserialize(&mut **self).map(Some),
-            Some(FieldSet::Seq(_)) => {
-                let mut de = Deserializer::new(self.input, self.fields.next().unwrap());
-                seed.deserialize(&mut de).map(Some)
+        self.skip_literals()?;
+
+        match self.fields.next_if_seq() {
+            None if self.done() => Ok(None),
+            None => seed.deserialize(&mut **self).map(Some),
+            Some(fields) => {
+                self.taken += 1;
+                // Only the top level fields are checked by `finish`, so the nested record end
+                // is not needed.
+                let mut de = Deserializer::from_fields(self.input, fields, None)
+                    .strict(self.strict)
+                    .by_name(self.by_name);
+                de.errors = self.errors.take();
+                de.latin1 = self.latin1;
+
+                let res = seed.deserialize(&mut de);
+                self.errors = de.errors.take();
+                self.last = de.last.take();
+
+                let value = res?;
+                if self.strict {
+                    de.check_unconsumed()?;
+                }
+                Ok(Some(value))
             }
-            None => Ok(None),
         }
     }
 }
@@ -524,18 +1794,25 @@ impl<'a, 'de: 'a> de::MapAccess<'de> for &'a mut Deserializer<'de> {
         &mut self,
         seed: S,
     ) -> Result<Option<S::Value>, Self::Error> {
+        self.skip_literals()?;
+
         if self.done() {
             Ok(None)
         } else {
-            let name = match self.peek_field() {
-                Some(FieldSet::Item(f)) => f
-                    .name
-                    .clone()
-                    .unwrap_or_else(|| format!("{}..{}", f.range.start, f.range.end)),
+            // Names of borrowed fields live as long as the input, so they can be handed out as is.
+            if let Some(name) = self.fields.peek_borrowed().and_then(|f| f.name.as_deref()) {
+                return seed
+                    .deserialize(de::value::BorrowedStrDeserializer::new(name))
+                    .map(Some);
+            }
+
+            let mut buf = [0; RANGE_KEY_LEN];
+            let key = match self.peek_field() {
+                Some(Peeked::Item(f)) => f.key(&mut buf),
                 Some(_) => return Err(DeserializeError::UnexpectedEndOfRecord),
                 None => return Err(DeserializeError::UnexpectedEndOfRecord),
             };
-            seed.deserialize(name.into_deserializer()).map(Some)
+            seed.deserialize(key.into_deserializer()).map(Some)
         }
     }
 
@@ -547,6 +1824,139 @@ impl<'a, 'de: 'a> de::MapAccess<'de> for &'a mut Deserializer<'de> {
     }
 }
 
+/// Fields taken out of their place in the layout when deserializing `by_name`, as a map of each
+/// field keyed by its name, whatever nested field set it is in.
+struct ByName<'a, 'r> {
+    de: &'a mut Deserializer<'r>,
+    fields: vec::IntoIter<Cow<'r, FieldConfig>>,
+    /// The field whose key was the last one visited, when visited as a map.
+    value: Option<Cow<'r, FieldConfig>>,
+}
+
+impl<'a, 'r> ByName<'a, 'r> {
+    fn deserialize<S: de::DeserializeSeed<'r>>(
+        &mut self,
+        conf: Cow<'r, FieldConfig>,
+        seed: S,
+    ) -> Result<S::Value, DeserializeError> {
+        let mut de = Deserializer::from_fields(self.de.input, Fields::single(conf), None)
+            .strict(self.de.strict)
+            .by_name(self.de.by_name);
+        de.errors = self.de.errors.take();
+        de.latin1 = self.de.latin1;
+
+        let res = seed.deserialize(&mut de);
+        self.de.errors = de.errors.take();
+        self.de.last = de.last.take();
+        res
+    }
+}
+
+impl<'a, 'de: 'a> de::MapAccess<'de> for ByName<'a, 'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<S: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        let conf = match self.fields.next() {
+            Some(conf) => conf,
+            None => return Ok(None),
+        };
+
+        let mut buf = [0; RANGE_KEY_LEN];
+        let key = seed.deserialize(conf.key(&mut buf).into_deserializer());
+        self.value = Some(conf);
+        key.map(Some)
+    }
+
+    fn next_value_seed<S: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        match self.value.take() {
+            Some(conf) => self.deserialize(conf, seed),
+            None => Err(DeserializeError::UnexpectedEndOfRecord),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// The flags of a flag field, as a sequence.
+struct FlagSeq<'a> {
+    bytes: iter::Enumerate<iter::Take<slice::Iter<'a, u8>>>,
+    flags: Flags,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for FlagSeq<'a> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<S: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        match self.bytes.next() {
+            Some((index, &byte)) => seed
+                .deserialize(Flag {
+                    byte,
+                    index,
+                    flags: self.flags,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.bytes.len())
+    }
+}
+
+/// A single flag of a flag field, which is a `bool`, or an `Option<bool>` that is `None` when
+/// the flag is a space.
+struct Flag {
+    byte: u8,
+    index: usize,
+    flags: Flags,
+}
+
+impl Flag {
+    fn value(&self) -> Result<bool, DeserializeError> {
+        match self.byte {
+            b if b == self.flags.yes => Ok(true),
+            b if b == self.flags.no || b == b' ' => Ok(false),
+            b => Err(DeserializeError::InvalidFlag {
+                found: b as char,
+                index: self.index,
+            }),
+        }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for Flag {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bool(self.value()?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.byte == b' ' {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
 impl<'a, 'de: 'a> de::EnumAccess<'de> for &'a mut Deserializer<'de> {
     type Error = DeserializeError;
     type Variant = Self;
@@ -555,7 +1965,7 @@ impl<'a, 'de: 'a> de::EnumAccess<'de> for &'a mut Deserializer<'de> {
         self,
         seed: S,
     ) -> Result<(S::Value, Self::Variant), Self::Error> {
-        seed.deserialize(self.next_str()?.into_deserializer())
+        seed.deserialize(self.next_text()?.into_deserializer())
             .map(|v| (v, self))
     }
 }
@@ -681,7 +2091,7 @@ where
 mod test {
     use super::*;
     use crate::{FieldSet, FixedWidth};
-    use serde::Deserialize;
+    use serde::{de::IgnoredAny, Deserialize};
     use serde_bytes::ByteBuf;
     use serde_derive::Deserialize;
     use std::collections::HashMap;
@@ -825,76 +2235,494 @@ mod test {
         d: Option<usize>,
     }
 
-    impl FixedWidth for Test1 {
-        fn fields() -> FieldSet {
-            FieldSet::Seq(vec![
-                FieldSet::new_field(0..3),
-                FieldSet::new_field(3..6),
-                FieldSet::new_field(6..10),
-                FieldSet::new_field(10..13),
-            ])
+    impl FixedWidth for Test1 {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..3),
+                FieldSet::new_field(3..6),
+                FieldSet::new_field(6..10),
+                FieldSet::new_field(10..13),
+            ])
+        }
+    }
+
+    #[test]
+    fn struct_de() {
+        let input = b"123abc9876 12";
+        let test: Test1 = from_bytes(input).unwrap();
+
+        assert_eq!(test.a, 123);
+        assert_eq!(test.b, "abc");
+        assert_eq!(test.c, 9876.0);
+        assert_eq!(test.d, Some(12));
+    }
+
+    #[test]
+    fn tuple_de() {
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3), FieldSet::new_field(3..6)]);
+        let t: (usize, usize) = from_bytes_with_fields(b"111222", fields).unwrap();
+        assert_eq!(t, (111, 222));
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Tuple(usize, usize);
+
+    #[test]
+    fn tuple_struct_de() {
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3), FieldSet::new_field(3..6)]);
+        let t: Tuple = from_bytes_with_fields(b"111222", fields).unwrap();
+        assert_eq!(t, Tuple(111, 222));
+    }
+
+    #[test]
+    fn hashmap_de() {
+        let input = b"123abc9876 12";
+        let mut de = Deserializer::new(
+            input,
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..3).name("a"),
+                FieldSet::new_field(3..6).name("b"),
+                FieldSet::new_field(6..10),
+                FieldSet::new_field(10..13).name("d"),
+            ]),
+        );
+
+        let test: HashMap<String, String> = HashMap::deserialize(&mut de).unwrap();
+
+        assert_eq!(test.get("a").unwrap(), "123");
+        assert_eq!(test.get("b").unwrap(), "abc");
+        assert_eq!(test.get("6..10").unwrap(), "9876");
+        assert_eq!(test.get("d").unwrap(), "12");
+    }
+
+    #[test]
+    fn hashmap_keys_stay_the_same_across_records() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).name("a"),
+            FieldSet::new_field(3..6),
+            FieldSet::new_field_to_end(6),
+        ]);
+        let flat = fields.clone().flatten();
+        let expected = vec!["3..6", "6..", "a"];
+
+        for input in ["123abc98", "456def76", "789ghi54"].iter() {
+            let owned: HashMap<String, String> =
+                from_str_with_fields(input, fields.clone()).unwrap();
+            let borrowed: HashMap<String, String> =
+                from_str_with_fields_ref(input, &fields).unwrap();
+            let mut de = Deserializer::with_flattened_ref(input.as_bytes(), &flat);
+            let flattened: HashMap<String, String> = HashMap::deserialize(&mut de).unwrap();
+
+            for map in [owned, borrowed, flattened].iter() {
+                let mut keys: Vec<_> = map.keys().map(String::as_str).collect();
+                keys.sort_unstable();
+                assert_eq!(keys, expected);
+                assert_eq!(map["a"], input[0..3]);
+                assert_eq!(map["6.."], input[6..]);
+            }
+        }
+
+        let named = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).name("a"),
+            FieldSet::new_field(3..6).name("b"),
+        ]);
+        let map: HashMap<&str, &str> = from_str_with_fields_ref("123abc", &named).unwrap();
+        assert_eq!(map["a"], "123");
+        assert_eq!(map["b"], "abc");
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        Foo,
+    }
+
+    #[test]
+    fn enum_de() {
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3)]);
+        let e: Enum = from_bytes_with_fields(b"Foo", fields).unwrap();
+        assert_eq!(e, Enum::Foo);
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Account {
+        Checking,
+        Savings,
+        Other,
+    }
+
+    #[test]
+    fn enum_codes_de() {
+        let field = FieldSet::new_field(0..2).variants(&[("01", "Checking"), ("02", "Savings")]);
+        let flat = field.clone().flatten();
+
+        let a: Account = from_str_with_fields("01", field.clone()).unwrap();
+        assert_eq!(a, Account::Checking);
+        let a: Account = from_str_with_fields_ref("02", &field).unwrap();
+        assert_eq!(a, Account::Savings);
+        let mut de = Deserializer::with_flattened_ref(b"01", &flat);
+        assert_eq!(Account::deserialize(&mut de).unwrap(), Account::Checking);
+        let s: String = from_str_with_fields("02", field.clone()).unwrap();
+        assert_eq!(s, "Savings");
+
+        let a: Option<Account> = from_str_with_fields("  ", field.clone()).unwrap();
+        assert_eq!(a, None);
+
+        let mut de = Deserializer::with_fields(b"07", &field);
+        match Account::deserialize(&mut de) {
+            Err(DeserializeError::UnknownCode(code)) => assert_eq!(code, "07"),
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let field = field.catch_all_variant("Other");
+        let a: Account = from_str_with_fields("07", field).unwrap();
+        assert_eq!(a, Account::Other);
+    }
+
+    fn person_fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "P").strict(true),
+            FieldSet::new_field(1..7).name("name"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(7..10).name("age"),
+                FieldSet::new_field(10..13).name("height"),
+            ]),
+            FieldSet::new_field(13..16).name("city"),
+        ])
+    }
+
+    #[test]
+    fn by_name_reordered() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Person {
+            #[serde(rename = "name")]
+            first_name: String,
+            height: usize,
+            age: usize,
+            city: String,
+        }
+
+        let fields = person_fields();
+        let input = b"PCarl   42180NYC";
+
+        let mut de = Deserializer::with_fields(input, &fields).by_name(true);
+        let person = Person::deserialize(&mut de).unwrap();
+        assert_eq!(
+            person,
+            Person {
+                first_name: "Carl".to_string(),
+                height: 180,
+                age: 42,
+                city: "NYC".to_string(),
+            }
+        );
+
+        // By position, height and age are silently swapped.
+        let flat = FieldSet::Seq(fields.iter_flat().cloned().map(FieldSet::Item).collect());
+        let person: Person = from_bytes_with_fields(input, flat.clone()).unwrap();
+        assert_eq!((person.height, person.age), (42, 180));
+
+        let mut de = Deserializer::new(input, flat).by_name(true);
+        let person = Person::deserialize(&mut de).unwrap();
+        assert_eq!((person.height, person.age), (180, 42));
+    }
+
+    #[test]
+    fn by_name_subset() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Summary {
+            city: String,
+            age: u8,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Line {
+            #[serde(flatten)]
+            summary: Summary,
+        }
+
+        let fields = person_fields();
+        let mut de = Deserializer::new(b"PCarl   42180NYC", fields.clone()).by_name(true);
+        let summary = Summary::deserialize(&mut de).unwrap();
+        assert_eq!(
+            summary,
+            Summary {
+                city: "NYC".to_string(),
+                age: 42
+            }
+        );
+        de.finish().unwrap();
+
+        let mut de = Deserializer::new(b"PCarl   42180NYC", fields.clone()).by_name(true);
+        let line = Line::deserialize(&mut de).unwrap();
+        assert_eq!(line.summary, summary);
+
+        let mut de = Deserializer::new(b"XCarl   42180NYC", fields).by_name(true);
+        match Summary::deserialize(&mut de) {
+            Err(DeserializeError::LiteralMismatch { expected, found }) => {
+                assert_eq!((expected.as_str(), found.as_str()), ("P", "X"));
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn by_name_missing_member() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Person {
+            name: String,
+            weight: u16,
+        }
+
+        let fields = person_fields();
+        let mut de = Deserializer::with_fields(b"PCarl   42180NYC", &fields).by_name(true);
+        let err = Person::deserialize(&mut de).unwrap_err();
+        assert_eq!(err.to_string(), "missing field `weight`");
+    }
+
+    #[test]
+    fn by_name_nested() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Body {
+            height: u16,
+            age: u8,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Record {
+            kind: String,
+            person: (String, Body),
+        }
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1).name("kind"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(1..7).name("name"),
+                FieldSet::Seq(vec![
+                    FieldSet::new_field(7..10).name("age"),
+                    FieldSet::new_field(10..13).name("height"),
+                ]),
+            ]),
+        ]);
+
+        let mut de = Deserializer::with_fields(b"PCarl   42180", &fields).by_name(true);
+        let err = Record::deserialize(&mut de).unwrap_err();
+        assert_eq!(err.to_string(), "missing field `person`");
+
+        let mut de = Deserializer::with_fields(b"PCarl   42180", &fields).by_name(true);
+        let map = HashMap::<String, String>::deserialize(&mut de).unwrap();
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["height"], "180");
+
+        let mut de = Deserializer::with_fields(b"PCarl   42180", &fields).by_name(true);
+        let (kind, (name, body)) = <(String, (String, Body))>::deserialize(&mut de).unwrap();
+        assert_eq!((kind.as_str(), name.as_str()), ("P", "Carl"));
+        assert_eq!(
+            body,
+            Body {
+                height: 180,
+                age: 42
+            }
+        );
+    }
+
+    #[test]
+    fn from_str_by_name_keys() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Person {
+            #[serde(alias = "name")]
+            first_name: String,
+            height: usize,
+            age: usize,
+        }
+
+        impl FixedWidth for Person {
+            fn fields() -> FieldSet {
+                person_fields()
+            }
+        }
+
+        let person: Person = from_str_by_name("PCarl   42180NYC").unwrap();
+        assert_eq!(
+            person,
+            Person {
+                first_name: "Carl".to_string(),
+                height: 180,
+                age: 42,
+            }
+        );
+
+        match from_str_by_name::<Person>("XCarl   42180NYC") {
+            Err(error::Error::DeserializeError(DeserializeError::LiteralMismatch { .. })) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Pair {
+        a: String,
+        b: String,
+    }
+
+    #[test]
+    fn struct_with_fewer_members_than_fields() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("a"),
+            FieldSet::new_literal(2..3, "-"),
+            FieldSet::new_field(3..5).name("b"),
+            FieldSet::new_field(5..7).name("c"),
+            FieldSet::new_field(7..9),
+        ]);
+
+        match from_str_with_fields::<Pair>("aa-bbccdd", fields.clone()) {
+            Err(error::Error::DeserializeError(DeserializeError::UnconsumedFields(f))) => {
+                assert_eq!(f, vec!["c", "7..9"])
+            }
+            res => panic!("unexpected result: {:?}", res),
         }
+        assert!(from_str_with_fields_ref::<Pair>("aa-bbccdd", &fields).is_err());
+
+        let mut de = Deserializer::new(b"aa-bbccdd", fields).allow_trailing_fields(true);
+        let pair = Pair::deserialize(&mut de).unwrap();
+        assert_eq!(pair.b, "bb");
+        de.finish().unwrap();
     }
 
     #[test]
-    fn struct_de() {
-        let input = b"123abc9876 12";
-        let test: Test1 = from_bytes(input).unwrap();
+    fn struct_with_more_members_than_fields() {
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..2)]);
+        let err = from_str_with_fields::<Pair>("aabb", fields).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid length 1, expected struct Pair with 2 elements"
+        );
 
-        assert_eq!(test.a, 123);
-        assert_eq!(test.b, "abc");
-        assert_eq!(test.c, 9876.0);
-        assert_eq!(test.d, Some(12));
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "P"),
+            FieldSet::new_field(1..3),
+        ]);
+        let mut de = Deserializer::new(b"Paabb", fields);
+        let first = String::deserialize(&mut de).unwrap();
+        assert_eq!(first, "aa");
+        match String::deserialize(&mut de) {
+            Err(e @ DeserializeError::UnexpectedEndOfFields { .. }) => {
+                assert_eq!(
+                    e.to_string(),
+                    "value 2 was requested, but only 1 fields are defined"
+                );
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let err = from_str_with_fields::<String>("aa", FieldSet::Seq(vec![])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value 1 was requested, but only 0 fields are defined"
+        );
     }
 
     #[test]
-    fn tuple_de() {
-        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3), FieldSet::new_field(3..6)]);
-        let t: (usize, usize) = from_bytes_with_fields(b"111222", fields).unwrap();
-        assert_eq!(t, (111, 222));
-    }
+    fn decimal_comma_de() {
+        let field = FieldSet::new_field(0..10).decimal_separator(DecimalSeparator::Comma);
 
-    #[derive(Debug, PartialEq, Deserialize)]
-    struct Tuple(usize, usize);
+        let n: f64 = from_str_with_fields("1234,56   ", field.clone()).unwrap();
+        assert_eq!(n, 1234.56);
+        let n: f64 = from_str_with_fields("  1.234,56", field.clone()).unwrap();
+        assert_eq!(n, 1234.56);
+        let n: f64 = from_str_with_fields("-1.234.567", field.clone()).unwrap();
+        assert_eq!(n, -1234567.0);
+        let n: u32 = from_str_with_fields("     1.234", field.clone()).unwrap();
+        assert_eq!(n, 1234);
 
-    #[test]
-    fn tuple_struct_de() {
-        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3), FieldSet::new_field(3..6)]);
-        let t: Tuple = from_bytes_with_fields(b"111222", fields).unwrap();
-        assert_eq!(t, Tuple(111, 222));
+        let field = field.sign(Sign::TrailingChar);
+        let n: f32 = from_str_with_fields(" 1.234,50-", field).unwrap();
+        assert_eq!(n, -1234.5);
     }
 
-    #[test]
-    fn hashmap_de() {
-        let input = b"123abc9876 12";
-        let mut de = Deserializer::new(
-            input,
-            FieldSet::Seq(vec![
-                FieldSet::new_field(0..3).name("a"),
-                FieldSet::new_field(3..6).name("b"),
-                FieldSet::new_field(6..10),
-                FieldSet::new_field(10..13).name("d"),
-            ]),
-        );
+    /// An exact decimal amount, which like `rust_decimal::Decimal` is read from text only.
+    #[derive(Debug, PartialEq)]
+    struct Money(String);
 
-        let test: HashMap<String, String> = HashMap::deserialize(&mut de).unwrap();
+    impl<'de> Deserialize<'de> for Money {
+        fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            struct MoneyVisitor;
 
-        assert_eq!(test.get("a").unwrap(), "123");
-        assert_eq!(test.get("b").unwrap(), "abc");
-        assert_eq!(test.get("6..10").unwrap(), "9876");
-        assert_eq!(test.get("d").unwrap(), "12");
+            impl<'de> Visitor<'de> for MoneyVisitor {
+                type Value = Money;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a decimal amount as text")
+                }
+
+                fn visit_str<E: de::Error>(self, s: &str) -> Result<Money, E> {
+                    Ok(Money(s.to_string()))
+                }
+            }
+
+            d.deserialize_any(MoneyVisitor)
+        }
     }
 
-    #[derive(Debug, PartialEq, Deserialize)]
-    enum Enum {
-        Foo,
+    #[test]
+    fn decimal_text_de() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..22).decimals(2),
+            FieldSet::new_field(22..30)
+                .decimals(2)
+                .decimal_separator(DecimalSeparator::Comma)
+                .sign(Sign::TrailingChar),
+            FieldSet::new_field(30..36)
+                .decimals(3)
+                .decimal_separator(DecimalSeparator::Implied),
+            FieldSet::new_field(36..42).decimals(2),
+        ]);
+
+        let s = "12345678901234567.89  1.234,5-   -42      ";
+        let read: (Money, Money, String, Option<Money>) = from_str_with_fields(s, fields).unwrap();
+        assert_eq!(
+            read,
+            (
+                Money("12345678901234567.89".to_string()),
+                Money("-1234.5".to_string()),
+                "-0.042".to_string(),
+                None,
+            )
+        );
     }
 
     #[test]
-    fn enum_de() {
-        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..3)]);
-        let e: Enum = from_bytes_with_fields(b"Foo", fields).unwrap();
-        assert_eq!(e, Enum::Foo);
+    fn flags_de() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1),
+            FieldSet::new_field(1..6).flags(5, 'Y', 'N'),
+            FieldSet::new_field(6..7),
+        ]);
+
+        let full: (char, [bool; 5], char) =
+            from_str_with_fields("aYNYYNz", fields.clone()).unwrap();
+        assert_eq!(full, ('a', [true, false, true, true, false], 'z'));
+        let full: (char, Vec<bool>, char) =
+            from_str_with_fields("aYNYYNz", fields.clone()).unwrap();
+        assert_eq!(full.1, vec![true, false, true, true, false]);
+
+        let blank: (char, [bool; 5], char) =
+            from_str_with_fields("aY N  z", fields.clone()).unwrap();
+        assert_eq!(blank.1, [true, false, false, false, false]);
+        let blank: (char, [Option<bool>; 5], char) =
+            from_str_with_fields("aY N  z", fields.clone()).unwrap();
+        assert_eq!(blank.1, [Some(true), None, Some(false), None, None]);
+
+        let mut de = Deserializer::with_fields(b"aYNxYNz", &fields);
+        match <(char, [bool; 5], char)>::deserialize(&mut de) {
+            Err(DeserializeError::InvalidFlag { found, index }) => {
+                assert_eq!((found, index), ('x', 2));
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // A field read into anything other than a sequence of its flags is read as usual.
+        let s: (char, String, char) = from_str_with_fields("aYNYYNz", fields).unwrap();
+        assert_eq!(s.1, "YNYYN");
     }
 
     #[test]
@@ -1058,4 +2886,544 @@ mod test {
         assert_eq!(arr[2], Some((253, 254)));
         assert_eq!(arr[3], Some((121, 232)));
     }
+
+    #[test]
+    fn test_field_to_end() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3),
+            FieldSet::new_field_to_end(3),
+        ]);
+
+        let (a, b): (usize, String) =
+            from_str_with_fields("123 some notes ", fields.clone()).unwrap();
+        assert_eq!(a, 123);
+        assert_eq!(b, "some notes");
+
+        let (_, b): (usize, String) = from_str_with_fields("123", fields.clone()).unwrap();
+        assert_eq!(b, "");
+
+        let res: Result<(usize, String), _> = from_str_with_fields("12", fields.clone());
+        assert!(res.is_err());
+
+        let h: HashMap<String, String> = from_str_with_fields("123notes", fields).unwrap();
+        assert_eq!(h.get("3..").unwrap(), "notes");
+    }
+
+    #[test]
+    fn test_literal_fields() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("a"),
+            FieldSet::new_literal(2..4, "xy"),
+            FieldSet::new_field(4..6).name("b"),
+            FieldSet::new_literal(6..8, "z").strict(true),
+        ]);
+
+        let (a, b): (String, String) = from_str_with_fields("ab--cdz ", fields.clone()).unwrap();
+        assert_eq!(a, "ab");
+        assert_eq!(b, "cd");
+
+        let h: HashMap<String, String> = from_str_with_fields("abxycdz ", fields.clone()).unwrap();
+        assert_eq!(h.len(), 2);
+
+        let res: Result<(String, String), _> = from_str_with_fields("abxycdzz", fields);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_default_value() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).default_value("7"),
+            FieldSet::new_field(3..6).pad_with('*').default_value("abc"),
+            FieldSet::new_field(6..9).default_value("xyz"),
+        ]);
+
+        let (a, b, c): (Option<usize>, String, ByteBuf) =
+            from_str_with_fields("   **  x ", fields.clone()).unwrap();
+        assert_eq!(a, Some(7));
+        assert_eq!(b, "abc");
+        assert_eq!(c.as_ref(), b" x ");
+
+        let (a, b, c): (usize, &str, ByteBuf) = from_str_with_fields(" 1 de    ", fields).unwrap();
+        assert_eq!(a, 1);
+        assert_eq!(b, "de");
+        assert_eq!(c.as_ref(), b"xyz");
+    }
+
+    #[test]
+    fn test_bytes_fields() {
+        #[derive(Deserialize)]
+        struct Test<'a> {
+            a: String,
+            b: Vec<u8>,
+            c: ByteBuf,
+            d: &'a [u8],
+            e: usize,
+        }
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).name("a"),
+            FieldSet::new_field(3..5).name("b").bytes(true),
+            FieldSet::new_field(5..7).name("c").bytes(true),
+            FieldSet::new_field(7..9).name("d").bytes(true),
+            FieldSet::new_field(9..11).name("e"),
+        ]);
+        let input = b"abc\xff \xfe\x00 \xc312";
+
+        let test: Test = from_bytes_with_fields(input, fields.clone()).unwrap();
+        assert_eq!(test.a, "abc");
+        assert_eq!(test.b, b"\xff ");
+        assert_eq!(test.c.as_ref(), b"\xfe\x00");
+        assert_eq!(test.d, b" \xc3");
+        assert_eq!(test.e, 12);
+
+        let h: HashMap<String, ByteBuf> = from_bytes_with_fields(input, fields.clone()).unwrap();
+        assert_eq!(h["b"].as_ref(), b"\xff ");
+        assert_eq!(h["e"].as_ref(), b"12");
+
+        let (a, _, _, _, e): (String, IgnoredAny, IgnoredAny, IgnoredAny, usize) =
+            from_bytes_with_fields(input, fields).unwrap();
+        assert_eq!(a, "abc");
+        assert_eq!(e, 12);
+    }
+
+    #[test]
+    fn test_ignored_invalid_utf8() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3),
+            FieldSet::new_field(3..6),
+            FieldSet::new_field(6..9),
+        ]);
+
+        let (a, _, c): (String, IgnoredAny, usize) =
+            from_bytes_with_fields(b"abc\xff\xfe\x00123", fields).unwrap();
+        assert_eq!(a, "abc");
+        assert_eq!(c, 123);
+    }
+
+    #[test]
+    fn test_ignored_unparseable() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3),
+            FieldSet::Seq(vec![FieldSet::new_field(3..6), FieldSet::new_field(6..9)]),
+            FieldSet::new_field(9..12),
+        ]);
+
+        let (a, _, c): (usize, IgnoredAny, usize) =
+            from_str_with_fields("123?!#@@@456", fields).unwrap();
+        assert_eq!(a, 123);
+        assert_eq!(c, 456);
+    }
+
+    #[test]
+    fn test_strict() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("a"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(2..4).name("b"),
+                FieldSet::new_field(4..6),
+            ]),
+            FieldSet::new_literal(6..8, "zz"),
+        ]);
+
+        let mut de = Deserializer::new(b"aabbcczz  ", fields.clone()).strict(true);
+        let (a, (b, c)) = <(String, (String, String))>::deserialize(&mut de).unwrap();
+        assert_eq!((a.as_str(), b.as_str(), c.as_str()), ("aa", "bb", "cc"));
+        de.finish().unwrap();
+
+        let mut de = Deserializer::new(b"aabbcczz", fields.clone()).strict(true);
+        match <(String, (String,))>::deserialize(&mut de) {
+            Err(DeserializeError::UnconsumedFields(f)) => assert_eq!(f, vec!["4..6"]),
+            _ => panic!("expected unconsumed fields"),
+        }
+
+        let mut de = Deserializer::new(b"aabbcczz", fields.clone()).strict(true);
+        let _ = <(String,)>::deserialize(&mut de).unwrap();
+        match de.finish() {
+            Err(DeserializeError::UnconsumedFields(f)) => assert_eq!(f, vec!["b", "4..6"]),
+            _ => panic!("expected unconsumed fields"),
+        }
+
+        let mut de = Deserializer::new(b"aabbcczz x", fields.clone());
+        let _ = <(String, (String, String))>::deserialize(&mut de).unwrap();
+        assert!(matches!(
+            de.finish(),
+            Err(DeserializeError::TrailingData { width: 8, len: 10 })
+        ));
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2),
+            FieldSet::new_field_to_end(2),
+        ]);
+        let mut de = Deserializer::new(b"aa and the rest", fields);
+        let _ = <(String, String)>::deserialize(&mut de).unwrap();
+        de.finish().unwrap();
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        #[derive(Debug, Deserialize)]
+        struct Test {
+            amount: u32,
+            name: String,
+            rate: f64,
+            active: bool,
+            nested: (u8, String),
+        }
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("amount"),
+            FieldSet::new_field(4..8).name("name"),
+            FieldSet::new_field(8..12).name("rate"),
+            FieldSet::new_field(12..14).name("active"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(14..16).name("count"),
+                FieldSet::new_field(16..18),
+            ]),
+        ]);
+
+        let input = b"12x4\xffbcd1.5.11ab\xfe ";
+        let mut de = Deserializer::new(input, fields.clone());
+        let errors = de.deserialize_collecting::<Test>().unwrap_err();
+        let names: Vec<&str> = errors.iter().map(|e| e.name.as_str()).collect();
+
+        assert_eq!(
+            names,
+            vec!["amount", "name", "rate", "active", "count", "16..18"]
+        );
+        assert_eq!(errors[0].range, 0..4);
+        assert_eq!(errors[0].value, b"12x4");
+        assert!(matches!(
+            errors[0].source,
+            DeserializeError::ParseIntError(_)
+        ));
+        assert!(matches!(errors[1].source, DeserializeError::InvalidUtf8(_)));
+        assert!(matches!(
+            errors[2].source,
+            DeserializeError::ParseFloatError(_)
+        ));
+        assert_eq!(errors[4].value, b"ab");
+
+        let mut de = Deserializer::new(b"1234abcd1.501 12ab", fields.clone());
+        let test = de.deserialize_collecting::<Test>().unwrap();
+        assert_eq!(test.amount, 1234);
+        assert_eq!(test.name, "abcd");
+        assert_eq!(test.rate, 1.5);
+        assert!(test.active);
+        assert_eq!(test.nested, (12, "ab".to_string()));
+
+        let mut de = Deserializer::new(b"12x4abcd1.5", fields);
+        let errors = de.deserialize_collecting::<Test>().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[1].source,
+            DeserializeError::UnexpectedEndOfRecord
+        ));
+    }
+
+    #[test]
+    fn test_capture_only() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("code"),
+            FieldSet::new_field(4..8).name("amount"),
+            FieldSet::new_field(0..0).name("raw").capture_only(true),
+        ]);
+
+        let input = b"ab  0012  ";
+        let h: HashMap<String, String> = from_bytes_with_fields(input, fields.clone()).unwrap();
+        assert_eq!(h["code"], "ab");
+        assert_eq!(h["amount"], "0012");
+        assert_eq!(h["raw"], "ab  0012  ");
+
+        let (_, _, raw): (String, u32, ByteBuf) =
+            from_bytes_with_fields(input, fields.clone()).unwrap();
+        assert_eq!(raw.as_ref(), input);
+
+        let mut de = Deserializer::new(b"ab  00x2", fields);
+        let errors = de
+            .deserialize_collecting::<(String, u32, String)>()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].name, "amount");
+    }
+
+    #[test]
+    fn test_128_bit_ints() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..39),
+            FieldSet::new_field(39..79),
+        ]);
+        let input = format!("{}{}", u128::MAX, i128::MIN);
+
+        let (u, i): (u128, i128) = from_str_with_fields(&input, fields.clone()).unwrap();
+        assert_eq!(u, u128::MAX);
+        assert_eq!(i, i128::MIN);
+
+        let input = format!("{:0>39}{:>40}", "123456789012345678901234567890", "-1");
+        let (u, i): (u128, i128) = from_str_with_fields(&input, fields.clone()).unwrap();
+        assert_eq!(u, 123456789012345678901234567890);
+        assert_eq!(i, -1);
+
+        let input = format!("{}{}", "9".repeat(39), i128::MIN);
+        let err = from_str_with_fields::<(u128, i128)>(&input, fields).unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::DeserializeError(DeserializeError::ParseIntError(_))
+        ));
+    }
+
+    #[test]
+    fn test_non_zero_ints() {
+        use std::num::{NonZeroU128, NonZeroU32};
+
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)]);
+
+        let (a, b): (NonZeroU32, NonZeroU128) =
+            from_str_with_fields("0012  34", fields.clone()).unwrap();
+        assert_eq!(a.get(), 12);
+        assert_eq!(b.get(), 34);
+
+        let err =
+            from_str_with_fields::<(NonZeroU32, NonZeroU128)>("0000  34", fields).unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::DeserializeError(DeserializeError::Message(_))
+        ));
+    }
+
+    #[test]
+    fn test_multi_byte_chars() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1),
+            FieldSet::new_field(1..3),
+            FieldSet::new_field(3..6),
+        ]);
+
+        let input = "aéあ";
+        let chars: (char, char, char) = from_str_with_fields(input, fields.clone()).unwrap();
+        assert_eq!(chars, ('a', 'é', 'あ'));
+
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)]);
+        let chars: (char, char) = from_str_with_fields(" é  あ", fields.clone()).unwrap();
+        assert_eq!(chars, ('é', 'あ'));
+
+        let err = from_str_with_fields::<(char, char)>("éé  あ  ", fields).unwrap_err();
+        assert_eq!(err.to_string(), "expected char field to hold 1 char, got 2");
+    }
+
+    #[test]
+    fn test_owned_and_borrowed_strings() {
+        #[derive(Debug, PartialEq)]
+        enum Visited {
+            Borrowed(String),
+            Transient(String),
+        }
+
+        struct VisitedVisitor;
+
+        impl<'de> Visitor<'de> for VisitedVisitor {
+            type Value = Visited;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, s: &'de str) -> Result<Visited, E> {
+                Ok(Visited::Borrowed(s.to_string()))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Visited, E> {
+                Ok(Visited::Transient(s.to_string()))
+            }
+        }
+
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)]);
+        let input = b"abc defg";
+
+        let mut de = Deserializer::new(input, fields.clone());
+        let borrowed = serde::Deserializer::deserialize_str(&mut de, VisitedVisitor).unwrap();
+        let transient = serde::Deserializer::deserialize_string(&mut de, VisitedVisitor).unwrap();
+        assert_eq!(borrowed, Visited::Borrowed("abc".to_string()));
+        assert_eq!(transient, Visited::Transient("defg".to_string()));
+
+        let borrowed: (&str, &str) = from_bytes_with_fields(input, fields.clone()).unwrap();
+        let owned: (String, String) = from_bytes_with_fields(input, fields.clone()).unwrap();
+        assert_eq!(borrowed, ("abc", "defg"));
+        assert_eq!(owned, ("abc".to_string(), "defg".to_string()));
+
+        // Owned strings can be read out of a buffer that is then overwritten.
+        let mut buf = input.to_vec();
+        let owned: (String, String) = from_bytes_with_fields(&buf, fields).unwrap();
+        buf.copy_from_slice(b"zzzzzzzz");
+        assert_eq!(owned, ("abc".to_string(), "defg".to_string()));
+        assert_eq!(buf, b"zzzzzzzz");
+    }
+
+    #[test]
+    fn test_with_flattened() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..5),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(5..8),
+                FieldSet::new_field(8..11).bytes(true),
+            ]),
+        ]);
+        let flat = fields.clone().flatten();
+
+        for input in [&b"Dab    1x  "[..], b"Dwxyz123yz0", b"D          "].iter() {
+            let mut de = Deserializer::new(input, fields.clone());
+            let expected: (String, (Option<u32>, &[u8])) =
+                Deserialize::deserialize(&mut de).unwrap();
+
+            let mut de = Deserializer::with_flattened(input, &flat);
+            let (a, b, c): (String, Option<u32>, &[u8]) =
+                Deserialize::deserialize(&mut de).unwrap();
+            assert_eq!((a, (b, c)), expected);
+            de.finish().unwrap();
+
+            let mut de = Deserializer::with_flattened_ref(input, &flat);
+            let (a, b, c): (String, Option<u32>, &[u8]) =
+                Deserialize::deserialize(&mut de).unwrap();
+            assert_eq!((a, (b, c)), expected);
+            de.finish().unwrap();
+        }
+
+        let mut de = Deserializer::with_flattened(b"Dab    1x  zz", &flat);
+        let _: Vec<String> = Deserialize::deserialize(&mut de).unwrap();
+        assert!(matches!(
+            de.finish(),
+            Err(DeserializeError::TrailingData { width: 11, len: 13 })
+        ));
+
+        let mut de = Deserializer::with_flattened(b"abcdef", &[FieldConfig::to_end(0)]);
+        let s: String = Deserialize::deserialize(&mut de).unwrap();
+        assert_eq!(s, "abcdef");
+        de.finish().unwrap();
+    }
+
+    #[test]
+    fn test_with_fields_matches_owned() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..5).name("name"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(5..8),
+                FieldSet::new_field(8..11).bytes(true),
+            ]),
+        ]);
+
+        for input in [&b"Dab    1x  "[..], b"Dwxyz123yz0", b"D          "].iter() {
+            let expected: (String, (Option<u32>, &[u8])) =
+                from_bytes_with_fields(input, fields.clone()).unwrap();
+            let borrowed: (String, (Option<u32>, &[u8])) =
+                from_bytes_with_fields_ref(input, &fields).unwrap();
+            assert_eq!(borrowed, expected);
+
+            let mut de = Deserializer::with_fields(input, &fields);
+            let _: (String, (Option<u32>, &[u8])) = Deserialize::deserialize(&mut de).unwrap();
+            de.finish().unwrap();
+        }
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("a"),
+            FieldSet::new_field(2..4),
+        ]);
+        let expected: HashMap<String, String> =
+            from_str_with_fields("aabb", fields.clone()).unwrap();
+        let borrowed: HashMap<String, String> = from_str_with_fields_ref("aabb", &fields).unwrap();
+        assert_eq!(borrowed, expected);
+        assert_eq!(borrowed["2..4"], "bb");
+
+        let item = FieldSet::new_field(0..3);
+        let n: u32 = from_str_with_fields_ref("042", &item).unwrap();
+        assert_eq!(n, 42);
+    }
+
+    #[test]
+    fn test_with_fields_errors_match_owned() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("a"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(2..4).name("b"),
+                FieldSet::new_field(4..6),
+            ]),
+            FieldSet::new_literal(6..8, "zz"),
+        ]);
+
+        let mut de = Deserializer::with_fields(b"aabbcczz", &fields).strict(true);
+        match <(String, (String,))>::deserialize(&mut de) {
+            Err(DeserializeError::UnconsumedFields(f)) => assert_eq!(f, vec!["4..6"]),
+            _ => panic!("expected unconsumed fields"),
+        }
+
+        let mut de = Deserializer::with_fields(b"aabbcczz", &fields);
+        let _ = <(String,)>::deserialize(&mut de).unwrap();
+        match de.finish() {
+            Err(DeserializeError::UnconsumedFields(f)) => assert_eq!(f, vec!["b", "4..6"]),
+            _ => panic!("expected unconsumed fields"),
+        }
+
+        let owned = from_str_with_fields::<(u8, (u8, u8))>("1x2233zz", fields.clone());
+        let borrowed = from_str_with_fields_ref::<(u8, (u8, u8))>("1x2233zz", &fields);
+        assert_eq!(
+            borrowed.unwrap_err().to_string(),
+            owned.unwrap_err().to_string()
+        );
+
+        let mut de = Deserializer::with_fields(b"1x2y33zz", &fields);
+        let errors = de.deserialize_collecting::<(u8, (u8, u8))>().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].name, "a");
+        assert_eq!(errors[1].name, "b");
+        assert_eq!(errors[1].value, b"2y");
+    }
+
+    #[test]
+    fn test_center_justified() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..7, "HDR")
+                .justify(Justify::Center)
+                .pad_with('*')
+                .strict(true),
+            FieldSet::new_field(7..14).justify(Justify::Center),
+            FieldSet::new_field(14..18).justify(Justify::Center),
+        ]);
+
+        let (title, n): (String, u32) =
+            from_bytes_with_fields(b"**HDR** TITLE   42 ", fields.clone()).unwrap();
+        assert_eq!(title, "TITLE");
+        assert_eq!(n, 42);
+
+        let fields_padded = FieldSet::Seq(vec![
+            FieldSet::new_field(0..7)
+                .justify(Justify::Center)
+                .pad_with('*'),
+            FieldSet::new_field(7..11)
+                .justify(Justify::Center)
+                .pad_with('*'),
+            FieldSet::new_field(11..15)
+                .justify(Justify::Right)
+                .pad_with('*'),
+        ]);
+        let (title, n, right): (String, u32, String) =
+            from_bytes_with_fields(b"**TI*T**42***AB", fields_padded.clone()).unwrap();
+        assert_eq!(title, "TI*T");
+        assert_eq!(n, 42);
+        assert_eq!(right, "**AB");
+
+        let mut de = Deserializer::with_fields(b"* A B *", &fields_padded);
+        let value = crate::Value::deserialize(&mut de).unwrap();
+        assert_eq!(value, crate::Value::Str("A B".to_string()));
+
+        match from_bytes_with_fields::<(String, u32)>(b"*HDR-** TITLE   42 ", fields) {
+            Err(error::Error::DeserializeError(DeserializeError::LiteralMismatch {
+                expected,
+                found,
+            })) => {
+                assert_eq!(expected, "HDR");
+                assert_eq!(found, "*HDR-**");
+            }
+            other => panic!("expected a literal mismatch, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,850 @@
+//! Helpers for debugging field layouts against real records.
+//!
+//! `explain` renders a record under a column ruler, followed by a table of its fields with their
+//! ranges, raw bytes and the trimmed values they deserialize from. Gaps between fields, bytes
+//! past the last field and overlapping fields are called out in the table.
+//!
+//! The layout of a set of fields alone, without a record, is rendered by `FieldSet::describe`.
+
+use crate::{
+    de::field_str, Case, DecimalSeparator, DeserializeError, Error, FieldConfig, FieldSet, Sign,
+};
+use std::{ascii, fmt, ops::Range};
+
+const DEFAULT_WRAP: usize = 80;
+
+/// Renders a record and the fields it is split into. Records wider than 80 columns are wrapped.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{debug, FieldSet};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6).name("name"),
+///     FieldSet::new_field(6..10).name("age"),
+/// ]);
+///
+/// let explained = debug::explain(b"Carl  0042", &fields);
+///
+/// assert_eq!(
+///     explained,
+///     "\
+/// 0
+/// 0123456789
+/// Carl  0042
+///
+///   field  range  raw       value
+///   name   0..6   \"Carl  \"  \"Carl\"
+///   age    6..10  \"0042\"    \"0042\"
+/// "
+/// );
+/// ```
+pub fn explain(record: &[u8], fields: &FieldSet) -> String {
+    Explain::new(record, fields).to_string()
+}
+
+/// Renders a record and the fields it is split into, highlighting the field named by the given
+/// error and ending with the error itself.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{debug, DeserializeError, Deserializer, Error, FieldSet, FixedWidth};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Person {
+///     name: String,
+///     age: usize,
+/// }
+///
+/// impl FixedWidth for Person {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..6).name("name"),
+///             FieldSet::new_field(6..10).name("age"),
+///         ])
+///     }
+/// }
+///
+/// let record = b"Carl  00x2";
+/// let mut de = Deserializer::new(record, Person::fields());
+/// let mut errors = de.deserialize_collecting::<Person>().unwrap_err();
+/// let err = Error::from(DeserializeError::from(errors.remove(0)));
+/// let explained = debug::explain_error(record, &Person::fields(), &err);
+///
+/// assert!(explained.contains("      ^^^^\n"));
+/// assert!(explained.contains("> age    6..10  \"00x2\"    \"00x2\"\n"));
+/// assert!(explained.ends_with("error: field 'age' (6..10): invalid digit found in string\n"));
+/// ```
+pub fn explain_error(record: &[u8], fields: &FieldSet, err: &Error) -> String {
+    Explain::new(record, fields).error(err).to_string()
+}
+
+/// A record rendered along with the fields it is split into, as built by `explain`. Use this
+/// directly to change how the record is wrapped.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{debug::Explain, FieldSet};
+///
+/// let fields = FieldSet::new_field(0..30).name("text");
+/// let record = [b'x'; 30];
+///
+/// let explained = Explain::new(&record, &fields).wrap(20).to_string();
+///
+/// assert!(explained.starts_with(
+///     "0         1\n01234567890123456789\nxxxxxxxxxxxxxxxxxxxx\n\n2\n0123456789\nxxxxxxxxxx\n"
+/// ));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Explain<'a> {
+    record: &'a [u8],
+    fields: &'a FieldSet,
+    wrap: usize,
+    error: Option<&'a Error>,
+}
+
+impl<'a> Explain<'a> {
+    /// Creates a new rendering of the given record split into the given fields.
+    pub fn new(record: &'a [u8], fields: &'a FieldSet) -> Self {
+        Self {
+            record,
+            fields,
+            wrap: DEFAULT_WRAP,
+            error: None,
+        }
+    }
+
+    /// Sets the number of columns the record is wrapped at. Defaults to 80, and a width of 0
+    /// does not wrap the record at all.
+    pub fn wrap(mut self, width: usize) -> Self {
+        self.wrap = width;
+        self
+    }
+
+    /// Sets an error to highlight the field of, and to show after the fields. The field is found
+    /// from the innermost `DeserializeError::Field` of the error, if it has one.
+    pub fn error(mut self, err: &'a Error) -> Self {
+        self.error = Some(err);
+        self
+    }
+
+    fn highlighted(&self) -> Option<(&'a str, &'a Range<usize>)> {
+        let mut found = None;
+        let mut next = self.error?;
+        while let Error::Record { ref source, .. } = next {
+            next = source;
+        }
+
+        let mut de_err = match next {
+            Error::DeserializeError(ref e) => e,
+            _ => return None,
+        };
+
+        while let DeserializeError::Field {
+            ref name,
+            ref range,
+            ref source,
+        } = de_err
+        {
+            found = Some((name.as_str(), range));
+            de_err = source;
+        }
+
+        found
+    }
+
+    fn write_record(
+        &self,
+        f: &mut fmt::Formatter,
+        highlight: Option<&Range<usize>>,
+    ) -> fmt::Result {
+        let len = self.record.len();
+        let wrap = if self.wrap == 0 {
+            len.max(1)
+        } else {
+            self.wrap
+        };
+
+        let mut start = 0;
+        loop {
+            let end = (start + wrap).min(len);
+            if start > 0 {
+                writeln!(f)?;
+            }
+
+            // Tens are labelled where they start, so a label runs on into the following columns.
+            let cols = start..end.max(start + 1);
+            let mut tens = vec![' '; cols.len()];
+            for col in cols.clone().filter(|col| col % 10 == 0) {
+                let label = (col / 10).to_string();
+                for (mark, c) in tens[col - start..].iter_mut().zip(label.chars()) {
+                    *mark = c;
+                }
+            }
+            let tens: String = tens.into_iter().collect();
+            let units: String = cols
+                .map(|col| char::from(b'0' + (col % 10) as u8))
+                .collect();
+            writeln!(f, "{}", tens.trim_end())?;
+            writeln!(f, "{}", units)?;
+
+            let line: String = self.record[start..end]
+                .iter()
+                .map(|&b| {
+                    if b == b' ' || b.is_ascii_graphic() {
+                        char::from(b)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line)?;
+
+            if let Some(range) = highlight {
+                let marks: String = (start..end)
+                    .map(|col| if range.contains(&col) { '^' } else { ' ' })
+                    .collect();
+                if marks.contains('^') {
+                    writeln!(f, "{}", marks.trim_end())?;
+                }
+            }
+
+            start = end;
+            if start >= len {
+                return Ok(());
+            }
+        }
+    }
+
+    fn rows(&self, highlight: Option<(&str, &Range<usize>)>) -> Vec<Row> {
+        let len = self.record.len();
+        let fields: Vec<&FieldConfig> = self.fields.iter_flat().collect();
+        let mut covered = vec![false; len];
+        let mut rows = Vec::new();
+
+        for (i, conf) in fields.iter().enumerate() {
+            let range = conf_range(conf, len);
+            if !conf.capture_only {
+                for covered in &mut covered[range.start.min(len)..range.end.min(len)] {
+                    *covered = true;
+                }
+            }
+
+            let mut notes = Vec::new();
+            if conf.capture_only {
+                notes.push("whole record".to_string());
+            }
+
+            let overlaps: Vec<String> = fields
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| {
+                    let other_range = conf_range(other, len);
+                    i != j
+                        && !other.capture_only
+                        && !conf.capture_only
+                        && range.start < other_range.end
+                        && other_range.start < range.end
+                })
+                .map(|(_, other)| other.describe())
+                .collect();
+            if !overlaps.is_empty() {
+                notes.push(format!("overlaps {}", overlaps.join(", ")));
+            }
+
+            let highlighted = highlight
+                .map(|(name, hl_range)| conf.describe() == name && conf.range == *hl_range)
+                .unwrap_or(false);
+
+            rows.push(Row {
+                start: range.start,
+                highlighted,
+                name: conf.name.clone().unwrap_or_else(|| "-".to_string()),
+                range: conf.range_string(),
+                raw: conf.slice(self.record).map(escape).unwrap_or_else(|| {
+                    escape(&self.record[range.start.min(len)..range.end.min(len)])
+                }),
+                value: field_value(conf, self.record),
+                notes,
+            });
+        }
+
+        // Bytes that no field covers, after the last field or between fields.
+        let last_end = fields
+            .iter()
+            .filter(|conf| !conf.capture_only)
+            .map(|conf| conf_range(conf, len).end)
+            .max()
+            .unwrap_or(0);
+        let mut col = 0;
+        while col < len {
+            if covered[col] {
+                col += 1;
+                continue;
+            }
+
+            let start = col;
+            while col < len && !covered[col] {
+                col += 1;
+            }
+
+            let name = if start >= last_end {
+                "(trailing)"
+            } else {
+                "(gap)"
+            };
+            rows.push(Row {
+                start,
+                highlighted: false,
+                name: name.to_string(),
+                range: format!("{}..{}", start, col),
+                raw: escape(&self.record[start..col]),
+                value: String::new(),
+                notes: Vec::new(),
+            });
+        }
+
+        rows.sort_by_key(|row| row.start);
+        rows
+    }
+}
+
+impl<'a> fmt::Display for Explain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let highlight = self.highlighted();
+        self.write_record(f, highlight.map(|(_, range)| range))?;
+        writeln!(f)?;
+
+        let rows = self.rows(highlight);
+        let header = Row {
+            start: 0,
+            highlighted: false,
+            name: "field".to_string(),
+            range: "range".to_string(),
+            raw: "raw".to_string(),
+            value: "value".to_string(),
+            notes: Vec::new(),
+        };
+
+        let width = |col: fn(&Row) -> &str| {
+            rows.iter()
+                .chain(Some(&header))
+                .map(|row| col(row).chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let name_width = width(|row| &row.name);
+        let range_width = width(|row| &row.range);
+        let raw_width = width(|row| &row.raw);
+
+        for row in Some(&header).into_iter().chain(&rows) {
+            let mut line = format!(
+                "{} {:name_width$}  {:range_width$}  {:raw_width$}  {}",
+                if row.highlighted { '>' } else { ' ' },
+                row.name,
+                row.range,
+                row.raw,
+                row.value,
+                name_width = name_width,
+                range_width = range_width,
+                raw_width = raw_width,
+            );
+            if !row.notes.is_empty() {
+                line.push_str(&format!("  ({})", row.notes.join("; ")));
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        if let Some(err) = self.error {
+            writeln!(f)?;
+            writeln!(f, "error: {}", err)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Row {
+    start: usize,
+    highlighted: bool,
+    name: String,
+    range: String,
+    raw: String,
+    value: String,
+    notes: Vec<String>,
+}
+
+/// Renders the layout of a set of fields as a table, for `FieldSet::describe`.
+pub(crate) fn layout(fields: &FieldSet) -> String {
+    let fields: Vec<&FieldConfig> = fields.iter_flat().collect();
+    // Fields that run to the end of the record cover every byte from their start.
+    let range = |conf: &FieldConfig| conf_range(conf, usize::MAX);
+    let mut rows: Vec<(usize, [String; 7], Vec<String>)> = Vec::new();
+
+    for (i, conf) in fields.iter().enumerate() {
+        let mut notes = Vec::new();
+        let overlaps: Vec<String> = fields
+            .iter()
+            .enumerate()
+            .filter(|&(j, other)| {
+                i != j
+                    && !other.capture_only
+                    && !conf.capture_only
+                    && range(conf).start < range(other).end
+                    && range(other).start < range(conf).end
+            })
+            .map(|(_, other)| other.describe())
+            .collect();
+        if !overlaps.is_empty() {
+            notes.push(format!("overlaps {}", overlaps.join(", ")));
+        }
+
+        let width = if conf.capture_only || conf.to_end {
+            "-".to_string()
+        } else {
+            range(conf).len().to_string()
+        };
+        rows.push((
+            conf.range.start,
+            [
+                i.to_string(),
+                conf.name.clone().unwrap_or_else(|| "-".to_string()),
+                conf.range_string(),
+                width,
+                format!("{:?}", conf.pad_with),
+                conf.justify.to_string(),
+                options(conf).join(" "),
+            ],
+            notes,
+        ));
+    }
+
+    let mut covered: Vec<Range<usize>> = fields
+        .iter()
+        .filter(|conf| !conf.capture_only)
+        .map(|conf| range(conf))
+        .collect();
+    covered.sort_by_key(|range| range.start);
+
+    let mut end = 0;
+    for range in covered {
+        if range.start > end {
+            let gap = [
+                String::new(),
+                "(gap)".to_string(),
+                format!("{}..{}", end, range.start),
+                (range.start - end).to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ];
+            rows.push((end, gap, Vec::new()));
+        }
+        end = end.max(range.end);
+    }
+
+    // Gaps sort before the field that ends them, and fields keep their order otherwise.
+    rows.sort_by_key(|(start, _, _)| *start);
+
+    let header = ["#", "name", "range", "width", "pad", "justify", "options"].map(String::from);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            rows.iter()
+                .map(|(_, cells, _)| &cells[col])
+                .chain(Some(&header[col]))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut table = String::new();
+    for (cells, notes) in Some((&header, &Vec::new()))
+        .into_iter()
+        .chain(rows.iter().map(|(_, cells, notes)| (cells, notes)))
+    {
+        let mut line = String::new();
+        for (cell, width) in cells.iter().zip(&widths) {
+            line.push_str(&format!("{:width$}  ", cell, width = width));
+        }
+        if !notes.is_empty() {
+            line.push_str(&format!("({})", notes.join("; ")));
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+
+    table
+}
+
+/// The settings of a field other than its name, range, padding and justification, as they are
+/// shown by `FieldSet::describe`.
+fn options(conf: &FieldConfig) -> Vec<String> {
+    let mut options = Vec::new();
+
+    if let Some(ref literal) = conf.literal {
+        options.push(format!("literal={:?}", literal));
+    }
+    if conf.strict {
+        options.push("strict".to_string());
+    }
+    if let Some(ref default) = conf.default {
+        options.push(format!("default={:?}", default));
+    }
+    if conf.capture_only {
+        options.push("capture_only".to_string());
+    }
+    if conf.bytes {
+        options.push("bytes".to_string());
+    }
+    if conf.write_as_blank {
+        options.push("write_as_blank".to_string());
+    }
+    if let Some(decimals) = conf.decimals {
+        options.push(format!("decimals={}", decimals));
+    }
+    match conf.sign {
+        Sign::NegativeOnly => {}
+        Sign::Always => options.push("sign=always".to_string()),
+        Sign::TrailingChar => options.push("sign=trailing".to_string()),
+    }
+    match conf.decimal_separator {
+        DecimalSeparator::Point => {}
+        DecimalSeparator::Comma => options.push("decimal_separator=','".to_string()),
+        DecimalSeparator::Implied => options.push("decimal_separator=implied".to_string()),
+    }
+    if conf.blank_when_zero {
+        options.push("blank_when_zero".to_string());
+    }
+    match conf.case {
+        Case::None => {}
+        Case::Upper => options.push("case=upper".to_string()),
+        Case::Lower => options.push("case=lower".to_string()),
+    }
+    if conf.case_on_read {
+        options.push("case_on_read".to_string());
+    }
+    if let Some(ref variants) = conf.variants {
+        let mut codes: Vec<String> = variants
+            .codes
+            .iter()
+            .map(|(code, variant)| format!("{}={}", code, variant))
+            .collect();
+        if let Some(ref catch_all) = variants.catch_all {
+            codes.push(format!("*={}", catch_all));
+        }
+        options.push(format!("variants=[{}]", codes.join(",")));
+    }
+    if let Some(flags) = conf.flags {
+        options.push(format!(
+            "flags={}x{}/{}",
+            flags.count,
+            char::from(flags.yes),
+            char::from(flags.no)
+        ));
+    }
+    if let Some(count) = conf.occurs {
+        options.push(format!("occurs={}", count));
+    }
+    if conf.deserialize_with.is_some() {
+        options.push("deserialize_with".to_string());
+    }
+    if conf.serialize_with.is_some() {
+        options.push("serialize_with".to_string());
+    }
+
+    options
+}
+
+/// The columns of the record a field covers, running to the end of the record for fields that
+/// do. A range that starts after it ends covers nothing.
+fn conf_range(conf: &FieldConfig, len: usize) -> Range<usize> {
+    if conf.capture_only {
+        0..len
+    } else if conf.to_end {
+        conf.range.start..len.max(conf.range.start)
+    } else {
+        conf.range.start.min(conf.range.end)..conf.range.end
+    }
+}
+
+/// The value a field deserializes from, as it would be handed to a `String`.
+fn field_value(conf: &FieldConfig, record: &[u8]) -> String {
+    if let Some(ref literal) = conf.literal {
+        return format!("{:?} (literal)", literal);
+    }
+    if conf.capture_only || conf.bytes {
+        return "(raw bytes)".to_string();
+    }
+
+    match field_str(conf, record) {
+        Ok(value) => format!("{:?}", value),
+        Err(DeserializeError::UnexpectedEndOfRecord) => "(past end of record)".to_string(),
+        Err(e) => format!("<{}>", e),
+    }
+}
+
+/// Quotes bytes, escaping anything that is not printable ASCII.
+fn escape(bytes: &[u8]) -> String {
+    let mut s = String::from("\"");
+    for &b in bytes {
+        s.extend(ascii::escape_default(b).map(char::from));
+    }
+    s.push('"');
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn person_fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..6).name("name"),
+            FieldSet::new_field(6..10).name("age"),
+        ])
+    }
+
+    #[test]
+    fn explain_record() {
+        assert_eq!(
+            explain(b"Carl  0042", &person_fields()),
+            "\
+0
+0123456789
+Carl  0042
+
+  field  range  raw       value
+  name   0..6   \"Carl  \"  \"Carl\"
+  age    6..10  \"0042\"    \"0042\"
+"
+        );
+    }
+
+    #[test]
+    fn explain_escapes() {
+        let explained = explain(b"a\tb\x00 \xffz", &FieldSet::new_field(0..7).name("text"));
+
+        assert!(explained.contains("\na.b. .z\n"));
+        assert!(explained.contains("  text   0..7   \"a\\tb\\x00 \\xffz\"  <"));
+    }
+
+    #[test]
+    fn explain_gaps_and_overlaps() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field(6..10).name("code"),
+            FieldSet::new_field(8..12).name("sub"),
+            FieldSet::new_literal(12..13, "-"),
+        ]);
+        let explained = explain(b"0001  ABCDEF-xyz", &fields);
+
+        assert_eq!(
+            explained,
+            "\
+0         1
+0123456789012345
+0001  ABCDEF-xyz
+
+  field       range   raw     value
+  id          0..4    \"0001\"  \"0001\"
+  (gap)       4..6    \"  \"
+  code        6..10   \"ABCD\"  \"ABCD\"  (overlaps sub)
+  sub         8..12   \"CDEF\"  \"CDEF\"  (overlaps code)
+  -           12..13  \"-\"     \"-\" (literal)
+  (trailing)  13..16  \"xyz\"
+"
+        );
+    }
+
+    #[test]
+    fn explain_short_record() {
+        let explained = explain(b"Carl  00", &person_fields());
+
+        assert!(explained.contains("  age    6..10  \"00\"      (past end of record)\n"));
+    }
+
+    #[test]
+    fn explain_to_end_and_capture() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("kind"),
+            FieldSet::new_field_to_end(2).name("rest"),
+            FieldSet::new_field(0..0).name("raw").capture_only(true),
+        ]);
+        let explained = explain(b"ABcdef", &fields);
+
+        assert!(explained.contains("  rest   2..    \"cdef\"    \"cdef\"\n"));
+        assert!(explained.contains("(raw bytes)  (whole record)\n"));
+        assert!(!explained.contains("(gap)"));
+        assert!(!explained.contains("overlaps"));
+    }
+
+    #[test]
+    fn layout_options() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(3..9)
+                .name("amount")
+                .pad_with('0')
+                .justify("right")
+                .decimals(2)
+                .sign(Sign::TrailingChar)
+                .blank_when_zero(true),
+            FieldSet::new_field(9..10)
+                .name("kind")
+                .variants(&[("A", "Active"), ("C", "Closed")])
+                .catch_all_variant("Other"),
+            FieldSet::new_field(10..13).name("flags").flags(3, 'Y', 'N'),
+            FieldSet::new_field_to_end(13)
+                .name("note")
+                .transform(Case::Upper)
+                .default_value("none"),
+            FieldSet::new_field(0..0).name("raw").capture_only(true),
+        ]);
+
+        assert_eq!(
+            fields.describe(),
+            "\
+#  name    range   width  pad  justify  options
+0  -       0..1    1      ' '  left     literal=\"D\"
+5  raw     0..0    -      ' '  left     capture_only
+   (gap)   1..3    2
+1  amount  3..9    6      '0'  right    decimals=2 sign=trailing blank_when_zero
+2  kind    9..10   1      ' '  left     variants=[A=Active,C=Closed,*=Other]
+3  flags   10..13  3      ' '  left     flags=3xY/N
+4  note    13..    -      ' '  left     default=\"none\" case=upper
+"
+        );
+    }
+
+    #[test]
+    fn layout_overlaps_to_end() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(2..4),
+            FieldSet::new_field_to_end(4).name("rest"),
+            FieldSet::new_field(8..10).name("late"),
+        ]);
+
+        assert_eq!(
+            fields.describe(),
+            "\
+#  name   range  width  pad  justify  options
+   (gap)  0..2   2
+0  -      2..4   2      ' '  left
+1  rest   4..    -      ' '  left              (overlaps late)
+2  late   8..10  2      ' '  left              (overlaps rest)
+"
+        );
+    }
+
+    #[test]
+    fn explain_wraps() {
+        let record: Vec<u8> = (0..125).map(|i| b'a' + (i % 26) as u8).collect();
+        let fields = FieldSet::new_field(0..125).name("text");
+        let explained = Explain::new(&record, &fields).wrap(120).to_string();
+        let lines: Vec<&str> = explained.lines().collect();
+
+        assert_eq!(lines[0].len(), 112);
+        assert!(lines[0].ends_with("11"));
+        assert_eq!(lines[1].len(), 120);
+        assert_eq!(lines[2].len(), 120);
+        assert_eq!(lines[3], "");
+        assert_eq!(lines[4], "12");
+        assert_eq!(lines[5], "01234");
+        assert_eq!(lines[6], "qrstu");
+
+        let explained = Explain::new(&record, &fields).wrap(0).to_string();
+        assert_eq!(explained.lines().nth(2).unwrap().len(), 125);
+    }
+
+    #[test]
+    fn explain_empty_record() {
+        let explained = explain(b"", &person_fields());
+
+        assert_eq!(
+            explained,
+            "\
+0
+0
+
+
+  field  range  raw  value
+  name   0..6   \"\"   (past end of record)
+  age    6..10  \"\"   (past end of record)
+"
+        );
+    }
+
+    #[test]
+    fn inverted_range() {
+        let (start, end) = (6, 4);
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field(start..end).name("bad"),
+        ]);
+
+        let explained = explain(b"0001abcd", &fields);
+        assert!(explained.contains("  bad         6..4   \"\"      (past end of record)\n"));
+
+        let described = fields.describe();
+        assert!(described.contains("1  bad   6..4   0      ' '  left\n"));
+    }
+
+    #[test]
+    fn layout_occurs() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(2..4)]).occurs(2),
+            FieldSet::new_field(4..6).name("end"),
+        ]);
+
+        assert_eq!(
+            fields.describe(),
+            "\
+#  name  range  width  pad  justify  options
+0  -     0..2   2      ' '  left     occurs=2
+1  -     2..4   2      ' '  left
+2  end   4..6   2      ' '  left
+"
+        );
+    }
+
+    #[test]
+    fn explain_error_highlight() {
+        let err = Error::Record {
+            index: 3,
+            source: Box::new(Error::from(DeserializeError::Field {
+                name: "age".to_string(),
+                range: 6..10,
+                source: Box::new(DeserializeError::Message("bad age".to_string())),
+            })),
+        };
+        let explained = explain_error(b"Carl  00x2", &person_fields(), &err);
+
+        assert_eq!(
+            explained,
+            "\
+0
+0123456789
+Carl  00x2
+      ^^^^
+
+  field  range  raw       value
+  name   0..6   \"Carl  \"  \"Carl\"
+> age    6..10  \"00x2\"    \"00x2\"
+
+error: record 3, field 'age' (6..10): bad age
+"
+        );
+
+        // Errors without a field are shown without highlighting anything.
+        let err = Error::from(DeserializeError::UnexpectedEndOfRecord);
+        let explained = explain_error(b"Carl  00x2", &person_fields(), &err);
+        assert!(!explained.contains('^'));
+        assert!(!explained.contains('>'));
+        assert!(explained.ends_with("error: byte length of record was less than defined length\n"));
+    }
+}
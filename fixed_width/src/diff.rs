@@ -0,0 +1,606 @@
+//! Compares two fixed width files field by field, to find how the output of a job changed.
+//!
+//! Unlike a diff of the bytes, values are compared with their padding trimmed as they would be
+//! when deserialized, so re-padding a value is not a difference.
+
+use crate::{error::Error, DecimalSeparator, FieldConfig, FieldSet, Reader, Result};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    io::Read,
+};
+
+const DEFAULT_MAX_DIFFERENCES: usize = 100;
+
+/// Options for `compare`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::diff::DiffOptions;
+///
+/// let opts = DiffOptions::new()
+///     .key("id")
+///     .ignore("updated")
+///     .tolerance(0.005)
+///     .tolerant("rate")
+///     .max_differences(10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    key: Option<String>,
+    ignore: Vec<String>,
+    tolerance: Option<f64>,
+    tolerant: Vec<String>,
+    max_differences: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            key: None,
+            ignore: Vec::new(),
+            tolerance: None,
+            tolerant: Vec::new(),
+            max_differences: DEFAULT_MAX_DIFFERENCES,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Creates options that align records by their index, compare every field exactly, and keep
+    /// the first 100 differences.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aligns records by the trimmed value of the field with the given name rather than by their
+    /// index, so records that moved are still compared with each other. Records with the same
+    /// key are matched in the order they are read.
+    pub fn key<T: Into<String>>(mut self, field: T) -> Self {
+        self.key = Some(field.into());
+        self
+    }
+
+    /// Leaves the field with the given name out of the comparison, such as a timestamp that is
+    /// expected to differ. May be called more than once.
+    pub fn ignore<T: Into<String>>(mut self, field: T) -> Self {
+        self.ignore.push(field.into());
+        self
+    }
+
+    /// Treats values of fields with `decimals`, and of fields named with `tolerant`, as the same
+    /// if they both parse as numbers that differ by no more than the given amount. Other fields,
+    /// such as ids and dates, are always compared exactly. By default, values must be the same
+    /// once trimmed.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Compares the field with the given name within the `tolerance`, as for fields with
+    /// `decimals`. May be called more than once.
+    pub fn tolerant<T: Into<String>>(mut self, field: T) -> Self {
+        self.tolerant.push(field.into());
+        self
+    }
+
+    /// Sets the most differences kept in the report. Differences beyond this are still counted.
+    /// Defaults to 100.
+    pub fn max_differences(mut self, max: usize) -> Self {
+        self.max_differences = max;
+        self
+    }
+}
+
+/// A difference between two files found by `compare`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The zero based index of the record among those read from the left file, or from the right
+    /// file for a record that is only in the right file.
+    pub index: u64,
+    /// The key of the record, when aligning records by key.
+    pub key: Option<String>,
+    /// The name of the field that differs, or its range if it has no name. `None` when the whole
+    /// record is only in one of the files.
+    pub field: Option<String>,
+    /// The trimmed value in the left file, or the whole record for a record only in the left
+    /// file. `None` for a record only in the right file.
+    pub left: Option<String>,
+    /// The trimmed value in the right file, or the whole record for a record only in the right
+    /// file. `None` for a record only in the left file.
+    pub right: Option<String>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "record {}", self.index)?;
+        if let Some(ref key) = self.key {
+            write!(f, " (key {:?})", key)?;
+        }
+
+        match (&self.field, &self.left, &self.right) {
+            (Some(field), Some(left), Some(right)) => {
+                write!(f, ", field '{}': {:?} != {:?}", field, left, right)
+            }
+            (_, Some(left), _) => write!(f, ": only in left: {:?}", left),
+            (_, _, Some(right)) => write!(f, ": only in right: {:?}", right),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The results of comparing two files with `compare`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// The number of records read from the left file.
+    pub left_records: u64,
+    /// The number of records read from the right file.
+    pub right_records: u64,
+    /// The number of records in both files that were compared with each other.
+    pub compared: u64,
+    /// The number of compared records with at least one field that differs.
+    pub differing_records: u64,
+    /// The number of records only in the left file.
+    pub only_left: u64,
+    /// The number of records only in the right file.
+    pub only_right: u64,
+    /// The number of differences found in each field, by the field's name or range, including
+    /// those beyond `max_differences`.
+    pub field_differences: HashMap<String, u64>,
+    /// The differences found, up to `max_differences`. Differences in compared records come
+    /// first, in the order of the left file, followed by records only in one of the files.
+    pub differences: Vec<Difference>,
+    /// Whether more differences were found than were kept in `differences`.
+    pub truncated: bool,
+}
+
+impl DiffReport {
+    /// Whether the files hold the same records, apart from ignored fields and padding.
+    pub fn is_identical(&self) -> bool {
+        self.differing_records == 0 && self.only_left == 0 && self.only_right == 0
+    }
+
+    fn push(&mut self, difference: Difference, max: usize) {
+        if self.differences.len() < max {
+            self.differences.push(difference);
+        } else {
+            self.truncated = true;
+        }
+    }
+}
+
+/// Compares the records of two readers field by field, with the padding of each field trimmed.
+/// Records are aligned by their index, or by a key field if `DiffOptions::key` is set. Errors
+/// reading either file are returned rather than reported, as is `Error::UnknownField` if an
+/// option names a field that is not in `fields`.
+///
+/// Aligning by key holds the records of the right file that are yet to be matched in memory.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{diff::{self, DiffOptions}, FieldSet, Reader};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("id"),
+///     FieldSet::new_field(4..10).name("amount").pad_with('0').justify("right"),
+///     FieldSet::new_field(10..14).name("time"),
+/// ]);
+///
+/// let mut yesterday = Reader::from_string("0001000042083000020000071200").width(14);
+/// let mut today = Reader::from_string("0001    42091500020000081300").width(14);
+///
+/// let opts = DiffOptions::new().ignore("time");
+/// let report = diff::compare(&mut yesterday, &mut today, &fields, opts).unwrap();
+///
+/// assert_eq!(report.differing_records, 1);
+/// assert_eq!(report.field_differences["amount"], 1);
+/// assert_eq!(
+///     report.differences[0].to_string(),
+///     r#"record 1, field 'amount': "7" != "8""#
+/// );
+/// ```
+pub fn compare<A, B>(
+    a: &mut Reader<A>,
+    b: &mut Reader<B>,
+    fields: &FieldSet,
+    opts: DiffOptions,
+) -> Result<DiffReport>
+where
+    A: Read,
+    B: Read,
+{
+    let find = |name: &str| {
+        fields
+            .iter_flat()
+            .find(|conf| conf.name.as_deref() == Some(name))
+            .ok_or_else(|| Error::UnknownField(name.to_string()))
+    };
+
+    for name in opts.ignore.iter().chain(&opts.tolerant) {
+        find(name)?;
+    }
+    let key = opts.key.as_deref().map(find).transpose()?;
+
+    let compared: Vec<(&FieldConfig, bool)> = fields
+        .iter_flat()
+        .filter(|conf| {
+            conf.name
+                .as_ref()
+                .is_none_or(|name| !opts.ignore.contains(name))
+        })
+        .map(|conf| {
+            let named = conf
+                .name
+                .as_ref()
+                .is_some_and(|name| opts.tolerant.contains(name));
+            (conf, named || conf.decimals.is_some())
+        })
+        .collect();
+
+    let mut diff = Diff {
+        fields: compared,
+        tolerance: opts.tolerance,
+        max: opts.max_differences,
+        report: DiffReport::default(),
+    };
+
+    match key {
+        Some(key) => diff.by_key(a, b, key)?,
+        None => diff.by_index(a, b)?,
+    }
+
+    Ok(diff.report)
+}
+
+struct Diff<'a> {
+    /// The fields to compare, and whether each is compared within the tolerance.
+    fields: Vec<(&'a FieldConfig, bool)>,
+    tolerance: Option<f64>,
+    max: usize,
+    report: DiffReport,
+}
+
+impl Diff<'_> {
+    fn by_index<A: Read, B: Read>(&mut self, a: &mut Reader<A>, b: &mut Reader<B>) -> Result<()> {
+        let mut left_done = false;
+        let mut right_done = false;
+
+        loop {
+            let left = if left_done { None } else { a.next_record() };
+            let right = if right_done { None } else { b.next_record() };
+
+            match (left, right) {
+                (Some(left), Some(right)) => {
+                    let (left, right) = (left?, right?);
+                    let index = self.report.left_records;
+                    self.report.left_records += 1;
+                    self.report.right_records += 1;
+                    self.compare_records(index, None, left, right);
+                }
+                (Some(left), None) => {
+                    right_done = true;
+                    let index = self.report.left_records;
+                    self.report.left_records += 1;
+                    self.only_left(index, None, left?);
+                }
+                (None, Some(right)) => {
+                    left_done = true;
+                    let index = self.report.right_records;
+                    self.report.right_records += 1;
+                    self.only_right(index, None, right?);
+                }
+                (None, None) => return Ok(()),
+            }
+        }
+    }
+
+    fn by_key<A: Read, B: Read>(
+        &mut self,
+        a: &mut Reader<A>,
+        b: &mut Reader<B>,
+        key: &FieldConfig,
+    ) -> Result<()> {
+        let mut unmatched: HashMap<Vec<u8>, VecDeque<(u64, Vec<u8>)>> = HashMap::new();
+        while let Some(record) = b.next_record() {
+            let record = record?;
+            let index = self.report.right_records;
+            self.report.right_records += 1;
+            unmatched
+                .entry(trimmed(key, record).to_vec())
+                .or_default()
+                .push_back((index, record.to_vec()));
+        }
+
+        while let Some(record) = a.next_record() {
+            let record = record?;
+            let index = self.report.left_records;
+            self.report.left_records += 1;
+
+            let value = trimmed(key, record);
+            let name = Some(String::from_utf8_lossy(value).into_owned());
+            match unmatched.get_mut(value).and_then(VecDeque::pop_front) {
+                Some((_, right)) => self.compare_records(index, name, record, &right),
+                None => self.only_left(index, name, record),
+            }
+        }
+
+        let mut rest: Vec<(u64, Vec<u8>, Vec<u8>)> = unmatched
+            .into_iter()
+            .flat_map(|(key, records)| {
+                records
+                    .into_iter()
+                    .map(move |(index, record)| (index, key.clone(), record))
+            })
+            .collect();
+        rest.sort_unstable_by_key(|(index, _, _)| *index);
+
+        for (index, key, record) in rest {
+            let key = Some(String::from_utf8_lossy(&key).into_owned());
+            self.only_right(index, key, &record);
+        }
+
+        Ok(())
+    }
+
+    fn compare_records(&mut self, index: u64, key: Option<String>, left: &[u8], right: &[u8]) {
+        self.report.compared += 1;
+        let mut differs = false;
+
+        for &(conf, tolerant) in &self.fields {
+            let (l, r) = (trimmed(conf, left), trimmed(conf, right));
+            if l == r || (tolerant && self.within_tolerance(conf, l, r)) {
+                continue;
+            }
+
+            differs = true;
+            let field = conf.describe();
+            *self
+                .report
+                .field_differences
+                .entry(field.clone())
+                .or_insert(0) += 1;
+
+            let difference = Difference {
+                index,
+                key: key.clone(),
+                field: Some(field),
+                left: Some(String::from_utf8_lossy(l).into_owned()),
+                right: Some(String::from_utf8_lossy(r).into_owned()),
+            };
+            self.report.push(difference, self.max);
+        }
+
+        if differs {
+            self.report.differing_records += 1;
+        }
+    }
+
+    fn within_tolerance(&self, conf: &FieldConfig, left: &[u8], right: &[u8]) -> bool {
+        let tolerance = match self.tolerance {
+            Some(tolerance) => tolerance,
+            None => return false,
+        };
+
+        let parse = |bytes| {
+            let s = std::str::from_utf8(bytes).ok()?;
+            match conf.decimal_separator {
+                DecimalSeparator::Point => s.parse::<f64>().ok(),
+                DecimalSeparator::Comma => s.replace(',', ".").parse::<f64>().ok(),
+                DecimalSeparator::Implied => {
+                    let scale = 10f64.powi(conf.decimals.unwrap_or(0) as i32);
+                    s.parse::<f64>().ok().map(|f| f / scale)
+                }
+            }
+        };
+        match (parse(left), parse(right)) {
+            (Some(l), Some(r)) => (l - r).abs() <= tolerance,
+            _ => false,
+        }
+    }
+
+    fn only_left(&mut self, index: u64, key: Option<String>, record: &[u8]) {
+        self.report.only_left += 1;
+        let difference = Difference {
+            index,
+            key,
+            field: None,
+            left: Some(String::from_utf8_lossy(record).into_owned()),
+            right: None,
+        };
+        self.report.push(difference, self.max);
+    }
+
+    fn only_right(&mut self, index: u64, key: Option<String>, record: &[u8]) {
+        self.report.only_right += 1;
+        let difference = Difference {
+            index,
+            key,
+            field: None,
+            left: None,
+            right: Some(String::from_utf8_lossy(record).into_owned()),
+        };
+        self.report.push(difference, self.max);
+    }
+}
+
+/// The value of a field with its padding trimmed, or empty if the record ends before it.
+fn trimmed<'a>(conf: &FieldConfig, record: &'a [u8]) -> &'a [u8] {
+    conf.slice(record).map_or(&[][..], |bytes| conf.trim(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LineBreak;
+    use std::io::Cursor;
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field(4..10).name("name"),
+            FieldSet::new_field(10..16)
+                .name("amount")
+                .pad_with('0')
+                .justify("right"),
+            FieldSet::new_field(16..20).name("time"),
+        ])
+    }
+
+    fn reader(data: &'static str) -> Reader<Cursor<Vec<u8>>> {
+        Reader::from_string(data)
+            .width(20)
+            .linebreak(LineBreak::Newline)
+    }
+
+    fn diff(left: &'static str, right: &'static str, opts: DiffOptions) -> DiffReport {
+        compare(&mut reader(left), &mut reader(right), &fields(), opts).unwrap()
+    }
+
+    const LEFT: &str = "0001Carl  0000420830\n0002Ann   0000070830\n0003Bob   0012000830";
+
+    #[test]
+    fn one_field_differs() {
+        let right = "0001Carl  0000420915\n0002Ann   0000080915\n0003Bob   0012000915";
+
+        let report = diff(LEFT, right, DiffOptions::new());
+        assert_eq!(report.compared, 3);
+        assert_eq!(report.differing_records, 3);
+        assert_eq!(report.field_differences["time"], 3);
+        assert_eq!(report.field_differences["amount"], 1);
+
+        let report = diff(LEFT, right, DiffOptions::new().ignore("time"));
+        assert_eq!(report.differing_records, 1);
+        assert_eq!(report.field_differences.len(), 1);
+        assert_eq!(
+            report.differences,
+            vec![Difference {
+                index: 1,
+                key: None,
+                field: Some("amount".to_string()),
+                left: Some("7".to_string()),
+                right: Some("8".to_string()),
+            }]
+        );
+        assert!(!report.is_identical());
+
+        let opts = DiffOptions::new().ignore("time").tolerance(1.0);
+        assert!(!diff(LEFT, right, opts).is_identical());
+
+        let opts = DiffOptions::new()
+            .ignore("time")
+            .tolerance(1.0)
+            .tolerant("amount");
+        assert!(diff(LEFT, right, opts).is_identical());
+    }
+
+    #[test]
+    fn tolerance_only_for_decimals_and_named_fields() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..8).name("date"),
+            FieldSet::new_field(8..14)
+                .name("rate")
+                .decimals(2)
+                .decimal_separator(DecimalSeparator::Comma),
+        ]);
+        let compare = |left: &'static str, right: &'static str| {
+            let opts = DiffOptions::new().tolerance(1.0);
+            let mut left = Reader::from_string(left).width(14);
+            let mut right = Reader::from_string(right).width(14);
+            compare(&mut left, &mut right, &fields, opts).unwrap()
+        };
+
+        assert!(compare("20240101  1,50", "20240101  2,25").is_identical());
+
+        let report = compare("20240101  1,50", "20240102  1,50");
+        assert_eq!(report.field_differences["date"], 1);
+    }
+
+    #[test]
+    fn only_padding_differs() {
+        let right = "0001  Carl    420830\n0002Ann        70830\n0003   Bob0012000830";
+
+        let report = diff(LEFT, right, DiffOptions::new());
+        assert!(report.is_identical(), "{:?}", report.differences);
+        assert_eq!((report.left_records, report.right_records), (3, 3));
+    }
+
+    #[test]
+    fn record_count_differs() {
+        let right = "0001Carl  0000420830\n0002Ann   0000070830";
+
+        let report = diff(LEFT, right, DiffOptions::new());
+        assert_eq!((report.left_records, report.right_records), (3, 2));
+        assert_eq!(
+            (report.compared, report.only_left, report.only_right),
+            (2, 1, 0)
+        );
+        assert_eq!(
+            report.differences[0].to_string(),
+            r#"record 2: only in left: "0003Bob   0012000830""#
+        );
+
+        let report = diff(right, LEFT, DiffOptions::new());
+        assert_eq!((report.only_left, report.only_right), (0, 1));
+        assert_eq!(
+            report.differences[0].right.as_deref(),
+            Some("0003Bob   0012000830")
+        );
+    }
+
+    #[test]
+    fn align_by_key() {
+        let right = "0003Bob   0012000830\n0004Dee   0000010830\n0001Carl  0000430830";
+
+        let report = diff(LEFT, right, DiffOptions::new().key("id"));
+        assert_eq!(report.compared, 2);
+        assert_eq!((report.only_left, report.only_right), (1, 1));
+
+        let messages: Vec<String> = report.differences.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                r#"record 0 (key "0001"), field 'amount': "42" != "43""#,
+                r#"record 1 (key "0002"): only in left: "0002Ann   0000070830""#,
+                r#"record 1 (key "0004"): only in right: "0004Dee   0000010830""#,
+            ]
+        );
+
+        // Aligned by index, every record differs.
+        let report = diff(LEFT, right, DiffOptions::new());
+        assert_eq!(report.differing_records, 3);
+    }
+
+    #[test]
+    fn max_differences() {
+        let right = "0001Carl  0000420915\n0002Ann   0000080915\n0003Bob   0012000915";
+
+        let report = diff(LEFT, right, DiffOptions::new().max_differences(2));
+        assert_eq!(report.differences.len(), 2);
+        assert!(report.truncated);
+        assert_eq!(report.field_differences.values().sum::<u64>(), 4);
+    }
+
+    #[test]
+    fn unknown_field() {
+        let err = compare(
+            &mut reader(LEFT),
+            &mut reader(LEFT),
+            &fields(),
+            DiffOptions::new().ignore("updated"),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::UnknownField(ref name) if name == "updated"));
+
+        let err = compare(
+            &mut reader(LEFT),
+            &mut reader(LEFT),
+            &fields(),
+            DiffOptions::new().tolerant("rate"),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "no field named 'rate'");
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{de, error::Error, FixedWidth, Reader, Result};
+use serde::de::DeserializeOwned;
+use std::{io::Read, ops::Range};
+
+type Handler<T> = Box<dyn Fn(&[u8]) -> Result<T> + Send + Sync>;
+
+/// Parses records of several types from the same data, choosing the type of each record by the
+/// value of a discriminator field. This is the usual shape of files made up of header, detail
+/// and trailer records.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, FixedWidth, RecordDispatcher, Reader};
+///
+/// #[derive(Deserialize)]
+/// struct Header {
+///     date: String,
+/// }
+///
+/// impl FixedWidth for Header {
+///     fn fields() -> FieldSet {
+///         FieldSet::new_field(1..9)
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Detail {
+///     name: String,
+///     amount: usize,
+/// }
+///
+/// impl FixedWidth for Detail {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![FieldSet::new_field(1..5), FieldSet::new_field(5..9)])
+///     }
+/// }
+///
+/// enum Record {
+///     Header(Header),
+///     Detail(Detail),
+/// }
+///
+/// let dispatcher = RecordDispatcher::new(0..1)
+///     .register("H", Record::Header)
+///     .register("D", Record::Detail);
+///
+/// let mut reader = Reader::from_string("H20240101Dfoo 0012").width(9);
+/// let records: Vec<Record> = reader.dispatch(&dispatcher).collect::<Result<_, _>>().unwrap();
+///
+/// assert!(matches!(&records[0], Record::Header(h) if h.date == "20240101"));
+/// assert!(matches!(&records[1], Record::Detail(d) if d.amount == 12));
+/// ```
+pub struct RecordDispatcher<T> {
+    range: Range<usize>,
+    handlers: Vec<(Vec<u8>, Handler<T>)>,
+}
+
+/// An iterator of records parsed by a `RecordDispatcher`.
+///
+/// The lifetime 'a denotes the lifetime of the reader, R, and of the dispatcher.
+pub struct DispatchReader<'a, R: 'a, T> {
+    r: &'a mut Reader<R>,
+    dispatcher: &'a RecordDispatcher<T>,
+}
+
+impl<T> RecordDispatcher<T> {
+    /// Creates a new dispatcher that reads the discriminator from the given byte range of each
+    /// record.
+    pub fn new(range: Range<usize>) -> Self {
+        Self {
+            range,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Registers the record type `R` for records whose discriminator equals `tag`. Parsed records
+    /// are converted to the dispatcher's output with `f`, which is typically an enum variant.
+    pub fn register<R, B, F>(self, tag: B, f: F) -> Self
+    where
+        R: FixedWidth + DeserializeOwned,
+        B: AsRef<[u8]>,
+        F: Fn(R) -> T + Send + Sync + 'static,
+    {
+        self.register_fn(tag, move |bytes| de::from_bytes::<R>(bytes).map(&f))
+    }
+
+    /// Registers a function that parses records whose discriminator equals `tag`, for records
+    /// that do not have a `FixedWidth` type.
+    pub fn register_fn<B, F>(mut self, tag: B, f: F) -> Self
+    where
+        B: AsRef<[u8]>,
+        F: Fn(&[u8]) -> Result<T> + Send + Sync + 'static,
+    {
+        self.handlers.push((tag.as_ref().to_vec(), Box::new(f)));
+        self
+    }
+
+    /// Parses the given record with the handler registered for its discriminator. Returns
+    /// `Error::UnknownRecordType` if no handler was registered for it.
+    pub fn dispatch(&self, record: &[u8]) -> Result<T> {
+        let tag = match record.get(self.range.clone()) {
+            Some(tag) => tag,
+            None => {
+                return Err(Error::UnknownRecordType {
+                    tag: Vec::new(),
+                    record: record.to_vec(),
+                })
+            }
+        };
+
+        match self.handlers.iter().find(|(t, _)| t.as_slice() == tag) {
+            Some((_, handler)) => handler(record),
+            None => Err(Error::UnknownRecordType {
+                tag: tag.to_vec(),
+                record: record.to_vec(),
+            }),
+        }
+    }
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Reads each record of the data and parses it with the given dispatcher.
+    pub fn dispatch<'a, T>(
+        &'a mut self,
+        dispatcher: &'a RecordDispatcher<T>,
+    ) -> DispatchReader<'a, R, T> {
+        DispatchReader {
+            r: self,
+            dispatcher,
+        }
+    }
+}
+
+impl<'a, R, T> Iterator for DispatchReader<'a, R, T>
+where
+    R: Read,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dispatcher = self.dispatcher;
+        self.r
+            .next_record()
+            .map(|record| record.and_then(|r| dispatcher.dispatch(r)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{FieldSet, LineBreak};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Header {
+        kind: String,
+        date: String,
+    }
+
+    impl FixedWidth for Header {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..9)])
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Detail {
+        kind: String,
+        name: String,
+        amount: usize,
+    }
+
+    impl FixedWidth for Detail {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..1),
+                FieldSet::new_field(1..5),
+                FieldSet::new_field(5..9),
+            ])
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Record {
+        Header(Header),
+        Detail(Detail),
+        Trailer(usize),
+    }
+
+    fn dispatcher() -> RecordDispatcher<Record> {
+        RecordDispatcher::new(0..1)
+            .register("H", Record::Header)
+            .register("D", Record::Detail)
+            .register_fn("T", |bytes| {
+                crate::from_bytes_with_fields(bytes, FieldSet::new_field(1..9)).map(Record::Trailer)
+            })
+    }
+
+    #[test]
+    fn dispatch_header_detail_trailer() {
+        let s = "H20240101\nDfoo 0012\nDbar 0030\nT00000002";
+        let dispatcher = dispatcher();
+
+        let mut rdr = Reader::from_string(s)
+            .width(9)
+            .linebreak(LineBreak::Newline);
+        let records = rdr
+            .dispatch(&dispatcher)
+            .collect::<Result<Vec<Record>>>()
+            .unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                Record::Header(Header {
+                    kind: "H".to_string(),
+                    date: "20240101".to_string()
+                }),
+                Record::Detail(Detail {
+                    kind: "D".to_string(),
+                    name: "foo".to_string(),
+                    amount: 12
+                }),
+                Record::Detail(Detail {
+                    kind: "D".to_string(),
+                    name: "bar".to_string(),
+                    amount: 30
+                }),
+                Record::Trailer(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn dispatch_unknown_record_type() {
+        let dispatcher = dispatcher();
+
+        match dispatcher.dispatch(b"Xfoo 0012") {
+            Err(Error::UnknownRecordType { tag, record }) => {
+                assert_eq!(tag, b"X");
+                assert_eq!(record, b"Xfoo 0012");
+            }
+            _ => panic!("expected an unknown record type error"),
+        }
+
+        match dispatcher.dispatch(b"") {
+            Err(Error::UnknownRecordType { tag, record }) => {
+                assert!(tag.is_empty());
+                assert!(record.is_empty());
+            }
+            _ => panic!("expected an unknown record type error"),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{de::DeserializeError, ser::SerializeError};
-use std::{error::Error as StdError, fmt, io, string};
+use std::{error::Error as StdError, fmt, io, ops::Range, string};
 
 /// An error produced while parsing fixed width data.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// An IO error occured while reading the data.
     IOError(io::Error),
@@ -12,6 +13,90 @@ pub enum Error {
     DeserializeError(DeserializeError),
     /// An error occurred during serialization.
     SerializeError(SerializeError),
+    /// The configured record width cannot hold the records being read.
+    InvalidWidth {
+        /// The record width that was configured.
+        width: usize,
+        /// The minimum record width required.
+        required: usize,
+    },
+    /// An error occurred while reading the record at the given index. It displays as
+    /// `record N, ` followed by the error that occurred.
+    Record {
+        /// The zero based index of the record in the data.
+        index: u64,
+        /// The error that occurred.
+        source: Box<Error>,
+    },
+    /// No record type was registered for the discriminator of a record.
+    UnknownRecordType {
+        /// The discriminator bytes of the record.
+        tag: Vec<u8>,
+        /// The raw bytes of the record.
+        record: Vec<u8>,
+    },
+    /// A schema of field definitions could not be read.
+    Schema {
+        /// The path to the offending entry, ie `fields[2].width`.
+        path: String,
+        /// What is wrong with the entry.
+        message: String,
+    },
+    /// The data ended part way through a record or its length prefix.
+    IncompleteRecord {
+        /// The number of bytes that were expected.
+        expected: usize,
+        /// The number of bytes that could be read.
+        read: usize,
+    },
+    /// A `FieldSet` was given a setting it cannot hold, such as a name for a `FieldSet::Seq`.
+    InvalidFieldSet(String),
+    /// The bytes after a record were not the configured linebreak.
+    BadLineBreak {
+        /// The zero based index of the record the linebreak follows.
+        record_index: u64,
+        /// The bytes of the configured linebreak.
+        expected: Vec<u8>,
+        /// The bytes that were found instead.
+        found: Vec<u8>,
+    },
+    /// A line read in `LineMode` is not the width it is expected to be. It is held by an
+    /// `Error::Record` naming the index of the record read from the line.
+    LineWidth {
+        /// The width the line was expected to be.
+        expected: usize,
+        /// The width of the line, without its linebreak.
+        actual: usize,
+    },
+    /// A record was found to be corrupt and skipped, along with the bytes up to the next
+    /// linebreak, by a reader set to `resync_on_linebreak`.
+    Resync {
+        /// The zero based index of the corrupt record.
+        record_index: u64,
+        /// The offsets in the data of the bytes that were skipped, including the linebreak.
+        skipped: Range<u64>,
+    },
+    /// The header, detail and trailer records of a file were written or read out of order, or
+    /// one of them is missing.
+    FileStructure(String),
+    /// The record count in a trailer does not match the number of detail records.
+    CountMismatch {
+        /// The count held by the trailer.
+        trailer: u64,
+        /// The number of detail records written or read.
+        details: u64,
+    },
+    /// A total held by a trailer does not match the total of the detail records.
+    TrailerMismatch {
+        /// The name, or range, of the trailer field holding the total.
+        field: String,
+        /// The total held by the trailer.
+        expected: String,
+        /// The total of the detail records.
+        actual: String,
+    },
+    /// A field was looked up by a name that none of the fields have.
+    UnknownField(String),
 }
 
 impl fmt::Display for Error {
@@ -21,6 +106,71 @@ impl fmt::Display for Error {
             Error::FormatError(ref e) => write!(f, "{}", e),
             Error::DeserializeError(ref e) => write!(f, "{}", e),
             Error::SerializeError(ref e) => write!(f, "{}", e),
+            Error::InvalidWidth { width, required } => write!(
+                f,
+                "record width of {} is invalid, at least {} bytes are required",
+                width, required
+            ),
+            Error::Record { index, ref source } => write!(f, "record {}, {}", index, source),
+            Error::UnknownRecordType { ref tag, .. } => {
+                write!(f, "unknown record type {:?}", String::from_utf8_lossy(tag))
+            }
+            Error::Schema {
+                ref path,
+                ref message,
+            } if path.is_empty() => {
+                write!(f, "invalid schema: {}", message)
+            }
+            Error::Schema {
+                ref path,
+                ref message,
+            } => {
+                write!(f, "invalid schema at {}: {}", path, message)
+            }
+            Error::IncompleteRecord { expected, read } => write!(
+                f,
+                "incomplete record, expected {} bytes but only {} could be read",
+                expected, read
+            ),
+            Error::InvalidFieldSet(ref message) => write!(f, "invalid field set: {}", message),
+            Error::BadLineBreak {
+                record_index,
+                ref expected,
+                ref found,
+            } => write!(
+                f,
+                "expected linebreak {:?} after record {}, found {:?}",
+                String::from_utf8_lossy(expected),
+                record_index,
+                String::from_utf8_lossy(found)
+            ),
+            Error::LineWidth { expected, actual } => {
+                write!(f, "line is {} bytes long, expected {}", actual, expected)
+            }
+            Error::Resync {
+                record_index,
+                ref skipped,
+            } => write!(
+                f,
+                "record {} is corrupt, skipped bytes {}..{} up to the next linebreak",
+                record_index, skipped.start, skipped.end
+            ),
+            Error::FileStructure(ref message) => write!(f, "invalid file structure: {}", message),
+            Error::CountMismatch { trailer, details } => write!(
+                f,
+                "trailer count of {} does not match the {} detail records",
+                trailer, details
+            ),
+            Error::TrailerMismatch {
+                ref field,
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "trailer field {} holds {}, but the detail records total {}",
+                field, expected, actual
+            ),
+            Error::UnknownField(ref name) => write!(f, "no field named '{}'", name),
         }
     }
 }
@@ -43,13 +193,157 @@ impl From<SerializeError> for Error {
     }
 }
 
+impl From<ParseJustifyError> for Error {
+    fn from(e: ParseJustifyError) -> Self {
+        Error::InvalidFieldSet(e.to_string())
+    }
+}
+
+// Errors that display the error within them pass on its source rather than the error itself, so
+// that error chains print each message once.
 impl StdError for Error {
-    fn cause(&self) -> Option<&dyn StdError> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
-            Error::IOError(ref e) => Some(e),
-            Error::FormatError(ref e) => Some(e),
-            Error::DeserializeError(ref e) => Some(e),
-            Error::SerializeError(ref e) => Some(e),
+            Error::IOError(ref e) => e.source(),
+            Error::FormatError(ref e) => e.source(),
+            Error::DeserializeError(ref e) => e.source(),
+            Error::SerializeError(ref e) => e.source(),
+            Error::InvalidWidth { .. } => None,
+            Error::Record { ref source, .. } => source.source(),
+            Error::UnknownRecordType { .. } => None,
+            Error::Schema { .. } => None,
+            Error::IncompleteRecord { .. } => None,
+            Error::InvalidFieldSet(_) => None,
+            Error::BadLineBreak { .. } => None,
+            Error::LineWidth { .. } => None,
+            Error::Resync { .. } => None,
+            Error::FileStructure(_) => None,
+            Error::CountMismatch { .. } => None,
+            Error::TrailerMismatch { .. } => None,
+            Error::UnknownField(_) => None,
         }
     }
 }
+
+/// A record that could not be read or deserialized, along with where it occurred.
+#[derive(Debug)]
+pub struct RecordError {
+    /// The zero based index of the record in the data, counting skipped records.
+    pub index: u64,
+    /// The raw bytes of the record. Empty if the record could not be read at all.
+    pub bytes: Vec<u8>,
+    /// The error that occurred for this record.
+    pub error: Error,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "record {}: {}", self.index, self.error)
+    }
+}
+
+impl StdError for RecordError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error.source()
+    }
+}
+
+/// A field that lies past the end of the record being split by `FieldSet::split`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitError {
+    /// The name of the field, or its range if it has no name.
+    pub field: String,
+    /// The range of the field. A field that runs to the end of the record has an empty range at
+    /// its start.
+    pub range: Range<usize>,
+    /// The length of the record in bytes.
+    pub record_len: usize,
+}
+
+impl fmt::Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "field {} is past the end of the record, which is {} bytes long",
+            self.field, self.record_len
+        )
+    }
+}
+
+impl StdError for SplitError {}
+
+/// An error parsing a `Justify` from a string that is not `left`, `right` or `center`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseJustifyError {
+    value: String,
+}
+
+impl ParseJustifyError {
+    pub(crate) fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseJustifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "justify must be 'left', 'right' or 'center', got {:?}",
+            self.value
+        )
+    }
+}
+
+impl StdError for ParseJustifyError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn field_error() -> Error {
+        let source = "4x".parse::<u32>().unwrap_err();
+        Error::Record {
+            index: 1042,
+            source: Box::new(Error::from(DeserializeError::Field {
+                name: "amount".to_string(),
+                range: 34..45,
+                source: Box::new(DeserializeError::ParseIntError(source)),
+            })),
+        }
+    }
+
+    #[test]
+    fn record_field_display() {
+        assert_eq!(
+            field_error().to_string(),
+            "record 1042, field 'amount' (34..45): invalid digit found in string"
+        );
+
+        let err = Error::Record {
+            index: 7,
+            source: Box::new(Error::from(DeserializeError::UnexpectedEndOfRecord)),
+        };
+        assert_eq!(
+            err.to_string(),
+            "record 7, byte length of record was less than defined length"
+        );
+    }
+
+    #[test]
+    fn error_source_chain() {
+        let err = field_error();
+        let mut chain = Vec::new();
+        let mut next: Option<&(dyn StdError + 'static)> = Some(&err);
+        while let Some(e) = next {
+            chain.push(e.to_string());
+            next = e.source();
+        }
+
+        assert_eq!(
+            chain,
+            vec!["record 1042, field 'amount' (34..45): invalid digit found in string"]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! Writing strings as JSON string literals, for the `json` and `schema` features.
+
+use std::fmt::Write;
+
+/// Writes the given string to `out` as a quoted JSON string, escaping quotes, backslashes and
+/// control characters.
+pub(crate) fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
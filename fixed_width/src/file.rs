@@ -0,0 +1,1068 @@
+use crate::{
+    de, error::Error, ser, DeserializeError, Deserializer, FieldConfig, FieldSet, FixedWidth,
+    Reader, Result, Writer, WriterStats,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    io::{Read, Write},
+    iter,
+    marker::PhantomData,
+    mem, slice,
+};
+
+/// Writes a file made up of a header record, any number of detail records and a trailer record,
+/// in that order. Each record after the header is preceded by the writer's linebreak.
+///
+/// Writing a detail before the header, or a second header, is an `Error::FileStructure`. The
+/// trailer is built from the writer's stats by `finish`, which consumes the `FileWriter` so that
+/// nothing can follow it. A `FileWriter` dropped without calling `finish` leaves the file without
+/// a trailer.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Serialize;
+/// use fixed_width::{FieldSet, FileWriter, FixedWidth, LineBreak, Writer};
+///
+/// #[derive(Serialize)]
+/// struct Header {
+///     kind: &'static str,
+///     date: &'static str,
+/// }
+///
+/// impl FixedWidth for Header {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..9)])
+///     }
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Detail {
+///     kind: &'static str,
+///     name: &'static str,
+/// }
+///
+/// impl FixedWidth for Detail {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..5)])
+///     }
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Trailer {
+///     kind: &'static str,
+///     count: u64,
+/// }
+///
+/// impl FixedWidth for Trailer {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..1),
+///             FieldSet::new_field(1..4).pad_with('0').justify("right"),
+///         ])
+///     }
+/// }
+///
+/// let wrtr = Writer::from_memory().linebreak(LineBreak::Newline);
+/// let mut file = FileWriter::new(wrtr).trailer_count(|t: &Trailer| t.count);
+///
+/// file.write_header(&Header { kind: "H", date: "20240101" }).unwrap();
+/// file.write_detail(&Detail { kind: "D", name: "foo" }).unwrap();
+/// file.write_detail(&Detail { kind: "D", name: "bar" }).unwrap();
+///
+/// let bytes = file
+///     .finish(|stats| Trailer { kind: "T", count: stats.records })
+///     .unwrap();
+///
+/// assert_eq!(String::from_utf8(bytes).unwrap(), "H20240101\nDfoo \nDbar \nT002");
+/// ```
+pub struct FileWriter<W: Write, H, D, T> {
+    wrtr: Writer<W>,
+    header_written: bool,
+    count: Option<fn(&T) -> u64>,
+    records: PhantomData<fn(&H, &D)>,
+}
+
+impl<W, H, D, T> FileWriter<W, H, D, T>
+where
+    W: Write,
+    H: FixedWidth + Serialize,
+    D: FixedWidth + Serialize,
+    T: FixedWidth + Serialize,
+{
+    /// Creates a new file writer that writes its records to the given writer, using the writer's
+    /// linebreak between them.
+    pub fn new(wrtr: Writer<W>) -> Self {
+        Self {
+            wrtr,
+            header_written: false,
+            count: None,
+            records: PhantomData,
+        }
+    }
+
+    /// Sets how to read the detail record count from the trailer. `finish` then returns
+    /// `Error::CountMismatch`, without writing the trailer, if the count does not match the
+    /// number of details written.
+    pub fn trailer_count(mut self, count: fn(&T) -> u64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Writes the header record. It must be written exactly once, before any details.
+    pub fn write_header(&mut self, header: &H) -> Result<()> {
+        if self.header_written {
+            return Err(Error::FileStructure(
+                "the header has already been written".to_string(),
+            ));
+        }
+
+        self.header_written = true;
+        ser::to_writer(&mut self.wrtr, header).map_err(|e| Error::Record {
+            index: 0,
+            source: Box::new(e),
+        })
+    }
+
+    /// Writes a detail record. Details are counted in the writer's stats, unlike the header and
+    /// trailer. An error serializing the detail is returned as an `Error::Record` with the
+    /// record's index in the file, counting the header, and nothing is written for it, so the
+    /// file can still be finished.
+    pub fn write_detail(&mut self, detail: &D) -> Result<()> {
+        if !self.header_written {
+            return Err(Error::FileStructure(
+                "the header must be written before any details".to_string(),
+            ));
+        }
+
+        let index = self.wrtr.stats().records + 1;
+        self.wrtr
+            .write_counted(detail, true)
+            .map_err(|e| Error::Record {
+                index,
+                source: Box::new(e),
+            })
+    }
+
+    /// The number of details and bytes written so far.
+    pub fn stats(&self) -> WriterStats {
+        self.wrtr.stats()
+    }
+
+    /// Writes the trailer record built from the writer's stats, then flushes and returns the
+    /// underlying writer.
+    pub fn finish<F>(mut self, trailer: F) -> Result<W>
+    where
+        F: FnOnce(&WriterStats) -> T,
+    {
+        if !self.header_written {
+            return Err(Error::FileStructure(
+                "the header must be written before the trailer".to_string(),
+            ));
+        }
+
+        let stats = self.wrtr.stats();
+        let trailer = trailer(&stats);
+        check_count(self.count, &trailer, stats.records)?;
+
+        self.wrtr
+            .write_trailer(|_| trailer)
+            .map_err(|e| Error::Record {
+                index: stats.records + 1,
+                source: Box::new(e),
+            })?;
+        self.wrtr.into_inner()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadState {
+    /// The header has not been read.
+    Header,
+    /// Reading details. `next` holds the record after the last detail, if it has been read.
+    Details { primed: bool },
+    /// The details have all been read and `next` holds the trailer.
+    Trailer,
+    /// The trailer has been read.
+    Done,
+}
+
+/// Reads a file made up of a header record, any number of detail records and a trailer record,
+/// in that order. The last record of the data is the trailer, so no discriminator is needed to
+/// tell the details from it. Records are split by the given `Reader`, so record types of
+/// different widths need `Reader::dynamic_width`.
+///
+/// A missing header or trailer, or reading the records out of order, is an
+/// `Error::FileStructure`.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, FileReader, FixedWidth, LineBreak, Reader};
+///
+/// #[derive(Deserialize)]
+/// struct Header {
+///     date: String,
+/// }
+///
+/// impl FixedWidth for Header {
+///     fn fields() -> FieldSet {
+///         FieldSet::new_field(1..9)
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Detail {
+///     name: String,
+/// }
+///
+/// impl FixedWidth for Detail {
+///     fn fields() -> FieldSet {
+///         FieldSet::new_field(1..5)
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Trailer {
+///     count: u64,
+/// }
+///
+/// impl FixedWidth for Trailer {
+///     fn fields() -> FieldSet {
+///         FieldSet::new_field(1..4)
+///     }
+/// }
+///
+/// let rdr = Reader::from_string("H20240101\nDfoo     \nDbar     \nT002     ")
+///     .width(9)
+///     .linebreak(LineBreak::Newline);
+/// let mut file = FileReader::new(rdr).trailer_count(|t: &Trailer| t.count);
+///
+/// let header: Header = file.read_header().unwrap();
+/// let details: Vec<Detail> = file.details().collect::<Result<_, _>>().unwrap();
+/// let trailer: Trailer = file.read_trailer().unwrap();
+///
+/// assert_eq!(header.date, "20240101");
+/// assert_eq!(details[1].name, "bar");
+/// assert_eq!(trailer.count, 2);
+/// ```
+pub struct FileReader<R, H, D, T> {
+    rdr: Reader<R>,
+    state: ReadState,
+    current: Vec<u8>,
+    next: Vec<u8>,
+    details: u64,
+    count: Option<fn(&T) -> u64>,
+    records: PhantomData<fn() -> (H, D)>,
+}
+
+/// An iterator of the detail records of a `FileReader`.
+///
+/// The lifetime 'a denotes the lifetime of the file reader.
+pub struct FileDetails<'a, R: 'a, H: 'a, D: 'a, T: 'a> {
+    file: &'a mut FileReader<R, H, D, T>,
+}
+
+impl<R, H, D, T> FileReader<R, H, D, T>
+where
+    R: Read,
+    H: FixedWidth + DeserializeOwned,
+    D: FixedWidth + DeserializeOwned,
+    T: FixedWidth + DeserializeOwned,
+{
+    /// Creates a new file reader that reads its records from the given reader.
+    pub fn new(rdr: Reader<R>) -> Self {
+        Self {
+            rdr,
+            state: ReadState::Header,
+            current: Vec::new(),
+            next: Vec::new(),
+            details: 0,
+            count: None,
+            records: PhantomData,
+        }
+    }
+
+    /// Sets how to read the detail record count from the trailer. `read_trailer` then returns
+    /// `Error::CountMismatch` if the count does not match the number of details read.
+    pub fn trailer_count(mut self, count: fn(&T) -> u64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Reads the header record. It must be read exactly once, before any details.
+    pub fn read_header(&mut self) -> Result<H> {
+        if self.state != ReadState::Header {
+            return Err(Error::FileStructure(
+                "the header has already been read".to_string(),
+            ));
+        }
+
+        if !self.rdr.read_record_into(&mut self.current)? {
+            return Err(Error::FileStructure("missing header record".to_string()));
+        }
+
+        self.state = ReadState::Details { primed: false };
+        de::from_bytes(&self.current).map_err(|e| Error::Record {
+            index: 0,
+            source: Box::new(e),
+        })
+    }
+
+    /// Returns an iterator of the detail records. An error deserializing a detail is returned as
+    /// an `Error::Record` with the record's index in the file, counting the header.
+    pub fn details(&mut self) -> FileDetails<'_, R, H, D, T> {
+        FileDetails { file: self }
+    }
+
+    /// The number of detail records read so far.
+    pub fn details_read(&self) -> u64 {
+        self.details
+    }
+
+    /// Reads the trailer record, skipping any details that have not been read. Skipped details
+    /// are still counted when checking the trailer's count.
+    pub fn read_trailer(&mut self) -> Result<T> {
+        while self.advance()? {}
+
+        if self.state != ReadState::Trailer {
+            return Err(Error::FileStructure(
+                "the trailer has already been read".to_string(),
+            ));
+        }
+
+        self.state = ReadState::Done;
+        let trailer = de::from_bytes(&self.next).map_err(|e| Error::Record {
+            index: self.details + 1,
+            source: Box::new(e),
+        })?;
+        check_count(self.count, &trailer, self.details)?;
+        Ok(trailer)
+    }
+
+    /// Moves the next detail into `current`. Returns `false` once only the trailer is left.
+    fn advance(&mut self) -> Result<bool> {
+        let primed = match self.state {
+            ReadState::Header => {
+                return Err(Error::FileStructure(
+                    "the header must be read first".to_string(),
+                ))
+            }
+            ReadState::Details { primed } => primed,
+            ReadState::Trailer | ReadState::Done => return Ok(false),
+        };
+
+        if !primed {
+            if !self.rdr.read_record_into(&mut self.next)? {
+                self.state = ReadState::Done;
+                return Err(Error::FileStructure("missing trailer record".to_string()));
+            }
+            self.state = ReadState::Details { primed: true };
+        }
+
+        if self.rdr.read_record_into(&mut self.current)? {
+            // The record read ahead is a detail, since another record follows it.
+            mem::swap(&mut self.current, &mut self.next);
+            self.details += 1;
+            Ok(true)
+        } else {
+            self.state = ReadState::Trailer;
+            Ok(false)
+        }
+    }
+}
+
+impl<'a, R, H, D, T> Iterator for FileDetails<'a, R, H, D, T>
+where
+    R: Read,
+    H: FixedWidth + DeserializeOwned,
+    D: FixedWidth + DeserializeOwned,
+    T: FixedWidth + DeserializeOwned,
+{
+    type Item = Result<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.file.advance() {
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+            Ok(true) => Some(
+                de::from_bytes(&self.file.current).map_err(|e| Error::Record {
+                    index: self.file.details,
+                    source: Box::new(e),
+                }),
+            ),
+        }
+    }
+}
+
+/// Describes the totals held by a trailer record, for checking them against the detail records
+/// with `verify_trailer`.
+#[derive(Debug, Clone)]
+pub struct TrailerSpec {
+    layout: FieldSet,
+    count_field: String,
+    sum: Option<TrailerSum>,
+    header: bool,
+}
+
+#[derive(Debug, Clone)]
+struct TrailerSum {
+    detail: FieldSet,
+    trailer_field: String,
+    scale: u32,
+}
+
+/// The totals of the detail records checked by `verify_trailer`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrailerStats {
+    /// The number of detail records.
+    pub details: u64,
+    /// The total of the summed detail field in units of 10^-scale, ie cents for a scale of 2.
+    /// `None` if no sum was checked.
+    pub sum: Option<i128>,
+}
+
+impl TrailerSpec {
+    /// Creates a spec for trailers with the given layout, whose field named `count_field` holds
+    /// the number of detail records.
+    pub fn new<T: Into<String>>(layout: FieldSet, count_field: T) -> Self {
+        Self {
+            layout,
+            count_field: count_field.into(),
+            sum: None,
+            header: false,
+        }
+    }
+
+    /// Also checks that the trailer field named `trailer_field` holds the total of the single
+    /// field `detail` of every detail record. Both are parsed as decimal numbers with at most
+    /// `scale` decimal places, so a hash total of implied decimal amounts uses a scale of 0.
+    pub fn sum<T: Into<String>>(mut self, detail: FieldSet, trailer_field: T, scale: u32) -> Self {
+        self.sum = Some(TrailerSum {
+            detail,
+            trailer_field: trailer_field.into(),
+            scale,
+        });
+        self
+    }
+
+    /// Sets whether the first record of the data is a header, which is not counted as a detail.
+    /// Defaults to `false`.
+    pub fn header(mut self, val: bool) -> Self {
+        self.header = val;
+        self
+    }
+
+    fn trailer_field(&self, name: &str) -> Result<FieldConfig> {
+        self.layout
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownField(name.to_string()))
+    }
+}
+
+/// Reads all of the records of the data, checking the totals in the last record, the trailer,
+/// against the detail records before it. The data is streamed one record at a time, so a file of
+/// any size can be checked before any of its records are processed.
+///
+/// A trailer total that does not match is returned as `Error::TrailerMismatch`. An error reading
+/// a total is returned as an `Error::Record` with the record's index, and a trailer field name
+/// that the trailer layout does not have as `Error::UnknownField`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{verify_trailer, Error, FieldSet, LineBreak, Reader, TrailerSpec};
+///
+/// let layout = FieldSet::Seq(vec![
+///     FieldSet::new_field(1..4).name("count"),
+///     FieldSet::new_field(4..10).name("total"),
+/// ]);
+/// let spec = TrailerSpec::new(layout, "count").sum(FieldSet::new_field(4..10), "total", 2);
+///
+/// let data = "Dfoo 12.50\nDbar  0.25\nT002 12.75";
+/// let mut reader = Reader::from_string(data).width(10).linebreak(LineBreak::Newline);
+/// let stats = verify_trailer(&mut reader, &spec).unwrap();
+///
+/// assert_eq!(stats.details, 2);
+/// assert_eq!(stats.sum, Some(1275));
+///
+/// let data = "Dfoo 12.50\nDbar  0.25\nT002 12.50";
+/// let mut reader = Reader::from_string(data).width(10).linebreak(LineBreak::Newline);
+/// let err = verify_trailer(&mut reader, &spec).unwrap_err();
+///
+/// assert!(matches!(err, Error::TrailerMismatch { .. }));
+/// assert_eq!(
+///     err.to_string(),
+///     "trailer field total holds 12.50, but the detail records total 12.75"
+/// );
+/// ```
+pub fn verify_trailer<R: Read>(reader: &mut Reader<R>, spec: &TrailerSpec) -> Result<TrailerStats> {
+    let count_field = spec.trailer_field(&spec.count_field)?;
+    let sum = match spec.sum {
+        Some(ref sum) => {
+            let detail = sum.detail.clone().flatten();
+            if detail.len() != 1 {
+                return Err(Error::InvalidFieldSet(
+                    "the summed detail field must be a single field".to_string(),
+                ));
+            }
+            Some((detail, spec.trailer_field(&sum.trailer_field)?, sum.scale))
+        }
+        None => None,
+    };
+
+    let mut current = Vec::new();
+    let mut next = Vec::new();
+    if spec.header && !reader.read_record_into(&mut current)? {
+        return Err(Error::FileStructure("missing header record".to_string()));
+    }
+    if !reader.read_record_into(&mut next)? {
+        return Err(Error::FileStructure("missing trailer record".to_string()));
+    }
+
+    let mut index = spec.header as u64;
+    let mut stats = TrailerStats {
+        details: 0,
+        sum: sum.as_ref().map(|_| 0),
+    };
+
+    while reader.read_record_into(&mut current)? {
+        // The record read ahead is a detail, since another record follows it.
+        if let Some((ref detail, _, scale)) = sum {
+            let amount = read_total(&next, &detail[0], scale)
+                .and_then(|amount| {
+                    stats
+                        .sum
+                        .and_then(|total| total.checked_add(amount))
+                        .ok_or_else(|| {
+                            total_error(&detail[0], "the total is too large".to_string())
+                        })
+                })
+                .map_err(|e| Error::Record {
+                    index,
+                    source: Box::new(e),
+                })?;
+            stats.sum = Some(amount);
+        }
+
+        stats.details += 1;
+        index += 1;
+        mem::swap(&mut current, &mut next);
+    }
+
+    let trailer_error = |e| Error::Record {
+        index,
+        source: Box::new(e),
+    };
+    let mut de = Deserializer::with_flattened_ref(&next, slice::from_ref(&count_field));
+    let count = u64::deserialize(&mut de).map_err(|e| trailer_error(Error::from(e)))?;
+    if count != stats.details {
+        return Err(Error::TrailerMismatch {
+            field: count_field.describe(),
+            expected: count.to_string(),
+            actual: stats.details.to_string(),
+        });
+    }
+
+    if let Some((_, ref field, scale)) = sum {
+        let total = read_total(&next, field, scale).map_err(trailer_error)?;
+        let actual = stats.sum.unwrap_or_default();
+        if total != actual {
+            return Err(Error::TrailerMismatch {
+                field: field.describe(),
+                expected: format_decimal(total, scale),
+                actual: format_decimal(actual, scale),
+            });
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Reads a field as a decimal number in units of 10^-scale.
+fn read_total(record: &[u8], field: &FieldConfig, scale: u32) -> Result<i128> {
+    let mut de = Deserializer::with_flattened_ref(record, slice::from_ref(field));
+    let value = String::deserialize(&mut de)?;
+
+    parse_decimal(&value, scale).ok_or_else(|| {
+        total_error(
+            field,
+            format!(
+                "{:?} is not a decimal number with at most {} decimal places",
+                value, scale
+            ),
+        )
+    })
+}
+
+fn total_error(field: &FieldConfig, message: String) -> Error {
+    Error::from(DeserializeError::Field {
+        name: field.describe(),
+        range: field.range(),
+        source: Box::new(DeserializeError::Message(message)),
+    })
+}
+
+fn parse_decimal(s: &str, scale: u32) -> Option<i128> {
+    let s = s.trim();
+    let (negative, digits) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let (int, frac) = match digits.find('.') {
+        Some(i) => (&digits[..i], &digits[i + 1..]),
+        None => (digits, ""),
+    };
+
+    let scale = scale as usize;
+    let valid = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (int.is_empty() && frac.is_empty()) || !valid(int) || !valid(frac) {
+        return None;
+    }
+    if frac.len() > scale && frac.bytes().skip(scale).any(|b| b != b'0') {
+        return None;
+    }
+
+    let frac = frac.bytes().chain(iter::repeat(b'0')).take(scale);
+    let mut total: i128 = 0;
+    for b in int.bytes().chain(frac) {
+        total = total.checked_mul(10)?.checked_add(i128::from(b - b'0'))?;
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+fn format_decimal(value: i128, scale: u32) -> String {
+    let scale = scale as usize;
+    if scale == 0 {
+        return value.to_string();
+    }
+
+    let digits = format!("{:0>width$}", value.unsigned_abs(), width = scale + 1);
+    let (int, frac) = digits.split_at(digits.len() - scale);
+    let sign = if value < 0 { "-" } else { "" };
+    format!("{}{}.{}", sign, int, frac)
+}
+
+fn check_count<T>(count: Option<fn(&T) -> u64>, trailer: &T, details: u64) -> Result<()> {
+    match count.map(|count| count(trailer)) {
+        Some(count) if count != details => Err(Error::CountMismatch {
+            trailer: count,
+            details,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{FieldSet, LineBreak};
+    use serde_derive::{Deserialize, Serialize};
+    use std::io;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Header {
+        kind: String,
+        date: String,
+    }
+
+    impl FixedWidth for Header {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..9)])
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Detail {
+        kind: char,
+        amount: u32,
+    }
+
+    impl FixedWidth for Detail {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..1),
+                FieldSet::new_field(1..5).pad_with('0').justify("right"),
+            ])
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Trailer {
+        kind: String,
+        count: u64,
+        bytes: u64,
+    }
+
+    impl FixedWidth for Trailer {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..1),
+                FieldSet::new_field(1..4).pad_with('0').justify("right"),
+                FieldSet::new_field(4..7).pad_with('0').justify("right"),
+            ])
+        }
+    }
+
+    type TestWriter = FileWriter<Vec<u8>, Header, Detail, Trailer>;
+    type TestReader = FileReader<io::Cursor<Vec<u8>>, Header, Detail, Trailer>;
+
+    fn header() -> Header {
+        Header {
+            kind: "H".to_string(),
+            date: "20240101".to_string(),
+        }
+    }
+
+    fn detail(amount: u32) -> Detail {
+        Detail { kind: 'D', amount }
+    }
+
+    fn trailer(stats: &WriterStats) -> Trailer {
+        Trailer {
+            kind: "T".to_string(),
+            count: stats.records,
+            bytes: stats.data_bytes,
+        }
+    }
+
+    fn file_writer() -> TestWriter {
+        FileWriter::new(Writer::from_memory().linebreak(LineBreak::CRLF))
+            .trailer_count(|t: &Trailer| t.count)
+    }
+
+    fn file_reader(data: &'static str) -> TestReader {
+        let rdr = Reader::from_string(data)
+            .width(9)
+            .linebreak(LineBreak::CRLF);
+        FileReader::new(rdr).trailer_count(|t: &Trailer| t.count)
+    }
+
+    #[test]
+    fn write_file() {
+        let mut file = file_writer();
+        file.write_header(&header()).unwrap();
+        for amount in 1..=3 {
+            file.write_detail(&detail(amount)).unwrap();
+        }
+
+        assert_eq!(file.stats().records, 3);
+
+        let bytes = file.finish(trailer).unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "H20240101\r\nD0001\r\nD0002\r\nD0003\r\nT003015"
+        );
+    }
+
+    #[test]
+    fn write_file_without_details() {
+        let mut file = file_writer();
+        file.write_header(&header()).unwrap();
+
+        let bytes = file.finish(trailer).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), "H20240101\r\nT000000");
+    }
+
+    #[test]
+    fn write_file_out_of_order() {
+        let mut file = file_writer();
+        let err = file.write_detail(&detail(1)).unwrap_err();
+        assert!(matches!(err, Error::FileStructure(_)));
+        assert_eq!(
+            err.to_string(),
+            "invalid file structure: the header must be written before any details"
+        );
+
+        file.write_header(&header()).unwrap();
+        let err = file.write_header(&header()).unwrap_err();
+        assert!(matches!(err, Error::FileStructure(_)));
+
+        let err = file_writer().finish(trailer).unwrap_err();
+        assert!(matches!(err, Error::FileStructure(_)));
+    }
+
+    #[test]
+    fn write_file_count_mismatch() {
+        let mut file = file_writer();
+        file.write_header(&header()).unwrap();
+        file.write_detail(&detail(1)).unwrap();
+
+        let err = file
+            .finish(|stats| Trailer {
+                count: stats.records + 1,
+                ..trailer(stats)
+            })
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::CountMismatch {
+                trailer: 2,
+                details: 1
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "trailer count of 2 does not match the 1 detail records"
+        );
+    }
+
+    #[test]
+    fn write_file_detail_error() {
+        let mut file = file_writer();
+        file.write_header(&header()).unwrap();
+        file.write_detail(&detail(1)).unwrap();
+
+        let wide = Detail {
+            kind: 'é',
+            amount: 2,
+        };
+        let err = file.write_detail(&wide).unwrap_err();
+        assert!(matches!(err, Error::Record { index: 2, .. }));
+        assert_eq!(file.stats().records, 1);
+
+        file.write_detail(&detail(2)).unwrap();
+        let bytes = file.finish(trailer).unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "H20240101\r\nD0001\r\nD0002\r\nT002010"
+        );
+    }
+
+    #[test]
+    fn read_file() {
+        let mut file = file_reader("H20240101\r\nD0001    \r\nD0002    \r\nT002010  ");
+
+        assert_eq!(file.read_header().unwrap(), header());
+
+        let details: Vec<Detail> = file.details().collect::<Result<_>>().unwrap();
+        assert_eq!(details, vec![detail(1), detail(2)]);
+        assert_eq!(file.details_read(), 2);
+
+        let trailer = file.read_trailer().unwrap();
+        assert_eq!(trailer.count, 2);
+        assert_eq!(trailer.bytes, 10);
+    }
+
+    #[test]
+    fn read_file_round_trip() {
+        let mut wrtr = file_writer();
+        wrtr.write_header(&header()).unwrap();
+        wrtr.write_detail(&detail(7)).unwrap();
+        wrtr.write_detail(&detail(42)).unwrap();
+        let bytes = wrtr.finish(trailer).unwrap();
+
+        // Each record type has its own width, chosen by its first byte.
+        let rdr = Reader::from_bytes(bytes)
+            .linebreak(LineBreak::CRLF)
+            .dynamic_width(1, |tag| match tag {
+                b"H" => Ok(9),
+                b"D" => Ok(5),
+                _ => Ok(7),
+            });
+        let mut file = FileReader::new(rdr).trailer_count(|t: &Trailer| t.count);
+
+        let header: Header = file.read_header().unwrap();
+        let details: Vec<Detail> = file.details().collect::<Result<_>>().unwrap();
+        let trailer = file.read_trailer().unwrap();
+
+        assert_eq!(header.date, "20240101");
+        assert_eq!(details, vec![detail(7), detail(42)]);
+        assert_eq!(trailer.bytes, 10);
+    }
+
+    #[test]
+    fn read_file_skips_unread_details() {
+        let mut file = file_reader("H20240101\r\nD0001    \r\nD0002    \r\nT002010  ");
+        file.read_header().unwrap();
+
+        assert_eq!(file.details().next().unwrap().unwrap(), detail(1));
+        assert_eq!(file.read_trailer().unwrap().count, 2);
+        assert_eq!(file.details_read(), 2);
+
+        let err = file.read_trailer().unwrap_err();
+        assert!(matches!(err, Error::FileStructure(_)));
+        assert!(file.details().next().is_none());
+    }
+
+    #[test]
+    fn read_file_count_mismatch() {
+        let mut file = file_reader("H20240101\r\nD0001    \r\nT002005  ");
+        file.read_header().unwrap();
+
+        let err = file.read_trailer().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CountMismatch {
+                trailer: 2,
+                details: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn read_file_missing_records() {
+        let mut file = file_reader("");
+        let err = file.read_header().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid file structure: missing header record"
+        );
+
+        let mut file = file_reader("H20240101");
+        file.read_header().unwrap();
+        let err = file.details().next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid file structure: missing trailer record"
+        );
+        assert!(file.details().next().is_none());
+
+        let mut file = file_reader("H20240101\r\nT000000  ");
+        assert!(matches!(
+            file.details().next(),
+            Some(Err(Error::FileStructure(_)))
+        ));
+        file.read_header().unwrap();
+        assert!(file.details().next().is_none());
+        assert_eq!(file.read_trailer().unwrap().count, 0);
+    }
+
+    #[test]
+    fn read_file_detail_error() {
+        let mut file = file_reader("H20240101\r\nD0001    \r\nDabcd    \r\nT002010  ");
+        file.read_header().unwrap();
+
+        let details: Vec<Result<Detail>> = file.details().collect();
+        assert!(details[0].is_ok());
+        assert!(matches!(details[1], Err(Error::Record { index: 2, .. })));
+        assert_eq!(file.read_trailer().unwrap().count, 2);
+    }
+
+    fn trailer_spec() -> TrailerSpec {
+        let layout = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "T"),
+            FieldSet::new_field(1..4).name("count"),
+            FieldSet::new_field(4..9).name("total"),
+        ]);
+        TrailerSpec::new(layout, "count").sum(FieldSet::new_field(1..5).name("amount"), "total", 0)
+    }
+
+    fn verify(data: &'static str, spec: &TrailerSpec) -> Result<TrailerStats> {
+        let mut rdr = Reader::from_string(data)
+            .width(9)
+            .linebreak(LineBreak::Newline);
+        verify_trailer(&mut rdr, spec)
+    }
+
+    #[test]
+    fn verify_trailer_totals() {
+        let stats = verify(
+            "D0001    \nD0020    \nD0300    \nT00300321",
+            &trailer_spec(),
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            TrailerStats {
+                details: 3,
+                sum: Some(321),
+            }
+        );
+
+        let spec = trailer_spec().header(true);
+        let stats = verify("H20240101\nD0001    \nT00100001", &spec).unwrap();
+        assert_eq!(stats.details, 1);
+
+        let spec = TrailerSpec::new(FieldSet::new_field(1..4).name("count"), "count");
+        let stats = verify("T000     ", &spec).unwrap();
+        assert_eq!(
+            stats,
+            TrailerStats {
+                details: 0,
+                sum: None,
+            }
+        );
+    }
+
+    #[test]
+    fn verify_trailer_mismatch() {
+        let err = verify("D0001    \nD0020    \nT00300021", &trailer_spec()).unwrap_err();
+        match err {
+            Error::TrailerMismatch {
+                ref field,
+                ref expected,
+                ref actual,
+            } => {
+                assert_eq!(field, "count");
+                assert_eq!(expected, "3");
+                assert_eq!(actual, "2");
+            }
+            ref other => panic!("expected a trailer mismatch, got {:?}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "trailer field count holds 3, but the detail records total 2"
+        );
+
+        let err = verify("D0001    \nD0020    \nT00200022", &trailer_spec()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "trailer field total holds 22, but the detail records total 21"
+        );
+
+        // Without a header, the header is counted as a detail.
+        let err = verify("H20240101\nD0001    \nT00100001", &trailer_spec()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "trailer field count holds 1, but the detail records total 2"
+        );
+    }
+
+    #[test]
+    fn verify_trailer_errors() {
+        let err = verify("D0001    \nD00x0    \nT00200021", &trailer_spec()).unwrap_err();
+        match err {
+            Error::Record {
+                index: 1,
+                ref source,
+            } => assert_eq!(
+                source.to_string(),
+                "field 'amount' (1..5): \"00x0\" is not a decimal number with at most 0 decimal places"
+            ),
+            ref other => panic!("expected a record error, got {:?}", other),
+        }
+
+        let err = verify("", &trailer_spec()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid file structure: missing trailer record"
+        );
+
+        let spec = TrailerSpec::new(FieldSet::new_field(1..4).name("count"), "records");
+        let err = verify("T000     ", &spec).unwrap_err();
+        assert!(matches!(err, Error::UnknownField(ref name) if name == "records"));
+    }
+
+    #[test]
+    fn decimals() {
+        assert_eq!(parse_decimal("12.34", 2), Some(1234));
+        assert_eq!(parse_decimal(" -0.5", 2), Some(-50));
+        assert_eq!(parse_decimal("+7", 2), Some(700));
+        assert_eq!(parse_decimal("1.230", 2), Some(123));
+        assert_eq!(parse_decimal(".5", 1), Some(5));
+        assert_eq!(parse_decimal("1.234", 2), None);
+        assert_eq!(parse_decimal("1,000", 0), None);
+        assert_eq!(parse_decimal("", 0), None);
+        assert_eq!(parse_decimal("-", 0), None);
+
+        assert_eq!(format_decimal(1234, 2), "12.34");
+        assert_eq!(format_decimal(-5, 2), "-0.05");
+        assert_eq!(format_decimal(42, 0), "42");
+    }
+}
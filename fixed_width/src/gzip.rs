@@ -0,0 +1,582 @@
+//! A small gzip decoder, so compressed files can be read without another dependency.
+
+use crate::{Reader, Result};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    mem,
+    path::Path,
+};
+
+/// How far back a match may reach, and so how much output is kept.
+const WINDOW: usize = 32 * 1024;
+
+/// How much output is decoded at a time.
+const CHUNK: usize = 32 * 1024;
+
+/// The longest Huffman code in deflate data.
+const MAX_BITS: usize = 15;
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// The order the lengths of the code length codes are given in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+fn crc32(crc: u32, bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!crc, |c, &b| {
+        CRC_TABLE[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8)
+    })
+}
+
+/// Decompresses gzip data as it is read, so a `Reader` can read records from a compressed file
+/// without decompressing it up front. Several gzip members one after another, as `cat` makes of
+/// two gzip files, are read as one stream.
+///
+/// A decompressing source cannot seek, so `Reader::skip_records` reads and discards the records
+/// it skips. Everything else behaves as it does for uncompressed data.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{GzDecoder, LineBreak, Reader};
+///
+/// // "0001\n0002\n0003", gzipped with no compression.
+/// let data: &[u8] = &[
+///     0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 3, 1, 14, 0, 0xf1, 0xff, b'0', b'0', b'0', b'1', b'\n',
+///     b'0', b'0', b'0', b'2', b'\n', b'0', b'0', b'0', b'3', 0x63, 0x20, 0xb0, 0x23, 14, 0, 0, 0,
+/// ];
+///
+/// let mut reader = Reader::from_reader(GzDecoder::new(data))
+///     .width(4)
+///     .linebreak(LineBreak::Newline);
+///
+/// let records: Vec<String> = reader.string_reader().map(Result::unwrap).collect();
+/// assert_eq!(records, vec!["0001", "0002", "0003"]);
+/// ```
+pub struct GzDecoder<R> {
+    rdr: BufReader<R>,
+    state: State,
+    /// Whether the block being decoded is the last of its member.
+    last_block: bool,
+    bit_buf: u32,
+    bit_count: u32,
+    /// Decoded output, holding at least the last `WINDOW` bytes for matches to copy from.
+    out: Vec<u8>,
+    /// How much of `out` has been read.
+    pos: usize,
+    /// How much of `out` has been added to `crc`.
+    checked: usize,
+    crc: u32,
+    /// The length of the member's output, modulo 2^32, as the trailer gives it.
+    size: u32,
+    members: usize,
+}
+
+enum State {
+    Header,
+    Block,
+    Stored(usize),
+    Codes(Box<(Huffman, Huffman)>),
+    Trailer,
+    Done,
+}
+
+impl<R: Read> GzDecoder<R> {
+    /// Creates a decoder of the gzip data read from `rdr`.
+    pub fn new(rdr: R) -> Self {
+        GzDecoder {
+            rdr: BufReader::new(rdr),
+            state: State::Header,
+            last_block: false,
+            bit_buf: 0,
+            bit_count: 0,
+            out: Vec::new(),
+            pos: 0,
+            checked: 0,
+            crc: 0,
+            size: 0,
+            members: 0,
+        }
+    }
+
+    /// Gets a reference to the underlying reader of compressed data.
+    pub fn get_ref(&self) -> &R {
+        self.rdr.get_ref()
+    }
+
+    /// Decodes up to another `CHUNK` of output.
+    fn inflate(&mut self) -> io::Result<()> {
+        // Drop output that has been read and is too far back to be matched.
+        let discard = self.pos.min(self.out.len().saturating_sub(WINDOW));
+        if discard >= WINDOW {
+            self.out.drain(..discard);
+            self.pos -= discard;
+            self.checked -= discard;
+        }
+
+        let target = self.out.len() + CHUNK;
+        while self.out.len() < target {
+            match mem::replace(&mut self.state, State::Done) {
+                State::Header => {
+                    if self.members > 0 && self.rdr.fill_buf()?.is_empty() {
+                        break;
+                    }
+                    self.read_header()?;
+                    self.state = State::Block;
+                }
+                State::Block => self.state = self.read_block_header()?,
+                State::Stored(left) => self.state = self.copy_stored(left, target)?,
+                State::Codes(codes) => {
+                    let (lit, dist) = &*codes;
+                    self.state = match self.decode_codes(lit, dist, target)? {
+                        true => self.end_block(),
+                        false => State::Codes(codes),
+                    };
+                }
+                State::Trailer => {
+                    self.read_trailer()?;
+                    self.state = State::Header;
+                }
+                State::Done => break,
+            }
+        }
+
+        self.crc = crc32(self.crc, &self.out[self.checked..]);
+        self.checked = self.out.len();
+        Ok(())
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        let mut header = [0; 10];
+        self.read_bytes(&mut header)?;
+        if header[..3] != [0x1f, 0x8b, 8] {
+            return Err(invalid("not gzip data"));
+        }
+
+        let flags = header[3];
+        if flags & 0xe0 != 0 {
+            return Err(invalid("unknown gzip header flags"));
+        }
+        if flags & 0x04 != 0 {
+            let mut len = [0; 2];
+            self.read_bytes(&mut len)?;
+            for _ in 0..u16::from_le_bytes(len) {
+                self.byte()?;
+            }
+        }
+        // The file name and comment are ended by a zero byte.
+        for flag in [0x08, 0x10].iter() {
+            if flags & flag != 0 {
+                while self.byte()? != 0 {}
+            }
+        }
+        if flags & 0x02 != 0 {
+            self.read_bytes(&mut [0; 2])?;
+        }
+        Ok(())
+    }
+
+    fn read_block_header(&mut self) -> io::Result<State> {
+        self.last_block = self.bits(1)? == 1;
+        match self.bits(2)? {
+            0 => {
+                // Stored data starts at the next byte.
+                self.bit_buf = 0;
+                self.bit_count = 0;
+                let mut lens = [0; 4];
+                self.read_bytes(&mut lens)?;
+                let len = u16::from_le_bytes([lens[0], lens[1]]);
+                if len != !u16::from_le_bytes([lens[2], lens[3]]) {
+                    return Err(invalid("invalid stored block length"));
+                }
+                Ok(State::Stored(len as usize))
+            }
+            1 => Ok(State::Codes(Box::new(fixed_codes()))),
+            2 => Ok(State::Codes(Box::new(self.read_dynamic_codes()?))),
+            _ => Err(invalid("invalid block type")),
+        }
+    }
+
+    fn read_dynamic_codes(&mut self) -> io::Result<(Huffman, Huffman)> {
+        let lit_count = self.bits(5)? as usize + 257;
+        let dist_count = self.bits(5)? as usize + 1;
+        let code_count = self.bits(4)? as usize + 4;
+        if lit_count > 286 || dist_count > 30 {
+            return Err(invalid("too many length or distance codes"));
+        }
+
+        let mut lengths = [0; 19];
+        for &i in &CODE_LENGTH_ORDER[..code_count] {
+            lengths[i] = self.bits(3)? as u8;
+        }
+        let code_lengths = Huffman::new(&lengths)?;
+
+        let mut lengths = vec![0; lit_count + dist_count];
+        let mut i = 0;
+        while i < lengths.len() {
+            let (len, repeat) = match self.decode(&code_lengths)? {
+                sym @ 0..=15 => (sym as u8, 1),
+                16 if i == 0 => return Err(invalid("repeated length with no first length")),
+                16 => (lengths[i - 1], 3 + self.bits(2)?),
+                17 => (0, 3 + self.bits(3)?),
+                _ => (0, 11 + self.bits(7)?),
+            };
+            let end = i + repeat as usize;
+            if end > lengths.len() {
+                return Err(invalid("too many code lengths"));
+            }
+            lengths[i..end].iter_mut().for_each(|l| *l = len);
+            i = end;
+        }
+        if lengths[256] == 0 {
+            return Err(invalid("no end of block code"));
+        }
+
+        let (lit, dist) = lengths.split_at(lit_count);
+        Ok((Huffman::new(lit)?, Huffman::new(dist)?))
+    }
+
+    fn copy_stored(&mut self, left: usize, target: usize) -> io::Result<State> {
+        if left == 0 {
+            return Ok(self.end_block());
+        }
+
+        let buf = self.rdr.fill_buf()?;
+        if buf.is_empty() {
+            return Err(ends_early());
+        }
+        let n = left.min(buf.len()).min(target - self.out.len());
+        self.out.extend_from_slice(&buf[..n]);
+        self.rdr.consume(n);
+        self.size = self.size.wrapping_add(n as u32);
+        Ok(State::Stored(left - n))
+    }
+
+    /// Decodes literals and matches until the end of the block, returning `true`, or until the
+    /// output reaches `target`, returning `false`.
+    fn decode_codes(&mut self, lit: &Huffman, dist: &Huffman, target: usize) -> io::Result<bool> {
+        while self.out.len() < target {
+            let sym = self.decode(lit)? as usize;
+            if sym < 256 {
+                self.out.push(sym as u8);
+                self.size = self.size.wrapping_add(1);
+                continue;
+            }
+            if sym == 256 {
+                return Ok(true);
+            }
+
+            let sym = sym - 257;
+            if sym >= LEN_BASE.len() {
+                return Err(invalid("invalid length code"));
+            }
+            let len = LEN_BASE[sym] as usize + self.bits(LEN_EXTRA[sym] as u32)? as usize;
+            let sym = self.decode(dist)? as usize;
+            if sym >= DIST_BASE.len() {
+                return Err(invalid("invalid distance code"));
+            }
+            let distance = DIST_BASE[sym] as usize + self.bits(DIST_EXTRA[sym] as u32)? as usize;
+            if distance > self.out.len() {
+                return Err(invalid("distance too far back"));
+            }
+
+            // A match may overlap the bytes it adds, so it is copied a byte at a time.
+            let start = self.out.len() - distance;
+            for i in start..start + len {
+                let b = self.out[i];
+                self.out.push(b);
+            }
+            self.size = self.size.wrapping_add(len as u32);
+        }
+        Ok(false)
+    }
+
+    fn end_block(&self) -> State {
+        match self.last_block {
+            true => State::Trailer,
+            false => State::Block,
+        }
+    }
+
+    fn read_trailer(&mut self) -> io::Result<()> {
+        // The trailer starts at the next byte.
+        self.bit_buf = 0;
+        self.bit_count = 0;
+        self.crc = crc32(self.crc, &self.out[self.checked..]);
+        self.checked = self.out.len();
+
+        let mut trailer = [0; 8];
+        self.read_bytes(&mut trailer)?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc != self.crc || size != self.size {
+            return Err(invalid("gzip checksum does not match the data"));
+        }
+
+        self.crc = 0;
+        self.size = 0;
+        self.members += 1;
+        Ok(())
+    }
+
+    fn decode(&mut self, huffman: &Huffman) -> io::Result<u16> {
+        // Codes are read a bit at a time, most significant bit first, counting how many codes
+        // of each length come before the one read so far.
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+        for &count in &huffman.counts[1..] {
+            code |= self.bits(1)? as i32;
+            if code - (count as i32) < first {
+                return Ok(huffman.symbols[(index + code - first) as usize]);
+            }
+            index += count as i32;
+            first = (first + count as i32) << 1;
+            code <<= 1;
+        }
+        Err(invalid("invalid Huffman code"))
+    }
+
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.bit_count < n {
+            self.bit_buf |= (self.byte()? as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let bits = self.bit_buf & ((1 << n) - 1);
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Ok(bits)
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        let b = *self.rdr.fill_buf()?.first().ok_or_else(ends_early)?;
+        self.rdr.consume(1);
+        Ok(b)
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.rdr.read_exact(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => ends_early(),
+            _ => e,
+        })
+    }
+}
+
+impl<R: Read> Read for GzDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.out.len() {
+            if let State::Done = self.state {
+                return Ok(0);
+            }
+            // Decoding stops short of a chunk only at the end of the data.
+            self.inflate()?;
+        }
+
+        let n = buf.len().min(self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<R> fmt::Debug for GzDecoder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GzDecoder")
+            .field("members", &self.members)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A canonical Huffman code, given by how many codes there are of each length and the symbols
+/// in the order of their codes.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the length of the code of each symbol, where 0 means the symbol
+    /// has no code.
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let mut counts = [0; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+
+        // Each length doubles the codes there are room for, less those already used.
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(invalid("too many Huffman codes of a length"));
+            }
+        }
+
+        let mut offsets = [0; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (sym, &len) in lengths.iter().enumerate().filter(|(_, &len)| len != 0) {
+            symbols[offsets[len as usize] as usize] = sym as u16;
+            offsets[len as usize] += 1;
+        }
+
+        counts[0] = 0;
+        Ok(Huffman { counts, symbols })
+    }
+}
+
+/// The codes of blocks compressed with the fixed codes of the deflate format.
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8; 288];
+    lengths[144..256].iter_mut().for_each(|l| *l = 9);
+    lengths[256..280].iter_mut().for_each(|l| *l = 7);
+
+    // Every length is valid, so building them cannot fail.
+    let lit = Huffman::new(&lengths).expect("fixed literal codes");
+    let dist = Huffman::new(&[5; 30]).expect("fixed distance codes");
+    (lit, dist)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn ends_early() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "gzip data ends early")
+}
+
+impl Reader<GzDecoder<File>> {
+    /// Creates a new reader of the gzipped file at the given path, decompressing it as it is
+    /// read. Will return an io::Error if there are any issues opening the file. Data that is not
+    /// valid gzip is an `Error::IOError` when it is read.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,no_run
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// let mut reader = Reader::from_gz_file("records.txt.gz")
+    ///     .unwrap()
+    ///     .width(16)
+    ///     .linebreak(LineBreak::Newline);
+    ///
+    /// for record in reader.string_reader() {
+    ///     println!("{}", record.unwrap());
+    /// }
+    /// ```
+    pub fn from_gz_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_reader(GzDecoder::new(File::open(path)?)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Gzips data with stored blocks, which need no compression.
+    fn stored(data: &[u8]) -> Vec<u8> {
+        let mut gz = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+        let mut chunks = data.chunks(0xffff).peekable();
+        if chunks.peek().is_none() {
+            gz.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+        }
+        while let Some(chunk) = chunks.next() {
+            let len = chunk.len() as u16;
+            gz.push(chunks.peek().is_none() as u8);
+            gz.extend_from_slice(&len.to_le_bytes());
+            gz.extend_from_slice(&(!len).to_le_bytes());
+            gz.extend_from_slice(chunk);
+        }
+        gz.extend_from_slice(&crc32(0, data).to_le_bytes());
+        gz.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        gz
+    }
+
+    fn gunzip(gz: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        GzDecoder::new(gz).read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn gzip_stored_blocks() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        assert_eq!(gunzip(&stored(&data)).unwrap(), data);
+        assert_eq!(gunzip(&stored(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn gzip_fixed_codes() {
+        // "abcabcabcabc\n" with a name in the header, as `gzip` writes it.
+        let gz = [
+            0x1f, 0x8b, 8, 8, 0, 0, 0, 0, 0, 3, b'a', b'.', b't', b'x', b't', 0, 0x4b, 0x4c, 0x4a,
+            0x4e, 0x84, 0x21, 0x2e, 0, 12, 0x9c, 0x39, 0x13, 13, 0, 0, 0,
+        ];
+        assert_eq!(gunzip(&gz).unwrap(), b"abcabcabcabc\n");
+    }
+
+    #[test]
+    fn gzip_members() {
+        let mut gz = stored(b"first ");
+        gz.extend(stored(b"second"));
+        assert_eq!(gunzip(&gz).unwrap(), b"first second");
+    }
+
+    #[test]
+    fn gzip_invalid_data() {
+        let err = gunzip(b"plain text").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "not gzip data");
+
+        let mut gz = stored(b"some data");
+        let len = gz.len();
+        gz[len - 8] ^= 1;
+        let err = gunzip(&gz).unwrap_err();
+        assert_eq!(err.to_string(), "gzip checksum does not match the data");
+
+        let gz = stored(b"some data");
+        let err = gunzip(&gz[..gz.len() - 4]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
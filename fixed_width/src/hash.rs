@@ -0,0 +1,401 @@
+//! Stable hashes of records, to find the records that changed between two files.
+//!
+//! `record_digest` hashes a single record, either as raw bytes or as the trimmed values of its
+//! fields, so that records that differ only in padding hash the same. `DigestReader` folds the
+//! digests of every record read into one digest of the whole file.
+//!
+//! Digests are 64 bit FNV-1a hashes, which are the same on every platform and in every version,
+//! so they can be stored and compared with those of files read later. They are not suited to
+//! detecting deliberate tampering.
+
+use crate::{Error, FieldConfig, FieldSet, Reader, Result};
+use std::{hash::Hasher, io::Read};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// A 64 bit FNV-1a hasher, which gives the same hash for the same bytes everywhere.
+#[derive(Debug, Clone, Copy)]
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+}
+
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hashes a record. Without `fields`, the raw bytes of the record are hashed. With `fields`,
+/// the value of each field is hashed with its padding trimmed as it would be when deserialized,
+/// so re-padding a value does not change the digest. Literal fields are left out. With
+/// `selected`, only the fields with the given names are hashed, in the order given, and a name
+/// that no field has is an `Error::UnknownField`. Fields past the end of a short record are
+/// hashed as empty.
+///
+/// The value of each field is hashed along with its length, so moving bytes from the end of one
+/// field to the start of the next changes the digest.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{hash, FieldSet};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6).name("name"),
+///     FieldSet::new_field(6..10).name("amount").pad_with('0').justify("right"),
+///     FieldSet::new_field(10..18).name("updated"),
+/// ]);
+///
+/// let selected = Some(&["name", "amount"][..]);
+/// let before = hash::record_digest(b"Carl  004220240101", Some(&fields), selected).unwrap();
+/// let after = hash::record_digest(b"Carl    4220240102", Some(&fields), selected).unwrap();
+/// assert_eq!(before, after);
+///
+/// let raw_before = hash::record_digest(b"Carl  004220240101", None, None).unwrap();
+/// let raw_after = hash::record_digest(b"Carl    4220240101", None, None).unwrap();
+/// assert_ne!(raw_before, raw_after);
+///
+/// assert!(hash::record_digest(b"Carl", Some(&fields), Some(&["nmae"])).is_err());
+/// ```
+pub fn record_digest(
+    record: &[u8],
+    fields: Option<&FieldSet>,
+    selected: Option<&[&str]>,
+) -> Result<u64> {
+    let mut hasher = Fnv::default();
+    hash_record(record, fields, selected, &mut hasher)?;
+    Ok(hasher.finish())
+}
+
+/// Feeds a record to the given hasher as `record_digest` hashes it, to hash records with another
+/// algorithm. Fails with `Error::UnknownField` for a name in `selected` that no field has, before
+/// anything is hashed.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{hash, FieldSet};
+/// use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+///
+/// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)]);
+///
+/// let mut a = DefaultHasher::new();
+/// hash::hash_record(b"ab  cd  ", Some(&fields), None, &mut a).unwrap();
+/// let mut b = DefaultHasher::new();
+/// hash::hash_record(b"ab    cd", Some(&fields), None, &mut b).unwrap();
+///
+/// assert_eq!(a.finish(), b.finish());
+/// ```
+pub fn hash_record<H: Hasher>(
+    record: &[u8],
+    fields: Option<&FieldSet>,
+    selected: Option<&[&str]>,
+    state: &mut H,
+) -> Result<()> {
+    match fields {
+        Some(fields) => {
+            for conf in hashed_fields(fields, selected)? {
+                hash_field(conf, record, state);
+            }
+        }
+        None => state.write(record),
+    }
+    Ok(())
+}
+
+/// The fields that are hashed, in order: those named in `selected`, or every field that is not
+/// a literal.
+fn hashed_fields<'a>(
+    fields: &'a FieldSet,
+    selected: Option<&[&str]>,
+) -> Result<Vec<&'a FieldConfig>> {
+    match selected {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                fields
+                    .iter_flat()
+                    .find(|conf| conf.name.as_deref() == Some(*name))
+                    .ok_or_else(|| Error::UnknownField(name.to_string()))
+            })
+            .collect(),
+        None => Ok(fields
+            .iter_flat()
+            .filter(|conf| conf.literal.is_none())
+            .collect()),
+    }
+}
+
+/// Hashes the trimmed value of a field, preceded by its length.
+fn hash_field<H: Hasher>(conf: &FieldConfig, record: &[u8], state: &mut H) {
+    let value = conf.slice(record).map_or(&[][..], |bytes| conf.trim(bytes));
+    state.write(&(value.len() as u64).to_le_bytes());
+    state.write(value);
+}
+
+/// A reader that keeps a digest of every record read through it, in order, as a digest of the
+/// whole file. Records are read as they are by the wrapped `Reader`, and hashed as by
+/// `record_digest`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{hash::DigestReader, FieldSet, LineBreak, Reader};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("id"),
+///     FieldSet::new_field(4..8).name("name"),
+/// ]);
+///
+/// let reader = |data| Reader::from_string(data).width(8).linebreak(LineBreak::Newline);
+///
+/// let mut yesterday = DigestReader::new(reader("0001Carl\n0002Ann "))
+///     .fields(fields.clone(), None)
+///     .unwrap();
+/// let mut today = DigestReader::new(reader("0001Carl\n0002 Ann"))
+///     .fields(fields, None)
+///     .unwrap();
+///
+/// assert_eq!(yesterday.by_ref().count(), 2);
+/// assert_eq!(today.by_ref().count(), 2);
+/// assert_eq!(yesterday.digest(), today.digest());
+/// ```
+pub struct DigestReader<R> {
+    rdr: Reader<R>,
+    /// The fields hashed, in order, or `None` to hash the raw bytes of each record.
+    fields: Option<Vec<FieldConfig>>,
+    digest: Fnv,
+    records: u64,
+}
+
+impl<R: Read> DigestReader<R> {
+    /// Wraps a reader, hashing the raw bytes of each record.
+    pub fn new(rdr: Reader<R>) -> Self {
+        Self {
+            rdr,
+            fields: None,
+            digest: Fnv::default(),
+            records: 0,
+        }
+    }
+
+    /// Hashes the trimmed values of the given fields of each record rather than its raw bytes,
+    /// only those named in `selected` if given, as `record_digest` does. As with
+    /// `record_digest`, a name in `selected` that no field has is an `Error::UnknownField`.
+    pub fn fields(mut self, fields: FieldSet, selected: Option<&[&str]>) -> Result<Self> {
+        let hashed = hashed_fields(&fields, selected)?;
+        self.fields = Some(hashed.into_iter().cloned().collect());
+        Ok(self)
+    }
+
+    /// Reads the next record as a byte slice, adding it to the digest.
+    pub fn next_record(&mut self) -> Option<Result<&[u8]>> {
+        let record = match self.rdr.next_record()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let mut hasher = Fnv::default();
+        match self.fields {
+            Some(ref fields) => {
+                for conf in fields {
+                    hash_field(conf, record, &mut hasher);
+                }
+            }
+            None => hasher.write(record),
+        }
+        let digest = hasher.finish();
+        self.digest.write(&digest.to_le_bytes());
+        self.records += 1;
+
+        Some(Ok(record))
+    }
+
+    /// The digest of the records read so far. Two files have the same digest if their records
+    /// have the same digests in the same order.
+    pub fn digest(&self) -> u64 {
+        self.digest.finish()
+    }
+
+    /// The number of records added to the digest.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Gets the wrapped reader back.
+    pub fn into_inner(self) -> Reader<R> {
+        self.rdr
+    }
+}
+
+impl<R: Read> Iterator for DigestReader<R> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().map(|record| record.map(<[u8]>::to_vec))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LineBreak;
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..7).name("name"),
+            FieldSet::new_field(7..13)
+                .name("amount")
+                .pad_with('0')
+                .justify("right"),
+            FieldSet::new_field(13..21).name("date"),
+        ])
+    }
+
+    #[test]
+    fn fnv_known_values() {
+        let digest = |bytes: &[u8]| {
+            let mut hasher = Fnv::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+
+        assert_eq!(digest(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(digest(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(digest(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn repadded_records_hash_the_same() {
+        let fields = fields();
+        let records: [&[u8]; 4] = [
+            b"DCarl  00004220240101",
+            b"D  Carl    4220240101",
+            b"DCarl      4220240101",
+            b"DCarl  00004220240101   ",
+        ];
+
+        let digests: Vec<u64> = records
+            .iter()
+            .map(|r| record_digest(r, Some(&fields), None).unwrap())
+            .collect();
+        assert!(digests.windows(2).all(|w| w[0] == w[1]));
+
+        let raw: Vec<u64> = records
+            .iter()
+            .map(|r| record_digest(r, None, None).unwrap())
+            .collect();
+        assert_ne!(raw[0], raw[1]);
+
+        assert_ne!(
+            record_digest(b"DCarl  00004320240101", Some(&fields), None).unwrap(),
+            digests[0]
+        );
+        // Zero is all padding, but not blank.
+        assert_ne!(
+            record_digest(b"DCarl  00000020240101", Some(&fields), None).unwrap(),
+            record_digest(b"DCarl        20240101", Some(&fields), None).unwrap()
+        );
+    }
+
+    #[test]
+    fn selected_fields() {
+        let fields = fields();
+        let selected = Some(&["amount", "name"][..]);
+
+        assert_eq!(
+            record_digest(b"DCarl  00004220240101", Some(&fields), selected).unwrap(),
+            record_digest(b"X  Carl    4219991231", Some(&fields), selected).unwrap()
+        );
+        assert_ne!(
+            record_digest(b"DCarl  00004220240101", Some(&fields), selected).unwrap(),
+            record_digest(b"DCarl  00004220240101", Some(&fields), Some(&["name"])).unwrap()
+        );
+
+        // Bytes moved from one field into the next are not the same values.
+        let split = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).name("a"),
+            FieldSet::new_field(3..6).name("b"),
+        ]);
+        assert_ne!(
+            record_digest(b"ab cd ", Some(&split), None).unwrap(),
+            record_digest(b"a  bcd", Some(&split), None).unwrap()
+        );
+
+        assert!(matches!(
+            record_digest(b"DCarl", Some(&fields), Some(&["name", "missing"])),
+            Err(Error::UnknownField(ref name)) if name == "missing"
+        ));
+    }
+
+    #[test]
+    fn short_record_hashes_missing_fields_as_empty() {
+        let fields = fields();
+        assert_eq!(
+            record_digest(b"DCarl  000042", Some(&fields), None).unwrap(),
+            record_digest(b"DCarl  000042        ", Some(&fields), None).unwrap()
+        );
+    }
+
+    #[test]
+    fn digest_whole_file() {
+        let read = |data: &'static str| {
+            let rdr = Reader::from_string(data)
+                .width(21)
+                .linebreak(LineBreak::Newline);
+            let mut digests = DigestReader::new(rdr)
+                .fields(fields(), Some(&["name", "amount"]))
+                .unwrap();
+            let records: Vec<Vec<u8>> = digests.by_ref().map(Result::unwrap).collect();
+            (records.len(), digests.records(), digests.digest())
+        };
+
+        let (len, records, digest) = read("DCarl  00004220240101\nDAnn   00000720240101");
+        assert_eq!((len, records), (2, 2));
+
+        let (_, _, repadded) = read("DCarl      4220240102\nD   Ann     720240102");
+        assert_eq!(repadded, digest);
+
+        let (_, _, reordered) = read("DAnn   00000720240101\nDCarl  00004220240101");
+        assert_ne!(reordered, digest);
+
+        let (_, _, changed) = read("DCarl  00004220240101\nDAnn   00000820240101");
+        assert_ne!(changed, digest);
+
+        let (_, _, shorter) = read("DCarl  00004220240101");
+        assert_ne!(shorter, digest);
+
+        let rdr = Reader::from_string("DCarl  00004220240101").width(21);
+        let mut digests = DigestReader::new(rdr)
+            .fields(fields(), Some(&["name", "amount"]))
+            .unwrap();
+        let record = digests.next_record().unwrap().unwrap().to_vec();
+        let mut expected = Fnv::default();
+        let selected = Some(&["name", "amount"][..]);
+        expected.write(
+            &record_digest(&record, Some(&fields()), selected)
+                .unwrap()
+                .to_le_bytes(),
+        );
+        assert_eq!(digests.digest(), expected.finish());
+
+        let rdr = Reader::from_string("").width(21);
+        assert!(matches!(
+            DigestReader::new(rdr).fields(fields(), Some(&["name", "amout"])),
+            Err(Error::UnknownField(ref name)) if name == "amout"
+        ));
+    }
+}
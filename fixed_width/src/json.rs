@@ -0,0 +1,367 @@
+use crate::{escape, Deserializer, FieldConfig, FieldSet, Reader, Result, Value};
+use serde::Deserialize;
+use std::{fmt::Write, io::Read, slice};
+
+/// Converts a record to a JSON object, keyed by field name, with the type of each value guessed
+/// as for [`Value`](enum.Value.html). Shorthand for `JsonConverter::new(fields).convert(record)`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::FieldSet;
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("name"),
+///     FieldSet::new_field(4..8).name("id"),
+/// ]);
+///
+/// let json = fixed_width::to_json(b"Carl0042", &fields).unwrap();
+/// assert_eq!(json, r#"{"name":"Carl","id":42}"#);
+/// ```
+pub fn to_json(record: &[u8], fields: &FieldSet) -> Result<String> {
+    JsonConverter::new(fields.clone()).convert(record)
+}
+
+/// Converts records to JSON text, for instance to turn fixed width data into JSON lines.
+///
+/// Each record becomes an object keyed by field name, or by range for fields without a name.
+/// The type of each value is guessed as for [`Value`](enum.Value.html), so blank fields are
+/// `null` and numbers are JSON numbers, unless `all_strings` is set. Raw bytes fields become
+/// arrays of numbers. The fields of a nested `FieldSet::Seq` are part of the object of the
+/// record, as a group has no name of its own, except for a repeated field marked by
+/// `FieldSet::occurs`, which becomes an array keyed by the name of its first field. Literal
+/// fields are left out.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, JsonConverter, Reader};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("name"),
+///     FieldSet::Seq(vec![
+///         FieldSet::new_field(4..6).name("street"),
+///         FieldSet::new_field(6..8).name("city"),
+///     ]),
+///     FieldSet::Seq(vec![
+///         FieldSet::new_field(8..10).name("scores"),
+///         FieldSet::new_field(10..12),
+///     ])
+///     .occurs(2),
+/// ]);
+///
+/// let converter = JsonConverter::new(fields);
+/// let mut reader = Reader::from_string("CarlMaNY0102Jo  HiLA0304").width(12);
+/// let lines: Vec<String> = reader.json_lines(&converter).collect::<Result<_, _>>().unwrap();
+///
+/// assert_eq!(
+///     lines[0],
+///     r#"{"name":"Carl","street":"Ma","city":"NY","scores":[1,2]}"#
+/// );
+/// assert_eq!(
+///     lines[1],
+///     r#"{"name":"Jo","street":"Hi","city":"LA","scores":[3,4]}"#
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonConverter {
+    fields: FieldSet,
+    all_strings: bool,
+}
+
+/// An iterator of records converted to JSON text by a `JsonConverter`.
+///
+/// The lifetime 'a denotes the lifetime of the reader, R, and of the converter.
+pub struct JsonLines<'a, R: 'a> {
+    r: &'a mut Reader<R>,
+    converter: &'a JsonConverter,
+}
+
+impl JsonConverter {
+    /// Creates a converter for records with the given fields.
+    pub fn new(fields: FieldSet) -> Self {
+        Self {
+            fields,
+            all_strings: false,
+        }
+    }
+
+    /// Sets whether every value is a string, trimmed as when deserializing into a `String`,
+    /// rather than the type of each value being guessed. Codes such as `"0042"` then keep their
+    /// leading zeros, and blank fields are empty strings.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, JsonConverter};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("code"),
+    ///     FieldSet::new_field(4..8).name("notes"),
+    /// ]);
+    ///
+    /// let converter = JsonConverter::new(fields).all_strings(true);
+    /// assert_eq!(converter.convert(b"0042    ").unwrap(), r#"{"code":"0042","notes":""}"#);
+    /// ```
+    pub fn all_strings(mut self, val: bool) -> Self {
+        self.all_strings = val;
+        self
+    }
+
+    /// Converts a record to a JSON object.
+    pub fn convert(&self, record: &[u8]) -> Result<String> {
+        let mut out = String::new();
+        match self.fields {
+            FieldSet::Seq(ref seq) => self.write_object(&mut out, record, seq)?,
+            ref item => self.write_object(&mut out, record, std::slice::from_ref(item))?,
+        }
+        Ok(out)
+    }
+
+    fn write_object(&self, out: &mut String, record: &[u8], seq: &[FieldSet]) -> Result<()> {
+        out.push('{');
+        self.write_members(out, record, seq, &mut true)?;
+        out.push('}');
+        Ok(())
+    }
+
+    /// Writes the fields of `seq` as members of an object, with the fields of nested groups
+    /// written alongside them.
+    fn write_members(
+        &self,
+        out: &mut String,
+        record: &[u8],
+        seq: &[FieldSet],
+        first: &mut bool,
+    ) -> Result<()> {
+        for fs in seq {
+            let key = match fs {
+                FieldSet::Item(conf) if conf.literal.is_some() => continue,
+                FieldSet::Item(conf) => conf.describe(),
+                FieldSet::Seq(inner) if !is_repeated(inner) => {
+                    self.write_members(out, record, inner, first)?;
+                    continue;
+                }
+                FieldSet::Seq(inner) => match group_key(inner) {
+                    Some(key) => key,
+                    None => continue,
+                },
+            };
+
+            if !*first {
+                out.push(',');
+            }
+            *first = false;
+
+            escape::write_json_str(out, &key);
+            out.push(':');
+            self.write_value(out, record, fs)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a field, or a repeated group as an array with an object for each element that is a
+    /// group of several fields.
+    fn write_value(&self, out: &mut String, record: &[u8], fs: &FieldSet) -> Result<()> {
+        match fs {
+            FieldSet::Item(conf) => write_json(out, &self.field_value(record, conf)?),
+            FieldSet::Seq(seq) => {
+                out.push('[');
+                let values = seq.iter().filter(|fs| match fs {
+                    FieldSet::Item(conf) => conf.literal.is_none(),
+                    FieldSet::Seq(inner) => group_key(inner).is_some(),
+                });
+                for (i, fs) in values.enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    match fs {
+                        FieldSet::Item(conf) => write_json(out, &self.field_value(record, conf)?),
+                        FieldSet::Seq(inner) => self.write_object(out, record, inner)?,
+                    }
+                }
+                out.push(']');
+            }
+        }
+        Ok(())
+    }
+
+    fn field_value(&self, record: &[u8], conf: &FieldConfig) -> Result<Value> {
+        let mut de = Deserializer::with_flattened_ref(record, slice::from_ref(conf));
+
+        if self.all_strings && !conf.bytes {
+            return Ok(Value::Str(String::deserialize(&mut de)?));
+        }
+
+        Ok(match Value::deserialize(&mut de)? {
+            Value::Bytes(b) if self.all_strings => {
+                Value::Str(String::from_utf8_lossy(&b).into_owned())
+            }
+            value => value,
+        })
+    }
+}
+
+/// The key of a nested group of fields, which is the name of its first field that is not a
+/// literal, or `None` if it only holds literals.
+fn group_key(seq: &[FieldSet]) -> Option<String> {
+    seq.iter().find_map(|fs| match fs {
+        FieldSet::Item(conf) if conf.literal.is_some() => None,
+        FieldSet::Item(conf) => Some(conf.describe()),
+        FieldSet::Seq(inner) => group_key(inner),
+    })
+}
+
+/// Whether a nested group of fields is a repeated field, marked by `FieldSet::occurs`.
+fn is_repeated(seq: &[FieldSet]) -> bool {
+    match seq.first() {
+        Some(FieldSet::Item(conf)) => conf.occurs.is_some(),
+        Some(FieldSet::Seq(inner)) => is_repeated(inner),
+        None => false,
+    }
+}
+
+fn write_json(out: &mut String, value: &Value) {
+    match value {
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(i) => out.push_str(&i.to_string()),
+        Value::Float(f) if f.is_finite() => {
+            let _ = write!(out, "{:?}", f);
+        }
+        Value::Float(_) | Value::Null => out.push_str("null"),
+        Value::Char(c) => escape::write_json_str(out, c.encode_utf8(&mut [0; 4])),
+        Value::Str(s) => escape::write_json_str(out, s),
+        Value::Bytes(b) => {
+            out.push('[');
+            for (i, byte) in b.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&byte.to_string());
+            }
+            out.push(']');
+        }
+    }
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Reads each record of the data and converts it to JSON text with the given converter.
+    pub fn json_lines<'a>(&'a mut self, converter: &'a JsonConverter) -> JsonLines<'a, R> {
+        JsonLines { r: self, converter }
+    }
+}
+
+impl<'a, R> Iterator for JsonLines<'a, R>
+where
+    R: Read,
+{
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let converter = self.converter;
+        self.r
+            .next_record()
+            .map(|record| record.and_then(|r| converter.convert(r)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..7).name("id"),
+            FieldSet::new_field(7..17).name("name"),
+            FieldSet::new_field(17..24).name("balance"),
+            FieldSet::new_field(24..28).name("region"),
+            FieldSet::new_field(28..29).name("active"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(29..31).name("totals"),
+                FieldSet::new_field(31..33),
+            ])
+            .occurs(2),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(35..39).name("street"),
+                FieldSet::new_field(39..42).name("city"),
+            ]),
+            FieldSet::new_field(33..35).name("check").bytes(true),
+        ])
+    }
+
+    #[test]
+    fn record_to_json() {
+        let record = [
+            &b"D"[..],
+            b"000042",
+            b"Jo\t\"Bo\"   ",
+            b"-12.50 ",
+            b"    ",
+            b"Y",
+            b"0102",
+            b"\x01\xff",
+            b"Main",
+            b"NYC",
+        ]
+        .concat();
+
+        assert_eq!(
+            to_json(&record, &fields()).unwrap(),
+            r#"{"id":42,"name":"Jo\t\"Bo\"","balance":-12.5,"region":null,"active":"Y","totals":[1,2],"street":"Main","city":"NYC","check":[1,255]}"#
+        );
+
+        let converter = JsonConverter::new(fields()).all_strings(true);
+        assert_eq!(
+            converter.convert(&record).unwrap(),
+            format!(
+                r#"{{"id":"000042","name":"Jo\t\"Bo\"","balance":"-12.50","region":"","active":"Y","totals":["01","02"],"street":"Main","city":"NYC","check":"\u0001{}"}}"#,
+                char::REPLACEMENT_CHARACTER
+            )
+        );
+
+        let converter = JsonConverter::new(FieldSet::new_field(0..4));
+        assert_eq!(converter.convert(b"1e99").unwrap(), r#"{"0..4":1e99}"#);
+        assert_eq!(converter.convert(b"1e3 ").unwrap(), r#"{"0..4":1000.0}"#);
+        assert!(converter.convert(b"12").is_err());
+
+        let converter = JsonConverter::new(FieldSet::new_field(0..5));
+        assert_eq!(converter.convert(b"1e999").unwrap(), r#"{"0..5":null}"#);
+
+        // The elements of a repeated group of several fields are objects.
+        let item = || {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..2).name("sku"),
+                FieldSet::new_field(2..3).name("qty"),
+            ])
+        };
+        let fields = FieldSet::Seq(vec![FieldSet::Seq(vec![item(), item()]).occurs(2)]);
+        assert_eq!(
+            to_json(b"AB1", &fields).unwrap(),
+            r#"{"sku":[{"sku":"AB","qty":1},{"sku":"AB","qty":1}]}"#
+        );
+    }
+
+    #[test]
+    fn reader_json_lines() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).name("a"),
+            FieldSet::new_field(3..6).name("b\n"),
+        ]);
+        let converter = JsonConverter::new(fields);
+        let mut reader = Reader::from_string("x  1.5y  abc").width(6);
+
+        let lines: Vec<String> = reader
+            .json_lines(&converter)
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec![r#"{"a":"x","b\n":1.5}"#, r#"{"a":"y","b\n":"abc"}"#]
+        );
+    }
+}
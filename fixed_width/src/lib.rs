@@ -93,25 +93,93 @@ let records: Vec<Person> = reader
 #![crate_name = "fixed_width"]
 #![deny(missing_docs)]
 
+#[cfg(feature = "csv")]
+pub use crate::csv::{from_csv, to_csv, CsvStats};
 pub use crate::de::{
-    deserialize, from_bytes, from_bytes_with_fields, from_str, from_str_with_fields,
-    DeserializeError, Deserializer,
+    deserialize, from_bytes, from_bytes_borrowed, from_bytes_by_name, from_bytes_strict,
+    from_bytes_with_fields, from_bytes_with_fields_ref, from_str, from_str_by_name,
+    from_str_strict, from_str_with_fields, from_str_with_fields_ref, DeserializeError,
+    Deserializer, FieldError,
 };
+#[cfg(feature = "gzip")]
+pub use crate::gzip::GzDecoder;
+#[cfg(feature = "json")]
+pub use crate::json::{to_json, JsonConverter, JsonLines};
+#[cfg(feature = "mmap")]
+pub use crate::mmap::{MmapReader, MmapRecords};
+#[cfg(feature = "parallel")]
+pub use crate::par::ParDeserializeReader;
+#[cfg(feature = "ebcdic")]
+pub use crate::transcode::{CodePage, Transcode};
 pub use crate::{
-    error::Error,
-    reader::{ByteReader, Reader, StringReader},
-    ser::{to_bytes, to_string, to_writer, to_writer_with_fields, SerializeError, Serializer},
-    writer::{AsByteSlice, Writer},
+    builder::FieldSetBuilder,
+    check::{check_file, FileCheck, FileIssue, FileReport},
+    dispatch::{DispatchReader, RecordDispatcher},
+    error::{Error, ParseJustifyError, RecordError, SplitError},
+    file::{verify_trailer, FileDetails, FileReader, FileWriter, TrailerSpec, TrailerStats},
+    reader::{
+        ByteReader, DeserializeReader, FollowMode, IntoByteRecords, IntoDeserialize,
+        IntoStringRecords, LineMode, OnMismatch, Reader, StringReader, Utf8Policy,
+    },
+    record::{format_record, parse_record, Record},
+    remap::{Overflow, Remapper},
+    ser::{
+        to_bytes, to_string, to_writer, to_writer_latin1, to_writer_with_fields,
+        FieldSerializeReason, SerializeError, Serializer,
+    },
+    value::Value,
+    writer::{serialized_len, AsByteSlice, AtomicWriter, CountingWriter, Writer, WriterStats},
+};
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    mem,
+    ops::Range,
+    result, str,
+    sync::Arc,
 };
-use std::{ops::Range, result};
 
+mod builder;
+mod check;
+#[cfg(feature = "csv")]
+mod csv;
 mod de;
+pub mod debug;
+pub mod diff;
+mod dispatch;
 mod error;
+#[cfg(any(feature = "json", feature = "schema"))]
+mod escape;
+mod file;
+#[cfg(feature = "gzip")]
+mod gzip;
+pub mod hash;
+#[cfg(feature = "json")]
+mod json;
 mod macros;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "parallel")]
+mod par;
 mod reader;
+mod record;
+#[cfg(feature = "regex")]
+mod regex;
+mod remap;
+#[cfg(feature = "schema")]
+mod schema;
 mod ser;
+#[cfg(feature = "ebcdic")]
+mod transcode;
+pub mod validate;
+mod value;
 mod writer;
 
+/// The longest range key of a field, two 20 digit `usize`s and the `..` between them.
+const RANGE_KEY_LEN: usize = 42;
+
 /// Convenience type for `Result` types pertaining to this library.
 pub type Result<T> = result::Result<T, error::Error>;
 
@@ -119,29 +187,436 @@ pub type Result<T> = result::Result<T, error::Error>;
 pub trait FixedWidth {
     /// Returns field definitaions
     fn fields() -> FieldSet;
+
+    /// Returns the field definitions flattened once and cached for the life of the program, or
+    /// `None`, the default, if they are not cached. `from_bytes`, `from_str`, `to_writer` and the
+    /// `deserialize` reader use the cached fields when there are some, rather than building and
+    /// flattening `fields` for every record.
+    ///
+    /// The cached fields must equal `fields().flatten()`. As flattened fields lose their nesting,
+    /// only cache the fields of records that do not read a sequence such as a `Vec` from a
+    /// nested `FieldSet::Seq`. `#[derive(FixedWidth)]` caches the fields of records without
+    /// `occurs` fields or type parameters.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldConfig, FieldSet, FixedWidth};
+    /// use std::sync::OnceLock;
+    ///
+    /// struct Record;
+    ///
+    /// impl FixedWidth for Record {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)])
+    ///     }
+    ///
+    ///     fn flattened_fields() -> Option<&'static [FieldConfig]> {
+    ///         static FIELDS: OnceLock<Vec<FieldConfig>> = OnceLock::new();
+    ///         Some(FIELDS.get_or_init(|| Self::fields().flatten()))
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Record::flattened_fields().unwrap(), Record::fields().flatten());
+    /// ```
+    fn flattened_fields() -> Option<&'static [FieldConfig]> {
+        None
+    }
+
+    /// Returns the width in bytes of a record, the largest end of any field's range. Defaults to
+    /// `fields().total_width()`, while `#[derive(FixedWidth)]` computes the width up front.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, FixedWidth, Reader};
+    ///
+    /// struct Record;
+    ///
+    /// impl FixedWidth for Record {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(6..8)])
+    ///     }
+    /// }
+    ///
+    /// let reader = Reader::from_string("abcd  ef").width(Record::record_width());
+    /// assert_eq!(reader.record_width, 8);
+    /// ```
+    fn record_width() -> usize {
+        Self::fields().total_width()
+    }
+}
+
+/// A record that is one of several record types with their own fields, such as the header,
+/// detail and trailer records of a file. `#[derive(FixedWidthEnum)]` implements it for an enum
+/// with one variant per record type, so the records can be written with `Writer::write_mixed`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, FixedWidth, FixedWidthRecord, Result};
+/// use serde_derive::Serialize;
+/// use std::io;
+///
+/// #[derive(Serialize)]
+/// struct Header {
+///     kind: &'static str,
+///     date: &'static str,
+/// }
+///
+/// impl FixedWidth for Header {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..9)])
+///     }
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Detail {
+///     kind: &'static str,
+///     amount: u32,
+/// }
+///
+/// impl FixedWidth for Detail {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![
+///             FieldSet::new_field(0..1),
+///             FieldSet::new_field(1..5).pad_with('0').justify("right"),
+///         ])
+///     }
+/// }
+///
+/// enum Record {
+///     Header(Header),
+///     Detail(Detail),
+/// }
+///
+/// impl FixedWidthRecord for Record {
+///     fn write_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()> {
+///         match *self {
+///             Record::Header(ref header) => fixed_width::to_writer(wrtr, header),
+///             Record::Detail(ref detail) => fixed_width::to_writer(wrtr, detail),
+///         }
+///     }
+///
+///     fn record_width(&self) -> usize {
+///         match *self {
+///             Record::Header(_) => Header::record_width(),
+///             Record::Detail(_) => Detail::record_width(),
+///         }
+///     }
+/// }
+///
+/// let mut buf = Vec::new();
+/// Record::Detail(Detail { kind: "D", amount: 42 }).write_to(&mut buf).unwrap();
+///
+/// assert_eq!(buf, b"D0042");
+/// ```
+pub trait FixedWidthRecord {
+    /// Serializes the record to the given writer, using the fields of its record type.
+    fn write_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()>;
+
+    /// Returns the width in bytes of the record, from the fields of its record type.
+    fn record_width(&self) -> usize;
+
+    /// Serializes the record as `write_to` does, but with its text as ISO-8859-1 rather than
+    /// UTF-8, as a `Writer` that transcodes expects, such as with `to_writer_latin1`. Defaults to
+    /// `write_to`, which suits records whose text is ASCII.
+    fn write_latin1_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()> {
+        self.write_to(wrtr)
+    }
+
+    /// Returns the fields of the record type, if known, so that a `Writer` that transcodes can
+    /// leave its `bytes` fields as they are. Defaults to `None`, in which case the whole record
+    /// is converted.
+    fn record_fields(&self) -> Option<FieldSet> {
+        None
+    }
+}
+
+impl<T: FixedWidthRecord + ?Sized> FixedWidthRecord for &T {
+    fn write_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()> {
+        (**self).write_to(wrtr)
+    }
+
+    fn record_width(&self) -> usize {
+        (**self).record_width()
+    }
+
+    fn write_latin1_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()> {
+        (**self).write_latin1_to(wrtr)
+    }
+
+    fn record_fields(&self) -> Option<FieldSet> {
+        (**self).record_fields()
+    }
 }
 
 /// Justification of a fixed width field.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Justify {
     /// Justify the field to the left in the record.
     Left,
     /// Justify the field to the right in the record.
     Right,
+    /// Center the field in the record. When the padding cannot be split evenly, the extra byte
+    /// goes on the right. The pad char is trimmed from both sides when the field is read.
+    Center,
 }
 
+/// Converts a string such as `"right"` to a `Justify`, ignoring case and surrounding whitespace.
+///
+/// Panics if the string is not `left`, `right`, `center` or `centre`. Parse strings that come from
+/// outside the program, such as a configuration file, with `str::parse` instead.
 impl<T: AsRef<str>> From<T> for Justify {
     fn from(s: T) -> Self {
-        match s.as_ref().to_lowercase().trim() {
-            "right" => Justify::Right,
-            "left" => Justify::Left,
-            _ => panic!("Justify must be 'left' or 'right'"),
+        match s.as_ref().parse() {
+            Ok(justify) => justify,
+            Err(_) => panic!("Justify must be 'left', 'right' or 'center'"),
+        }
+    }
+}
+
+/// Parses a `Justify` from `left`, `right`, `center` or `centre`, ignoring case and surrounding
+/// whitespace.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::Justify;
+///
+/// assert_eq!("Right".parse::<Justify>().unwrap(), Justify::Right);
+///
+/// let err = "middle".parse::<Justify>().unwrap_err();
+/// assert_eq!(err.to_string(), r#"justify must be 'left', 'right' or 'center', got "middle""#);
+/// ```
+impl str::FromStr for Justify {
+    type Err = ParseJustifyError;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "right" => Ok(Justify::Right),
+            "left" => Ok(Justify::Left),
+            "center" | "centre" => Ok(Justify::Center),
+            _ => Err(ParseJustifyError::new(s)),
+        }
+    }
+}
+
+/// Formats a `Justify` as `left`, `right` or `center`, which parse back to the same `Justify`.
+impl fmt::Display for Justify {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Justify::Left => "left",
+            Justify::Right => "right",
+            Justify::Center => "center",
+        })
+    }
+}
+
+/// How the sign of a number is written in a field.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Justify, Sign};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6).sign(Sign::Always).pad_with('0').justify(Justify::Right),
+///     FieldSet::new_field(6..13).sign(Sign::TrailingChar).pad_with('0').justify(Justify::Right),
+/// ]);
+///
+/// let mut writer = fixed_width::Writer::from_memory();
+/// fixed_width::to_writer_with_fields(&mut writer, &(42, -1234), fields.clone()).unwrap();
+/// let s: String = writer.into();
+/// assert_eq!(s, "+00042001234-");
+///
+/// let record: (i32, i32) = fixed_width::from_str_with_fields(&s, fields).unwrap();
+/// assert_eq!(record, (42, -1234));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Sign {
+    /// A leading `-` on negative numbers and no sign otherwise, as Rust formats numbers. Zero is
+    /// written without a sign.
+    #[default]
+    NegativeOnly,
+    /// A leading `+` or `-` on every number, with zero written as `+0`. When the field is right
+    /// justified and padded with `0`, the sign goes before the padding, ie `+00042`.
+    Always,
+    /// A trailing `+` or `-` on every number, ie `001234-`, with zero written as `0+`.
+    TrailingChar,
+}
+
+/// The character that separates the whole part of a number from its decimals.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{DecimalSeparator, FieldSet, Justify};
+///
+/// let field = FieldSet::new_field(0..8)
+///     .decimal_separator(DecimalSeparator::Comma)
+///     .decimals(2)
+///     .justify(Justify::Right);
+///
+/// let mut writer = fixed_width::Writer::from_memory();
+/// fixed_width::to_writer_with_fields(&mut writer, &1234.56, field.clone()).unwrap();
+/// let s: String = writer.into();
+/// assert_eq!(s, " 1234,56");
+///
+/// let n: f64 = fixed_width::from_str_with_fields("1.234,56", field).unwrap();
+/// assert_eq!(n, 1234.56);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DecimalSeparator {
+    /// A `.`, as Rust formats numbers, ie `1234.56`.
+    #[default]
+    Point,
+    /// A `,`, as in much of Europe, ie `1234,56`. When deserializing, a `.` is read as a
+    /// thousands separator and ignored, so `1.234,56` is read as well.
+    Comma,
+    /// No separator at all, with the last `decimals` digits of a field taken to be its
+    /// decimals, ie `123456` for `1234.56` with 2 decimals. A field without `decimals` is read
+    /// and written as with `Point`.
+    Implied,
+}
+
+/// A change of case applied to the text of a field.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{Case, FieldSet};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..2).transform(Case::Upper),
+///     FieldSet::new_field(2..8).transform(Case::Upper),
+/// ]);
+///
+/// let mut writer = fixed_width::Writer::from_memory();
+/// fixed_width::to_writer_with_fields(&mut writer, &("ny", "Straße"), fields).unwrap();
+/// let s: String = writer.into();
+/// assert_eq!(s, "NYSTRASS");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Case {
+    /// The text is left as it is.
+    #[default]
+    None,
+    /// The text is upper cased, which may make it longer, as `ß` becomes `SS`.
+    Upper,
+    /// The text is lower cased.
+    Lower,
+}
+
+impl Case {
+    /// Changes the case of the given text, borrowing it if it is already in that case.
+    fn apply(self, s: &str) -> Cow<'_, str> {
+        match self {
+            Case::Upper if s.chars().any(char::is_lowercase) => Cow::Owned(s.to_uppercase()),
+            Case::Lower if s.chars().any(char::is_uppercase) => Cow::Owned(s.to_lowercase()),
+            _ => Cow::Borrowed(s),
         }
     }
 }
 
+/// The codes that stand for the variants of an enum in a field, set by `FieldSet::variants`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+struct VariantCodes {
+    /// Pairs of a code and the name of the variant it stands for.
+    codes: Vec<(String, String)>,
+    /// The variant that codes without a pair are read as.
+    catch_all: Option<String>,
+}
+
+impl VariantCodes {
+    /// The name of the variant the given code stands for, or the catch-all variant if there is no
+    /// pair for the code.
+    fn variant(&self, code: &str) -> Option<&str> {
+        self.codes
+            .iter()
+            .find(|(c, _)| c == code)
+            .map(|(_, variant)| variant.as_str())
+            .or(self.catch_all.as_deref())
+    }
+
+    /// The code that stands for the variant of the given name.
+    fn code(&self, variant: &str) -> Option<&str> {
+        self.codes
+            .iter()
+            .find(|(_, v)| v == variant)
+            .map(|(code, _)| code.as_str())
+    }
+}
+
+/// The characters of a field of yes/no flags, set by `FieldSet::flags`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Flags {
+    /// The number of flags, one byte each from the start of the field.
+    count: usize,
+    /// The byte of a flag that is set.
+    yes: u8,
+    /// The byte of a flag that is not set.
+    no: u8,
+}
+
+type DeserializeFn = dyn Fn(&[u8]) -> result::Result<String, DeserializeError> + Send + Sync;
+type SerializeFn = dyn Fn(&[u8]) -> result::Result<Vec<u8>, SerializeError> + Send + Sync;
+
+/// A function that converts the raw bytes of a field before they are deserialized.
+#[derive(Clone)]
+struct DeserializeWith(Arc<DeserializeFn>);
+
+/// A function that converts the bytes of a value before they are padded and written.
+#[derive(Clone)]
+struct SerializeWith(Arc<SerializeFn>);
+
+impl fmt::Debug for DeserializeWith {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DeserializeWith")
+    }
+}
+
+impl fmt::Debug for SerializeWith {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SerializeWith")
+    }
+}
+
+// Functions cannot be compared, so hooks are only equal if they are the same function, shared by
+// clones of a field.
+macro_rules! hook_identity {
+    ($hook:ident) => {
+        impl $hook {
+            fn addr(&self) -> *const () {
+                Arc::as_ptr(&self.0) as *const ()
+            }
+        }
+
+        impl PartialEq for $hook {
+            fn eq(&self, other: &Self) -> bool {
+                self.addr() == other.addr()
+            }
+        }
+
+        impl Eq for $hook {}
+
+        impl Hash for $hook {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.addr().hash(state)
+            }
+        }
+    };
+}
+
+hook_identity!(DeserializeWith);
+hook_identity!(SerializeWith);
+
 /// Defines a field in a fixed width record. There can be 1 or more fields in a fixed width record.
-#[derive(Debug, Clone)]
+///
+/// Fields are equal if all of their settings are equal. Fields with `deserialize_with` or
+/// `serialize_with` functions are only equal if they share the same functions, that is if one is
+/// a clone of the other.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FieldConfig {
     /// Name of the field.
     name: Option<String>,
@@ -151,6 +626,44 @@ pub struct FieldConfig {
     pad_with: char,
     /// The justification (Left or Right) of the field.
     justify: Justify,
+    /// Whether the field runs from the start of its range to the end of the record.
+    to_end: bool,
+    /// A constant value that is always written for this field, and is not part of the record's
+    /// type.
+    literal: Option<String>,
+    /// Whether a literal value is checked when deserializing.
+    strict: bool,
+    /// The value deserialized in place of a blank field.
+    default: Option<String>,
+    /// Converts the raw bytes of the field before deserializing.
+    deserialize_with: Option<DeserializeWith>,
+    /// Converts the bytes of the value before serializing.
+    serialize_with: Option<SerializeWith>,
+    /// Whether the field holds raw bytes, which are never validated as UTF-8.
+    bytes: bool,
+    /// Whether the field captures the whole raw record when deserializing, and is skipped when
+    /// serializing.
+    capture_only: bool,
+    /// Whether the field is written as padding, whatever its value, when serializing.
+    write_as_blank: bool,
+    /// The number of decimal places floats are written with, when serializing.
+    decimals: Option<usize>,
+    /// How the sign of a number is written and read.
+    sign: Sign,
+    /// The character written and read between the whole part of a number and its decimals.
+    decimal_separator: DecimalSeparator,
+    /// Whether a number that is zero is written as spaces, and spaces are read as zero.
+    blank_when_zero: bool,
+    /// The change of case applied to text when serializing.
+    case: Case,
+    /// Whether the change of case is applied when deserializing as well.
+    case_on_read: bool,
+    /// The codes that stand for the variants of an enum in this field.
+    variants: Option<Arc<VariantCodes>>,
+    /// Whether each byte of the field is a yes/no flag, as an element of a sequence.
+    flags: Option<Flags>,
+    /// The number of elements of the repeated group that this field is the first of.
+    occurs: Option<usize>,
 }
 
 impl Default for FieldConfig {
@@ -160,6 +673,24 @@ impl Default for FieldConfig {
             range: 0..0,
             pad_with: ' ',
             justify: Justify::Left,
+            to_end: false,
+            literal: None,
+            strict: false,
+            default: None,
+            deserialize_with: None,
+            serialize_with: None,
+            bytes: false,
+            capture_only: false,
+            write_as_blank: false,
+            decimals: None,
+            sign: Sign::NegativeOnly,
+            decimal_separator: DecimalSeparator::Point,
+            blank_when_zero: false,
+            case: Case::None,
+            case_on_read: false,
+            variants: None,
+            flags: None,
+            occurs: None,
         }
     }
 }
@@ -179,13 +710,189 @@ impl FieldConfig {
         }
     }
 
+    /// Create a new field that starts at the given byte and runs to the end of the record. When
+    /// serialized, the value of the field is written as is, without padding or truncation.
+    ///
+    /// ```rust
+    /// use fixed_width::FieldConfig;
+    ///
+    /// let field = FieldConfig::to_end(80);
+    /// ```
+    pub fn to_end(start: usize) -> Self {
+        FieldConfig {
+            range: start..start,
+            to_end: true,
+            ..Default::default()
+        }
+    }
+
+    /// The name of the field, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The byte range of the field. For a field that runs to the end of the record, only the
+    /// start of the range is meaningful.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The character used to pad the field.
+    pub fn pad_with(&self) -> char {
+        self.pad_with
+    }
+
+    /// The justification of the field.
+    pub fn justify(&self) -> Justify {
+        self.justify
+    }
+
+    /// Sets the name of the field.
+    pub fn set_name<T: Into<String>>(&mut self, val: T) {
+        self.name = Some(val.into());
+    }
+
+    /// Sets the byte range of the field, or returns `Error::InvalidFieldSet` if the range starts
+    /// after it ends, leaving the field as it was.
+    pub fn set_range(&mut self, val: Range<usize>) -> Result<()> {
+        if val.start > val.end {
+            return Err(Error::InvalidFieldSet(format!(
+                "range {}..{} starts after it ends",
+                val.start, val.end
+            )));
+        }
+
+        self.range = val;
+        Ok(())
+    }
+
+    /// Sets the character used to pad the field.
+    pub fn set_pad_with(&mut self, val: char) {
+        self.pad_with = val;
+    }
+
+    /// Sets the justification of the field.
+    pub fn set_justify<T: Into<Justify>>(&mut self, val: T) {
+        self.justify = val.into();
+    }
+
     fn width(&self) -> usize {
         self.range.end - self.range.start
     }
+
+    /// Gets the bytes of this field from the given record, or `None` if the record is too short.
+    fn slice<'a>(&self, record: &'a [u8]) -> Option<&'a [u8]> {
+        if self.capture_only {
+            Some(record)
+        } else if self.to_end {
+            record.get(self.range.start..)
+        } else {
+            record.get(self.range.clone())
+        }
+    }
+
+    /// Whether the given field data is blank, that is made up of only padding or spaces.
+    fn is_blank(&self, bytes: &[u8]) -> bool {
+        let pad = self.pad_with as u8;
+        bytes.iter().all(|&b| b == pad || b == b' ')
+    }
+
+    /// Trims the padding of this field from the given field data, along with any surrounding
+    /// spaces, as justified. A field that is all zero padding is trimmed to a single `0`.
+    fn trim<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        let pad = self.pad_with as u8;
+        let (trim_start, trim_end) = match self.justify {
+            Justify::Left => (b' ', pad),
+            Justify::Right => (pad, b' '),
+            Justify::Center => (pad, pad),
+        };
+
+        let start = bytes
+            .iter()
+            .position(|&b| b != b' ' && b != trim_start)
+            .unwrap_or(bytes.len());
+        let end = bytes
+            .iter()
+            .rposition(|&b| b != b' ' && b != trim_end)
+            .map_or(start, |i| i + 1)
+            .max(start);
+
+        if start == end && pad.is_ascii_digit() && bytes.contains(&pad) {
+            return &bytes[bytes.len() - 1..];
+        }
+
+        &bytes[start..end]
+    }
+
+    /// The name of this field, or its range if it has no name.
+    fn describe(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.range_string())
+    }
+
+    /// The name of this field, or its range if it has no name, formatted into `buf` rather than
+    /// allocating. Used as the key of the field when deserializing into a map.
+    fn key<'a>(&'a self, buf: &'a mut [u8; RANGE_KEY_LEN]) -> &'a str {
+        if let Some(ref name) = self.name {
+            return name;
+        }
+
+        let mut cursor = &mut buf[..];
+        // Two `usize`s and the `..` between them always fit, so writing cannot fail.
+        let _ = if self.to_end {
+            write!(cursor, "{}..", self.range.start)
+        } else {
+            write!(cursor, "{}..{}", self.range.start, self.range.end)
+        };
+        let len = RANGE_KEY_LEN - cursor.len();
+
+        str::from_utf8(&buf[..len]).unwrap_or_default()
+    }
+
+    /// Formats the range of this field, ie `0..4`, or `80..` for a field that runs to the end of
+    /// the record.
+    fn range_string(&self) -> String {
+        if self.to_end {
+            format!("{}..", self.range.start)
+        } else {
+            format!("{}..{}", self.range.start, self.range.end)
+        }
+    }
+}
+
+/// Formats a field as its name and range, followed by its padding and justification, ie
+/// `amount@10..16 pad='0' right`. A field with no name is formatted from its range alone. Use
+/// `FieldSet::describe` to render every setting of a set of fields.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::FieldConfig;
+///
+/// let mut field = FieldConfig::new(10..16);
+/// assert_eq!(field.to_string(), "10..16 pad=' ' left");
+///
+/// field.set_name("amount");
+/// field.set_pad_with('0');
+/// field.set_justify("right");
+/// assert_eq!(field.to_string(), "amount@10..16 pad='0' right");
+/// ```
+impl fmt::Display for FieldConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref name) = self.name {
+            write!(f, "{}@", name)?;
+        }
+        write!(
+            f,
+            "{} pad={:?} {}",
+            self.range_string(),
+            self.pad_with,
+            self.justify
+        )
+    }
 }
 
 /// Field structure definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FieldSet {
     /// For single Field
     Item(FieldConfig),
@@ -208,6 +915,96 @@ impl FieldSet {
         })
     }
 
+    ///  Create a sequence of consecutive unnamed fields of the given widths, starting at the
+    ///  first byte of the record. Use `FieldSet::builder` to also name the fields.
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::from_widths(&[10, 2, 8]);
+    ///
+    /// assert_eq!(fields, FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..10),
+    ///     FieldSet::new_field(10..12),
+    ///     FieldSet::new_field(12..20),
+    /// ]));
+    /// ```
+    pub fn from_widths(widths: &[usize]) -> Self {
+        widths
+            .iter()
+            .fold(Self::builder(), |builder, &width| builder.unnamed(width))
+            .build()
+    }
+
+    ///  Create a builder of consecutive fields, which works out the range of each field from
+    ///  its width.
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::builder().field("id", 10).field("state", 2).build();
+    ///
+    /// assert_eq!(fields, FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..10).name("id"),
+    ///     FieldSet::new_field(10..12).name("state"),
+    /// ]));
+    /// ```
+    pub fn builder() -> FieldSetBuilder {
+        FieldSetBuilder::default()
+    }
+
+    ///  Create a new field that starts at the given byte and runs to the end of the record. Useful
+    ///  for a trailing free text field of varying length.
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field_to_end(4),
+    /// ]);
+    ///
+    /// let (code, note): (String, String) = fixed_width::from_bytes_with_fields(
+    ///     b"1234free text of any length",
+    ///     fields,
+    /// ).unwrap();
+    ///
+    /// assert_eq!(code, "1234");
+    /// assert_eq!(note, "free text of any length");
+    /// ```
+    pub fn new_field_to_end(start: usize) -> Self {
+        Self::Item(FieldConfig::to_end(start))
+    }
+
+    ///  Create a new field that always holds the given constant value, such as a record type
+    ///  code, a format version or filler. The serializer writes the value without consuming any
+    ///  of the record's values, and the deserializer skips over the field, checking it only if
+    ///  `strict` is set.
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_literal(0..2, "01"),
+    ///     FieldSet::new_field(2..6),
+    /// ]);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &("abcd",), fields.clone()).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "01abcd");
+    ///
+    /// let (value,): (String,) = fixed_width::from_str_with_fields(&s, fields).unwrap();
+    /// assert_eq!(value, "abcd");
+    /// ```
+    pub fn new_literal<T: Into<String>>(range: std::ops::Range<usize>, value: T) -> Self {
+        Self::Item(FieldConfig {
+            range,
+            literal: Some(value.into()),
+            ..Default::default()
+        })
+    }
+
     /// Sets the name of this field. Mainly used when deserializing into a HashMap to derive the keys.
     /// (This method is not valid on `FieldSet::Seq` and cause panic)
     ///
@@ -215,218 +1012,1176 @@ impl FieldSet {
     /// use fixed_width::FieldSet;
     ///
     /// let fields = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1).name("foo"),
-    ///     FieldSet::Seq(vec![
-    ///         FieldSet::new_field(0..2).name("bar"), FieldSet::new_field(0..3).name("baz")
-    ///     ])
+    ///     FieldSet::new_field(0..1).name("foo"),
+    ///     FieldSet::Seq(vec![
+    ///         FieldSet::new_field(0..2).name("bar"), FieldSet::new_field(0..3).name("baz")
+    ///     ])
+    /// ]);
+    /// ```
+    pub fn name<T: Into<String>>(mut self, val: T) -> Self {
+        match &mut self {
+            Self::Item(conf) => {
+                conf.name = Some(val.into());
+                self
+            }
+            _ => panic!("Setting name on FieldSet::Seq is not feasible."),
+        }
+    }
+
+    /// Sets the name of this field, like `name`, but returns an `Error::InvalidFieldSet` rather
+    /// than panicking if this is a `FieldSet::Seq`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let field = FieldSet::new_field(0..1).try_name("foo").unwrap();
+    /// assert_eq!(field.get("foo").unwrap().range(), 0..1);
+    ///
+    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..2)]);
+    /// let err = fields.try_name("foo").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     r#"invalid field set: cannot name a FieldSet::Seq "foo", it holds 0..1, 1..2"#
+    /// );
+    /// ```
+    pub fn try_name<T: Into<String>>(self, val: T) -> Result<Self> {
+        match self {
+            Self::Item(_) => Ok(self.name(val)),
+            Self::Seq(_) => {
+                let fields: Vec<String> = self.iter_flat().map(FieldConfig::describe).collect();
+                Err(Error::InvalidFieldSet(format!(
+                    "cannot name a FieldSet::Seq {:?}, it holds {}",
+                    val.into(),
+                    fields.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// Sets the character to use as padding the value of this field to its byte width.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let field = FieldSet::new_field(0..1).pad_with('x');
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..1),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(0..3)]),
+    /// ])
+    /// .pad_with('x');
+    /// ```
+    pub fn pad_with(mut self, val: char) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.pad_with = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.pad_with(val)).collect()),
+        }
+    }
+
+    /// Sets the justification to use fields. Left will align to the left and Right to the
+    /// right.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Justify};
+    ///
+    /// let field = FieldSet::new_field(0..1).justify(Justify::Right);
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..1),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(0..3)]),
+    /// ])
+    /// .justify(Justify::Right);
+    /// ```
+    pub fn justify<T: Into<Justify>>(mut self, val: T) -> Self {
+        let val = val.into();
+        match self {
+            Self::Item(ref mut config) => {
+                config.justify = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.justify(val)).collect()),
+        }
+    }
+
+    /// Sets the justification of the fields from a string such as `"right"`, like `justify`, but
+    /// returns an `Error::InvalidFieldSet` rather than panicking if the string is not `left`,
+    /// `right` or `center`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Justify};
+    ///
+    /// let field = FieldSet::new_field(0..1).try_justify("right").unwrap();
+    /// assert_eq!(field, FieldSet::new_field(0..1).justify(Justify::Right));
+    ///
+    /// assert!(FieldSet::new_field(0..1).try_justify("up").is_err());
+    /// ```
+    pub fn try_justify<T: AsRef<str>>(self, val: T) -> Result<Self> {
+        let val: Justify = val.as_ref().parse()?;
+        Ok(self.justify(val))
+    }
+
+    /// Sets the value to deserialize in place of a blank field, one made up of only padding or
+    /// spaces. The value is parsed as if it had been read from the record, so a blank quantity
+    /// can mean `0` without the field having to be an `Option`. Fields with any other data are
+    /// unaffected. As the value is not part of the record, it cannot be deserialized into a
+    /// borrowed `&str` or `&[u8]`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).pad_with('0').default_value("0"),
+    ///     FieldSet::new_field(4..6).default_value("US"),
+    /// ]);
+    ///
+    /// let (quantity, country): (usize, String) =
+    ///     fixed_width::from_str_with_fields("0000  ", fields).unwrap();
+    ///
+    /// assert_eq!(quantity, 0);
+    /// assert_eq!(country, "US");
+    /// ```
+    pub fn default_value<T: Into<String>>(mut self, val: T) -> Self {
+        match &mut self {
+            Self::Item(conf) => {
+                conf.default = Some(val.into());
+                self
+            }
+            _ => panic!("Setting default_value on FieldSet::Seq is not feasible."),
+        }
+    }
+
+    /// Sets a function to convert the raw bytes of this field before they are deserialized. The
+    /// function is given the bytes untrimmed and without UTF-8 validation, and the string it
+    /// returns is deserialized in their place. Useful for cleaning up or mapping legacy data. A
+    /// blank field with a `default_value` uses the default and does not call the function.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{DeserializeError, FieldSet};
+    ///
+    /// fn strip_control(raw: &[u8]) -> Result<String, DeserializeError> {
+    ///     Ok(raw.iter().filter(|b| !b.is_ascii_control()).map(|&b| b as char).collect())
+    /// }
+    ///
+    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..5).deserialize_with(strip_control)]);
+    /// let (name,): (String,) = fixed_width::from_bytes_with_fields(b"ab\x07cd", fields).unwrap();
+    ///
+    /// assert_eq!(name, "abcd");
+    /// ```
+    pub fn deserialize_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[u8]) -> result::Result<String, DeserializeError> + Send + Sync + 'static,
+    {
+        match &mut self {
+            Self::Item(conf) => {
+                conf.deserialize_with = Some(DeserializeWith(Arc::new(f)));
+                self
+            }
+            _ => panic!("Setting deserialize_with on FieldSet::Seq is not feasible."),
+        }
+    }
+
+    /// Sets a function to convert the bytes of a value before they are padded and written to
+    /// this field. The counterpart of `deserialize_with`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, SerializeError, Writer};
+    ///
+    /// fn to_legacy_code(value: &[u8]) -> Result<Vec<u8>, SerializeError> {
+    ///     match value {
+    ///         b"active" => Ok(b"A".to_vec()),
+    ///         b"closed" => Ok(b"C".to_vec()),
+    ///         _ => Err(SerializeError::Message("unknown status".to_string())),
+    ///     }
+    /// }
+    ///
+    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..2).serialize_with(to_legacy_code)]);
+    /// let mut writer = Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &("closed",), fields).unwrap();
+    ///
+    /// assert_eq!(String::from(writer), "C ");
+    /// ```
+    pub fn serialize_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[u8]) -> result::Result<Vec<u8>, SerializeError> + Send + Sync + 'static,
+    {
+        match &mut self {
+            Self::Item(conf) => {
+                conf.serialize_with = Some(SerializeWith(Arc::new(f)));
+                self
+            }
+            _ => panic!("Setting serialize_with on FieldSet::Seq is not feasible."),
+        }
+    }
+
+    /// Sets whether this field holds raw bytes, such as binary data that is not valid UTF-8. The
+    /// deserializer hands the untrimmed bytes of such a field to the visitor without validating
+    /// them, even when a string is asked for, so it can be read into a `Vec<u8>`, a `ByteBuf` or
+    /// a map of byte values.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    /// use std::collections::HashMap;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("name"),
+    ///     FieldSet::new_field(4..6).name("noise").bytes(true),
+    /// ]);
+    ///
+    /// let h: HashMap<String, serde_bytes::ByteBuf> =
+    ///     fixed_width::from_bytes_with_fields(b"abcd\xff\xfe", fields).unwrap();
+    ///
+    /// assert_eq!(h["name"].as_ref(), b"abcd");
+    /// assert_eq!(h["noise"].as_ref(), b"\xff\xfe");
+    /// ```
+    pub fn bytes(mut self, val: bool) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.bytes = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.bytes(val)).collect()),
+        }
+    }
+
+    /// Sets whether this field captures the whole raw record. When deserializing, such a field
+    /// yields every byte of the record, untrimmed, regardless of its range, which makes it
+    /// possible to keep the original record alongside the parsed fields, for instance to write
+    /// rejected records verbatim. When serializing, the field's value is skipped and nothing is
+    /// written for it, so the record is not written twice.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field(0..0).capture_only(true),
+    ///     FieldSet::new_field(4..8),
+    /// ]);
+    ///
+    /// let record: (String, String, String) =
+    ///     fixed_width::from_str_with_fields("abc 1234", fields.clone()).unwrap();
+    /// assert_eq!(record, ("abc".to_string(), "abc 1234".to_string(), "1234".to_string()));
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &record, fields).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "abc 1234");
+    /// ```
+    pub fn capture_only(mut self, val: bool) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.capture_only = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.capture_only(val)).collect()),
+        }
+    }
+
+    /// Sets whether this field is written blank. The serializer ignores the field's value and
+    /// fills its width with the pad character, while the deserializer reads the field as usual.
+    /// Useful for obsolete columns that must still be read but should be blanked when records are
+    /// copied.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field(4..8).write_as_blank(true),
+    /// ]);
+    ///
+    /// let record: (String, String) =
+    ///     fixed_width::from_str_with_fields("abcdold1", fields.clone()).unwrap();
+    /// assert_eq!(record.1, "old1");
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &record, fields).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "abcd    ");
+    /// ```
+    pub fn write_as_blank(mut self, val: bool) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.write_as_blank = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.write_as_blank(val)).collect()),
+        }
+    }
+
+    /// Sets the number of decimal places a float is written with when serializing, such as
+    /// `12.30` for 2 decimals. The value is rounded to that many places and padded as usual.
+    /// Without it, floats are written with as many decimals as they need. A value that does not
+    /// fit the field is a `FieldSerializeReason::TooWide` error, rather than being cut short.
+    ///
+    /// A string that holds a plain decimal number, as exact decimal types such as
+    /// `rust_decimal::Decimal` serialize to, is written the same way without going through a
+    /// float, rounding half away from zero. When deserializing, such types are given the text of
+    /// the number, with its sign and decimal separator read as for a float, rather than a float.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Justify};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..8).decimals(2).justify(Justify::Right),
+    ///     FieldSet::new_field(8..16).decimals(2).justify(Justify::Right),
+    /// ]);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &(12.3, 0.1), fields).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "   12.30    0.10");
+    /// ```
+    pub fn decimals(mut self, val: usize) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.decimals = Some(val);
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.decimals(val)).collect()),
+        }
+    }
+
+    /// Sets how the sign of a number is written in this field, as described by `Sign`. When
+    /// deserializing, a field with `Sign::TrailingChar` has its trailing `+` or `-` read as the
+    /// sign of the number. A leading `+` is accepted whatever the setting.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Sign};
+    ///
+    /// let field = FieldSet::new_field(0..6).sign(Sign::TrailingChar);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &-12.5, field.clone()).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "12.5- ");
+    ///
+    /// let n: f64 = fixed_width::from_str_with_fields(&s, field).unwrap();
+    /// assert_eq!(n, -12.5);
+    /// ```
+    pub fn sign(mut self, val: Sign) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.sign = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.sign(val)).collect()),
+        }
+    }
+
+    /// Sets the character between the whole part of a number and its decimals in this field, as
+    /// described by `DecimalSeparator`. It is used with `decimals` as well, so a float with 2
+    /// decimals and `DecimalSeparator::Comma` is written as `12,30`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{DecimalSeparator, FieldSet};
+    ///
+    /// let field = FieldSet::new_field(0..6).decimal_separator(DecimalSeparator::Comma);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &-12.5, field.clone()).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "-12,5 ");
+    ///
+    /// let n: f64 = fixed_width::from_str_with_fields(&s, field).unwrap();
+    /// assert_eq!(n, -12.5);
+    /// ```
+    pub fn decimal_separator(mut self, val: DecimalSeparator) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.decimal_separator = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(
+                seq.into_iter()
+                    .map(|fs| fs.decimal_separator(val))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Sets whether a number that is zero is written as spaces across the whole field, as with
+    /// COBOL's `BLANK WHEN ZERO`. A number counts as zero if it is written as zero, so `0.001`
+    /// with 2 decimals is blanked too. `None` is written as spaces as well.
+    ///
+    /// When deserializing, a field of only spaces is read as zero by a number, even outside
+    /// lenient mode, while an `Option` of a number reads it as `None`, as for any blank field.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Justify};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).blank_when_zero(true).pad_with('0').justify(Justify::Right),
+    ///     FieldSet::new_field(4..8).blank_when_zero(true).pad_with('0').justify(Justify::Right),
+    /// ]);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &(0, 12), fields.clone()).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "    0012");
+    ///
+    /// let record: (u32, u32) = fixed_width::from_str_with_fields(&s, fields).unwrap();
+    /// assert_eq!(record, (0, 12));
+    /// ```
+    pub fn blank_when_zero(mut self, val: bool) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.blank_when_zero = val;
+                self
+            }
+            Self::Seq(seq) => {
+                Self::Seq(seq.into_iter().map(|fs| fs.blank_when_zero(val)).collect())
+            }
+        }
+    }
+
+    /// Sets the change of case applied to the text of this field when serializing, before it is
+    /// padded or cut to the width of the field. Numbers and raw bytes are left as they are. See
+    /// `transform_on_read` to apply it when deserializing as well.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Case, FieldSet};
+    ///
+    /// let field = FieldSet::new_field(0..6).transform(Case::Upper);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &"Smith", field).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "SMITH ");
+    /// ```
+    pub fn transform(mut self, val: Case) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.case = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.transform(val)).collect()),
+        }
+    }
+
+    /// Sets whether the change of case set by `transform` is also applied to text when
+    /// deserializing, after it has been trimmed, so that data read from elsewhere is normalized.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Case, FieldSet};
+    ///
+    /// let field = FieldSet::new_field(0..6)
+    ///     .transform(Case::Upper)
+    ///     .transform_on_read(true);
+    ///
+    /// let name: String = fixed_width::from_str_with_fields("Smith ", field).unwrap();
+    /// assert_eq!(name, "SMITH");
+    /// ```
+    pub fn transform_on_read(mut self, val: bool) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.case_on_read = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(
+                seq.into_iter()
+                    .map(|fs| fs.transform_on_read(val))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Sets the codes that stand for the variants of an enum in this field, as pairs of a code and
+    /// the name of the variant. When deserializing, the code in the field is read as its variant,
+    /// and a code without a pair is a `DeserializeError::UnknownCode`, unless a
+    /// `catch_all_variant` is set. When serializing, a variant is written as its code, and a
+    /// variant without a code is a `FieldSerializeReason::UnmappedVariant` error. Strings are
+    /// mapped the same way as unit variants.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    /// use serde_derive::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    /// enum Account {
+    ///     Checking,
+    ///     Savings,
+    /// }
+    ///
+    /// let field = FieldSet::new_field(0..2).variants(&[("01", "Checking"), ("02", "Savings")]);
+    ///
+    /// let account: Account = fixed_width::from_str_with_fields("02", field.clone()).unwrap();
+    /// assert_eq!(account, Account::Savings);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &Account::Checking, field.clone()).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "01");
+    ///
+    /// let err = fixed_width::from_str_with_fields::<Account>("07", field).unwrap_err();
+    /// assert!(err.to_string().contains(r#"unknown code "07""#));
+    /// ```
+    pub fn variants<C: AsRef<str>, V: AsRef<str>>(self, codes: &[(C, V)]) -> Self {
+        let codes: Vec<_> = codes
+            .iter()
+            .map(|(code, variant)| (code.as_ref().to_string(), variant.as_ref().to_string()))
+            .collect();
+        self.map_variants(&|variants| variants.codes = codes.clone())
+    }
+
+    /// Sets the variant that codes without a pair from `variants` are read as, instead of being
+    /// an error. The variant is written with its own code, so it needs a pair as well if it is
+    /// ever serialized.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// enum Account {
+    ///     Checking,
+    ///     Other,
+    /// }
+    ///
+    /// let field = FieldSet::new_field(0..2)
+    ///     .variants(&[("01", "Checking"), ("99", "Other")])
+    ///     .catch_all_variant("Other");
+    ///
+    /// let account: Account = fixed_width::from_str_with_fields("07", field).unwrap();
+    /// assert_eq!(account, Account::Other);
+    /// ```
+    pub fn catch_all_variant<T: Into<String>>(self, variant: T) -> Self {
+        let variant = variant.into();
+        self.map_variants(&|variants| variants.catch_all = Some(variant.clone()))
+    }
+
+    fn map_variants(mut self, f: &dyn Fn(&mut VariantCodes)) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                f(Arc::make_mut(
+                    config.variants.get_or_insert_with(Default::default),
+                ));
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.map_variants(f)).collect()),
+        }
+    }
+
+    /// Makes this a field of `count` yes/no flags, one byte each, such as the indicators `YNY Y`.
+    /// The field is read into a sequence of `bool`, such as `[bool; 5]`, rather than a sequence
+    /// taking one field per element. `yes` is read as `true` and `no` as `false`, while a space is
+    /// read as `false`, or as `None` for a sequence of `Option<bool>`. Any other character is a
+    /// `DeserializeError::InvalidFlag`. The flags are written back the same way, with `None`
+    /// written as a space.
+    ///
+    /// A tuple or array is only read or written as flags if its length is `count`.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `yes` or `no` is not ASCII, as each flag is a single byte.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..2),
+    ///     FieldSet::new_field(2..7).flags(5, 'Y', 'N'),
+    /// ]);
+    ///
+    /// let (id, flags): (u8, [bool; 5]) =
+    ///     fixed_width::from_str_with_fields("42YNY Y", fields.clone()).unwrap();
+    /// assert_eq!(id, 42);
+    /// assert_eq!(flags, [true, false, true, false, true]);
+    ///
+    /// let (_, flags): (u8, [Option<bool>; 5]) =
+    ///     fixed_width::from_str_with_fields("42YNY Y", fields.clone()).unwrap();
+    /// assert_eq!(flags, [Some(true), Some(false), Some(true), None, Some(true)]);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &(7, flags), fields).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "7 YNY Y");
+    /// ```
+    pub fn flags(mut self, count: usize, yes: char, no: char) -> Self {
+        assert!(
+            yes.is_ascii() && no.is_ascii(),
+            "flag characters must be ASCII, got {:?} and {:?}",
+            yes,
+            no
+        );
+
+        match self {
+            Self::Item(ref mut config) => {
+                config.flags = Some(Flags {
+                    count,
+                    yes: yes as u8,
+                    no: no as u8,
+                });
+                self
+            }
+            Self::Seq(seq) => {
+                Self::Seq(seq.into_iter().map(|fs| fs.flags(count, yes, no)).collect())
+            }
+        }
+    }
+
+    /// Marks this `FieldSet` as a repeated group of `count` elements, one field per element and
+    /// starting at its first field, such as the fields of a `Vec<u32>` or `[u32; 12]` that
+    /// occurs 12 times. A sequence serialized to the group must have exactly `count` elements,
+    /// otherwise it is a `FieldSerializeReason::ElementCount` error rather than a value being
+    /// shifted into a later field. `#[derive(FixedWidth)]` marks the fields of its `occurs` fields this way.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, FieldSerializeReason, FieldSet, SerializeError};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(2..4)]).occurs(2),
+    ///     FieldSet::new_field(4..6),
+    /// ]);
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// fixed_width::to_writer_with_fields(&mut writer, &(vec![1, 2], 3), fields.clone()).unwrap();
+    /// let s: String = writer.into();
+    /// assert_eq!(s, "1 2 3 ");
+    ///
+    /// let mut writer = fixed_width::Writer::from_memory();
+    /// match fixed_width::to_writer_with_fields(&mut writer, &(vec![1], 3), fields) {
+    ///     Err(Error::SerializeError(SerializeError::Field { reason, .. })) => {
+    ///         assert_eq!(reason, FieldSerializeReason::ElementCount { expected: 2, actual: 1 });
+    ///     }
+    ///     res => panic!("unexpected result: {:?}", res),
+    /// }
+    /// ```
+    pub fn occurs(self, count: usize) -> Self {
+        match self {
+            Self::Item(mut config) => {
+                config.occurs = Some(count);
+                Self::Item(config)
+            }
+            Self::Seq(mut seq) => {
+                if let Some(first) = seq.first_mut() {
+                    *first = mem::replace(first, Self::Seq(Vec::new())).occurs(count);
+                }
+                Self::Seq(seq)
+            }
+        }
+    }
+
+    /// Sets whether literal fields are checked when deserializing. A strict literal field whose
+    /// data does not match its value results in a `DeserializeError::LiteralMismatch`. Has no
+    /// effect on fields that are not literals.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_literal(0..2, "01"),
+    ///     FieldSet::new_field(2..6),
+    /// ])
+    /// .strict(true);
+    ///
+    /// let res: Result<(String,), _> = fixed_width::from_str_with_fields("02abcd", fields);
+    /// assert!(res.is_err());
+    /// ```
+    pub fn strict(mut self, val: bool) -> Self {
+        match self {
+            Self::Item(ref mut config) => {
+                config.strict = val;
+                self
+            }
+            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.strict(val)).collect()),
+        }
+    }
+
+    /// Append `FieldSet` with the given item.
+    ///
+    /// ### Example
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// // Suppose field defined as:
+    /// let append_fields_1 = FieldSet::new_field(0..1).append(FieldSet::new_field(1..2));
+    /// let append_fields_2 = FieldSet::new_field(0..1).append(
+    ///     FieldSet::Seq(vec![
+    ///         FieldSet::new_field(1..2),
+    ///         FieldSet::new_field(2..3),
+    ///     ])
+    /// );
+    ///
+    /// // Are identical to:
+    /// let fields_1 = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..1),
+    ///     FieldSet::new_field(1..2),
+    /// ]);
+    /// let fields_2 = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..1),
+    ///     FieldSet::Seq(vec![
+    ///         FieldSet::new_field(1..2),
+    ///         FieldSet::new_field(2..3),
+    ///     ]),
+    /// ]);
+    ///
+    /// assert_eq!(append_fields_1, fields_1);
+    /// assert_eq!(append_fields_2, fields_2);
+    /// ```
+    pub fn append(self, item: Self) -> Self {
+        match self {
+            Self::Item(_) => Self::Seq(vec![self, item]),
+            Self::Seq(mut seq) => {
+                seq.append(&mut vec![item]);
+                Self::Seq(seq)
+            }
+        }
+    }
+
+    /// Extend `FieldSet` with the given item.
+    ///
+    /// ### Example
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// // Suppose field defined as:
+    /// let extend_fields_1 = FieldSet::new_field(0..1).extend(FieldSet::new_field(1..2));
+    /// let extend_fields_2 = FieldSet::new_field(0..1).extend(
+    ///     FieldSet::Seq(vec![
+    ///         FieldSet::new_field(1..2),
+    ///         FieldSet::new_field(2..3),
+    ///     ])
+    /// );
+    ///
+    /// // Are identical to:
+    /// let fields_1 = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..1),
+    ///     FieldSet::new_field(1..2),
+    /// ]);
+    /// let fields_2 = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..1),
+    ///     FieldSet::new_field(1..2),
+    ///     FieldSet::new_field(2..3),
+    /// ]);
+    ///
+    /// assert_eq!(extend_fields_1, fields_1);
+    /// assert_eq!(extend_fields_2, fields_2);
+    /// ```
+    pub fn extend(self, item: Self) -> Self {
+        match self {
+            Self::Item(_) => match item {
+                Self::Item(_) => self.append(item),
+                Self::Seq(_) => Self::Seq(vec![self]).extend(item),
+            },
+            Self::Seq(mut seq) => {
+                seq.extend(item);
+                Self::Seq(seq)
+            }
+        }
+    }
+
+    /// Finds the first field with the given name, searching nested fields in order.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(4..10).name("amount")]),
+    /// ]);
+    ///
+    /// assert_eq!(fields.get("amount").unwrap().range(), 4..10);
+    /// assert!(fields.get("missing").is_none());
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&FieldConfig> {
+        match self {
+            Self::Item(conf) if conf.name.as_deref() == Some(name) => Some(conf),
+            Self::Item(_) => None,
+            Self::Seq(seq) => seq.iter().find_map(|fs| fs.get(name)),
+        }
+    }
+
+    /// Finds the first field with the given name, searching nested fields in order, so that it
+    /// can be changed in place.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Justify};
+    ///
+    /// let mut fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("amount"),
+    /// ]);
+    ///
+    /// let amount = fields.get_mut("amount").unwrap();
+    /// amount.set_justify(Justify::Right);
+    /// amount.set_pad_with('0');
+    ///
+    /// assert_eq!(fields.get("amount").unwrap().pad_with(), '0');
+    /// ```
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut FieldConfig> {
+        match self {
+            Self::Item(conf) if conf.name.as_deref() == Some(name) => Some(conf),
+            Self::Item(_) => None,
+            Self::Seq(seq) => seq.iter_mut().find_map(|fs| fs.get_mut(name)),
+        }
+    }
+
+    /// Transforms the first field with the given name, leaving the rest of the layout and its
+    /// nesting as it is. The layout is returned unchanged if no field has the name.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Justify};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("amount"),
+    /// ]);
+    ///
+    /// let fields = fields.map_field("amount", |mut conf| {
+    ///     conf.set_justify(Justify::Right);
+    ///     conf.set_pad_with('0');
+    ///     conf
+    /// });
+    ///
+    /// assert_eq!(fields, FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("amount").justify(Justify::Right).pad_with('0'),
+    /// ]));
+    /// ```
+    pub fn map_field<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: FnOnce(FieldConfig) -> FieldConfig,
+    {
+        if let Some(conf) = self.get_mut(name) {
+            *conf = f(std::mem::take(conf));
+        }
+        self
+    }
+
+    /// The names that are given to more than one field, each listed once in the order they
+    /// first repeat. Useful to check a layout before deserializing into a map, where later
+    /// fields would overwrite earlier ones.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(4..6).name("id")]),
+    ///     FieldSet::new_field(6..8),
+    /// ]);
+    ///
+    /// assert_eq!(fields.duplicate_names(), vec!["id"]);
+    /// ```
+    pub fn duplicate_names(&self) -> Vec<String> {
+        fn collect<'a>(fs: &'a FieldSet, seen: &mut Vec<&'a str>, dups: &mut Vec<String>) {
+            match fs {
+                FieldSet::Item(conf) => {
+                    if let Some(ref name) = conf.name {
+                        if !seen.contains(&name.as_str()) {
+                            seen.push(name);
+                        } else if !dups.contains(name) {
+                            dups.push(name.clone());
+                        }
+                    }
+                }
+                FieldSet::Seq(seq) => seq.iter().for_each(|fs| collect(fs, seen, dups)),
+            }
+        }
+
+        let mut dups = Vec::new();
+        collect(self, &mut Vec::new(), &mut dups);
+        dups
+    }
+
+    /// Whether the two layouts are the same but for the names of their fields, that is whether
+    /// they read and write records in the same way by position.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..8).name("amount"),
     /// ]);
+    /// let renamed = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("customer_id"),
+    ///     FieldSet::new_field(4..8),
+    /// ]);
+    ///
+    /// assert_ne!(fields, renamed);
+    /// assert!(fields.structurally_equal_ignoring_names(&renamed));
+    /// assert!(!fields.structurally_equal_ignoring_names(&renamed.pad_with('0')));
     /// ```
-    pub fn name<T: Into<String>>(mut self, val: T) -> Self {
-        match &mut self {
-            Self::Item(conf) => {
-                conf.name = Some(val.into());
-                self
+    pub fn structurally_equal_ignoring_names(&self, other: &FieldSet) -> bool {
+        match (self, other) {
+            (Self::Item(a), Self::Item(b)) => {
+                let unnamed = |conf: &FieldConfig| FieldConfig {
+                    name: None,
+                    ..conf.clone()
+                };
+                unnamed(a) == unnamed(b)
             }
-            _ => panic!("Setting name on FieldSet::Seq is not feasible."),
+            (Self::Seq(a), Self::Seq(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|(a, b)| a.structurally_equal_ignoring_names(b))
+            }
+            _ => false,
         }
     }
 
-    /// Sets the character to use as padding the value of this field to its byte width.
+    /// The number of bytes spanned by this layout, i.e. the largest end of any field's range.
     ///
     /// ### Example
     ///
     /// ```rust
     /// use fixed_width::FieldSet;
     ///
-    /// let field = FieldSet::new_field(0..1).pad_with('x');
     /// let fields = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1),
-    ///     FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(0..3)]),
-    /// ])
-    /// .pad_with('x');
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::Seq(vec![FieldSet::new_field(4..6), FieldSet::new_field(8..10)]),
+    /// ]);
+    ///
+    /// assert_eq!(fields.total_width(), 10);
     /// ```
-    pub fn pad_with(mut self, val: char) -> Self {
+    pub fn total_width(&self) -> usize {
         match self {
-            Self::Item(ref mut config) => {
-                config.pad_with = val;
-                self
-            }
-            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.pad_with(val)).collect()),
+            Self::Item(conf) => conf.range.end,
+            Self::Seq(seq) => seq.iter().map(Self::total_width).max().unwrap_or(0),
         }
     }
 
-    /// Sets the justification to use fields. Left will align to the left and Right to the
-    /// right.
+    /// The end of the last field, or `None` if a field runs to the end of the record.
+    pub(crate) fn record_end(&self) -> Option<usize> {
+        match self {
+            Self::Item(conf) if conf.to_end => None,
+            Self::Item(conf) => Some(conf.range.end),
+            Self::Seq(seq) => seq
+                .iter()
+                .try_fold(0, |end, fs| fs.record_end().map(|e| end.max(e))),
+        }
+    }
+
+    /// Converts `FieldSet` into flatten `Vec<FieldConfig>`.
     ///
     /// ### Example
     ///
     /// ```rust
-    /// use fixed_width::{FieldSet, Justify};
+    /// use fixed_width::{FieldConfig, FieldSet};
     ///
-    /// let field = FieldSet::new_field(0..1).justify(Justify::Right);
     /// let fields = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1),
-    ///     FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(0..3)]),
-    /// ])
-    /// .justify(Justify::Right);
+    ///     FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..2)]),
+    ///     FieldSet::new_field(2..3)
+    /// ]);
+    /// let flatten_fields = vec![
+    ///     FieldConfig::new(0..1), FieldConfig::new(1..2), FieldConfig::new(2..3)
+    /// ];
+    ///
+    /// assert_eq!(fields.flatten(), flatten_fields);
     /// ```
-    pub fn justify<T: Into<Justify>>(mut self, val: T) -> Self {
-        let val = val.into();
-        match self {
-            Self::Item(ref mut config) => {
-                config.justify = val;
-                self
+    pub fn flatten(self) -> Vec<FieldConfig> {
+        let mut flatten = vec![];
+        let mut stack = vec![vec![self].into_iter()];
+
+        while let Some(last) = stack.last_mut() {
+            match last.next() {
+                Some(FieldSet::Item(conf)) => flatten.push(conf),
+                Some(FieldSet::Seq(seq)) => stack.push(seq.into_iter()),
+                None => {
+                    stack.pop();
+                }
             }
-            Self::Seq(seq) => Self::Seq(seq.into_iter().map(|fs| fs.justify(val)).collect()),
         }
+
+        flatten
     }
 
-    /// Append `FieldSet` with the given item.
+    /// Iterates over the fields of the `FieldSet` by reference, depth first, in the same order as
+    /// `flatten` but without cloning or consuming the `FieldSet`.
     ///
     /// ### Example
+    ///
     /// ```rust
     /// use fixed_width::FieldSet;
     ///
-    /// // Suppose field defined as:
-    /// let append_fields_1 = FieldSet::new_field(0..1).append(FieldSet::new_field(1..2));
-    /// let append_fields_2 = FieldSet::new_field(0..1).append(
-    ///     FieldSet::Seq(vec![
-    ///         FieldSet::new_field(1..2),
-    ///         FieldSet::new_field(2..3),
-    ///     ])
-    /// );
-    ///
-    /// // Are identical to:
-    /// let fields_1 = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1),
-    ///     FieldSet::new_field(1..2),
-    /// ]);
-    /// let fields_2 = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1),
-    ///     FieldSet::Seq(vec![
-    ///         FieldSet::new_field(1..2),
-    ///         FieldSet::new_field(2..3),
-    ///     ]),
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..2)]),
+    ///     FieldSet::new_field(2..3)
     /// ]);
+    /// let ranges: Vec<_> = fields.iter_flat().map(|conf| conf.range()).collect();
     ///
-    /// # assert_eq!(
-    /// #     format!("{:?}", append_fields_1),
-    /// #     format!("{:?}", FieldSet::Seq(vec![
-    /// #         FieldSet::new_field(0..1),
-    /// #         FieldSet::new_field(1..2),
-    /// #     ]))
-    /// # );
-    /// # assert_eq!(
-    /// #     format!("{:?}", append_fields_2),
-    /// #     format!("{:?}", FieldSet::Seq(vec![
-    /// #         FieldSet::new_field(0..1),
-    /// #         FieldSet::Seq(vec![
-    /// #             FieldSet::new_field(1..2),
-    /// #             FieldSet::new_field(2..3),
-    /// #         ]),
-    /// #     ])),
-    /// # );
+    /// assert_eq!(ranges, vec![0..1, 1..2, 2..3]);
     /// ```
-    pub fn append(self, item: Self) -> Self {
-        match self {
-            Self::Item(_) => Self::Seq(vec![self, item]),
-            Self::Seq(mut seq) => {
-                seq.append(&mut vec![item]);
-                Self::Seq(seq)
-            }
+    pub fn iter_flat(&self) -> impl Iterator<Item = &FieldConfig> {
+        FlatIter {
+            stack: vec![std::slice::from_ref(self).iter()],
         }
     }
 
-    /// Extend `FieldSet` with the given item.
+    /// Renders the fields as a table of their index, name, range, width, padding, justification
+    /// and any other settings, in the order of their ranges. Bytes no field covers are shown as
+    /// gaps between the fields, and fields that overlap are noted. The table only changes when
+    /// the fields do, so it can be logged to record the layout a job ran with.
     ///
     /// ### Example
+    ///
     /// ```rust
     /// use fixed_width::FieldSet;
     ///
-    /// // Suppose field defined as:
-    /// let extend_fields_1 = FieldSet::new_field(0..1).extend(FieldSet::new_field(1..2));
-    /// let extend_fields_2 = FieldSet::new_field(0..1).extend(
-    ///     FieldSet::Seq(vec![
-    ///         FieldSet::new_field(1..2),
-    ///         FieldSet::new_field(2..3),
-    ///     ])
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id").pad_with('0').justify("right"),
+    ///     FieldSet::new_field(6..14).name("amount").decimals(2),
+    ///     FieldSet::new_field(12..16).name("code"),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     fields.describe(),
+    ///     "\
+    /// ##  name    range   width  pad  justify  options
+    /// 0  id      0..4    4      '0'  right
+    ///    (gap)   4..6    2
+    /// 1  amount  6..14   8      ' '  left     decimals=2  (overlaps code)
+    /// 2  code    12..16  4      ' '  left                 (overlaps amount)
+    /// "
     /// );
+    /// ```
+    pub fn describe(&self) -> String {
+        debug::layout(self)
+    }
+
+    /// Splits a record into the raw bytes of each field, in the same order as `iter_flat`,
+    /// without deserializing them. A field that runs to the end of the record gets the rest of
+    /// it, and a `capture_only` field gets the whole record. A field past the end of the record
+    /// is a `SplitError`, and the fields after it are still split.
     ///
-    /// // Are identical to:
-    /// let fields_1 = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1),
-    ///     FieldSet::new_field(1..2),
-    /// ]);
-    /// let fields_2 = FieldSet::Seq(vec![
-    ///     FieldSet::new_field(0..1),
-    ///     FieldSet::new_field(1..2),
-    ///     FieldSet::new_field(2..3),
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("name"),
     /// ]);
     ///
-    /// # assert_eq!(
-    /// #     format!("{:?}", extend_fields_1),
-    /// #     format!("{:?}", FieldSet::Seq(vec![
-    /// #         FieldSet::new_field(0..1),
-    /// #         FieldSet::new_field(1..2),
-    /// #     ]))
-    /// # );
-    /// # assert_eq!(
-    /// #     format!("{:?}", extend_fields_2),
-    /// #     format!("{:?}", FieldSet::Seq(vec![
-    /// #         FieldSet::new_field(0..1),
-    /// #         FieldSet::new_field(1..2),
-    /// #         FieldSet::new_field(2..3),
-    /// #     ])),
-    /// # );
+    /// let mut split = fields.split(b"0042Carl  ");
+    /// let (conf, bytes) = split.next().unwrap().unwrap();
+    /// assert_eq!((conf.name(), bytes), (Some("id"), &b"0042"[..]));
+    /// let (conf, bytes) = split.next().unwrap().unwrap();
+    /// assert_eq!((conf.name(), bytes), (Some("name"), &b"Carl  "[..]));
+    /// assert!(split.next().is_none());
+    ///
+    /// let err = fields.split(b"0042Ca").nth(1).unwrap().unwrap_err();
+    /// assert_eq!(err.field, "name");
+    /// assert_eq!(err.record_len, 6);
     /// ```
-    pub fn extend(self, item: Self) -> Self {
-        match self {
-            Self::Item(_) => match item {
-                Self::Item(_) => self.append(item),
-                Self::Seq(_) => Self::Seq(vec![self]).extend(item),
-            },
-            Self::Seq(mut seq) => {
-                seq.extend(item);
-                Self::Seq(seq)
-            }
-        }
+    pub fn split<'a>(
+        &'a self,
+        record: &'a [u8],
+    ) -> impl Iterator<Item = result::Result<(&'a FieldConfig, &'a [u8]), SplitError>> {
+        self.iter_flat().map(move |conf| match conf.slice(record) {
+            Some(bytes) => Ok((conf, bytes)),
+            None => Err(SplitError {
+                field: conf.describe(),
+                range: conf.range.clone(),
+                record_len: record.len(),
+            }),
+        })
     }
 
-    /// Converts `FieldSet` into flatten `Vec<FieldConfig>`.
+    /// Splits a record into the bytes of each field as `split` does, with the padding of each
+    /// field trimmed from them as its justification says, along with any spaces around them.
     ///
     /// ### Example
     ///
     /// ```rust
-    /// use fixed_width::{FieldConfig, FieldSet};
+    /// use fixed_width::{FieldSet, Justify};
     ///
     /// let fields = FieldSet::Seq(vec![
-    ///     FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..2)]),
-    ///     FieldSet::new_field(2..3)
+    ///     FieldSet::new_field(0..6).pad_with('0').justify(Justify::Right),
+    ///     FieldSet::new_field(6..12),
     /// ]);
-    /// let flatten_fields = vec![
-    ///     FieldConfig::new(0..1), FieldConfig::new(1..2), FieldConfig::new(2..3)
-    /// ];
     ///
-    /// assert_eq!(format!("{:?}", fields.flatten()), format!("{:?}", flatten_fields));
+    /// let values: Vec<&[u8]> = fields
+    ///     .split_trimmed(b"000042Carl  ")
+    ///     .map(|field| field.unwrap().1)
+    ///     .collect();
+    /// assert_eq!(values, vec![&b"42"[..], &b"Carl"[..]]);
     /// ```
-    pub fn flatten(self) -> Vec<FieldConfig> {
-        let mut flatten = vec![];
-        let mut stack = vec![vec![self]];
-
-        while !stack.is_empty() {
-            let last = stack.last_mut().unwrap();
-            if last.is_empty() {
-                stack.pop();
-            } else {
-                let field = last.drain(..1).next().unwrap();
-                match field {
-                    FieldSet::Item(conf) => flatten.push(conf),
-                    FieldSet::Seq(seq) => stack.push(seq.to_vec()),
+    pub fn split_trimmed<'a>(
+        &'a self,
+        record: &'a [u8],
+    ) -> impl Iterator<Item = result::Result<(&'a FieldConfig, &'a [u8]), SplitError>> {
+        self.split(record)
+            .map(|field| field.map(|(conf, bytes)| (conf, conf.trim(bytes))))
+    }
+}
+
+/// A depth first iterator over the fields of a `FieldSet`, by reference.
+struct FlatIter<'a> {
+    stack: Vec<std::slice::Iter<'a, FieldSet>>,
+}
+
+impl<'a> Iterator for FlatIter<'a> {
+    type Item = &'a FieldConfig;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(last) = self.stack.last_mut() {
+            match last.next() {
+                Some(FieldSet::Item(conf)) => return Some(conf),
+                Some(FieldSet::Seq(seq)) => self.stack.push(seq.iter()),
+                None => {
+                    self.stack.pop();
                 }
             }
         }
-
-        flatten
+        None
     }
 }
 
@@ -443,7 +2198,7 @@ impl IntoIterator for FieldSet {
 }
 
 /// The type of line break between each record that should be inserted or skipped while reading.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LineBreak {
     /// No linebreak
     None,
@@ -476,6 +2231,68 @@ impl LineBreak {
             LineBreak::CRLF => 2,
         }
     }
+
+    /// The bytes of the given line break.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::LineBreak;
+    ///
+    /// assert_eq!(LineBreak::None.as_bytes(), b"");
+    /// assert_eq!(LineBreak::Newline.as_bytes(), b"\n");
+    /// assert_eq!(LineBreak::CRLF.as_bytes(), b"\r\n");
+    /// ```
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineBreak::None => b"",
+            LineBreak::Newline => b"\n",
+            LineBreak::CRLF => b"\r\n",
+        }
+    }
+}
+
+/// The length prefix that precedes each variable length record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthPrefix {
+    /// An IBM record descriptor word: a 2 byte big endian length followed by 2 reserved bytes.
+    /// The length includes the 4 bytes of the descriptor word itself.
+    Rdw,
+    /// A 2 byte big endian length of the record data, followed by `skip` bytes that are ignored.
+    BigEndianU16 {
+        /// The number of bytes to ignore after the length.
+        skip: usize,
+    },
+}
+
+impl LengthPrefix {
+    /// The width in bytes of the given length prefix.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::LengthPrefix;
+    ///
+    /// assert_eq!(LengthPrefix::Rdw.byte_width(), 4);
+    /// assert_eq!(LengthPrefix::BigEndianU16 { skip: 0 }.byte_width(), 2);
+    /// ```
+    pub fn byte_width(&self) -> usize {
+        match self {
+            LengthPrefix::Rdw => 4,
+            LengthPrefix::BigEndianU16 { skip } => 2 + skip,
+        }
+    }
+
+    /// The number of bytes of record data described by the given prefix bytes, or `None` if
+    /// the prefix describes an impossible length.
+    fn record_width(&self, prefix: &[u8]) -> Option<usize> {
+        let len = u16::from_be_bytes([prefix[0], prefix[1]]) as usize;
+
+        match self {
+            LengthPrefix::Rdw => len.checked_sub(4),
+            LengthPrefix::BigEndianU16 { .. } => Some(len),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -489,6 +2306,16 @@ mod test {
         assert_eq!(LineBreak::CRLF.byte_width(), 2);
     }
 
+    #[test]
+    fn length_prefix_record_width() {
+        assert_eq!(LengthPrefix::Rdw.record_width(&[0, 10, 0, 0]), Some(6));
+        assert_eq!(LengthPrefix::Rdw.record_width(&[0, 2, 0, 0]), None);
+        assert_eq!(
+            LengthPrefix::BigEndianU16 { skip: 1 }.record_width(&[1, 0, 0]),
+            Some(256)
+        );
+    }
+
     #[test]
     fn fieldset_name() {
         let field = FieldSet::new_field(0..0).name("foo");
@@ -541,6 +2368,56 @@ mod test {
         }
     }
 
+    #[test]
+    fn justify_from_str() {
+        assert_eq!(Justify::from("Left"), Justify::Left);
+        assert_eq!(Justify::from(" right "), Justify::Right);
+        assert_eq!(Justify::from("center"), Justify::Center);
+        assert_eq!(Justify::from("CENTRE"), Justify::Center);
+    }
+
+    #[test]
+    fn justify_parse_errors() {
+        assert_eq!(" centre ".parse::<Justify>(), Ok(Justify::Center));
+
+        let err = "".parse::<Justify>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "justify must be 'left', 'right' or 'center', got \"\""
+        );
+
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..2)]);
+        assert_eq!(
+            fields.clone().try_justify("LEFT").unwrap(),
+            fields.clone().justify(Justify::Left)
+        );
+        match fields.try_justify("sideways") {
+            Err(Error::InvalidFieldSet(message)) => assert_eq!(
+                message,
+                "justify must be 'left', 'right' or 'center', got \"sideways\""
+            ),
+            other => panic!("expected an invalid field set, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fieldset_try_name() {
+        let field = FieldSet::new_field(0..1).try_name("a").unwrap();
+        assert_eq!(field, FieldSet::new_field(0..1).name("a"));
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1).name("a"),
+            FieldSet::Seq(vec![FieldSet::new_field(1..3)]),
+        ]);
+        match fields.try_name("b") {
+            Err(Error::InvalidFieldSet(message)) => assert_eq!(
+                message,
+                "cannot name a FieldSet::Seq \"b\", it holds a, 1..3"
+            ),
+            other => panic!("expected an invalid field set, got {:?}", other),
+        }
+    }
+
     #[test]
     #[should_panic]
     fn fieldset_justify_panic() {
@@ -551,6 +2428,18 @@ mod test {
         .justify("foo");
     }
 
+    #[test]
+    fn fieldset_total_width() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4),
+            FieldSet::Seq(vec![FieldSet::new_field(12..16), FieldSet::new_field(4..8)]),
+        ]);
+
+        assert_eq!(fields.total_width(), 16);
+        assert_eq!(FieldSet::new_field(3..9).total_width(), 9);
+        assert_eq!(FieldSet::Seq(vec![]).total_width(), 0);
+    }
+
     #[test]
     fn field_building() {
         let field = FieldSet::new_field(0..10)
@@ -564,4 +2453,242 @@ mod test {
         assert_eq!(field.pad_with, 'a');
         assert_eq!(field.justify, Justify::Right);
     }
+
+    #[test]
+    fn fieldset_equality() {
+        use std::collections::HashSet;
+
+        let fields = || {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..4).name("foo"),
+                FieldSet::Seq(vec![FieldSet::new_field(4..6), FieldSet::new_field(6..8)]),
+            ])
+        };
+
+        assert_eq!(fields(), fields());
+        assert_ne!(fields(), fields().pad_with('0'));
+        assert_ne!(fields(), fields().justify(Justify::Right));
+        assert_ne!(
+            FieldSet::new_field(0..4).name("foo"),
+            FieldSet::new_field(0..4).name("bar")
+        );
+        assert_ne!(
+            fields(),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..4).name("foo"),
+                FieldSet::new_field(4..6),
+                FieldSet::new_field(6..8),
+            ])
+        );
+
+        let set: HashSet<FieldSet> = vec![fields(), fields(), fields().pad_with('0')]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn fieldset_hook_equality() {
+        let hooked = FieldSet::new_field(0..4).deserialize_with(|b| Ok(b.len().to_string()));
+
+        assert_eq!(hooked, hooked.clone());
+        assert_ne!(
+            hooked,
+            FieldSet::new_field(0..4).deserialize_with(|b| Ok(b.len().to_string()))
+        );
+        assert_ne!(hooked, FieldSet::new_field(0..4));
+    }
+
+    #[test]
+    fn fieldset_structural_equality() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("foo"),
+            FieldSet::Seq(vec![FieldSet::new_field(4..6).name("bar")]),
+        ]);
+        let renamed = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4),
+            FieldSet::Seq(vec![FieldSet::new_field(4..6).name("baz")]),
+        ]);
+        let flattened = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..6)]);
+
+        assert!(fields.structurally_equal_ignoring_names(&renamed));
+        assert!(!fields.structurally_equal_ignoring_names(&flattened));
+        assert!(!fields.structurally_equal_ignoring_names(&renamed.justify(Justify::Right)));
+    }
+
+    #[test]
+    fn fieldset_lookup() {
+        let mut fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(4..6).name("street"),
+                FieldSet::Seq(vec![FieldSet::new_field(6..8).name("city")]),
+            ]),
+            FieldSet::new_field(8..10).name("city"),
+        ]);
+
+        assert_eq!(fields.get("city").unwrap().range(), 6..8);
+        assert_eq!(fields.get("street").unwrap().name(), Some("street"));
+        assert!(fields.get("zip").is_none());
+        assert!(FieldSet::new_field(0..1).get("id").is_none());
+
+        fields.get_mut("street").unwrap().set_range(4..7).unwrap();
+        assert!(fields.get_mut("zip").is_none());
+        assert_eq!(fields.get("street").unwrap().range(), 4..7);
+
+        let (start, end) = (7, 4);
+        let street = fields.get_mut("street").unwrap();
+        let err = street.set_range(start..end).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid field set: range 7..4 starts after it ends"
+        );
+        assert_eq!(fields.get("street").unwrap().range(), 4..7);
+
+        let fields = fields
+            .map_field("city", |mut conf| {
+                conf.set_name("town");
+                conf.set_justify("right");
+                conf
+            })
+            .map_field("zip", |_| unreachable!());
+
+        assert_eq!(
+            fields,
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..4).name("id"),
+                FieldSet::Seq(vec![
+                    FieldSet::new_field(4..7).name("street"),
+                    FieldSet::Seq(vec![FieldSet::new_field(6..8)
+                        .name("town")
+                        .justify(Justify::Right)]),
+                ]),
+                FieldSet::new_field(8..10).name("city"),
+            ])
+        );
+    }
+
+    #[test]
+    fn fieldset_duplicate_names() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1).name("b"),
+            FieldSet::new_field(1..2).name("a"),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(2..3).name("a"),
+                FieldSet::new_field(3..4).name("b"),
+            ]),
+            FieldSet::new_field(4..5).name("a"),
+            FieldSet::new_field(5..6),
+            FieldSet::new_field(6..7),
+        ]);
+
+        assert_eq!(fields.duplicate_names(), vec!["a", "b"]);
+        assert!(FieldSet::from_widths(&[1, 2]).duplicate_names().is_empty());
+    }
+
+    #[test]
+    fn fieldset_iter_flat() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1).name("a"),
+            FieldSet::Seq(vec![]),
+            FieldSet::Seq(vec![
+                FieldSet::new_literal(1..2, "-"),
+                FieldSet::Seq(vec![FieldSet::new_field(2..3).name("b")]),
+            ]),
+            FieldSet::new_field(3..4),
+        ]);
+
+        let by_ref: Vec<FieldConfig> = fields.iter_flat().cloned().collect();
+        assert_eq!(by_ref, fields.clone().flatten());
+        assert_eq!(by_ref.len(), 4);
+
+        let item = FieldSet::new_field(0..1);
+        assert_eq!(
+            item.iter_flat().collect::<Vec<_>>(),
+            vec![&FieldConfig::new(0..1)]
+        );
+        assert_eq!(FieldSet::Seq(vec![]).iter_flat().count(), 0);
+    }
+
+    #[test]
+    fn fieldset_split() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("kind"),
+            FieldSet::Seq(vec![
+                FieldSet::new_literal(2..3, "|"),
+                FieldSet::Seq(vec![
+                    FieldSet::new_field(3..8)
+                        .pad_with('0')
+                        .justify(Justify::Right),
+                    FieldSet::new_field(8..12)
+                        .justify(Justify::Center)
+                        .pad_with('*'),
+                ]),
+            ]),
+            FieldSet::new_field_to_end(12).name("rest"),
+        ]);
+
+        let record = b"AB|00420*xy*free text ";
+        let split: Vec<(Option<&str>, &[u8])> = fields
+            .split(record)
+            .map(|field| field.map(|(conf, bytes)| (conf.name(), bytes)).unwrap())
+            .collect();
+        assert_eq!(
+            split,
+            vec![
+                (Some("kind"), &b"AB"[..]),
+                (None, &b"|"[..]),
+                (None, &b"00420"[..]),
+                (None, &b"*xy*"[..]),
+                (Some("rest"), &b"free text "[..]),
+            ]
+        );
+
+        let trimmed: Vec<&[u8]> = fields
+            .split_trimmed(record)
+            .map(|field| field.unwrap().1)
+            .collect();
+        assert_eq!(trimmed, vec![&b"AB"[..], b"|", b"420", b"xy", b"free text"]);
+
+        let zero_filled = FieldSet::new_field(0..4)
+            .pad_with('0')
+            .justify(Justify::Right);
+        let zero: Vec<&[u8]> = zero_filled
+            .split_trimmed(b"0000")
+            .map(|field| field.unwrap().1)
+            .collect();
+        assert_eq!(zero, vec![&b"0"[..]]);
+    }
+
+    #[test]
+    fn fieldset_split_short_record() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::Seq(vec![FieldSet::new_field(4..8), FieldSet::new_field(8..10)]),
+            FieldSet::new_field_to_end(10).name("rest"),
+            FieldSet::new_field(0..1).capture_only(true),
+        ]);
+
+        let split: Vec<_> = fields.split(b"0042abcd").collect();
+        assert_eq!(split.len(), 5);
+        assert_eq!(split[0].as_ref().unwrap().1, b"0042");
+        assert_eq!(split[1].as_ref().unwrap().1, b"abcd");
+        assert_eq!(
+            split[2].as_ref().unwrap_err(),
+            &SplitError {
+                field: "8..10".to_string(),
+                range: 8..10,
+                record_len: 8,
+            }
+        );
+        let err = split[3].as_ref().unwrap_err();
+        assert_eq!(err.field, "rest");
+        assert_eq!(
+            err.to_string(),
+            "field rest is past the end of the record, which is 8 bytes long"
+        );
+        assert_eq!(split[4].as_ref().unwrap().1, b"0042abcd");
+
+        assert_eq!(fields.split(b"").filter(|field| field.is_err()).count(), 4);
+    }
 }
@@ -27,7 +27,7 @@
 ///     ]
 /// ];
 ///
-/// assert_eq!(format!("{:?}", fields), format!("{:?}", fields_with_macro));
+/// assert_eq!(fields, fields_with_macro);
 /// ```
 #[macro_export]
 macro_rules! field_seq {
@@ -0,0 +1,188 @@
+use crate::{LineBreak, Reader, Result};
+use memmap2::Mmap;
+use std::{fs, path::Path};
+
+/// A fixed width data reader over a memory mapped file. Records are yielded as slices of the
+/// mapping, so nothing is copied and deserialized `&str` or `&[u8]` fields can borrow directly
+/// from the file.
+///
+/// Every record borrows from the `MmapReader`, so the reader must outlive any value that was
+/// deserialized with borrowed fields. The file must also not be modified while it is mapped.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+/// use fixed_width::{FieldSet, LineBreak, Reader};
+/// # use std::io::Write;
+///
+/// #[derive(Deserialize)]
+/// struct Record<'a> {
+///     name: &'a str,
+///     age: &'a str,
+/// }
+///
+/// # let path = std::env::temp_dir().join("fixed_width_mmap_doc.txt");
+/// # std::fs::File::create(&path).unwrap().write_all(b"foobar1234\nfoobaz6789").unwrap();
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6),
+///     FieldSet::new_field(6..10),
+/// ]);
+/// let reader = Reader::from_mmap(&path).unwrap().width(10).linebreak(LineBreak::Newline);
+///
+/// for bytes in reader.records() {
+///     let record: Record = fixed_width::from_bytes_borrowed(bytes, &fields).unwrap();
+///     println!("{} is {}", record.name, record.age);
+/// }
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct MmapReader {
+    map: Mmap,
+    /// The width in bytes of the record. Required in order to parse.
+    pub record_width: usize,
+    /// The line break that occurs between each record. Defaults to `LineBreak::None`
+    pub linebreak: LineBreak,
+}
+
+/// An iterator of records borrowed from a `MmapReader`.
+///
+/// The lifetime 'a denotes the lifetime of the memory mapped reader.
+pub struct MmapRecords<'a> {
+    data: &'a [u8],
+    record_width: usize,
+    linebreak_width: usize,
+}
+
+impl MmapReader {
+    /// Defines the width of each record in the file. See `Reader::width`.
+    pub fn width(mut self, width: usize) -> Self {
+        self.record_width = width;
+        self
+    }
+
+    /// Defines the linebreak to use while reading data. See `Reader::linebreak`.
+    pub fn linebreak(mut self, linebreak: LineBreak) -> Self {
+        self.linebreak = linebreak;
+        self
+    }
+
+    /// Gets a reference to the whole memory mapped file.
+    pub fn get_ref(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Iterates over each record of the file as a slice of the mapping. A trailing partial
+    /// record is ignored, as with `Reader::next_record`.
+    pub fn records(&self) -> MmapRecords<'_> {
+        MmapRecords {
+            data: if self.record_width == 0 {
+                &[]
+            } else {
+                &self.map
+            },
+            record_width: self.record_width,
+            linebreak_width: self.linebreak.byte_width(),
+        }
+    }
+}
+
+impl<'a> Iterator for MmapRecords<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < self.record_width || self.record_width == 0 {
+            return None;
+        }
+
+        let (record, rest) = self.data.split_at(self.record_width);
+        self.data = &rest[self.linebreak_width.min(rest.len())..];
+
+        Some(record)
+    }
+}
+
+impl Reader<fs::File> {
+    /// Memory maps the file at the given path for zero copy reading. Will return an io::Error if
+    /// there are any issues opening or mapping the file.
+    pub fn from_mmap<P: AsRef<Path>>(path: P) -> Result<MmapReader> {
+        let file = fs::File::open(path)?;
+        // Safety: the mapping is read only, and callers are told not to modify the file while it
+        // is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+
+        Ok(MmapReader {
+            map,
+            record_width: 0,
+            linebreak: LineBreak::None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{from_bytes_borrowed, FieldSet, LineBreak, Reader};
+    use serde_derive::Deserialize;
+    use std::{env, fs, path::PathBuf};
+
+    #[derive(Deserialize)]
+    struct Rec<'a> {
+        name: &'a str,
+        code: &'a [u8],
+        age: usize,
+    }
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..6),
+            FieldSet::new_field(6..8),
+            FieldSet::new_field(8..10),
+        ])
+    }
+
+    fn write_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn read_borrowed_records() {
+        let path = write_file(
+            "fixed_width_mmap_borrowed.txt",
+            "foo   ab25\r\nbarbazcd30\r\n",
+        );
+        let reader = Reader::from_mmap(&path)
+            .unwrap()
+            .width(10)
+            .linebreak(LineBreak::CRLF);
+        let fields = fields();
+
+        let records: Vec<Rec> = reader
+            .records()
+            .map(|bytes| from_bytes_borrowed(bytes, &fields).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].name, "foo");
+        assert_eq!(records[0].code, b"ab");
+        assert_eq!(records[0].age, 25);
+        assert_eq!(records[1].name, "barbaz");
+        assert_eq!(records[1].code, b"cd");
+        assert_eq!(records[1].age, 30);
+
+        let range = reader.get_ref().as_ptr_range();
+        assert!(range.contains(&records[1].name.as_ptr()));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn ignore_partial_trailing_record() {
+        let path = write_file("fixed_width_mmap_partial.txt", "foo   ab25barbaz");
+        let reader = Reader::from_mmap(&path).unwrap().width(10);
+
+        assert_eq!(reader.records().collect::<Vec<_>>(), vec![b"foo   ab25"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
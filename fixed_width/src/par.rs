@@ -0,0 +1,205 @@
+use crate::{de, error::RecordError, FieldSet, FixedWidth, Reader};
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use std::{io::Read, marker::PhantomData, vec};
+
+const BATCH_SIZE: usize = 4096;
+
+/// An iterator of records deserialized into `T` in parallel. Records are read in batches and
+/// each batch is deserialized on the rayon thread pool, but results are yielded in the same
+/// order as the data.
+///
+/// The lifetime 'a denotes the lifetime of the reader, R.
+pub struct ParDeserializeReader<'a, R: 'a, T> {
+    r: &'a mut Reader<R>,
+    fields: FieldSet,
+    batch: vec::IntoIter<Result<T, RecordError>>,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Reads each record of the data and deserializes it into `T` on the rayon thread pool.
+    /// Failed records are reported with their index and raw bytes, as with
+    /// `deserialize_lossy`, and iteration continues after them.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde_derive::Deserialize;
+    /// use fixed_width::{FieldSet, FixedWidth, Reader};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Foo {
+    ///     name: String,
+    ///     age: usize,
+    /// }
+    ///
+    /// impl FixedWidth for Foo {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![
+    ///             FieldSet::new_field(0..6),
+    ///             FieldSet::new_field(6..10),
+    ///         ])
+    ///     }
+    /// }
+    ///
+    /// let mut reader = Reader::from_string("foobar1234foobaz6789").width(10);
+    /// let records: Vec<Foo> = reader.par_deserialize().collect::<Result<_, _>>().unwrap();
+    ///
+    /// assert_eq!(records[0].name, "foobar");
+    /// assert_eq!(records[1].name, "foobaz");
+    /// ```
+    pub fn par_deserialize<T>(&mut self) -> ParDeserializeReader<'_, R, T>
+    where
+        T: FixedWidth + DeserializeOwned + Send,
+    {
+        ParDeserializeReader {
+            r: self,
+            fields: T::fields(),
+            batch: Vec::new().into_iter(),
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, R, T> ParDeserializeReader<'a, R, T>
+where
+    R: Read,
+    T: FixedWidth + DeserializeOwned + Send,
+{
+    fn read_batch(&mut self) -> Vec<Result<T, RecordError>> {
+        let mut data = Vec::with_capacity(self.r.record_width * BATCH_SIZE);
+        let mut records = Vec::with_capacity(BATCH_SIZE);
+        let mut failed = None;
+
+        while records.len() < BATCH_SIZE {
+            match self.r.next_record() {
+                None => {
+                    self.done = true;
+                    break;
+                }
+                Some(Ok(record)) => {
+                    let start = data.len();
+                    data.extend_from_slice(record);
+                    records.push((self.r.records_read() - 1, start..data.len()));
+                }
+                Some(Err(error)) => {
+                    self.done = true;
+                    failed = Some(RecordError {
+                        index: self.r.records_read(),
+                        bytes: Vec::new(),
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+
+        let fields = &self.fields;
+        let allow_trailing = self.r.allows_trailing_fields();
+        let data = &data;
+        let mut results: Vec<Result<T, RecordError>> = records
+            .into_par_iter()
+            .map(|(index, range)| {
+                let bytes = &data[range];
+                let mut de = de::Deserializer::with_fields(bytes, fields)
+                    .allow_trailing_fields(allow_trailing);
+                de::deserialize_all(&mut de).map_err(|error| RecordError {
+                    index,
+                    bytes: bytes.to_vec(),
+                    error,
+                })
+            })
+            .collect();
+
+        results.extend(failed.map(Err));
+        results
+    }
+}
+
+impl<'a, R, T> Iterator for ParDeserializeReader<'a, R, T>
+where
+    R: Read,
+    T: FixedWidth + DeserializeOwned + Send,
+{
+    type Item = Result<T, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.batch.next() {
+                return Some(result);
+            }
+
+            if self.done {
+                return None;
+            }
+
+            self.batch = self.read_batch().into_iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{FieldSet, FixedWidth, LineBreak, Reader};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Test {
+        id: usize,
+        name: String,
+    }
+
+    impl FixedWidth for Test {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![FieldSet::new_field(0..6), FieldSet::new_field(6..12)])
+        }
+    }
+
+    fn record(i: usize) -> String {
+        if i % 1000 == 999 {
+            format!("   badname{:02}", i % 100)
+        } else {
+            format!("{:06}name{:02}", i, i % 100)
+        }
+    }
+
+    #[test]
+    fn par_deserialize_matches_sequential() {
+        let s = (0..10_000)
+            .map(record)
+            .collect::<Vec<String>>()
+            .join("\r\n");
+
+        let mut rdr = Reader::from_string(s.as_str())
+            .width(12)
+            .linebreak(LineBreak::CRLF);
+        let parallel: Vec<_> = rdr.par_deserialize::<Test>().collect();
+
+        let mut rdr = Reader::from_string(s.as_str())
+            .width(12)
+            .linebreak(LineBreak::CRLF);
+        let sequential: Vec<_> = rdr.deserialize::<Test>().collect();
+
+        assert_eq!(parallel.len(), 10_000);
+        assert_eq!(parallel.len(), sequential.len());
+
+        for (i, (par, seq)) in parallel.iter().zip(sequential.iter()).enumerate() {
+            match (par, seq) {
+                (Ok(par), Ok(seq)) => assert_eq!(par, seq),
+                (Err(par), Err(_)) => {
+                    assert_eq!(par.index, i as u64);
+                    assert_eq!(par.bytes, record(i).into_bytes());
+                }
+                _ => panic!("results differ at record {}", i),
+            }
+        }
+
+        assert_eq!(parallel.iter().filter(|r| r.is_err()).count(), 10);
+    }
+}
@@ -1,12 +1,95 @@
-use crate::{error::Error, LineBreak, Result};
+use crate::{
+    de,
+    error::{Error, RecordError},
+    FixedWidth, LengthPrefix, LineBreak, Result,
+};
+#[cfg(feature = "ebcdic")]
+use crate::{
+    transcode::{Transcode, Transcoder},
+    FieldSet,
+};
+use serde::de::DeserializeOwned;
 use std::{
+    borrow::Cow,
     fs,
-    io::{self, Read},
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    marker::PhantomData,
+    ops::Range,
     path::Path,
+    str, thread,
+    time::{Duration, Instant},
 };
 
 const BUFFER_SIZE: usize = 8 * (1 << 10);
 
+type WidthFn = Box<dyn Fn(&[u8]) -> Result<usize> + Send>;
+type SeekFn<R> = fn(&mut io::BufReader<R>, SeekFrom) -> io::Result<u64>;
+type ProgressFn = Box<dyn FnMut(u64, u64) + Send>;
+
+struct DynamicWidth {
+    header_len: usize,
+    width_fn: WidthFn,
+}
+
+/// How a reader set to `follow` its data waits for more of it, as `tail -f` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FollowMode {
+    /// How long to sleep before trying again once the end of the data is reached.
+    pub poll_interval: Duration,
+    /// How long to wait for more data before treating the end of the data as final. `None`
+    /// waits forever.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl FollowMode {
+    /// Sleeps for the poll interval, unless no data has arrived since `idle_since` for longer
+    /// than the idle timeout. Returns whether to try reading again.
+    fn wait(&self, idle_since: &mut Option<Instant>) -> bool {
+        let since = *idle_since.get_or_insert_with(Instant::now);
+        if matches!(self.idle_timeout, Some(timeout) if since.elapsed() >= timeout) {
+            return false;
+        }
+
+        thread::sleep(self.poll_interval);
+        true
+    }
+}
+
+/// How a reader reads each line as a record and checks its width, set by `Reader::line_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineMode {
+    /// The width every line is expected to be, without its linebreak. `None` expects the
+    /// reader's `width`, and lines are not checked at all if that is 0.
+    pub expect_width: Option<usize>,
+    /// What to do with a line that is not the expected width.
+    pub on_mismatch: OnMismatch,
+}
+
+/// What a reader in `LineMode` does with a line that is not the expected width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OnMismatch {
+    /// Skips the line. Skipped lines still count towards `records_read`.
+    Skip,
+    /// Fails with an `Error::Record` holding an `Error::LineWidth`, naming the index of the
+    /// record and the width of the line. Reading carries on from the next line.
+    Error,
+    /// Pads a short line with spaces to the expected width. A long line is an error.
+    Pad,
+    /// Cuts a long line short at the expected width. A short line is an error.
+    Truncate,
+}
+
+/// How records that are not valid UTF-8 are handled when they are read as strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Utf8Policy {
+    /// Replaces invalid bytes with U+FFFD, as `String::from_utf8_lossy` does.
+    #[default]
+    Lossy,
+    /// Fails with an `Error::Record` holding an `Error::FormatError`, naming the index of the
+    /// record. Reading carries on from the next record.
+    Strict,
+}
+
 /// An iterator of `Vec<u8>` records.
 ///
 /// The lifetime 'a denotes the lifetime of the reader, R.
@@ -21,6 +104,36 @@ pub struct StringReader<'a, R: 'a> {
     r: &'a mut Reader<R>,
 }
 
+/// An iterator of records deserialized into `T`.
+///
+/// The lifetime 'a denotes the lifetime of the reader, R.
+pub struct DeserializeReader<'a, R: 'a, T> {
+    r: &'a mut Reader<R>,
+    required_width: usize,
+    _marker: PhantomData<T>,
+}
+
+/// An iterator of `Vec<u8>` records that owns its reader, so it can be returned from a function
+/// or stored without borrowing the reader. Created by `Reader::into_byte_records`, or by
+/// iterating over a `Reader` directly.
+pub struct IntoByteRecords<R> {
+    r: Reader<R>,
+}
+
+/// An iterator of `String` records that owns its reader. Created by
+/// `Reader::into_string_records`.
+pub struct IntoStringRecords<R> {
+    r: Reader<R>,
+}
+
+/// An iterator of records deserialized into `T` that owns its reader. Created by
+/// `Reader::into_deserialize`.
+pub struct IntoDeserialize<R, T> {
+    r: Reader<R>,
+    required_width: usize,
+    _marker: PhantomData<T>,
+}
+
 /// A fixed width data reader. It parses fixed width data and provides the data via iterators.
 ///
 /// ### Example
@@ -144,6 +257,40 @@ pub struct Reader<R> {
     buf: Vec<u8>,
     linebreak_buf: Vec<u8>,
     eof: bool,
+    detect_linebreak: bool,
+    lenient_linebreaks: bool,
+    strict_linebreaks: bool,
+    skip_blank_records: bool,
+    blank_byte: u8,
+    records_read: u64,
+    bytes_read: u64,
+    on_record: Option<ProgressFn>,
+    length_prefix: Option<LengthPrefix>,
+    dynamic_width: Option<DynamicWidth>,
+    /// The longest line allowed when each line is read as a record.
+    max_line_len: Option<usize>,
+    line_mode: Option<LineMode>,
+    resync: bool,
+    /// Bytes already read past a corrupt record, or while looking for a byte order mark, which
+    /// are read again before the rest of the data.
+    resync_buf: Vec<u8>,
+    /// Where the last record read by width starts in the data, until it is skipped by a resync.
+    record_start: Option<u64>,
+    /// Set for sources that can seek, so `skip_records` can jump over records.
+    seek: Option<SeekFn<R>>,
+    records_left: Option<u64>,
+    data_offset: u64,
+    skip_bom: bool,
+    utf8_policy: Utf8Policy,
+    allow_trailing_fields: bool,
+    follow: Option<FollowMode>,
+    /// When a reader following its data ran out of it, until more arrives. The idle timeout is
+    /// measured from then, however many times the reader waits in between.
+    idle_since: Option<Instant>,
+    /// The length of the byte order mark skipped, once the start of the data has been checked.
+    bom_len: Option<u64>,
+    #[cfg(feature = "ebcdic")]
+    transcoder: Option<Box<Transcoder>>,
     /// The width in bytes of the record. Required in order to parse.
     pub record_width: usize,
     /// The line break that occurs between each record. Defaults to `LineBreak::None`
@@ -155,19 +302,89 @@ where
     R: Read,
 {
     /// Creates a new reader from any type that implements io::Read.
+    ///
+    /// Any source can be read this way, including one that decompresses its data as it is read,
+    /// such as a `GzDecoder` with the `gzip` feature or one from another crate. Sources that
+    /// cannot seek read and discard the records `skip_records` skips, but otherwise behave as a
+    /// file would.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    /// use std::io::Read;
+    ///
+    /// // Any io::Read that cannot seek, such as a decompressor.
+    /// let source = "0001\n0002\n0003".as_bytes().chain("\n0004".as_bytes());
+    /// let mut reader = Reader::from_reader(source)
+    ///     .width(4)
+    ///     .linebreak(LineBreak::Newline);
+    ///
+    /// assert_eq!(reader.skip_records(2).unwrap(), 2);
+    /// let records: Vec<String> = reader.string_reader().map(Result::unwrap).collect();
+    /// assert_eq!(records, vec!["0003", "0004"]);
+    /// ```
     pub fn from_reader(rdr: R) -> Self {
+        Self::with_capacity(BUFFER_SIZE, rdr)
+    }
+
+    /// Creates a new reader from any type that implements io::Read, buffering up to `capacity`
+    /// bytes of it at a time. The default capacity is 8 KiB.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    ///
+    /// let reader = Reader::with_capacity(1 << 20, "foobar".as_bytes()).width(3);
+    ///
+    /// assert_eq!(reader.buffer_capacity(), 1 << 20);
+    /// ```
+    pub fn with_capacity(capacity: usize, rdr: R) -> Self {
         Reader {
-            rdr: io::BufReader::with_capacity(BUFFER_SIZE, rdr),
+            rdr: io::BufReader::with_capacity(capacity, rdr),
             record_width: 0,
             buf: Vec::new(),
             linebreak: LineBreak::None,
             linebreak_buf: Vec::new(),
             eof: false,
+            detect_linebreak: false,
+            lenient_linebreaks: false,
+            strict_linebreaks: true,
+            skip_blank_records: false,
+            blank_byte: b' ',
+            records_read: 0,
+            bytes_read: 0,
+            on_record: None,
+            length_prefix: None,
+            dynamic_width: None,
+            max_line_len: None,
+            line_mode: None,
+            resync: false,
+            resync_buf: Vec::new(),
+            record_start: None,
+            seek: None,
+            records_left: None,
+            data_offset: 0,
+            skip_bom: false,
+            utf8_policy: Utf8Policy::Lossy,
+            allow_trailing_fields: false,
+            follow: None,
+            idle_since: None,
+            bom_len: None,
+            #[cfg(feature = "ebcdic")]
+            transcoder: None,
         }
     }
 
-    /// Reads each record of the data as a `String`. If the data is not valid UTF-8, then
-    /// you should use `byte_reader` instead.
+    /// The capacity in bytes of the reader's internal buffer.
+    pub fn buffer_capacity(&self) -> usize {
+        self.rdr.capacity()
+    }
+
+    /// Reads each record of the data as a `String`. Bytes that are not valid UTF-8 are replaced
+    /// with U+FFFD, unless `utf8_policy` is `Utf8Policy::Strict`. If the data is not meant to be
+    /// UTF-8, then you should use `byte_reader` instead.
     ///
     /// ### Example
     ///
@@ -180,7 +397,7 @@ where
     ///     assert_eq!(record.unwrap(), "abcd1234")
     /// }
     /// ```
-    pub fn string_reader(&mut self) -> StringReader<R> {
+    pub fn string_reader(&mut self) -> StringReader<'_, R> {
         StringReader { r: self }
     }
 
@@ -197,10 +414,224 @@ where
     ///     assert_eq!(record.unwrap(), b"abcd1234".to_vec())
     /// }
     /// ```
-    pub fn byte_reader(&mut self) -> ByteReader<R> {
+    pub fn byte_reader(&mut self) -> ByteReader<'_, R> {
         ByteReader { r: self }
     }
 
+    /// Reads each record of the data and deserializes it into `T` using the fields defined by
+    /// its `FixedWidth` implementation. Yields an error if the reader's width is smaller than
+    /// the extent of those fields.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde_derive::Deserialize;
+    /// use fixed_width::{FieldSet, FixedWidth, Reader};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Foo {
+    ///     name: String,
+    ///     age: usize,
+    /// }
+    ///
+    /// impl FixedWidth for Foo {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![
+    ///             FieldSet::new_field(0..6),
+    ///             FieldSet::new_field(6..10),
+    ///         ])
+    ///     }
+    /// }
+    ///
+    /// let mut reader = Reader::from_string("foobar1234foobaz6789").width(10);
+    /// let records: Vec<Foo> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+    ///
+    /// assert_eq!(records[1].name, "foobaz");
+    /// assert_eq!(records[1].age, 6789);
+    /// ```
+    pub fn deserialize<T>(&mut self) -> DeserializeReader<'_, R, T>
+    where
+        T: FixedWidth + DeserializeOwned,
+    {
+        self.keep_bytes_fields_of::<T>();
+        DeserializeReader {
+            r: self,
+            required_width: T::record_width(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Takes ownership of the reader and reads each record of the data as a `Vec<u8>`, as
+    /// `byte_reader` does. Iterating over a `Reader` directly does the same.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Reader, Result};
+    ///
+    /// fn records(data: &'static str) -> impl Iterator<Item = Result<Vec<u8>>> {
+    ///     Reader::from_string(data).width(4).into_byte_records()
+    /// }
+    ///
+    /// let records: Vec<Vec<u8>> = records("abcd1234").collect::<Result<_>>().unwrap();
+    ///
+    /// assert_eq!(records, vec![b"abcd".to_vec(), b"1234".to_vec()]);
+    /// ```
+    pub fn into_byte_records(self) -> IntoByteRecords<R> {
+        IntoByteRecords { r: self }
+    }
+
+    /// Takes ownership of the reader and reads each record of the data as a `String`, as
+    /// `string_reader` does.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    ///
+    /// let records = Reader::from_string("abcd1234").width(4).into_string_records();
+    ///
+    /// assert_eq!(records.map(Result::unwrap).collect::<Vec<_>>(), vec!["abcd", "1234"]);
+    /// ```
+    pub fn into_string_records(self) -> IntoStringRecords<R> {
+        IntoStringRecords { r: self }
+    }
+
+    /// Takes ownership of the reader and deserializes each record of the data into `T`, as
+    /// `deserialize` does.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde_derive::Deserialize;
+    /// use fixed_width::{FieldSet, FixedWidth, Reader, Result};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Foo {
+    ///     name: String,
+    ///     age: usize,
+    /// }
+    ///
+    /// impl FixedWidth for Foo {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![
+    ///             FieldSet::new_field(0..6),
+    ///             FieldSet::new_field(6..10),
+    ///         ])
+    ///     }
+    /// }
+    ///
+    /// fn people(data: &'static str) -> impl Iterator<Item = Result<Foo>> {
+    ///     Reader::from_string(data).width(10).into_deserialize()
+    /// }
+    ///
+    /// let records: Vec<Foo> = people("foobar1234foobaz6789").collect::<Result<_>>().unwrap();
+    ///
+    /// assert_eq!(records[1].name, "foobaz");
+    /// assert_eq!(records[1].age, 6789);
+    /// ```
+    pub fn into_deserialize<T>(self) -> IntoDeserialize<R, T>
+    where
+        T: FixedWidth + DeserializeOwned,
+    {
+        let mut r = self;
+        r.keep_bytes_fields_of::<T>();
+        IntoDeserialize {
+            r,
+            required_width: T::record_width(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Deserializes every remaining record into `T`, collecting the records that fail rather
+    /// than stopping at the first error. Each failure keeps the index and raw bytes of the
+    /// record so it can be reported or written to a reject file. Reading stops if the
+    /// underlying data cannot be read.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde_derive::Deserialize;
+    /// use fixed_width::{FieldSet, FixedWidth, Reader};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Foo {
+    ///     name: String,
+    ///     age: usize,
+    /// }
+    ///
+    /// impl FixedWidth for Foo {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![
+    ///             FieldSet::new_field(0..6),
+    ///             FieldSet::new_field(6..10),
+    ///         ])
+    ///     }
+    /// }
+    ///
+    /// let mut reader = Reader::from_string("foobar1234foobazabcdfoobax6789").width(10);
+    /// let (records, errors) = reader.deserialize_lossy::<Foo>();
+    ///
+    /// assert_eq!(records.len(), 2);
+    /// assert_eq!(errors[0].index, 1);
+    /// assert_eq!(errors[0].bytes, b"foobazabcd");
+    /// ```
+    pub fn deserialize_lossy<T>(&mut self) -> (Vec<T>, Vec<RecordError>)
+    where
+        T: FixedWidth + DeserializeOwned,
+    {
+        self.keep_bytes_fields_of::<T>();
+        let fields = T::fields();
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+
+        let latin1 = self.transcodes();
+        let allow_trailing = self.allow_trailing_fields;
+        let required = fields.total_width();
+        if self.record_width < required {
+            errors.push(RecordError {
+                index: self.records_read,
+                bytes: Vec::new(),
+                error: Error::InvalidWidth {
+                    width: self.record_width,
+                    required,
+                },
+            });
+            return (records, errors);
+        }
+
+        loop {
+            let failed = match self.next_record() {
+                None => break,
+                Some(Err(error)) => {
+                    errors.push(RecordError {
+                        index: self.records_read,
+                        bytes: Vec::new(),
+                        error,
+                    });
+                    break;
+                }
+                Some(Ok(bytes)) => match deserialize_record(bytes, latin1, allow_trailing) {
+                    Ok(record) => {
+                        records.push(record);
+                        None
+                    }
+                    Err(error) => Some((bytes.to_vec(), error)),
+                },
+            };
+
+            if let Some((bytes, error)) = failed {
+                errors.push(RecordError {
+                    index: self.records_read - 1,
+                    bytes,
+                    error,
+                });
+            }
+        }
+
+        (records, errors)
+    }
+
     /// Reads the next record as a byte slice
     ///
     /// ### Example
@@ -221,27 +652,300 @@ where
     /// }
     /// ```
     pub fn next_record(&mut self) -> Option<Result<&[u8]>> {
-        if self.eof {
+        if self.eof || self.records_left == Some(0) {
             return None;
         }
 
-        match self.fill_buf() {
-            Ok(0) => return None,
-            Ok(_) => {}
-            Err(e) => return Some(Err(e)),
+        if self.record_width == 0 && !self.has_variable_width() {
+            self.eof = true;
+            return Some(Err(Error::InvalidWidth {
+                width: 0,
+                required: 1,
+            }));
+        }
+
+        loop {
+            match self.read_record() {
+                Ok(false) => return None,
+                Ok(true) => {}
+                Err(e) => return Some(Err(e)),
+            }
+
+            if !(self.skip_blank_records && self.buf.iter().all(|b| *b == self.blank_byte)) {
+                if let Some(ref mut left) = self.records_left {
+                    *left -= 1;
+                }
+                return Some(Ok(&self.buf));
+            }
         }
+    }
 
-        if let Err(e) = self.read_linebreak() {
+    /// Reads the next record as a string slice, borrowing the reader's buffer, so no allocation
+    /// is made for a record that is valid UTF-8. A record that is not is handled as set by
+    /// `utf8_policy`: by default its invalid bytes are replaced in an owned copy, and with
+    /// `Utf8Policy::Strict` it is an `Error::Record` holding an `Error::FormatError`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    ///
+    /// let mut reader = Reader::from_bytes(&b"foobar1234foob\xffz6789"[..]).width(10);
+    ///
+    /// assert_eq!(reader.next_record_str().unwrap().unwrap(), "foobar1234");
+    /// assert_eq!(reader.next_record_str().unwrap().unwrap(), "foob\u{fffd}z6789");
+    /// assert!(reader.next_record_str().is_none());
+    /// ```
+    pub fn next_record_str(&mut self) -> Option<Result<Cow<'_, str>>> {
+        if let Err(e) = self.next_record()? {
             return Some(Err(e));
         }
 
-        Some(Ok(&self.buf))
+        // The record just read is the contents of the buffer.
+        let record = &self.buf;
+        if self.transcodes() && !record.is_ascii() {
+            return Some(Ok(Cow::Owned(de::latin1_to_string(record))));
+        }
+        match str::from_utf8(record) {
+            Ok(s) => Some(Ok(Cow::Borrowed(s))),
+            Err(_) if self.utf8_policy == Utf8Policy::Lossy => {
+                Some(Ok(String::from_utf8_lossy(record)))
+            }
+            Err(_) => Some(
+                String::from_utf8(record.to_vec())
+                    .map(Cow::Owned)
+                    .map_err(|e| Error::Record {
+                        index: self.records_read - 1,
+                        source: Box::new(Error::FormatError(e)),
+                    }),
+            ),
+        }
+    }
+
+    /// Reads the next record into the given buffer, replacing its contents. Returns `false` once
+    /// there are no more records. Reusing the same buffer across calls avoids allocating for
+    /// each record.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    ///
+    /// let mut reader = Reader::from_string("foobar1234foobaz6789").width(10);
+    /// let mut buf = Vec::with_capacity(10);
+    ///
+    /// assert!(reader.read_record_into(&mut buf).unwrap());
+    /// assert_eq!(buf, b"foobar1234");
+    ///
+    /// assert!(reader.read_record_into(&mut buf).unwrap());
+    /// assert_eq!(buf, b"foobaz6789");
+    ///
+    /// assert!(!reader.read_record_into(&mut buf).unwrap());
+    /// ```
+    pub fn read_record_into(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        match self.next_record() {
+            None => Ok(false),
+            Some(Err(e)) => Err(e),
+            Some(Ok(record)) => {
+                buf.clear();
+                buf.extend_from_slice(record);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Skips over the next `n` records without yielding them, returning the number skipped,
+    /// which is less than `n` only if the data ends first. Skipped records count towards
+    /// `records_read` but not towards a limit set by `take_records`.
+    ///
+    /// For a seekable source, such as a reader from `from_file`, `from_bytes` or one marked with
+    /// `seekable`, the reader seeks straight past the records from its current position, so any
+    /// records already read, such as a header, are accounted for. The linebreaks of records
+    /// skipped this way are not checked. Records are read and discarded instead if the source
+    /// cannot seek or their widths are not known up front: with `length_prefixed`,
+    /// `dynamic_width`, `line_records`, `line_mode`, `lenient_linebreaks` or
+    /// `skip_blank_records`, or before the linebreak has been detected by `detect_linebreak`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// let mut reader = Reader::from_string("0001\n0002\n0003\n0004\n0005")
+    ///     .width(4)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .take_records(2);
+    ///
+    /// assert_eq!(reader.skip_records(2).unwrap(), 2);
+    ///
+    /// let records: Vec<String> = reader.string_reader().map(Result::unwrap).collect();
+    /// assert_eq!(records, vec!["0003", "0004"]);
+    /// assert_eq!(reader.records_read(), 4);
+    /// ```
+    pub fn skip_records(&mut self, n: u64) -> Result<u64> {
+        let seek = match self.seek {
+            Some(seek) if self.can_seek_records() => seek,
+            _ => return self.discard_records(n),
+        };
+
+        if self.eof || n == 0 {
+            return Ok(0);
+        }
+        self.check_bom()?;
+
+        let linebreak = self.linebreak.byte_width() as u64;
+        let stride = self.record_width as u64 + linebreak;
+
+        let pos = seek(&mut self.rdr, SeekFrom::Current(0))?;
+        let end = seek(&mut self.rdr, SeekFrom::End(0))?;
+        // The last record does not need a linebreak after it.
+        let available = (end.saturating_sub(pos) + linebreak) / stride;
+        let skipped = n.min(available);
+
+        seek(&mut self.rdr, SeekFrom::Start(pos + skipped * stride))?;
+        self.records_read += skipped;
+        if skipped > 0 {
+            self.bytes_read += (skipped * stride).min(end.saturating_sub(pos));
+            self.report_progress();
+        }
+
+        Ok(skipped)
+    }
+
+    /// Stops yielding records once `n` more have been read, as `Iterator::take` would. Unlike
+    /// `take`, the limit is kept by the reader, so it also applies to `next_record` and to every
+    /// iterator over the reader.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    ///
+    /// let mut reader = Reader::from_string("aaaabbbbcccc").width(4).take_records(2);
+    /// let records: Vec<Vec<u8>> = reader.byte_reader().map(Result::unwrap).collect();
+    ///
+    /// assert_eq!(records, vec![b"aaaa".to_vec(), b"bbbb".to_vec()]);
+    /// ```
+    pub fn take_records(mut self, n: u64) -> Self {
+        self.records_left = Some(n);
+        self
+    }
+
+    /// Whether the byte offset of a later record can be worked out from the current position.
+    fn can_seek_records(&self) -> bool {
+        self.record_width > 0
+            && self.length_prefix.is_none()
+            && self.dynamic_width.is_none()
+            && !self.reads_lines()
+            && !self.lenient_linebreaks
+            && !self.detect_linebreak
+            && !self.skip_blank_records
+            && self.resync_buf.is_empty()
+    }
+
+    /// Reads and discards up to `n` records, returning how many there were.
+    fn discard_records(&mut self, n: u64) -> Result<u64> {
+        // Skipped records do not count towards the limit set by `take_records`.
+        let left = self.records_left.take();
+        let mut skipped = 0;
+
+        let result = loop {
+            if skipped == n {
+                break Ok(skipped);
+            }
+            match self.next_record() {
+                Some(Ok(_)) => skipped += 1,
+                Some(Err(e)) => break Err(e),
+                None => break Ok(skipped),
+            }
+        };
+
+        self.records_left = left;
+        result
+    }
+
+    /// The number of records read from the underlying data so far, including any records that
+    /// were skipped rather than yielded.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    ///
+    /// let mut reader = Reader::from_string("foo   bar").width(3).skip_blank_records(true);
+    ///
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"foo");
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"bar");
+    /// assert_eq!(reader.records_read(), 3);
+    /// ```
+    pub fn records_read(&self) -> u64 {
+        self.records_read
+    }
+
+    /// The number of bytes of the underlying data read so far, including linebreaks, a skipped
+    /// byte order mark, records that were skipped rather than yielded, bytes read directly
+    /// through `io::Read` and any partial record at the end of the data. After `record_at`, it
+    /// is the position in the data of the end of the record read.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// let mut reader = Reader::from_string("foo\r\nbar\r\nbaz").width(3).linebreak(LineBreak::CRLF);
+    ///
+    /// reader.next_record().unwrap().unwrap();
+    /// assert_eq!(reader.bytes_read(), 5);
+    ///
+    /// assert_eq!(reader.byte_reader().count(), 2);
+    /// assert_eq!(reader.bytes_read(), 13);
+    /// ```
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Calls `f` with `records_read` and `bytes_read` after each record is read, for example to
+    /// drive a progress bar. Records skipped by `skip_blank_records`, `skip_records` or
+    /// `OnMismatch::Skip` are reported too, except that `skip_records` reports once after seeking
+    /// past records.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let progress = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = Arc::clone(&progress);
+    ///
+    /// let mut reader = Reader::from_string("foo\nbar\nbaz")
+    ///     .width(3)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .on_record(move |records, bytes| seen.lock().unwrap().push((records, bytes)));
+    /// assert_eq!(reader.byte_reader().count(), 3);
+    ///
+    /// assert_eq!(*progress.lock().unwrap(), vec![(1, 4), (2, 8), (3, 11)]);
+    /// ```
+    pub fn on_record<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        self.on_record = Some(Box::new(f));
+        self
+    }
+
+    #[inline]
+    fn report_progress(&mut self) {
+        if let Some(ref mut on_record) = self.on_record {
+            on_record(self.records_read, self.bytes_read);
+        }
     }
 
     /// Defines the width of each record in the file. It is required to set prior to reading
     /// since fixed width data is not self describing. Consumers must tell the reader how many
     /// bytes to read for each field. Do not include linebreaks in the width, you should only
-    /// define a width to be the number of bytes in the record data itself.
+    /// define a width to be the number of bytes in the record data itself. A width of 0 is
+    /// rejected with `Error::InvalidWidth` on the first read.
     ///
     /// ### Example
     ///
@@ -297,247 +1001,3318 @@ where
         self
     }
 
-    #[inline]
-    fn has_linebreak(&self) -> bool {
-        !matches!(self.linebreak, LineBreak::None)
+    /// Detects the linebreak from the data instead of requiring it up front. After the first
+    /// record has been read, the next bytes are inspected to decide between `LineBreak::None`,
+    /// `LineBreak::Newline` and `LineBreak::CRLF`, and that choice is used for the rest of the
+    /// data. Files that mix separators should use `lenient_linebreaks` instead.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    /// use std::result;
+    ///
+    /// let data = "foo\r\nbar";
+    /// let mut reader = Reader::from_string(data).width(3).detect_linebreak();
+    /// let records: Vec<String> = reader.string_reader().filter_map(result::Result::ok).collect();
+    ///
+    /// assert_eq!(records, vec!["foo".to_string(), "bar".to_string()]);
+    /// assert_eq!(reader.linebreak, LineBreak::CRLF);
+    /// ```
+    pub fn detect_linebreak(mut self) -> Self {
+        self.detect_linebreak = true;
+        self
     }
 
-    #[inline]
-    fn fill_buf(&mut self) -> Result<usize> {
-        match self.rdr.read_exact(&mut self.buf) {
-            Ok(_) => Ok(self.record_width),
-            Err(e) => match e.kind() {
-                io::ErrorKind::UnexpectedEof => {
-                    self.eof = true;
-                    Ok(0)
+    /// Accepts any mix of `\r\n`, `\n` or no separator at all between records. When enabled,
+    /// an optional `\r` followed by an optional `\n` is consumed after each record and the
+    /// configured `linebreak` is ignored while reading.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    /// use std::result;
+    ///
+    /// let data = "foo\r\nbar\nbazqux";
+    /// let mut reader = Reader::from_string(data).width(3).lenient_linebreaks(true);
+    /// let records: Vec<String> = reader.string_reader().filter_map(result::Result::ok).collect();
+    ///
+    /// assert_eq!(records, vec!["foo", "bar", "baz", "qux"]);
+    /// ```
+    pub fn lenient_linebreaks(mut self, lenient: bool) -> Self {
+        self.lenient_linebreaks = lenient;
+        self
+    }
+
+    /// Sets whether the bytes after each record are checked against the configured `linebreak`.
+    /// Defaults to `true`, in which case any other bytes result in an `Error::BadLineBreak`, for
+    /// instance when data said to be CRLF separated is only separated by `\n`. Without the check,
+    /// the bytes are skipped regardless and every record after a mismatch is read out of place.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, LineBreak, Reader};
+    ///
+    /// let data = "foo\nbar\nbaz";
+    /// let mut reader = Reader::from_string(data).width(3).linebreak(LineBreak::CRLF);
+    ///
+    /// match reader.next_record() {
+    ///     Some(Err(Error::BadLineBreak { record_index, found, .. })) => {
+    ///         assert_eq!(record_index, 0);
+    ///         assert_eq!(found, b"\nb");
+    ///     }
+    ///     _ => panic!("expected a bad linebreak"),
+    /// }
+    ///
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(3)
+    ///     .linebreak(LineBreak::CRLF)
+    ///     .strict_linebreaks(false);
+    ///
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"foo");
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"ar\n");
+    /// ```
+    pub fn strict_linebreaks(mut self, strict: bool) -> Self {
+        self.strict_linebreaks = strict;
+        self
+    }
+
+    /// Skips records made up entirely of the blank byte (a space by default) instead of
+    /// yielding them. Skipped records still count towards `records_read`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    /// use std::result;
+    ///
+    /// let data = "foo\n   \nbar";
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(3)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .skip_blank_records(true);
+    /// let records: Vec<String> = reader.string_reader().filter_map(result::Result::ok).collect();
+    ///
+    /// assert_eq!(records, vec!["foo", "bar"]);
+    /// ```
+    pub fn skip_blank_records(mut self, skip: bool) -> Self {
+        self.skip_blank_records = skip;
+        self
+    }
+
+    /// Sets the byte that makes up a blank record when `skip_blank_records` is enabled. Defaults
+    /// to a space.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    /// use std::result;
+    ///
+    /// let mut reader = Reader::from_string("foo000bar")
+    ///     .width(3)
+    ///     .skip_blank_records(true)
+    ///     .blank_byte(b'0');
+    /// let records: Vec<String> = reader.string_reader().filter_map(result::Result::ok).collect();
+    ///
+    /// assert_eq!(records, vec!["foo", "bar"]);
+    /// ```
+    pub fn blank_byte(mut self, byte: u8) -> Self {
+        self.blank_byte = byte;
+        self
+    }
+
+    /// Sets whether a UTF-8 byte order mark at the start of the data is skipped, rather than read
+    /// as the first 3 bytes of the first record. Defaults to `false`. When enabled, data starting
+    /// with a UTF-16 byte order mark fails with an `io::ErrorKind::InvalidData` error, as UTF-16
+    /// data is not supported. The byte order mark is also allowed for by `skip_records`,
+    /// `record_at` and `record_count`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// let data = "\u{feff}foo\nbar";
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(3)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .skip_bom(true);
+    /// let records: Vec<String> = reader.string_reader().map(Result::unwrap).collect();
+    ///
+    /// assert_eq!(records, vec!["foo", "bar"]);
+    /// ```
+    pub fn skip_bom(mut self, skip: bool) -> Self {
+        self.skip_bom = skip;
+        self
+    }
+
+    /// Sets how `next_record_str`, `string_reader` and `into_string_records` handle records that
+    /// are not valid UTF-8. Defaults to `Utf8Policy::Lossy`, which replaces invalid bytes, while
+    /// `Utf8Policy::Strict` fails with an error so that corrupt data is never passed on.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, LineBreak, Reader, Utf8Policy};
+    ///
+    /// let mut reader = Reader::from_bytes(&b"abc\na\xffc\nxyz"[..])
+    ///     .width(3)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .utf8_policy(Utf8Policy::Strict);
+    /// let records: Vec<_> = reader.string_reader().collect();
+    ///
+    /// assert_eq!(records[0].as_ref().unwrap(), "abc");
+    /// assert!(matches!(records[1], Err(Error::Record { index: 1, .. })));
+    /// assert_eq!(records[2].as_ref().unwrap(), "xyz");
+    /// ```
+    pub fn utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.utf8_policy = policy;
+        self
+    }
+
+    /// Sets whether `deserialize`, `into_deserialize`, `deserialize_lossy` and `par_deserialize`
+    /// allow a type to leave fields of its layout unread, so a struct can take just the leading
+    /// fields of a record. Defaults to false, where any fields left over are a
+    /// `DeserializeError::UnconsumedFields`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde_derive::Deserialize;
+    /// use fixed_width::{FieldSet, FixedWidth, Reader};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Name {
+    ///     name: String,
+    /// }
+    ///
+    /// impl FixedWidth for Name {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![FieldSet::new_field(0..6), FieldSet::new_field(6..10)])
+    ///     }
+    /// }
+    ///
+    /// let mut reader = Reader::from_string("foobar1234").width(10);
+    /// assert!(reader.deserialize::<Name>().next().unwrap().is_err());
+    ///
+    /// let mut reader = Reader::from_string("foobar1234")
+    ///     .width(10)
+    ///     .allow_trailing_fields(true);
+    /// let name: Name = reader.deserialize().next().unwrap().unwrap();
+    /// assert_eq!(name.name, "foobar");
+    /// ```
+    pub fn allow_trailing_fields(mut self, val: bool) -> Self {
+        self.allow_trailing_fields = val;
+        self
+    }
+
+    /// Skips ahead to the next linebreak after a corrupt record, instead of reading every record
+    /// after it out of place. A record is corrupt if the bytes after it are not the `linebreak`,
+    /// with `strict_linebreaks`, or once it is passed to `mark_bad_record`. The reader looks for
+    /// the first linebreak from the start of the record and carries on reading from just after
+    /// it, so a line that is too short costs only the record read across it, and a line that is
+    /// too long costs only itself.
+    ///
+    /// For a bad linebreak, an `Error::Resync` is returned in place of the record, naming its
+    /// index and the span of bytes skipped, and the records after it are read as usual. Has no
+    /// effect without a `linebreak`, or for records that are not read by `width`, as with
+    /// `line_records` or `line_mode`, which read each record up to the linebreak anyway.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, LineBreak, Reader};
+    ///
+    /// let data = "aaaa\nbb\ncccc\ndddddd\neeee";
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(4)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .resync_on_linebreak(true);
+    ///
+    /// let mut records = Vec::new();
+    /// let mut quarantined = Vec::new();
+    /// for record in reader.string_reader() {
+    ///     match record {
+    ///         Ok(record) => records.push(record),
+    ///         Err(Error::Resync { skipped, .. }) => quarantined.push(skipped),
+    ///         Err(e) => panic!("{}", e),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(records, vec!["aaaa", "cccc", "eeee"]);
+    /// assert_eq!(quarantined, vec![5..8, 13..20]);
+    /// ```
+    pub fn resync_on_linebreak(mut self, resync: bool) -> Self {
+        self.resync = resync;
+        self
+    }
+
+    /// Marks the last record read as corrupt, such as one that could not be deserialized, so
+    /// that a reader set to `resync_on_linebreak` skips from the start of the record to the
+    /// first linebreak in or after it, and carries on from there. Returns the span of bytes
+    /// skipped, which is just the record and its linebreak if it was read in place. Returns
+    /// `None` if the reader does not resync, or there is no record to skip.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// // The second line is short, so the record read after the first is `"bb\ncc"`.
+    /// let data = "aaaa\nbb\ncccc\ndddd";
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(4)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .strict_linebreaks(false)
+    ///     .resync_on_linebreak(true);
+    ///
+    /// let mut records = Vec::new();
+    /// while let Some(record) = reader.next_record() {
+    ///     let record = record.unwrap();
+    ///     match std::str::from_utf8(record) {
+    ///         Ok(s) if !s.contains('\n') => records.push(s.to_string()),
+    ///         _ => assert_eq!(reader.mark_bad_record().unwrap(), Some(5..8)),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(records, vec!["aaaa", "cccc", "dddd"]);
+    /// ```
+    pub fn mark_bad_record(&mut self) -> Result<Option<Range<u64>>> {
+        if !self.resyncs() || self.record_start.is_none() {
+            return Ok(None);
+        }
+        self.skip_to_linebreak().map(Some)
+    }
+
+    /// Waits for more data at the end of the data instead of finishing, as `tail -f` does, for
+    /// reading a file that is still being written to. A record is only yielded once all of it,
+    /// and the linebreak after it, is available, so a record that has only been partly written
+    /// is never yielded early. Reading finishes once no more data has arrived for the idle
+    /// timeout, and an incomplete record at the end is then handled as it would be otherwise.
+    ///
+    /// With `lenient_linebreaks`, whether a linebreak follows a record is only known once the
+    /// next byte arrives, so each record is yielded once the data after it starts to arrive.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,no_run
+    /// use fixed_width::{FollowMode, LineBreak, Reader};
+    /// use std::time::Duration;
+    ///
+    /// let mut reader = Reader::from_file("live.txt")
+    ///     .unwrap()
+    ///     .width(16)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .follow(FollowMode {
+    ///         poll_interval: Duration::from_millis(250),
+    ///         idle_timeout: Some(Duration::from_secs(60)),
+    ///     });
+    ///
+    /// for record in reader.string_reader() {
+    ///     println!("{}", record.unwrap());
+    /// }
+    /// ```
+    pub fn follow(mut self, mode: FollowMode) -> Self {
+        self.follow = Some(mode);
+        self
+    }
+
+    /// Reads variable length records that are each preceded by the given length prefix, instead
+    /// of records of a fixed `width`. The data ending part way through a prefix or a record
+    /// results in `Error::IncompleteRecord`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LengthPrefix, Reader};
+    ///
+    /// let data = b"\x00\x07\x00\x00foo\x00\x0a\x00\x00barbaz".to_vec();
+    /// let mut reader = Reader::from_bytes(data).length_prefixed(LengthPrefix::Rdw);
+    ///
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"foo");
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"barbaz");
+    /// assert!(reader.next_record().is_none());
+    /// ```
+    pub fn length_prefixed(mut self, prefix: LengthPrefix) -> Self {
+        self.length_prefix = Some(prefix);
+        self
+    }
+
+    /// Reads variable length records whose width is given by a field inside the record. For each
+    /// record, `header_len` bytes are read and passed to `width_fn`, which returns the width of
+    /// the whole record including the header. The rest of the record is then read and the whole
+    /// record is yielded. Errors from `width_fn`, or widths smaller than the header, are returned
+    /// as `Error::Record` with the index of the record. A `header_len` of 0 is an
+    /// `Error::InvalidWidth` on the first read.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// let data = "05foo\n08foobar";
+    /// let mut reader = Reader::from_string(data)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .dynamic_width(2, |header| Ok(fixed_width::from_bytes_with_fields(
+    ///         header,
+    ///         fixed_width::FieldSet::new_field(0..2),
+    ///     )?));
+    ///
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"05foo");
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"08foobar");
+    /// assert!(reader.next_record().is_none());
+    /// ```
+    pub fn dynamic_width<F>(mut self, header_len: usize, width_fn: F) -> Self
+    where
+        F: Fn(&[u8]) -> Result<usize> + Send + 'static,
+    {
+        self.dynamic_width = Some(DynamicWidth {
+            header_len,
+            width_fn: Box::new(width_fn),
+        });
+        self
+    }
+
+    /// Reads each line as a record, up to the configured `linebreak`, instead of exactly `width`
+    /// bytes, for files whose last field is free text that runs to the end of the line. A line
+    /// shorter than `width` is padded to it with the blank byte, so the fixed fields before an
+    /// open ended field, such as one made by `FieldSet::new_field_to_end`, are always there to
+    /// be read. A line longer than `max_len` results in an `Error::Record` holding an
+    /// `Error::InvalidWidth`, and reading carries on from the next line.
+    ///
+    /// Has no effect without a `linebreak`, in which case records are read by `width` as usual.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, LineBreak, Reader};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field_to_end(4),
+    /// ]);
+    /// let data = "0001a short note\n0002\n03\n0004a much longer note\n";
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(fields.total_width())
+    ///     .linebreak(LineBreak::Newline)
+    ///     .line_records(80);
+    ///
+    /// let records: Vec<(String, String)> = reader
+    ///     .byte_reader()
+    ///     .map(|r| fixed_width::from_bytes_with_fields(&r.unwrap(), fields.clone()).unwrap())
+    ///     .collect();
+    ///
+    /// assert_eq!(records[0], ("0001".to_string(), "a short note".to_string()));
+    /// assert_eq!(records[1], ("0002".to_string(), "".to_string()));
+    /// assert_eq!(records[2], ("03".to_string(), "".to_string()));
+    /// assert_eq!(records[3], ("0004".to_string(), "a much longer note".to_string()));
+    /// ```
+    pub fn line_records(mut self, max_len: usize) -> Self {
+        self.max_line_len = Some(max_len);
+        self
+    }
+
+    /// Reads each line as a record, up to the configured `linebreak`, and checks that it is the
+    /// expected width, handling any line that is not as `mode` says. Unlike reading exactly
+    /// `width` bytes at a time, a line of the wrong width does not throw off the records after
+    /// it. Can be combined with `line_records`, which sets the longest line allowed.
+    ///
+    /// Has no effect without a `linebreak`, in which case records are read by `width` as usual.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, LineBreak, LineMode, OnMismatch, Reader};
+    ///
+    /// let data = "foo\nba\nbazz\nqux";
+    /// let mut reader = Reader::from_string(data)
+    ///     .width(3)
+    ///     .linebreak(LineBreak::Newline)
+    ///     .line_mode(LineMode {
+    ///         expect_width: None,
+    ///         on_mismatch: OnMismatch::Pad,
+    ///     });
+    ///
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"foo");
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"ba ");
+    /// match reader.next_record() {
+    ///     Some(Err(Error::Record { index: 2, source })) => match *source {
+    ///         Error::LineWidth { expected, actual, .. } => assert_eq!((expected, actual), (3, 4)),
+    ///         e => panic!("unexpected error: {}", e),
+    ///     },
+    ///     _ => panic!("expected a line width error"),
+    /// }
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"qux");
+    /// ```
+    pub fn line_mode(mut self, mode: LineMode) -> Self {
+        self.line_mode = Some(mode);
+        self
+    }
+
+    /// Converts each record as it is read, before it is split into fields, such as from EBCDIC
+    /// to ASCII. Linebreaks are read as they are, before the record is converted. Fields set as
+    /// `bytes`, such as packed decimals, are left as they are when deserializing records with
+    /// `deserialize`, or when given to `keep_bytes_fields` for records read as bytes.
+    ///
+    /// Each byte is converted to a single ISO-8859-1 byte, so every field keeps its offset.
+    /// Records deserialized with `deserialize`, `into_deserialize` or `deserialize_lossy`, and
+    /// records read as strings, have their text read as ISO-8859-1, so characters outside ASCII,
+    /// such as `é` or `¢`, are read as the same characters in UTF-8. Records read as bytes, such
+    /// as by `next_record`, hold the ISO-8859-1 bytes.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{CodePage, Reader, Transcode};
+    ///
+    /// let ebcdic = [0xC8, 0x85, 0x93, 0x93, 0x96, 0x5A, 0xF1, 0xF2, 0xF3, 0x40, 0x51];
+    /// let mut reader = Reader::from_bytes(&ebcdic[..10])
+    ///     .width(5)
+    ///     .transcode(Transcode::Ebcdic(CodePage::Cp037));
+    ///
+    /// let records: Vec<String> = reader.string_reader().map(Result::unwrap).collect();
+    /// assert_eq!(records, vec!["Hello", "!123 "]);
+    ///
+    /// // `é` is read as the ISO-8859-1 byte 0xE9.
+    /// let mut reader = Reader::from_bytes(&ebcdic[10..])
+    ///     .width(1)
+    ///     .transcode(Transcode::Ebcdic(CodePage::Cp037));
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"\xE9");
+    /// ```
+    #[cfg(feature = "ebcdic")]
+    pub fn transcode(mut self, transcode: Transcode) -> Self {
+        self.transcoder = Some(Box::new(Transcoder::new(transcode)));
+        self
+    }
+
+    /// Leaves the fields among `fields` that are set as `bytes` as they are when transcoding
+    /// records with `transcode`. Deserializing records with `deserialize` or `into_deserialize`
+    /// does this for the fields of the record type.
+    #[cfg(feature = "ebcdic")]
+    pub fn keep_bytes_fields(mut self, fields: &FieldSet) -> Self {
+        if let Some(ref mut transcoder) = self.transcoder {
+            transcoder.keep_bytes_fields(fields);
+        }
+        self
+    }
+
+    /// Leaves the `bytes` fields of `T` as they are when transcoding.
+    fn keep_bytes_fields_of<T: FixedWidth>(&mut self) {
+        #[cfg(feature = "ebcdic")]
+        if let Some(ref mut transcoder) = self.transcoder {
+            transcoder.keep_bytes_fields(&T::fields());
+        }
+    }
+
+    #[inline]
+    fn has_variable_width(&self) -> bool {
+        self.length_prefix.is_some() || self.dynamic_width.is_some() || self.reads_lines()
+    }
+
+    #[inline]
+    fn resyncs(&self) -> bool {
+        self.resync
+            && self.has_linebreak()
+            && !self.lenient_linebreaks
+            && !self.has_variable_width()
+    }
+
+    #[inline]
+    fn reads_lines(&self) -> bool {
+        (self.max_line_len.is_some() || self.line_mode.is_some()) && self.has_linebreak()
+    }
+
+    #[inline]
+    fn has_linebreak(&self) -> bool {
+        !matches!(self.linebreak, LineBreak::None)
+    }
+
+    #[inline]
+    fn fill_buf(&mut self) -> Result<bool> {
+        let mut buf = std::mem::take(&mut self.buf);
+        let read = self.read_full(&mut buf);
+        let width = buf.len();
+        self.buf = buf;
+
+        // A short record at the end of the data is dropped.
+        if read? < width {
+            self.eof = true;
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    fn fill_prefixed_buf(&mut self, prefix: LengthPrefix) -> Result<bool> {
+        let mut prefix_buf = vec![0; prefix.byte_width()];
+
+        match self.read_full(&mut prefix_buf)? {
+            0 => {
+                self.eof = true;
+                return Ok(false);
+            }
+            read if read < prefix_buf.len() => {
+                self.eof = true;
+                return Err(Error::IncompleteRecord {
+                    expected: prefix_buf.len(),
+                    read,
+                });
+            }
+            _ => {}
+        }
+
+        let width = match prefix.record_width(&prefix_buf) {
+            Some(width) => width,
+            None => {
+                self.eof = true;
+                return Err(Error::InvalidWidth {
+                    width: u16::from_be_bytes([prefix_buf[0], prefix_buf[1]]) as usize,
+                    required: prefix.byte_width(),
+                });
+            }
+        };
+
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.resize(width, 0);
+        let read = self.read_full(&mut buf);
+        self.buf = buf;
+
+        let read = read?;
+        if read < width {
+            self.eof = true;
+            return Err(Error::IncompleteRecord {
+                expected: width,
+                read,
+            });
+        }
+
+        Ok(true)
+    }
+
+    fn fill_dynamic_buf(&mut self) -> Result<bool> {
+        let header_len = match self.dynamic_width {
+            Some(ref dynamic) => dynamic.header_len,
+            None => return Ok(false),
+        };
+
+        // Without a header, every record would be read from nothing and could be empty.
+        if header_len == 0 {
+            self.eof = true;
+            return Err(Error::InvalidWidth {
+                width: 0,
+                required: 1,
+            });
+        }
+
+        if !self.fill_exact(0, header_len, true)? {
+            return Ok(false);
+        }
+
+        let width = match self.dynamic_width {
+            Some(ref dynamic) => (dynamic.width_fn)(&self.buf),
+            None => return Ok(false),
+        };
+
+        let error = match width {
+            Ok(width) if width >= header_len => return self.fill_exact(header_len, width, false),
+            Ok(width) => Error::InvalidWidth {
+                width,
+                required: header_len,
+            },
+            Err(e) => e,
+        };
+
+        // The end of this record is unknown, so there is no way to continue reading.
+        self.eof = true;
+        Err(Error::Record {
+            index: self.records_read,
+            source: Box::new(error),
+        })
+    }
+
+    /// Reads the next line into the record buffer, consuming the linebreak after it, and checks
+    /// its width against the `line_mode`, or pads it to the record width for `line_records`.
+    /// A line that fails is consumed all the same, so reading can carry on after it.
+    fn fill_line_buf(&mut self) -> Result<bool> {
+        let expected = self
+            .line_mode
+            .map(|mode| {
+                (
+                    mode.expect_width.unwrap_or(self.record_width),
+                    mode.on_mismatch,
+                )
+            })
+            .filter(|&(width, _)| width > 0);
+        // Only as much of a line is kept as could be needed.
+        let keep = match (self.max_line_len, expected) {
+            (Some(max_len), _) => max_len,
+            (None, Some((width, _))) => width,
+            (None, None) => usize::MAX,
+        };
+
+        loop {
+            let mut buf = std::mem::take(&mut self.buf);
+            buf.clear();
+            let read = self.read_line(&mut buf, keep);
+            self.buf = buf;
+
+            let len = match read? {
+                None => {
+                    self.eof = true;
+                    return Ok(false);
+                }
+                Some(len) => len,
+            };
+
+            let index = self.records_read;
+            let error = match (self.max_line_len, expected) {
+                (Some(max_len), _) if len > max_len => Error::Record {
+                    index,
+                    source: Box::new(Error::InvalidWidth {
+                        width: max_len,
+                        required: len,
+                    }),
+                },
+                (_, Some((width, on_mismatch))) if len != width => match on_mismatch {
+                    OnMismatch::Skip => {
+                        self.records_read += 1;
+                        self.report_progress();
+                        continue;
+                    }
+                    OnMismatch::Pad if len < width => {
+                        let pad = self.raw_byte(b' ');
+                        self.buf.resize(width, pad);
+                        return Ok(true);
+                    }
+                    OnMismatch::Truncate if len > width => {
+                        self.buf.truncate(width);
+                        return Ok(true);
+                    }
+                    _ => Error::Record {
+                        index,
+                        source: Box::new(Error::LineWidth {
+                            expected: width,
+                            actual: len,
+                        }),
+                    },
+                },
+                _ => {
+                    if self.buf.len() < self.record_width && self.max_line_len.is_some() {
+                        let pad = self.raw_byte(self.blank_byte);
+                        self.buf.resize(self.record_width, pad);
+                    }
+                    return Ok(true);
+                }
+            };
+
+            self.records_read += 1;
+            return Err(error);
+        }
+    }
+
+    /// Reads up to and including the next `\n`, appending up to `keep` bytes of the line without
+    /// it, or the `\r` before it for `LineBreak::CRLF`, to `buf`. Returns the length of the line,
+    /// or `None` if the data has ended.
+    fn read_line(&mut self, buf: &mut Vec<u8>, keep: usize) -> Result<Option<usize>> {
+        let crlf = self.linebreak == LineBreak::CRLF;
+        let mut len = 0;
+        let mut ends_with_cr = false;
+        let mut newline = false;
+
+        loop {
+            let (used, done) = {
+                let available = self.peek()?;
+                if available.is_empty() {
+                    break;
+                }
+
+                let (line, done) = match available.iter().position(|&b| b == b'\n') {
+                    Some(i) => (&available[..i], true),
+                    None => (available, false),
+                };
+                let room = keep.saturating_sub(buf.len());
+                buf.extend_from_slice(&line[..line.len().min(room)]);
+                len += line.len();
+                if let Some(&last) = line.last() {
+                    ends_with_cr = last == b'\r';
+                }
+                (line.len() + done as usize, done)
+            };
+
+            self.consume(used);
+            if done {
+                newline = true;
+                break;
+            }
+        }
+
+        if len == 0 && !newline {
+            return Ok(None);
+        }
+        if crlf && ends_with_cr {
+            len -= 1;
+            buf.truncate(len);
+        }
+        Ok(Some(len))
+    }
+
+    /// Resizes the record buffer to `end` bytes and reads the data into `buf[start..end]`,
+    /// erroring if the data ends part way through. If `eof_allowed` is set and there is no data
+    /// at all, the reader is marked done instead.
+    fn fill_exact(&mut self, start: usize, end: usize, eof_allowed: bool) -> Result<bool> {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.resize(end, 0);
+        let read = self.read_full(&mut buf[start..]);
+        self.buf = buf;
+
+        match read? {
+            0 if eof_allowed && end > start => {
+                self.eof = true;
+                Ok(false)
+            }
+            read if read < end - start => {
+                self.eof = true;
+                Err(Error::IncompleteRecord {
+                    expected: end - start,
+                    read,
+                })
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// Reads until `buf` is full or the data ends, returning the number of bytes read.
+    fn read_full(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let mut read = self.resync_buf.len().min(buf.len());
+        buf[..read].copy_from_slice(&self.resync_buf[..read]);
+        self.resync_buf.drain(..read);
+
+        while read < buf.len() {
+            match self.rdr.read(&mut buf[read..]) {
+                Ok(0) => match self.follow {
+                    Some(follow) if follow.wait(&mut self.idle_since) => {}
+                    _ => break,
+                },
+                Ok(n) => {
+                    read += n;
+                    self.bytes_read += n as u64;
+                    self.idle_since = None;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::from(e)),
+            }
+        }
+
+        Ok(read)
+    }
+
+    /// Whether records are deserialized with `allow_trailing_fields` set.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn allows_trailing_fields(&self) -> bool {
+        self.allow_trailing_fields
+    }
+
+    /// Whether records are converted as they are read, leaving their text as ISO-8859-1.
+    fn transcodes(&self) -> bool {
+        #[cfg(feature = "ebcdic")]
+        return self.transcoder.is_some();
+        #[cfg(not(feature = "ebcdic"))]
+        false
+    }
+
+    /// The byte in the data that reads as `byte`, which differs when the reader transcodes. Short
+    /// lines are padded with it before the record is converted.
+    fn raw_byte(&self, byte: u8) -> u8 {
+        #[cfg(feature = "ebcdic")]
+        if let Some(ref transcoder) = self.transcoder {
+            return transcoder.encode_byte(byte);
+        }
+        byte
+    }
+
+    /// Reads the next record into the buffer, converting it if the reader transcodes.
+    fn read_record(&mut self) -> Result<bool> {
+        let filled = self.fill_record()?;
+
+        #[cfg(feature = "ebcdic")]
+        if let (true, Some(transcoder)) = (filled, &self.transcoder) {
+            transcoder.decode(&mut self.buf);
+        }
+        Ok(filled)
+    }
+
+    fn fill_record(&mut self) -> Result<bool> {
+        self.check_bom()?;
+        self.record_start = Some(self.bytes_read - self.resync_buf.len() as u64);
+
+        let filled = match self.length_prefix {
+            Some(prefix) => self.fill_prefixed_buf(prefix)?,
+            None if self.dynamic_width.is_some() => self.fill_dynamic_buf()?,
+            None if self.reads_lines() => {
+                if !self.fill_line_buf()? {
+                    return Ok(false);
+                }
+                self.records_read += 1;
+                self.report_progress();
+                return Ok(true);
+            }
+            None => self.fill_buf()?,
+        };
+
+        if !filled {
+            return Ok(false);
+        }
+
+        if self.detect_linebreak {
+            self.resolve_linebreak()?;
+        }
+
+        // The record has been consumed even if the linebreak after it is bad.
+        let linebreak = self.read_linebreak();
+        self.records_read += 1;
+        self.report_progress();
+        linebreak?;
+
+        Ok(true)
+    }
+
+    /// Skips a UTF-8 byte order mark at the start of the data if `skip_bom` is set, returning its
+    /// length. Only the first call looks at the data.
+    fn check_bom(&mut self) -> Result<u64> {
+        if !self.skip_bom {
+            return Ok(0);
+        }
+        if let Some(len) = self.bom_len {
+            return Ok(len);
+        }
+
+        // A short read may hold only the start of a mark, so the first bytes are gathered until
+        // there are 3 of them or the data ends. Any that are not a mark are read again.
+        if self.peek()?.len() < 3 {
+            let mut start = Vec::with_capacity(3);
+            loop {
+                let available = self.peek()?;
+                let n = available.len().min(3 - start.len());
+                if n == 0 {
+                    break;
+                }
+                start.extend_from_slice(&available[..n]);
+                self.consume(n);
+            }
+            self.resync_buf.splice(0..0, start);
+        }
+
+        let len = match self.peek()? {
+            [0xEF, 0xBB, 0xBF, ..] => 3,
+            [0xFF, 0xFE, ..] | [0xFE, 0xFF, ..] => {
+                self.eof = true;
+                return Err(Error::from(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "UTF-16 input is not supported",
+                )));
+            }
+            _ => 0,
+        };
+
+        self.consume(len);
+        self.bom_len = Some(len as u64);
+        Ok(len as u64)
+    }
+
+    fn resolve_linebreak(&mut self) -> Result<()> {
+        let peeked = self.peek()?;
+
+        // A lone carriage return at the end of the buffer can only be the start of a CRLF, since
+        // a bare `\r` is not a supported separator.
+        let linebreak = match peeked {
+            [] => return Ok(()),
+            [b'\n', ..] => LineBreak::Newline,
+            [b'\r', b'\n', ..] | [b'\r'] => LineBreak::CRLF,
+            _ => LineBreak::None,
+        };
+
+        self.detect_linebreak = false;
+        self.linebreak_buf = vec![0; linebreak.byte_width()];
+        self.linebreak = linebreak;
+
+        Ok(())
+    }
+
+    // TODO: use skip_relative once stable
+    #[inline]
+    fn read_linebreak(&mut self) -> Result<()> {
+        if self.lenient_linebreaks {
+            self.skip_byte_if(b'\r')?;
+            self.skip_byte_if(b'\n')?;
+            return Ok(());
+        }
+
+        if !self.has_linebreak() {
+            return Ok(());
+        }
+
+        let mut linebreak_buf = std::mem::take(&mut self.linebreak_buf);
+        let read = self.read_full(&mut linebreak_buf);
+        let width = linebreak_buf.len();
+        self.linebreak_buf = linebreak_buf;
+
+        // There will not necessarily be a trailing line break, so if the data ends while reading
+        // the linebreak, mark the reader done and return without error.
+        if read? < width {
+            self.eof = true;
+            return Ok(());
+        }
+
+        let expected = self.linebreak.as_bytes();
+        if self.strict_linebreaks && self.linebreak_buf != expected {
+            if self.resyncs() {
+                return Err(Error::Resync {
+                    record_index: self.records_read,
+                    skipped: self.skip_to_linebreak()?,
+                });
+            }
+            return Err(Error::BadLineBreak {
+                record_index: self.records_read,
+                expected: expected.to_vec(),
+                found: self.linebreak_buf.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Skips the last record read, up to and including the first linebreak in it or after it, so
+    /// that reading carries on from just after the linebreak. Any bytes already read past the
+    /// linebreak are kept to be read again. Returns the span of bytes skipped.
+    fn skip_to_linebreak(&mut self) -> Result<Range<u64>> {
+        let start = match self.record_start.take() {
+            Some(start) => start,
+            None => return Ok(self.bytes_read..self.bytes_read),
+        };
+        let linebreak = self.linebreak.as_bytes();
+
+        // The record and as much of the linebreak after it as has been read.
+        let read = (self.bytes_read - self.resync_buf.len() as u64 - start) as usize;
+        let mut consumed = self.buf.clone();
+        let linebreak_read = read
+            .saturating_sub(consumed.len())
+            .min(self.linebreak_buf.len());
+        consumed.extend_from_slice(&self.linebreak_buf[..linebreak_read]);
+
+        if let Some(i) = consumed
+            .windows(linebreak.len())
+            .position(|w| w == linebreak)
+        {
+            let end = i + linebreak.len();
+            let mut rest = consumed.split_off(end);
+            rest.append(&mut self.resync_buf);
+            self.resync_buf = rest;
+            return Ok(start..start + end as u64);
+        }
+
+        // Keep enough of the end of the record to find a linebreak that starts within it.
+        let mut skipped = consumed.len();
+        let mut tail = consumed.split_off(consumed.len().saturating_sub(linebreak.len() - 1));
+        let mut byte = [0];
+        while !tail.ends_with(linebreak) {
+            if self.read_full(&mut byte)? == 0 {
+                self.eof = true;
+                break;
+            }
+            skipped += 1;
+            if tail.len() == linebreak.len() {
+                tail.remove(0);
+            }
+            tail.push(byte[0]);
+        }
+
+        Ok(start..start + skipped as u64)
+    }
+
+    /// Returns the buffered data without consuming it, which is only empty at the end of the
+    /// data. Bytes to be read again come first. When following, waits for more data first if
+    /// there is none.
+    fn peek(&mut self) -> Result<&[u8]> {
+        if !self.resync_buf.is_empty() {
+            return Ok(&self.resync_buf);
+        }
+        if let Some(follow) = self.follow {
+            while self.rdr.fill_buf()?.is_empty() && follow.wait(&mut self.idle_since) {}
+        }
+
+        let buf = self.rdr.fill_buf()?;
+        if !buf.is_empty() {
+            self.idle_since = None;
+        }
+        Ok(buf)
+    }
+
+    /// Consumes `n` bytes of those returned by `peek`.
+    fn consume(&mut self, n: usize) {
+        if self.resync_buf.is_empty() {
+            self.rdr.consume(n);
+            self.bytes_read += n as u64;
+        } else {
+            // Bytes to be read again were counted when they were first read.
+            self.resync_buf.drain(..n);
+        }
+    }
+
+    #[inline]
+    fn skip_byte_if(&mut self, byte: u8) -> Result<()> {
+        if self.peek()?.first() == Some(&byte) {
+            self.consume(1);
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Creates a new reader from any type that implements io::Read, with the width set to the
+    /// extent of the fields defined for `T`.
+    pub fn from_reader_for<T: FixedWidth>(rdr: R) -> Self {
+        Self::from_reader(rdr).width(T::record_width())
+    }
+}
+
+impl<R> Reader<R>
+where
+    R: Read + Seek,
+{
+    /// Lets `skip_records` seek past records rather than reading them. Readers from `from_file`
+    /// and `from_bytes` can already seek.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Reader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = Reader::from_reader(Cursor::new("aaaabbbbcccc")).width(4).seekable();
+    ///
+    /// assert_eq!(reader.skip_records(2).unwrap(), 2);
+    /// assert_eq!(reader.next_record().unwrap().unwrap(), b"cccc");
+    /// ```
+    pub fn seekable(mut self) -> Self {
+        self.seek = Some(<io::BufReader<R> as Seek>::seek);
+        self
+    }
+
+    /// Sets the number of bytes before the first record, such as a header and the linebreak
+    /// after it, for `record_at` and `record_count`. Defaults to 0. Reading records in order is
+    /// not affected.
+    pub fn data_offset(mut self, offset: u64) -> Self {
+        self.data_offset = offset;
+        self
+    }
+
+    /// Reads the record at the given zero based index, working out where it starts from the
+    /// record width, the linebreak and the `data_offset`, and seeking straight to it. Returns
+    /// `None` if the data ends before the record, or if the record would start past the largest
+    /// offset that can be seeked to. Reading in order carries on from the record after it, and
+    /// `records_read` counts it and every record before it.
+    ///
+    /// Fails with an `io::ErrorKind::Unsupported` error if the widths of records are not known
+    /// up front: with `length_prefixed`, `dynamic_width`, `line_records`, `line_mode` or
+    /// `lenient_linebreaks`, or before the linebreak has been detected by `detect_linebreak`.
+    ///
+    /// ### Example
+    ///
+    /// Finding a record in a file sorted by its key, without reading the whole file:
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Reader};
+    ///
+    /// let data = "0003 Carl\n0007 Dan \n0012 Eve \n0020 Fay \n0031 Gus ";
+    /// let mut reader = Reader::from_string(data).width(9).linebreak(LineBreak::Newline);
+    ///
+    /// let (mut lo, mut hi) = (0, reader.record_count().unwrap());
+    /// while lo < hi {
+    ///     let mid = (lo + hi) / 2;
+    ///     let record = reader.record_at(mid).unwrap().unwrap();
+    ///     if &record[..4] < b"0020" {
+    ///         lo = mid + 1;
+    ///     } else {
+    ///         hi = mid;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(reader.record_at(lo).unwrap().unwrap(), b"0020 Fay ");
+    /// ```
+    pub fn record_at(&mut self, index: u64) -> Result<Option<&[u8]>> {
+        let stride = self.record_stride()?;
+        let start = self.check_bom()? + self.data_offset;
+        let offset = match index
+            .checked_mul(stride)
+            .and_then(|offset| offset.checked_add(start))
+        {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        self.bytes_read = self.rdr.seek(SeekFrom::Start(offset))?;
+        self.resync_buf.clear();
+        self.eof = false;
+        self.records_read = index;
+
+        if self.read_record()? {
+            Ok(Some(&self.buf))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Counts the records in the data from the length of the data, without reading them. The
+    /// last record does not need a linebreak after it. Fails with an `Error::Record` holding an
+    /// `Error::IncompleteRecord` if the data ends part way through a record, naming the index of
+    /// the incomplete record and how many bytes of it there are, which is a quick check for
+    /// truncated or corrupt files. Fails as `record_at` does if the widths of records are not
+    /// known up front.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, LineBreak, Reader};
+    ///
+    /// let mut reader = Reader::from_string("aaaa\r\nbbbb\r\n").width(4).linebreak(LineBreak::CRLF);
+    /// assert_eq!(reader.record_count().unwrap(), 2);
+    ///
+    /// let mut reader = Reader::from_string("aaaa\r\nbb").width(4).linebreak(LineBreak::CRLF);
+    /// match reader.record_count() {
+    ///     Err(Error::Record { index: 1, source }) => assert_eq!(
+    ///         source.to_string(),
+    ///         "incomplete record, expected 4 bytes but only 2 could be read"
+    ///     ),
+    ///     res => panic!("unexpected result: {:?}", res),
+    /// }
+    /// ```
+    pub fn record_count(&mut self) -> Result<u64> {
+        let stride = self.record_stride()?;
+        let width = self.record_width as u64;
+        let start = self.check_bom()? + self.data_offset;
+
+        let pos = self.rdr.stream_position()?;
+        let len = self.rdr.seek(SeekFrom::End(0))?.saturating_sub(start);
+        self.rdr.seek(SeekFrom::Start(pos))?;
+
+        let (count, remainder) = (len / stride, len % stride);
+        match remainder {
+            0 => Ok(count),
+            // The last record has no linebreak after it.
+            r if r == width => Ok(count + 1),
+            r => Err(Error::Record {
+                index: count,
+                source: Box::new(Error::IncompleteRecord {
+                    expected: if r < width { width } else { stride } as usize,
+                    read: r as usize,
+                }),
+            }),
+        }
+    }
+
+    /// The number of bytes from the start of one record to the start of the next, or an error if
+    /// that differs between records.
+    fn record_stride(&self) -> Result<u64> {
+        if self.record_width == 0 {
+            return Err(Error::InvalidWidth {
+                width: 0,
+                required: 1,
+            });
+        }
+
+        if self.length_prefix.is_some()
+            || self.dynamic_width.is_some()
+            || self.reads_lines()
+            || self.lenient_linebreaks
+            || self.detect_linebreak
+        {
+            return Err(Error::from(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the width of each record must be known to find a record by its index",
+            )));
+        }
+
+        Ok((self.record_width + self.linebreak.byte_width()) as u64)
+    }
+}
+
+impl Reader<fs::File> {
+    /// Creates a new reader from a filepath. Will return an io::Error if there are any issues
+    /// opening the file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_reader(fs::File::open(path)?).seekable())
+    }
+
+    /// Creates a new reader from a filepath, with the width set to the extent of the fields
+    /// defined for `T`.
+    pub fn from_file_for<T: FixedWidth, P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_reader_for::<T>(fs::File::open(path)?).seekable())
+    }
+
+    /// The size of the file in bytes, read from its metadata, which together with `bytes_read`
+    /// gives how much of the file has been read.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,no_run
+    /// use fixed_width::Reader;
+    ///
+    /// let mut reader = Reader::from_file("records.txt").unwrap().width(16);
+    /// let total = reader.total_bytes().unwrap().max(1);
+    ///
+    /// while let Some(record) = reader.next_record() {
+    ///     record.unwrap();
+    ///     println!("{}%", reader.bytes_read() * 100 / total);
+    /// }
+    /// ```
+    pub fn total_bytes(&self) -> Result<u64> {
+        Ok(self.rdr.get_ref().metadata()?.len())
+    }
+}
+
+impl Reader<io::StdinLock<'static>> {
+    /// Creates a new reader from standard input. Standard input is locked once, for as long as
+    /// the reader lives, rather than on every read as `Reader::from_reader(io::stdin())` would.
+    ///
+    /// ### Example
+    ///
+    /// A filter that reads records of 16 bytes from standard input, upper cases the name held in
+    /// the first 10 bytes of each, and writes them to standard output with the same linebreak:
+    ///
+    /// ```rust,no_run
+    /// use fixed_width::{FieldSet, LineBreak, Reader, Record, Result, Writer};
+    /// use std::io::Write;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let fields = FieldSet::Seq(vec![
+    ///         FieldSet::new_field(0..10).name("name"),
+    ///         FieldSet::new_field(10..16).name("amount"),
+    ///     ]);
+    ///
+    ///     let reader = Reader::from_stdin().width(16).linebreak(LineBreak::Newline);
+    ///     let mut writer = Writer::to_stdout().linebreak(LineBreak::Newline);
+    ///
+    ///     for (i, bytes) in reader.into_iter().enumerate() {
+    ///         let mut record = Record::parse(&bytes?, &fields)?;
+    ///         let name = record.get_str("name")?.to_uppercase();
+    ///         record.set("name", name)?;
+    ///
+    ///         if i > 0 {
+    ///             writer.write_linebreak()?;
+    ///         }
+    ///         writer.write_all(record.as_bytes())?;
+    ///     }
+    ///
+    ///     writer.close()
+    /// }
+    /// ```
+    pub fn from_stdin() -> Self {
+        Self::from_reader(io::stdin().lock())
+    }
+}
+
+impl Reader<io::Cursor<Vec<u8>>> {
+    /// Creates a new reader from a series of bytes.
+    pub fn from_bytes<T>(bytes: T) -> Self
+    where
+        T: Into<Vec<u8>>,
+    {
+        Self::from_reader(io::Cursor::new(bytes.into())).seekable()
+    }
+
+    /// Creates a new reader from a `String` or `&str`.
+    pub fn from_string<T>(s: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::from_bytes(s.into().into_bytes())
+    }
+
+    /// Creates a new reader from a series of bytes, with the width set to the extent of the
+    /// fields defined for `T`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde_derive::Deserialize;
+    /// use fixed_width::{FieldSet, FixedWidth, Reader};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Foo {
+    ///     name: String,
+    ///     age: usize,
+    /// }
+    ///
+    /// impl FixedWidth for Foo {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![
+    ///             FieldSet::new_field(0..6),
+    ///             FieldSet::new_field(6..10),
+    ///         ])
+    ///     }
+    /// }
+    ///
+    /// let reader = Reader::from_bytes_for::<Foo, _>("foobar1234foobaz6789");
+    ///
+    /// assert_eq!(reader.record_width, 10);
+    /// ```
+    pub fn from_bytes_for<T, B>(bytes: B) -> Self
+    where
+        T: FixedWidth,
+        B: Into<Vec<u8>>,
+    {
+        Self::from_reader_for::<T>(io::Cursor::new(bytes.into())).seekable()
+    }
+
+    /// Creates a new reader from a `String` or `&str`, with the width set to the extent of the
+    /// fields defined for `T`.
+    pub fn from_string_for<T, S>(s: S) -> Self
+    where
+        T: FixedWidth,
+        S: Into<String>,
+    {
+        Self::from_bytes_for::<T, _>(s.into().into_bytes())
+    }
+}
+
+/// Reads the raw data that follows the records read so far, from where the next record would
+/// be read: bytes read past a record skipped by `resync_on_linebreak` come first, and a byte order
+/// mark is skipped if `skip_bom` is set. The data is not transcoded.
+///
+/// Raw reads and records can be mixed, and no bytes are lost or read twice: `next_record` carries
+/// on from the byte after the last one read. To keep records aligned, read whole records along
+/// with the linebreak after each. Bytes read this way count towards `bytes_read`, but not
+/// `records_read`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{LineBreak, Reader};
+/// use std::io::Read;
+///
+/// let mut reader = Reader::from_string("aaaa\nbbbb\ncccc").width(4).linebreak(LineBreak::Newline);
+/// assert_eq!(reader.next_record().unwrap().unwrap(), b"aaaa");
+///
+/// let mut raw = [0; 5];
+/// reader.read_exact(&mut raw).unwrap();
+/// assert_eq!(&raw, b"bbbb\n");
+///
+/// assert_eq!(reader.next_record().unwrap().unwrap(), b"cccc");
+/// ```
+impl<R> Read for Reader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.check_bom() {
+            Ok(_) => {}
+            Err(Error::IOError(e)) => return Err(e),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        }
+        // The next record no longer starts where the last one did, so there is nothing to resync.
+        self.record_start = None;
+
+        if !self.resync_buf.is_empty() {
+            let n = self.resync_buf.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.resync_buf[..n]);
+            self.resync_buf.drain(..n);
+            return Ok(n);
+        }
+
+        let n = self.rdr.read(buf)?;
+        self.bytes_read += n as u64;
+        Ok(n)
+    }
+}
+
+impl<'a, R> Iterator for ByteReader<'a, R>
+where
+    R: Read,
+{
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.r
+            .next_record()
+            .map(|record| record.map(|r| r.to_vec()))
+    }
+}
+
+impl<'a, R> Iterator for StringReader<'a, R>
+where
+    R: Read,
+{
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.r
+            .next_record_str()
+            .map(|record| record.map(Cow::into_owned))
+    }
+}
+
+impl<'a, R, T> Iterator for DeserializeReader<'a, R, T>
+where
+    R: Read,
+    T: FixedWidth + DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.r.record_width < self.required_width && !self.r.eof {
+            self.r.eof = true;
+            return Some(Err(Error::InvalidWidth {
+                width: self.r.record_width,
+                required: self.required_width,
+            }));
+        }
+
+        let latin1 = self.r.transcodes();
+        let allow_trailing = self.r.allow_trailing_fields;
+        self.r
+            .next_record()
+            .map(|record| record.and_then(|r| deserialize_record(r, latin1, allow_trailing)))
+    }
+}
+
+/// Deserializes a record read by a reader, reading its text as ISO-8859-1 if the reader
+/// transcodes.
+fn deserialize_record<T>(record: &[u8], latin1: bool, allow_trailing: bool) -> Result<T>
+where
+    T: FixedWidth + DeserializeOwned,
+{
+    let fields;
+    let de = match T::flattened_fields() {
+        Some(flattened) => de::Deserializer::with_flattened_ref(record, flattened),
+        None => {
+            fields = T::fields();
+            de::Deserializer::with_fields(record, &fields)
+        }
+    };
+    de::deserialize_all(&mut de.latin1(latin1).allow_trailing_fields(allow_trailing))
+}
+
+impl<R> IntoIterator for Reader<R>
+where
+    R: Read,
+{
+    type Item = Result<Vec<u8>>;
+    type IntoIter = IntoByteRecords<R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_byte_records()
+    }
+}
+
+impl<R> IntoByteRecords<R> {
+    /// Gets the reader back, to read any records that remain in another way.
+    pub fn into_reader(self) -> Reader<R> {
+        self.r
+    }
+}
+
+impl<R> IntoStringRecords<R> {
+    /// Gets the reader back, to read any records that remain in another way.
+    pub fn into_reader(self) -> Reader<R> {
+        self.r
+    }
+}
+
+impl<R, T> IntoDeserialize<R, T> {
+    /// Gets the reader back, to read any records that remain in another way.
+    pub fn into_reader(self) -> Reader<R> {
+        self.r
+    }
+}
+
+impl<R> Iterator for IntoByteRecords<R>
+where
+    R: Read,
+{
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.r.byte_reader().next()
+    }
+}
+
+impl<R> Iterator for IntoStringRecords<R>
+where
+    R: Read,
+{
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.r.string_reader().next()
+    }
+}
+
+impl<R, T> Iterator for IntoDeserialize<R, T>
+where
+    R: Read,
+    T: FixedWidth + DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        DeserializeReader {
+            r: &mut self.r,
+            required_width: self.required_width,
+            _marker: PhantomData::<T>,
+        }
+        .next()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod test {
+    use super::*;
+    use crate::{FieldSet, FixedWidth};
+    use serde_derive::Deserialize;
+    use std::{
+        result,
+        sync::{Arc, Mutex},
+    };
+
+    #[test]
+    fn read_next_record() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16);
+        let mut count = 0;
+
+        while let Some(r) = rdr.next_record() {
+            count += 1;
+            assert_eq!(b"1111222233334444", r.unwrap());
+        }
+
+        assert_eq!(3, count);
+    }
+
+    #[test]
+    fn read_from_string() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows.len(), 3);
+
+        for row in rows {
+            assert_eq!("1111222233334444", row);
+        }
+    }
+
+    #[test]
+    fn read_from_string_with_newlines() {
+        let s = "1111222233334444\n1111222233334444\n1111222233334444";
+
+        let mut rdr = Reader::from_string(s)
+            .width(16)
+            .linebreak(LineBreak::Newline);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows.len(), 3);
+
+        for row in rows {
+            assert_eq!("1111222233334444", row);
+        }
+    }
+
+    #[test]
+    fn read_from_string_with_crlf() {
+        let s = "1111222233334444\r\n1111222233334444\r\n1111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16).linebreak(LineBreak::CRLF);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows.len(), 3);
+
+        for row in rows {
+            assert_eq!("1111222233334444", row);
+        }
+    }
+
+    #[test]
+    fn read_bad_linebreak() {
+        let s = "1111\r\n2222\n3333\r\n4444\r";
+
+        let mut rdr = Reader::from_string(s).width(4).linebreak(LineBreak::CRLF);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"1111");
+
+        let err = rdr.next_record().unwrap().unwrap_err();
+        match err {
+            Error::BadLineBreak {
+                record_index,
+                ref expected,
+                ref found,
+            } => {
+                assert_eq!(record_index, 1);
+                assert_eq!(expected, b"\r\n");
+                assert_eq!(found, b"\n3");
+            }
+            ref other => panic!("expected a bad linebreak, got {:?}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "expected linebreak \"\\r\\n\" after record 1, found \"\\n3\""
+        );
+        assert_eq!(rdr.records_read(), 2);
+
+        // A partial linebreak at the end of the data is not checked.
+        let mut rdr = Reader::from_string("1111\r\n2222\r")
+            .width(4)
+            .linebreak(LineBreak::CRLF);
+        let rows: Vec<String> = rdr.string_reader().collect::<Result<_>>().unwrap();
+        assert_eq!(rows, vec!["1111", "2222"]);
+
+        // A detected linebreak is checked after the first record.
+        let mut rdr = Reader::from_string("1111\n2222\r\n3333")
+            .width(4)
+            .detect_linebreak();
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"1111");
+        assert!(matches!(
+            rdr.next_record(),
+            Some(Err(Error::BadLineBreak {
+                record_index: 1,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn read_from_bytes() {
+        let b = "111122223333444411112222333344441111222233334444".as_bytes();
+
+        let mut rdr = Reader::from_bytes(b).width(16);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows.len(), 3);
+
+        for row in rows {
+            assert_eq!("1111222233334444", row);
+        }
+    }
+
+    #[test]
+    fn read_from_bytes_with_crlf() {
+        let b = "1111222233334444\r\n1111222233334444\r\n1111222233334444".as_bytes();
+
+        let mut rdr = Reader::from_bytes(b).width(16).linebreak(LineBreak::CRLF);
+
+        let rows = rdr
+            .byte_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<Vec<u8>>>();
+
+        assert_eq!(rows.len(), 3);
+
+        for row in rows {
+            assert_eq!(b"1111222233334444".to_vec(), row);
+        }
+    }
+
+    #[test]
+    fn detect_no_linebreak() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16).detect_linebreak();
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rdr.linebreak, LineBreak::None);
+        assert_eq!(rows, vec!["1111222233334444"; 3]);
+    }
+
+    #[test]
+    fn detect_newline_linebreak() {
+        let s = "1111222233334444\n1111222233334444\n1111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16).detect_linebreak();
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rdr.linebreak, LineBreak::Newline);
+        assert_eq!(rows, vec!["1111222233334444"; 3]);
+    }
+
+    #[test]
+    fn detect_crlf_linebreak() {
+        let s = "1111222233334444\r\n1111222233334444\r\n1111222233334444\r\n";
+
+        let mut rdr = Reader::from_string(s).width(16).detect_linebreak();
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rdr.linebreak, LineBreak::CRLF);
+        assert_eq!(rows, vec!["1111222233334444"; 3]);
+    }
+
+    #[test]
+    fn read_mixed_linebreaks_leniently() {
+        let s = "1111222233334444\r\n1111222233334444\n1111222233334444\r\n1111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16).lenient_linebreaks(true);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows, vec!["1111222233334444"; 4]);
+    }
+
+    #[test]
+    fn read_lenient_linebreaks_without_separators() {
+        let s = "1111222233334444\n11112222333344441111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(16).lenient_linebreaks(true);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows, vec!["1111222233334444"; 3]);
+    }
+
+    #[test]
+    fn skip_blank_records() {
+        let s = "                \n1111222233334444\n                \n                \n1111222233334444\n                ";
+
+        let mut rdr = Reader::from_string(s)
+            .width(16)
+            .linebreak(LineBreak::Newline)
+            .skip_blank_records(true);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows, vec!["1111222233334444"; 2]);
+        assert_eq!(rdr.records_read(), 6);
+    }
+
+    #[test]
+    fn keep_blank_records_by_default() {
+        let s = "1111222233334444                ";
+
+        let mut rdr = Reader::from_string(s).width(16);
+
+        let rows = rdr
+            .string_reader()
+            .filter_map(result::Result::ok)
+            .collect::<Vec<String>>();
+
+        assert_eq!(rows, vec!["1111222233334444", "                "]);
+    }
+
+    #[test]
+    fn records_read_counts_skipped_blank_records() {
+        let s = "    1111    2222";
+
+        let mut rdr = Reader::from_string(s).width(4).skip_blank_records(true);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"1111");
+        assert_eq!(rdr.records_read(), 2);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"2222");
+        assert_eq!(rdr.records_read(), 4);
+        assert!(rdr.next_record().is_none());
+    }
+
+    #[derive(Deserialize)]
+    struct Test {
+        a: String,
+        b: String,
+        c: usize,
+    }
+
+    impl FixedWidth for Test {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..4),
+                FieldSet::new_field(4..8),
+                FieldSet::new_field(8..16),
+            ])
+        }
+    }
+
+    #[test]
+    fn read_deserialized() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let mut rdr = Reader::from_string_for::<Test, _>(s);
+        let rows = rdr
+            .deserialize::<Test>()
+            .collect::<Result<Vec<Test>>>()
+            .unwrap();
+
+        assert_eq!(rdr.record_width, 16);
+        assert_eq!(rows.len(), 3);
+
+        for row in rows {
+            assert_eq!(row.a, "1111");
+            assert_eq!(row.b, "2222");
+            assert_eq!(row.c, 33334444);
+        }
+    }
+
+    #[test]
+    fn read_deserialized_owned_strings() {
+        use serde::de::{self, Visitor};
+        use std::fmt;
+
+        // Refuses to borrow from the input, as a type that copies out of each record must.
+        #[derive(Debug, PartialEq)]
+        struct Copied(String);
+
+        impl<'de> serde::Deserialize<'de> for Copied {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> result::Result<Self, D::Error> {
+                struct CopiedVisitor;
+
+                impl<'de> Visitor<'de> for CopiedVisitor {
+                    type Value = Copied;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a string")
+                    }
+
+                    fn visit_borrowed_str<E: de::Error>(
+                        self,
+                        _: &'de str,
+                    ) -> result::Result<Copied, E> {
+                        Err(E::custom("string borrowed from the record"))
+                    }
+
+                    fn visit_str<E: de::Error>(self, s: &str) -> result::Result<Copied, E> {
+                        Ok(Copied(s.to_string()))
+                    }
                 }
-                _ => Err(Error::from(e)),
+
+                d.deserialize_string(CopiedVisitor)
+            }
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            a: Copied,
+            b: Copied,
+        }
+
+        impl FixedWidth for Row {
+            fn fields() -> FieldSet {
+                FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)])
+            }
+        }
+
+        let mut rdr = Reader::from_string_for::<Row, _>("aaaabbbbccccdddd");
+        let rows = rdr
+            .deserialize::<Row>()
+            .collect::<Result<Vec<Row>>>()
+            .unwrap();
+
+        let row = |a: &str, b: &str| Row {
+            a: Copied(a.to_string()),
+            b: Copied(b.to_string()),
+        };
+        assert_eq!(rows, vec![row("aaaa", "bbbb"), row("cccc", "dddd")]);
+    }
+
+    #[test]
+    fn read_deserialized_with_short_width() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let mut rdr = Reader::from_string(s).width(8);
+        let mut rows = rdr.deserialize::<Test>();
+
+        match rows.next() {
+            Some(Err(Error::InvalidWidth { width, required })) => {
+                assert_eq!(width, 8);
+                assert_eq!(required, 16);
+            }
+            _ => panic!("expected an invalid width error"),
+        }
+
+        assert!(rows.next().is_none());
+    }
+
+    #[test]
+    fn read_deserialized_leading_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Lead {
+            a: String,
+        }
+
+        impl FixedWidth for Lead {
+            fn fields() -> FieldSet {
+                FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)])
+            }
+        }
+
+        let mut rdr = Reader::from_string("aaaabbbb").width(8);
+        assert!(matches!(
+            rdr.deserialize::<Lead>().next(),
+            Some(Err(Error::DeserializeError(
+                crate::DeserializeError::UnconsumedFields(_)
+            )))
+        ));
+
+        let lead = |a: &str| Lead { a: a.to_string() };
+        let mut rdr = Reader::from_string("aaaabbbbccccdddd")
+            .width(8)
+            .allow_trailing_fields(true);
+        let rows = rdr.deserialize().collect::<Result<Vec<Lead>>>().unwrap();
+        assert_eq!(rows, vec![lead("aaaa"), lead("cccc")]);
+
+        let rows = Reader::from_string("aaaabbbb")
+            .width(8)
+            .allow_trailing_fields(true)
+            .into_deserialize()
+            .collect::<Result<Vec<Lead>>>()
+            .unwrap();
+        assert_eq!(rows, vec![lead("aaaa")]);
+
+        let mut rdr = Reader::from_string("aaaabbbb")
+            .width(8)
+            .allow_trailing_fields(true);
+        let (rows, errors) = rdr.deserialize_lossy::<Lead>();
+        assert_eq!(rows, vec![lead("aaaa")]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn read_owned_byte_records() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let rows = Reader::from_string(s)
+            .width(16)
+            .into_byte_records()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(rows, vec![b"1111222233334444".to_vec(); 3]);
+    }
+
+    #[test]
+    fn read_owned_string_records() {
+        let mut rows = Reader::from_string("aaaabbbb")
+            .width(4)
+            .into_string_records();
+
+        assert_eq!(rows.next().unwrap().unwrap(), "aaaa");
+        assert_eq!(rows.next().unwrap().unwrap(), "bbbb");
+        assert!(rows.next().is_none());
+    }
+
+    #[test]
+    fn string_records_invalid_utf8() {
+        let data = &b"abcd\nab\xffd\nwxyz"[..];
+        let reader = || {
+            Reader::from_bytes(data)
+                .width(4)
+                .linebreak(LineBreak::Newline)
+        };
+
+        let rows: Vec<String> = reader().string_reader().collect::<Result<_>>().unwrap();
+        assert_eq!(rows, vec!["abcd", "ab\u{fffd}d", "wxyz"]);
+
+        let mut rows = reader()
+            .utf8_policy(Utf8Policy::Strict)
+            .into_string_records();
+        assert_eq!(rows.next().unwrap().unwrap(), "abcd");
+        match rows.next() {
+            Some(Err(Error::Record { index: 1, source })) => match *source {
+                Error::FormatError(ref e) => assert_eq!(e.as_bytes(), b"ab\xffd"),
+                ref e => panic!("unexpected error: {:?}", e),
             },
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert_eq!(rows.next().unwrap().unwrap(), "wxyz");
+        assert!(rows.next().is_none());
+
+        let err = reader()
+            .utf8_policy(Utf8Policy::Strict)
+            .string_reader()
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+        match err {
+            Error::Record {
+                index: 1,
+                ref source,
+            } => assert_eq!(
+                source.to_string(),
+                "invalid utf-8 sequence of 1 bytes from index 2"
+            ),
+            ref other => panic!("expected a record error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn string_records_match_lossy_conversion() {
+        let mut data = Vec::new();
+        for i in 0..20_000u32 {
+            let mut record = format!("{:06}é{:013}", i, i * 7).into_bytes();
+            if i % 97 == 0 {
+                record[i as usize % 20] = 0xff;
+            }
+            data.extend_from_slice(&record);
+        }
+
+        let expected: Vec<String> = data
+            .chunks(21)
+            .map(|r| String::from_utf8_lossy(r).to_string())
+            .collect();
+        let rows: Vec<String> = Reader::from_bytes(&data[..])
+            .width(21)
+            .string_reader()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(rows.len(), 20_000);
+        assert_eq!(rows, expected);
+
+        let mut reader = Reader::from_bytes(&data[..])
+            .width(21)
+            .utf8_policy(Utf8Policy::Strict);
+        let mut invalid = Vec::new();
+        for expected in &expected {
+            match reader.next_record_str().unwrap() {
+                Ok(s) => assert_eq!(s, *expected),
+                Err(Error::Record { index, .. }) => invalid.push(index),
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+        }
+        assert!(reader.next_record_str().is_none());
+        assert_eq!(invalid, (0..20_000).step_by(97).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn read_owned_deserialized() {
+        let s = "111122223333444411112222333344441111222233334444";
+
+        let rows = Reader::from_string_for::<Test, _>(s)
+            .into_deserialize::<Test>()
+            .collect::<Result<Vec<Test>>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].c, 33334444);
+    }
+
+    #[test]
+    fn read_owned_deserialized_with_short_width() {
+        let mut rows = Reader::from_string("11112222")
+            .width(8)
+            .into_deserialize::<Test>();
+
+        assert!(matches!(
+            rows.next(),
+            Some(Err(Error::InvalidWidth {
+                width: 8,
+                required: 16
+            }))
+        ));
+        assert!(rows.next().is_none());
+    }
+
+    #[test]
+    fn read_with_into_iterator() {
+        let mut count = 0;
+
+        for record in Reader::from_string("aaaabbbb").width(4) {
+            assert_eq!(record.unwrap().len(), 4);
+            count += 1;
+        }
+
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn read_owned_then_into_reader() {
+        let mut rows = Reader::from_string("aaaabbbbcccc")
+            .width(4)
+            .into_byte_records();
+        assert_eq!(rows.next().unwrap().unwrap(), b"aaaa");
+
+        let mut rdr = rows.into_reader();
+        assert_eq!(rdr.records_read(), 1);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"bbbb");
+    }
+
+    /// Hides the `Seek` implementation of the wrapped reader.
+    struct NonSeekable<R>(R);
+
+    impl<R: Read> Read for NonSeekable<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    fn skip_then_read<R: Read>(mut rdr: Reader<R>, n: u64) -> (u64, Vec<Vec<u8>>, u64) {
+        let skipped = rdr.skip_records(n).unwrap();
+        let rows = rdr.byte_reader().collect::<Result<Vec<_>>>().unwrap();
+        (skipped, rows, rdr.records_read())
+    }
+
+    #[test]
+    fn skip_records_seeking_and_reading_agree() {
+        let data = "0001\r\n0002\r\n0003\r\n0004\r\n0005";
+
+        for n in 0..7 {
+            let seeking = Reader::from_string(data)
+                .width(4)
+                .linebreak(LineBreak::CRLF);
+            let reading = Reader::from_reader(NonSeekable(data.as_bytes()))
+                .width(4)
+                .linebreak(LineBreak::CRLF);
+
+            let expected = (
+                n.min(5),
+                (n.min(5)..5).map(|i| format!("{:04}", i + 1).into_bytes()),
+            );
+            let result = skip_then_read(seeking, n);
+            assert_eq!(result, skip_then_read(reading, n));
+            assert_eq!(result.0, expected.0);
+            assert_eq!(result.1, expected.1.collect::<Vec<_>>());
+            assert_eq!(result.2, 5);
+        }
+    }
+
+    #[test]
+    fn skip_records_with_trailing_linebreak_and_partial_record() {
+        for data in ["aaaa\nbbbb\n", "aaaa\nbbbb\ncc"] {
+            let seeking = Reader::from_string(data)
+                .width(4)
+                .linebreak(LineBreak::Newline);
+            let reading = Reader::from_reader(NonSeekable(data.as_bytes()))
+                .width(4)
+                .linebreak(LineBreak::Newline);
+
+            assert_eq!(skip_then_read(seeking, 5), (2, vec![], 2));
+            assert_eq!(skip_then_read(reading, 5), (2, vec![], 2));
+        }
+    }
+
+    #[test]
+    fn skip_records_after_header() {
+        let data = "HEAD0001000200030004";
+
+        let mut rdr = Reader::from_string(data).width(4);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"HEAD");
+        assert_eq!(rdr.skip_records(2).unwrap(), 2);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"0003");
+        assert_eq!(rdr.records_read(), 4);
+    }
+
+    #[test]
+    fn skip_records_reads_when_widths_vary() {
+        let mut rdr = Reader::from_string("aaaa    bbbb    cccc")
+            .width(4)
+            .skip_blank_records(true);
+
+        assert_eq!(rdr.skip_records(2).unwrap(), 2);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"cccc");
+        assert_eq!(rdr.records_read(), 5);
+
+        let mut rdr = Reader::from_string("aaaa\nbbbb\r\ncccc")
+            .width(4)
+            .lenient_linebreaks(true);
+
+        assert_eq!(rdr.skip_records(2).unwrap(), 2);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"cccc");
+    }
+
+    #[test]
+    fn skip_records_returns_read_errors() {
+        let mut rdr = Reader::from_reader(NonSeekable("aaaa|bbbb".as_bytes()))
+            .width(4)
+            .linebreak(LineBreak::Newline);
+
+        assert!(matches!(
+            rdr.skip_records(2),
+            Err(Error::BadLineBreak {
+                record_index: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn take_records_caps_iteration() {
+        let mut rdr = Reader::from_string("aaaabbbbccccdddd")
+            .width(4)
+            .take_records(2);
+
+        assert_eq!(rdr.skip_records(1).unwrap(), 1);
+        let rows = rdr.string_reader().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(rows, vec!["bbbb", "cccc"]);
+        assert!(rdr.next_record().is_none());
+        assert_eq!(rdr.records_read(), 3);
+    }
+
+    #[test]
+    fn take_records_with_non_seekable_skip() {
+        let mut rdr = Reader::from_reader(NonSeekable("aaaabbbbccccdddd".as_bytes()))
+            .width(4)
+            .take_records(1);
+
+        assert_eq!(rdr.skip_records(2).unwrap(), 2);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"cccc");
+        assert!(rdr.next_record().is_none());
+    }
+
+    #[test]
+    fn record_at_indices() {
+        for (data, linebreak) in [
+            ("aaaabbbbccccdddd", LineBreak::None),
+            ("aaaa\nbbbb\ncccc\ndddd\n", LineBreak::Newline),
+            ("aaaa\r\nbbbb\r\ncccc\r\ndddd", LineBreak::CRLF),
+        ] {
+            let mut rdr = Reader::from_string(data).width(4).linebreak(linebreak);
+
+            assert_eq!(rdr.record_count().unwrap(), 4);
+            assert_eq!(rdr.record_at(3).unwrap().unwrap(), b"dddd");
+            assert_eq!(rdr.record_at(0).unwrap().unwrap(), b"aaaa");
+            assert_eq!(rdr.record_at(2).unwrap().unwrap(), b"cccc");
+            assert!(rdr.record_at(4).unwrap().is_none());
+            assert!(rdr.record_at(1000).unwrap().is_none());
+            assert_eq!(rdr.record_at(1).unwrap().unwrap(), b"bbbb");
+        }
+    }
+
+    #[test]
+    fn record_at_past_largest_offset() {
+        let mut rdr = Reader::from_string("HEADER\naaaa\nbbbb\n")
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .data_offset(7);
+
+        assert!(rdr.record_at(u64::MAX / 2).unwrap().is_none());
+        assert!(rdr.record_at(u64::MAX).unwrap().is_none());
+        // The offset of the record fits, but not once the data offset is added to it.
+        assert!(rdr.record_at(u64::MAX / 5).unwrap().is_none());
+        assert_eq!(rdr.record_at(1).unwrap().unwrap(), b"bbbb");
+    }
+
+    #[test]
+    fn record_at_then_read_in_order() {
+        let mut rdr = Reader::from_string("aaaa\r\nbbbb\r\ncccc\r\ndddd")
+            .width(4)
+            .linebreak(LineBreak::CRLF);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aaaa");
+        assert_eq!(rdr.record_at(2).unwrap().unwrap(), b"cccc");
+        assert_eq!(rdr.records_read(), 3);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"dddd");
+        assert!(rdr.next_record().is_none());
+
+        // Reaching the end does not stop random access.
+        assert_eq!(rdr.record_at(1).unwrap().unwrap(), b"bbbb");
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"cccc");
+    }
+
+    #[test]
+    fn record_at_with_data_offset() {
+        let mut rdr = Reader::from_string("HEADER\naaaa\nbbbb\n")
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .data_offset(7);
+
+        assert_eq!(rdr.record_count().unwrap(), 2);
+        assert_eq!(rdr.record_at(1).unwrap().unwrap(), b"bbbb");
+        assert_eq!(rdr.record_at(0).unwrap().unwrap(), b"aaaa");
+    }
+
+    #[test]
+    fn record_count_of_empty_data() {
+        let mut rdr = Reader::from_string("").width(4).linebreak(LineBreak::CRLF);
+
+        assert_eq!(rdr.record_count().unwrap(), 0);
+        assert!(rdr.record_at(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn record_count_names_remainder() {
+        for (data, linebreak, index, expected, read) in [
+            ("aaaabbbbcc", LineBreak::None, 2, 4, 2),
+            ("aaaa\nbbbb\nc", LineBreak::Newline, 2, 4, 1),
+            ("aaaa\r\nbbbb\r", LineBreak::CRLF, 1, 6, 5),
+        ] {
+            let mut rdr = Reader::from_string(data).width(4).linebreak(linebreak);
+
+            match rdr.record_count() {
+                Err(Error::Record { index: i, source }) => {
+                    assert_eq!(i, index);
+                    assert!(matches!(
+                        *source,
+                        Error::IncompleteRecord { expected: e, read: r } if e == expected && r == read
+                    ));
+                }
+                r => panic!("unexpected result: {:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    fn record_count_keeps_position() {
+        let mut rdr = Reader::from_string("aaaabbbbcccc").width(4);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aaaa");
+        assert_eq!(rdr.record_count().unwrap(), 3);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"bbbb");
+    }
+
+    #[test]
+    fn record_at_needs_known_widths() {
+        let mut rdr = Reader::from_string("aaaa\nbbbb")
+            .width(4)
+            .lenient_linebreaks(true);
+
+        match rdr.record_at(1) {
+            Err(Error::IOError(e)) => assert_eq!(e.kind(), io::ErrorKind::Unsupported),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert!(matches!(
+            Reader::from_string("aaaa").record_count(),
+            Err(Error::InvalidWidth { width: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn skip_bom_matches_clean_data() {
+        let clean = "foo1\r\nbar2\r\nbaz3";
+        let bommed = format!("\u{feff}{}", clean);
+
+        let read = |data: &str| {
+            Reader::from_string(data)
+                .width(4)
+                .linebreak(LineBreak::CRLF)
+                .skip_bom(true)
+                .string_reader()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        };
+
+        assert_eq!(read(&bommed), read(clean));
+        assert_eq!(read(clean), vec!["foo1", "bar2", "baz3"]);
+    }
+
+    #[test]
+    fn bom_kept_by_default() {
+        let mut rdr = Reader::from_string("\u{feff}foo1").width(4);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"\xEF\xBB\xBFf");
+    }
+
+    #[test]
+    fn skip_bom_deserialized() {
+        let data = "\u{feff}1111222233334444";
+        let mut rdr = Reader::from_string_for::<Test, _>(data).skip_bom(true);
+        let rows = rdr
+            .deserialize::<Test>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(rows[0].a, "1111");
+        assert_eq!(rows[0].c, 33334444);
+    }
+
+    #[test]
+    fn skip_bom_rejects_utf16() {
+        for data in [&b"\xFF\xFEf\x00o\x00"[..], b"\xFE\xFF\x00f\x00o"] {
+            let mut rdr = Reader::from_bytes(data).width(2).skip_bom(true);
+
+            match rdr.next_record() {
+                Some(Err(Error::IOError(e))) => {
+                    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                    assert_eq!(e.to_string(), "UTF-16 input is not supported");
+                }
+                r => panic!("unexpected result: {:?}", r.map(|r| r.map(<[u8]>::to_vec))),
+            }
+            assert!(rdr.next_record().is_none());
+        }
+    }
+
+    /// Reads a single byte at a time.
+    struct OneByte<R>(R);
+
+    impl<R: Read> Read for OneByte<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn skip_bom_split_across_reads() {
+        let read = |data: &'static [u8], linebreak| {
+            Reader::from_reader(OneByte(data))
+                .width(4)
+                .linebreak(linebreak)
+                .skip_bom(true)
+                .byte_reader()
+                .collect::<Result<Vec<_>>>()
+        };
+
+        for linebreak in [LineBreak::None, LineBreak::Newline] {
+            let data: &[u8] = match linebreak {
+                LineBreak::None => b"\xEF\xBB\xBFfoo1bar2",
+                _ => b"\xEF\xBB\xBFfoo1\nbar2",
+            };
+            assert_eq!(read(data, linebreak).unwrap(), vec![b"foo1", b"bar2"]);
+        }
+
+        // Data that only starts like a mark is read as it is.
+        assert_eq!(
+            read(b"\xEF\xBB\x80a", LineBreak::None).unwrap(),
+            vec![b"\xEF\xBB\x80a"]
+        );
+        assert_eq!(
+            read(b"\xEF", LineBreak::None).unwrap(),
+            Vec::<Vec<u8>>::new()
+        );
+
+        let mut rdr = Reader::from_reader(OneByte(&b"\xEF\xBBfoo1"[..]))
+            .width(4)
+            .skip_bom(true);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"\xEF\xBBfo");
+        assert_eq!(rdr.bytes_read(), 4);
+
+        match read(b"\xFF\xFEf\x00", LineBreak::None) {
+            Err(Error::IOError(e)) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn skip_bom_with_seeking() {
+        let data = "\u{feff}aaaa\nbbbb\ncccc";
+
+        let mut rdr = Reader::from_string(data)
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .skip_bom(true);
+        assert_eq!(rdr.skip_records(1).unwrap(), 1);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"bbbb");
+
+        let mut rdr = Reader::from_string(data)
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .skip_bom(true);
+        assert_eq!(rdr.record_count().unwrap(), 3);
+        assert_eq!(rdr.record_at(2).unwrap().unwrap(), b"cccc");
+        assert_eq!(rdr.record_at(0).unwrap().unwrap(), b"aaaa");
+    }
+
+    #[test]
+    fn skip_bom_short_data() {
+        let mut rdr = Reader::from_string("ab").width(2).skip_bom(true);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"ab");
+        assert!(rdr.next_record().is_none());
+    }
+
+    type ProgressLog = Arc<Mutex<Vec<(u64, u64)>>>;
+
+    fn progress_log<R: Read>(rdr: Reader<R>) -> (Reader<R>, ProgressLog) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&log);
+        let rdr = rdr.on_record(move |records, bytes| seen.lock().unwrap().push((records, bytes)));
+        (rdr, log)
+    }
+
+    #[test]
+    fn progress_with_header_and_short_record() {
+        let data = "HEADER\r\naaaa\r\nbbbb\r\ncc";
+        let (mut rdr, log) = progress_log(
+            Reader::from_string(data)
+                .width(4)
+                .linebreak(LineBreak::CRLF),
+        );
+
+        let mut header = [0; 8];
+        rdr.read_exact(&mut header).unwrap();
+        assert_eq!(rdr.bytes_read(), 8);
+
+        assert_eq!(rdr.byte_reader().count(), 2);
+        assert_eq!(rdr.records_read(), 2);
+        assert_eq!(rdr.bytes_read(), data.len() as u64);
+        assert_eq!(*log.lock().unwrap(), vec![(1, 14), (2, 20)]);
+    }
+
+    #[test]
+    fn progress_with_skipped_records() {
+        let data = "aaaa\n    \nbbbb\ncccc\ndddd";
+
+        let (mut rdr, log) = progress_log(
+            Reader::from_string(data)
+                .width(4)
+                .linebreak(LineBreak::Newline)
+                .skip_blank_records(true),
+        );
+        assert_eq!(rdr.byte_reader().count(), 4);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![(1, 5), (2, 10), (3, 15), (4, 20), (5, 24)]
+        );
+
+        let (mut rdr, log) = progress_log(
+            Reader::from_string(data)
+                .width(4)
+                .linebreak(LineBreak::Newline),
+        );
+        assert_eq!(rdr.skip_records(3).unwrap(), 3);
+        assert_eq!(rdr.bytes_read(), 15);
+        assert_eq!(rdr.skip_records(5).unwrap(), 2);
+        assert_eq!(rdr.bytes_read(), 24);
+        assert_eq!(*log.lock().unwrap(), vec![(3, 15), (5, 24)]);
+    }
+
+    #[test]
+    fn progress_with_lenient_linebreaks_and_bom() {
+        let mut rdr = Reader::from_string("\u{feff}aaaa\r\nbbbb\ncccc")
+            .width(4)
+            .lenient_linebreaks(true)
+            .skip_bom(true);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aaaa");
+        assert_eq!(rdr.bytes_read(), 9);
+        assert_eq!(rdr.byte_reader().count(), 2);
+        assert_eq!(rdr.bytes_read(), 18);
+    }
+
+    #[test]
+    fn bytes_read_after_record_at() {
+        let mut rdr = Reader::from_string("aaaa\r\nbbbb\r\ncccc")
+            .width(4)
+            .linebreak(LineBreak::CRLF);
+
+        assert_eq!(rdr.record_at(1).unwrap().unwrap(), b"bbbb");
+        assert_eq!(rdr.bytes_read(), 12);
+        assert_eq!(rdr.record_at(0).unwrap().unwrap(), b"aaaa");
+        assert_eq!(rdr.bytes_read(), 6);
+    }
+
+    /// Yields each chunk in turn, where `None` is the end of the data for now.
+    struct Trickle(std::collections::VecDeque<Option<&'static [u8]>>);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.pop_front() {
+                Some(Some(chunk)) => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    Ok(chunk.len())
+                }
+                _ => Ok(0),
+            }
         }
     }
 
-    // TODO: use skip_relative once stable
-    #[inline]
-    fn read_linebreak(&mut self) -> Result<()> {
-        if !self.has_linebreak() {
-            return Ok(());
+    fn follow_mode() -> FollowMode {
+        FollowMode {
+            poll_interval: Duration::from_millis(1),
+            idle_timeout: Some(Duration::from_millis(50)),
         }
+    }
+
+    #[test]
+    fn follow_waits_for_whole_records() {
+        let chunks = vec![
+            Some(&b"aa"[..]),
+            None,
+            Some(b"aa"),
+            None,
+            None,
+            Some(b"\nbb"),
+            None,
+            Some(b"bb"),
+            None,
+            Some(b"\ncc"),
+        ];
+        let mut rdr = Reader::from_reader(Trickle(chunks.into()))
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .follow(follow_mode());
 
-        if let Err(e) = self.rdr.read_exact(&mut self.linebreak_buf) {
-            // There will not necessarily be a trailing line break, so if reading the linebreak
-            // results in an EOF error, mark the reader done and return without error.
-            match e.kind() {
-                io::ErrorKind::UnexpectedEof => self.eof = true,
-                _ => return Err(Error::from(e)),
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aaaa");
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"bbbb");
+        // The partial record is dropped once the idle timeout elapses.
+        assert!(rdr.next_record().is_none());
+        assert_eq!(rdr.records_read(), 2);
+        assert_eq!(rdr.bytes_read(), 12);
+    }
+
+    #[test]
+    fn follow_idle_timeout_spans_waits() {
+        /// Data that never arrives, counting how often it is asked for.
+        struct Empty(Arc<Mutex<usize>>);
+
+        impl Read for Empty {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                *self.0.lock().unwrap() += 1;
+                Ok(0)
             }
         }
 
-        Ok(())
-    }
-}
+        let reads = Arc::new(Mutex::new(0));
+        let mut rdr = Reader::from_reader(Empty(Arc::clone(&reads)))
+            .width(4)
+            .follow(FollowMode {
+                poll_interval: Duration::from_millis(20),
+                idle_timeout: Some(Duration::from_millis(50)),
+            });
 
-impl Reader<fs::File> {
-    /// Creates a new reader from a filepath. Will return an io::Error if there are any issues
-    /// opening the file.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Ok(Self::from_reader(fs::File::open(path)?))
+        assert!(rdr.next_record().is_none());
+        // Looking for a byte order mark waits out the timeout, after which reading the record
+        // gives up at once rather than waiting all over again.
+        assert!(
+            *reads.lock().unwrap() <= 5,
+            "{} reads",
+            reads.lock().unwrap()
+        );
     }
-}
 
-impl Reader<io::Cursor<Vec<u8>>> {
-    /// Creates a new reader from a series of bytes.
-    pub fn from_bytes<T>(bytes: T) -> Self
-    where
-        T: Into<Vec<u8>>,
-    {
-        Self::from_reader(io::Cursor::new(bytes.into()))
-    }
+    #[test]
+    fn follow_with_lenient_linebreaks() {
+        let chunks = vec![Some(&b"aaaa\r"[..]), None, Some(b"\nbbbb"), None];
+        let mut rdr = Reader::from_reader(Trickle(chunks.into()))
+            .width(4)
+            .lenient_linebreaks(true)
+            .follow(follow_mode());
+        let records: Vec<String> = rdr.string_reader().map(Result::unwrap).collect();
 
-    /// Creates a new reader from a `String` or `&str`.
-    pub fn from_string<T>(s: T) -> Self
-    where
-        T: Into<String>,
-    {
-        Self::from_bytes(s.into().into_bytes())
+        assert_eq!(records, vec!["aaaa", "bbbb"]);
     }
-}
 
-impl<R> Read for Reader<R>
-where
-    R: Read,
-{
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.rdr.read(buf)
+    #[test]
+    fn read_with_zero_width() {
+        let mut rdr = Reader::from_string("1111");
+
+        match rdr.next_record() {
+            Some(Err(Error::InvalidWidth { width: 0, .. })) => {}
+            _ => panic!("expected an invalid width error"),
+        }
+
+        assert!(rdr.next_record().is_none());
     }
-}
 
-impl<'a, R> Iterator for ByteReader<'a, R>
-where
-    R: Read,
-{
-    type Item = Result<Vec<u8>>;
+    #[test]
+    fn read_deserialized_lossy() {
+        let s = "1111222233334444\n11112222333x4444\n1111222233334444\n111122223-334444\n1111222233334444";
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.r
-            .next_record()
-            .map(|record| record.map(|r| r.to_vec()))
+        let mut rdr = Reader::from_string(s)
+            .width(16)
+            .linebreak(LineBreak::Newline);
+        let (rows, errors) = rdr.deserialize_lossy::<Test>();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(errors.len(), 2);
+
+        assert_eq!(errors[0].index, 1);
+        assert_eq!(errors[0].bytes, b"11112222333x4444");
+        assert!(matches!(errors[0].error, Error::DeserializeError(_)));
+
+        assert_eq!(errors[1].index, 3);
+        assert_eq!(errors[1].bytes, b"111122223-334444");
+        assert!(matches!(errors[1].error, Error::DeserializeError(_)));
     }
-}
 
-impl<'a, R> Iterator for StringReader<'a, R>
-where
-    R: Read,
-{
-    type Item = Result<String>;
+    #[test]
+    fn read_deserialized_lossy_with_short_width() {
+        let mut rdr = Reader::from_string("1111222233334444").width(4);
+        let (rows, errors) = rdr.deserialize_lossy::<Test>();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.r
-            .next_record()
-            .map(|record| record.map(|r| String::from_utf8_lossy(r).to_string()))
+        assert!(rows.is_empty());
+        assert!(matches!(
+            errors[0].error,
+            Error::InvalidWidth {
+                width: 4,
+                required: 16
+            }
+        ));
     }
-}
 
-#[cfg(test)]
-#[allow(dead_code)]
-mod test {
-    use super::*;
-    use crate::{FieldSet, FixedWidth};
-    use serde_derive::Deserialize;
-    use std::result;
+    #[test]
+    fn read_record_into_buffer() {
+        let s = "1111222233334444\n5555666677778888";
+
+        let mut rdr = Reader::from_string(s)
+            .width(16)
+            .linebreak(LineBreak::Newline);
+        let mut buf = b"leftover".to_vec();
+
+        assert!(rdr.read_record_into(&mut buf).unwrap());
+        assert_eq!(buf, b"1111222233334444");
+        assert!(rdr.read_record_into(&mut buf).unwrap());
+        assert_eq!(buf, b"5555666677778888");
+        assert!(!rdr.read_record_into(&mut buf).unwrap());
+    }
 
     #[test]
-    fn read_next_record() {
-        let s = "111122223333444411112222333344441111222233334444";
+    fn read_with_capacities() {
+        let s = "1111222233334444\r\n5555666677778888\r\n99990000aaaabbbb";
 
-        let mut rdr = Reader::from_string(s).width(16);
-        let mut count = 0;
+        for capacity in [1, 2, 3, 16, 17, 18, 1 << 20] {
+            let mut rdr = Reader::with_capacity(capacity, s.as_bytes())
+                .width(16)
+                .linebreak(LineBreak::CRLF);
 
-        while let Some(r) = rdr.next_record() {
-            count += 1;
-            assert_eq!(b"1111222233334444", r.unwrap());
+            let rows = rdr
+                .string_reader()
+                .filter_map(result::Result::ok)
+                .collect::<Vec<String>>();
+
+            assert_eq!(rdr.buffer_capacity(), capacity);
+            assert_eq!(
+                rows,
+                vec!["1111222233334444", "5555666677778888", "99990000aaaabbbb"]
+            );
         }
+    }
 
-        assert_eq!(3, count);
+    #[test]
+    fn read_rdw_records() {
+        let mut data = vec![0, 20, 0, 0];
+        data.extend_from_slice(b"1111222233334444");
+        data.extend_from_slice(&[0, 8, 0, 0]);
+        data.extend_from_slice(b"5555");
+
+        let mut rdr = Reader::from_bytes(data).length_prefixed(LengthPrefix::Rdw);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"1111222233334444");
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"5555");
+        assert!(rdr.next_record().is_none());
+        assert_eq!(rdr.records_read(), 2);
     }
 
     #[test]
-    fn read_from_string() {
-        let s = "111122223333444411112222333344441111222233334444";
+    fn read_big_endian_prefixed_records() {
+        let mut data = vec![0, 3, 0xff];
+        data.extend_from_slice(b"foo");
+        data.extend_from_slice(&[0, 0, 0xff]);
+        data.extend_from_slice(&[0, 6, 0xff]);
+        data.extend_from_slice(b"barbaz");
 
-        let mut rdr = Reader::from_string(s).width(16);
+        let mut rdr =
+            Reader::from_bytes(data).length_prefixed(LengthPrefix::BigEndianU16 { skip: 1 });
 
         let rows = rdr
-            .string_reader()
+            .byte_reader()
             .filter_map(result::Result::ok)
-            .collect::<Vec<String>>();
+            .collect::<Vec<Vec<u8>>>();
 
-        assert_eq!(rows.len(), 3);
+        assert_eq!(rows, vec![b"foo".to_vec(), vec![], b"barbaz".to_vec()]);
+    }
 
-        for row in rows {
-            assert_eq!("1111222233334444", row);
+    #[test]
+    fn read_prefixed_record_with_truncated_prefix() {
+        let mut data = vec![0, 7, 0, 0];
+        data.extend_from_slice(b"foo");
+        data.extend_from_slice(&[0, 7]);
+
+        let mut rdr = Reader::from_bytes(data).length_prefixed(LengthPrefix::Rdw);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"foo");
+        match rdr.next_record() {
+            Some(Err(Error::IncompleteRecord {
+                expected: 4,
+                read: 2,
+            })) => {}
+            _ => panic!("expected an incomplete record error"),
         }
+        assert!(rdr.next_record().is_none());
     }
 
     #[test]
-    fn read_from_string_with_newlines() {
-        let s = "1111222233334444\n1111222233334444\n1111222233334444";
+    fn read_prefixed_record_with_truncated_record() {
+        let mut data = vec![0, 10, 0, 0];
+        data.extend_from_slice(b"foo");
+
+        let mut rdr = Reader::from_bytes(data).length_prefixed(LengthPrefix::Rdw);
+
+        match rdr.next_record() {
+            Some(Err(Error::IncompleteRecord {
+                expected: 6,
+                read: 3,
+            })) => {}
+            _ => panic!("expected an incomplete record error"),
+        }
+        assert!(rdr.next_record().is_none());
+    }
+
+    fn width_from_header(header: &[u8]) -> Result<usize> {
+        crate::from_bytes_with_fields(header, FieldSet::new_field(2..4))
+    }
+
+    #[test]
+    fn read_dynamic_width_records() {
+        let s = "AA04\nBB10barbaz\r\nCC04";
 
         let mut rdr = Reader::from_string(s)
-            .width(16)
-            .linebreak(LineBreak::Newline);
+            .lenient_linebreaks(true)
+            .dynamic_width(4, width_from_header);
 
         let rows = rdr
             .string_reader()
             .filter_map(result::Result::ok)
             .collect::<Vec<String>>();
 
-        assert_eq!(rows.len(), 3);
+        assert_eq!(rows, vec!["AA04", "BB10barbaz", "CC04"]);
+    }
 
-        for row in rows {
-            assert_eq!("1111222233334444", row);
+    #[test]
+    fn read_dynamic_width_smaller_than_header() {
+        let s = "AA04BB02CC04";
+
+        let mut rdr = Reader::from_string(s).dynamic_width(4, width_from_header);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"AA04");
+        match rdr.next_record() {
+            Some(Err(Error::Record { index: 1, source })) => match *source {
+                Error::InvalidWidth {
+                    width: 2,
+                    required: 4,
+                } => {}
+                e => panic!("unexpected source error: {}", e),
+            },
+            _ => panic!("expected a record error"),
         }
+        assert!(rdr.next_record().is_none());
     }
 
     #[test]
-    fn read_from_string_with_crlf() {
-        let s = "1111222233334444\r\n1111222233334444\r\n1111222233334444";
+    fn read_dynamic_width_zero() {
+        let mut rdr = Reader::from_string("AA04").dynamic_width(0, |_| Ok(4));
 
-        let mut rdr = Reader::from_string(s).width(16).linebreak(LineBreak::CRLF);
+        assert!(matches!(
+            rdr.next_record(),
+            Some(Err(Error::InvalidWidth {
+                width: 0,
+                required: 1
+            }))
+        ));
+        assert!(rdr.next_record().is_none());
 
-        let rows = rdr
-            .string_reader()
-            .filter_map(result::Result::ok)
-            .collect::<Vec<String>>();
+        let mut rdr = Reader::from_string("AA04").dynamic_width(2, |_| Ok(0));
 
-        assert_eq!(rows.len(), 3);
+        match rdr.next_record() {
+            Some(Err(Error::Record { index: 0, source })) => {
+                assert!(matches!(
+                    *source,
+                    Error::InvalidWidth {
+                        width: 0,
+                        required: 2
+                    }
+                ))
+            }
+            _ => panic!("expected a record error"),
+        }
+        assert!(rdr.next_record().is_none());
+    }
 
-        for row in rows {
-            assert_eq!("1111222233334444", row);
+    #[test]
+    fn read_dynamic_width_with_failing_width_fn() {
+        let s = "AA04BBxxCC04";
+
+        let mut rdr = Reader::from_string(s).dynamic_width(4, width_from_header);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"AA04");
+        match rdr.next_record() {
+            Some(Err(Error::Record { index: 1, source })) => {
+                assert!(matches!(*source, Error::DeserializeError(_)))
+            }
+            _ => panic!("expected a record error"),
         }
     }
 
     #[test]
-    fn read_from_bytes() {
-        let b = "111122223333444411112222333344441111222233334444".as_bytes();
+    fn read_dynamic_width_with_truncated_record() {
+        let s = "AA10foo";
 
-        let mut rdr = Reader::from_bytes(b).width(16);
+        let mut rdr = Reader::from_string(s).dynamic_width(4, width_from_header);
 
-        let rows = rdr
-            .string_reader()
-            .filter_map(result::Result::ok)
-            .collect::<Vec<String>>();
+        assert!(matches!(
+            rdr.next_record(),
+            Some(Err(Error::IncompleteRecord {
+                expected: 6,
+                read: 3
+            }))
+        ));
+    }
 
-        assert_eq!(rows.len(), 3);
+    #[test]
+    fn read_line_records() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Note {
+            id: String,
+            kind: String,
+            text: String,
+        }
 
-        for row in rows {
-            assert_eq!("1111222233334444", row);
+        impl FixedWidth for Note {
+            fn fields() -> FieldSet {
+                FieldSet::Seq(vec![
+                    FieldSet::new_field(0..4),
+                    FieldSet::new_field(4..6),
+                    FieldSet::new_field_to_end(6),
+                ])
+            }
         }
+
+        let note = |id: &str, kind: &str, text: &str| Note {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            text: text.to_string(),
+        };
+
+        let s =
+            "0001ABsome free text\r\n0002CD\r\n0003\r\n\r\n0005EFlonger than the prefix\r\n0006GH";
+        let mut rdr = Reader::from_string_for::<Note, _>(s)
+            .linebreak(LineBreak::CRLF)
+            .line_records(40);
+
+        let notes: Vec<Note> = rdr.deserialize().map(Result::unwrap).collect();
+        assert_eq!(
+            notes,
+            vec![
+                note("0001", "AB", "some free text"),
+                note("0002", "CD", ""),
+                note("0003", "", ""),
+                note("", "", ""),
+                note("0005", "EF", "longer than the prefix"),
+                note("0006", "GH", ""),
+            ]
+        );
+        assert_eq!(rdr.records_read(), 6);
+        assert_eq!(rdr.bytes_read(), s.len() as u64);
     }
 
     #[test]
-    fn read_from_bytes_with_crlf() {
-        let b = "1111222233334444\r\n1111222233334444\r\n1111222233334444".as_bytes();
+    fn read_line_records_too_long() {
+        let s = "0001abc\n0002abcdefgh\n0003";
+        let mut rdr = Reader::from_string(s)
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .line_records(8);
 
-        let mut rdr = Reader::from_bytes(b).width(16).linebreak(LineBreak::CRLF);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"0001abc");
+        match rdr.next_record() {
+            Some(Err(Error::Record { index: 1, source })) => match *source {
+                Error::InvalidWidth {
+                    width: 8,
+                    required: 12,
+                } => {}
+                e => panic!("unexpected source error: {}", e),
+            },
+            _ => panic!("expected a record error"),
+        }
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"0003");
+        assert!(rdr.next_record().is_none());
+    }
 
-        let rows = rdr
-            .byte_reader()
-            .filter_map(result::Result::ok)
-            .collect::<Vec<Vec<u8>>>();
+    #[test]
+    fn line_records_without_linebreak() {
+        let mut rdr = Reader::from_string("0001\n0002").width(5).line_records(8);
 
-        assert_eq!(rows.len(), 3);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"0001\n");
+        assert!(rdr.next_record().is_none());
+    }
 
-        for row in rows {
-            assert_eq!(b"1111222233334444".to_vec(), row);
-        }
+    fn line_mode_reader(s: &str, on_mismatch: OnMismatch) -> Reader<io::Cursor<Vec<u8>>> {
+        Reader::from_string(s)
+            .width(4)
+            .linebreak(LineBreak::CRLF)
+            .line_mode(LineMode {
+                expect_width: None,
+                on_mismatch,
+            })
     }
 
-    #[derive(Deserialize)]
-    struct Test {
-        a: String,
-        b: String,
-        c: usize,
+    fn read_lines<R: Read>(rdr: &mut Reader<R>) -> Vec<result::Result<String, (u64, usize)>> {
+        rdr.string_reader()
+            .map(|r| match r {
+                Ok(s) => Ok(s),
+                Err(Error::Record { index, source }) => match *source {
+                    Error::LineWidth { actual, .. } => Err((index, actual)),
+                    e => panic!("unexpected error: {}", e),
+                },
+                Err(e) => panic!("unexpected error: {}", e),
+            })
+            .collect()
     }
 
-    impl FixedWidth for Test {
-        fn fields() -> FieldSet {
-            FieldSet::Seq(vec![
-                FieldSet::new_field(0..4),
-                FieldSet::new_field(4..8),
-                FieldSet::new_field(8..16),
-            ])
+    const MISMATCHED_LINES: &str = "aaaa\r\nbb\r\ncccccc\r\ndddd\r\n\r\neeee";
+
+    #[test]
+    fn line_mode_skip() {
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&progress);
+        let mut rdr = line_mode_reader(MISMATCHED_LINES, OnMismatch::Skip)
+            .on_record(move |records, bytes| seen.lock().unwrap().push((records, bytes)));
+
+        let lines = read_lines(&mut rdr);
+        assert_eq!(
+            lines,
+            vec![Ok("aaaa".into()), Ok("dddd".into()), Ok("eeee".into())]
+        );
+        assert_eq!(rdr.records_read(), 6);
+        // Skipped lines are reported as they are read past.
+        assert_eq!(
+            *progress.lock().unwrap(),
+            vec![(1, 6), (2, 10), (3, 18), (4, 24), (5, 26), (6, 30)]
+        );
+    }
+
+    #[test]
+    fn line_mode_error() {
+        let mut rdr = line_mode_reader(MISMATCHED_LINES, OnMismatch::Error);
+
+        let lines = read_lines(&mut rdr);
+        assert_eq!(
+            lines,
+            vec![
+                Ok("aaaa".into()),
+                Err((1, 2)),
+                Err((2, 6)),
+                Ok("dddd".into()),
+                Err((4, 0)),
+                Ok("eeee".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_mode_pad() {
+        let mut rdr = line_mode_reader(MISMATCHED_LINES, OnMismatch::Pad);
+
+        let lines = read_lines(&mut rdr);
+        assert_eq!(
+            lines,
+            vec![
+                Ok("aaaa".into()),
+                Ok("bb  ".into()),
+                Err((2, 6)),
+                Ok("dddd".into()),
+                Ok("    ".into()),
+                Ok("eeee".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_mode_truncate() {
+        let mut rdr = line_mode_reader(MISMATCHED_LINES, OnMismatch::Truncate);
+
+        let lines = read_lines(&mut rdr);
+        assert_eq!(
+            lines,
+            vec![
+                Ok("aaaa".into()),
+                Err((1, 2)),
+                Ok("cccc".into()),
+                Ok("dddd".into()),
+                Err((4, 0)),
+                Ok("eeee".into()),
+            ]
+        );
+        assert_eq!(rdr.bytes_read(), MISMATCHED_LINES.len() as u64);
+    }
+
+    #[test]
+    fn line_mode_expected_width() {
+        let mut rdr = Reader::from_string("abcdefgh\nabcdefghij\nabc")
+            .linebreak(LineBreak::Newline)
+            .line_mode(LineMode {
+                expect_width: Some(8),
+                on_mismatch: OnMismatch::Truncate,
+            });
+
+        let lines = read_lines(&mut rdr);
+        assert_eq!(
+            lines,
+            vec![Ok("abcdefgh".into()), Ok("abcdefgh".into()), Err((2, 3))]
+        );
+    }
+
+    fn read_resynced<R: Read>(rdr: &mut Reader<R>) -> Vec<result::Result<String, Range<u64>>> {
+        rdr.string_reader()
+            .map(|r| match r {
+                Ok(s) => Ok(s),
+                Err(Error::Resync { skipped, .. }) => Err(skipped),
+                Err(e) => panic!("unexpected error: {}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resync_after_long_and_short_lines() {
+        let s = "0001\r\n0002XX\r\n0003\r\n04\r\n0005\r\n0006";
+        let mut rdr = Reader::from_string(s)
+            .width(4)
+            .linebreak(LineBreak::CRLF)
+            .resync_on_linebreak(true);
+
+        assert_eq!(
+            read_resynced(&mut rdr),
+            vec![
+                Ok("0001".into()),
+                Err(6..14),
+                Ok("0003".into()),
+                Err(20..24),
+                Ok("0005".into()),
+                Ok("0006".into()),
+            ]
+        );
+        assert_eq!(rdr.records_read(), 6);
+        assert_eq!(rdr.bytes_read(), s.len() as u64);
+    }
+
+    #[test]
+    fn resync_to_end_of_data() {
+        let s = "0001\n0002XX";
+        let mut rdr = Reader::from_string(s)
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .resync_on_linebreak(true);
+
+        assert_eq!(read_resynced(&mut rdr), vec![Ok("0001".into()), Err(5..11)]);
+    }
+
+    #[test]
+    fn resync_off_keeps_bad_linebreak_error() {
+        let s = "0001\n0002XX\n0003";
+        let mut rdr = Reader::from_string(s)
+            .width(4)
+            .linebreak(LineBreak::Newline);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"0001");
+        assert!(matches!(
+            rdr.next_record(),
+            Some(Err(Error::BadLineBreak {
+                record_index: 1,
+                ..
+            }))
+        ));
+        assert_eq!(rdr.mark_bad_record().unwrap(), None);
+    }
+
+    #[test]
+    fn mark_bad_record_resyncs() {
+        let s = "0001\n02\n0003\n0004";
+        let mut rdr = Reader::from_string(s)
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .strict_linebreaks(false)
+            .resync_on_linebreak(true);
+
+        let mut ids = Vec::new();
+        let mut skipped = Vec::new();
+        while let Some(record) = rdr.next_record() {
+            match crate::from_bytes_with_fields::<u32>(record.unwrap(), FieldSet::new_field(0..4)) {
+                Ok(id) => ids.push(id),
+                Err(_) => {
+                    skipped.push(rdr.mark_bad_record().unwrap().unwrap());
+                    // A record can only be skipped once.
+                    assert_eq!(rdr.mark_bad_record().unwrap(), None);
+                }
+            }
         }
+
+        assert_eq!(ids, vec![1, 3, 4]);
+        assert_eq!(skipped, vec![5..8]);
     }
 
     #[test]
-    fn test_read() {
-        let b = "111122223333444411112222333344441111222233334444".as_bytes();
+    fn read_between_records() {
+        let data = "aaaa\nbbbb\ncccc\ndddd";
+        let mut rdr = Reader::from_string(data)
+            .width(4)
+            .linebreak(LineBreak::Newline);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aaaa");
+
+        let mut raw = [0; 5];
+        rdr.read_exact(&mut raw).unwrap();
+        assert_eq!(&raw, b"bbbb\n");
+        assert_eq!(rdr.bytes_read(), 10);
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"cccc");
+
+        let mut rest = String::new();
+        rdr.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "dddd");
 
-        let mut rdr = Reader::from_bytes(b);
+        assert!(rdr.next_record().is_none());
+        assert_eq!(rdr.records_read(), 2);
+        assert_eq!(rdr.bytes_read(), data.len() as u64);
+    }
+
+    #[test]
+    fn read_part_of_record() {
+        let mut rdr = Reader::from_string("aaaabbbbcccc").width(4);
+
+        let mut raw = [0; 2];
+        rdr.read_exact(&mut raw).unwrap();
+        assert_eq!(&raw, b"aa");
+
+        // Records carry on from the byte after the last one read.
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aabb");
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"bbcc");
+        // The `cc` left over is a short record, which is dropped.
+        assert!(rdr.next_record().is_none());
+    }
+
+    #[test]
+    fn read_skips_bom_and_keeps_resync_bytes() {
+        let mut rdr = Reader::from_string("\u{feff}abcd").skip_bom(true);
+        let mut raw = String::new();
+        rdr.read_to_string(&mut raw).unwrap();
+        assert_eq!(raw, "abcd");
 
-        let mut buf = vec![0; 16];
-        let bytes_read = rdr.read(&mut buf).unwrap();
+        let mut rdr = Reader::from_string("aaaa\nbb\ncccc\ndddd")
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .resync_on_linebreak(true);
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"aaaa");
+        assert!(matches!(rdr.next_record(), Some(Err(Error::Resync { .. }))));
 
-        assert_eq!(buf, b[..bytes_read].to_vec());
+        // The `c` read past the bad record is not lost.
+        let mut raw = String::new();
+        rdr.read_to_string(&mut raw).unwrap();
+        assert_eq!(raw, "cccc\ndddd");
     }
 }
@@ -0,0 +1,659 @@
+use crate::{
+    de::{self, DeserializeError},
+    error::Error,
+    ser::{self, FieldSerializeReason, SerializeError, Serializer},
+    Deserializer, FieldConfig, FieldSet, Result, RANGE_KEY_LEN,
+};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, slice, sync::Arc};
+
+/// A single record whose fields are read and written by name, for working with records without
+/// defining a type for them. The raw bytes of the record are kept as they are, and a field is only
+/// parsed when it is read, so fields that are never read cost nothing and are never changed.
+/// Setting a field pads and justifies the new value as serializing does, and writes it over the
+/// field's bytes in place, leaving every other byte of the record untouched.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Record};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..6).name("name"),
+///     FieldSet::new_field(6..9).name("age").pad_with('0').justify("right"),
+///     FieldSet::new_field(9..12).name("city"),
+/// ]);
+///
+/// let mut rec = Record::parse(b"Carl  030NYC", &fields).unwrap();
+///
+/// assert_eq!(rec.get_str("name").unwrap(), "Carl");
+/// assert_eq!(rec.get::<u32>("age").unwrap(), 30);
+///
+/// rec.set("age", 31).unwrap();
+///
+/// assert_eq!(rec.as_bytes(), b"Carl  031NYC");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Record {
+    bytes: Vec<u8>,
+    fields: Arc<[FieldConfig]>,
+}
+
+impl Record {
+    /// Creates a record from the given bytes and fields. Fails with a
+    /// `DeserializeError::UnexpectedEndOfRecord` for the first field that the bytes are too short
+    /// to hold, as a field that is not there could be neither read nor set.
+    ///
+    /// The fields are flattened each time. To read many records with the same fields, flatten
+    /// them once with `FieldSet::flatten` and use `Record::with_flattened`.
+    pub fn parse(bytes: &[u8], fields: &FieldSet) -> Result<Self> {
+        Self::with_flattened(bytes.to_vec(), fields.clone().flatten())
+    }
+
+    /// Creates a record from the given bytes and fields that have already been flattened, such as
+    /// by `FieldSet::flatten`. Passing an `Arc<[FieldConfig]>` shares the fields between records
+    /// rather than copying them.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldConfig, FieldSet, Record};
+    /// use std::sync::Arc;
+    ///
+    /// let fields: Arc<[FieldConfig]> = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..8).name("qty"),
+    /// ])
+    /// .flatten()
+    /// .into();
+    ///
+    /// for line in ["0001  12", "0002   7"] {
+    ///     let rec = Record::with_flattened(line, fields.clone()).unwrap();
+    ///     assert!(rec.get::<u32>("qty").unwrap() > 0);
+    /// }
+    /// ```
+    pub fn with_flattened<B, F>(bytes: B, fields: F) -> Result<Self>
+    where
+        B: Into<Vec<u8>>,
+        F: Into<Arc<[FieldConfig]>>,
+    {
+        let record = Self {
+            bytes: bytes.into(),
+            fields: fields.into(),
+        };
+
+        if let Some(conf) = record
+            .fields
+            .iter()
+            .find(|conf| conf.slice(&record.bytes).is_none())
+        {
+            return Err(field_error(conf, DeserializeError::UnexpectedEndOfRecord));
+        }
+
+        Ok(record)
+    }
+
+    /// Deserializes the value of the named field. Fails with `Error::UnknownField` if the record
+    /// has no field of that name, or with a `DeserializeError::Field` if the field's data cannot
+    /// be read as a `T`, such as letters in a number field.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Record};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("name"),
+    ///     FieldSet::new_field(4..7).name("age"),
+    /// ]);
+    /// let rec = Record::parse(b"Carl 3x", &fields).unwrap();
+    ///
+    /// let name: &str = rec.get("name").unwrap();
+    /// assert_eq!(name, "Carl");
+    ///
+    /// let err = rec.get::<u32>("age").unwrap_err();
+    /// assert_eq!(err.to_string(), "field 'age' (4..7): invalid digit found in string");
+    /// ```
+    pub fn get<'a, T: Deserialize<'a>>(&'a self, name: &str) -> Result<T> {
+        let conf = self.field(name)?;
+        let mut de = Deserializer::with_flattened_ref(&self.bytes, slice::from_ref(conf));
+
+        T::deserialize(&mut de).map_err(|e| field_error(conf, e))
+    }
+
+    /// Gets the value of the named field as a string, trimmed of whitespace, and of its pad char
+    /// if it is centered. A field with a default value gives the default when it is blank.
+    pub fn get_str(&self, name: &str) -> Result<Cow<'_, str>> {
+        let conf = self.field(name)?;
+
+        de::field_str(conf, &self.bytes).map_err(|e| field_error(conf, e))
+    }
+
+    /// Gets the raw bytes of the named field, including any padding.
+    pub fn get_bytes(&self, name: &str) -> Result<&[u8]> {
+        let conf = self.field(name)?;
+
+        conf.slice(&self.bytes)
+            .ok_or_else(|| field_error(conf, DeserializeError::UnexpectedEndOfRecord))
+    }
+
+    /// Serializes the given value into the named field, padding and justifying it as the field
+    /// is configured. As when serializing a whole record, a value wider than the field is cut
+    /// short to fit it. A field that runs to the end of the record takes the value as it is, so
+    /// the record grows or shrinks to fit it.
+    ///
+    /// Fails with `Error::UnknownField` if the record has no field of that name, or with a
+    /// `SerializeError` if the value cannot be written to the field. Literal fields cannot be set.
+    /// The record is left unchanged when an error is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Record};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("name"),
+    ///     FieldSet::new_field(10..14).name("qty").justify("right"),
+    /// ]);
+    /// let mut rec = Record::parse(b"0001Carl    12", &fields).unwrap();
+    ///
+    /// rec.set("name", "Bo").unwrap();
+    /// rec.set("qty", 7).unwrap();
+    ///
+    /// assert_eq!(rec.as_bytes(), b"0001Bo       7");
+    /// ```
+    pub fn set<T: Serialize>(&mut self, name: &str, value: T) -> Result<()> {
+        let conf = self.field(name)?;
+        if conf.literal.is_some() || conf.capture_only {
+            return Err(Error::from(SerializeError::Message(format!(
+                "field '{}' cannot be set",
+                conf.describe()
+            ))));
+        }
+
+        let mut bytes = Vec::with_capacity(conf.width());
+        value.serialize(&mut Serializer::with_flattened_ref(
+            &mut bytes,
+            slice::from_ref(conf),
+        ))?;
+
+        let start = conf.range.start;
+        if conf.to_end {
+            self.bytes.truncate(start);
+            self.bytes.extend_from_slice(&bytes);
+        } else {
+            self.bytes[start..start + bytes.len()].copy_from_slice(&bytes);
+        }
+
+        Ok(())
+    }
+
+    /// The names of the record's named fields, in the order they were given.
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().filter_map(|conf| conf.name())
+    }
+
+    /// The fields of the record.
+    pub fn fields(&self) -> &[FieldConfig] {
+        &self.fields
+    }
+
+    /// The bytes of the record, with any fields that were set written in.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Gets the bytes of the record, with any fields that were set written in.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    fn field(&self, name: &str) -> Result<&FieldConfig> {
+        self.fields
+            .iter()
+            .find(|conf| conf.name() == Some(name))
+            .ok_or_else(|| Error::UnknownField(name.to_string()))
+    }
+}
+
+impl AsRef<[u8]> for Record {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Formats a record from pairs of a field name and its value, without serde. Each value has its
+/// case changed, is passed to the field's `serialize_with` function and is padded and justified
+/// as the field is configured. Fields that are not given a value are written as their default
+/// value, or as padding. Literal fields are written as their literal, and bytes of the record
+/// that no field covers are spaces. A field without a name is set by its range, such as `"7..11"`,
+/// as `parse_record` names it, so that the pairs `parse_record` returns can be formatted back.
+///
+/// Fails with `Error::UnknownField` for a name that no field has, with a `SerializeError` for a
+/// name given more than once or for a literal field, and with a `SerializeError::Field` with a
+/// reason of `FieldSerializeReason::TooWide` for a value that is wider than its field, rather
+/// than cutting the value short.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::FieldSet;
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_literal(0..1, "D"),
+///     FieldSet::new_field(1..7).name("name"),
+///     FieldSet::new_field(7..11).name("qty").pad_with('0').justify("right"),
+///     FieldSet::new_field(11..13).name("country").default_value("US"),
+/// ]);
+///
+/// let record = fixed_width::format_record(&[("qty", "42"), ("name", "Carl")], &fields).unwrap();
+/// assert_eq!(record, b"DCarl  0042US");
+/// ```
+pub fn format_record(values: &[(&str, &str)], fields: &FieldSet) -> Result<Vec<u8>> {
+    let mut buf = [0; RANGE_KEY_LEN];
+
+    for (i, (name, _)) in values.iter().enumerate() {
+        let conf = fields
+            .iter_flat()
+            .find(|conf| conf.key(&mut buf) == *name)
+            .ok_or_else(|| Error::UnknownField(name.to_string()))?;
+        if conf.literal.is_some() || conf.capture_only {
+            return Err(Error::from(SerializeError::Message(format!(
+                "field '{}' cannot be set",
+                name
+            ))));
+        }
+        if values[..i].iter().any(|(other, _)| other == name) {
+            return Err(Error::from(SerializeError::Message(format!(
+                "field '{}' is given more than once",
+                name
+            ))));
+        }
+    }
+
+    let mut out = Vec::with_capacity(fields.total_width());
+
+    for conf in fields.iter_flat() {
+        if conf.capture_only {
+            continue;
+        }
+
+        let key = conf.key(&mut buf);
+        let value = values
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| conf.case.apply(value));
+        let bytes: Cow<[u8]> = match (&conf.literal, value) {
+            _ if conf.write_as_blank => Cow::Borrowed(&[]),
+            (Some(literal), _) => Cow::Borrowed(literal.as_bytes()),
+            (None, Some(value)) => match conf.serialize_with {
+                Some(ref f) => Cow::Owned((f.0)(value.as_bytes())?),
+                None => Cow::Owned(value.into_owned().into_bytes()),
+            },
+            (None, None) => Cow::Borrowed(conf.default.as_deref().unwrap_or_default().as_bytes()),
+        };
+
+        if !conf.to_end && bytes.len() > conf.width() {
+            return Err(ser::field_error(
+                conf,
+                FieldSerializeReason::TooWide {
+                    actual: bytes.len(),
+                },
+            ));
+        }
+
+        let start = conf.range.start;
+        let end = start + ser::padded_len(&bytes, conf);
+        if out.len() < end {
+            out.resize(end, b' ');
+        }
+        ser::write_padded(&mut &mut out[start..end], &bytes, conf)?;
+    }
+
+    Ok(out)
+}
+
+/// Parses a record into pairs of a field name and its value, without serde, in the same order as
+/// `FieldSet::iter_flat`. Values are read as by `Record::get_str`, trimmed of whitespace, with
+/// the field's default value in place of a blank field. Fields without a name are given their
+/// range as a name, as when deserializing into a map, which `format_record` accepts in turn.
+/// Literal and `capture_only` fields are left out.
+///
+/// Fails with a `DeserializeError::Field` for a field past the end of the record, or for a field
+/// that is not valid UTF-8.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::FieldSet;
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_literal(0..1, "D"),
+///     FieldSet::new_field(1..7).name("name"),
+///     FieldSet::new_field(7..11).pad_with('0').justify("right"),
+/// ]);
+///
+/// let values = fixed_width::parse_record(b"DCarl  0042", &fields).unwrap();
+/// assert_eq!(
+///     values,
+///     vec![
+///         ("name".to_string(), "Carl".to_string()),
+///         ("7..11".to_string(), "0042".to_string()),
+///     ]
+/// );
+/// ```
+pub fn parse_record(bytes: &[u8], fields: &FieldSet) -> Result<Vec<(String, String)>> {
+    fields
+        .iter_flat()
+        .filter(|conf| conf.literal.is_none() && !conf.capture_only)
+        .map(|conf| {
+            let value = de::field_str(conf, bytes).map_err(|e| field_error(conf, e))?;
+            Ok((conf.describe(), value.into_owned()))
+        })
+        .collect()
+}
+
+/// Wraps the given error with the name and range of the field it occurred for.
+fn field_error(conf: &FieldConfig, source: DeserializeError) -> Error {
+    let source = match source {
+        e @ DeserializeError::Field { .. } => e,
+        e => DeserializeError::Field {
+            name: conf.describe(),
+            range: conf.range.clone(),
+            source: Box::new(e),
+        },
+    };
+    Error::from(source)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Case;
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..4)
+                .name("id")
+                .pad_with('0')
+                .justify("right"),
+            FieldSet::new_field(4..10).name("name"),
+            FieldSet::new_field(10..13).name("age").justify("right"),
+            FieldSet::new_field(13..16).name("city"),
+        ])
+    }
+
+    #[test]
+    fn get_typed_fields() {
+        let rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+
+        assert_eq!(rec.get::<u16>("id").unwrap(), 42);
+        assert_eq!(rec.get::<String>("name").unwrap(), "Carl");
+        assert_eq!(rec.get::<u32>("age").unwrap(), 30);
+        assert_eq!(rec.get::<Option<u32>>("age").unwrap(), Some(30));
+        assert_eq!(rec.get_str("city").unwrap(), "NYC");
+        assert_eq!(rec.get_bytes("name").unwrap(), b"Carl  ");
+    }
+
+    #[test]
+    fn get_borrows_from_record() {
+        let rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+        let name: &str = rec.get("name").unwrap();
+
+        assert_eq!(name, "Carl");
+        assert!(matches!(
+            rec.get_str("name").unwrap(),
+            Cow::Borrowed("Carl")
+        ));
+    }
+
+    #[test]
+    fn get_unknown_field() {
+        let rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+
+        let err = rec.get::<u32>("height").unwrap_err();
+        assert!(matches!(err, Error::UnknownField(ref name) if name == "height"));
+        assert_eq!(err.to_string(), "no field named 'height'");
+        assert!(matches!(rec.get_str("height"), Err(Error::UnknownField(_))));
+    }
+
+    #[test]
+    fn get_type_mismatch() {
+        let rec = Record::parse(b"0042Carl   3xNYC", &fields()).unwrap();
+
+        match rec.get::<u32>("age").unwrap_err() {
+            Error::DeserializeError(DeserializeError::Field {
+                name,
+                range,
+                source,
+            }) => {
+                assert_eq!(name, "age");
+                assert_eq!(range, 10..13);
+                assert!(matches!(*source, DeserializeError::ParseIntError(_)));
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        // The other fields can still be read.
+        assert_eq!(rec.get::<u16>("id").unwrap(), 42);
+    }
+
+    #[test]
+    fn get_blank_field_uses_default() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("name"),
+            FieldSet::new_field(4..7).name("qty").default_value("1"),
+        ]);
+        let rec = Record::parse(b"Carl   ", &fields).unwrap();
+
+        assert_eq!(rec.get::<u32>("qty").unwrap(), 1);
+        assert_eq!(rec.get_str("qty").unwrap(), "1");
+        assert_eq!(rec.get_bytes("qty").unwrap(), b"   ");
+    }
+
+    #[test]
+    fn parse_short_record() {
+        let err = Record::parse(b"0042Carl   30", &fields()).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "field 'city' (13..16): byte length of record was less than defined length"
+        );
+    }
+
+    #[test]
+    fn set_middle_field_round_trip() {
+        let mut rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+
+        rec.set("name", "Dan").unwrap();
+
+        assert_eq!(rec.as_bytes(), b"0042Dan    30NYC");
+        assert_eq!(rec.get_str("name").unwrap(), "Dan");
+        assert_eq!(rec.get::<u16>("id").unwrap(), 42);
+        assert_eq!(rec.get::<u32>("age").unwrap(), 30);
+        assert_eq!(rec.get_str("city").unwrap(), "NYC");
+
+        let reparsed = Record::parse(&rec.clone().into_bytes(), &fields()).unwrap();
+        assert_eq!(reparsed.as_bytes(), rec.as_bytes());
+        assert_eq!(reparsed.get_str("name").unwrap(), "Dan");
+    }
+
+    #[test]
+    fn set_pads_and_justifies() {
+        let mut rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+
+        rec.set("id", 7).unwrap();
+        rec.set("age", 101).unwrap();
+
+        assert_eq!(rec.as_bytes(), b"0007Carl  101NYC");
+        assert_eq!(rec.get::<u16>("id").unwrap(), 7);
+    }
+
+    #[test]
+    fn set_truncates_wide_value() {
+        let mut rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+
+        rec.set("name", "Bartholomew").unwrap();
+
+        assert_eq!(rec.as_bytes(), b"0042Bartho 30NYC");
+    }
+
+    #[test]
+    fn set_error_leaves_record_unchanged() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..1).name("flag"),
+            FieldSet::new_field(1..4).name("code"),
+        ]);
+        let mut rec = Record::parse(b"YABC", &fields).unwrap();
+
+        match rec.set("flag", 'é').unwrap_err() {
+            Error::SerializeError(SerializeError::Field { name, reason, .. }) => {
+                assert_eq!(name.as_deref(), Some("flag"));
+                assert_eq!(reason, FieldSerializeReason::TooWide { actual: 2 });
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+        assert!(matches!(rec.set("nope", "x"), Err(Error::UnknownField(_))));
+        assert_eq!(rec.as_bytes(), b"YABC");
+    }
+
+    #[test]
+    fn set_literal_field() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..2, "HD").name("kind"),
+            FieldSet::new_field(2..6).name("date"),
+        ]);
+        let mut rec = Record::parse(b"HD0101", &fields).unwrap();
+
+        let err = rec.set("kind", "XX").unwrap_err();
+        assert_eq!(err.to_string(), "field 'kind' cannot be set");
+        assert_eq!(rec.get_str("kind").unwrap(), "HD");
+        assert_eq!(rec.as_bytes(), b"HD0101");
+    }
+
+    #[test]
+    fn set_to_end_field() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field_to_end(4).name("notes"),
+        ]);
+        let mut rec = Record::parse(b"0001short", &fields).unwrap();
+
+        rec.set("notes", "a longer note").unwrap();
+        assert_eq!(rec.as_bytes(), b"0001a longer note");
+
+        rec.set("notes", "").unwrap();
+        assert_eq!(rec.as_bytes(), b"0001");
+        assert_eq!(rec.get_str("notes").unwrap(), "");
+    }
+
+    #[test]
+    fn field_names() {
+        let rec = Record::parse(b"0042Carl   30NYC", &fields()).unwrap();
+
+        assert_eq!(
+            rec.field_names().collect::<Vec<_>>(),
+            vec!["id", "name", "age", "city"]
+        );
+        assert_eq!(rec.fields().len(), 4);
+    }
+
+    #[test]
+    fn format_record_fills_missing_fields() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..5)
+                .name("id")
+                .pad_with('0')
+                .justify("right"),
+            FieldSet::new_field(5..11)
+                .name("name")
+                .transform(Case::Upper),
+            FieldSet::new_field(12..15).name("qty").justify("right"),
+            FieldSet::new_field(15..17)
+                .name("country")
+                .default_value("US"),
+        ]);
+
+        let record = format_record(&[("name", "carl"), ("id", "42")], &fields).unwrap();
+        assert_eq!(record, b"D0042CARL      US");
+
+        let record = format_record(&[], &fields).unwrap();
+        assert_eq!(record, b"D0000          US");
+        assert_eq!(parse_record(&record, &fields).unwrap()[0].1, "0000");
+    }
+
+    #[test]
+    fn format_record_rejects_names() {
+        let fields = fields();
+
+        assert!(matches!(
+            format_record(&[("id", "1"), ("nope", "x")], &fields),
+            Err(Error::UnknownField(ref name)) if name == "nope"
+        ));
+
+        let err = format_record(&[("id", "1"), ("name", "a"), ("id", "2")], &fields).unwrap_err();
+        assert_eq!(err.to_string(), "field 'id' is given more than once");
+
+        let literal = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..2, "HD").name("kind"),
+            FieldSet::new_field(2..6).name("date"),
+        ]);
+        let err = format_record(&[("kind", "XX")], &literal).unwrap_err();
+        assert_eq!(err.to_string(), "field 'kind' cannot be set");
+    }
+
+    #[test]
+    fn format_record_overflow() {
+        match format_record(&[("name", "Caroline")], &fields()).unwrap_err() {
+            Error::SerializeError(SerializeError::Field { name, reason, .. }) => {
+                assert_eq!(name.as_deref(), Some("name"));
+                assert_eq!(reason, FieldSerializeReason::TooWide { actual: 8 });
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        let to_end = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field_to_end(4).name("notes"),
+        ]);
+        let record = format_record(&[("notes", "as long as it needs")], &to_end).unwrap();
+        assert_eq!(record, b"    as long as it needs");
+    }
+
+    #[test]
+    fn parse_record_round_trip() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..5).name("id").justify("right"),
+            FieldSet::new_field(5..11),
+            FieldSet::new_field(11..13)
+                .name("country")
+                .default_value("US"),
+        ]);
+
+        let values = parse_record(b"D  42Carl    ", &fields).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                ("id".to_string(), "42".to_string()),
+                ("5..11".to_string(), "Carl".to_string()),
+                ("country".to_string(), "US".to_string()),
+            ]
+        );
+
+        let pairs: Vec<(&str, &str)> = values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(format_record(&pairs, &fields).unwrap(), b"D  42Carl  US");
+
+        let err = parse_record(b"D  42Ca", &fields).unwrap_err();
+        assert!(
+            err.to_string().starts_with("field '5..11' (5..11)"),
+            "{}",
+            err
+        );
+    }
+}
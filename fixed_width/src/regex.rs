@@ -0,0 +1,480 @@
+//! A small backtracking regular expression matcher for `Rule::Regex`.
+
+use std::{error::Error as StdError, fmt};
+
+/// The most a repetition such as `{m,n}` may give as its bounds.
+const MAX_REPEAT: usize = 1000;
+
+/// The deepest groups may be nested.
+const MAX_DEPTH: usize = 64;
+
+/// A compiled regular expression, as used by `Rule::Regex`.
+///
+/// The syntax is a subset of the common one: literal characters; `.` for any character; classes
+/// such as `[A-Z0-9_]` and `[^ ]`; the escapes `\d`, `\w` and `\s` and their negations `\D`, `\W`
+/// and `\S`; `\` before any other punctuation to match it literally; the anchors `^` and `$`;
+/// groups `(..)` and `(?:..)`; alternation with `|`; and the repetitions `*`, `+`, `?`, `{n}`,
+/// `{n,}` and `{n,m}`, each of which may be followed by `?` to match as few as possible. A
+/// pattern matches if it is found anywhere in the text, so use `^` and `$` to match the whole
+/// of it.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::validate::Regex;
+///
+/// let re = Regex::new(r"^[A-Z]{2}\d{3}$").unwrap();
+/// assert!(re.is_match("AB123"));
+/// assert!(!re.is_match("AB12"));
+///
+/// assert!(Regex::new("a(b").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Regex {
+    pattern: String,
+    node: Node,
+}
+
+impl Regex {
+    /// Compiles the given pattern, or returns a `RegexError` if it is not valid.
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            pattern,
+            chars: pattern.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+
+        let node = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unmatched `)`"));
+        }
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            node,
+        })
+    }
+
+    /// The pattern the regular expression was compiled from.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the pattern is found anywhere in the given text.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        (0..=text.len()).any(|start| self.node.matches(&text, start, &mut |_| true))
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+/// An error compiling the pattern of a `Regex`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    pattern: String,
+    pos: usize,
+    message: &'static str,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid regex {:?} at character {}: {}",
+            self.pattern, self.pos, self.message
+        )
+    }
+}
+
+impl StdError for RegexError {}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+impl Node {
+    /// Matches this node at `pos` in the text, calling `next` with the end of each way it
+    /// matches until `next` returns true.
+    fn matches(&self, text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+        match self {
+            Node::Char(c) => text.get(pos) == Some(c) && next(pos + 1),
+            Node::Any => pos < text.len() && next(pos + 1),
+            Node::Class { ranges, negated } => match text.get(pos) {
+                Some(c) => {
+                    let found = ranges.iter().any(|&(lo, hi)| lo <= *c && *c <= hi);
+                    found != *negated && next(pos + 1)
+                }
+                None => false,
+            },
+            Node::Start => pos == 0 && next(pos),
+            Node::End => pos == text.len() && next(pos),
+            Node::Concat(nodes) => match_all(nodes, text, pos, next),
+            Node::Alt(nodes) => nodes.iter().any(|node| node.matches(text, pos, next)),
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => match_repeat(node, *min, *max, *greedy, text, pos, 0, next),
+        }
+    }
+}
+
+fn match_all(
+    nodes: &[Node],
+    text: &[char],
+    pos: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    match nodes.split_first() {
+        Some((first, rest)) => first.matches(text, pos, &mut |p| match_all(rest, text, p, next)),
+        None => next(pos),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn match_repeat(
+    node: &Node,
+    min: usize,
+    max: Option<usize>,
+    greedy: bool,
+    text: &[char],
+    pos: usize,
+    count: usize,
+    next: &mut dyn FnMut(usize) -> bool,
+) -> bool {
+    let done = count >= min;
+    let more = max.is_none_or(|max| count < max);
+
+    let again = |next: &mut dyn FnMut(usize) -> bool| {
+        more && node.matches(text, pos, &mut |p| {
+            // Stop once a repetition matches nothing, as repeating it again never ends.
+            (p != pos || !done) && match_repeat(node, min, max, greedy, text, p, count + 1, next)
+        })
+    };
+
+    // A greedy repetition tries to match once more before stopping, a lazy one after.
+    if !greedy && done && next(pos) {
+        return true;
+    }
+    again(next) || (greedy && done && next(pos))
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> RegexError {
+        RegexError {
+            pattern: self.pattern.to_string(),
+            pos: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+
+        Ok(match branches.len() {
+            1 => branches.remove(0),
+            _ => Node::Alt(branches),
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.repeat(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("expected a character")),
+        };
+        self.pos += 1;
+
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '[' => self.class()?,
+            '\\' => self.escape()?,
+            '(' => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("groups are nested too deeply"));
+                }
+                if self.eat('?') && !self.eat(':') {
+                    return Err(self.error("only `(?:` groups are supported"));
+                }
+
+                self.depth += 1;
+                let node = self.alternation()?;
+                self.depth -= 1;
+
+                if !self.eat(')') {
+                    return Err(self.error("unclosed group"));
+                }
+                node
+            }
+            '*' | '+' | '?' | '{' => return Err(self.error("nothing to repeat")),
+            c => Node::Char(c),
+        })
+    }
+
+    fn escape(&mut self) -> Result<Node, RegexError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("expected a character after `\\`")),
+        };
+        self.pos += 1;
+
+        let (ranges, negated) = match c {
+            'd' | 'D' => (DIGIT.to_vec(), c == 'D'),
+            'w' | 'W' => (WORD.to_vec(), c == 'W'),
+            's' | 'S' => (SPACE.to_vec(), c == 'S'),
+            c if c.is_ascii_punctuation() || c == ' ' => return Ok(Node::Char(c)),
+            _ => return Err(self.error("unknown escape")),
+        };
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn class(&mut self) -> Result<Node, RegexError> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+
+        loop {
+            let c = match self.peek() {
+                Some(']') if !first => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) => c,
+                None => return Err(self.error("unclosed class")),
+            };
+            self.pos += 1;
+            first = false;
+
+            let lo = match c {
+                '\\' => match self.escape()? {
+                    Node::Char(c) => c,
+                    Node::Class {
+                        ranges: escaped,
+                        negated: false,
+                    } => {
+                        ranges.extend(escaped);
+                        continue;
+                    }
+                    _ => return Err(self.error("negated escapes cannot be used in a class")),
+                },
+                c => c,
+            };
+
+            let is_range =
+                self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']');
+            if !is_range {
+                ranges.push((lo, lo));
+                continue;
+            }
+
+            self.pos += 1;
+            let hi = match self.peek() {
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.escape()? {
+                        Node::Char(c) => c,
+                        _ => return Err(self.error("invalid range")),
+                    }
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    c
+                }
+                None => return Err(self.error("unclosed class")),
+            };
+            if hi < lo {
+                return Err(self.error("invalid range"));
+            }
+            ranges.push((lo, hi));
+        }
+
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn repeat(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.pos += 1;
+                self.bounds()?
+            }
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+
+        if matches!(atom, Node::Start | Node::End) {
+            return Err(self.error("nothing to repeat"));
+        }
+        let greedy = !self.eat('?');
+
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// Parses the bounds of `{n}`, `{n,}` or `{n,m}` after the `{`, leaving the position on the
+    /// closing `}`.
+    fn bounds(&mut self) -> Result<(usize, Option<usize>), RegexError> {
+        let min = self.number()?;
+        let max = if self.eat(',') {
+            match self.peek() {
+                Some('}') => None,
+                _ => Some(self.number()?),
+            }
+        } else {
+            Some(min)
+        };
+
+        if self.peek() != Some('}') {
+            return Err(self.error("expected `}`"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error("invalid repetition bounds"));
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Result<usize, RegexError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+            .filter(|&n| n <= MAX_REPEAT)
+            .ok_or_else(|| self.error("expected a number up to 1000"))
+    }
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn regex_matches() {
+        assert!(is_match("abc", "xxabcxx"));
+        assert!(!is_match("^abc", "xxabc"));
+        assert!(is_match("^a.c$", "a-c"));
+        assert!(is_match(r"^\d{4}-\d{2}$", "2024-01"));
+        assert!(!is_match(r"^\d{4}-\d{2}$", "2024-1"));
+        assert!(is_match("^(AA|BB)+$", "AABBAA"));
+        assert!(!is_match("^(AA|BB)+$", "AAB"));
+        assert!(is_match("^[A-Z][a-z_]*$", "Name_x"));
+        assert!(!is_match("^[^ ]+$", "a b"));
+        assert!(is_match(r"^\w+\s\W$", "ab1 !"));
+        assert!(is_match(r"^[\d.-]+$", "-12.5"));
+        assert!(is_match(r"^a{2,}b?$", "aaa"));
+        assert!(!is_match(r"^a{2,3}$", "aaaa"));
+        assert!(is_match("^(?:a*)*b$", "aaab"));
+        assert!(!is_match("^(a*)*$", "aaaaaaaaaab"));
+        assert!(is_match("^a+?b$", "aab"));
+        assert!(is_match("^é+$", "éé"));
+        assert!(is_match("", ""));
+    }
+
+    #[test]
+    fn regex_errors() {
+        let error = |pattern: &str| Regex::new(pattern).unwrap_err().to_string();
+
+        assert_eq!(
+            error("a(b"),
+            r#"invalid regex "a(b" at character 3: unclosed group"#
+        );
+        assert_eq!(
+            error("ab)"),
+            r#"invalid regex "ab)" at character 2: unmatched `)`"#
+        );
+        assert_eq!(
+            error("*a"),
+            r#"invalid regex "*a" at character 1: nothing to repeat"#
+        );
+        assert_eq!(
+            error("[z-a]"),
+            r#"invalid regex "[z-a]" at character 4: invalid range"#
+        );
+        assert_eq!(
+            error("a{3,1}"),
+            r#"invalid regex "a{3,1}" at character 5: invalid repetition bounds"#
+        );
+        assert_eq!(
+            error(r"\q"),
+            r#"invalid regex "\\q" at character 2: unknown escape"#
+        );
+        assert!(Regex::new(&"(".repeat(100)).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+use crate::{
+    error::Error,
+    ser::{self, FieldSerializeReason},
+    DeserializeError, FieldConfig, FieldSet, Reader, Result, Writer,
+};
+use std::{
+    io::{Read, Write},
+    iter,
+};
+
+/// What a `Remapper` does with a value that is wider than the field it is copied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Cuts the value short to fit the field, keeping its first bytes, as serializing does. Only
+    /// suited to text, as a number such as `12345` cut to `123` changes its value.
+    Truncate,
+    /// Returns an `Error::SerializeError` with a reason of `FieldSerializeReason::TooWide`. The
+    /// default.
+    Error,
+}
+
+/// Converts records from one layout to another, copying each field of the source layout to the
+/// field of the target layout it is mapped to. Values are trimmed of the source field's padding
+/// and padded again as the target field is, so fields can move, change width, padding and
+/// justification. Target fields that nothing is mapped to are filled with their default value,
+/// their literal value, or padding.
+///
+/// A source field that is all padding, where the padding is a digit such as `'0'`, is copied as
+/// a single padding digit rather than as a blank value, so a zero stays a zero.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Remapper};
+///
+/// let vendor = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..10).name("name"),
+///     FieldSet::new_field(10..16).name("amount").pad_with('0').justify("right"),
+/// ]);
+/// let canonical = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..8).name("amount").justify("right"),
+///     FieldSet::new_field(8..14).name("name"),
+///     FieldSet::new_field(14..16).name("country").default_value("US"),
+/// ]);
+///
+/// let remapper = Remapper::new(vendor, canonical);
+/// let mut out = Vec::new();
+/// remapper.remap(b"Carl      001250", &mut out).unwrap();
+///
+/// assert_eq!(out, b"    1250Carl  US");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Remapper {
+    targets: Vec<(FieldConfig, Option<FieldConfig>)>,
+    overflow: Overflow,
+}
+
+impl Remapper {
+    /// Creates a new remapper that copies each field of the source layout to the field of the
+    /// target layout with the same name.
+    pub fn new(source: FieldSet, target: FieldSet) -> Self {
+        let source = source.flatten();
+        let targets = target
+            .flatten()
+            .into_iter()
+            .map(|conf| {
+                let from = conf
+                    .name
+                    .as_ref()
+                    .and_then(|name| source.iter().find(|s| s.name.as_ref() == Some(name)))
+                    .cloned();
+                (conf, from)
+            })
+            .collect();
+
+        Self {
+            targets,
+            overflow: Overflow::Error,
+        }
+    }
+
+    /// Creates a new remapper that copies fields of the source layout to fields of the target
+    /// layout by the given pairs of names, source name first. Returns `Error::InvalidFieldSet` if
+    /// either layout has no field with a name given.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Remapper};
+    ///
+    /// let vendor = FieldSet::new_field(0..4).name("cust_no");
+    /// let canonical = FieldSet::new_field(0..6).name("customer").justify("right");
+    ///
+    /// let remapper = Remapper::with_pairs(vendor, canonical, &[("cust_no", "customer")]).unwrap();
+    /// let mut out = Vec::new();
+    /// remapper.remap(b"1234", &mut out).unwrap();
+    ///
+    /// assert_eq!(out, b"  1234");
+    /// ```
+    pub fn with_pairs(source: FieldSet, target: FieldSet, pairs: &[(&str, &str)]) -> Result<Self> {
+        let mut targets: Vec<(FieldConfig, Option<FieldConfig>)> = target
+            .flatten()
+            .into_iter()
+            .map(|conf| (conf, None))
+            .collect();
+
+        for (from, to) in pairs {
+            let from = source.get(from).cloned().ok_or_else(|| {
+                Error::InvalidFieldSet(format!("no source field named {:?}", from))
+            })?;
+            let (_, slot) = targets
+                .iter_mut()
+                .find(|(conf, _)| conf.name.as_deref() == Some(to))
+                .ok_or_else(|| Error::InvalidFieldSet(format!("no target field named {:?}", to)))?;
+            *slot = Some(from);
+        }
+
+        Ok(Self {
+            targets,
+            overflow: Overflow::Error,
+        })
+    }
+
+    /// Sets what is done with a value that is wider than its target field. Defaults to
+    /// `Overflow::Error`.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Converts a record of the source layout into the target layout, replacing the contents of
+    /// `out`. Bytes of the target record that no field covers are spaces. A source field past
+    /// the end of the input is an error.
+    pub fn remap(&self, input: &[u8], out: &mut Vec<u8>) -> Result<()> {
+        out.clear();
+
+        for (target, source) in &self.targets {
+            if target.capture_only {
+                continue;
+            }
+
+            let value = match (&target.literal, source) {
+                (Some(literal), _) => literal.as_bytes(),
+                (None, Some(source)) => source_value(source, input)?,
+                (None, None) => target.default.as_deref().unwrap_or_default().as_bytes(),
+            };
+
+            if self.overflow == Overflow::Error && !target.to_end && value.len() > target.width() {
+                return Err(ser::field_error(
+                    target,
+                    FieldSerializeReason::TooWide {
+                        actual: value.len(),
+                    },
+                ));
+            }
+
+            let start = target.range.start;
+            let end = start + ser::padded_len(value, target);
+            if out.len() < end {
+                out.resize(end, b' ');
+            }
+            ser::write_padded(&mut &mut out[start..end], value, target)?;
+        }
+
+        Ok(())
+    }
+
+    /// Converts every record of the reader and writes it to the writer, separated by the
+    /// writer's linebreak. Returns the number of records converted. An error converting a
+    /// record is returned as an `Error::Record` with the record's index.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, LineBreak, Reader, Remapper, Writer};
+    ///
+    /// let remapper = Remapper::new(
+    ///     FieldSet::new_field(0..4).name("id").pad_with('0').justify("right"),
+    ///     FieldSet::new_field(0..3).name("id").justify("right"),
+    /// );
+    ///
+    /// let mut reader = Reader::from_string("00120345").width(4);
+    /// let mut writer = Writer::from_memory().linebreak(LineBreak::Newline);
+    ///
+    /// assert_eq!(remapper.remap_file(&mut reader, &mut writer).unwrap(), 2);
+    /// assert_eq!(String::from(writer), " 12\n345");
+    /// ```
+    pub fn remap_file<R: Read, W: Write>(
+        &self,
+        reader: &mut Reader<R>,
+        writer: &mut Writer<W>,
+    ) -> Result<u64> {
+        let mut buf = Vec::new();
+        let mut count = 0;
+
+        while let Some(record) = reader.next_record() {
+            let result = self.remap(record?, &mut buf);
+            result.map_err(|e| Error::Record {
+                index: reader.records_read() - 1,
+                source: Box::new(e),
+            })?;
+
+            if count > 0 {
+                writer.write_linebreak()?;
+            }
+            writer.write_iter(iter::once(&buf))?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+/// The bytes of a source field, without its padding or surrounding spaces.
+fn source_value<'a>(conf: &FieldConfig, input: &'a [u8]) -> Result<&'a [u8]> {
+    let bytes = conf.slice(input).ok_or_else(|| {
+        Error::from(DeserializeError::Field {
+            name: conf.describe(),
+            range: conf.range.clone(),
+            source: Box::new(DeserializeError::UnexpectedEndOfRecord),
+        })
+    })?;
+
+    Ok(conf.trim(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{LineBreak, SerializeError};
+
+    fn vendor() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("kind"),
+            FieldSet::new_field(2..10).name("name"),
+            FieldSet::new_field(10..16)
+                .name("amount")
+                .pad_with('0')
+                .justify("right"),
+        ])
+    }
+
+    fn remap(remapper: &Remapper, input: &[u8]) -> String {
+        let mut out = Vec::new();
+        remapper.remap(input, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn widen_and_rejustify() {
+        let canonical = FieldSet::Seq(vec![
+            FieldSet::new_field(0..10)
+                .name("amount")
+                .pad_with('*')
+                .justify("right"),
+            FieldSet::new_field(10..22).name("name").justify("center"),
+            FieldSet::new_field(22..25).name("kind").justify("right"),
+        ]);
+        let remapper = Remapper::new(vendor(), canonical);
+
+        assert_eq!(
+            remap(&remapper, b"D1Carl    001250"),
+            "******1250    Carl     D1"
+        );
+        assert_eq!(
+            remap(&remapper, b"D1  Jo Bo 000000"),
+            "*********0   Jo Bo     D1"
+        );
+    }
+
+    #[test]
+    fn narrow() {
+        let canonical = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).name("name"),
+            FieldSet::new_field(3..6)
+                .name("amount")
+                .pad_with('0')
+                .justify("right"),
+        ]);
+
+        let remapper = Remapper::new(vendor(), canonical.clone()).overflow(Overflow::Truncate);
+        assert_eq!(remap(&remapper, b"D1Carl    000042"), "Car042");
+        assert_eq!(remap(&remapper, b"D1Jo      012345"), "Jo 123");
+
+        let remapper = Remapper::new(vendor(), canonical);
+        assert_eq!(remap(&remapper, b"D1Jo      000042"), "Jo 042");
+
+        let mut out = Vec::new();
+        let err = remapper.remap(b"D1Carl    000042", &mut out).unwrap_err();
+        match err {
+            Error::SerializeError(SerializeError::Field {
+                ref name,
+                ref range,
+                ref reason,
+            }) => {
+                assert_eq!(name.as_deref(), Some("name"));
+                assert_eq!(*range, 0..3);
+                assert_eq!(*reason, FieldSerializeReason::TooWide { actual: 4 });
+            }
+            ref other => panic!("expected a field error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unmapped_fields() {
+        let canonical = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..3, "REC"),
+            FieldSet::new_field(3..7).name("name"),
+            FieldSet::new_field(7..9)
+                .name("country")
+                .default_value("US"),
+            FieldSet::new_field(9..12).name("filler").pad_with('-'),
+            FieldSet::new_field(14..16).name("kind"),
+        ]);
+        let remapper = Remapper::new(vendor(), canonical);
+
+        // The gap from 12 to 14 is filled with spaces.
+        assert_eq!(remap(&remapper, b"D1Carl    000042"), "RECCarlUS---  D1");
+    }
+
+    #[test]
+    fn to_end_fields() {
+        let source = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("kind"),
+            FieldSet::new_field_to_end(2).name("rest"),
+        ]);
+        let target = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("kind").justify("right"),
+            FieldSet::new_field_to_end(4).name("rest"),
+        ]);
+        let remapper = Remapper::new(source, target);
+
+        assert_eq!(remap(&remapper, b"D1some text  "), "  D1some text");
+    }
+
+    #[test]
+    fn pairs() {
+        let canonical = FieldSet::Seq(vec![
+            FieldSet::new_field(0..6).name("customer"),
+            FieldSet::new_field(6..8).name("kind"),
+        ]);
+        let remapper =
+            Remapper::with_pairs(vendor(), canonical.clone(), &[("name", "customer")]).unwrap();
+
+        // Only the given pairs are mapped, even when names match.
+        assert_eq!(remap(&remapper, b"D1Carl    000042"), "Carl    ");
+
+        let err =
+            Remapper::with_pairs(vendor(), canonical.clone(), &[("nom", "customer")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"invalid field set: no source field named "nom""#
+        );
+
+        let err = Remapper::with_pairs(vendor(), canonical, &[("name", "client")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"invalid field set: no target field named "client""#
+        );
+    }
+
+    #[test]
+    fn short_input() {
+        let remapper = Remapper::new(vendor(), FieldSet::new_field(0..6).name("amount"));
+
+        let mut out = Vec::new();
+        let err = remapper.remap(b"D1Carl    00", &mut out).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field 'amount' (10..16): byte length of record was less than defined length"
+        );
+    }
+
+    #[test]
+    fn remap_file() {
+        let canonical = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("name"),
+            FieldSet::new_field(4..8).name("amount").justify("right"),
+        ]);
+        let remapper = Remapper::new(vendor(), canonical);
+
+        let mut reader = Reader::from_string("D1Carl    000042\r\nD1Jo      001000")
+            .width(16)
+            .linebreak(LineBreak::CRLF);
+        let mut writer = Writer::from_memory().linebreak(LineBreak::Newline);
+
+        assert_eq!(remapper.remap_file(&mut reader, &mut writer).unwrap(), 2);
+        assert_eq!(writer.stats().records, 2);
+        assert_eq!(String::from(writer), "Carl  42\nJo  1000");
+
+        let mut reader = Reader::from_string("D1Carl    000042D1Carlos  000042").width(16);
+        let mut writer = Writer::from_memory();
+        let err = remapper.remap_file(&mut reader, &mut writer).unwrap_err();
+
+        assert!(matches!(err, Error::Record { index: 1, .. }));
+        assert_eq!(String::from(writer), "Carl  42");
+    }
+}
@@ -0,0 +1,1188 @@
+use crate::{
+    escape, Case, DecimalSeparator, Error, FieldConfig, FieldSet, Flags, Justify, Result, Sign,
+    VariantCodes,
+};
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer},
+};
+use std::{fmt, fmt::Write, sync::Arc};
+
+mod yaml;
+
+/// The most decimal places a field may be given, more than the digits of COBOL's largest numbers.
+const MAX_DECIMALS: usize = 31;
+
+/// How deeply arrays and objects may be nested in a schema, far more than any layout needs but
+/// few enough that parsing a hostile schema cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+impl FieldSet {
+    /// Parses field definitions from a JSON or YAML schema, so that layouts can be maintained as
+    /// data rather than code. A schema that starts with `{` or `[` is read as JSON, and any other
+    /// as block style YAML. The schema is either an array of fields or an object with a `fields`
+    /// array. Each field is an object with these keys:
+    ///
+    /// - `name`: optional, the name of the field.
+    /// - `range`: the byte range of the field, ie `"0..4"`, `"0..=3"` or `"80.."` for a field
+    ///   that runs to the end of the record, or an object such as `{"start": 0, "end": 4}`.
+    /// - `width`: instead of `range`, the width of a field that starts where the previous field
+    ///   ended, or at the start of the record or of its group.
+    /// - `pad_with`: optional, the padding character, a single character string.
+    /// - `justify`: optional, `"left"`, `"right"` or `"center"`.
+    ///
+    /// The other settings of a field are optional keys as well, each set as by the `FieldSet`
+    /// method of the same name: `literal`, `default` (for `default_value`) and `catch_all` (for
+    /// `catch_all_variant`) are strings; `strict`, `bytes`, `capture_only`, `write_as_blank`,
+    /// `blank_when_zero` and `case_on_read` (for `transform_on_read`) are `true` or `false`;
+    /// `decimals` and `occurs` are whole numbers, with `occurs` on the first field of a repeated
+    /// group as `FieldSet::occurs` sets it; `sign` is `"negative_only"`, `"always"` or
+    /// `"trailing"`; `decimal_separator` is `"."`, `","` or `"implied"`; `case` (for
+    /// `transform`) is `"none"`, `"upper"` or `"lower"`; `variants` is an object of codes and the
+    /// names of the variants they stand for, ie `{"01": "Checking"}`; and `flags` is an object
+    /// such as `{"count": 5, "yes": "Y", "no": "N"}`. `deserialize_with` and `serialize_with`
+    /// functions cannot be given in a schema.
+    ///
+    /// An object with a `fields` array, and no other keys, is a nested group of fields. Any
+    /// other key is an error, reported with the path to the offending entry, ie
+    /// `fields[2].fields[0]`.
+    ///
+    /// In YAML, values such as `"0"` for `pad_with` or `"01"` for a variant must be quoted to be
+    /// read as strings rather than numbers, as YAML requires.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let schema = r#"{
+    ///     "fields": [
+    ///         { "name": "id", "width": 4 },
+    ///         { "name": "amount", "width": 6, "pad_with": "0", "justify": "right" },
+    ///         { "name": "notes", "range": "12.." }
+    ///     ]
+    /// }"#;
+    ///
+    /// let fields = FieldSet::from_schema_str(schema).unwrap();
+    /// let (id, amount, notes): (String, u32, String) =
+    ///     fixed_width::from_str_with_fields("A1  000042  free text", fields).unwrap();
+    ///
+    /// assert_eq!((id.as_str(), amount, notes.as_str()), ("A1", 42, "free text"));
+    /// ```
+    pub fn from_schema_str(s: &str) -> Result<FieldSet> {
+        let schema = match s.trim_start().as_bytes().first() {
+            Some(b'{' | b'[') => Parser::new(s).parse()?,
+            _ => yaml::parse(s)?,
+        };
+        from_schema(&schema)
+    }
+
+    /// Writes the field definitions as a JSON schema that can be read by `from_schema_str`.
+    /// Every setting is written apart from `deserialize_with` and `serialize_with` functions,
+    /// which are lost. Settings left at their defaults are left out.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::FieldSet;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("id"),
+    ///     FieldSet::new_field(4..10).name("amount").pad_with('0').justify("right"),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     fields.to_schema_string(),
+    ///     r#"{"fields":[{"name":"id","range":"0..4"},{"name":"amount","range":"4..10","pad_with":"0","justify":"right"}]}"#
+    /// );
+    /// ```
+    pub fn to_schema_string(&self) -> String {
+        let mut out = String::new();
+        match self {
+            FieldSet::Seq(_) => schema_json(self),
+            item => Json::Object(vec![(
+                "fields".to_string(),
+                Json::Array(vec![schema_json(item)]),
+            )]),
+        }
+        .write(&mut out);
+        out
+    }
+}
+
+/// Serializes the field definitions in the form read by `FieldSet::from_schema_str`, so that
+/// schemas can be written with any serde format, and layouts can be stored as part of larger
+/// configuration types. This form is stable: keys are only ever added to it. A field with a
+/// `deserialize_with` or `serialize_with` function cannot be serialized, and is an error.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::FieldSet;
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Job {
+///     name: String,
+///     layout: FieldSet,
+/// }
+/// ```
+impl Serialize for FieldSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        check_serializable(self.iter_flat()).map_err(ser::Error::custom)?;
+        schema_json(self).serialize(serializer)
+    }
+}
+
+/// Deserializes field definitions in any serde format, in the form read by
+/// `FieldSet::from_schema_str`.
+impl<'de> Deserialize<'de> for FieldSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        from_schema(&Json::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// Serializes a field as a single entry of the form read by `FieldSet::from_schema_str`.
+impl Serialize for FieldConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        check_serializable(Some(self)).map_err(ser::Error::custom)?;
+        field_json(self).serialize(serializer)
+    }
+}
+
+/// Deserializes a field from a single entry of the form read by `FieldSet::from_schema_str`. A
+/// field given by its `width` starts at the start of the record.
+impl<'de> Deserialize<'de> for FieldConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        match Json::deserialize(deserializer)? {
+            Json::Object(ref entries) => field(entries, "", &mut 0),
+            _ => Err(schema_error("", "expected an object")),
+        }
+        .map_err(de::Error::custom)
+    }
+}
+
+/// Serializes a `Justify` as `"left"`, `"right"` or `"center"`.
+impl Serialize for Justify {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes a `Justify` from a string, as parsed by `str::parse`.
+impl<'de> Deserialize<'de> for Justify {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Checks that none of the given fields have functions, which cannot be serialized.
+fn check_serializable<'a, I>(fields: I) -> Result<()>
+where
+    I: IntoIterator<Item = &'a FieldConfig>,
+{
+    for conf in fields {
+        if conf.deserialize_with.is_some() || conf.serialize_with.is_some() {
+            return Err(schema_error(
+                "",
+                &format!(
+                    "field {} has a function, which cannot be serialized",
+                    conf.describe()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn from_schema(schema: &Json) -> Result<FieldSet> {
+    let fields = match schema {
+        Json::Array(_) => schema,
+        Json::Object(ref entries) => group(entries, "")?,
+        _ => return Err(schema_error("", "expected an array or an object of fields")),
+    };
+
+    let mut cursor = 0;
+    field_set(fields, "fields", &mut cursor)
+}
+
+fn schema_json(fields: &FieldSet) -> Json {
+    match fields {
+        FieldSet::Item(conf) => field_json(conf),
+        FieldSet::Seq(seq) => Json::Object(vec![(
+            "fields".to_string(),
+            Json::Array(seq.iter().map(schema_json).collect()),
+        )]),
+    }
+}
+
+fn field_json(conf: &FieldConfig) -> Json {
+    let mut entries = Vec::new();
+    let mut push = |key: &str, value: Json| entries.push((key.to_string(), value));
+    let string = |s: &str| Json::String(s.to_string());
+
+    if let Some(ref name) = conf.name {
+        push("name", string(name));
+    }
+    push("range", Json::String(conf.range_string()));
+    if conf.pad_with != ' ' {
+        push("pad_with", Json::String(conf.pad_with.to_string()));
+    }
+    if conf.justify != Justify::Left {
+        push("justify", Json::String(conf.justify.to_string()));
+    }
+    if let Some(ref literal) = conf.literal {
+        push("literal", string(literal));
+    }
+    if let Some(ref default) = conf.default {
+        push("default", string(default));
+    }
+    if let Some(decimals) = conf.decimals {
+        push("decimals", Json::Number(decimals as f64));
+    }
+    if let Some(count) = conf.occurs {
+        push("occurs", Json::Number(count as f64));
+    }
+    match conf.sign {
+        Sign::NegativeOnly => {}
+        Sign::Always => push("sign", string("always")),
+        Sign::TrailingChar => push("sign", string("trailing")),
+    }
+    match conf.decimal_separator {
+        DecimalSeparator::Point => {}
+        DecimalSeparator::Comma => push("decimal_separator", string(",")),
+        DecimalSeparator::Implied => push("decimal_separator", string("implied")),
+    }
+    match conf.case {
+        Case::None => {}
+        Case::Upper => push("case", string("upper")),
+        Case::Lower => push("case", string("lower")),
+    }
+    if let Some(ref variants) = conf.variants {
+        let codes = variants
+            .codes
+            .iter()
+            .map(|(code, variant)| (code.clone(), string(variant)))
+            .collect();
+        push("variants", Json::Object(codes));
+        if let Some(ref catch_all) = variants.catch_all {
+            push("catch_all", string(catch_all));
+        }
+    }
+    if let Some(flags) = conf.flags {
+        push(
+            "flags",
+            Json::Object(vec![
+                ("count".to_string(), Json::Number(flags.count as f64)),
+                (
+                    "yes".to_string(),
+                    Json::String(char::from(flags.yes).to_string()),
+                ),
+                (
+                    "no".to_string(),
+                    Json::String(char::from(flags.no).to_string()),
+                ),
+            ]),
+        );
+    }
+    for (key, set) in [
+        ("strict", conf.strict),
+        ("bytes", conf.bytes),
+        ("capture_only", conf.capture_only),
+        ("write_as_blank", conf.write_as_blank),
+        ("blank_when_zero", conf.blank_when_zero),
+        ("case_on_read", conf.case_on_read),
+    ] {
+        if set {
+            push(key, Json::Bool(true));
+        }
+    }
+
+    Json::Object(entries)
+}
+
+fn schema_error(path: &str, message: &str) -> Error {
+    Error::Schema {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Gets the `fields` array of a group, which must be its only key.
+fn group<'a>(entries: &'a [(String, Json)], path: &str) -> Result<&'a Json> {
+    match entries {
+        [(key, fields)] if key == "fields" => Ok(fields),
+        _ => Err(schema_error(
+            path,
+            "a group must only have a `fields` array",
+        )),
+    }
+}
+
+fn field_set(json: &Json, path: &str, cursor: &mut usize) -> Result<FieldSet> {
+    let items = match json {
+        Json::Array(items) => items,
+        _ => return Err(schema_error(path, "expected an array of fields")),
+    };
+
+    let mut seq = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let path = format!("{}[{}]", path, i);
+        let entries = match item {
+            Json::Object(entries) => entries,
+            _ => return Err(schema_error(&path, "expected an object")),
+        };
+
+        if entries.iter().any(|(key, _)| key == "fields") {
+            let fields = group(entries, &path)?;
+            seq.push(field_set(fields, &format!("{}.fields", path), cursor)?);
+        } else {
+            seq.push(FieldSet::Item(field(entries, &path, cursor)?));
+        }
+    }
+
+    Ok(FieldSet::Seq(seq))
+}
+
+fn field(entries: &[(String, Json)], path: &str, cursor: &mut usize) -> Result<FieldConfig> {
+    let mut conf = FieldConfig::default();
+    let mut range = None;
+    let mut width = None;
+    let mut variants = None;
+    let mut catch_all = None;
+
+    for (key, value) in entries {
+        let path = format!("{}.{}", path, key);
+        let expected = |what: &str| Err(schema_error(&path, &format!("expected {}", what)));
+
+        if let Some(setting) = bool_setting(&mut conf, key) {
+            match value {
+                Json::Bool(b) => *setting = *b,
+                _ => return expected("`true` or `false`"),
+            }
+            continue;
+        }
+
+        match (key.as_str(), value) {
+            ("name", Json::String(s)) => conf.name = Some(s.clone()),
+            ("range", Json::String(s)) => match parse_range(s) {
+                Some(r) => range = Some(r),
+                None => return expected("`a..b`, `a..=b` or `a..`"),
+            },
+            ("range", Json::Object(bounds)) => match object_range(bounds) {
+                Some(r) => range = Some((r, false)),
+                None => return expected("whole numbers `start` and `end`, in order"),
+            },
+            ("width", Json::Number(n)) if is_whole(*n) => width = Some(*n as usize),
+            ("pad_with", Json::String(s)) if s.chars().count() == 1 => {
+                conf.pad_with = s.chars().next().unwrap_or(' ')
+            }
+            ("justify", Json::String(s)) => match s.parse() {
+                Ok(justify) => conf.justify = justify,
+                Err(_) => return expected("`left`, `right` or `center`"),
+            },
+            ("literal", Json::String(s)) => conf.literal = Some(s.clone()),
+            ("default", Json::String(s)) => conf.default = Some(s.clone()),
+            ("decimals", Json::Number(n)) if is_whole(*n) && *n <= MAX_DECIMALS as f64 => {
+                conf.decimals = Some(*n as usize)
+            }
+            ("occurs", Json::Number(n)) if is_whole(*n) => conf.occurs = Some(*n as usize),
+            ("sign", Json::String(s)) => {
+                conf.sign = match s.as_str() {
+                    "negative_only" => Sign::NegativeOnly,
+                    "always" => Sign::Always,
+                    "trailing" => Sign::TrailingChar,
+                    _ => return expected("`negative_only`, `always` or `trailing`"),
+                }
+            }
+            ("decimal_separator", Json::String(s)) => {
+                conf.decimal_separator = match s.as_str() {
+                    "." => DecimalSeparator::Point,
+                    "," => DecimalSeparator::Comma,
+                    "implied" => DecimalSeparator::Implied,
+                    _ => return expected("`.`, `,` or `implied`"),
+                }
+            }
+            ("case", Json::String(s)) => {
+                conf.case = match s.as_str() {
+                    "none" => Case::None,
+                    "upper" => Case::Upper,
+                    "lower" => Case::Lower,
+                    _ => return expected("`none`, `upper` or `lower`"),
+                }
+            }
+            ("variants", Json::Object(codes)) => {
+                let mut pairs = Vec::with_capacity(codes.len());
+                for (code, variant) in codes {
+                    match variant {
+                        Json::String(variant) => pairs.push((code.clone(), variant.clone())),
+                        _ => return expected("an object of codes and variant names"),
+                    }
+                }
+                variants = Some(pairs);
+            }
+            ("catch_all", Json::String(s)) => catch_all = Some(s.clone()),
+            ("flags", Json::Object(flags)) => match object_flags(flags) {
+                Some(flags) => conf.flags = Some(flags),
+                None => {
+                    return expected("a whole number `count` and ASCII characters `yes` and `no`")
+                }
+            },
+            ("name" | "justify" | "literal" | "default" | "sign", _)
+            | ("decimal_separator" | "case" | "catch_all", _) => return expected("a string"),
+            ("range", _) => return expected("a string or an object"),
+            ("width" | "occurs", _) => return expected("a whole number"),
+            ("decimals", _) => return expected(&format!("a whole number up to {}", MAX_DECIMALS)),
+            ("pad_with", _) => return expected("a single character"),
+            ("variants" | "flags", _) => return expected("an object"),
+            _ => return Err(schema_error(&path, "unknown key")),
+        }
+    }
+
+    if variants.is_some() || catch_all.is_some() {
+        conf.variants = Some(Arc::new(VariantCodes {
+            codes: variants.unwrap_or_default(),
+            catch_all,
+        }));
+    }
+
+    match (range, width) {
+        (Some((range, to_end)), None) => {
+            conf.range = range;
+            conf.to_end = to_end;
+        }
+        (None, Some(width)) => match cursor.checked_add(width) {
+            Some(end) => conf.range = *cursor..end,
+            None => return Err(schema_error(path, "the field ends past the largest offset")),
+        },
+        (Some(_), Some(_)) => return Err(schema_error(path, "give either `range` or `width`")),
+        (None, None) => return Err(schema_error(path, "a field needs a `range` or a `width`")),
+    }
+    *cursor = conf.range.end;
+
+    Ok(conf)
+}
+
+/// Gets the setting of a field that the given key sets to `true` or `false`.
+fn bool_setting<'a>(conf: &'a mut FieldConfig, key: &str) -> Option<&'a mut bool> {
+    match key {
+        "strict" => Some(&mut conf.strict),
+        "bytes" => Some(&mut conf.bytes),
+        "capture_only" => Some(&mut conf.capture_only),
+        "write_as_blank" => Some(&mut conf.write_as_blank),
+        "blank_when_zero" => Some(&mut conf.blank_when_zero),
+        "case_on_read" => Some(&mut conf.case_on_read),
+        _ => None,
+    }
+}
+
+fn is_whole(n: f64) -> bool {
+    n.fract() == 0.0 && n >= 0.0
+}
+
+/// Gets the whole number of the given key of an object.
+fn whole_entry(entries: &[(String, Json)], key: &str) -> Option<usize> {
+    entries
+        .iter()
+        .find(|(k, _)| k == key)
+        .and_then(|(_, value)| match value {
+            Json::Number(n) if is_whole(*n) => Some(*n as usize),
+            _ => None,
+        })
+}
+
+/// Parses a range of the form `{"start": a, "end": b}`.
+fn object_range(entries: &[(String, Json)]) -> Option<std::ops::Range<usize>> {
+    if entries.len() != 2 {
+        return None;
+    }
+
+    let start = whole_entry(entries, "start")?;
+    let end = whole_entry(entries, "end")?;
+    (start <= end).then_some(start..end)
+}
+
+/// Parses flags of the form `{"count": 5, "yes": "Y", "no": "N"}`.
+fn object_flags(entries: &[(String, Json)]) -> Option<Flags> {
+    let ascii = |key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, value)| match value {
+                Json::String(s) if s.len() == 1 && s.is_ascii() => Some(s.as_bytes()[0]),
+                _ => None,
+            })
+    };
+
+    if entries.len() != 3 {
+        return None;
+    }
+
+    Some(Flags {
+        count: whole_entry(entries, "count")?,
+        yes: ascii("yes")?,
+        no: ascii("no")?,
+    })
+}
+
+/// Parses a range of the form `a..b`, `a..=b` or `a..`, returning the equivalent half open range
+/// and whether it runs to the end of the record.
+fn parse_range(s: &str) -> Option<(std::ops::Range<usize>, bool)> {
+    let (start, end) = s.trim().split_once("..")?;
+    let start = start.trim().parse::<usize>().ok()?;
+    let end = end.trim();
+
+    if end.is_empty() {
+        return Some((start..start, true));
+    }
+
+    let end = match end.strip_prefix('=') {
+        Some(end) => end.trim().parse::<usize>().ok()?.checked_add(1)?,
+        None => end.parse::<usize>().ok()?,
+    };
+
+    if start > end {
+        return None;
+    }
+
+    Some((start..end, false))
+}
+
+/// A parsed JSON document. Object keys keep their order.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn write(&self, out: &mut String) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Number(n) => {
+                let _ = write!(out, "{}", n);
+            }
+            Json::String(s) => escape::write_json_str(out, s),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    item.write(out);
+                }
+                out.push(']');
+            }
+            Json::Object(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    escape::write_json_str(out, key);
+                    out.push(':');
+                    value.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl Serialize for Json {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Json::Null => serializer.serialize_unit(),
+            Json::Bool(b) => serializer.serialize_bool(*b),
+            Json::Number(n) if is_whole(*n) => serializer.serialize_u64(*n as u64),
+            Json::Number(n) => serializer.serialize_f64(*n),
+            Json::String(s) => serializer.serialize_str(s),
+            Json::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Json::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Json {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a schema value")
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> std::result::Result<Json, D::Error> {
+        Json::deserialize(d)
+    }
+
+    fn visit_bool<E>(self, b: bool) -> std::result::Result<Json, E> {
+        Ok(Json::Bool(b))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> std::result::Result<Json, E> {
+        Ok(Json::Number(n as f64))
+    }
+
+    fn visit_u64<E>(self, n: u64) -> std::result::Result<Json, E> {
+        Ok(Json::Number(n as f64))
+    }
+
+    fn visit_f64<E>(self, n: f64) -> std::result::Result<Json, E> {
+        Ok(Json::Number(n))
+    }
+
+    fn visit_str<E>(self, s: &str) -> std::result::Result<Json, E> {
+        Ok(Json::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> std::result::Result<Json, E> {
+        Ok(Json::String(s))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Json, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Json::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Json, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Json::Object(entries))
+    }
+}
+
+/// A parser for JSON documents.
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            s,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Json> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.pos < self.s.len() {
+            return Err(self.error("unexpected data after the schema"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        schema_error(&format!("byte {}", self.pos), message)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", b as char)))
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json> {
+        if self.s[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(open @ (b'{' | b'[')) => {
+                self.depth += 1;
+                if self.depth > MAX_DEPTH {
+                    return Err(self.error("the schema is nested too deeply"));
+                }
+                let value = if open == b'{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':')?;
+            entries.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error("expected a string"));
+        }
+        self.pos += 1;
+
+        let mut s = String::new();
+        loop {
+            let rest = &self.s[self.pos..];
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let hex = self.s.get(self.pos + 1..self.pos + 5);
+                            let c = hex
+                                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            self.pos += 4;
+                            c
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    s.push(escaped);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json> {
+        let start = self.pos;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+
+        self.s[start..self.pos]
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::de::{
+        value::{self, MapDeserializer, SeqDeserializer},
+        IntoDeserializer,
+    };
+    use serde_derive::Deserialize;
+
+    // Deserializes from a parsed document, standing in for a serde format such as JSON.
+    impl<'de> Deserializer<'de> for Json {
+        type Error = value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            match self {
+                Json::Null => visitor.visit_unit(),
+                Json::Bool(b) => visitor.visit_bool(b),
+                Json::Number(n) if n.fract() == 0.0 && n >= 0.0 => visitor.visit_u64(n as u64),
+                Json::Number(n) => visitor.visit_f64(n),
+                Json::String(s) => visitor.visit_string(s),
+                Json::Array(items) => visitor.visit_seq(SeqDeserializer::new(items.into_iter())),
+                Json::Object(entries) => {
+                    visitor.visit_map(MapDeserializer::new(entries.into_iter()))
+                }
+            }
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum
+            identifier ignored_any
+        }
+    }
+
+    impl<'de> IntoDeserializer<'de, value::Error> for Json {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    fn parse(s: &str) -> Json {
+        Parser::new(s).parse().unwrap()
+    }
+
+    const SCHEMA: &str = r#"
+    {
+        "fields": [
+            { "name": "type", "width": 1 },
+            { "name": "id", "width": 6, "pad_with": "0", "justify": "right" },
+            {
+                "fields": [
+                    { "name": "street", "width": 20 },
+                    { "name": "city", "range": "27..=41" }
+                ]
+            },
+            { "width": 2, "justify": "centre" },
+            { "name": "notes\té", "range": "50.." }
+        ]
+    }"#;
+
+    fn hand_built() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..1).name("type"),
+            FieldSet::new_field(1..7)
+                .name("id")
+                .pad_with('0')
+                .justify(Justify::Right),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(7..27).name("street"),
+                FieldSet::new_field(27..42).name("city"),
+            ]),
+            FieldSet::new_field(42..44).justify(Justify::Center),
+            FieldSet::new_field_to_end(50).name("notes\té"),
+        ])
+    }
+
+    #[test]
+    fn schema_from_str() {
+        let fields = FieldSet::from_schema_str(SCHEMA).unwrap();
+        assert_eq!(fields, hand_built());
+
+        let fields = FieldSet::from_schema_str(r#"[{"range": "0..2"}, {"width": 3}]"#).unwrap();
+        assert_eq!(
+            fields,
+            FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(2..5)])
+        );
+    }
+
+    #[test]
+    fn schema_from_yaml() {
+        let schema = r#"
+# The customer layout
+fields:
+  - name: type
+    width: 1
+  - {name: id, width: 6, pad_with: "0", justify: right}
+  - fields:
+      - name: street
+        width: 20
+      - name: city
+        range: 27..=41
+  - width: 2
+    justify: centre
+  - name: "notes\té"
+    range: '50..'
+"#;
+
+        assert_eq!(FieldSet::from_schema_str(schema).unwrap(), hand_built());
+        assert_eq!(
+            FieldSet::from_schema_str("- range: 0..2\n- width: 3\n").unwrap(),
+            FieldSet::Seq(vec![FieldSet::new_field(0..2), FieldSet::new_field(2..5)])
+        );
+        assert_eq!(
+            FieldSet::from_schema_str("- width: 1\n  pad_with: 0")
+                .unwrap_err()
+                .to_string(),
+            "invalid schema at fields[0].pad_with: expected a single character"
+        );
+    }
+
+    #[test]
+    fn schema_round_trip() {
+        let schema = hand_built().to_schema_string();
+        let fields = FieldSet::from_schema_str(&schema).unwrap();
+
+        assert_eq!(fields, hand_built());
+        assert_eq!(fields.to_schema_string(), schema);
+    }
+
+    fn every_option() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D").strict(true),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(1..9)
+                    .name("amount")
+                    .pad_with('0')
+                    .justify(Justify::Right)
+                    .decimals(2)
+                    .sign(Sign::TrailingChar)
+                    .decimal_separator(DecimalSeparator::Comma)
+                    .blank_when_zero(true),
+                FieldSet::Seq(vec![FieldSet::new_field(9..11)
+                    .name("account")
+                    .variants(&[("01", "Checking"), ("02", "Savings")])
+                    .catch_all_variant("Other")]),
+            ]),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(30..32),
+                FieldSet::new_field(32..34),
+            ])
+            .occurs(2),
+            FieldSet::new_field(11..16).name("flags").flags(5, 'Y', 'N'),
+            FieldSet::new_field(16..26)
+                .name("city")
+                .transform(Case::Upper)
+                .transform_on_read(true)
+                .default_value("unknown")
+                .write_as_blank(true),
+            FieldSet::new_field(26..30).name("raw").bytes(true),
+            FieldSet::new_field(0..0).name("record").capture_only(true),
+            FieldSet::new_field_to_end(30).name("rest"),
+        ])
+    }
+
+    #[test]
+    fn schema_round_trip_every_option() {
+        let schema = every_option().to_schema_string();
+        assert_eq!(FieldSet::from_schema_str(&schema).unwrap(), every_option());
+
+        let fields = FieldSet::deserialize(parse(&schema)).unwrap();
+        assert_eq!(fields, every_option());
+        assert_eq!(fields.to_schema_string(), schema);
+    }
+
+    #[test]
+    fn serde_embedded() {
+        #[derive(Debug, Deserialize)]
+        struct Job {
+            name: String,
+            layout: FieldSet,
+            key: FieldConfig,
+            justify: Justify,
+        }
+
+        let job = Job::deserialize(parse(
+            r#"{
+                "name": "daily",
+                "layout": [{"width": 4}, {"range": {"start": 4, "end": 10}, "justify": "right"}],
+                "key": {"name": "id", "range": "0..4", "pad_with": "0"},
+                "justify": "Centre"
+            }"#,
+        ))
+        .unwrap();
+
+        assert_eq!(job.name, "daily");
+        assert_eq!(
+            job.layout,
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..4),
+                FieldSet::new_field(4..10).justify(Justify::Right),
+            ])
+        );
+        assert_eq!(job.key.to_string(), "id@0..4 pad='0' left");
+        assert_eq!(job.justify, Justify::Center);
+
+        let err = Job::deserialize(parse(
+            r#"{"name": "a", "layout": [{"width": 4, "sign": "+"}], "key": {}, "justify": "left"}"#,
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid schema at fields[0].sign: expected `negative_only`, `always` or `trailing`"
+        );
+    }
+
+    #[test]
+    fn functions_are_not_serializable() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field(4..8)
+                .name("code")
+                .deserialize_with(|bytes| Ok(String::from_utf8_lossy(bytes).into_owned())),
+        ]);
+
+        assert!(check_serializable(fields.iter_flat().take(1)).is_ok());
+        assert_eq!(
+            check_serializable(fields.iter_flat())
+                .unwrap_err()
+                .to_string(),
+            "invalid schema: field code has a function, which cannot be serialized"
+        );
+    }
+
+    #[test]
+    fn schema_errors() {
+        let error = |s: &str| FieldSet::from_schema_str(s).unwrap_err().to_string();
+
+        assert_eq!(
+            error(r#"{"fields": [{"width": 1}, {"fields": [{"width": 2, "pad": "0"}]}]}"#),
+            "invalid schema at fields[1].fields[0].pad: unknown key"
+        );
+        assert_eq!(
+            error(r#"[{"name": "a", "range": "4..2"}]"#),
+            "invalid schema at fields[0].range: expected `a..b`, `a..=b` or `a..`"
+        );
+        assert_eq!(
+            error(r#"[{"name": "a"}]"#),
+            "invalid schema at fields[0]: a field needs a `range` or a `width`"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1.5}]"#),
+            "invalid schema at fields[0].width: expected a whole number"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1, "pad_with": "ab"}]"#),
+            "invalid schema at fields[0].pad_with: expected a single character"
+        );
+        assert_eq!(
+            error(r#"{"fields": [], "version": 2}"#),
+            "invalid schema: a group must only have a `fields` array"
+        );
+        assert_eq!(
+            error(r#"[{"range": {"start": 4}}]"#),
+            "invalid schema at fields[0].range: expected whole numbers `start` and `end`, in order"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1, "strict": "yes"}]"#),
+            "invalid schema at fields[0].strict: expected `true` or `false`"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1, "flags": {"count": 1, "yes": "✓", "no": "N"}}]"#),
+            "invalid schema at fields[0].flags: expected a whole number `count` and ASCII \
+             characters `yes` and `no`"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1}, {"width": 1e30}]"#),
+            "invalid schema at fields[1]: the field ends past the largest offset"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1, "decimals": 1e12}]"#),
+            "invalid schema at fields[0].decimals: expected a whole number up to 31"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1, "occurs": "2"}]"#),
+            "invalid schema at fields[0].occurs: expected a whole number"
+        );
+        assert_eq!(
+            error(&"[".repeat(200_000)),
+            "invalid schema at byte 64: the schema is nested too deeply"
+        );
+        assert_eq!(
+            error(r#"[{"width": 1},]"#),
+            "invalid schema at byte 14: expected a value"
+        );
+    }
+}
@@ -0,0 +1,549 @@
+//! A parser for the block style YAML that schemas are commonly written in.
+//!
+//! Supported are block mappings and sequences, flow collections written on one line, plain,
+//! single quoted and double quoted scalars, and comments. Anchors, aliases, tags, block scalars
+//! and documents spanning several `---` markers are not, and are an error rather than being
+//! misread.
+
+use super::{schema_error, Json, MAX_DEPTH};
+use crate::Result;
+
+/// A line of the document, with its comment and trailing whitespace removed.
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    content: &'a str,
+}
+
+/// Parses a YAML document into the values it holds.
+pub(super) fn parse(s: &str) -> Result<Json> {
+    let mut lines = Vec::new();
+    for (i, raw) in s.lines().enumerate() {
+        let number = i + 1;
+        let content = strip_comment(raw).trim_end();
+        let trimmed = content.trim_start_matches(' ');
+
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with('\t') {
+            return Err(line_error(number, "tabs cannot be used for indentation"));
+        }
+        if lines.is_empty() && trimmed == "---" {
+            continue;
+        }
+        if trimmed == "---" || trimmed == "..." {
+            return Err(line_error(number, "only a single document is supported"));
+        }
+
+        lines.push(Line {
+            number,
+            indent: content.len() - trimmed.len(),
+            content: trimmed,
+        });
+    }
+
+    let mut parser = Parser {
+        lines,
+        pos: 0,
+        depth: 0,
+    };
+    let value = match parser.lines.first() {
+        Some(line) => parser.block(line.indent)?,
+        None => Json::Null,
+    };
+
+    match parser.lines.get(parser.pos) {
+        Some(line) => Err(line_error(line.number, "unexpected indentation")),
+        None => Ok(value),
+    }
+}
+
+fn line_error(number: usize, message: &str) -> crate::Error {
+    schema_error(&format!("line {}", number), message)
+}
+
+/// Removes a comment, which starts with a `#` at the start of the line or after a space, and is
+/// not within quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut prev = ' ';
+
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '#' && (prev == ' ' || prev == '\t') => return &line[..i],
+            None if (c == '"' || c == '\'') && is_value_start(prev) => quote = Some(c),
+            None => {}
+        }
+        prev = c;
+    }
+
+    line
+}
+
+/// Whether a quote after the given character starts a quoted scalar, rather than being part of
+/// a plain one such as `it's`.
+fn is_value_start(prev: char) -> bool {
+    matches!(prev, ' ' | '\t' | ':' | '-' | '[' | '{' | ',')
+}
+
+/// Whether the content of a line is an entry of a block sequence.
+fn is_seq_entry(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn enter(&mut self, number: usize) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(line_error(number, "the schema is nested too deeply"));
+        }
+        Ok(())
+    }
+
+    /// Parses the block collection starting at the current line, which is indented by `indent`.
+    fn block(&mut self, indent: usize) -> Result<Json> {
+        let Line {
+            number, content, ..
+        } = self.lines[self.pos];
+        self.enter(number)?;
+
+        let value = if is_seq_entry(content) {
+            self.seq(indent)
+        } else if find_key_end(content).is_some() {
+            self.map(indent)
+        } else {
+            // A lone scalar, such as a whole document that is a flow collection.
+            let value = inline(content, number, self.depth);
+            self.pos += 1;
+            value
+        };
+
+        self.depth -= 1;
+        value
+    }
+
+    fn seq(&mut self, indent: usize) -> Result<Json> {
+        let mut items = Vec::new();
+
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.indent != indent || !is_seq_entry(line.content) {
+                break;
+            }
+
+            let rest = line.content[1..].trim_start_matches(' ');
+            let number = line.number;
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.nested(indent)?);
+            } else if is_seq_entry(rest) || find_key_end(rest).is_some() {
+                // A collection that starts on the line of its entry, such as `- name: id`, whose
+                // other lines are indented to line up with it.
+                let offset = line.content.len() - rest.len();
+                self.lines[self.pos] = Line {
+                    number,
+                    indent: indent + offset,
+                    content: rest,
+                };
+                items.push(self.block(indent + offset)?);
+            } else {
+                items.push(inline(rest, number, self.depth)?);
+                self.pos += 1;
+            }
+        }
+
+        Ok(Json::Array(items))
+    }
+
+    fn map(&mut self, indent: usize) -> Result<Json> {
+        let mut entries = Vec::new();
+
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.indent != indent || is_seq_entry(line.content) {
+                break;
+            }
+
+            let number = line.number;
+            let key_end = find_key_end(line.content)
+                .ok_or_else(|| line_error(number, "expected a `key: value` entry"))?;
+            let key = match inline(&line.content[..key_end], number, self.depth)? {
+                Json::String(key) => key,
+                Json::Null => String::new(),
+                key => {
+                    let mut s = String::new();
+                    key.write(&mut s);
+                    s
+                }
+            };
+            let rest = line.content[key_end + 1..].trim_start_matches(' ');
+            self.pos += 1;
+
+            let value = if !rest.is_empty() {
+                inline(rest, number, self.depth)?
+            } else {
+                match self.lines.get(self.pos) {
+                    // A sequence may be indented as far as its key.
+                    Some(next) if next.indent == indent && is_seq_entry(next.content) => {
+                        self.enter(number)?;
+                        let value = self.seq(indent)?;
+                        self.depth -= 1;
+                        value
+                    }
+                    _ => self.nested(indent)?,
+                }
+            };
+            entries.push((key, value));
+        }
+
+        Ok(Json::Object(entries))
+    }
+
+    /// Parses the block collection on the lines after an entry with no value on its own line, if
+    /// they are indented further than the entry, or `null` otherwise.
+    fn nested(&mut self, indent: usize) -> Result<Json> {
+        match self.lines.get(self.pos) {
+            Some(next) if next.indent > indent => self.block(next.indent),
+            _ => Ok(Json::Null),
+        }
+    }
+}
+
+/// Finds the `:` that ends the key of a `key: value` entry, skipping quoted keys and the `:`s of
+/// flow collections.
+fn find_key_end(content: &str) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut i = 0;
+
+    match bytes.first() {
+        Some(b'"') | Some(b'\'') => {
+            let quote = bytes[0];
+            i = 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' if quote == b'"' => i += 1,
+                    b'\'' if quote == b'\'' && bytes.get(i + 1) == Some(&b'\'') => i += 1,
+                    b if b == quote => break,
+                    _ => {}
+                }
+                i += 1;
+            }
+            i += 1;
+            let after = content.get(i..)?.trim_start_matches(' ');
+            return match after.strip_prefix(':') {
+                Some(rest) if rest.is_empty() || rest.starts_with(' ') => {
+                    Some(content.len() - after.len())
+                }
+                _ => None,
+            };
+        }
+        Some(b'[') | Some(b'{') => return None,
+        _ => {}
+    }
+
+    while i < bytes.len() {
+        if bytes[i] == b':' && (i + 1 == bytes.len() || bytes[i + 1] == b' ') {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Parses a value written on a single line: a scalar or a flow collection.
+fn inline(s: &str, number: usize, depth: usize) -> Result<Json> {
+    let mut flow = Flow {
+        s: s.trim(),
+        pos: 0,
+        number,
+        depth,
+    };
+
+    let value = flow.value(false)?;
+    flow.skip_spaces();
+    if flow.pos < flow.s.len() {
+        return Err(line_error(number, "unexpected characters after the value"));
+    }
+    Ok(value)
+}
+
+/// A parser for a value on a single line, which may be a flow collection such as `[1, 2]` or
+/// `{start: 0, end: 4}`.
+struct Flow<'a> {
+    s: &'a str,
+    pos: usize,
+    number: usize,
+    depth: usize,
+}
+
+impl Flow<'_> {
+    fn error(&self, message: &str) -> crate::Error {
+        line_error(self.number, message)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(b' ' | b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a value, which ends at a `,`, `]` or `}` within a flow collection.
+    fn value(&mut self, in_flow: bool) -> Result<Json> {
+        self.skip_spaces();
+        match self.peek() {
+            Some(b'[') => self.collection(b']', |flow| flow.value(true).map(|value| (None, value))),
+            Some(b'{') => self.collection(b'}', |flow| {
+                let key = match flow.value(true)? {
+                    Json::String(key) => key,
+                    _ => return Err(flow.error("expected a string key")),
+                };
+                flow.skip_spaces();
+                if flow.peek() != Some(b':') {
+                    return Err(flow.error("expected `:`"));
+                }
+                flow.pos += 1;
+                Ok((Some(key), flow.value(true)?))
+            }),
+            Some(b'"') => self.double_quoted().map(Json::String),
+            Some(b'\'') => self.single_quoted().map(Json::String),
+            Some(b'|' | b'>') => Err(self.error("block scalars are not supported")),
+            Some(b'&' | b'*' | b'!') => {
+                Err(self.error("anchors, aliases and tags are not supported"))
+            }
+            _ => Ok(self.plain(in_flow)),
+        }
+    }
+
+    fn collection<F>(&mut self, close: u8, mut entry: F) -> Result<Json>
+    where
+        F: FnMut(&mut Self) -> Result<(Option<String>, Json)>,
+    {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error("the schema is nested too deeply"));
+        }
+        self.pos += 1;
+
+        let mut items = Vec::new();
+        let mut entries = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                break;
+            }
+
+            match entry(self)? {
+                (Some(key), value) => entries.push((key, value)),
+                (None, value) => items.push(value),
+            }
+
+            self.skip_spaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b) if b == close => {}
+                _ => {
+                    return Err(self.error(&format!("expected `,` or `{}`", close as char)));
+                }
+            }
+        }
+
+        self.depth -= 1;
+        Ok(match close {
+            b']' => Json::Array(items),
+            _ => Json::Object(entries),
+        })
+    }
+
+    fn plain(&mut self, in_flow: bool) -> Json {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            let ends_flow = in_flow && matches!(b, b',' | b']' | b'}');
+            let ends_key = in_flow
+                && b == b':'
+                && matches!(self.s.as_bytes().get(self.pos + 1), None | Some(b' '));
+            if ends_flow || ends_key {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        resolve(self.s[start..self.pos].trim_end())
+    }
+
+    fn double_quoted(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut s = String::new();
+
+        loop {
+            let c = match self.s[self.pos..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'0') => '\0',
+                        Some(b'b') => '\u{8}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b' ') => ' ',
+                        Some(b'u') => {
+                            let hex = self.s.get(self.pos + 1..self.pos + 5);
+                            let c = hex
+                                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            self.pos += 4;
+                            c
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    s.push(escaped);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn single_quoted(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut s = String::new();
+
+        loop {
+            let c = match self.s[self.pos..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += c.len_utf8();
+
+            if c != '\'' {
+                s.push(c);
+            } else if self.peek() == Some(b'\'') {
+                self.pos += 1;
+                s.push('\'');
+            } else {
+                return Ok(s);
+            }
+        }
+    }
+}
+
+/// Resolves a plain scalar to a null, a bool, a number or a string, as YAML's core schema does.
+fn resolve(s: &str) -> Json {
+    match s {
+        "" | "~" | "null" | "Null" | "NULL" => return Json::Null,
+        "true" | "True" | "TRUE" => return Json::Bool(true),
+        "false" | "False" | "FALSE" => return Json::Bool(false),
+        _ => {}
+    }
+
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let is_number = digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'));
+
+    match s.parse() {
+        Ok(n) if is_number => Json::Number(n),
+        _ => Json::String(s.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_str(s: &str) -> String {
+        let mut out = String::new();
+        parse(s).unwrap().write(&mut out);
+        out
+    }
+
+    #[test]
+    fn yaml_values() {
+        assert_eq!(
+            parse_str(
+                "\
+# A layout
+---
+fields:
+  - name: id   # the key
+    width: 4
+  - {name: 'it''s', range: \"4..6\", strict: true}
+  -
+    fields:
+    - range: {start: 6, end: 8}
+      pad_with: '#'
+  - [1, -2.5, ~, x y]
+"
+            ),
+            r##"{"fields":[{"name":"id","width":4},{"name":"it's","range":"4..6","strict":true},{"fields":[{"range":{"start":6,"end":8},"pad_with":"#"}]},[1,-2.5,null,"x y"]]}"##
+        );
+
+        assert_eq!(parse_str("- - a\n  - b\n- c"), r#"[["a","b"],"c"]"#);
+        assert_eq!(parse_str("a:\nb: 80..\n"), r#"{"a":null,"b":"80.."}"#);
+        assert_eq!(parse_str("[1, [2]]"), "[1,[2]]");
+        assert_eq!(parse_str(""), "null");
+    }
+
+    #[test]
+    fn yaml_errors() {
+        let error = |s: &str| parse(s).unwrap_err().to_string();
+
+        assert_eq!(
+            error("a: 1\n  b: 2"),
+            "invalid schema at line 2: unexpected indentation"
+        );
+        assert_eq!(
+            error("a: |\n  text"),
+            "invalid schema at line 1: block scalars are not supported"
+        );
+        assert_eq!(
+            error("a: &x 1"),
+            "invalid schema at line 1: anchors, aliases and tags are not supported"
+        );
+        assert_eq!(
+            error("a: [1, 2"),
+            "invalid schema at line 1: expected `,` or `]`"
+        );
+        assert_eq!(
+            error("a: 1\n---\nb: 2"),
+            "invalid schema at line 2: only a single document is supported"
+        );
+        assert_eq!(
+            error(&format!("a: {}", "[".repeat(1000))),
+            "invalid schema at line 1: the schema is nested too deeply"
+        );
+
+        let nested: String = (0..1000)
+            .map(|i| format!("{}- \n", " ".repeat(i)))
+            .collect();
+        assert_eq!(
+            error(&nested),
+            "invalid schema at line 65: the schema is nested too deeply"
+        );
+    }
+}
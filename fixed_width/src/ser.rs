@@ -1,6 +1,9 @@
-use crate::{error::Error, writer::Writer, FieldConfig, FieldSet, FixedWidth, Justify, Result};
+use crate::{
+    error::Error, writer::Writer, Case, DecimalSeparator, FieldConfig, FieldSet, FixedWidth,
+    Justify, Result, Sign,
+};
 use serde::ser::{self, Error as SerError, Serialize};
-use std::{error::Error as StdError, fmt, io, iter, vec};
+use std::{borrow::Cow, error::Error as StdError, fmt, io, mem, ops::Range};
 
 /// Serializes the given type that implements `FixedWidth` and `Serialize` to a `String`.
 ///
@@ -114,7 +117,59 @@ where
     T: FixedWidth + Serialize,
     W: 'w + io::Write,
 {
-    to_writer_with_fields(wrtr, val, T::fields())
+    match T::flattened_fields() {
+        Some(fields) => {
+            let mut ser = Serializer::with_flattened_ref(wrtr, fields);
+            val.serialize(&mut ser)?;
+            ser.write_literals()
+        }
+        None => to_writer_with_fields(wrtr, val, T::fields()),
+    }
+}
+
+/// Serializes a type that implements `FixedWidth` to the given writer as `to_writer` does, but
+/// with its text as ISO-8859-1, one byte for each char, rather than UTF-8, as a `Writer` that
+/// transcodes records expects.
+///
+/// ### Example
+///
+/// ```rust
+/// use serde_derive::Serialize;
+/// use fixed_width::{FieldSet, FixedWidth};
+///
+/// #[derive(Serialize)]
+/// struct Place {
+///     name: String,
+/// }
+///
+/// impl FixedWidth for Place {
+///     fn fields() -> FieldSet {
+///         FieldSet::new_field(0..6)
+///     }
+/// }
+///
+/// let mut buf = Vec::new();
+/// fixed_width::to_writer_latin1(&mut buf, &Place { name: "Zürich".to_string() }).unwrap();
+///
+/// assert_eq!(buf, b"Z\xFCrich");
+/// ```
+pub fn to_writer_latin1<'w, T, W>(wrtr: &'w mut W, val: &T) -> Result<()>
+where
+    T: FixedWidth + Serialize,
+    W: 'w + io::Write,
+{
+    let flattened;
+    let fields = match T::flattened_fields() {
+        Some(fields) => fields,
+        None => {
+            flattened = T::fields().flatten();
+            &flattened
+        }
+    };
+
+    let mut ser = Serializer::with_flattened_ref(wrtr, fields).latin1(true);
+    val.serialize(&mut ser)?;
+    ser.write_literals()
 }
 
 /// Serializes data to the given writer using the provided `Field`s.
@@ -142,18 +197,69 @@ where
     W: 'w + io::Write,
 {
     let mut ser = Serializer::new(wrtr, fields);
-    val.serialize(&mut ser)
+    val.serialize(&mut ser)?;
+    ser.write_literals()
 }
 
 /// Errors that occur during serialization.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SerializeError {
     /// General error message as a `String`.
     Message(String),
     /// The desired type is unsupported by this serializer.
     Unsupported(String),
     /// The number of `Field`s given were less than the number of values to be serialized.
-    UnexpectedEndOfFields,
+    UnexpectedEndOfFields {
+        /// The position of the value that had no field, from 0, not counting literals.
+        index: usize,
+        /// The struct member being serialized, if the value is, or is part of, one.
+        member: Option<&'static str>,
+    },
+    /// Fields were left over after serializing a value with `Serializer::strict` set. Holds the
+    /// name, or range, of each.
+    UnserializedFields(Vec<String>),
+    /// A value could not be written to the given field.
+    Field {
+        /// The name of the field, if it has one.
+        name: Option<String>,
+        /// The byte range of the field in the record.
+        range: Range<usize>,
+        /// Why the value could not be written.
+        reason: FieldSerializeReason,
+    },
+}
+
+/// Why a value could not be written to a field, as part of a `SerializeError::Field`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FieldSerializeReason {
+    /// The value is wider than the field and cannot be cut short, such as a multi-byte char.
+    TooWide {
+        /// The width of the value in bytes.
+        actual: usize,
+    },
+    /// The value holds a char that cannot be written to the field.
+    Unencodable {
+        /// The char that cannot be written.
+        char: char,
+    },
+    /// No value was given for the field, such as for a struct field skipped by
+    /// `#[serde(skip_serializing_if = "...")]`. Writing nothing would shift every later field.
+    MissingValue,
+    /// The value is a variant that has no code among those set by `FieldSet::variants`.
+    UnmappedVariant {
+        /// The name of the variant.
+        variant: String,
+    },
+    /// A sequence written to a repeated group of fields, set by `FieldSet::occurs`, does not have
+    /// one element per field.
+    ElementCount {
+        /// The number of fields in the group.
+        expected: usize,
+        /// The number of elements in the sequence.
+        actual: usize,
+    },
 }
 
 impl fmt::Display for SerializeError {
@@ -161,13 +267,72 @@ impl fmt::Display for SerializeError {
         match self {
             SerializeError::Message(ref e) => write!(f, "{}", e),
             SerializeError::Unsupported(ref e) => write!(f, "{}", e),
-            SerializeError::UnexpectedEndOfFields => write!(f, "Unexpected End of Fields"),
+            SerializeError::UnexpectedEndOfFields {
+                index,
+                member: Some(member),
+            } => write!(
+                f,
+                "no field definition for struct member `{}` (value index {})",
+                member, index
+            ),
+            SerializeError::UnexpectedEndOfFields {
+                index,
+                member: None,
+            } => write!(f, "no field definition for value index {}", index),
+            SerializeError::UnserializedFields(ref fields) => {
+                write!(f, "fields were not serialized: {}", fields.join(", "))
+            }
+            SerializeError::Field {
+                name: Some(ref name),
+                ref range,
+                ref reason,
+            } => write!(
+                f,
+                "field '{}' ({}..{}): {}",
+                name, range.start, range.end, reason
+            ),
+            SerializeError::Field {
+                name: None,
+                ref range,
+                ref reason,
+            } => write!(f, "field {}..{}: {}", range.start, range.end, reason),
         }
     }
 }
 
+impl fmt::Display for FieldSerializeReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldSerializeReason::TooWide { actual } => {
+                write!(f, "value of {} bytes is too wide for the field", actual)
+            }
+            FieldSerializeReason::Unencodable { char } => {
+                write!(f, "char {:?} cannot be written to the field", char)
+            }
+            FieldSerializeReason::MissingValue => write!(f, "no value was given for the field"),
+            FieldSerializeReason::UnmappedVariant { ref variant } => {
+                write!(f, "variant {:?} has no code", variant)
+            }
+            FieldSerializeReason::ElementCount { expected, actual } => write!(
+                f,
+                "expected {} elements for the repeated field, found {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// Builds a `SerializeError::Field` for the given field.
+pub(crate) fn field_error(field: &FieldConfig, reason: FieldSerializeReason) -> Error {
+    Error::from(SerializeError::Field {
+        name: field.name.clone(),
+        range: field.range.clone(),
+        reason,
+    })
+}
+
 impl StdError for SerializeError {
-    fn cause(&self) -> Option<&dyn StdError> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         None
     }
 }
@@ -181,8 +346,33 @@ impl SerError for Error {
 /// A serializer for fixed width data. Writes to the given Writer using the provided field
 /// definitions to determine how to serialize data into records.
 pub struct Serializer<'w, W: 'w + io::Write> {
-    fields: iter::Peekable<vec::IntoIter<FieldConfig>>,
+    fields: Cow<'w, [FieldConfig]>,
+    pos: usize,
     wrtr: &'w mut W,
+    /// Whether the elements of a flag field are being written, into `flag_buf`.
+    in_flags: bool,
+    flag_buf: Vec<u8>,
+    strict: bool,
+    /// How many sequences, tuples and structs are being serialized, one within the other.
+    depth: usize,
+    /// The struct member being serialized, if any.
+    member: Option<&'static str>,
+    /// The repeated group of fields a sequence is being written to, if any.
+    group: Option<Group>,
+    /// Whether text is written as ISO-8859-1 rather than UTF-8, for a writer that transcodes.
+    latin1: bool,
+}
+
+/// A repeated group of fields, set by `FieldSet::occurs`, that a sequence is being written to.
+struct Group {
+    /// The depth of the sequence.
+    depth: usize,
+    /// The position of the first field of the group.
+    first: usize,
+    /// The number of fields in the group.
+    count: usize,
+    /// The number of elements of the sequence so far.
+    len: usize,
 }
 
 impl<'w, W: 'w + io::Write> Serializer<'w, W> {
@@ -213,28 +403,496 @@ impl<'w, W: 'w + io::Write> Serializer<'w, W> {
     /// ```
     pub fn new(wrtr: &'w mut W, fields: FieldSet) -> Self {
         Self {
-            fields: fields.flatten().into_iter().peekable(),
+            fields: Cow::Owned(fields.flatten()),
+            pos: 0,
+            wrtr,
+            in_flags: false,
+            flag_buf: Vec::new(),
+            strict: false,
+            depth: 0,
+            member: None,
+            group: None,
+            latin1: false,
+        }
+    }
+
+    /// Creates a new Serializer from a Writer and fields that have already been flattened, such as
+    /// by `FieldSet::flatten`. Flattening once and reusing the fields for every record saves
+    /// walking the `FieldSet` each time.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use serde;
+    /// use fixed_width::{FieldSet, Serializer, Writer};
+    /// use serde::Serialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("letters"),
+    ///     FieldSet::new_field(4..8).name("numbers"),
+    /// ])
+    /// .flatten();
+    ///
+    /// let mut writer = Writer::from_memory();
+    ///
+    /// for record in &[vec!["abcd", "1234"], vec!["efgh", "5678"]] {
+    ///     let mut ser = Serializer::with_flattened(&mut writer, &fields);
+    ///     record.serialize(&mut ser).unwrap();
+    /// }
+    ///
+    /// let s: String = writer.into();
+    /// assert_eq!("abcd1234efgh5678", s);
+    /// ```
+    pub fn with_flattened(wrtr: &'w mut W, fields: &[FieldConfig]) -> Self {
+        Self {
+            fields: Cow::Owned(fields.to_vec()),
+            pos: 0,
             wrtr,
+            in_flags: false,
+            flag_buf: Vec::new(),
+            strict: false,
+            depth: 0,
+            member: None,
+            group: None,
+            latin1: false,
         }
     }
 
-    fn next_field(&mut self) -> Result<FieldConfig> {
-        match self.fields.next() {
-            Some(f) => Ok(f),
-            None => Err(Error::from(SerializeError::UnexpectedEndOfFields)),
+    /// Creates a new Serializer from a Writer and borrowed fields that have already been
+    /// flattened, as `with_flattened` does but without cloning the fields.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Serializer, Writer};
+    /// use serde::Serialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field(4..8),
+    /// ])
+    /// .flatten();
+    ///
+    /// let mut writer = Writer::from_memory();
+    /// {
+    ///     let mut ser = Serializer::with_flattened_ref(&mut writer, &fields);
+    ///     vec!["abcd", "1234"].serialize(&mut ser).unwrap();
+    /// }
+    ///
+    /// let s: String = writer.into();
+    /// assert_eq!("abcd1234", s);
+    /// ```
+    pub fn with_flattened_ref(wrtr: &'w mut W, fields: &'w [FieldConfig]) -> Self {
+        Self {
+            fields: Cow::Borrowed(fields),
+            pos: 0,
+            wrtr,
+            in_flags: false,
+            flag_buf: Vec::new(),
+            strict: false,
+            depth: 0,
+            member: None,
+            group: None,
+            latin1: false,
         }
     }
 
-    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
-        self.wrtr.write_all(bytes)?;
+    /// Rewinds the serializer to the first field, so that it can serialize another record. The
+    /// fields are kept, so one serializer can write any number of records. Nothing is written
+    /// between records, so any linebreak has to be written through `get_mut`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Serializer};
+    /// use serde::Serialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4),
+    ///     FieldSet::new_field(4..8),
+    /// ]);
+    ///
+    /// let mut out = Vec::new();
+    /// let mut ser = Serializer::new(&mut out, fields);
+    ///
+    /// for record in &[vec!["abcd", "1234"], vec!["efgh", "5678"]] {
+    ///     ser.reset();
+    ///     record.serialize(&mut ser).unwrap();
+    /// }
+    ///
+    /// assert_eq!(out, b"abcd1234efgh5678");
+    /// ```
+    pub fn reset(&mut self) {
+        self.pos = 0;
+        self.depth = 0;
+        self.member = None;
+        self.group = None;
+        self.in_flags = false;
+        self.flag_buf.clear();
+    }
+
+    /// Sets whether every field must be written. Once the outermost sequence, tuple or struct has
+    /// been serialized, any fields left over result in a `SerializeError::UnserializedFields`,
+    /// such as when a member was removed from a struct but not from its fields.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{Error, FieldSet, SerializeError, Serializer};
+    /// use serde::Serialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..4).name("letters"),
+    ///     FieldSet::new_field(4..8).name("numbers"),
+    /// ]);
+    ///
+    /// let mut out = Vec::new();
+    /// let mut ser = Serializer::new(&mut out, fields).strict(true);
+    ///
+    /// match ("abcd",).serialize(&mut ser) {
+    ///     Err(Error::SerializeError(SerializeError::UnserializedFields(fields))) => {
+    ///         assert_eq!(fields, vec!["numbers"]);
+    ///     }
+    ///     _ => panic!("expected unserialized fields"),
+    /// }
+    /// ```
+    pub fn strict(mut self, val: bool) -> Self {
+        self.strict = val;
+        self
+    }
+
+    /// Sets whether text is written as ISO-8859-1, one byte for each char, rather than UTF-8.
+    /// Defaults to `false`. A char that ISO-8859-1 does not have is a
+    /// `FieldSerializeReason::Unencodable` error.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, Serializer};
+    /// use serde::Serialize;
+    ///
+    /// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..6)]);
+    /// let mut buf = Vec::new();
+    /// let mut ser = Serializer::new(&mut buf, fields).latin1(true);
+    /// ("Café", "é").serialize(&mut ser).unwrap();
+    ///
+    /// assert_eq!(buf, b"Caf\xE9\xE9 ");
+    /// ```
+    pub fn latin1(mut self, val: bool) -> Self {
+        self.latin1 = val;
+        self
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.wrtr
+    }
+
+    /// Takes the next field that takes a value, along with the writer to write it to.
+    fn next_field(&mut self) -> Result<(&FieldConfig, &mut W)> {
+        if self.in_flags {
+            return Err(Error::from(SerializeError::Unsupported(
+                "only bools can be written to a flag field".to_string(),
+            )));
+        }
+        self.write_literals()?;
+
+        match self.fields.get(self.pos) {
+            Some(f) => {
+                self.pos += 1;
+                Ok((f, self.wrtr))
+            }
+            None => Err(Error::from(SerializeError::UnexpectedEndOfFields {
+                index: self.fields.iter().filter(|f| f.literal.is_none()).count(),
+                member: self.member,
+            })),
+        }
+    }
+
+    fn peek_field(&self) -> Option<&FieldConfig> {
+        self.fields.get(self.pos)
+    }
+
+    /// The change of case the next field applies to text.
+    fn next_case(&self) -> Case {
+        self.peek_field().map_or(Case::None, |f| f.case)
+    }
+
+    /// Fails if a value of the given width would have to be cut short to fit the next field.
+    fn check_fits(&self, width: usize) -> Result<()> {
+        match self.peek_field() {
+            Some(field)
+                if !(field.to_end || field.capture_only || field.write_as_blank)
+                    && width > field.width() =>
+            {
+                Err(field_error(
+                    field,
+                    FieldSerializeReason::TooWide { actual: width },
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes a float with the next field's number of decimals, if it has one, and its decimal
+    /// separator.
+    fn serialize_float<F: fmt::Display>(&mut self, val: F) -> Result<()> {
+        self.write_literals()?;
+
+        let field = self.peek_field();
+        let decimals = field.and_then(|f| f.decimals);
+        let s = match decimals {
+            Some(decimals) => format!("{:.*}", decimals, val),
+            None => val.to_string(),
+        };
+        let s = with_separator(s, field);
+
+        // Cutting a rounded number short would change its value.
+        self.serialize_number(&s, decimals.is_some())
+    }
+
+    /// Writes a string holding a plain decimal number to the next field, if it has a number of
+    /// decimals, as a float would be written but without going through one. Returns whether it
+    /// was written.
+    fn serialize_decimal(&mut self, val: &str) -> Result<bool> {
+        if self.in_flags {
+            return Ok(false);
+        }
+        self.write_literals()?;
+
+        let field = self.peek_field().filter(|f| f.variants.is_none());
+        let s = match field.and_then(|f| rescale(val, f.decimals?)) {
+            Some(s) => with_separator(s, field),
+            None => return Ok(false),
+        };
+        self.serialize_number(&s, true)?;
+        Ok(true)
+    }
+
+    /// Writes a formatted number with its sign as the next field asks for. With `exact`, a
+    /// number too wide for the field is an error rather than being cut short.
+    fn serialize_number(&mut self, s: &str, exact: bool) -> Result<()> {
+        self.write_literals()?;
+
+        let is_zero = || {
+            s.bytes()
+                .all(|b| matches!(b, b'0' | b'.' | b',' | b'-' | b'+'))
+        };
+        if self.peek_field().is_some_and(|f| f.blank_when_zero) && is_zero() {
+            return self.write_blank();
+        }
+
+        let signed = match self.peek_field() {
+            Some(field) if field.sign != Sign::NegativeOnly => {
+                let (sign, digits) = match s.strip_prefix('-') {
+                    Some(digits) => ('-', digits),
+                    None => ('+', s),
+                };
+                let zero_filled =
+                    field.pad_with == '0' && field.justify == Justify::Right && !field.to_end;
+
+                Some(match field.sign {
+                    Sign::TrailingChar => format!("{}{}", digits, sign),
+                    _ if zero_filled => {
+                        let width = field.width().saturating_sub(1);
+                        format!("{}{:0>width$}", sign, digits, width = width)
+                    }
+                    _ => format!("{}{}", sign, digits),
+                })
+            }
+            _ => None,
+        };
+        let s = signed.as_deref().unwrap_or(s);
+
+        if exact {
+            self.check_fits(s.len())?;
+        }
+        ser::Serializer::serialize_bytes(self, s.as_bytes())
+    }
+
+    /// Writes any literal fields that come next, as they do not consume a value.
+    pub(crate) fn write_literals(&mut self) -> Result<()> {
+        while let Some(field) = self.fields.get(self.pos).filter(|f| f.literal.is_some()) {
+            self.pos += 1;
+            let literal = field.literal.as_deref().unwrap_or_default();
+            write_padded(self.wrtr, literal.as_bytes(), field)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the next field as spaces, whatever its pad character.
+    fn write_blank(&mut self) -> Result<()> {
+        let (field, wrtr) = self.next_field()?;
+        if !field.capture_only {
+            write_fill(wrtr, b' ', padded_len(&[], field))?;
+        }
+        Ok(())
+    }
+
+    /// Starts writing the elements of a sequence of `len` elements to the next field, if it is a
+    /// flag field with as many flags.
+    fn start_flags(&mut self, len: Option<usize>) -> Result<()> {
+        self.write_literals()?;
+
+        if let Some(flags) = self.peek_field().and_then(|f| f.flags) {
+            if len.is_none_or(|len| len == flags.count) {
+                if self.in_flags {
+                    return Err(Error::from(SerializeError::Unsupported(
+                        "only bools can be written to a flag field".to_string(),
+                    )));
+                }
+                self.in_flags = true;
+                self.flag_buf.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a flag to the flag field being written, with `None` written as a space.
+    fn push_flag(&mut self, val: Option<bool>) -> Result<()> {
+        if let Some(flags) = self.peek_field().and_then(|f| f.flags) {
+            self.flag_buf.push(match val {
+                Some(true) => flags.yes,
+                Some(false) => flags.no,
+                None => b' ',
+            });
+        }
         Ok(())
     }
+
+    /// Starts serializing a sequence, tuple or struct.
+    fn start_compound(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Starts writing the elements of a sequence to the next fields, if they are a repeated group.
+    /// A sequence within one that starts at the same field, such as a `Vec` as the first element
+    /// of a tuple, takes the group over, as nothing has been written to it yet.
+    fn start_group(&mut self) {
+        if self.in_flags || self.group.as_ref().is_some_and(|g| g.first != self.pos) {
+            return;
+        }
+        if let Some(count) = self.peek_field().and_then(|f| f.occurs) {
+            self.group = Some(Group {
+                depth: self.depth,
+                first: self.pos,
+                count,
+                len: 0,
+            });
+        }
+    }
+
+    /// Serializes an element of a sequence. Elements past the end of a repeated group are counted
+    /// but not written, so that the error at the end of the sequence can give their number.
+    fn write_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        if let Some(ref mut group) = self.group {
+            if group.depth == self.depth {
+                group.len += 1;
+                if group.len > group.count {
+                    return Ok(());
+                }
+            }
+        }
+        value.serialize(&mut *self)
+    }
+
+    /// Checks that a sequence written to a repeated group had one element per field.
+    fn end_group(&mut self) -> Result<()> {
+        match self.group {
+            Some(ref group) if group.depth == self.depth => {}
+            _ => return Ok(()),
+        }
+        let group = self.group.take().unwrap();
+        if group.len == group.count {
+            return Ok(());
+        }
+
+        let end = (group.first + group.count).min(self.fields.len());
+        let fields = &self.fields[group.first..end];
+        Err(Error::from(SerializeError::Field {
+            name: fields[0]
+                .name
+                .clone()
+                .or_else(|| self.member.map(str::to_string)),
+            range: fields[0].range.start..fields[fields.len() - 1].range.end,
+            reason: FieldSerializeReason::ElementCount {
+                expected: group.count,
+                actual: group.len,
+            },
+        }))
+    }
+
+    /// Writes the literals after a sequence, tuple or struct. If it is the outermost one and the
+    /// serializer is strict, checks that no fields are left over.
+    fn end_compound(&mut self) -> Result<()> {
+        self.end_group()?;
+        self.write_literals()?;
+        self.depth = self.depth.saturating_sub(1);
+
+        if self.strict && self.depth == 0 && self.pos < self.fields.len() {
+            let unserialized = self.fields[self.pos..]
+                .iter()
+                .filter(|f| f.literal.is_none())
+                .map(FieldConfig::describe)
+                .collect();
+            return Err(SerializeError::UnserializedFields(unserialized).into());
+        }
+        Ok(())
+    }
+
+    /// Serializes a struct member, so that it can be named if it has no field.
+    fn serialize_member<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let outer = self.member.replace(key);
+        let res = value.serialize(&mut *self);
+        self.member = outer;
+        res
+    }
+
+    /// Fails for the next field, as no value was given for the struct member.
+    fn skip_member(&mut self, key: &'static str) -> Result<()> {
+        let outer = self.member.replace(key);
+        let res = self.missing_value();
+        self.member = outer;
+        res
+    }
+
+    /// Writes the flags of the flag field being written, or the literals after a sequence.
+    fn end_seq(&mut self) -> Result<()> {
+        if !self.in_flags {
+            return self.end_compound();
+        }
+        self.depth = self.depth.saturating_sub(1);
+
+        self.in_flags = false;
+        let buf = mem::take(&mut self.flag_buf);
+        let res = self
+            .next_field()
+            .and_then(|(field, wrtr)| match field.flags {
+                Some(flags) if buf.len() > flags.count => Err(field_error(
+                    field,
+                    FieldSerializeReason::TooWide { actual: buf.len() },
+                )),
+                _ => write_value(wrtr, &buf, field),
+            });
+        self.flag_buf = buf;
+        res
+    }
+
+    /// Fails for the next field, as no value was given for it.
+    fn missing_value(&mut self) -> Result<()> {
+        let (field, _) = self.next_field()?;
+        Err(field_error(field, FieldSerializeReason::MissingValue))
+    }
 }
 
-macro_rules! serialize_with_str {
+macro_rules! serialize_int {
     ($ser_fn:ident, $int_ty:ty) => {
         fn $ser_fn(self, val: $int_ty) -> Result<Self::Ok> {
-            self.serialize_str(&val.to_string())
+            self.serialize_number(&val.to_string(), false)
         }
     };
 }
@@ -250,33 +908,89 @@ impl<'a, 'w, W: io::Write> ser::Serializer for &'a mut Serializer<'w, W> {
     type SerializeStruct = Self;
     type SerializeStructVariant = Self;
 
-    serialize_with_str!(serialize_u8, u8);
-    serialize_with_str!(serialize_i8, i8);
-    serialize_with_str!(serialize_u16, u16);
-    serialize_with_str!(serialize_i16, i16);
-    serialize_with_str!(serialize_u32, u32);
-    serialize_with_str!(serialize_i32, i32);
-    serialize_with_str!(serialize_u64, u64);
-    serialize_with_str!(serialize_i64, i64);
-    serialize_with_str!(serialize_f32, f32);
-    serialize_with_str!(serialize_f64, f64);
-    serialize_with_str!(serialize_char, char);
+    serialize_int!(serialize_u8, u8);
+    serialize_int!(serialize_i8, i8);
+    serialize_int!(serialize_u16, u16);
+    serialize_int!(serialize_i16, i16);
+    serialize_int!(serialize_u32, u32);
+    serialize_int!(serialize_i32, i32);
+    serialize_int!(serialize_u64, u64);
+    serialize_int!(serialize_i64, i64);
+    serialize_int!(serialize_u128, u128);
+    serialize_int!(serialize_i128, i128);
+
+    fn serialize_f32(self, val: f32) -> Result<Self::Ok> {
+        self.serialize_float(val)
+    }
+
+    fn serialize_f64(self, val: f64) -> Result<Self::Ok> {
+        self.serialize_float(val)
+    }
+
+    fn serialize_char(self, val: char) -> Result<Self::Ok> {
+        self.write_literals()?;
+
+        // Padding works in bytes, so a char wider than its field would be cut in two.
+        let mut buf = [0; 4];
+        let s = val.encode_utf8(&mut buf);
+        let s = self.next_case().apply(s);
+        let bytes = match self.peek_field() {
+            Some(field) if self.latin1 => to_latin1(&s, field)?,
+            _ => Cow::Borrowed(s.as_bytes()),
+        };
+        self.check_fits(bytes.len())?;
+
+        self.serialize_bytes(&bytes)
+    }
 
     fn serialize_bool(self, val: bool) -> Result<Self::Ok> {
+        if self.in_flags {
+            return self.push_flag(Some(val));
+        }
         self.serialize_str(&(val as u8).to_string())
     }
 
     fn serialize_str(self, val: &str) -> Result<Self::Ok> {
-        let bytes = val.as_bytes();
-        self.serialize_bytes(bytes)
+        if self.serialize_decimal(val)? {
+            return Ok(());
+        }
+
+        let latin1 = self.latin1;
+        let (field, wrtr) = self.next_field()?;
+        let val = match field.variants {
+            Some(ref codes) => codes.code(val).ok_or_else(|| {
+                field_error(
+                    field,
+                    FieldSerializeReason::UnmappedVariant {
+                        variant: val.to_string(),
+                    },
+                )
+            })?,
+            None => val,
+        };
+
+        // The case is changed before padding, as it can change the width of the text.
+        let s = field.case.apply(val);
+        if latin1 {
+            return write_value(wrtr, &to_latin1(&s, field)?, field);
+        }
+        write_value(wrtr, s.as_bytes(), field)
     }
 
     fn serialize_bytes(self, val: &[u8]) -> Result<Self::Ok> {
-        let bytes = pad(val, &self.next_field()?);
-        self.write_bytes(&bytes)
+        let (field, wrtr) = self.next_field()?;
+        write_value(wrtr, val, field)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
+        if self.in_flags {
+            return self.push_flag(None);
+        }
+        self.write_literals()?;
+
+        if self.peek_field().is_some_and(|f| f.blank_when_zero) {
+            return self.write_blank();
+        }
         self.serialize_bytes(&[])
     }
 
@@ -319,7 +1033,10 @@ impl<'a, 'w, W: io::Write> ser::Serializer for &'a mut Serializer<'w, W> {
         val.serialize(&mut *self)
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.start_flags(len)?;
+        self.start_compound();
+        self.start_group();
         Ok(self)
     }
 
@@ -343,6 +1060,7 @@ impl<'a, 'w, W: io::Write> ser::Serializer for &'a mut Serializer<'w, W> {
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
         variant.serialize(&mut *self)?;
+        self.start_compound();
         Ok(self)
     }
 
@@ -351,6 +1069,7 @@ impl<'a, 'w, W: io::Write> ser::Serializer for &'a mut Serializer<'w, W> {
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        self.start_compound();
         Ok(self)
     }
 
@@ -362,6 +1081,7 @@ impl<'a, 'w, W: io::Write> ser::Serializer for &'a mut Serializer<'w, W> {
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
         variant.serialize(&mut *self)?;
+        self.start_compound();
         Ok(self)
     }
 }
@@ -371,11 +1091,11 @@ impl<'a, 'w, W: io::Write> ser::SerializeSeq for &'a mut Serializer<'w, W> {
     type Error = Error;
 
     fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut **self)
+        self.write_element(value)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.end_seq()
     }
 }
 
@@ -384,11 +1104,11 @@ impl<'a, 'w, W: io::Write> ser::SerializeTuple for &'a mut Serializer<'w, W> {
     type Error = Error;
 
     fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut **self)
+        self.write_element(value)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.end_seq()
     }
 }
 
@@ -401,7 +1121,7 @@ impl<'a, 'w, W: io::Write> ser::SerializeTupleStruct for &'a mut Serializer<'w,
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -414,7 +1134,7 @@ impl<'a, 'w, W: io::Write> ser::SerializeTupleVariant for &'a mut Serializer<'w,
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -431,7 +1151,7 @@ impl<'a, 'w, W: io::Write> ser::SerializeMap for &'a mut Serializer<'w, W> {
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.write_literals()
     }
 }
 
@@ -441,14 +1161,18 @@ impl<'a, 'w, W: io::Write> ser::SerializeStruct for &'a mut Serializer<'w, W> {
 
     fn serialize_field<T: ?Sized + Serialize>(
         &mut self,
-        _key: &'static str,
+        key: &'static str,
         value: &T,
     ) -> Result<()> {
-        value.serialize(&mut **self)
+        self.serialize_member(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.skip_member(key)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -458,35 +1182,170 @@ impl<'a, 'w, W: io::Write> ser::SerializeStructVariant for &'a mut Serializer<'w
 
     fn serialize_field<T: ?Sized + Serialize>(
         &mut self,
-        _key: &'static str,
+        key: &'static str,
         value: &T,
     ) -> Result<()> {
-        value.serialize(&mut **self)
+        self.serialize_member(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.skip_member(key)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.end_compound()
     }
 }
 
-#[inline]
-fn pad(bytes: &[u8], field: &FieldConfig) -> Vec<u8> {
+/// Writes the value of a field, as its settings ask for.
+/// Rescales a plain decimal number, such as `-12.345`, to the given number of decimals,
+/// rounding half away from zero. Anything else, such as an exponent or a word, gives `None`.
+fn rescale(s: &str, decimals: usize) -> Option<String> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if (int.is_empty() && frac.is_empty())
+        || !(int.bytes().chain(frac.bytes())).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let mut digits: Vec<u8> = int
+        .bytes()
+        .chain(frac.bytes().chain(std::iter::repeat(b'0')).take(decimals))
+        .collect();
+    if frac.as_bytes().get(decimals).is_some_and(|&b| b >= b'5') {
+        // Nines round up to zeros, carrying into the digit before them.
+        match digits.iter().rposition(|&b| b != b'9') {
+            Some(i) => {
+                digits[i] += 1;
+                digits[i + 1..].iter_mut().for_each(|b| *b = b'0');
+            }
+            None => {
+                digits.iter_mut().for_each(|b| *b = b'0');
+                digits.insert(0, b'1');
+            }
+        }
+    }
+
+    let (int, frac) = digits.split_at(digits.len() - decimals);
+    let int = match int.iter().position(|&b| b != b'0') {
+        Some(i) => &int[i..],
+        None => b"0",
+    };
+    let zero = digits.iter().all(|&b| b == b'0');
+
+    let mut out = String::with_capacity(digits.len() + 2);
+    if negative && !zero {
+        out.push('-');
+    }
+    out.extend(int.iter().map(|&b| b as char));
+    if decimals > 0 {
+        out.push('.');
+        out.extend(frac.iter().map(|&b| b as char));
+    }
+    Some(out)
+}
+
+/// Writes the decimal point of a formatted number as the field asks for. An implied point is
+/// left out along with any leading zeros, so `-0.05` is written as `-5`.
+fn with_separator(s: String, field: Option<&FieldConfig>) -> String {
+    match field {
+        Some(f) if f.decimal_separator == DecimalSeparator::Comma => s.replace('.', ","),
+        Some(f) if f.decimal_separator == DecimalSeparator::Implied && f.decimals.is_some() => {
+            let (sign, digits) = match s.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", s.as_str()),
+            };
+            let digits = digits.replace('.', "");
+            match digits.trim_start_matches('0') {
+                "" => "0".to_string(),
+                digits => format!("{}{}", sign, digits),
+            }
+        }
+        _ => s,
+    }
+}
+
+fn write_value<W: io::Write + ?Sized>(wrtr: &mut W, val: &[u8], field: &FieldConfig) -> Result<()> {
+    if field.capture_only {
+        return Ok(());
+    }
+
+    match field.serialize_with {
+        _ if field.write_as_blank => write_padded(wrtr, &[], field)?,
+        Some(ref f) => write_padded(wrtr, &(f.0)(val)?, field)?,
+        None => write_padded(wrtr, val, field)?,
+    }
+    Ok(())
+}
+
+/// Converts text to ISO-8859-1, one byte for each char, failing on a char that ISO-8859-1 does
+/// not have.
+pub(crate) fn to_latin1<'a>(s: &'a str, field: &FieldConfig) -> Result<Cow<'a, [u8]>> {
+    if s.is_ascii() {
+        return Ok(Cow::Borrowed(s.as_bytes()));
+    }
+
+    s.chars()
+        .map(|char| {
+            std::convert::TryFrom::try_from(char)
+                .map_err(|_| field_error(field, FieldSerializeReason::Unencodable { char }))
+        })
+        .collect::<Result<Vec<u8>>>()
+        .map(Cow::Owned)
+}
+
+/// Writes the given bytes as the value of the field, padded out to its width according to its
+/// justification or cut short if they are too wide. Fields that run to the end of the record are
+/// written as is.
+pub(crate) fn write_padded<W: io::Write + ?Sized>(
+    wrtr: &mut W,
+    bytes: &[u8],
+    field: &FieldConfig,
+) -> io::Result<()> {
+    if field.to_end {
+        return wrtr.write_all(bytes);
+    }
+
     let width = field.width();
+    let value = &bytes[..bytes.len().min(width)];
+    let fill = width - value.len();
+    let left = match field.justify {
+        Justify::Left => 0,
+        Justify::Right => fill,
+        Justify::Center => fill / 2,
+    };
     let pad = field.pad_with as u8;
-    let mut v = bytes.to_vec();
 
-    if v.len() > width {
-        v.resize(width, pad);
+    write_fill(wrtr, pad, left)?;
+    wrtr.write_all(value)?;
+    write_fill(wrtr, pad, fill - left)
+}
+
+/// The number of bytes `write_padded` writes for the given value.
+pub(crate) fn padded_len(bytes: &[u8], field: &FieldConfig) -> usize {
+    if field.to_end {
+        bytes.len()
     } else {
-        for _ in 0..(width - v.len()) {
-            match field.justify {
-                Justify::Left => v.push(pad),
-                _ => v.insert(0, pad),
-            }
-        }
+        field.width()
+    }
+}
+
+/// Writes `len` padding bytes, a chunk at a time from a buffer on the stack.
+fn write_fill<W: io::Write + ?Sized>(wrtr: &mut W, pad: u8, len: usize) -> io::Result<()> {
+    let chunk = [pad; 64];
+    let mut remaining = len;
+
+    while remaining > 0 {
+        let n = remaining.min(chunk.len());
+        wrtr.write_all(&chunk[..n])?;
+        remaining -= n;
     }
 
-    v
+    Ok(())
 }
 
 #[cfg(test)]
@@ -497,6 +1356,13 @@ mod test {
     use serde_derive::Serialize;
     use std::collections::HashMap;
 
+    fn pad(bytes: &[u8], field: &FieldConfig) -> Vec<u8> {
+        let mut v = Vec::new();
+        write_padded(&mut v, bytes, field).unwrap();
+        assert_eq!(v.len(), padded_len(bytes, field));
+        v
+    }
+
     #[test]
     fn bool_ser() {
         let mut wrtr = Writer::from_memory();
@@ -748,6 +1614,594 @@ mod test {
         }
     }
 
+    #[test]
+    fn pad_center_justified() {
+        let inputs = ["123456789", "12345", "1234", "123", "12", ""];
+        let field = &FieldSet::new_field(0..5)
+            .justify(Justify::Center)
+            .pad_with('T')
+            .flatten()[0];
+
+        let expected = ["12345", "12345", "1234T", "T123T", "T12TT", "TTTTT"];
+
+        for (input, expected) in inputs.iter().zip(expected.iter()) {
+            assert_eq!(pad(input.as_bytes(), field), expected.as_bytes());
+        }
+
+        let field = &FieldSet::new_field(0..6).justify("centre").flatten()[0];
+        assert_eq!(pad(b"ab", field), b"  ab  ");
+        assert_eq!(pad(b"abc", field), b" abc  ");
+    }
+
+    #[test]
+    fn reused_serializer() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..4).justify(Justify::Right),
+            FieldSet::new_field(4..7),
+            FieldSet::new_literal(7..8, "*"),
+        ]);
+        let records = [("1", "ab"), ("22", "cde"), ("333", "")];
+
+        let mut expected = Vec::new();
+        for record in records.iter() {
+            to_writer_with_fields(&mut expected, record, fields.clone()).unwrap();
+        }
+
+        let flat = fields.clone().flatten();
+        for mut ser in [
+            Serializer::new(&mut Vec::new(), fields.clone()),
+            Serializer::with_flattened_ref(&mut Vec::new(), &flat),
+        ] {
+            for record in records.iter() {
+                ser.reset();
+                record.serialize(&mut ser).unwrap();
+            }
+            assert_eq!(*ser.get_mut(), expected);
+        }
+        assert_eq!(expected, b"D  1ab *D 22cde*D333   *");
+    }
+
+    #[test]
+    fn float_decimals_ser() {
+        let field = FieldSet::new_field(0..10)
+            .decimals(2)
+            .justify(Justify::Right);
+        let inputs = [0.1, 1e-7, 12345.678];
+        let expected = ["      0.10", "      0.00", "  12345.68"];
+
+        for (input, expected) in inputs.iter().zip(expected.iter()) {
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, input, field.clone()).unwrap();
+            let s: String = w.into();
+            assert_eq!(s, *expected);
+
+            let read: f64 = crate::from_str_with_fields(&s, field.clone()).unwrap();
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, &read, field.clone()).unwrap();
+            let again: String = w.into();
+            assert_eq!(again, s);
+        }
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &1e-7_f32, FieldSet::new_field(0..12).decimals(9)).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "0.000000100 ");
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &12.4, FieldSet::new_field(0..4).decimals(0)).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "12  ");
+    }
+
+    #[test]
+    fn float_decimals_too_wide() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2),
+            FieldSet::new_field(2..8).decimals(2).name("amount"),
+        ]);
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &("ab", 12345.6), fields).unwrap_err();
+
+        match err {
+            Error::SerializeError(SerializeError::Field {
+                ref name,
+                ref reason,
+                ..
+            }) => {
+                assert_eq!(name.as_deref(), Some("amount"));
+                assert_eq!(*reason, FieldSerializeReason::TooWide { actual: 8 });
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn sign_round_trip() {
+        let zero_filled = |sign| {
+            FieldSet::new_field(0..6)
+                .sign(sign)
+                .pad_with('0')
+                .justify(Justify::Right)
+        };
+        let cases = [
+            (FieldSet::new_field(0..6), ["42    ", "-42   ", "0     "]),
+            (
+                zero_filled(Sign::NegativeOnly),
+                ["000042", "000-42", "000000"],
+            ),
+            (
+                FieldSet::new_field(0..6).sign(Sign::Always),
+                ["+42   ", "-42   ", "+0    "],
+            ),
+            (zero_filled(Sign::Always), ["+00042", "-00042", "+00000"]),
+            (
+                FieldSet::new_field(0..6).sign(Sign::TrailingChar),
+                ["42+   ", "42-   ", "0+    "],
+            ),
+            (
+                zero_filled(Sign::TrailingChar),
+                ["00042+", "00042-", "00000+"],
+            ),
+        ];
+
+        for (field, expected) in cases.iter() {
+            for (n, expected) in [42, -42, 0].iter().zip(expected.iter()) {
+                let mut w = Writer::from_memory();
+                to_writer_with_fields(&mut w, n, field.clone()).unwrap();
+                let s: String = w.into();
+                assert_eq!(s, *expected);
+
+                // The zero filled negative number is written as Rust pads it, and cannot be read.
+                if s != "000-42" {
+                    let read: i64 = crate::from_str_with_fields(&s, field.clone()).unwrap();
+                    assert_eq!(read, *n);
+                }
+            }
+        }
+
+        let unsigned: u32 =
+            crate::from_str_with_fields("00042+", zero_filled(Sign::TrailingChar)).unwrap();
+        assert_eq!(unsigned, 42);
+    }
+
+    #[test]
+    fn sign_with_decimals() {
+        let field = FieldSet::new_field(0..7)
+            .decimals(2)
+            .sign(Sign::Always)
+            .pad_with('0')
+            .justify(Justify::Right);
+
+        for (n, expected) in [(1.5, "+001.50"), (-1.5, "-001.50"), (0.0, "+000.00")].iter() {
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, n, field.clone()).unwrap();
+            let s: String = w.into();
+            assert_eq!(s, *expected);
+
+            let read: f64 = crate::from_str_with_fields(&s, field.clone()).unwrap();
+            assert_eq!(read, *n);
+        }
+
+        let field = FieldSet::new_field(0..7)
+            .decimals(2)
+            .sign(Sign::TrailingChar);
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &-1234.5, field).unwrap_err();
+        match err {
+            Error::SerializeError(SerializeError::Field { ref reason, .. }) => {
+                assert_eq!(*reason, FieldSerializeReason::TooWide { actual: 8 });
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn blank_when_zero_round_trip() {
+        fn write<T: Serialize>(val: &T, field: &FieldSet) -> String {
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, val, field.clone()).unwrap();
+            w.into()
+        }
+
+        let field = FieldSet::new_field(0..5)
+            .blank_when_zero(true)
+            .pad_with('0')
+            .justify(Justify::Right);
+
+        // A plain number: zero blanks and reads back as zero.
+        assert_eq!(write(&0_u32, &field), "     ");
+        assert_eq!(write(&7_u32, &field), "00007");
+        let n: u32 = crate::from_str_with_fields("     ", field.clone()).unwrap();
+        assert_eq!(n, 0);
+
+        // An optional number: `Some(0)` and `None` both blank, and blank reads back as `None`.
+        assert_eq!(write(&Some(0_i64), &field), "     ");
+        assert_eq!(write(&None::<i64>, &field), "     ");
+        assert_eq!(write(&Some(7_i64), &field), "00007");
+        let n: Option<i64> = crate::from_str_with_fields("     ", field.clone()).unwrap();
+        assert_eq!(n, None);
+
+        // A float is blanked if it is written as zero.
+        assert_eq!(write(&-0.0, &field), "     ");
+        assert_eq!(write(&0.001, &field), "0.001");
+        let field = field.decimals(2);
+        assert_eq!(write(&0.001, &field), "     ");
+        let n: f64 = crate::from_str_with_fields("     ", field).unwrap();
+        assert_eq!(n, 0.0);
+    }
+
+    #[test]
+    fn case_transform() {
+        fn write<T: Serialize>(val: &T, field: FieldSet) -> Result<String> {
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, val, field)?;
+            Ok(w.into())
+        }
+
+        let upper = |range| FieldSet::new_field(range).transform(Case::Upper);
+        let lower = |range| FieldSet::new_field(range).transform(Case::Lower);
+
+        assert_eq!(write(&"ny", upper(0..4)).unwrap(), "NY  ");
+        assert_eq!(write(&"New York", lower(0..8)).unwrap(), "new york");
+        assert_eq!(write(&"ny", FieldSet::new_field(0..2)).unwrap(), "ny");
+        assert_eq!(write(&'q', upper(0..1)).unwrap(), "Q");
+        assert_eq!(write(&f64::INFINITY, upper(0..3)).unwrap(), "inf");
+
+        // Upper casing `ß` makes it longer, and the text is cut to the field after that.
+        assert_eq!(write(&"Straße", upper(0..8)).unwrap(), "STRASSE ");
+        assert_eq!(write(&"Straße", upper(0..6)).unwrap(), "STRASS");
+        assert_eq!(write(&'ß', upper(0..2)).unwrap(), "SS");
+        match write(&'ß', upper(0..1)).unwrap_err() {
+            Error::SerializeError(SerializeError::Field { ref reason, .. }) => {
+                assert_eq!(*reason, FieldSerializeReason::TooWide { actual: 2 });
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        // Text is only changed on read when asked for.
+        let s: String = crate::from_str_with_fields("Straße", upper(0..7)).unwrap();
+        assert_eq!(s, "Straße");
+        let field = upper(0..7).transform_on_read(true);
+        let s: String = crate::from_str_with_fields("Straße", field.clone()).unwrap();
+        assert_eq!(s, "STRASSE");
+        let s: &str = crate::from_str_with_fields("NY     ", field).unwrap();
+        assert_eq!(s, "NY");
+        let c: char =
+            crate::from_str_with_fields("q", upper(0..1).transform_on_read(true)).unwrap();
+        assert_eq!(c, 'Q');
+    }
+
+    #[test]
+    fn enum_codes_round_trip() {
+        #[derive(Debug, PartialEq, Serialize, serde_derive::Deserialize)]
+        enum Account {
+            Checking,
+            Savings,
+            Closed,
+        }
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).justify(Justify::Right),
+            FieldSet::new_field(3..5).variants(&[("01", "Checking"), ("02", "Savings")]),
+        ]);
+
+        for (record, expected) in [
+            ((7, Account::Checking), "  701"),
+            ((12, Account::Savings), " 1202"),
+        ]
+        .iter()
+        {
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, record, fields.clone()).unwrap();
+            let s: String = w.into();
+            assert_eq!(s, *expected);
+
+            let read: (u32, Account) = crate::from_str_with_fields(&s, fields.clone()).unwrap();
+            assert_eq!(read, *record);
+        }
+
+        let mut w = Writer::from_memory();
+        match to_writer_with_fields(&mut w, &(1, Account::Closed), fields).unwrap_err() {
+            Error::SerializeError(SerializeError::Field {
+                ref range,
+                ref reason,
+                ..
+            }) => {
+                assert_eq!(*range, 3..5);
+                assert_eq!(
+                    *reason,
+                    FieldSerializeReason::UnmappedVariant {
+                        variant: "Closed".to_string()
+                    }
+                );
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn decimal_comma_round_trip() {
+        let amount = |range| {
+            FieldSet::new_field(range)
+                .decimal_separator(DecimalSeparator::Comma)
+                .decimals(2)
+                .justify(Justify::Right)
+                .pad_with('0')
+        };
+        let fields = FieldSet::Seq(vec![
+            amount(0..9),
+            amount(9..18).sign(Sign::Always),
+            FieldSet::new_field(18..22)
+                .decimal_separator(DecimalSeparator::Comma)
+                .blank_when_zero(true),
+        ]);
+
+        let record = (1234.56, -0.07, 0.0);
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &record, fields.clone()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "001234,56-00000,07    ");
+
+        let read: (f64, f64, f64) = crate::from_str_with_fields(&s, fields).unwrap();
+        assert_eq!(read, record);
+
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &1234567.891, amount(0..9)).unwrap_err();
+        match err {
+            Error::SerializeError(SerializeError::Field { ref reason, .. }) => {
+                assert_eq!(*reason, FieldSerializeReason::TooWide { actual: 10 });
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn decimal_text_ser() {
+        let amount = |range| {
+            FieldSet::new_field(range)
+                .decimals(2)
+                .justify(Justify::Right)
+        };
+        let fields = FieldSet::Seq(vec![
+            amount(0..22),
+            amount(22..28),
+            amount(28..34),
+            amount(34..40).pad_with('0').sign(Sign::Always),
+            FieldSet::new_field(40..45).decimals(2),
+        ]);
+
+        let record = ("12345678901234567.891", "99.995", "-0.004", "-1.5", "n/a");
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &record, fields).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "  12345678901234567.89100.00  0.00-01.50n/a  ");
+    }
+
+    #[test]
+    fn implied_decimal_round_trip() {
+        let amount = |range| {
+            FieldSet::new_field(range)
+                .decimal_separator(DecimalSeparator::Implied)
+                .decimals(2)
+                .justify(Justify::Right)
+                .pad_with('0')
+        };
+        let fields = FieldSet::Seq(vec![
+            amount(0..8),
+            amount(8..16).sign(Sign::TrailingChar),
+            amount(16..24).sign(Sign::Always),
+            FieldSet::new_field(24..28)
+                .decimal_separator(DecimalSeparator::Implied)
+                .justify(Justify::Right),
+        ]);
+
+        let record = (1234.56, -0.05, "-12.345", 1.5);
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &record, fields.clone()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "001234560000005--0001235 1.5");
+
+        let read: (f64, f64, String, f64) = crate::from_str_with_fields(&s, fields).unwrap();
+        assert_eq!(read, (1234.56, -0.05, "-12.35".to_string(), 1.5));
+    }
+
+    #[test]
+    fn flags_ser() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "F"),
+            FieldSet::new_field(1..5).flags(4, 'Y', 'N'),
+            FieldSet::new_field(5..8).flags(3, '1', '0'),
+        ]);
+
+        let record = ([true, false, false, true], [Some(true), None, Some(false)]);
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &record, fields.clone()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "FYNNY1 0");
+
+        let read: ([bool; 4], [Option<bool>; 3]) =
+            crate::from_str_with_fields(&s, fields.clone()).unwrap();
+        assert_eq!(read, record);
+
+        let mut w = Writer::from_memory();
+        let too_many = (true, true, true, true, true);
+        assert!(to_writer_with_fields(&mut w, &(too_many, [true; 3]), fields.clone()).is_err());
+
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &([1, 2, 3, 4], [true; 3]), fields).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SerializeError(SerializeError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn flags_reset_after_error() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3).flags(3, 'Y', 'N'),
+            FieldSet::new_field(3..5),
+        ]);
+
+        let mut out = Vec::new();
+        let mut ser = Serializer::new(&mut out, fields);
+
+        assert!((["a", "b", "c"], "1").serialize(&mut ser).is_err());
+        ser.reset();
+        ([true, false, true], "2").serialize(&mut ser).unwrap();
+
+        assert_eq!(out, b"YNY2 ");
+    }
+
+    #[test]
+    #[should_panic(expected = "flag characters must be ASCII")]
+    fn flags_non_ascii() {
+        let _ = FieldSet::new_field(0..2).flags(2, 'é', 'N');
+    }
+
+    #[test]
+    fn occurs_ser() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..2),
+                FieldSet::new_field(2..4),
+                FieldSet::new_field(4..6),
+            ])
+            .occurs(3),
+            FieldSet::new_literal(6..7, "*"),
+            FieldSet::new_field(7..9).name("trailer"),
+        ]);
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &(vec![1, 2, 3], 4), fields.clone()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "1 2 3 *4 ");
+
+        for (values, actual) in [(vec![1, 2], 2), (vec![1, 2, 3, 4, 5], 5)] {
+            let mut w = Writer::from_memory();
+            let err = to_writer_with_fields(&mut w, &(values, 4), fields.clone()).unwrap_err();
+
+            match err {
+                Error::SerializeError(SerializeError::Field {
+                    ref name,
+                    ref range,
+                    ref reason,
+                }) => {
+                    assert_eq!(*name, None);
+                    assert_eq!(*range, 0..6);
+                    assert_eq!(
+                        *reason,
+                        FieldSerializeReason::ElementCount {
+                            expected: 3,
+                            actual
+                        }
+                    );
+                }
+                ref other => panic!("expected a field error, got {:?}", other),
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Account {
+        number: u32,
+        holder: (String, String),
+        suffix: String,
+    }
+
+    fn account() -> Account {
+        Account {
+            number: 42,
+            holder: ("Carl".to_string(), "Sagan".to_string()),
+            suffix: "XY".to_string(),
+        }
+    }
+
+    #[test]
+    fn end_of_fields_names_member() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "A"),
+            FieldSet::new_field(1..5),
+            FieldSet::new_field(5..9),
+            FieldSet::new_field(9..14),
+        ]);
+
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &account(), fields).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no field definition for struct member `suffix` (value index 3)"
+        );
+
+        let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..8)]);
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &account(), fields).unwrap_err();
+        match err {
+            Error::SerializeError(SerializeError::UnexpectedEndOfFields { index, member }) => {
+                assert_eq!((index, member), (2, Some("holder")));
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &(1, 2), FieldSet::new_field(0..1)).unwrap_err();
+        assert_eq!(err.to_string(), "no field definition for value index 1");
+    }
+
+    #[test]
+    fn strict_unserialized_fields() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4),
+            FieldSet::new_field(4..8),
+            FieldSet::new_field(8..13),
+            FieldSet::new_field(13..15).name("suffix"),
+            FieldSet::new_field(15..20).name("branch"),
+            FieldSet::new_literal(20..21, "Z"),
+            FieldSet::new_field(21..22),
+        ]);
+
+        let mut out = Vec::new();
+        let mut ser = Serializer::new(&mut out, fields.clone());
+        account().serialize(&mut ser).unwrap();
+        assert_eq!(out, b"42  CarlSaganXY");
+
+        let mut out = Vec::new();
+        let mut ser = Serializer::new(&mut out, fields.clone()).strict(true);
+        match account().serialize(&mut ser) {
+            Err(Error::SerializeError(SerializeError::UnserializedFields(f))) => {
+                assert_eq!(f, vec!["branch", "21..22"])
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // Only the end of the outermost value is checked, not the struct nested in it.
+        let mut out = Vec::new();
+        let mut ser = Serializer::new(&mut out, fields).strict(true);
+        (account(), "North", "1").serialize(&mut ser).unwrap();
+        ser.reset();
+        ((42, ("Carl", "Sagan"), "XY"), "South")
+            .serialize(&mut ser)
+            .unwrap_err();
+        assert_eq!(out, b"42  CarlSaganXYNorthZ142  CarlSaganXYSouthZ");
+    }
+
+    #[test]
+    fn pad_wider_than_fill_chunk() {
+        let field = &FieldSet::new_field(0..150)
+            .justify(Justify::Center)
+            .pad_with('0')
+            .flatten()[0];
+
+        let padded = pad(b"xy", field);
+        assert_eq!(padded.len(), 150);
+        assert_eq!(&padded[..74], &[b'0'; 74][..]);
+        assert_eq!(&padded[74..76], b"xy");
+        assert_eq!(&padded[76..], &[b'0'; 74][..]);
+
+        let field = &FieldSet::new_field_to_end(3).flatten()[0];
+        assert_eq!(pad(b"anything", field), b"anything");
+    }
+
     #[test]
     fn to_string_ser() {
         let test = Test1 {
@@ -819,4 +2273,177 @@ mod test {
         let s = to_string(&test).unwrap();
         assert_eq!(s, "123abc987612 321cba678921 ".to_string());
     }
+
+    #[test]
+    fn field_to_end_ser() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..4),
+            FieldSet::new_field_to_end(4),
+        ]);
+
+        let mut wrtr = Writer::from_memory();
+        to_writer_with_fields(&mut wrtr, &("ab", "any length of text"), fields.clone()).unwrap();
+        to_writer_with_fields(&mut wrtr, &("cd", ""), fields).unwrap();
+        let s: String = wrtr.into();
+
+        assert_eq!(s, "ab  any length of textcd  ");
+    }
+
+    #[test]
+    fn capture_only_ser() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..0).capture_only(true),
+            FieldSet::new_field(0..4),
+            FieldSet::new_field(4..8).justify(Justify::Right),
+        ]);
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &("stale record", "abc", 12), fields).unwrap();
+
+        let s: String = w.into();
+        assert_eq!(s, "abc   12");
+    }
+
+    #[test]
+    fn write_as_blank_ser() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3),
+            FieldSet::new_field(3..6).pad_with('0').write_as_blank(true),
+            FieldSet::Seq(vec![FieldSet::new_field(6..8), FieldSet::new_field(8..10)])
+                .write_as_blank(true),
+            FieldSet::new_field(10..12),
+        ]);
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &("abc", 123, ("de", "fg"), "hi"), fields).unwrap();
+
+        let s: String = w.into();
+        assert_eq!(s, "abc000    hi");
+    }
+
+    #[test]
+    fn ser_128_bit_ints() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..39),
+            FieldSet::new_field(39..79).justify(Justify::Right),
+        ]);
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &(u128::MAX, i128::MIN), fields.clone()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, format!("{}{}", u128::MAX, i128::MIN));
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &(1u128, -1i128), fields).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, format!("1{}{}-1", " ".repeat(38), " ".repeat(38)));
+    }
+
+    #[test]
+    fn ser_multi_byte_chars() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..3),
+            FieldSet::new_field(3..6).justify(Justify::Right),
+        ]);
+
+        for c in ['a', 'é', 'あ'] {
+            let mut w = Writer::from_memory();
+            to_writer_with_fields(&mut w, &(c, c), fields.clone()).unwrap();
+            let s: String = w.into();
+            let pad = " ".repeat(3 - c.len_utf8());
+            assert_eq!(s, format!("{}{}{}{}", c, pad, pad, c));
+        }
+
+        let fields = FieldSet::new_field(0..1).name("initial");
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &'a', fields.clone()).unwrap();
+        let err = to_writer_with_fields(&mut w, &'é', fields).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field 'initial' (0..1): value of 2 bytes is too wide for the field"
+        );
+    }
+
+    #[test]
+    fn ser_with_flattened() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_literal(0..1, "D"),
+            FieldSet::new_field(1..5),
+            FieldSet::Seq(vec![
+                FieldSet::new_field(5..8).justify(Justify::Right),
+                FieldSet::new_field(8..11).pad_with('0'),
+            ]),
+        ]);
+        let flat = fields.clone().flatten();
+        let records = [("ab", 1, "x"), ("wxyz", 123, "yz"), ("", 0, "")];
+
+        for record in records.iter() {
+            let mut expected = Writer::from_memory();
+            to_writer_with_fields(&mut expected, record, fields.clone()).unwrap();
+
+            let mut w = Writer::from_memory();
+            record
+                .serialize(&mut Serializer::with_flattened(&mut w, &flat))
+                .unwrap();
+
+            assert_eq!(String::from(w), String::from(expected));
+        }
+    }
+
+    #[test]
+    fn ser_field_errors() {
+        #[derive(Serialize)]
+        struct Partial {
+            code: &'static str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            amount: Option<u32>,
+        }
+
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..2).name("code"),
+            FieldSet::new_field(2..6).name("amount"),
+        ]);
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(
+            &mut w,
+            &Partial {
+                code: "AB",
+                amount: None,
+            },
+            fields,
+        )
+        .unwrap_err();
+
+        match err {
+            Error::SerializeError(SerializeError::Field {
+                ref name,
+                ref range,
+                ref reason,
+            }) => {
+                assert_eq!(name.as_deref(), Some("amount"));
+                assert_eq!(*range, 2..6);
+                assert_eq!(*reason, FieldSerializeReason::MissingValue);
+            }
+            ref other => panic!("expected a field error, got {:?}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "field 'amount' (2..6): no value was given for the field"
+        );
+
+        let mut w = Writer::from_memory();
+        let err = to_writer_with_fields(&mut w, &'あ', FieldSet::new_field(4..6)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SerializeError(SerializeError::Field {
+                name: None,
+                reason: FieldSerializeReason::TooWide { actual: 3 },
+                ..
+            })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "field 4..6: value of 3 bytes is too wide for the field"
+        );
+    }
 }
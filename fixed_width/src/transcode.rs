@@ -0,0 +1,434 @@
+//! Conversion of whole records between EBCDIC code pages and ASCII, as they are read by a
+//! `Reader` or written by a `Writer`.
+
+use crate::{FieldConfig, FieldSet};
+use std::ops::Range;
+
+/// How each record is converted as it is read or written, set by `Reader::transcode` and
+/// `Writer::transcode`. Only the records are converted, not any linebreaks between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Transcode {
+    /// The records are EBCDIC in the given code page. Each byte is converted to the byte of the
+    /// same character in ISO-8859-1 when reading, and back when writing, so every field keeps
+    /// its offset. Text is read from and written to ISO-8859-1 as UTF-8, so characters outside
+    /// ASCII, such as `é` or `¢`, take a single byte in the record.
+    Ebcdic(CodePage),
+}
+
+/// An EBCDIC code page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CodePage {
+    /// CP037, EBCDIC for the USA and Canada.
+    Cp037,
+    /// CP500, international EBCDIC.
+    Cp500,
+    /// Any other code page, given by the ISO-8859-1 byte each of its bytes stands for. A
+    /// character that several bytes stand for is written as the lowest of them, and one that no
+    /// byte stands for as the EBCDIC substitute character, `0x3F`.
+    Custom(&'static [u8; 256]),
+}
+
+impl CodePage {
+    /// The ISO-8859-1 byte that each byte of the code page stands for.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::CodePage;
+    ///
+    /// assert_eq!(CodePage::Cp037.table()[0xC1], b'A');
+    /// assert_eq!(CodePage::Cp037.table()[0x5A], b'!');
+    /// assert_eq!(CodePage::Cp500.table()[0x5A], b']');
+    /// ```
+    pub fn table(&self) -> &'static [u8; 256] {
+        match self {
+            CodePage::Cp037 => &CP037,
+            CodePage::Cp500 => &CP500,
+            CodePage::Custom(table) => table,
+        }
+    }
+}
+
+/// The EBCDIC substitute character, written for characters the code page cannot hold.
+const EBCDIC_SUB: u8 = 0x3F;
+
+/// Lookup tables to convert records in both directions, with the ranges of the fields to leave
+/// as they are.
+#[derive(Debug, Clone)]
+pub(crate) struct Transcoder {
+    decode: [u8; 256],
+    encode: [u8; 256],
+    keep: Vec<Range<usize>>,
+}
+
+impl Transcoder {
+    pub(crate) fn new(transcode: Transcode) -> Self {
+        let Transcode::Ebcdic(page) = transcode;
+        let decode = *page.table();
+
+        let mut encode = [EBCDIC_SUB; 256];
+        for (byte, &decoded) in decode.iter().enumerate().rev() {
+            encode[decoded as usize] = byte as u8;
+        }
+
+        Self {
+            decode,
+            encode,
+            keep: Vec::new(),
+        }
+    }
+
+    /// Leaves the fields set as `bytes`, such as packed decimals, as they are.
+    pub(crate) fn keep_bytes_fields(&mut self, fields: &FieldSet) {
+        self.keep = bytes_ranges(fields.iter_flat());
+    }
+
+    /// Converts a record that was read to ISO-8859-1.
+    pub(crate) fn decode(&self, record: &mut [u8]) {
+        apply(&self.decode, record, &self.keep);
+    }
+
+    /// Converts a record to be written from ISO-8859-1, leaving the given byte ranges as they are.
+    pub(crate) fn encode(&self, record: &mut [u8], keep: &[Range<usize>]) {
+        apply(&self.encode, record, keep);
+    }
+
+    /// Converts a record to be written from ISO-8859-1, leaving the fields set by
+    /// `keep_bytes_fields` as they are.
+    pub(crate) fn encode_kept(&self, record: &mut [u8]) {
+        apply(&self.encode, record, &self.keep);
+    }
+
+    /// The byte of the code page for a single ISO-8859-1 byte.
+    pub(crate) fn encode_byte(&self, byte: u8) -> u8 {
+        self.encode[byte as usize]
+    }
+}
+
+/// The ranges of the fields set as `bytes`, in order.
+pub(crate) fn bytes_ranges<'a>(fields: impl Iterator<Item = &'a FieldConfig>) -> Vec<Range<usize>> {
+    let mut ranges: Vec<_> = fields
+        .filter(|conf| conf.bytes)
+        .map(|conf| match conf.to_end {
+            true => conf.range.start..usize::MAX,
+            false => conf.range.clone(),
+        })
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+/// Maps each byte of the record through the table, except those in the `keep` ranges.
+fn apply(table: &[u8; 256], record: &mut [u8], keep: &[Range<usize>]) {
+    let mut start = 0;
+
+    for range in keep {
+        let end = range.start.clamp(start, record.len());
+        record[start..end]
+            .iter_mut()
+            .for_each(|b| *b = table[*b as usize]);
+        start = range.end.clamp(start, record.len());
+    }
+    record[start..]
+        .iter_mut()
+        .for_each(|b| *b = table[*b as usize]);
+}
+
+static CP037: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9c, 0x09, 0x86, 0x7f, 0x97, 0x8d, 0x8e, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x9d, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8f, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0a, 0x17, 0x1b, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9a, 0x9b, 0x14, 0x15, 0x9e, 0x1a,
+    0x20, 0xa0, 0xe2, 0xe4, 0xe0, 0xe1, 0xe3, 0xe5, 0xe7, 0xf1, 0xa2, 0x2e, 0x3c, 0x28, 0x2b, 0x7c,
+    0x26, 0xe9, 0xea, 0xeb, 0xe8, 0xed, 0xee, 0xef, 0xec, 0xdf, 0x21, 0x24, 0x2a, 0x29, 0x3b, 0xac,
+    0x2d, 0x2f, 0xc2, 0xc4, 0xc0, 0xc1, 0xc3, 0xc5, 0xc7, 0xd1, 0xa6, 0x2c, 0x25, 0x5f, 0x3e, 0x3f,
+    0xf8, 0xc9, 0xca, 0xcb, 0xc8, 0xcd, 0xce, 0xcf, 0xcc, 0x60, 0x3a, 0x23, 0x40, 0x27, 0x3d, 0x22,
+    0xd8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xab, 0xbb, 0xf0, 0xfd, 0xfe, 0xb1,
+    0xb0, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0xaa, 0xba, 0xe6, 0xb8, 0xc6, 0xa4,
+    0xb5, 0x7e, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0xa1, 0xbf, 0xd0, 0xdd, 0xde, 0xae,
+    0x5e, 0xa3, 0xa5, 0xb7, 0xa9, 0xa7, 0xb6, 0xbc, 0xbd, 0xbe, 0x5b, 0x5d, 0xaf, 0xa8, 0xb4, 0xd7,
+    0x7b, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xad, 0xf4, 0xf6, 0xf2, 0xf3, 0xf5,
+    0x7d, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x52, 0xb9, 0xfb, 0xfc, 0xf9, 0xfa, 0xff,
+    0x5c, 0xf7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0xb2, 0xd4, 0xd6, 0xd2, 0xd3, 0xd5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xb3, 0xdb, 0xdc, 0xd9, 0xda, 0x9f,
+];
+
+static CP500: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9c, 0x09, 0x86, 0x7f, 0x97, 0x8d, 0x8e, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x9d, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8f, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0a, 0x17, 0x1b, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9a, 0x9b, 0x14, 0x15, 0x9e, 0x1a,
+    0x20, 0xa0, 0xe2, 0xe4, 0xe0, 0xe1, 0xe3, 0xe5, 0xe7, 0xf1, 0x5b, 0x2e, 0x3c, 0x28, 0x2b, 0x21,
+    0x26, 0xe9, 0xea, 0xeb, 0xe8, 0xed, 0xee, 0xef, 0xec, 0xdf, 0x5d, 0x24, 0x2a, 0x29, 0x3b, 0x5e,
+    0x2d, 0x2f, 0xc2, 0xc4, 0xc0, 0xc1, 0xc3, 0xc5, 0xc7, 0xd1, 0xa6, 0x2c, 0x25, 0x5f, 0x3e, 0x3f,
+    0xf8, 0xc9, 0xca, 0xcb, 0xc8, 0xcd, 0xce, 0xcf, 0xcc, 0x60, 0x3a, 0x23, 0x40, 0x27, 0x3d, 0x22,
+    0xd8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xab, 0xbb, 0xf0, 0xfd, 0xfe, 0xb1,
+    0xb0, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0xaa, 0xba, 0xe6, 0xb8, 0xc6, 0xa4,
+    0xb5, 0x7e, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0xa1, 0xbf, 0xd0, 0xdd, 0xde, 0xae,
+    0xa2, 0xa3, 0xa5, 0xb7, 0xa9, 0xa7, 0xb6, 0xbc, 0xbd, 0xbe, 0xac, 0x7c, 0xaf, 0xa8, 0xb4, 0xd7,
+    0x7b, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xad, 0xf4, 0xf6, 0xf2, 0xf3, 0xf5,
+    0x7d, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x52, 0xb9, 0xfb, 0xfc, 0xf9, 0xfa, 0xff,
+    0x5c, 0xf7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0xb2, 0xd4, 0xd6, 0xd2, 0xd3, 0xd5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xb3, 0xdb, 0xdc, 0xd9, 0xda, 0x9f,
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Error, FixedWidth, FixedWidthRecord, LineBreak, LineMode, OnMismatch, Reader, Result,
+        Writer,
+    };
+    use serde_bytes::ByteBuf;
+    use serde_derive::{Deserialize, Serialize};
+    use std::io;
+
+    #[test]
+    fn tables_round_trip() {
+        for page in [CodePage::Cp037, CodePage::Cp500] {
+            let transcoder = Transcoder::new(Transcode::Ebcdic(page));
+            let mut buf: Vec<u8> = (0..=255).collect();
+            transcoder.encode(&mut buf, &[]);
+            transcoder.decode(&mut buf);
+            assert_eq!(buf, (0..=255).collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    fn known_bytes() {
+        // "Price: $12.50 [ok]" in each code page.
+        let cp037 = [
+            0xD7, 0x99, 0x89, 0x83, 0x85, 0x7A, 0x40, 0x5B, 0xF1, 0xF2, 0x4B, 0xF5, 0xF0, 0x40,
+            0xBA, 0x96, 0x92, 0xBB,
+        ];
+        let mut cp500 = cp037;
+        cp500[14] = 0x4A;
+        cp500[17] = 0x5A;
+
+        for (page, ebcdic) in [(CodePage::Cp037, cp037), (CodePage::Cp500, cp500)] {
+            let transcoder = Transcoder::new(Transcode::Ebcdic(page));
+            let mut buf = ebcdic.to_vec();
+            transcoder.decode(&mut buf);
+            assert_eq!(buf, b"Price: $12.50 [ok]");
+
+            transcoder.encode(&mut buf, &[]);
+            assert_eq!(buf, ebcdic);
+        }
+
+        let transcoder = Transcoder::new(Transcode::Ebcdic(CodePage::Cp037));
+        let mut buf = vec![0x4A, 0xC0, 0x51];
+        transcoder.decode(&mut buf);
+        assert_eq!(buf, [0xA2, b'{', 0xE9]);
+    }
+
+    #[test]
+    fn custom_code_page() {
+        static UPPER: [u8; 256] = {
+            let mut table = [0; 256];
+            let mut i = 0;
+            while i < 256 {
+                table[i] = i as u8;
+                i += 1;
+            }
+            table[b'a' as usize] = b'A';
+            table
+        };
+
+        let transcoder = Transcoder::new(Transcode::Ebcdic(CodePage::Custom(&UPPER)));
+        let mut buf = b"aAb".to_vec();
+        transcoder.decode(&mut buf);
+        assert_eq!(buf, b"AAb");
+
+        // Both `a` and `A` stand for `A`, and nothing stands for `a`.
+        transcoder.encode(&mut buf, &[]);
+        assert_eq!(buf, b"AAb");
+        let mut buf = b"a".to_vec();
+        transcoder.encode(&mut buf, &[]);
+        assert_eq!(buf, [EBCDIC_SUB]);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Payment {
+        name: String,
+        amount: ByteBuf,
+        code: String,
+    }
+
+    impl FixedWidth for Payment {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![
+                FieldSet::new_field(0..4),
+                FieldSet::new_field(4..7).bytes(true),
+                FieldSet::new_field(7..9),
+            ])
+        }
+    }
+
+    #[test]
+    fn packed_fields_kept() {
+        // "Carl", the packed decimal +12345 and "OK", then "Ann ", -00001 and "NO".
+        let data = [
+            0xC3, 0x81, 0x99, 0x93, 0x12, 0x34, 0x5C, 0xD6, 0xD2, b'\n', 0xC1, 0x95, 0x95, 0x40,
+            0x00, 0x00, 0x1D, 0xD5, 0xD6,
+        ];
+
+        let mut rdr = Reader::from_bytes(&data[..])
+            .width(9)
+            .linebreak(LineBreak::Newline)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+        let records: Vec<Payment> = rdr.deserialize().map(Result::unwrap).collect();
+        assert_eq!(records[0].name, "Carl");
+        assert_eq!(records[0].amount.as_ref(), [0x12, 0x34, 0x5C]);
+        assert_eq!(records[1].name, "Ann");
+        assert_eq!(records[1].amount.as_ref(), [0x00, 0x00, 0x1D]);
+        assert_eq!(records[1].code, "NO");
+
+        let mut wrtr = Writer::from_memory()
+            .linebreak(LineBreak::Newline)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+        wrtr.write_serialized(records.into_iter()).unwrap();
+        let written: Vec<u8> = wrtr.into();
+        assert_eq!(written, data);
+
+        // Read as bytes, the packed field is converted unless it is kept.
+        let rdr = Reader::from_bytes(&data[..9])
+            .width(9)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+        let record = rdr.into_byte_records().next().unwrap().unwrap();
+        assert_ne!(record[4..7], [0x12, 0x34, 0x5C]);
+
+        let rdr = Reader::from_bytes(&data[..9])
+            .width(9)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037))
+            .keep_bytes_fields(&Payment::fields());
+        let record = rdr.into_byte_records().next().unwrap().unwrap();
+        assert_eq!(record, b"Carl\x12\x34\x5cOK");
+    }
+
+    #[test]
+    fn short_lines_padded_with_spaces() {
+        // "AB", two bytes short, then "CDEF".
+        let data = [0xC1, 0xC2, b'\n', 0xC3, 0xC4, 0xC5, 0xC6];
+        let mut rdr = Reader::from_bytes(&data[..])
+            .width(4)
+            .linebreak(LineBreak::Newline)
+            .line_mode(LineMode {
+                expect_width: None,
+                on_mismatch: OnMismatch::Pad,
+            })
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"AB  ");
+        assert_eq!(rdr.next_record().unwrap().unwrap(), b"CDEF");
+    }
+
+    // "Café", the packed decimal +12345 and "Zé".
+    const CAFE: [u8; 9] = [0xC3, 0x81, 0x86, 0x51, 0x12, 0x34, 0x5C, 0xE9, 0x51];
+
+    fn cafe() -> Payment {
+        Payment {
+            name: "Café".to_string(),
+            amount: ByteBuf::from(vec![0x12, 0x34, 0x5C]),
+            code: "Zé".to_string(),
+        }
+    }
+
+    #[test]
+    fn text_outside_ascii() {
+        let mut rdr = Reader::from_bytes(&CAFE[..])
+            .width(9)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+        let records: Vec<Payment> = rdr.deserialize().map(Result::unwrap).collect();
+        assert_eq!(records, vec![cafe()]);
+
+        let mut wrtr = Writer::from_memory().transcode(Transcode::Ebcdic(CodePage::Cp037));
+        wrtr.write_serialized(records.into_iter()).unwrap();
+        let written: Vec<u8> = wrtr.into();
+        assert_eq!(written, CAFE);
+
+        let mut rdr = Reader::from_bytes(&CAFE[..4])
+            .width(4)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+        assert_eq!(rdr.next_record_str().unwrap().unwrap(), "Café");
+
+        let mut wrtr = Writer::from_memory().transcode(Transcode::Ebcdic(CodePage::Cp037));
+        let euro = Payment {
+            name: "€".to_string(),
+            ..cafe()
+        };
+        match wrtr.write_serialized(std::iter::once(euro)) {
+            Err(Error::Record { source, .. }) => assert_eq!(
+                source.to_string(),
+                "field 0..4: char '€' cannot be written to the field"
+            ),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    enum Mixed {
+        Payment(Payment),
+        Note(&'static str),
+    }
+
+    impl FixedWidthRecord for Mixed {
+        fn write_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()> {
+            match *self {
+                Mixed::Payment(ref payment) => crate::to_writer(wrtr, payment),
+                Mixed::Note(note) => {
+                    crate::to_writer_with_fields(wrtr, &note, FieldSet::new_field(0..9))
+                }
+            }
+        }
+
+        fn write_latin1_to<W: io::Write>(&self, wrtr: &mut W) -> Result<()> {
+            match *self {
+                Mixed::Payment(ref payment) => crate::to_writer_latin1(wrtr, payment),
+                Mixed::Note(_) => self.write_to(wrtr),
+            }
+        }
+
+        fn record_width(&self) -> usize {
+            9
+        }
+
+        fn record_fields(&self) -> Option<FieldSet> {
+            match *self {
+                Mixed::Payment(_) => Some(Payment::fields()),
+                Mixed::Note(_) => None,
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_and_byte_records_keep_bytes_fields() {
+        let mut wrtr = Writer::from_memory()
+            .linebreak(LineBreak::Newline)
+            .transcode(Transcode::Ebcdic(CodePage::Cp037));
+        let records = vec![Mixed::Payment(cafe()), Mixed::Note("Hello")];
+        wrtr.write_mixed(records.into_iter()).unwrap();
+
+        let mut expected = CAFE.to_vec();
+        expected.push(b'\n');
+        expected.extend([0xC8, 0x85, 0x93, 0x93, 0x96, 0x40, 0x40, 0x40, 0x40]);
+        let written: Vec<u8> = wrtr.into();
+        assert_eq!(written, expected);
+
+        let mut wrtr = Writer::from_memory()
+            .transcode(Transcode::Ebcdic(CodePage::Cp037))
+            .keep_bytes_fields(&Payment::fields());
+        wrtr.write_iter([&b"Caf\xE9\x12\x34\x5CZ\xE9"[..]].iter())
+            .unwrap();
+        let written: Vec<u8> = wrtr.into();
+        assert_eq!(written, CAFE);
+    }
+
+    #[test]
+    fn apply_around_kept_ranges() {
+        let table: [u8; 256] = std::array::from_fn(|b| (b as u8).wrapping_add(1));
+        let mut buf = vec![0; 8];
+        apply(&table, &mut buf, &[1..3, 5..6, 7..20]);
+        assert_eq!(buf, [1, 0, 0, 1, 1, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,646 @@
+//! Checks records against rules for their fields, without deserializing them.
+
+use crate::{error::Error, FieldConfig, FieldSet, Reader, Result};
+use std::{collections::HashMap, fmt, io::Read, ops::Range, sync::Arc};
+
+#[cfg(feature = "regex")]
+pub use crate::regex::{Regex, RegexError};
+
+const DEFAULT_MAX_VIOLATIONS: usize = 1000;
+
+type CheckFn = dyn Fn(&[u8]) -> bool + Send + Sync;
+
+/// A check made on the bytes of a field by a `Validator`. Apart from `NotBlank`, rules pass
+/// blank fields, so add `NotBlank` as well for fields that are mandatory.
+#[derive(Clone)]
+pub enum Rule {
+    /// The field holds only digits, with an optional leading `+` or `-`, padded with spaces or
+    /// the field's padding.
+    Numeric,
+    /// The field is not made up of only spaces or the field's padding.
+    NotBlank,
+    /// The field, with surrounding whitespace trimmed, is one of the given values.
+    OneOf(&'static [&'static str]),
+    /// The field passes the given check, which is described by the given name in violations.
+    /// Useful for checks that the other rules do not cover.
+    Custom(String, Arc<CheckFn>),
+    /// The field, with its padding trimmed as when it is deserialized, matches the regex.
+    /// Fields that are not valid UTF-8 do not match.
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+}
+
+impl Rule {
+    /// Creates a rule that passes fields for which `check` returns true. The `description` is
+    /// used to describe the rule in violations.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::validate::Rule;
+    ///
+    /// let rule = Rule::custom("upper case", |bytes| bytes.iter().all(u8::is_ascii_uppercase));
+    /// ```
+    pub fn custom<T, F>(description: T, check: F) -> Self
+    where
+        T: Into<String>,
+        F: Fn(&[u8]) -> bool + Send + Sync + 'static,
+    {
+        Rule::Custom(description.into(), Arc::new(check))
+    }
+
+    /// Creates a `Rule::Regex` from the given pattern, or returns a `RegexError` if the pattern
+    /// is not valid.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::validate::Rule;
+    ///
+    /// let rule = Rule::regex(r"^[A-Z]{2}\d{4}$").unwrap();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> std::result::Result<Self, RegexError> {
+        Regex::new(pattern).map(Rule::Regex)
+    }
+
+    fn check(&self, conf: &FieldConfig, bytes: &[u8]) -> Option<ViolationKind> {
+        let blank = trim_pad(bytes, conf.pad_with).is_empty();
+        let passed = match self {
+            Rule::Numeric => blank || is_numeric(conf, bytes),
+            Rule::NotBlank => !blank,
+            Rule::OneOf(values) => blank || values.contains(&String::from_utf8_lossy(bytes).trim()),
+            Rule::Custom(_, check) => check(bytes),
+            #[cfg(feature = "regex")]
+            Rule::Regex(re) => {
+                blank || std::str::from_utf8(conf.trim(bytes)).is_ok_and(|s| re.is_match(s))
+            }
+        };
+
+        if passed {
+            return None;
+        }
+
+        Some(match self {
+            Rule::Numeric => ViolationKind::NotNumeric,
+            Rule::NotBlank => ViolationKind::Blank,
+            Rule::OneOf(values) => ViolationKind::NotAllowed(values),
+            Rule::Custom(description, _) => ViolationKind::Custom(description.clone()),
+            #[cfg(feature = "regex")]
+            Rule::Regex(re) => ViolationKind::NoMatch(re.as_str().to_string()),
+        })
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::Numeric => f.write_str("Numeric"),
+            Rule::NotBlank => f.write_str("NotBlank"),
+            Rule::OneOf(values) => f.debug_tuple("OneOf").field(values).finish(),
+            Rule::Custom(description, _) => f.debug_tuple("Custom").field(description).finish(),
+            #[cfg(feature = "regex")]
+            Rule::Regex(re) => f.debug_tuple("Regex").field(&re.as_str()).finish(),
+        }
+    }
+}
+
+fn is_numeric(conf: &FieldConfig, bytes: &[u8]) -> bool {
+    // Zero padding is part of the number, and may follow a sign.
+    let pad = if conf.pad_with.is_ascii_digit() {
+        ' '
+    } else {
+        conf.pad_with
+    };
+    let digits = trim_pad(bytes, pad);
+
+    let digits = match digits.split_first() {
+        Some((b'+', rest)) | Some((b'-', rest)) => rest,
+        _ => digits,
+    };
+    !digits.is_empty() && digits.iter().all(u8::is_ascii_digit)
+}
+
+/// Trims spaces and the given padding character, encoded as UTF-8, from both ends of the bytes.
+fn trim_pad(mut bytes: &[u8], pad: char) -> &[u8] {
+    let mut buf = [0; 4];
+    let pad = pad.encode_utf8(&mut buf).as_bytes();
+
+    while let Some(rest) = bytes.strip_prefix(pad).or_else(|| bytes.strip_prefix(b" ")) {
+        bytes = rest;
+    }
+    while let Some(rest) = bytes.strip_suffix(pad).or_else(|| bytes.strip_suffix(b" ")) {
+        bytes = rest;
+    }
+    bytes
+}
+
+/// The way a field or record broke the rules of a `Validator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// The record was not the expected width.
+    Width {
+        /// The width the record should be.
+        expected: usize,
+        /// The width of the record.
+        actual: usize,
+    },
+    /// The record ended before the field.
+    Missing,
+    /// The field broke `Rule::Numeric`.
+    NotNumeric,
+    /// The field broke `Rule::NotBlank`.
+    Blank,
+    /// The field broke `Rule::OneOf` with the given values.
+    NotAllowed(&'static [&'static str]),
+    /// The field broke the `Rule::Custom` with the given description.
+    Custom(String),
+    /// The field broke the `Rule::Regex` with the given pattern.
+    #[cfg(feature = "regex")]
+    NoMatch(String),
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViolationKind::Width { expected, actual } => {
+                write!(f, "record is {} bytes wide, expected {}", actual, expected)
+            }
+            ViolationKind::Missing => f.write_str("is cut short by the end of the record"),
+            ViolationKind::NotNumeric => f.write_str("is not numeric"),
+            ViolationKind::Blank => f.write_str("is blank"),
+            ViolationKind::NotAllowed(values) => {
+                write!(f, "is not one of {}", values.join(", "))
+            }
+            ViolationKind::Custom(description) => write!(f, "is not {}", description),
+            #[cfg(feature = "regex")]
+            ViolationKind::NoMatch(pattern) => write!(f, "does not match {}", pattern),
+        }
+    }
+}
+
+/// A field or record that broke the rules of a `Validator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The name of the field, or its range if it has no name. `None` for a violation of the
+    /// whole record, such as its width.
+    pub field: Option<String>,
+    /// The byte range of the field, or of the whole record.
+    pub range: Range<usize>,
+    /// The bytes found, which may be shorter than the range if the record ended early.
+    pub value: Vec<u8>,
+    /// How the rules were broken.
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.field {
+            Some(ref field) => write!(
+                f,
+                "field '{}' ({}..{}): value {:?} {}",
+                field,
+                self.range.start,
+                self.range.end,
+                String::from_utf8_lossy(&self.value),
+                self.kind
+            ),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+/// A violation found by `Validator::validate_reader`, along with the record it was found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordViolation {
+    /// The zero based index of the record in the data, counting skipped records.
+    pub index: u64,
+    /// The violation that was found.
+    pub violation: Violation,
+}
+
+impl fmt::Display for RecordViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.violation.field {
+            Some(_) => write!(f, "record {}, {}", self.index, self.violation),
+            None => write!(f, "record {}: {}", self.index, self.violation),
+        }
+    }
+}
+
+/// The results of validating every record of some data with `Validator::validate_reader`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The number of records validated.
+    pub records: u64,
+    /// The number of records with at least one violation.
+    pub invalid_records: u64,
+    /// The violations found, in the order they were found, up to the validator's
+    /// `max_violations`.
+    pub violations: Vec<RecordViolation>,
+    /// The number of violations found for each field, by the field's name or range, including
+    /// those beyond `max_violations`. Width violations are counted in `width_violations` instead.
+    pub field_violations: HashMap<String, u64>,
+    /// The number of records that were not the expected width.
+    pub width_violations: u64,
+    /// Whether more violations were found than were kept in `violations`.
+    pub truncated: bool,
+}
+
+impl ValidationReport {
+    /// Whether no violations were found.
+    pub fn is_valid(&self) -> bool {
+        self.invalid_records == 0
+    }
+}
+
+/// Checks records against a set of rules for their fields without deserializing them, finding
+/// every problem with a record rather than stopping at the first. This makes a cheap first pass
+/// over data before parsing it.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{validate::{Rule, Validator, ViolationKind}, FieldSet, Reader};
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("id"),
+///     FieldSet::new_field(4..6).name("code"),
+///     FieldSet::new_field(6..10).name("amount"),
+/// ]);
+///
+/// let validator = Validator::new(fields)
+///     .width(10)
+///     .rule("id", Rule::NotBlank)
+///     .rule("code", Rule::OneOf(&["AA", "BB"]))
+///     .rule("amount", Rule::Numeric);
+///
+/// let violations = validator.validate_record(b"    CC12x4");
+/// assert_eq!(violations.len(), 3);
+/// assert_eq!(violations[1].kind, ViolationKind::NotAllowed(&["AA", "BB"]));
+/// assert_eq!(violations[2].to_string(), r#"field 'amount' (6..10): value "12x4" is not numeric"#);
+///
+/// let mut reader = Reader::from_string("0001AA00420002CC  -7").width(10);
+/// let report = validator.validate_reader(&mut reader).unwrap();
+/// assert_eq!(report.records, 2);
+/// assert_eq!(report.invalid_records, 1);
+/// assert_eq!(report.field_violations["code"], 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Validator {
+    fields: Vec<FieldConfig>,
+    rules: Vec<(usize, Rule)>,
+    width: Option<usize>,
+    max_violations: usize,
+}
+
+impl Validator {
+    /// Creates a new validator for records with the given fields, with no rules.
+    pub fn new(fields: FieldSet) -> Self {
+        Self {
+            fields: fields.flatten(),
+            rules: Vec::new(),
+            width: None,
+            max_violations: DEFAULT_MAX_VIOLATIONS,
+        }
+    }
+
+    /// Adds a rule for the field with the given name. Rules are checked in the order they are
+    /// added.
+    ///
+    /// Panics if there is no field with the given name. Use `try_rule` to handle a missing
+    /// field instead.
+    pub fn rule(self, field: &str, rule: Rule) -> Self {
+        match self.try_rule(field, rule) {
+            Ok(validator) => validator,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Adds a rule for the field with the given name, or returns `Error::UnknownField` if there
+    /// is no field with the name.
+    pub fn try_rule(mut self, field: &str, rule: Rule) -> Result<Self> {
+        let index = self
+            .fields
+            .iter()
+            .position(|conf| conf.name.as_deref() == Some(field))
+            .ok_or_else(|| Error::UnknownField(field.to_string()))?;
+
+        self.rules.push((index, rule));
+        Ok(self)
+    }
+
+    /// Sets the width in bytes every record must be. By default the width is not checked.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the most violations `validate_reader` keeps in its report. Violations beyond this
+    /// are still counted. Defaults to 1000.
+    pub fn max_violations(mut self, max: usize) -> Self {
+        self.max_violations = max;
+        self
+    }
+
+    /// Checks a record against the validator's width and rules, returning every violation found.
+    pub fn validate_record(&self, record: &[u8]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        if let Some(expected) = self.width {
+            if record.len() != expected {
+                violations.push(Violation {
+                    field: None,
+                    range: 0..record.len(),
+                    value: record.to_vec(),
+                    kind: ViolationKind::Width {
+                        expected,
+                        actual: record.len(),
+                    },
+                });
+            }
+        }
+
+        for (index, rule) in &self.rules {
+            let conf = &self.fields[*index];
+            let (value, kind) = match conf.slice(record) {
+                Some(bytes) => match rule.check(conf, bytes) {
+                    Some(kind) => (bytes, kind),
+                    None => continue,
+                },
+                None => (
+                    record.get(conf.range.start..).unwrap_or_default(),
+                    ViolationKind::Missing,
+                ),
+            };
+
+            violations.push(Violation {
+                field: Some(conf.describe()),
+                range: conf.range.clone(),
+                value: value.to_vec(),
+                kind,
+            });
+        }
+
+        violations
+    }
+
+    /// Checks every record of the reader, collecting the violations found into a report. Errors
+    /// reading the data are returned rather than reported.
+    pub fn validate_reader<R: Read>(&self, reader: &mut Reader<R>) -> Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+
+        while let Some(record) = reader.next_record() {
+            let violations = self.validate_record(record?);
+            let index = reader.records_read() - 1;
+            report.records += 1;
+
+            if violations.is_empty() {
+                continue;
+            }
+
+            report.invalid_records += 1;
+            for violation in violations {
+                match violation.field {
+                    Some(ref field) => {
+                        *report.field_violations.entry(field.clone()).or_insert(0) += 1
+                    }
+                    None => report.width_violations += 1,
+                }
+
+                if report.violations.len() < self.max_violations {
+                    report.violations.push(RecordViolation { index, violation });
+                } else {
+                    report.truncated = true;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LineBreak;
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..4).name("id"),
+            FieldSet::new_field(4..6).name("code"),
+            FieldSet::new_field(6..10).name("amount").pad_with('0'),
+        ])
+    }
+
+    fn validator() -> Validator {
+        Validator::new(fields())
+            .width(10)
+            .rule("id", Rule::NotBlank)
+            .rule("id", Rule::Numeric)
+            .rule("code", Rule::OneOf(&["AA", "BB"]))
+            .rule("amount", Rule::Numeric)
+    }
+
+    #[test]
+    fn valid_record() {
+        let validator = validator();
+
+        assert!(validator.validate_record(b"0001AA0042").is_empty());
+        assert!(validator.validate_record(b"  12BB  -7").is_empty());
+        assert!(validator.validate_record(b"0001  +000").is_empty());
+        assert!(validator.validate_record(b"0001AA    ").is_empty());
+    }
+
+    #[test]
+    fn numeric() {
+        let conf = FieldConfig::new(0..6);
+        let numeric = |s: &str| Rule::Numeric.check(&conf, s.as_bytes()).is_none();
+
+        assert!(numeric("  0012"));
+        assert!(numeric("-12   "));
+        assert!(numeric("      "));
+        assert!(!numeric("1 2   "));
+        assert!(!numeric("12-   "));
+        assert!(!numeric("  -   "));
+        assert!(!numeric("1.5   "));
+        assert!(!numeric("+-1   "));
+
+        let conf = FieldConfig {
+            pad_with: '·',
+            ..FieldConfig::new(0..8)
+        };
+        let numeric = |s: &str| Rule::Numeric.check(&conf, s.as_bytes()).is_none();
+
+        assert!(numeric("··-12·"));
+        assert!(numeric(" ·12 "));
+        assert!(!numeric("·1·2"));
+        assert!(Rule::NotBlank.check(&conf, "·· ·".as_bytes()).is_some());
+    }
+
+    #[test]
+    fn record_violations() {
+        let violations = validator().validate_record(b"    CC1x");
+
+        assert_eq!(
+            violations,
+            vec![
+                Violation {
+                    field: None,
+                    range: 0..8,
+                    value: b"    CC1x".to_vec(),
+                    kind: ViolationKind::Width {
+                        expected: 10,
+                        actual: 8,
+                    },
+                },
+                Violation {
+                    field: Some("id".to_string()),
+                    range: 0..4,
+                    value: b"    ".to_vec(),
+                    kind: ViolationKind::Blank,
+                },
+                Violation {
+                    field: Some("code".to_string()),
+                    range: 4..6,
+                    value: b"CC".to_vec(),
+                    kind: ViolationKind::NotAllowed(&["AA", "BB"]),
+                },
+                Violation {
+                    field: Some("amount".to_string()),
+                    range: 6..10,
+                    value: b"1x".to_vec(),
+                    kind: ViolationKind::Missing,
+                },
+            ]
+        );
+
+        let messages: Vec<String> = violations.iter().map(Violation::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "record is 8 bytes wide, expected 10",
+                r#"field 'id' (0..4): value "    " is blank"#,
+                r#"field 'code' (4..6): value "CC" is not one of AA, BB"#,
+                r#"field 'amount' (6..10): value "1x" is cut short by the end of the record"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_rule() {
+        let validator = Validator::new(fields()).rule(
+            "code",
+            Rule::custom("upper case", |bytes| {
+                bytes.iter().all(u8::is_ascii_uppercase)
+            }),
+        );
+
+        assert!(validator.validate_record(b"0001AB0000").is_empty());
+
+        let violations = validator.validate_record(b"0001Ab0000");
+        assert_eq!(
+            violations[0].kind,
+            ViolationKind::Custom("upper case".to_string())
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            r#"field 'code' (4..6): value "Ab" is not upper case"#
+        );
+        assert_eq!(
+            format!("{:?}", Rule::custom("upper case", |_| true)),
+            r#"Custom("upper case")"#
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_rule() {
+        let validator = Validator::new(fields())
+            .rule("id", Rule::regex(r"^\d+$").unwrap())
+            .rule("code", Rule::regex("^(AA|B+)$").unwrap());
+
+        assert!(validator.validate_record(b"  12BB0000").is_empty());
+        assert!(validator.validate_record(b"0001  0000").is_empty());
+
+        let violations = validator.validate_record(b"01x1AB0000");
+        assert_eq!(violations.len(), 2);
+        assert_eq!(
+            violations[0].kind,
+            ViolationKind::NoMatch(r"^\d+$".to_string())
+        );
+        assert_eq!(
+            violations[1].to_string(),
+            r#"field 'code' (4..6): value "AB" does not match ^(AA|B+)$"#
+        );
+        assert_eq!(
+            format!("{:?}", Rule::regex("a+").unwrap()),
+            r#"Regex("a+")"#
+        );
+    }
+
+    #[test]
+    fn unknown_field() {
+        let err = Validator::new(fields())
+            .try_rule("name", Rule::NotBlank)
+            .unwrap_err();
+
+        assert!(matches!(err, Error::UnknownField(ref name) if name == "name"));
+        assert_eq!(err.to_string(), "no field named 'name'");
+    }
+
+    #[test]
+    #[should_panic(expected = "no field named")]
+    fn unknown_field_panics() {
+        Validator::new(fields()).rule("name", Rule::NotBlank);
+    }
+
+    #[test]
+    fn validate_reader() {
+        let data = "0001AA0042\n0002CCxx42\n0003BB0042\n    DD0042\n0005AA00-1";
+        let mut reader = Reader::from_string(data)
+            .width(10)
+            .linebreak(LineBreak::Newline);
+        let report = validator()
+            .max_violations(3)
+            .validate_reader(&mut reader)
+            .unwrap();
+
+        assert_eq!(report.records, 5);
+        assert_eq!(report.invalid_records, 3);
+        assert!(!report.is_valid());
+        assert!(report.truncated);
+        assert_eq!(report.width_violations, 0);
+        assert_eq!(report.field_violations["code"], 2);
+        assert_eq!(report.field_violations["amount"], 2);
+        assert_eq!(report.field_violations["id"], 1);
+
+        let found: Vec<String> = report.violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            found,
+            vec![
+                r#"record 1, field 'code' (4..6): value "CC" is not one of AA, BB"#,
+                r#"record 1, field 'amount' (6..10): value "xx42" is not numeric"#,
+                r#"record 3, field 'id' (0..4): value "    " is blank"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_reader_widths() {
+        let mut reader = Reader::from_string("0001AA00420002BB0042").width(10);
+        let report = Validator::new(fields())
+            .width(12)
+            .validate_reader(&mut reader)
+            .unwrap();
+
+        assert_eq!(report.records, 2);
+        assert_eq!(report.width_violations, 2);
+        assert!(report.field_violations.is_empty());
+        assert_eq!(
+            report.violations[1].to_string(),
+            "record 1: record is 10 bytes wide, expected 12"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    ser::{Serialize, Serializer},
+};
+use std::{convert::TryFrom, fmt};
+
+/// A field value of any type, for reading records without defining a type for them, such as
+/// into a `HashMap<String, Value>`. As fixed width data is not self describing, the type of each
+/// field is guessed from its data: blank fields are `Null`, whole numbers are `Int`, other
+/// numbers are `Float` and anything else is `Str`, except for raw bytes fields which are `Bytes`.
+/// Numbers are written back without any formatting they were read with, such as leading zeros.
+/// Fields are never read as `Bool` or `Char`, which are only there to be written, or read from
+/// self describing formats.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{FieldSet, Value};
+/// use std::collections::HashMap;
+///
+/// let fields = FieldSet::Seq(vec![
+///     FieldSet::new_field(0..4).name("name"),
+///     FieldSet::new_field(4..8).name("age"),
+///     FieldSet::new_field(8..12).name("score"),
+///     FieldSet::new_field(12..16).name("notes"),
+/// ]);
+///
+/// let record: HashMap<String, Value> =
+///     fixed_width::from_str_with_fields("Carl  421.5     ", fields).unwrap();
+///
+/// assert_eq!(record["name"], Value::Str("Carl".to_string()));
+/// assert_eq!(record["age"], Value::Int(42));
+/// assert_eq!(record["score"], Value::Float(1.5));
+/// assert_eq!(record["notes"], Value::Null);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A boolean. Never read from fixed width data, where `Y` or `1` is read as `Str` or `Int`.
+    Bool(bool),
+    /// A whole number.
+    Int(i64),
+    /// A number with a fractional part, or a whole number too large for an `i64`.
+    Float(f64),
+    /// A single character. Never read from fixed width data, where a single character is read
+    /// as `Str`.
+    Char(char),
+    /// Text.
+    Str(String),
+    /// Raw bytes.
+    Bytes(Vec<u8>),
+    /// A blank field.
+    Null,
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(i) => serializer.serialize_i64(*i),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::Char(c) => serializer.serialize_char(*c),
+            Value::Str(s) => serializer.serialize_str(s),
+            Value::Bytes(b) => serializer.serialize_bytes(b),
+            Value::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a fixed width field value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(i64::try_from(v).map_or(Value::Float(v as f64), Value::Int))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::Str(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::Str(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{from_bytes_with_fields, to_writer_with_fields, FieldSet, Writer};
+    use std::collections::HashMap;
+
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![
+            FieldSet::new_field(0..6).name("id"),
+            FieldSet::new_field(6..14).name("name"),
+            FieldSet::new_field(14..21).name("balance"),
+            FieldSet::new_field(21..25).name("region"),
+            FieldSet::new_field(25..26).name("active"),
+            FieldSet::new_field(26..28).name("checksum").bytes(true),
+        ])
+    }
+
+    #[test]
+    fn value_map() {
+        let input = b"-00042Jo Smith-12.50     Y\x01\xff";
+        let record: HashMap<String, Value> = from_bytes_with_fields(input, fields()).unwrap();
+
+        assert_eq!(record["id"], Value::Int(-42));
+        assert_eq!(record["name"], Value::Str("Jo Smith".to_string()));
+        assert_eq!(record["balance"], Value::Float(-12.5));
+        assert_eq!(record["region"], Value::Null);
+        assert_eq!(record["active"], Value::Str("Y".to_string()));
+        assert_eq!(record["checksum"], Value::Bytes(vec![0x01, 0xff]));
+    }
+
+    #[test]
+    fn value_guessing() {
+        let fields = FieldSet::Seq(vec![
+            FieldSet::new_field(0..20),
+            FieldSet::new_field(20..24),
+            FieldSet::new_field(24..27),
+            FieldSet::new_field(27..30),
+        ]);
+        let input = format!("{:<20}1e3 inf12a", u64::MAX);
+        let values: Vec<Value> = from_bytes_with_fields(input.as_bytes(), fields).unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                Value::Float(u64::MAX as f64),
+                Value::Float(1000.0),
+                Value::Str("inf".to_string()),
+                Value::Str("12a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn value_round_trip() {
+        let input = b"42    Jo Smith12.5   EU  1\x01\xff";
+        let record: Vec<Value> = from_bytes_with_fields(input, fields()).unwrap();
+
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &record, fields()).unwrap();
+        let bytes: Vec<u8> = w.into();
+        assert_eq!(bytes, input);
+
+        let typed = vec![
+            Value::Int(7),
+            Value::Str("Al".to_string()),
+            Value::Float(0.25),
+            Value::Null,
+            Value::Bool(true),
+            Value::Bytes(b"ab".to_vec()),
+        ];
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &typed, fields()).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "7     Al      0.25       1ab");
+
+        let fields = FieldSet::new_field(0..1);
+        let mut w = Writer::from_memory();
+        to_writer_with_fields(&mut w, &Value::Char('x'), fields).unwrap();
+        let s: String = w.into();
+        assert_eq!(s, "x");
+    }
+}
@@ -1,12 +1,24 @@
-use crate::{ser, FixedWidth, LineBreak, Result};
+use crate::{ser, ser::Serializer, Error, FixedWidth, FixedWidthRecord, LineBreak, Result};
+#[cfg(feature = "ebcdic")]
+use crate::{
+    transcode::{bytes_ranges, Transcode, Transcoder},
+    FieldConfig, FieldSet,
+};
 use serde::ser::Serialize;
 use std::{
     borrow::Cow,
-    io::{self, Write},
+    fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 const BUFFER_SIZE: usize = 65_536;
 
+type ProgressFn = Box<dyn FnMut(u64, u64) + Send>;
+
 /// A trait to ease converting byte like data into a byte slice. This allows handling these types
 /// with one generic function.
 pub trait AsByteSlice {
@@ -56,12 +68,25 @@ where
     }
 }
 
-impl<'a, T: ?Sized + AsByteSlice> AsByteSlice for &'a T {
+impl<T: ?Sized + AsByteSlice> AsByteSlice for &T {
     fn as_byte_slice(&self) -> &[u8] {
         (*self).as_byte_slice()
     }
 }
 
+/// Counts of the data written by a `Writer`, for example to fill in a trailer record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriterStats {
+    /// The number of records written successfully by `write_iter`, `write_serialized` or
+    /// `write_mixed`.
+    pub records: u64,
+    /// The number of bytes in the records counted by `records`, not counting linebreaks.
+    pub data_bytes: u64,
+    /// The number of bytes written in total, including linebreaks, trailers, bytes written
+    /// directly through `io::Write` and any partial record written before an error.
+    pub total_bytes: u64,
+}
+
 /// A fixed width data writer. It writes data provided in iterators to any type that implements
 /// io::Write.
 ///
@@ -83,9 +108,20 @@ impl<'a, T: ?Sized + AsByteSlice> AsByteSlice for &'a T {
 /// wrtr.write_iter(data.iter());
 /// wrtr.flush();
 /// ```
+///
+/// Any buffered data is flushed when the writer is dropped, but errors doing so are ignored. Use
+/// `close` or `into_inner` to find out whether the data was written.
 pub struct Writer<W: Write> {
-    wrtr: io::BufWriter<W>,
+    /// Only `None` once the writer has been taken apart by `into_inner`.
+    wrtr: Option<io::BufWriter<W>>,
     linebreak: LineBreak,
+    /// The last bytes of the file being appended to, until the first write.
+    append_tail: Option<Vec<u8>>,
+    linebreak_before_append: bool,
+    stats: WriterStats,
+    on_record: Option<ProgressFn>,
+    #[cfg(feature = "ebcdic")]
+    transcoder: Option<Box<Transcoder>>,
 }
 
 impl<W> Writer<W>
@@ -97,35 +133,352 @@ where
         Self::from_buffer(io::BufWriter::with_capacity(BUFFER_SIZE, wrtr))
     }
 
+    /// Creates a new writer from any type that implements io::Write, buffering up to `capacity`
+    /// bytes before writing to it. The default capacity is 64 KiB.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Writer;
+    ///
+    /// let wrtr = Writer::with_capacity(512, Vec::new());
+    ///
+    /// assert_eq!(wrtr.buffer_capacity(), 512);
+    /// ```
+    pub fn with_capacity(capacity: usize, wrtr: W) -> Self {
+        Self::from_buffer(io::BufWriter::with_capacity(capacity, wrtr))
+    }
+
+    /// Flushes any buffered data and returns the underlying writer. Unlike dropping the writer,
+    /// an error flushing is returned rather than ignored.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Writer;
+    ///
+    /// let mut wrtr = Writer::from_writer(Vec::new());
+    /// wrtr.write_iter(["1111", "2222"].iter()).unwrap();
+    ///
+    /// assert_eq!(wrtr.into_inner().unwrap(), b"11112222");
+    /// ```
+    pub fn into_inner(mut self) -> Result<W> {
+        let wrtr = self
+            .wrtr
+            .take()
+            .expect("writer is only taken by into_inner");
+        Ok(wrtr.into_inner().map_err(|e| e.into_error())?)
+    }
+
+    /// Flushes any buffered data and closes the writer, returning any error that occurs. Dropping
+    /// the writer also flushes, but ignores errors.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::Writer;
+    ///
+    /// let mut wrtr = Writer::from_memory();
+    /// wrtr.write_iter(["1111", "2222"].iter()).unwrap();
+    /// wrtr.close().unwrap();
+    /// ```
+    pub fn close(self) -> Result<()> {
+        self.into_inner().map(|_| ())
+    }
+
+    fn buffer(&self) -> &io::BufWriter<W> {
+        self.wrtr
+            .as_ref()
+            .expect("writer is only taken by into_inner")
+    }
+
+    fn buffer_mut(&mut self) -> &mut io::BufWriter<W> {
+        self.wrtr
+            .as_mut()
+            .expect("writer is only taken by into_inner")
+    }
+
     /// Creates a new writer from a io::BufWriter that wraps a type that implements io::Write
     pub fn from_buffer(buf: io::BufWriter<W>) -> Self {
         Self {
-            wrtr: buf,
+            wrtr: Some(buf),
             linebreak: LineBreak::None,
+            append_tail: None,
+            linebreak_before_append: true,
+            stats: WriterStats::default(),
+            on_record: None,
+            #[cfg(feature = "ebcdic")]
+            transcoder: None,
         }
     }
 
+    /// The capacity in bytes of the writer's internal buffer.
+    pub fn buffer_capacity(&self) -> usize {
+        self.buffer().capacity()
+    }
+
     /// Writes the given iterator of `FixedWidth + Serialize` types to the underlying writer,
-    /// optionally inserting linebreaks if specified.
+    /// optionally inserting linebreaks if specified. An error serializing a record is returned as
+    /// an `Error::Record` with the record's index.
     pub fn write_serialized<T: FixedWidth + Serialize>(
         &mut self,
         records: impl Iterator<Item = T>,
     ) -> Result<()> {
-        let mut first_record = true;
+        let flattened;
+        let fields = match T::flattened_fields() {
+            Some(fields) => fields,
+            None => {
+                flattened = T::fields().flatten();
+                &flattened
+            }
+        };
+        #[cfg(feature = "ebcdic")]
+        if self.transcoder.is_some() {
+            for (index, record) in records.enumerate() {
+                if index > 0 {
+                    self.write_linebreak()?;
+                }
 
-        for record in records {
-            if !first_record {
+                let start = self.record_start()?;
+                self.serialize_transcoded(&record, fields)
+                    .map_err(|e| Error::Record {
+                        index: index as u64,
+                        source: Box::new(e),
+                    })?;
+                self.record_written(start);
+            }
+            return Ok(());
+        }
+
+        let mut ser = Serializer::with_flattened_ref(self, fields);
+
+        for (index, record) in records.enumerate() {
+            if index > 0 {
+                ser.get_mut().write_linebreak()?;
+            }
+
+            let start = ser.get_mut().record_start()?;
+            ser.reset();
+            record
+                .serialize(&mut ser)
+                .and_then(|_| ser.write_literals())
+                .map_err(|e| Error::Record {
+                    index: index as u64,
+                    source: Box::new(e),
+                })?;
+
+            ser.get_mut().record_written(start);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the given iterator of records of several record types, such as an enum derived
+    /// with `FixedWidthEnum`, optionally inserting linebreaks if specified. Each record is
+    /// serialized with the fields of its own record type. An error serializing a record, or a
+    /// record shorter than the width of its record type, is returned as an `Error::Record` with
+    /// the record's index, and nothing of that record is written.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Writer};
+    /// use fixed_width_derive::{FixedWidth, FixedWidthEnum};
+    /// use serde_derive::{Deserialize, Serialize};
+    ///
+    /// #[derive(FixedWidth, Serialize, Deserialize)]
+    /// struct Header {
+    ///     #[fixed_width(range = "0..1")]
+    ///     kind: String,
+    ///     #[fixed_width(range = "1..9")]
+    ///     date: String,
+    /// }
+    ///
+    /// #[derive(FixedWidth, Serialize, Deserialize)]
+    /// struct Detail {
+    ///     #[fixed_width(range = "0..1")]
+    ///     kind: String,
+    ///     #[fixed_width(range = "1..5", pad_with = "0", justify = "right")]
+    ///     amount: usize,
+    /// }
+    ///
+    /// #[derive(FixedWidthEnum)]
+    /// #[fixed_width(tag_range = "0..1")]
+    /// enum Record {
+    ///     #[fixed_width(tag = "H")]
+    ///     Header(Header),
+    ///     #[fixed_width(tag = "D")]
+    ///     Detail(Detail),
+    /// }
+    ///
+    /// let detail = |amount| Record::Detail(Detail { kind: "D".to_string(), amount });
+    /// let records = vec![
+    ///     Record::Header(Header { kind: "H".to_string(), date: "20240101".to_string() }),
+    ///     detail(12),
+    ///     detail(345),
+    /// ];
+    ///
+    /// let mut wrtr = Writer::from_memory().linebreak(LineBreak::Newline);
+    /// wrtr.write_mixed(records.into_iter()).unwrap();
+    ///
+    /// assert_eq!(String::from(wrtr), "H20240101\nD0012\nD0345");
+    /// ```
+    pub fn write_mixed<E: FixedWidthRecord>(
+        &mut self,
+        records: impl Iterator<Item = E>,
+    ) -> Result<()> {
+        let mut buf = Vec::new();
+
+        for (index, record) in records.enumerate() {
+            buf.clear();
+            self.write_mixed_record(&mut buf, &record)
+                .and_then(|_| match record.record_width() {
+                    required if buf.len() < required => Err(Error::InvalidWidth {
+                        width: buf.len(),
+                        required,
+                    }),
+                    _ => Ok(()),
+                })
+                .map_err(|e| Error::Record {
+                    index: index as u64,
+                    source: Box::new(e),
+                })?;
+
+            if index > 0 {
                 self.write_linebreak()?;
-            } else {
-                first_record = false;
             }
 
-            ser::to_writer(self, &record)?;
+            self.write_all(&buf)?;
+            self.stats.records += 1;
+            self.stats.data_bytes += buf.len() as u64;
+            self.report_progress();
         }
 
         Ok(())
     }
 
+    /// Serializes a record for `write_mixed`, converting it if the writer transcodes. With the
+    /// fields of its record type, its `bytes` fields are left as they are.
+    fn write_mixed_record<E: FixedWidthRecord>(&self, buf: &mut Vec<u8>, record: &E) -> Result<()> {
+        #[cfg(feature = "ebcdic")]
+        if let Some(ref transcoder) = self.transcoder {
+            record.write_latin1_to(buf)?;
+            let ranges = match record.record_fields() {
+                Some(fields) => bytes_ranges(fields.iter_flat()),
+                None => Vec::new(),
+            };
+            transcoder.encode(buf, &ranges);
+            return Ok(());
+        }
+
+        record.write_to(buf)
+    }
+
+    /// Serializes a single record, preceded by a linebreak if `linebreak` is set, and counts it in
+    /// the writer's stats. The record is serialized before anything is written, so nothing is
+    /// written if it fails.
+    pub(crate) fn write_counted<T: FixedWidth + Serialize>(
+        &mut self,
+        record: &T,
+        linebreak: bool,
+    ) -> Result<()> {
+        let mut buf = Vec::new();
+        self.serialize_to(&mut buf, record)?;
+
+        if linebreak {
+            self.write_linebreak()?;
+        }
+        self.write_all(&buf)?;
+        self.stats.records += 1;
+        self.stats.data_bytes += buf.len() as u64;
+        self.report_progress();
+        Ok(())
+    }
+
+    /// Serializes a single record, converting it if the writer transcodes.
+    fn serialize<T: FixedWidth + Serialize>(&mut self, record: &T) -> Result<()> {
+        #[cfg(feature = "ebcdic")]
+        if self.transcoder.is_some() {
+            let mut buf = Vec::new();
+            self.serialize_to(&mut buf, record)?;
+            return Ok(self.write_all(&buf)?);
+        }
+
+        ser::to_writer(self, record)
+    }
+
+    /// Serializes a single record to `buf`, converting it if the writer transcodes.
+    fn serialize_to<T: FixedWidth + Serialize>(&self, buf: &mut Vec<u8>, record: &T) -> Result<()> {
+        #[cfg(feature = "ebcdic")]
+        if self.transcoder.is_some() {
+            return match T::flattened_fields() {
+                Some(fields) => self.transcode_to(buf, record, fields),
+                None => self.transcode_to(buf, record, &T::fields().flatten()),
+            };
+        }
+
+        ser::to_writer(buf, record)
+    }
+
+    /// Serializes a record with the given fields and converts it before writing it, leaving the
+    /// fields set as `bytes` as they are.
+    #[cfg(feature = "ebcdic")]
+    fn serialize_transcoded<T: Serialize>(
+        &mut self,
+        record: &T,
+        fields: &[FieldConfig],
+    ) -> Result<()> {
+        let mut buf = Vec::new();
+        self.transcode_to(&mut buf, record, fields)?;
+        Ok(self.write_all(&buf)?)
+    }
+
+    /// Serializes a record with the given fields to `buf` as ISO-8859-1 text, then converts it
+    /// with the writer's transcoder, leaving the fields set as `bytes` as they are.
+    #[cfg(feature = "ebcdic")]
+    fn transcode_to<T: Serialize>(
+        &self,
+        buf: &mut Vec<u8>,
+        record: &T,
+        fields: &[FieldConfig],
+    ) -> Result<()> {
+        let mut ser = Serializer::with_flattened_ref(&mut *buf, fields).latin1(true);
+        record.serialize(&mut ser)?;
+        ser.write_literals()?;
+
+        if let Some(ref transcoder) = self.transcoder {
+            transcoder.encode(buf, &bytes_ranges(fields.iter()));
+        }
+        Ok(())
+    }
+
+    /// Where the next record starts in the output, after the linebreak that separates it from
+    /// the data of a file being appended to, which is not part of the record.
+    fn record_start(&mut self) -> Result<u64> {
+        self.write_append_linebreak()?;
+        Ok(self.stats.total_bytes)
+    }
+
+    /// Writes a linebreak before anything else when appending to data that does not end with
+    /// one, unless `linebreak_before_append` is turned off.
+    fn write_append_linebreak(&mut self) -> io::Result<()> {
+        if let Some(tail) = self.append_tail.take() {
+            let linebreak = self.linebreak.as_bytes();
+            if self.linebreak_before_append && !tail.is_empty() && !tail.ends_with(linebreak) {
+                self.buffer_mut().write_all(linebreak)?;
+                self.stats.total_bytes += linebreak.len() as u64;
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts a record that was written starting at `start` bytes into the output.
+    fn record_written(&mut self, start: u64) {
+        self.stats.records += 1;
+        self.stats.data_bytes += self.stats.total_bytes - start;
+        self.report_progress();
+    }
+
     /// Writes the given iterator of types that implement AsByteSlice to the underlying writer,
     /// optionally inserting linebreaks if specified.
     pub fn write_iter<T: AsByteSlice>(&mut self, records: impl Iterator<Item = T>) -> Result<()> {
@@ -138,12 +491,118 @@ where
                 first_record = false;
             }
 
-            self.write_all(record.as_byte_slice())?;
+            let bytes = record.as_byte_slice();
+            self.write_record_bytes(bytes)?;
+            self.stats.records += 1;
+            self.stats.data_bytes += bytes.len() as u64;
+            self.report_progress();
         }
 
         Ok(())
     }
 
+    /// Writes the bytes of a record, converting them if the writer transcodes.
+    fn write_record_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        #[cfg(feature = "ebcdic")]
+        if let Some(ref transcoder) = self.transcoder {
+            let mut buf = bytes.to_vec();
+            transcoder.encode_kept(&mut buf);
+            return Ok(self.write_all(&buf)?);
+        }
+
+        Ok(self.write_all(bytes)?)
+    }
+
+    /// The number of records and bytes written so far.
+    pub fn stats(&self) -> WriterStats {
+        self.stats
+    }
+
+    /// Calls `f` with the `records` and `total_bytes` of the writer's stats after each record is
+    /// written by `write_iter`, `write_serialized` or `write_mixed`, for example to drive a
+    /// progress bar. The byte count includes the linebreaks before each record.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Writer};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let progress = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = Arc::clone(&progress);
+    ///
+    /// let mut wrtr = Writer::from_memory()
+    ///     .linebreak(LineBreak::CRLF)
+    ///     .on_record(move |records, bytes| seen.lock().unwrap().push((records, bytes)));
+    /// wrtr.write_iter(["1111", "2222"].iter()).unwrap();
+    ///
+    /// assert_eq!(*progress.lock().unwrap(), vec![(1, 4), (2, 10)]);
+    /// ```
+    pub fn on_record<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        self.on_record = Some(Box::new(f));
+        self
+    }
+
+    #[inline]
+    fn report_progress(&mut self) {
+        if let Some(ref mut on_record) = self.on_record {
+            on_record(self.stats.records, self.stats.total_bytes);
+        }
+    }
+
+    /// Serializes a trailer record built from the writer's stats, preceded by a linebreak if
+    /// anything has been written. The trailer is not counted in `records` or `data_bytes`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{FieldSet, FixedWidth, LineBreak, Writer};
+    /// use serde_derive::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Trailer {
+    ///     tag: &'static str,
+    ///     records: u64,
+    ///     bytes: u64,
+    /// }
+    ///
+    /// impl FixedWidth for Trailer {
+    ///     fn fields() -> FieldSet {
+    ///         FieldSet::Seq(vec![
+    ///             FieldSet::new_field(0..1),
+    ///             FieldSet::new_field(1..4).pad_with('0').justify("right"),
+    ///             FieldSet::new_field(4..8).pad_with('0').justify("right"),
+    ///         ])
+    ///     }
+    /// }
+    ///
+    /// let mut wrtr = Writer::from_memory().linebreak(LineBreak::Newline);
+    /// wrtr.write_iter(["1111", "2222"].iter()).unwrap();
+    /// wrtr.write_trailer(|stats| Trailer {
+    ///     tag: "T",
+    ///     records: stats.records,
+    ///     bytes: stats.data_bytes,
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(String::from(wrtr), "1111\n2222\nT0020008");
+    /// ```
+    pub fn write_trailer<T, F>(&mut self, trailer: F) -> Result<()>
+    where
+        T: FixedWidth + Serialize,
+        F: FnOnce(&WriterStats) -> T,
+    {
+        let trailer = trailer(&self.stats);
+        if self.stats.total_bytes > 0 {
+            self.write_linebreak()?;
+        }
+
+        self.serialize(&trailer)
+    }
+
     /// Writes the linebreak specified to the underlying writer. Does nothing if there is no
     /// linebreak.
     #[inline]
@@ -161,11 +620,243 @@ where
         Ok(())
     }
 
+    /// Converts each record before it is written, such as from ASCII to EBCDIC. Linebreaks are
+    /// written as they are. The text of serialized records is written as ISO-8859-1, one byte for
+    /// each char, before it is converted, so characters outside ASCII such as `é` take a single
+    /// byte. Fields set as `bytes`, such as packed decimals, are left as they are in serialized
+    /// records, in records written by `write_mixed` whose type gives its fields with
+    /// `FixedWidthRecord::record_fields`, and in records written as bytes by `write_iter` if
+    /// given to `keep_bytes_fields`. Records written as bytes are expected to be ISO-8859-1.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{CodePage, Transcode, Writer};
+    ///
+    /// let mut wrtr = Writer::from_memory().transcode(Transcode::Ebcdic(CodePage::Cp037));
+    /// wrtr.write_iter(["Hello", "!123 "].iter()).unwrap();
+    ///
+    /// let bytes: Vec<u8> = wrtr.into();
+    /// assert_eq!(bytes, [0xC8, 0x85, 0x93, 0x93, 0x96, 0x5A, 0xF1, 0xF2, 0xF3, 0x40]);
+    /// ```
+    #[cfg(feature = "ebcdic")]
+    pub fn transcode(mut self, transcode: Transcode) -> Self {
+        self.transcoder = Some(Box::new(Transcoder::new(transcode)));
+        self
+    }
+
+    /// Leaves the fields among `fields` that are set as `bytes` as they are when transcoding
+    /// records written as bytes by `write_iter`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{CodePage, FieldSet, Transcode, Writer};
+    ///
+    /// let fields = FieldSet::Seq(vec![
+    ///     FieldSet::new_field(0..2),
+    ///     FieldSet::new_field(2..4).bytes(true),
+    /// ]);
+    /// let mut wrtr = Writer::from_memory()
+    ///     .transcode(Transcode::Ebcdic(CodePage::Cp037))
+    ///     .keep_bytes_fields(&fields);
+    /// wrtr.write_iter([&b"OK\x12\x3C"[..]].iter()).unwrap();
+    ///
+    /// let bytes: Vec<u8> = wrtr.into();
+    /// assert_eq!(bytes, [0xD6, 0xD2, 0x12, 0x3C]);
+    /// ```
+    #[cfg(feature = "ebcdic")]
+    pub fn keep_bytes_fields(mut self, fields: &FieldSet) -> Self {
+        if let Some(ref mut transcoder) = self.transcoder {
+            transcoder.keep_bytes_fields(fields);
+        }
+        self
+    }
+
     /// Sets the linebreak desired for this data. Defaults to `LineBreak::None`.
     pub fn linebreak(mut self, linebreak: LineBreak) -> Self {
         self.linebreak = linebreak;
         self
     }
+
+    /// Sets whether a writer from `append_to_path` writes a linebreak before the first new
+    /// record when the existing file does not already end with one, so the new records do not
+    /// run on from the last record of the file. Defaults to `true`. Has no effect on other
+    /// writers, or when the linebreak is `LineBreak::None`.
+    pub fn linebreak_before_append(mut self, val: bool) -> Self {
+        self.linebreak_before_append = val;
+        self
+    }
+}
+
+impl Writer<fs::File> {
+    /// Creates a new writer to the file at the given path, creating the file if it does not
+    /// exist and truncating it if it does. Will return an io::Error if there are any issues
+    /// opening the file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_writer(fs::File::create(path)?))
+    }
+
+    /// Creates a new writer that appends to the file at the given path, creating the file if it
+    /// does not exist. Will return an io::Error if there are any issues opening the file.
+    ///
+    /// If the file does not end with the configured linebreak, one is written before the first
+    /// new record, unless turned off with `linebreak_before_append`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use fixed_width::{LineBreak, Writer};
+    /// use std::{fs, io::Write};
+    ///
+    /// let path = std::env::temp_dir().join("fixed_width_append_doc.txt");
+    /// fs::write(&path, "1111\n2222").unwrap();
+    ///
+    /// let mut wrtr = Writer::append_to_path(&path).unwrap().linebreak(LineBreak::Newline);
+    /// wrtr.write_iter(["3333", "4444"].iter()).unwrap();
+    /// wrtr.flush().unwrap();
+    ///
+    /// assert_eq!(fs::read_to_string(&path).unwrap(), "1111\n2222\n3333\n4444");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn append_to_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        // A linebreak is at most 2 bytes, so only the last 2 bytes of the file are needed.
+        let len = file.seek(SeekFrom::End(0))?;
+        let mut tail = Vec::with_capacity(2);
+        file.seek(SeekFrom::Start(len.saturating_sub(2)))?;
+        file.read_to_end(&mut tail)?;
+
+        let mut wrtr = Self::from_writer(file);
+        wrtr.append_tail = Some(tail);
+        Ok(wrtr)
+    }
+
+    /// Creates a new writer that writes to a temporary file in the same directory as the given
+    /// path, which only replaces the file at the path once the `AtomicWriter` is committed. See
+    /// `AtomicWriter`.
+    pub fn atomic_path<P: AsRef<Path>>(path: P) -> Result<AtomicWriter> {
+        AtomicWriter::new(path.as_ref())
+    }
+}
+
+/// A writer to a temporary file that is renamed over its target path on `commit`, so that the
+/// target is never seen half written. If the `AtomicWriter` is dropped without being committed,
+/// for instance after an error, the temporary file is removed and the target is left untouched.
+///
+/// Derefs to a `Writer`, so records are written with `write_serialized`, `write_iter` and the
+/// other methods of `Writer`.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{LineBreak, Writer};
+/// use std::fs;
+///
+/// let path = std::env::temp_dir().join("fixed_width_atomic_doc.txt");
+///
+/// let mut wrtr = Writer::atomic_path(&path).unwrap().linebreak(LineBreak::Newline);
+/// wrtr.write_iter(["1111", "2222"].iter()).unwrap();
+/// assert!(!path.exists());
+///
+/// wrtr.commit().unwrap();
+/// assert_eq!(fs::read_to_string(&path).unwrap(), "1111\n2222");
+/// # fs::remove_file(&path).unwrap();
+/// ```
+pub struct AtomicWriter {
+    wrtr: Option<Writer<fs::File>>,
+    temp_path: PathBuf,
+    path: PathBuf,
+}
+
+impl AtomicWriter {
+    fn new(path: &Path) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp_path = path.with_file_name(temp_name);
+
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+
+        Ok(Self {
+            wrtr: Some(Writer::from_writer(file)),
+            temp_path,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Sets the linebreak desired for this data. Defaults to `LineBreak::None`.
+    pub fn linebreak(mut self, linebreak: LineBreak) -> Self {
+        self.wrtr = self.wrtr.take().map(|w| w.linebreak(linebreak));
+        self
+    }
+
+    /// The path of the temporary file being written to.
+    pub fn temp_path(&self) -> &Path {
+        &self.temp_path
+    }
+
+    /// Flushes the written data, syncs it to disk and renames the temporary file over the target
+    /// path. On error the temporary file is removed.
+    pub fn commit(mut self) -> Result<()> {
+        if let Some(wrtr) = self.wrtr.take() {
+            let file = wrtr.into_inner()?;
+            file.sync_all()?;
+        }
+
+        fs::rename(&self.temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Deref for AtomicWriter {
+    type Target = Writer<fs::File>;
+
+    fn deref(&self) -> &Self::Target {
+        self.wrtr.as_ref().expect("writer is only taken on commit")
+    }
+}
+
+impl DerefMut for AtomicWriter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.wrtr.as_mut().expect("writer is only taken on commit")
+    }
+}
+
+impl Write for AtomicWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.deref_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.deref_mut().flush()
+    }
+}
+
+impl Drop for AtomicWriter {
+    fn drop(&mut self) {
+        // Close the file before removing it. After a successful commit the temporary file has
+        // been renamed, so there is nothing to remove.
+        self.wrtr.take();
+        let _ = fs::remove_file(&self.temp_path);
+    }
 }
 
 impl<W> Write for Writer<W>
@@ -174,16 +865,41 @@ where
 {
     /// Writes a buffer into the underlying writer.
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.wrtr.write(buf)
+        self.write_append_linebreak()?;
+
+        let n = self.buffer_mut().write(buf)?;
+        self.stats.total_bytes += n as u64;
+        Ok(n)
     }
 
     /// flushes the underlying writer.
     fn flush(&mut self) -> io::Result<()> {
-        self.wrtr.flush()?;
+        self.buffer_mut().flush()?;
         Ok(())
     }
 }
 
+impl<W> Drop for Writer<W>
+where
+    W: Write,
+{
+    /// Flushes any buffered data, ignoring errors. Nothing is flushed after `into_inner`.
+    fn drop(&mut self) {
+        if let Some(ref mut wrtr) = self.wrtr {
+            let _ = wrtr.flush();
+        }
+    }
+}
+
+impl Writer<io::StdoutLock<'static>> {
+    /// Creates a new writer to standard output. Standard output is locked once, for as long as
+    /// the writer lives, and records are buffered rather than written a line at a time. See
+    /// `Reader::from_stdin` for a complete filter program.
+    pub fn to_stdout() -> Self {
+        Self::from_writer(io::stdout().lock())
+    }
+}
+
 impl Writer<Vec<u8>> {
     /// Creates a new writer in memory from a `Vec<u8>`.
     pub fn from_memory() -> Self {
@@ -194,21 +910,96 @@ impl Writer<Vec<u8>> {
 impl From<Writer<Vec<u8>>> for Vec<u8> {
     /// Converts the writer into a `Vec<u8>`, but panics if unable to flush to the underlying
     /// writer.
-    fn from(mut writer: Writer<Vec<u8>>) -> Self {
-        match writer.wrtr.flush() {
+    fn from(writer: Writer<Vec<u8>>) -> Self {
+        match writer.into_inner() {
             Err(e) => panic!("could not flush bytes: {}", e),
-            Ok(()) => writer.wrtr.into_inner().unwrap(),
+            Ok(bytes) => bytes,
         }
     }
 }
 
 impl From<Writer<Vec<u8>>> for String {
     /// Converts the writer into a `String`, but panics if unable to flush to the underlying
-    fn from(mut writer: Writer<Vec<u8>>) -> Self {
-        match writer.wrtr.flush() {
-            Err(e) => panic!("could not flush bytes: {}", e),
-            Ok(()) => String::from_utf8(writer.into()).unwrap(),
-        }
+    fn from(writer: Writer<Vec<u8>>) -> Self {
+        String::from_utf8(writer.into()).unwrap()
+    }
+}
+
+/// The number of bytes `count` records of type `T` take when written by `Writer::write_serialized`
+/// with the given linebreak between them, and after the last one if `trailing` is set. Each
+/// record is taken to be `T::record_width()` bytes, so the fields must cover the record without
+/// gaps and none may run to the end of the record.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{serialized_len, FieldSet, FixedWidth, LineBreak};
+///
+/// struct Record;
+///
+/// impl FixedWidth for Record {
+///     fn fields() -> FieldSet {
+///         FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..10)])
+///     }
+/// }
+///
+/// assert_eq!(serialized_len::<Record>(3, &LineBreak::None, false), 30);
+/// assert_eq!(serialized_len::<Record>(3, &LineBreak::CRLF, false), 34);
+/// assert_eq!(serialized_len::<Record>(3, &LineBreak::CRLF, true), 36);
+/// ```
+pub fn serialized_len<T: FixedWidth>(count: u64, linebreak: &LineBreak, trailing: bool) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+
+    let linebreaks = if trailing { count } else { count - 1 };
+    count * T::record_width() as u64 + linebreaks * linebreak.byte_width() as u64
+}
+
+/// A writer that counts the bytes written to it and throws them away, to measure the output of
+/// a `Serializer` or `Writer` without keeping it.
+///
+/// ### Example
+///
+/// ```rust
+/// use fixed_width::{to_writer_with_fields, CountingWriter, FieldSet};
+///
+/// let fields = FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..10)]);
+/// let mut counter = CountingWriter::new();
+///
+/// to_writer_with_fields(&mut counter, &("abc", 123), fields).unwrap();
+/// assert_eq!(counter.count(), 10);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountingWriter {
+    count: u64,
+}
+
+impl CountingWriter {
+    /// Creates a new writer that has counted no bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of bytes written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.count += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.count += buf.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
 
@@ -217,6 +1008,7 @@ mod test {
     use super::*;
     use crate::{FieldSet, FixedWidth};
     use serde_derive::Serialize;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn write_to_memory() {
@@ -293,6 +1085,87 @@ mod test {
         assert_eq!(s, "123foo\n12 fb \n123foo");
     }
 
+    #[test]
+    fn serialized_len_matches_output() {
+        let records = || {
+            (0..5).map(|i| Test2 {
+                a: i,
+                b: "x".repeat(i),
+            })
+        };
+
+        for linebreak in [LineBreak::None, LineBreak::Newline, LineBreak::CRLF] {
+            for trailing in [false, true] {
+                let mut w = Writer::from_memory().linebreak(linebreak.clone());
+                w.write_serialized(records()).unwrap();
+                if trailing {
+                    w.write_linebreak().unwrap();
+                }
+                let bytes: Vec<u8> = w.into();
+
+                let mut w = Writer::from_writer(CountingWriter::new()).linebreak(linebreak.clone());
+                w.write_serialized(records()).unwrap();
+                if trailing {
+                    w.write_linebreak().unwrap();
+                }
+                let counted = w.into_inner().unwrap().count();
+
+                let predicted = serialized_len::<Test2>(5, &linebreak, trailing);
+                assert_eq!(predicted, bytes.len() as u64);
+                assert_eq!(predicted, counted);
+            }
+        }
+
+        assert_eq!(serialized_len::<Test2>(0, &LineBreak::CRLF, true), 0);
+    }
+
+    #[test]
+    fn serialized_write_error() {
+        #[derive(Serialize)]
+        struct Initial {
+            a: char,
+        }
+
+        impl FixedWidth for Initial {
+            fn fields() -> FieldSet {
+                FieldSet::new_field(0..1).name("initial")
+            }
+        }
+
+        let records = vec![Initial { a: 'a' }, Initial { a: 'b' }, Initial { a: 'é' }];
+        let mut w = Writer::from_memory();
+        let err = w.write_serialized(records.into_iter()).unwrap_err();
+
+        match err {
+            Error::Record {
+                index: 2,
+                ref source,
+            } => assert_eq!(
+                source.to_string(),
+                "field 'initial' (0..1): value of 2 bytes is too wide for the field"
+            ),
+            ref other => panic!("expected a record error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn write_with_capacities() {
+        let records = ["1111222233334444", "5555666677778888", "99990000aaaabbbb"];
+
+        for capacity in [1, 2, 3, 16, 17, 18, 1 << 20] {
+            let mut w = Writer::with_capacity(capacity, Vec::new()).linebreak(LineBreak::CRLF);
+            w.write_iter(records.iter()).unwrap();
+
+            assert_eq!(w.buffer_capacity(), capacity);
+
+            let s: String = w.into();
+            assert_eq!(
+                s,
+                "1111222233334444\r\n5555666677778888\r\n99990000aaaabbbb"
+            );
+        }
+    }
+
     #[test]
     fn test_write() {
         let bytes = b"abcd1234";
@@ -303,4 +1176,222 @@ mod test {
         assert!(written > 0);
         assert_eq!(s, "abcd1234");
     }
+
+    struct FailingFlush;
+
+    impl Write for FailingFlush {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn into_inner_flushes() {
+        let mut w = Writer::with_capacity(64, Vec::new()).linebreak(LineBreak::Newline);
+        w.write_iter(["1111", "2222"].iter()).unwrap();
+
+        assert_eq!(w.into_inner().unwrap(), b"1111\n2222");
+    }
+
+    #[test]
+    fn drop_flushes() {
+        let mut buf = Vec::new();
+        {
+            let mut w = Writer::from_writer(&mut buf);
+            w.write_iter(["1111", "2222"].iter()).unwrap();
+        }
+
+        assert_eq!(buf, b"11112222");
+    }
+
+    #[test]
+    fn close_surfaces_errors() {
+        let mut w = Writer::from_writer(FailingFlush);
+        w.write_iter(["1111"].iter()).unwrap();
+
+        let err = w.close().unwrap_err();
+        assert!(matches!(err, Error::IOError(_)));
+        assert_eq!(err.to_string(), "disk full");
+
+        // Dropping swallows the same error.
+        let mut w = Writer::from_writer(FailingFlush);
+        w.write_iter(["1111"].iter()).unwrap();
+        drop(w);
+    }
+
+    #[test]
+    fn stats_across_writes() {
+        #[derive(Serialize)]
+        struct Initial {
+            a: char,
+        }
+
+        impl FixedWidth for Initial {
+            fn fields() -> FieldSet {
+                FieldSet::new_field(0..1).name("initial")
+            }
+        }
+
+        let mut w = Writer::from_memory().linebreak(LineBreak::CRLF);
+        assert_eq!(w.stats(), WriterStats::default());
+
+        w.write_iter(["1111", "2222"].iter()).unwrap();
+        w.write_linebreak().unwrap();
+        w.write_serialized(vec![Initial { a: 'a' }, Initial { a: 'b' }].into_iter())
+            .unwrap();
+
+        assert_eq!(
+            w.stats(),
+            WriterStats {
+                records: 4,
+                data_bytes: 10,
+                total_bytes: 16,
+            }
+        );
+
+        // Only the records before the one that errors are counted.
+        w.write_linebreak().unwrap();
+        let records = vec![Initial { a: 'c' }, Initial { a: 'é' }];
+        assert!(w.write_serialized(records.into_iter()).is_err());
+
+        let stats = w.stats();
+        assert_eq!(stats.records, 5);
+        assert_eq!(stats.data_bytes, 11);
+
+        w.write_all(b"xy").unwrap();
+        assert_eq!(w.stats().records, 5);
+        assert_eq!(w.stats().total_bytes, stats.total_bytes + 2);
+
+        let s: String = w.into();
+        assert_eq!(s.len() as u64, stats.total_bytes + 2);
+    }
+
+    #[test]
+    fn progress_after_each_record() {
+        #[derive(Serialize)]
+        struct Initial {
+            a: char,
+        }
+
+        impl FixedWidth for Initial {
+            fn fields() -> FieldSet {
+                FieldSet::new_field(0..1)
+            }
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&log);
+        let mut w = Writer::from_memory()
+            .linebreak(LineBreak::CRLF)
+            .on_record(move |records, bytes| seen.lock().unwrap().push((records, bytes)));
+
+        w.write_all(b"HEADER").unwrap();
+        w.write_linebreak().unwrap();
+        w.write_iter(["1111", "2222"].iter()).unwrap();
+        w.write_linebreak().unwrap();
+        let records = vec![Initial { a: 'a' }, Initial { a: 'é' }];
+        assert!(w.write_serialized(records.into_iter()).is_err());
+
+        assert_eq!(*log.lock().unwrap(), vec![(1, 12), (2, 18), (3, 21)]);
+    }
+
+    #[derive(Serialize)]
+    struct Pair(&'static str, &'static str);
+
+    impl FixedWidth for Pair {
+        fn fields() -> FieldSet {
+            FieldSet::Seq(vec![FieldSet::new_field(0..1), FieldSet::new_field(1..3)])
+        }
+    }
+
+    enum Mixed {
+        Single(char),
+        Pair(Pair),
+    }
+
+    impl FixedWidthRecord for Mixed {
+        fn write_to<W: Write>(&self, wrtr: &mut W) -> Result<()> {
+            match *self {
+                Mixed::Single(c) => ser::to_writer_with_fields(wrtr, &c, FieldSet::new_field(0..2)),
+                Mixed::Pair(ref pair) => ser::to_writer(wrtr, pair),
+            }
+        }
+
+        fn record_width(&self) -> usize {
+            match *self {
+                Mixed::Single(_) => 2,
+                // Wider than the fields, so every pair is too short.
+                Mixed::Pair(_) => 4,
+            }
+        }
+    }
+
+    #[test]
+    fn write_mixed_checks_each_width() {
+        let mut w = Writer::from_memory().linebreak(LineBreak::Newline);
+        w.write_mixed(vec![Mixed::Single('a'), Mixed::Single('b')].into_iter())
+            .unwrap();
+
+        let records = vec![Mixed::Single('c'), Mixed::Pair(Pair("d", "ef"))];
+        match w.write_mixed(records.into_iter()) {
+            Err(Error::Record { index, source }) => {
+                assert_eq!(index, 1);
+                assert!(matches!(
+                    *source,
+                    Error::InvalidWidth {
+                        width: 3,
+                        required: 4
+                    }
+                ));
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        assert_eq!(w.stats().records, 3);
+        let s: String = w.into();
+        assert_eq!(s, "a \nb c ");
+    }
+
+    #[test]
+    fn write_trailer() {
+        #[derive(Serialize)]
+        struct Trailer {
+            records: u64,
+            data_bytes: u64,
+            total_bytes: u64,
+        }
+
+        impl FixedWidth for Trailer {
+            fn fields() -> FieldSet {
+                FieldSet::Seq(vec![
+                    FieldSet::new_field(0..2),
+                    FieldSet::new_field(2..4),
+                    FieldSet::new_field(4..6),
+                ])
+            }
+        }
+
+        let trailer = |stats: &WriterStats| Trailer {
+            records: stats.records,
+            data_bytes: stats.data_bytes,
+            total_bytes: stats.total_bytes,
+        };
+
+        let mut w = Writer::from_memory().linebreak(LineBreak::Newline);
+        w.write_trailer(trailer).unwrap();
+        assert_eq!(w.stats().records, 0);
+        assert_eq!(String::from(w), "0 0 0 ");
+
+        let mut w = Writer::from_memory().linebreak(LineBreak::Newline);
+        w.write_iter(["111", "222", "333"].iter()).unwrap();
+        w.write_trailer(trailer).unwrap();
+
+        assert_eq!(w.stats().records, 3);
+        assert_eq!(w.stats().total_bytes, 18);
+        assert_eq!(String::from(w), "111\n222\n333\n3 9 11");
+    }
 }
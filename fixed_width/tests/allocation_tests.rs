@@ -0,0 +1,133 @@
+use fixed_width::{from_bytes_with_fields_ref, FieldSet, Reader};
+use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, Visitor};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    // Counted per thread so that tests running in parallel do not see each other's allocations.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn read_record_into_reuses_buffer() {
+    let data = "1111222233334444".repeat(1000);
+    let mut rdr = Reader::from_string(data).width(16);
+    let mut buf = Vec::with_capacity(16);
+    let mut count = 0;
+
+    let before = allocations();
+
+    while rdr.read_record_into(&mut buf).unwrap() {
+        assert_eq!(buf, b"1111222233334444");
+        count += 1;
+    }
+
+    let allocations = allocations() - before;
+
+    assert_eq!(count, 1000);
+    assert!(
+        allocations <= 1,
+        "{} allocations for 1000 records",
+        allocations
+    );
+}
+
+#[test]
+fn next_record_str_borrows_valid_records() {
+    let data = "1111222233334444".repeat(1000);
+    let mut rdr = Reader::from_string(data).width(16);
+    let mut count = 0;
+
+    let before = allocations();
+
+    while let Some(record) = rdr.next_record_str() {
+        assert_eq!(record.unwrap(), "1111222233334444");
+        count += 1;
+    }
+
+    let allocations = allocations() - before;
+
+    assert_eq!(count, 1000);
+    assert!(
+        allocations <= 1,
+        "{} allocations for 1000 records",
+        allocations
+    );
+}
+
+/// Counts the keys of a map without allocating for them or their values.
+struct KeyCount(usize);
+
+impl<'de> Deserialize<'de> for KeyCount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyCountVisitor;
+
+        impl<'de> Visitor<'de> for KeyCountVisitor {
+            type Value = KeyCount;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeyCount, A::Error> {
+                let mut count = 0;
+                while map.next_key::<IgnoredAny>()?.is_some() {
+                    map.next_value::<IgnoredAny>()?;
+                    count += 1;
+                }
+                Ok(KeyCount(count))
+            }
+        }
+
+        deserializer.deserialize_map(KeyCountVisitor)
+    }
+}
+
+#[test]
+fn map_keys_do_not_allocate() {
+    let fields = FieldSet::Seq(vec![
+        FieldSet::new_field(0..4).name("a"),
+        FieldSet::new_field(4..8),
+        FieldSet::new_field(8..12).name("c"),
+        FieldSet::new_field_to_end(12),
+    ]);
+    let records: Vec<String> = (0..1000).map(|n| format!("{:016}", n)).collect();
+
+    let before = allocations();
+
+    for record in &records {
+        let KeyCount(count) = from_bytes_with_fields_ref(record.as_bytes(), &fields).unwrap();
+        assert_eq!(count, 4);
+    }
+
+    let allocations = allocations() - before;
+
+    assert_eq!(
+        allocations, 0,
+        "{} allocations for 1000 records",
+        allocations
+    );
+}
@@ -1,8 +1,16 @@
-use fixed_width::{LineBreak, Reader, Writer};
+use fixed_width::{
+    check_file, FieldSet, FileIssue, FixedWidth, FollowMode, LineBreak, Reader, Record, Result,
+    Writer,
+};
+use serde_derive::Serialize;
 use std::{
     fs::{self, File},
-    io::Write,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
     result,
+    sync::mpsc,
+    thread,
+    time::Duration,
 };
 
 #[test]
@@ -23,6 +31,33 @@ fn read_from_file() {
     }
 }
 
+fn records(path: &Path) -> impl Iterator<Item = Result<Vec<u8>>> {
+    Reader::from_file(path)
+        .unwrap()
+        .width(16)
+        .linebreak(LineBreak::Newline)
+        .into_byte_records()
+}
+
+#[test]
+fn read_owned_records_from_file() {
+    let rows = records(Path::new("./tests/data/sample_file_newlines.txt"))
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+
+    assert_eq!(rows, vec![b"1111222233334444".to_vec(); 3]);
+
+    let rdr = Reader::from_file("./tests/data/sample_file.txt")
+        .unwrap()
+        .width(16);
+    let mut count = 0;
+    for record in rdr {
+        assert_eq!(record.unwrap(), b"1111222233334444");
+        count += 1;
+    }
+    assert_eq!(count, 3);
+}
+
 #[test]
 fn read_from_file_with_newlines() {
     let mut rdr = Reader::from_file("./tests/data/sample_file_newlines.txt")
@@ -42,6 +77,25 @@ fn read_from_file_with_newlines() {
     }
 }
 
+#[test]
+fn read_from_file_with_mixed_linebreaks() {
+    let mut rdr = Reader::from_file("./tests/data/sample_file_mixed_linebreaks.txt")
+        .unwrap()
+        .width(16)
+        .lenient_linebreaks(true);
+
+    let rows = rdr
+        .string_reader()
+        .filter_map(result::Result::ok)
+        .collect::<Vec<String>>();
+
+    assert_eq!(rows.len(), 4);
+
+    for row in rows {
+        assert_eq!("1111222233334444", row);
+    }
+}
+
 #[test]
 fn write_to_file() {
     let path = "./tests/data/sample_write.txt";
@@ -85,3 +139,429 @@ fn write_to_file_with_newlines() {
     fs::remove_file(path).unwrap();
     assert_eq!(expected, s);
 }
+
+#[test]
+fn write_to_path() {
+    let path = "./tests/data/sample_write_path.txt";
+    fs::write(path, "old contents").unwrap();
+
+    let mut wrtr = Writer::from_path(path).unwrap().linebreak(LineBreak::CRLF);
+    wrtr.write_iter(["1111222233334444", "5555666677778888"].iter())
+        .unwrap();
+    wrtr.flush().unwrap();
+
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("1111222233334444\r\n5555666677778888", s);
+}
+
+#[test]
+fn append_to_path() {
+    let path = "./tests/data/sample_append.txt";
+    let _ = fs::remove_file(path);
+
+    // The file is created if it does not exist, with no linebreak before the first record.
+    for records in [["1111", "2222"], ["3333", "4444"]].iter() {
+        let mut wrtr = Writer::append_to_path(path)
+            .unwrap()
+            .linebreak(LineBreak::Newline);
+        wrtr.write_iter(records.iter()).unwrap();
+        wrtr.flush().unwrap();
+    }
+
+    // A file that already ends with a linebreak does not get another.
+    fs::write(path, fs::read_to_string(path).unwrap() + "\n").unwrap();
+    let mut wrtr = Writer::append_to_path(path)
+        .unwrap()
+        .linebreak(LineBreak::Newline);
+    wrtr.write_iter(["5555"].iter()).unwrap();
+    wrtr.flush().unwrap();
+
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("1111\n2222\n3333\n4444\n5555", s);
+}
+
+#[test]
+fn append_to_path_without_trailing_linebreak() {
+    let path = "./tests/data/sample_append_no_linebreak.txt";
+    fs::write(path, "1111\r\n2222").unwrap();
+
+    let mut wrtr = Writer::append_to_path(path)
+        .unwrap()
+        .linebreak(LineBreak::CRLF);
+    wrtr.write_iter(["3333"].iter()).unwrap();
+    wrtr.flush().unwrap();
+    let s = fs::read_to_string(path).unwrap();
+    assert_eq!("1111\r\n2222\r\n3333", s);
+
+    let mut wrtr = Writer::append_to_path(path)
+        .unwrap()
+        .linebreak(LineBreak::CRLF)
+        .linebreak_before_append(false);
+    wrtr.write_iter(["4444"].iter()).unwrap();
+    wrtr.flush().unwrap();
+    let s = fs::read_to_string(path).unwrap();
+    assert_eq!("1111\r\n2222\r\n33334444", s);
+
+    let mut wrtr = Writer::append_to_path(path)
+        .unwrap()
+        .linebreak(LineBreak::CRLF);
+    let initial = Initial {
+        code: "5555",
+        initial: 'x',
+    };
+    wrtr.write_serialized(std::iter::once(initial)).unwrap();
+    wrtr.flush().unwrap();
+    // The linebreak before the appended records is not part of a record.
+    assert_eq!(wrtr.stats().data_bytes, 5);
+    assert_eq!(wrtr.stats().total_bytes, 7);
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("1111\r\n2222\r\n33334444\r\n5555x", s);
+}
+
+#[derive(Serialize)]
+struct Initial {
+    code: &'static str,
+    initial: char,
+}
+
+impl FixedWidth for Initial {
+    fn fields() -> FieldSet {
+        FieldSet::Seq(vec![FieldSet::new_field(0..4), FieldSet::new_field(4..5)])
+    }
+}
+
+fn leftover_files(dir: &str, prefix: &str) -> Vec<String> {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(prefix))
+        .collect()
+}
+
+#[test]
+fn atomic_write_commit() {
+    let path = "./tests/data/sample_atomic.txt";
+    let records = vec![
+        Initial {
+            code: "1111",
+            initial: 'a',
+        },
+        Initial {
+            code: "2222",
+            initial: 'b',
+        },
+    ];
+
+    let mut wrtr = Writer::atomic_path(path)
+        .unwrap()
+        .linebreak(LineBreak::Newline);
+    wrtr.write_serialized(records.into_iter()).unwrap();
+    assert!(wrtr.temp_path().exists());
+    assert!(fs::metadata(path).is_err());
+
+    wrtr.commit().unwrap();
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("1111a\n2222b", s);
+    assert!(leftover_files("./tests/data", ".sample_atomic.txt").is_empty());
+}
+
+#[test]
+fn atomic_write_failure() {
+    let path = "./tests/data/sample_atomic_failure.txt";
+    let records = vec![
+        Initial {
+            code: "1111",
+            initial: 'a',
+        },
+        Initial {
+            code: "2222",
+            initial: 'é',
+        },
+    ];
+
+    {
+        let mut wrtr = Writer::atomic_path(path)
+            .unwrap()
+            .linebreak(LineBreak::Newline);
+        assert!(wrtr.write_serialized(records.into_iter()).is_err());
+        wrtr.flush().unwrap();
+        assert!(fs::metadata(path).is_err());
+    }
+
+    assert!(fs::metadata(path).is_err());
+    assert!(leftover_files("./tests/data", ".sample_atomic_failure.txt").is_empty());
+
+    // An existing file is left untouched.
+    fs::write(path, "old").unwrap();
+    {
+        let mut wrtr = Writer::atomic_path(path).unwrap();
+        wrtr.write_iter(["new"].iter()).unwrap();
+    }
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("old", s);
+}
+
+#[test]
+fn write_to_file_without_flush() {
+    let path = "./tests/data/sample_write_no_flush.txt";
+    let records = ["1111", "2222", "3333"];
+
+    {
+        let mut wrtr = Writer::from_path(path)
+            .unwrap()
+            .linebreak(LineBreak::Newline);
+        wrtr.write_iter(records.iter()).unwrap();
+    }
+
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("1111\n2222\n3333", s);
+}
+
+#[test]
+fn write_to_file_close() {
+    let path = "./tests/data/sample_write_close.txt";
+
+    let mut wrtr = Writer::from_path(path).unwrap();
+    wrtr.write_iter(["1111", "2222"].iter()).unwrap();
+    wrtr.close().unwrap();
+
+    let s = fs::read_to_string(path).unwrap();
+
+    fs::remove_file(path).unwrap();
+    assert_eq!("11112222", s);
+}
+
+/// Upper cases the name of each record, as a filter program from stdin to stdout would.
+fn upper_case_names<R: Read, W: Write>(reader: Reader<R>, writer: &mut Writer<W>) -> Result<()> {
+    let fields = FieldSet::Seq(vec![
+        FieldSet::new_field(0..6).name("name"),
+        FieldSet::new_field(6..10).name("amount"),
+    ]);
+
+    for (i, bytes) in reader.into_iter().enumerate() {
+        let mut record = Record::parse(&bytes?, &fields)?;
+        let name = record.get_str("name")?.to_uppercase();
+        record.set("name", name)?;
+
+        if i > 0 {
+            writer.write_linebreak()?;
+        }
+        writer.write_all(record.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn filter_records() {
+    let reader = Reader::from_string("carl  0012\nbo    0340\n")
+        .width(10)
+        .linebreak(LineBreak::Newline);
+    let mut writer = Writer::from_memory().linebreak(LineBreak::Newline);
+
+    upper_case_names(reader, &mut writer).unwrap();
+
+    let s: String = writer.into();
+    assert_eq!(s, "CARL  0012\nBO    0340");
+}
+
+#[test]
+fn stdin_and_stdout_types_are_nameable() {
+    let _: fn() -> Reader<io::StdinLock<'static>> = Reader::from_stdin;
+    let _: fn() -> Writer<io::StdoutLock<'static>> = Writer::to_stdout;
+}
+
+/// Hides the `Seek` implementation of a `File`.
+struct NonSeekable(File);
+
+impl Read for NonSeekable {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+/// Reads the first record, then skips 50 and reads the rest.
+fn window<R: Read>(mut rdr: Reader<R>) -> (Vec<u8>, u64, Vec<String>, u64) {
+    let first = rdr.next_record().unwrap().unwrap().to_vec();
+    let skipped = rdr.skip_records(50).unwrap();
+    let rows = rdr
+        .string_reader()
+        .collect::<Result<Vec<String>>>()
+        .unwrap();
+    (first, skipped, rows, rdr.records_read())
+}
+
+/// A path in the temp directory for a test's file, unique to this test process so that test runs
+/// at the same time never share a file.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fixed_width_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn skip_and_take_records_from_file() {
+    let path = temp_path("skip_records.txt");
+    let data: Vec<String> = (0..100).map(|i| format!("{:08}", i)).collect();
+    fs::write(&path, data.join("\r\n")).unwrap();
+
+    let seeking = Reader::from_file(&path)
+        .unwrap()
+        .width(8)
+        .linebreak(LineBreak::CRLF)
+        .take_records(25);
+    let reading = Reader::from_reader(NonSeekable(File::open(&path).unwrap()))
+        .width(8)
+        .linebreak(LineBreak::CRLF)
+        .take_records(25);
+
+    let results = [window(seeking), window(reading)];
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(results[0], results[1]);
+
+    let (first, skipped, rows, records_read) = &results[0];
+    assert_eq!(first, b"00000000");
+    assert_eq!(*skipped, 50);
+    assert_eq!(rows.len(), 24);
+    assert_eq!(rows[0], "00000051");
+    assert_eq!(rows[23], "00000074");
+    assert_eq!(*records_read, 75);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn read_from_gz_file() {
+    let plain: Vec<Vec<u8>> = Reader::from_file("./tests/data/sample_records.txt")
+        .unwrap()
+        .width(16)
+        .linebreak(LineBreak::Newline)
+        .into_byte_records()
+        .map(result::Result::unwrap)
+        .collect();
+    assert_eq!(plain.len(), 200);
+
+    let gzipped: Vec<Vec<u8>> = Reader::from_gz_file("./tests/data/sample_records.txt.gz")
+        .unwrap()
+        .width(16)
+        .detect_linebreak()
+        .into_byte_records()
+        .map(result::Result::unwrap)
+        .collect();
+    assert_eq!(gzipped, plain);
+
+    let mut rdr = Reader::from_gz_file("./tests/data/sample_records.txt.gz")
+        .unwrap()
+        .width(16)
+        .linebreak(LineBreak::Newline);
+    assert_eq!(rdr.skip_records(150).unwrap(), 150);
+    let rest: Vec<Vec<u8>> = rdr.byte_reader().map(result::Result::unwrap).collect();
+    assert_eq!(rest, plain[150..]);
+    assert_eq!(rdr.records_read(), 200);
+}
+
+#[test]
+fn check_sample_files() {
+    let report = check_file(
+        "./tests/data/sample_file_newlines.txt",
+        16,
+        LineBreak::Newline,
+    )
+    .unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.records, 3);
+    assert!(report.ends_with_linebreak);
+
+    let report = check_file("./tests/data/sample_file.txt", 16, LineBreak::None).unwrap();
+    assert_eq!(report.records, 3);
+    assert_eq!(report.issues, vec![FileIssue::UnexpectedLinebreak]);
+
+    let report = check_file("./tests/data/sample_file_newlines.txt", 16, LineBreak::CRLF).unwrap();
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn progress_through_file() {
+    let path = temp_path("progress.txt");
+    fs::write(&path, "HEADER\r\n00000001\r\n00000002\r\n000003").unwrap();
+
+    let mut rdr = Reader::from_file(&path)
+        .unwrap()
+        .width(8)
+        .linebreak(LineBreak::CRLF);
+    let total = rdr.total_bytes().unwrap();
+
+    let mut header = [0; 8];
+    rdr.read_exact(&mut header).unwrap();
+    let mut percentages = Vec::new();
+    while let Some(record) = rdr.next_record() {
+        record.unwrap();
+        percentages.push(rdr.bytes_read() * 100 / total);
+    }
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(total, 34);
+    assert_eq!(percentages, vec![52, 82]);
+    assert_eq!(rdr.records_read(), 2);
+    assert_eq!(rdr.bytes_read(), total);
+}
+
+#[test]
+fn follow_growing_file() {
+    let path = temp_path("follow.txt");
+    fs::write(&path, "").unwrap();
+
+    let (read_tx, read_rx) = mpsc::channel::<String>();
+    let writer_path = path.clone();
+    let producer = thread::spawn(move || {
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(writer_path)
+            .unwrap();
+
+        // Each record is written in pieces, and the next only once the reader has seen it.
+        for (i, pieces) in [["0001", "\n"], ["00", "02\n"], ["0003", "\n0"]]
+            .iter()
+            .enumerate()
+        {
+            for piece in pieces {
+                file.write_all(piece.as_bytes()).unwrap();
+                file.flush().unwrap();
+                thread::sleep(Duration::from_millis(20));
+            }
+            assert_eq!(read_rx.recv().unwrap(), format!("{:04}", i + 1));
+        }
+    });
+
+    let mut rdr = Reader::from_file(&path)
+        .unwrap()
+        .width(4)
+        .linebreak(LineBreak::Newline)
+        .follow(FollowMode {
+            poll_interval: Duration::from_millis(5),
+            idle_timeout: Some(Duration::from_millis(500)),
+        });
+
+    let mut records = Vec::new();
+    for record in rdr.string_reader() {
+        let record = record.unwrap();
+        records.push(record.clone());
+        read_tx.send(record).unwrap();
+    }
+
+    producer.join().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(records, vec!["0001", "0002", "0003"]);
+    assert_eq!(rdr.bytes_read(), 16);
+}
@@ -0,0 +1,270 @@
+use proc_macro2::TokenStream;
+use std::ops::Range;
+use syn::{spanned::Spanned, DeriveInput, LitStr};
+
+struct Variant {
+    ident: syn::Ident,
+    ty: syn::Type,
+    tag: LitStr,
+}
+
+pub fn impl_fixed_width_enum(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let data = match ast.data {
+        syn::Data::Enum(ref data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "#[derive(FixedWidthEnum)] can only be used with enums",
+            ))
+        }
+    };
+
+    let mut tag_range = None;
+
+    for attr in &ast.attrs {
+        if attr.path().is_ident("fixed_width") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag_range") {
+                    set_tag_range(&mut tag_range, meta.value()?.parse()?)
+                } else {
+                    Err(meta.error("unsupported fixed_width enum attribute"))
+                }
+            })?;
+        }
+    }
+
+    let mut variants: Vec<Variant> = Vec::new();
+
+    for variant in &data.variants {
+        let ty = match variant.fields {
+            syn::Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                fields.unnamed[0].ty.clone()
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "FixedWidthEnum variants must have exactly one unnamed field, ie Header(HeaderRecord)",
+                ))
+            }
+        };
+
+        let mut tag: Option<LitStr> = None;
+
+        for attr in &variant.attrs {
+            if attr.path().is_ident("fixed_width") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("tag") {
+                        let value: LitStr = meta.value()?.parse()?;
+                        if tag.is_some() {
+                            return Err(syn::Error::new_spanned(value, "duplicate tag attribute"));
+                        }
+                        tag = Some(value);
+                        Ok(())
+                    } else if meta.path.is_ident("tag_range") {
+                        set_tag_range(&mut tag_range, meta.value()?.parse()?)
+                    } else {
+                        Err(meta.error("unsupported fixed_width variant attribute"))
+                    }
+                })?;
+            }
+        }
+
+        let tag = tag.ok_or_else(|| {
+            syn::Error::new_spanned(
+                &variant.ident,
+                format!(
+                    "missing tag for variant {}, ie #[fixed_width(tag = \"H\")]",
+                    variant.ident
+                ),
+            )
+        })?;
+
+        if let Some(other) = variants.iter().find(|v| v.tag.value() == tag.value()) {
+            return Err(syn::Error::new_spanned(
+                &tag,
+                format!(
+                    "tag {:?} is already used by variant {}",
+                    tag.value(),
+                    other.ident
+                ),
+            ));
+        }
+
+        variants.push(Variant {
+            ident: variant.ident.clone(),
+            ty,
+            tag,
+        });
+    }
+
+    let (tag_range, tag_range_lit) = tag_range.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &ast.ident,
+            "missing tag range, ie #[fixed_width(tag_range = \"0..1\")]",
+        )
+    })?;
+
+    for variant in &variants {
+        if variant.tag.value().len() != tag_range.len() {
+            return Err(syn::Error::new_spanned(
+                &variant.tag,
+                format!(
+                    "tag {:?} must be {} bytes long to match tag_range {:?}",
+                    variant.tag.value(),
+                    tag_range.len(),
+                    tag_range_lit.value()
+                ),
+            ));
+        }
+    }
+
+    let ident = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let start = tag_range.start;
+    let end = tag_range.end;
+
+    let from_arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let ty = &v.ty;
+        let tag = syn::LitByteStr::new(v.tag.value().as_bytes(), v.tag.span());
+        let parse = quote_spanned! {ty.span()=>
+            fixed_width::from_bytes::<#ty>(bytes)
+        };
+        quote! {
+            Some(#tag) => #parse.map(#ident::#variant),
+        }
+    });
+
+    let to_arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let ty = &v.ty;
+        let write = quote_spanned! {ty.span()=>
+            fixed_width::to_bytes::<#ty>(record)
+        };
+        quote! {
+            #ident::#variant(ref record) => #write,
+        }
+    });
+
+    let write_arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let ty = &v.ty;
+        let write = quote_spanned! {ty.span()=>
+            fixed_width::to_writer::<#ty, W>(wrtr, record)
+        };
+        quote! {
+            #ident::#variant(ref record) => #write,
+        }
+    });
+
+    let width_arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let ty = &v.ty;
+        let width = quote_spanned! {ty.span()=>
+            <#ty as fixed_width::FixedWidth>::record_width()
+        };
+        quote! {
+            #ident::#variant(_) => #width,
+        }
+    });
+
+    let latin1_arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let ty = &v.ty;
+        let write = quote_spanned! {ty.span()=>
+            fixed_width::to_writer_latin1::<#ty, W>(wrtr, record)
+        };
+        quote! {
+            #ident::#variant(ref record) => #write,
+        }
+    });
+
+    let fields_arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let ty = &v.ty;
+        let fields = quote_spanned! {ty.span()=>
+            <#ty as fixed_width::FixedWidth>::fields()
+        };
+        quote! {
+            #ident::#variant(_) => Some(#fields),
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics fixed_width::FixedWidthRecord for #ident #ty_generics #where_clause {
+            fn write_to<W: ::std::io::Write>(&self, wrtr: &mut W) -> fixed_width::Result<()> {
+                match *self {
+                    #(#write_arms)*
+                }
+            }
+
+            fn record_width(&self) -> usize {
+                match *self {
+                    #(#width_arms)*
+                }
+            }
+
+            fn write_latin1_to<W: ::std::io::Write>(
+                &self,
+                wrtr: &mut W,
+            ) -> fixed_width::Result<()> {
+                match *self {
+                    #(#latin1_arms)*
+                }
+            }
+
+            fn record_fields(&self) -> Option<fixed_width::FieldSet> {
+                match *self {
+                    #(#fields_arms)*
+                }
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Deserializes a record into the variant whose tag matches the record's tag range.
+            pub fn from_bytes(bytes: &[u8]) -> fixed_width::Result<Self> {
+                let tag = bytes.get(#start..#end);
+                match tag {
+                    #(#from_arms)*
+                    _ => Err(fixed_width::Error::UnknownRecordType {
+                        tag: tag.map(<[u8]>::to_vec).unwrap_or_default(),
+                        record: bytes.to_vec(),
+                    }),
+                }
+            }
+
+            /// Serializes the record held by the active variant.
+            pub fn to_bytes(&self) -> fixed_width::Result<Vec<u8>> {
+                match *self {
+                    #(#to_arms)*
+                }
+            }
+        }
+    })
+}
+
+fn set_tag_range(tag_range: &mut Option<(Range<usize>, LitStr)>, lit: LitStr) -> syn::Result<()> {
+    if tag_range.is_some() {
+        return Err(syn::Error::new_spanned(
+            lit,
+            "tag_range may only be specified once",
+        ));
+    }
+
+    let parts = lit
+        .value()
+        .split("..")
+        .map(str::parse)
+        .filter_map(Result::ok)
+        .collect::<Vec<usize>>();
+
+    if parts.len() != 2 || parts[0] >= parts[1] {
+        return Err(syn::Error::new_spanned(
+            lit,
+            "invalid tag_range, expected a non-empty range like \"0..1\"",
+        ));
+    }
+
+    *tag_range = Some((parts[0]..parts[1], lit));
+    Ok(())
+}
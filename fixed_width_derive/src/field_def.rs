@@ -1,124 +1,447 @@
 use std::{collections::HashMap, ops::Range};
-use syn::LitStr;
+use syn::{ext::IdentExt, spanned::Spanned, LitStr};
 
 pub struct Container {
     pub fixed_width_fn: Option<syn::Ident>,
+    /// Field attributes given as `default_<key>` on the container, keyed without the prefix,
+    /// which apply to every field that does not give its own.
+    pub defaults: HashMap<String, Metadata>,
+    pub literals: Vec<LiteralDef>,
+    pub rename_all: Option<RenameRule>,
+    pub unnamed_fields: bool,
+}
+
+/// A constant field given by the container attribute `literal(range = "...", value = "...")`.
+pub struct LiteralDef {
+    pub range: Range<usize>,
+    pub value: LitStr,
+    pub strict: bool,
+}
+
+impl LiteralDef {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut range = None;
+        let mut value = None;
+        let mut strict = false;
+
+        meta.parse_nested_meta(|inner| {
+            if inner.path.is_ident("range") {
+                let lit: LitStr = inner.value()?.parse()?;
+                match crate::parse_range(&lit.value()) {
+                    Some((r, false)) => range = Some(r),
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &lit,
+                            format!(
+                                "invalid literal range {:?}, expected `a..b` or `a..=b`",
+                                lit.value()
+                            ),
+                        ))
+                    }
+                }
+            } else if inner.path.is_ident("value") {
+                value = Some(inner.value()?.parse()?);
+            } else if inner.path.is_ident("strict") {
+                strict = true;
+            } else {
+                return Err(inner.error("expected `range`, `value` or `strict`"));
+            }
+            Ok(())
+        })?;
+
+        match (range, value) {
+            (Some(range), Some(value)) => Ok(Self {
+                range,
+                value,
+                strict,
+            }),
+            _ => Err(meta.error("literal requires both a range and a value")),
+        }
+    }
+}
+
+/// The case conversions supported by `#[serde(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            other => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    format!("unknown rename rule {:?}", other),
+                ))
+            }
+        })
+    }
+
+    /// Applies the rule to a snake_case field name, the same way serde does.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
 }
 
 impl Container {
-    pub fn from_ast(ast: &syn::DeriveInput) -> Self {
+    pub fn from_ast(ast: &syn::DeriveInput) -> syn::Result<Self> {
         let mut fixed_width_fn: Option<syn::Ident> = None;
+        let mut defaults: HashMap<String, Metadata> = HashMap::new();
+        let mut literals: Vec<LiteralDef> = Vec::new();
+        let mut rename_all = None;
+        let mut unnamed_fields = false;
 
         for attr in &ast.attrs {
             if attr.path().is_ident("fixed_width") {
                 attr.parse_nested_meta(|meta| {
                     if meta.path.is_ident("field_def") {
-                        let value = meta.value().expect("expected to find an expression, ie fixed_width(field_def = function_name)");
-                        let fixed_width_fn_name: LitStr = value.parse().expect("expected to find a function name, ie fixed_width(field_def = function_name)");
+                        let fixed_width_fn_name: LitStr = meta.value()?.parse()?;
 
                         if fixed_width_fn.is_some() {
-                            panic!("expected only 1 function to be specified for the field_def");
-                        } else {
-                            fixed_width_fn = Some(syn::Ident::new(&fixed_width_fn_name.value(), proc_macro2::Span::call_site()));
+                            return Err(syn::Error::new_spanned(
+                                fixed_width_fn_name,
+                                "expected only 1 function to be specified for the field_def",
+                            ));
+                        }
+
+                        fixed_width_fn = Some(syn::Ident::new(
+                            &fixed_width_fn_name.value(),
+                            fixed_width_fn_name.span(),
+                        ));
+                    } else if meta.path.is_ident("literal") {
+                        literals.push(LiteralDef::parse(&meta)?);
+                    } else if meta.path.is_ident("unnamed_fields") {
+                        unnamed_fields = true;
+                    } else if let Some(key) = default_key(&meta.path) {
+                        let lit: LitStr = meta.value()?.parse()?;
+                        if defaults.contains_key(&key) {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                format!("duplicate fixed_width attribute `default_{}`", key),
+                            ));
                         }
+                        defaults.insert(
+                            key.clone(),
+                            Metadata {
+                                name: key,
+                                value: lit.value(),
+                                lit,
+                            },
+                        );
+                    } else {
+                        return Err(meta.error(format!(
+                            "unknown fixed_width container attribute, expected one of: field_def, literal, unnamed_fields, {}",
+                            DEFAULT_KEYS
+                                .iter()
+                                .map(|key| format!("default_{}", key))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )));
                     }
                     Ok(())
-                }).expect("expected fixed_width(...)");
+                })?;
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename_all") {
+                        if let Some(lit) = deserialize_value(&meta)? {
+                            rename_all = Some(RenameRule::from_lit(&lit)?);
+                        }
+                        return Ok(());
+                    }
+                    skip_meta(&meta)
+                })?;
             }
         }
 
-        Self { fixed_width_fn }
+        literals.sort_by_key(|l| l.range.start);
+
+        Ok(Self {
+            fixed_width_fn,
+            defaults,
+            literals,
+            rename_all,
+            unnamed_fields,
+        })
     }
 }
 
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct FieldDef {
-    pub ident: syn::Ident,
+    pub field_name: String,
     pub field_type: syn::Type,
-    pub name: String,
+    pub name: Option<String>,
     pub pad_with: char,
     pub range: Range<usize>,
+    pub to_end: bool,
     pub justify: String,
+    pub occurs: Option<(usize, usize)>,
+    /// The length of an array field with `occurs` that is not a literal, such as a const generic
+    /// parameter, to be checked against `occurs` when the struct is used.
+    pub array_len: Option<syn::Expr>,
+    pub default: Option<String>,
+    pub deserialize_with: Option<syn::Path>,
+    pub serialize_with: Option<syn::Path>,
+    pub bytes: bool,
+    pub raw_record: bool,
+    pub blank_on_write: bool,
+    pub decimals: Option<usize>,
+    pub blank_when_zero: bool,
+    pub case: Option<syn::Ident>,
+    pub case_on_read: bool,
+    pub variants: Vec<(String, String)>,
+    pub variants_with: Option<syn::Path>,
+    pub catch_all: Option<String>,
+}
+
+/// The keys accepted by the field attribute `#[fixed_width(...)]`.
+const FIELD_KEYS: &[&str] = &[
+    "range",
+    "name",
+    "pad_with",
+    "justify",
+    "occurs",
+    "width",
+    "default",
+    "deserialize_with",
+    "serialize_with",
+    "bytes",
+    "raw_record",
+    "blank_on_write",
+    "decimals",
+    "blank_when_zero",
+    "case",
+    "case_on_read",
+    "variants",
+    "variants_with",
+    "catch_all",
+];
+
+/// The field attribute keys that can be given a default for every field on the container, as
+/// `default_<key>`.
+const DEFAULT_KEYS: &[&str] = &["pad_with", "justify", "case"];
+
+/// Gets the field attribute key of a container attribute such as `default_pad_with`.
+fn default_key(path: &syn::Path) -> Option<String> {
+    let ident = path.get_ident()?.to_string();
+    let key = ident.strip_prefix("default_")?;
+    DEFAULT_KEYS.contains(&key).then(|| key.to_string())
 }
 
+/// Pairs of field attribute keys that cannot be given together.
+const EXCLUSIVE_KEYS: &[(&str, &str)] = &[("range", "raw_record"), ("variants", "variants_with")];
+
 pub struct Context {
     pub field: syn::Field,
+    /// The position of the field in the struct, which names the fields of a tuple struct.
+    pub index: usize,
     pub skip: bool,
+    pub rename: Option<String>,
     pub metadata: HashMap<String, Metadata>,
 }
 
 impl Context {
-    pub fn from_field(field: &syn::Field) -> Self {
-        let mut fixed_width_attr_seen = 0;
-        let mut metadata = HashMap::new();
+    pub fn from_field(field: &syn::Field, index: usize) -> syn::Result<Self> {
+        let mut fixed_width_attr_seen = false;
+        let mut metadata: HashMap<String, Metadata> = HashMap::new();
         let mut skip = false;
+        let mut rename = None;
 
         for attr in &field.attrs {
             if attr.path().is_ident("fixed_width") {
-                fixed_width_attr_seen += 1;
-                if fixed_width_attr_seen > 1 {
-                    panic!(
-                        "Field: {} has more than 1 fixed_width attribute",
-                        field.ident.clone().unwrap(),
-                    );
+                if fixed_width_attr_seen {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        format!(
+                            "field {} has more than 1 fixed_width attribute",
+                            field_ident(field, index)
+                        ),
+                    ));
                 }
+                fixed_width_attr_seen = true;
 
-                let parse_result = attr.parse_nested_meta(|meta| {
-                    let ident = meta.path.get_ident().unwrap().clone();
-                    let s: LitStr = meta
-                        .value()
-                        .expect(
-                            "expected to find an expression, ie fixed_width(<field> = <metadata>)",
-                        )
-                        .parse()
-                        .expect("fixed width values must be strings");
-
-                    let mdata = Metadata {
-                        name: ident.clone().to_string(),
-                        value: s.value().to_string(),
+                attr.parse_nested_meta(|meta| {
+                    let ident = match meta.path.get_ident() {
+                        Some(ident) => ident.to_string(),
+                        None => {
+                            return Err(meta.error("expected fixed_width(<field> = <metadata>)"))
+                        }
+                    };
+                    if !FIELD_KEYS.contains(&ident.as_str()) {
+                        return Err(meta.error(format!(
+                            "unknown fixed_width attribute `{}` for field: {}, expected one of: {}",
+                            ident,
+                            field_ident(field, index),
+                            FIELD_KEYS.join(", ")
+                        )));
+                    }
+                    if let Some(first) = metadata.get(&ident) {
+                        let mut error = meta.error(format!(
+                            "duplicate fixed_width attribute `{}` for field: {}",
+                            ident,
+                            field_ident(field, index)
+                        ));
+                        error.combine(syn::Error::new_spanned(
+                            &first.lit,
+                            format!("`{}` first given here", ident),
+                        ));
+                        return Err(error);
+                    }
+                    // A flag such as `bytes` is given without a value.
+                    let lit: LitStr = if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        LitStr::new("true", meta.path.span())
                     };
-                    metadata.insert(ident.clone().to_string(), mdata);
-                    Ok(())
-                });
 
-                if parse_result.is_err() {
-                    panic!(
-                        "could not parse fixed_width metadata for field: {}",
-                        field.ident.clone().unwrap()
+                    metadata.insert(
+                        ident.clone(),
+                        Metadata {
+                            name: ident,
+                            value: lit.value(),
+                            lit,
+                        },
                     );
-                }
+                    Ok(())
+                })?;
             } else if attr.path().is_ident("serde") {
-                let parse_result = attr.parse_nested_meta(|meta| {
+                attr.parse_nested_meta(|meta| {
                     if meta.path.is_ident("skip") {
                         skip = true;
+                    } else if meta.path.is_ident("rename") {
+                        rename = deserialize_value(&meta)?.map(|lit| lit.value());
+                        return Ok(());
+                    } else if meta.path.is_ident("flatten") {
+                        // Flattened fields are matched on name, which a record read by
+                        // position cannot do.
+                        return Err(syn::Error::new_spanned(
+                            &meta.path,
+                            format!(
+                                "#[serde(flatten)] is not supported by #[derive(FixedWidth)] for field: {}, \
+                                 use the container attribute `field_def` to nest the fields of another type",
+                                field_ident(field, index)
+                            ),
+                        ));
                     }
-                    Ok(())
-                });
+                    skip_meta(&meta)
+                })?;
+            }
+        }
 
-                if parse_result.is_err() {
-                    panic!(
-                        "could not parse serde metadata for field: {}",
-                        field.ident.clone().unwrap()
-                    );
-                }
+        for (first, second) in EXCLUSIVE_KEYS {
+            if let (Some(a), Some(b)) = (metadata.get(*first), metadata.get(*second)) {
+                let message = format!(
+                    "{} and {} cannot both be given for field: {}",
+                    first,
+                    second,
+                    field_ident(field, index)
+                );
+                let mut error = syn::Error::new_spanned(&a.lit, &message);
+                error.combine(syn::Error::new_spanned(&b.lit, message));
+                return Err(error);
             }
         }
 
-        Self {
+        Ok(Self {
             field: field.clone(),
+            index,
             skip,
+            rename,
             metadata,
-        }
+        })
     }
 
     pub fn field_name(&self) -> String {
-        self.field.ident.clone().unwrap().to_string()
+        field_ident(&self.field, self.index)
+    }
+}
+
+/// Reads the value of a serde attribute that is either given directly, ie `rename = "..."`, or
+/// separately for serialization and deserialization, ie `rename(deserialize = "...")`. In the
+/// latter case the deserialize name is used, as that is the one the field names are matched on.
+fn deserialize_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+
+    let mut value = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("deserialize") {
+            value = Some(nested.value()?.parse()?);
+            Ok(())
+        } else {
+            skip_meta(&nested)
+        }
+    })?;
+    Ok(value)
+}
+
+/// Consumes the value of a nested meta item that is not used by this crate, such as the serde
+/// attributes that are validated by serde itself.
+fn skip_meta(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_meta(&nested))?;
     }
+    Ok(())
+}
+
+/// The name of a field, or its position for the fields of a tuple struct.
+fn field_ident(field: &syn::Field, index: usize) -> String {
+    field
+        .ident
+        .as_ref()
+        .map_or_else(|| index.to_string(), |ident| ident.unraw().to_string())
 }
 
 #[allow(dead_code)]
 pub struct Metadata {
     pub name: String,
     pub value: String,
+    pub lit: LitStr,
 }
@@ -95,13 +95,34 @@ There are two categories of attributes:
 Call a function to get the fields definition. The given function must be callable
 as `fn() -> fixed_width::FieldSet`.
 
+- `literal(range = "x..y", value = "s")`
+
+Adds a field that always holds the constant value `s`, such as a record type code, a format
+version or filler, without a matching struct field. The serializer writes the value and the
+deserializer skips over it. Add `strict` to have the deserializer check that the data matches
+the value, ie `literal(range = "0..2", value = "01", strict)`. May be repeated.
+
+- `default_pad_with = "c"`, `default_justify = "..."` and `default_case = "..."`
+
+Set `pad_with`, `justify` and `case` for every field that does not give its own, ie
+`#[fixed_width(default_pad_with = "0", default_justify = "right")]` for a record made up mostly of
+zero padded numbers.
+
+- `unnamed_fields`
+
+Do not generate field names, unless given explicitly with the `name` field attribute. Useful when
+records are only deserialized into structs, which match fields by position rather than by name.
+
 ## Field attributes
 
 The full set of options you can supply for the attribute annotations are:
 
 - `range = "x..y"`
 
-Required. Range values must be of type `usize`. The byte range of the given field.
+Required, except for `raw_record` fields. Range values must be of type `usize`. The byte range
+of the given field. Inclusive ranges, ie `"0..=9"`, are also accepted, as are open ended ranges,
+ie `"80.."`, for a trailing field that runs to the end of the record. An open ended field is
+written without padding.
 
 - `pad_with = "c"`
 
@@ -110,15 +131,197 @@ value of the field has been converted to bytes. For instance, if the width of
 the field was 5, and the value is `"foo"`, then a left justified field padded with `a`
 results in: `"fooaa"`.
 
-- `justify = "left|right"`
+- `justify = "left|right|center"`
 
 Defaults to `"left"`. Must be of enum type `Justify`. Indicates whether this field should be justified
-left or right once it has been converted to bytes.
+left, right or centered once it has been converted to bytes. `"centre"` is accepted as well. A
+centered value gets any odd byte of padding on its right.
+
+- `occurs = "n"` and `width = "w"`
+
+Repeats the field `n` times, each element `w` bytes wide, starting at the start of `range`. For a
+field such as `[u32; 12]` or `Vec<u32>`. The range may be open ended, ie `"10.."`, in which case
+its end is computed, otherwise it must span exactly `n * w` bytes and `width` may be left out.
+The `pad_with` and `justify` attributes apply to every element. A `Vec` must hold exactly `n`
+elements when serialized, and an array's length must equal `n`.
+
+- `default = "s"`
+
+The value to deserialize in place of the field when it is blank, that is made up of only padding
+or spaces. For instance `default = "0"` reads a blank quantity as `0`. With `occurs` it applies
+to every element.
+
+- `deserialize_with = "path"`
+
+Call a function to convert the raw bytes of the field before they are deserialized. The given
+function must be callable as `fn(&[u8]) -> Result<String, fixed_width::DeserializeError>`. It is
+given the bytes untrimmed and without UTF-8 validation.
+
+- `serialize_with = "path"`
+
+Call a function to convert the bytes of the value before they are padded and written. The given
+function must be callable as `fn(&[u8]) -> Result<Vec<u8>, fixed_width::SerializeError>`.
+
+- `bytes`
+
+Marks the field as holding raw bytes, such as binary data that is not valid UTF-8. The bytes are
+handed to the field's type untrimmed and without UTF-8 validation. For fields such as `Vec<u8>`
+or `serde_bytes::ByteBuf`.
+
+- `raw_record`
+
+Captures the whole raw record in the field, untrimmed, whatever its position in the struct, for
+instance to write rejected records verbatim. No `range` is given. The field is skipped when
+serializing, so the record is not written twice.
+
+- `blank_on_write`
+
+Writes the field as padding, whatever its value, while reading it as usual. Useful for obsolete
+columns that must still be read but should be blanked when records are copied. With `occurs` it
+applies to every element.
+
+- `decimals = "n"`
+
+Writes a float field with exactly `n` decimal places, rounding the value, such as `12.30` for
+`decimals = "2"`. A value too wide for the field is an error rather than being cut short. Has no
+effect when deserializing.
+
+- `blank_when_zero`
+
+Writes a number that is zero as spaces across the whole field, as with COBOL's `BLANK WHEN ZERO`,
+and reads a field of only spaces as zero. `None` is written as spaces too, and an `Option` reads
+a blank field as `None`. With `occurs` it applies to every element.
+
+- `case = "upper|lower|none"`
+
+Defaults to `"none"`. Changes the case of the field's text when serializing, before it is padded
+or cut to the width of the field, so upper casing `ß` to `SS` counts towards the width. Numbers
+and raw bytes are left as they are.
+
+- `case_on_read`
+
+Applies the change of case from `case` when deserializing as well, after the field is trimmed.
+
+- `variants = "code=Variant, ..."`
+
+Maps codes in the field to the variants of an enum, such as `variants = "01=Checking, 02=Savings"`
+for a field whose type is a unit-only enum. A code is read as its variant and a variant is written
+as its code. An unknown code is an error, unless `catch_all` is given.
+
+- `variants_with = "path"`
+
+As `variants`, but with the pairs returned by a function, callable as
+`fn() -> &'static [(&'static str, &'static str)]`, for codes that are shared between fields.
+
+- `catch_all = "Variant"`
+
+The variant that codes without a pair are read as, instead of being an error.
 
 - `name = "s"`
 
-Defaults to the name of the struct field. Indicates the name of the field. Useful if you wish to deserialize
-fixed width data into a HashMap.
+Defaults to the name of the struct field, or its position, ie `"0"`, in a tuple struct. Indicates the name of the field. Useful if you wish to deserialize
+fixed width data into a HashMap. When not given, `#[serde(rename = "...")]` on the field and
+`#[serde(rename_all = "...")]` on the struct are honoured, so the keys match those used by other
+serde formats.
+
+# Generic structs
+
+Structs with type parameters, lifetimes or const generics can be derived as well. The layout of
+the fields depends only on the attributes, not on the types of the fields, so no bounds are added
+to the generated impl. An array field whose length is a const generic, such as `[u16; N]` with
+`occurs = "3"`, fails to compile when used with an `N` other than 3. A field marked
+`#[serde(flatten)]`, such as one of a type parameter standing for a struct of its own, is rejected
+when deriving, as its fields would be matched on name rather than read by position; nest such
+fields with the container attribute `field_def` instead.
+
+```rust
+use serde_derive::Deserialize;
+use fixed_width_derive::FixedWidth;
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth, Deserialize)]
+struct Entry<'a, T> {
+    #[fixed_width(range = "0..4")]
+    pub code: &'a str,
+    #[fixed_width(range = "4..10", pad_with = "0", justify = "right")]
+    pub value: T,
+}
+
+let fields = Entry::<u32>::fields();
+let entry: Entry<u32> = fixed_width::from_bytes_borrowed(b"AB  000042", &fields).unwrap();
+assert_eq!((entry.code, entry.value), ("AB", 42));
+```
+
+# Tuple and newtype structs
+
+The fields of a tuple struct take the same attributes as those of a struct with named fields.
+A newtype struct whose field has no `fixed_width` attribute, such as `struct Wrapper(Inner)`,
+takes its fields from `Inner`, which must implement `FixedWidth`.
+
+```rust
+use serde_derive::{Deserialize, Serialize};
+use fixed_width_derive::FixedWidth;
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth, Serialize, Deserialize)]
+struct Pair(
+    #[fixed_width(range = "0..3", pad_with = "0", justify = "right")] u16,
+    #[fixed_width(range = "3..6")] String,
+);
+
+#[derive(FixedWidth, Serialize, Deserialize)]
+struct Checked(Pair);
+
+let pair: Checked = fixed_width::from_str("042abc").unwrap();
+assert_eq!((pair.0 .0, pair.0 .1.as_str()), (42, "abc"));
+assert_eq!(Checked::record_width(), 6);
+```
+
+# Enums of record types
+
+Files made up of several record types, such as a header, details and a trailer, can be read
+with `#[derive(FixedWidthEnum)]` on an enum with one variant per record type. Each variant
+holds a type that implements `FixedWidth`, `Serialize` and `Deserialize`, and is chosen by
+comparing its `tag` against the bytes in `tag_range`. The derive generates
+`from_bytes(&[u8]) -> fixed_width::Result<Self>` and `to_bytes(&self) -> fixed_width::Result<Vec<u8>>`,
+and implements `fixed_width::FixedWidthRecord` so a mix of records can be written with
+`Writer::write_mixed`.
+
+```rust
+use serde_derive::{Deserialize, Serialize};
+use fixed_width_derive::{FixedWidth, FixedWidthEnum};
+
+#[derive(FixedWidth, Serialize, Deserialize)]
+struct Header {
+    #[fixed_width(range = "0..1")]
+    pub kind: String,
+    #[fixed_width(range = "1..9")]
+    pub date: String,
+}
+
+#[derive(FixedWidth, Serialize, Deserialize)]
+struct Detail {
+    #[fixed_width(range = "0..1")]
+    pub kind: String,
+    #[fixed_width(range = "1..9", pad_with = "0", justify = "right")]
+    pub amount: usize,
+}
+
+#[derive(FixedWidthEnum)]
+#[fixed_width(tag_range = "0..1")]
+enum Record {
+    #[fixed_width(tag = "H")]
+    Header(Header),
+    #[fixed_width(tag = "D")]
+    Detail(Detail),
+}
+
+let record = Record::from_bytes(b"D00000042").unwrap();
+assert!(matches!(record, Record::Detail(ref d) if d.amount == 42));
+assert_eq!(record.to_bytes().unwrap(), b"D00000042");
+```
+
+The `tag_range` may be given on the enum or on any one of its variants.
 */
 
 extern crate proc_macro;
@@ -126,144 +329,1103 @@ extern crate proc_macro2;
 #[macro_use]
 extern crate quote;
 
-use crate::field_def::{Container, Context, FieldDef};
+use crate::field_def::{Container, Context, FieldDef, LiteralDef, Metadata};
 use proc_macro::TokenStream;
-use std::result;
+use std::ops::Range;
 use syn::DeriveInput;
 
+mod enum_def;
 mod field_def;
 
 #[proc_macro_derive(FixedWidth, attributes(fixed_width))]
 pub fn fixed_width(input: TokenStream) -> TokenStream {
     let input: DeriveInput = syn::parse(input).unwrap();
     impl_fixed_width(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FixedWidthEnum, attributes(fixed_width))]
+pub fn fixed_width_enum(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    enum_def::impl_fixed_width_enum(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn impl_fixed_width(ast: &DeriveInput) -> TokenStream {
+fn impl_fixed_width(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let fields: Vec<syn::Field> = match ast.data {
-        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => {
-            if fields.iter().any(|field| field.ident.is_none()) {
-                panic!("struct has unnamed fields");
-            }
-            fields.iter().cloned().collect()
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => fields.iter().cloned().collect(),
+        syn::Data::Enum(_) => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "#[derive(FixedWidth)] can only be used with structs, use #[derive(FixedWidthEnum)] for enums",
+            ))
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "#[derive(FixedWidth)] can only be used with structs",
+            ))
         }
-        _ => panic!("#[derive(FixedWidth)] can only be used with structs"),
     };
 
     let ident = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-    let container = Container::from_ast(ast);
+    let container = Container::from_ast(ast)?;
 
-    if container.fixed_width_fn.is_some() {
-        let field_def = container.fixed_width_fn.unwrap();
+    if let Some(ref field_def) = container.fixed_width_fn {
+        if let Some(literal) = container.literals.first() {
+            return Err(syn::Error::new_spanned(
+                &literal.value,
+                "literal fields cannot be used with the container attribute `field_def`",
+            ));
+        }
+
+        if let Some(default) = container.defaults.values().next() {
+            return Err(syn::Error::new_spanned(
+                &default.lit,
+                "field defaults cannot be used with the container attribute `field_def`",
+            ));
+        }
 
         for field in &fields {
             for attr in &field.attrs {
                 if attr.path().is_ident("fixed_width") {
-                    panic!("specify whether container attribue `field_def` or field attribue respectively");
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "specify either the container attribute `field_def` or field attributes, not both",
+                    ));
                 }
             }
         }
 
-        let quote = quote! {
+        Ok(quote! {
             impl #impl_generics fixed_width::FixedWidth for #ident #ty_generics #where_clause {
                 fn fields() -> fixed_width::FieldSet {
                     #field_def()
                 }
             }
-        };
+        })
+    } else if let Some(inner) = newtype_inner(ast) {
+        if let Some(literal) = container.literals.first() {
+            return Err(syn::Error::new_spanned(
+                &literal.value,
+                "literal fields cannot be used with a newtype struct that takes the fields of its inner type",
+            ));
+        }
 
-        quote.into()
-    } else {
-        let tokens: Vec<proc_macro2::TokenStream> = fields
-            .iter()
-            .filter(should_skip)
-            .map(build_field_def)
-            .map(build_fixed_width_field)
-            .collect();
+        if let Some(default) = container.defaults.values().next() {
+            return Err(syn::Error::new_spanned(
+                &default.lit,
+                "field defaults cannot be used with a newtype struct that takes the fields of its inner type",
+            ));
+        }
+
+        // The inner type may be a type parameter, so it is bound by `FixedWidth`.
+        let mut generics = ast.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote! { #inner: fixed_width::FixedWidth });
+        let where_clause = &generics.where_clause;
 
-        let quote = quote! {
+        Ok(quote! {
             impl #impl_generics fixed_width::FixedWidth for #ident #ty_generics #where_clause {
                 fn fields() -> fixed_width::FieldSet {
-                    fixed_width::field_seq![#(#tokens),*]
+                    <#inner as fixed_width::FixedWidth>::fields()
+                }
+
+                fn record_width() -> usize {
+                    <#inner as fixed_width::FixedWidth>::record_width()
+                }
+
+                fn flattened_fields() -> Option<&'static [fixed_width::FieldConfig]> {
+                    <#inner as fixed_width::FixedWidth>::flattened_fields()
                 }
             }
+        })
+    } else {
+        let mut tokens: Vec<proc_macro2::TokenStream> = Vec::new();
+        let mut checks: Vec<proc_macro2::TokenStream> = Vec::new();
+        let mut literals = container.literals.iter().peekable();
+        // Fields are only cached once flattened when nothing is nested, and when the static
+        // would not be shared between the instantiations of a generic type.
+        let mut cache = ast.generics.type_params().next().is_none();
+        // The fields themselves are built once and cloned on each call, unless a path given to a
+        // field attribute could refer to a type or const parameter, which a static cannot.
+        let build_once = cache && ast.generics.const_params().next().is_none();
+        let mut width = container
+            .literals
+            .iter()
+            .map(|l| l.range.end)
+            .max()
+            .unwrap_or(0);
+
+        for (index, field) in fields.iter().enumerate() {
+            let ctx = Context::from_field(field, index)?;
+            if !ctx.skip {
+                let field_def = build_field_def(&container, ctx)?;
+                cache &= field_def.occurs.is_none();
+                width = width.max(field_def.range.end);
+
+                if let (Some(len), Some((count, _))) = (&field_def.array_len, field_def.occurs) {
+                    let message = format!(
+                        "occurs {} does not match the array length of field: {}",
+                        count, field_def.field_name
+                    );
+                    checks.push(quote! {
+                        const { assert!(#len == #count, #message) };
+                    });
+                }
+
+                // Literals are placed before the first field that starts at or after them.
+                while let Some(literal) =
+                    literals.next_if(|l| l.range.start <= field_def.range.start)
+                {
+                    tokens.push(build_literal_field(literal));
+                }
+
+                tokens.push(build_fixed_width_field(field_def));
+            }
+        }
+
+        tokens.extend(literals.map(build_literal_field));
+
+        let fields = if build_once {
+            quote! {
+                static FIELDS: std::sync::OnceLock<fixed_width::FieldSet> =
+                    std::sync::OnceLock::new();
+                FIELDS
+                    .get_or_init(|| fixed_width::field_seq![#(#tokens),*])
+                    .clone()
+            }
+        } else {
+            quote! { fixed_width::field_seq![#(#tokens),*] }
         };
 
-        quote.into()
+        let flattened_fields = if cache {
+            Some(quote! {
+                fn flattened_fields() -> Option<&'static [fixed_width::FieldConfig]> {
+                    static FIELDS: std::sync::OnceLock<Vec<fixed_width::FieldConfig>> =
+                        std::sync::OnceLock::new();
+                    Some(FIELDS.get_or_init(|| {
+                        <Self as fixed_width::FixedWidth>::fields().flatten()
+                    }))
+                }
+            })
+        } else {
+            None
+        };
+
+        Ok(quote! {
+            impl #impl_generics fixed_width::FixedWidth for #ident #ty_generics #where_clause {
+                fn fields() -> fixed_width::FieldSet {
+                    #(#checks)*
+                    #fields
+                }
+
+                fn record_width() -> usize {
+                    #width
+                }
+
+                #flattened_fields
+            }
+        })
     }
 }
 
-fn should_skip(field: &&syn::Field) -> bool {
-    !Context::from_field(field).skip
+/// Gets the inner type of a newtype struct, such as `struct Wrapper(Inner)`, whose only field has
+/// no `fixed_width` attribute, so its fields are those of the inner type.
+fn newtype_inner(ast: &DeriveInput) -> Option<&syn::Type> {
+    match ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unnamed(ref fields),
+            ..
+        }) if fields.unnamed.len() == 1 => {
+            let field = &fields.unnamed[0];
+            if field
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("fixed_width"))
+            {
+                None
+            } else {
+                Some(&field.ty)
+            }
+        }
+        _ => None,
+    }
 }
 
-fn build_field_def(field: &syn::Field) -> FieldDef {
-    let ctx = Context::from_field(field);
-
+fn build_field_def(container: &Container, ctx: Context) -> syn::Result<FieldDef> {
     let name = match ctx.metadata.get("name") {
-        Some(name) => name.value.clone(),
-        None => ctx.field_name(),
+        Some(name) => Some(name.value.clone()),
+        None if container.unnamed_fields => None,
+        None => Some(match (&ctx.rename, container.rename_all) {
+            (Some(rename), _) => rename.clone(),
+            (None, Some(rule)) => rule.apply_to_field(&ctx.field_name()),
+            (None, None) => ctx.field_name(),
+        }),
     };
 
-    let range = if let Some(r) = ctx.metadata.get("range") {
-        let range_parts = r
-            .value
-            .split("..")
-            .map(str::parse)
-            .filter_map(result::Result::ok)
-            .collect::<Vec<usize>>();
+    let raw_record = flag(&ctx, "raw_record")?;
 
-        if range_parts.len() != 2 {
-            panic!("Invalid range {} for field: {}", r.value, ctx.field_name());
+    let (mut range, mut to_end) = match ctx.metadata.get("range") {
+        Some(r) => match parse_range(&r.value) {
+            Some(range) => range,
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &r.lit,
+                    format!(
+                        "invalid range {:?} for field: {}, expected `a..b`, `a..=b` or `a..`",
+                        r.value,
+                        ctx.field_name()
+                    ),
+                ))
+            }
+        },
+        None if raw_record => (0..0, false),
+        None => {
+            let message = format!("must supply a byte range for field: {}", ctx.field_name());
+            return Err(match ctx.field.ident {
+                Some(ref ident) => syn::Error::new_spanned(ident, message),
+                None => syn::Error::new_spanned(&ctx.field.ty, message),
+            });
         }
-
-        range_parts[0]..range_parts[1]
-    } else {
-        panic!("Must supply a byte range for field: {}", ctx.field_name());
     };
 
-    let pad_with = ctx.metadata.get("pad_with").map_or(' ', |c| {
-        if c.value.len() != 1 {
-            panic!("pad_with must be a char for field: {}", ctx.field_name());
+    let pad_with = match setting(container, &ctx, "pad_with") {
+        Some(c) => {
+            let mut chars = c.value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &c.lit,
+                        format!("pad_with must be a char for field: {}", ctx.field_name()),
+                    ))
+                }
+            }
         }
+        None => ' ',
+    };
 
-        c.value.chars().next().unwrap()
-    });
-
-    let justify = match ctx.metadata.get("justify") {
+    let justify = match setting(container, &ctx, "justify") {
         Some(j) => match j.value.to_lowercase().trim() {
-            "left" | "right" => j.value.clone(),
-            _ => panic!(
-                "justify must be 'left' or 'right' for field: {}",
-                ctx.field_name()
-            ),
+            "left" | "right" | "center" | "centre" => j.value.clone(),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &j.lit,
+                    format!(
+                        "justify must be 'left', 'right' or 'center' for field: {}",
+                        ctx.field_name()
+                    ),
+                ))
+            }
         },
         None => "left".to_string(),
     };
 
-    FieldDef {
-        ident: ctx.field.ident.unwrap(),
-        field_type: field.ty.clone(),
+    let mut array_len = None;
+    let occurs = match ctx.metadata.get("occurs") {
+        Some(o) => {
+            let count = match o.value.trim().parse::<usize>() {
+                Ok(count) if count > 0 => count,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &o.lit,
+                        format!(
+                            "occurs must be a positive integer for field: {}",
+                            ctx.field_name()
+                        ),
+                    ))
+                }
+            };
+
+            let width = match ctx.metadata.get("width") {
+                Some(w) => match w.value.trim().parse::<usize>() {
+                    Ok(width) if width > 0 => width,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &w.lit,
+                            format!(
+                                "width must be a positive integer for field: {}",
+                                ctx.field_name()
+                            ),
+                        ))
+                    }
+                },
+                None if !to_end && range.len() % count == 0 => range.len() / count,
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &o.lit,
+                        format!(
+                            "occurs requires a width, or a range that divides evenly into {} elements, for field: {}",
+                            count,
+                            ctx.field_name()
+                        ),
+                    ))
+                }
+            };
+
+            if to_end {
+                range.end = range.start + count * width;
+                to_end = false;
+            } else if range.len() != count * width {
+                return Err(syn::Error::new_spanned(
+                    &ctx.metadata["range"].lit,
+                    format!(
+                        "range of {} bytes does not fit {} elements of width {} for field: {}",
+                        range.len(),
+                        count,
+                        width,
+                        ctx.field_name()
+                    ),
+                ));
+            }
+
+            if let syn::Type::Array(ref array) = ctx.field.ty {
+                match array.len {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(ref len),
+                        ..
+                    }) => {
+                        if len.base10_parse::<usize>()? != count {
+                            return Err(syn::Error::new_spanned(
+                                &o.lit,
+                                format!(
+                                    "occurs {} does not match the array length {} of field: {}",
+                                    count,
+                                    len,
+                                    ctx.field_name()
+                                ),
+                            ));
+                        }
+                    }
+                    // A length such as a const generic parameter is only known once the struct
+                    // is used, so it is checked then.
+                    ref len => array_len = Some(len.clone()),
+                }
+            }
+
+            Some((count, width))
+        }
+        None => {
+            if let Some(w) = ctx.metadata.get("width") {
+                return Err(syn::Error::new_spanned(
+                    &w.lit,
+                    format!(
+                        "width is only valid with occurs for field: {}",
+                        ctx.field_name()
+                    ),
+                ));
+            }
+            None
+        }
+    };
+
+    let hook = |key: &str| -> syn::Result<Option<syn::Path>> {
+        ctx.metadata
+            .get(key)
+            .map(|m| {
+                m.lit.parse::<syn::Path>().map_err(|_| {
+                    syn::Error::new_spanned(
+                        &m.lit,
+                        format!(
+                            "{} must be the path of a function for field: {}",
+                            key,
+                            ctx.field_name()
+                        ),
+                    )
+                })
+            })
+            .transpose()
+    };
+    let bytes = flag(&ctx, "bytes")?;
+    let blank_on_write = flag(&ctx, "blank_on_write")?;
+    let blank_when_zero = flag(&ctx, "blank_when_zero")?;
+    let case_on_read = flag(&ctx, "case_on_read")?;
+
+    let case = match setting(container, &ctx, "case") {
+        Some(c) => match c.value.to_lowercase().trim() {
+            "upper" => Some(syn::Ident::new("Upper", c.lit.span())),
+            "lower" => Some(syn::Ident::new("Lower", c.lit.span())),
+            "none" => None,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &c.lit,
+                    format!(
+                        "case must be 'upper', 'lower' or 'none' for field: {}",
+                        ctx.field_name()
+                    ),
+                ))
+            }
+        },
+        None => None,
+    };
+    let deserialize_with = hook("deserialize_with")?;
+    let serialize_with = hook("serialize_with")?;
+    let variants_with = hook("variants_with")?;
+
+    let variants = match ctx.metadata.get("variants") {
+        Some(v) => match parse_variants(&v.value) {
+            Some(variants) => variants,
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &v.lit,
+                    format!(
+                        "variants must be `code=Variant` pairs separated by commas for field: {}",
+                        ctx.field_name()
+                    ),
+                ))
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let decimals = match ctx.metadata.get("decimals") {
+        Some(d) => match d.value.trim().parse::<usize>() {
+            Ok(decimals) => Some(decimals),
+            Err(_) => {
+                return Err(syn::Error::new_spanned(
+                    &d.lit,
+                    format!(
+                        "decimals must be a non-negative integer for field: {}",
+                        ctx.field_name()
+                    ),
+                ))
+            }
+        },
+        None => None,
+    };
+
+    Ok(FieldDef {
+        field_name: ctx.field_name(),
+        field_type: ctx.field.ty.clone(),
         name,
         pad_with,
         range,
+        to_end,
         justify,
+        occurs,
+        array_len,
+        default: ctx.metadata.get("default").map(|d| d.value.clone()),
+        deserialize_with,
+        serialize_with,
+        bytes,
+        raw_record,
+        blank_on_write,
+        decimals,
+        blank_when_zero,
+        case,
+        case_on_read,
+        variants,
+        variants_with,
+        catch_all: ctx.metadata.get("catch_all").map(|c| c.value.clone()),
+    })
+}
+
+/// Gets a field attribute, or the container's `default_<key>` when the field does not give it.
+fn setting<'a>(container: &'a Container, ctx: &'a Context, key: &str) -> Option<&'a Metadata> {
+    ctx.metadata
+        .get(key)
+        .or_else(|| container.defaults.get(key))
+}
+
+/// Gets the value of a flag attribute such as `bytes`, which may be given without a value.
+fn flag(ctx: &Context, key: &str) -> syn::Result<bool> {
+    match ctx.metadata.get(key) {
+        Some(m) => match m.value.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(syn::Error::new_spanned(
+                &m.lit,
+                format!(
+                    "{} must be 'true' or 'false' for field: {}",
+                    key,
+                    ctx.field_name()
+                ),
+            )),
+        },
+        None => Ok(false),
+    }
+}
+
+/// Parses pairs of a code and a variant name of the form `01=Checking, 02=Savings`.
+fn parse_variants(s: &str) -> Option<Vec<(String, String)>> {
+    s.split(',')
+        .map(|pair| {
+            let (code, variant) = pair.split_once('=')?;
+            let (code, variant) = (code.trim(), variant.trim());
+            if code.is_empty() || variant.is_empty() {
+                return None;
+            }
+            Some((code.to_string(), variant.to_string()))
+        })
+        .collect()
+}
+
+/// Parses a range of the form `a..b`, `a..=b` or `a..`, returning the equivalent half open range
+/// and whether it runs to the end of the record.
+pub(crate) fn parse_range(s: &str) -> Option<(Range<usize>, bool)> {
+    let (start, end) = s.trim().split_once("..")?;
+    let start = start.trim().parse::<usize>().ok()?;
+    let end = end.trim();
+
+    if end.is_empty() {
+        return Some((start..start, true));
+    }
+
+    let end = match end.strip_prefix('=') {
+        Some(end) => end.trim().parse::<usize>().ok()?.checked_add(1)?,
+        None => end.parse::<usize>().ok()?,
+    };
+
+    if start > end {
+        return None;
+    }
+
+    Some((start..end, false))
+}
+
+fn build_literal_field(literal: &LiteralDef) -> proc_macro2::TokenStream {
+    let start = literal.range.start;
+    let end = literal.range.end;
+    let value = &literal.value;
+    let strict = literal.strict;
+
+    quote! {
+        fixed_width::FieldSet::new_literal(#start..#end, #value).strict(#strict)
     }
 }
 
 fn build_fixed_width_field(field_def: FieldDef) -> proc_macro2::TokenStream {
-    let name = field_def.name;
+    let name = field_def.name.map(|name| quote! { .name(#name) });
     let start = field_def.range.start;
     let end = field_def.range.end;
     let pad_with = field_def.pad_with;
     let justify = field_def.justify;
+    let default = field_def
+        .default
+        .map(|default| quote! { .default_value(#default) });
+    let deserialize_with = field_def
+        .deserialize_with
+        .map(|f| quote! { .deserialize_with(#f) });
+    let serialize_with = field_def
+        .serialize_with
+        .map(|f| quote! { .serialize_with(#f) });
+    let bytes = if field_def.bytes {
+        Some(quote! { .bytes(true) })
+    } else {
+        None
+    };
+    let raw_record = if field_def.raw_record {
+        Some(quote! { .capture_only(true) })
+    } else {
+        None
+    };
+    let blank_on_write = if field_def.blank_on_write {
+        Some(quote! { .write_as_blank(true) })
+    } else {
+        None
+    };
+    let decimals = field_def
+        .decimals
+        .map(|decimals| quote! { .decimals(#decimals) });
+    let blank_when_zero = if field_def.blank_when_zero {
+        Some(quote! { .blank_when_zero(true) })
+    } else {
+        None
+    };
+    let case = field_def
+        .case
+        .map(|case| quote! { .transform(fixed_width::Case::#case) });
+    let case_on_read = if field_def.case_on_read {
+        Some(quote! { .transform_on_read(true) })
+    } else {
+        None
+    };
+    let variants = if let Some(f) = field_def.variants_with {
+        Some(quote! { .variants(#f()) })
+    } else if !field_def.variants.is_empty() {
+        let pairs = field_def
+            .variants
+            .iter()
+            .map(|(code, variant)| quote! { (#code, #variant) });
+        Some(quote! { .variants(&[#(#pairs),*]) })
+    } else {
+        None
+    };
+    let catch_all = field_def
+        .catch_all
+        .map(|variant| quote! { .catch_all_variant(#variant) });
+
+    if let Some((count, width)) = field_def.occurs {
+        let elements = (0..count).map(|i| {
+            let start = start + i * width;
+            let end = start + width;
+            quote! {
+                fixed_width::FieldSet::new_field(#start..#end)
+                    .pad_with(#pad_with)
+                    .justify(#justify.to_string())
+                    #default
+                    #deserialize_with
+                    #serialize_with
+                    #bytes
+                    #blank_on_write
+                    #decimals
+                    #blank_when_zero
+                    #case
+                    #case_on_read
+                    #variants
+                    #catch_all
+            }
+        });
+
+        return quote! { fixed_width::FieldSet::Seq(vec![#(#elements),*]).occurs(#count) };
+    }
+
+    let field = if field_def.to_end {
+        quote! { fixed_width::FieldSet::new_field_to_end(#start) }
+    } else {
+        quote! { fixed_width::FieldSet::new_field(#start..#end) }
+    };
 
     quote! {
-        fixed_width::FieldSet::new_field(#start..#end)
-            .name(#name)
+        #field
+            #name
             .pad_with(#pad_with)
             .justify(#justify.to_string())
+            #default
+            #deserialize_with
+            #serialize_with
+            #bytes
+            #raw_record
+            #blank_on_write
+            #decimals
+            #blank_when_zero
+            #case
+            #case_on_read
+            #variants
+            #catch_all
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use syn::parse_quote;
+
+    fn error(ast: DeriveInput) -> String {
+        impl_fixed_width(&ast).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn missing_range() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(name = "bar")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(error(ast), "must supply a byte range for field: bar");
+    }
+
+    #[test]
+    fn unknown_key() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(rnage = "0..4")]
+                bar: String,
+            }
+        };
+
+        let error = error(ast);
+        assert!(
+            error.starts_with("unknown fixed_width attribute `rnage` for field: bar, expected one of: range, name, "),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..4", pad_with = "0", pad_with = " ")]
+                bar: String,
+            }
+        };
+
+        let errors: Vec<String> = impl_fixed_width(&ast)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "duplicate fixed_width attribute `pad_with` for field: bar",
+                "`pad_with` first given here",
+            ]
+        );
+    }
+
+    #[test]
+    fn exclusive_keys() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(raw_record, range = "0..4")]
+                bar: String,
+            }
+        };
+
+        let errors: Vec<String> = impl_fixed_width(&ast)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec!["range and raw_record cannot both be given for field: bar"; 2]
+        );
+
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..2", variants = "01=A", variants_with = "codes")]
+                bar: Account,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "variants and variants_with cannot both be given for field: bar"
+        );
+    }
+
+    #[test]
+    fn unknown_container_key() {
+        let ast = parse_quote! {
+            #[fixed_width(unamed_fields)]
+            struct Foo {
+                #[fixed_width(range = "0..4")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "unknown fixed_width container attribute, expected one of: field_def, literal, \
+             unnamed_fields, default_pad_with, default_justify, default_case"
+        );
+    }
+
+    #[test]
+    fn fields_built_once() {
+        let built_once = |ast: DeriveInput| {
+            impl_fixed_width(&ast)
+                .unwrap()
+                .to_string()
+                .contains("OnceLock < fixed_width :: FieldSet >")
+        };
+
+        assert!(built_once(parse_quote! {
+            struct Foo<'a> {
+                #[fixed_width(range = "0..4")]
+                bar: &'a str,
+            }
+        }));
+        assert!(!built_once(parse_quote! {
+            struct Foo<T> {
+                #[fixed_width(range = "0..4", deserialize_with = "parse::<T>")]
+                bar: T,
+            }
+        }));
+        assert!(!built_once(parse_quote! {
+            struct Foo<const N: usize> {
+                #[fixed_width(range = "0..4", occurs = "2")]
+                bar: [u16; N],
+            }
+        }));
+    }
+
+    #[test]
+    fn container_defaults() {
+        let ast = parse_quote! {
+            #[fixed_width(default_pad_with = "00")]
+            struct Foo {
+                #[fixed_width(range = "0..4")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(error(ast), "pad_with must be a char for field: bar");
+
+        let ast = parse_quote! {
+            #[fixed_width(field_def = "foo_fields", default_justify = "right")]
+            struct Foo {
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "field defaults cannot be used with the container attribute `field_def`"
+        );
+    }
+
+    #[test]
+    fn invalid_range() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..x")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "invalid range \"0..x\" for field: bar, expected `a..b`, `a..=b` or `a..`"
+        );
+    }
+
+    #[test]
+    fn range_forms() {
+        assert_eq!(parse_range("0..10"), Some((0..10, false)));
+        assert_eq!(parse_range("0..=9"), Some((0..10, false)));
+        assert_eq!(parse_range("80.."), Some((80..80, true)));
+        assert_eq!(parse_range(" 2 .. 4 "), Some((2..4, false)));
+        assert_eq!(parse_range("5..3"), None);
+        assert_eq!(parse_range("0..=x"), None);
+        assert_eq!(parse_range("..4"), None);
+        assert_eq!(parse_range("0...4"), None);
+        assert_eq!(parse_range("4"), None);
+    }
+
+    #[test]
+    fn occurs_mismatch() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..", occurs = "3", width = "2")]
+                bar: [u8; 4],
+            }
+        };
+        assert_eq!(
+            error(ast),
+            "occurs 3 does not match the array length 4 of field: bar"
+        );
+
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..7", occurs = "3")]
+                bar: Vec<u8>,
+            }
+        };
+        assert_eq!(
+            error(ast),
+            "occurs requires a width, or a range that divides evenly into 3 elements, for field: bar"
+        );
+
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..7", occurs = "3", width = "2")]
+                bar: Vec<u8>,
+            }
+        };
+        assert_eq!(
+            error(ast),
+            "range of 7 bytes does not fit 3 elements of width 2 for field: bar"
+        );
+
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..2", width = "2")]
+                bar: u8,
+            }
+        };
+        assert_eq!(error(ast), "width is only valid with occurs for field: bar");
+    }
+
+    #[test]
+    fn invalid_pad_with() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..1", pad_with = "ab")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(error(ast), "pad_with must be a char for field: bar");
+    }
+
+    #[test]
+    fn invalid_decimals() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..8", decimals = "-2")]
+                bar: f64,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "decimals must be a non-negative integer for field: bar"
+        );
+    }
+
+    #[test]
+    fn invalid_case() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..1", case = "title")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "case must be 'upper', 'lower' or 'none' for field: bar"
+        );
+    }
+
+    #[test]
+    fn invalid_variants() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..2", variants = "01=Checking, 02")]
+                bar: Account,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "variants must be `code=Variant` pairs separated by commas for field: bar"
+        );
+    }
+
+    #[test]
+    fn invalid_justify() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..1", justify = "middle")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "justify must be 'left', 'right' or 'center' for field: bar"
+        );
+    }
+
+    #[test]
+    fn tuple_struct_missing_range() {
+        let ast = parse_quote! {
+            struct Foo(#[fixed_width(range = "0..2")] String, String);
+        };
+
+        assert_eq!(error(ast), "must supply a byte range for field: 1");
+    }
+
+    #[test]
+    fn newtype_struct_literal() {
+        let ast = parse_quote! {
+            #[fixed_width(literal(range = "0..1", value = "H"))]
+            struct Foo(Bar);
+        };
+
+        assert_eq!(
+            error(ast),
+            "literal fields cannot be used with a newtype struct that takes the fields of its inner type"
+        );
+    }
+
+    #[test]
+    fn not_a_struct() {
+        let ast = parse_quote! {
+            enum Foo { Bar }
+        };
+
+        assert!(error(ast).starts_with("#[derive(FixedWidth)] can only be used with structs"));
+    }
+
+    #[test]
+    fn field_def_and_field_attributes() {
+        let ast = parse_quote! {
+            #[fixed_width(field_def = "foo_fields")]
+            struct Foo {
+                #[fixed_width(range = "0..1")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "specify either the container attribute `field_def` or field attributes, not both"
+        );
+    }
+
+    #[test]
+    fn duplicate_fixed_width_attribute() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..1")]
+                #[fixed_width(name = "baz")]
+                bar: String,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "field bar has more than 1 fixed_width attribute"
+        );
+    }
+
+    #[test]
+    fn non_string_value() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = 0..1)]
+                bar: String,
+            }
+        };
+
+        assert!(impl_fixed_width(&ast).is_err());
+    }
+
+    #[test]
+    fn serde_flatten() {
+        let ast = parse_quote! {
+            struct Wrapper<T> {
+                #[fixed_width(range = "0..4")]
+                id: String,
+                #[serde(flatten)]
+                inner: T,
+            }
+        };
+
+        assert_eq!(
+            error(ast),
+            "#[serde(flatten)] is not supported by #[derive(FixedWidth)] for field: inner, \
+             use the container attribute `field_def` to nest the fields of another type"
+        );
+    }
+
+    #[test]
+    fn other_serde_attributes() {
+        let ast = parse_quote! {
+            struct Foo {
+                #[fixed_width(range = "0..1")]
+                #[serde(rename = "baz", deserialize_with = "de")]
+                bar: String,
+                #[serde(skip)]
+                qux: String,
+            }
+        };
+
+        assert!(impl_fixed_width(&ast).is_ok());
     }
 }
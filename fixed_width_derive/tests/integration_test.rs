@@ -1,5 +1,5 @@
 use fixed_width::{DeserializeError, Deserializer, FixedWidth, Reader, Serializer};
-use fixed_width_derive::FixedWidth;
+use fixed_width_derive::{FixedWidth, FixedWidthEnum};
 use serde::{Deserialize, Serialize};
 use serde_derive::{Deserialize, Serialize};
 use std::result;
@@ -220,3 +220,989 @@ fn test_specify_fields_by_field_def() {
     assert_eq!(data.id, 999);
     assert_eq!(data.name, "foobar");
 }
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct HeaderRec {
+    #[fixed_width(range = "0..1")]
+    pub kind: String,
+    #[fixed_width(range = "1..9")]
+    pub date: String,
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct DetailRec {
+    #[fixed_width(range = "0..1")]
+    pub kind: String,
+    #[fixed_width(range = "1..5")]
+    pub name: String,
+    #[fixed_width(range = "5..9", pad_with = "0", justify = "right")]
+    pub amount: usize,
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct TrailerRec {
+    #[fixed_width(range = "0..1")]
+    pub kind: String,
+    #[fixed_width(range = "1..9", pad_with = "0", justify = "right")]
+    pub count: usize,
+}
+
+#[derive(FixedWidthEnum, Debug, PartialEq)]
+enum TaggedRecord {
+    #[fixed_width(tag_range = "0..1", tag = "H")]
+    Header(HeaderRec),
+    #[fixed_width(tag = "D")]
+    Detail(DetailRec),
+    #[fixed_width(tag = "T")]
+    Trailer(TrailerRec),
+}
+
+#[test]
+fn test_enum_from_bytes() {
+    let records = ["H20240101", "Dfoo 0012", "T00000001"]
+        .iter()
+        .map(|r| TaggedRecord::from_bytes(r.as_bytes()).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        records,
+        vec![
+            TaggedRecord::Header(HeaderRec {
+                kind: "H".to_string(),
+                date: "20240101".to_string(),
+            }),
+            TaggedRecord::Detail(DetailRec {
+                kind: "D".to_string(),
+                name: "foo".to_string(),
+                amount: 12,
+            }),
+            TaggedRecord::Trailer(TrailerRec {
+                kind: "T".to_string(),
+                count: 1,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_enum_to_bytes() {
+    let record = TaggedRecord::Detail(DetailRec {
+        kind: "D".to_string(),
+        name: "foo".to_string(),
+        amount: 12,
+    });
+
+    assert_eq!(record.to_bytes().unwrap(), b"Dfoo 0012");
+}
+
+#[test]
+fn test_enum_write_latin1() {
+    use fixed_width::FixedWidthRecord;
+
+    let record = TaggedRecord::Detail(DetailRec {
+        kind: "D".to_string(),
+        name: "café".to_string(),
+        amount: 12,
+    });
+
+    let mut buf = Vec::new();
+    record.write_latin1_to(&mut buf).unwrap();
+    assert_eq!(buf, b"Dcaf\xE90012");
+    assert_eq!(record.record_fields(), Some(DetailRec::fields()));
+}
+
+#[test]
+fn test_enum_unknown_tag() {
+    match TaggedRecord::from_bytes(b"X12345678") {
+        Err(fixed_width::Error::UnknownRecordType { tag, record }) => {
+            assert_eq!(tag, b"X");
+            assert_eq!(record, b"X12345678");
+        }
+        _ => panic!("expected an unknown record type error"),
+    }
+
+    assert!(TaggedRecord::from_bytes(b"").is_err());
+}
+
+#[derive(FixedWidth, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct Renamed {
+    #[fixed_width(range = "0..2")]
+    pub account_number: String,
+    #[fixed_width(range = "2..4")]
+    #[serde(rename = "sortCode")]
+    pub sort: String,
+    #[fixed_width(range = "4..6", name = "BALANCE")]
+    #[serde(rename = "bal")]
+    pub balance: String,
+    #[fixed_width(range = "6..8")]
+    pub r#type: String,
+}
+
+#[derive(FixedWidth, Deserialize)]
+#[fixed_width(unnamed_fields)]
+#[allow(dead_code)]
+struct Unnamed {
+    #[fixed_width(range = "0..2")]
+    pub account_number: String,
+    #[fixed_width(range = "2..4", name = "sort")]
+    pub sort: String,
+}
+
+fn field_names<T: FixedWidth>(record: &str) -> Vec<String> {
+    let mut de = Deserializer::new(record.as_bytes(), T::fields());
+    let map: std::collections::HashMap<String, String> =
+        std::collections::HashMap::deserialize(&mut de).unwrap();
+    let mut keys: Vec<String> = map.into_keys().collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn test_serde_rename() {
+    assert_eq!(
+        field_names::<Renamed>("aabbccdd"),
+        vec!["BALANCE", "accountNumber", "sortCode", "type"]
+    );
+}
+
+#[test]
+fn test_unnamed_fields() {
+    assert_eq!(field_names::<Unnamed>("aabb"), vec!["0..2", "sort"]);
+
+    let record: Unnamed = fixed_width::from_bytes(b"aabb").unwrap();
+    assert_eq!(record.account_number, "aa");
+}
+
+#[test]
+fn test_rename_rules() {
+    #[derive(FixedWidth, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    #[allow(dead_code)]
+    struct Pascal {
+        #[fixed_width(range = "0..1")]
+        pub account_number: String,
+    }
+
+    #[derive(FixedWidth, Deserialize)]
+    #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+    #[allow(dead_code)]
+    struct ScreamingKebab {
+        #[fixed_width(range = "0..1")]
+        pub account_number: String,
+    }
+
+    #[derive(FixedWidth, Deserialize)]
+    #[serde(
+        rename(deserialize = "Renamed"),
+        rename_all(serialize = "UPPERCASE", deserialize = "kebab-case")
+    )]
+    #[allow(dead_code)]
+    struct Kebab {
+        #[fixed_width(range = "0..1")]
+        #[serde(rename(serialize = "ser_name", deserialize = "de_name"))]
+        pub renamed: String,
+        #[fixed_width(range = "1..2")]
+        pub account_number: String,
+    }
+
+    assert_eq!(field_names::<Pascal>("a"), vec!["AccountNumber"]);
+    assert_eq!(field_names::<ScreamingKebab>("a"), vec!["ACCOUNT-NUMBER"]);
+    assert_eq!(
+        field_names::<Kebab>("ab"),
+        vec!["account-number", "de_name"]
+    );
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Ranges {
+    #[fixed_width(range = "0..=3")]
+    pub code: String,
+    #[fixed_width(range = "4..6", justify = "right", pad_with = "0")]
+    pub count: usize,
+    #[fixed_width(range = "6..")]
+    pub notes: String,
+}
+
+#[test]
+fn test_inclusive_and_open_ended_ranges() {
+    let record: Ranges = fixed_width::from_bytes(b"abcd07free text notes").unwrap();
+
+    assert_eq!(
+        record,
+        Ranges {
+            code: "abcd".to_string(),
+            count: 7,
+            notes: "free text notes".to_string(),
+        }
+    );
+    assert_eq!(
+        fixed_width::to_string(&record).unwrap(),
+        "abcd07free text notes"
+    );
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Occurs {
+    #[fixed_width(range = "0..4")]
+    pub id: String,
+    #[fixed_width(
+        range = "4..",
+        occurs = "4",
+        width = "3",
+        pad_with = "0",
+        justify = "right"
+    )]
+    pub quarterly: [u32; 4],
+    #[fixed_width(range = "16..22", occurs = "3")]
+    pub codes: Vec<String>,
+    #[fixed_width(range = "22..24")]
+    pub trailer: String,
+}
+
+#[test]
+fn test_occurs_round_trip() {
+    let s = "ab  001020300400x y z zz";
+    let record: Occurs = fixed_width::from_str(s).unwrap();
+
+    assert_eq!(
+        record,
+        Occurs {
+            id: "ab".to_string(),
+            quarterly: [1, 20, 300, 400],
+            codes: vec!["x".to_string(), "y".to_string(), "z".to_string()],
+            trailer: "zz".to_string(),
+        }
+    );
+    assert_eq!(fixed_width::to_string(&record).unwrap(), s);
+}
+
+#[test]
+fn test_occurs_too_few_elements() {
+    #[derive(FixedWidth, Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Short {
+        #[fixed_width(range = "0..6", occurs = "2")]
+        pub values: [u32; 2],
+    }
+
+    let fields = fixed_width::FieldSet::Seq(vec![fixed_width::FieldSet::Seq(vec![
+        fixed_width::FieldSet::new_field(0..3),
+    ])]);
+    let res: Result<Short, _> = fixed_width::from_bytes_with_fields(b"001002", fields);
+
+    assert!(res.unwrap_err().to_string().contains("invalid length 1"));
+}
+
+#[test]
+fn test_occurs_wrong_vec_length() {
+    for (codes, actual) in [(vec!["x", "y"], 2), (vec!["w", "x", "y", "z"], 4)] {
+        let record = Occurs {
+            id: "ab".to_string(),
+            quarterly: [1, 20, 300, 400],
+            codes: codes.into_iter().map(String::from).collect(),
+            trailer: "zz".to_string(),
+        };
+
+        assert_eq!(
+            fixed_width::to_string(&record).unwrap_err().to_string(),
+            format!(
+                "field 'codes' (16..22): expected 3 elements for the repeated field, found {}",
+                actual
+            )
+        );
+    }
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+#[fixed_width(
+    literal(range = "0..2", value = "01", strict),
+    literal(range = "6..9", value = "***")
+)]
+struct Literals {
+    #[fixed_width(range = "2..6")]
+    pub name: String,
+    #[fixed_width(range = "9..12", pad_with = "0", justify = "right")]
+    pub count: usize,
+}
+
+#[test]
+fn test_literal_round_trip() {
+    let record = Literals {
+        name: "ab".to_string(),
+        count: 7,
+    };
+
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "01ab  ***007");
+    assert_eq!(fixed_width::from_str::<Literals>(&s).unwrap(), record);
+
+    // Only the strict literal is checked.
+    assert_eq!(
+        fixed_width::from_str::<Literals>("01ab  ---007").unwrap(),
+        record
+    );
+}
+
+#[test]
+fn test_strict_literal_mismatch() {
+    match fixed_width::from_str::<Literals>("02ab  ***007") {
+        Err(fixed_width::Error::DeserializeError(DeserializeError::LiteralMismatch {
+            expected,
+            found,
+        })) => {
+            assert_eq!(expected, "01");
+            assert_eq!(found, "02");
+        }
+        other => panic!("expected a literal mismatch, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let part = |r: std::ops::Range<usize>| {
+            s.get(r)
+                .and_then(|p| p.parse().ok())
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid date {:?}", s)))
+        };
+
+        Ok(Date {
+            year: part(0..4)?,
+            month: part(4..6)? as u8,
+            day: part(6..8)? as u8,
+        })
+    }
+}
+
+#[derive(FixedWidth, Deserialize, Debug, PartialEq)]
+struct Defaults {
+    #[fixed_width(range = "0..4", pad_with = "0", justify = "right", default = "0")]
+    pub quantity: u32,
+    #[fixed_width(range = "4..10", default = "1.5")]
+    pub rate: f64,
+    #[fixed_width(range = "10..12", default = "US")]
+    pub country: String,
+    #[fixed_width(range = "12..20", default = "19700101")]
+    pub date: Date,
+}
+
+#[test]
+fn test_blank_field_defaults() {
+    let record: Defaults = fixed_width::from_str("0000                ").unwrap();
+    assert_eq!(
+        record,
+        Defaults {
+            quantity: 0,
+            rate: 1.5,
+            country: "US".to_string(),
+            date: Date {
+                year: 1970,
+                month: 1,
+                day: 1
+            },
+        }
+    );
+
+    let record: Defaults = fixed_width::from_str("0012  2.25GB20240229").unwrap();
+    assert_eq!(
+        record,
+        Defaults {
+            quantity: 12,
+            rate: 2.25,
+            country: "GB".to_string(),
+            date: Date {
+                year: 2024,
+                month: 2,
+                day: 29
+            },
+        }
+    );
+
+    // Only a blank field is defaulted, other bad data still errors.
+    assert!(fixed_width::from_str::<Defaults>("00x0                ").is_err());
+}
+
+fn from_legacy_code(raw: &[u8]) -> result::Result<String, DeserializeError> {
+    match raw {
+        b"A" => Ok("Active".to_string()),
+        b"C" => Ok("Closed".to_string()),
+        _ => Err(DeserializeError::Message(format!(
+            "unknown status {:?}",
+            String::from_utf8_lossy(raw)
+        ))),
+    }
+}
+
+fn to_legacy_code(value: &[u8]) -> result::Result<Vec<u8>, fixed_width::SerializeError> {
+    Ok(value[..1].to_vec())
+}
+
+fn latin1(raw: &[u8]) -> result::Result<String, DeserializeError> {
+    Ok(raw.iter().map(|&b| b as char).collect())
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Status {
+    Active,
+    Closed,
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Hooks {
+    #[fixed_width(
+        range = "0..1",
+        deserialize_with = "from_legacy_code",
+        serialize_with = "to_legacy_code"
+    )]
+    pub status: Status,
+    #[fixed_width(range = "1..5", deserialize_with = "latin1")]
+    pub name: String,
+}
+
+#[test]
+fn test_field_hooks() {
+    let record: Hooks = fixed_width::from_bytes(b"Cj\xf6rn").unwrap();
+    assert_eq!(
+        record,
+        Hooks {
+            status: Status::Closed,
+            name: "jörn".to_string(),
+        }
+    );
+
+    let record = Hooks {
+        status: Status::Active,
+        name: "abc".to_string(),
+    };
+    assert_eq!(fixed_width::to_string(&record).unwrap(), "Aabc ");
+
+    assert!(fixed_width::from_bytes::<Hooks>(b"Xabcd").is_err());
+}
+
+#[derive(FixedWidth, Deserialize, Debug, PartialEq)]
+struct Binary {
+    #[fixed_width(range = "0..3")]
+    pub name: String,
+    #[fixed_width(range = "3..6", bytes)]
+    pub noise: Vec<u8>,
+    #[fixed_width(range = "6..8")]
+    pub count: usize,
+}
+
+#[test]
+fn test_bytes_field() {
+    let record: Binary = fixed_width::from_bytes(b"abc\xff\x00\xfe12").unwrap();
+
+    assert_eq!(
+        record,
+        Binary {
+            name: "abc".to_string(),
+            noise: vec![0xff, 0x00, 0xfe],
+            count: 12,
+        }
+    );
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Audited {
+    #[fixed_width(range = "0..4")]
+    pub code: String,
+    #[fixed_width(raw_record)]
+    pub raw: String,
+    #[fixed_width(range = "4..10", justify = "right", pad_with = "0")]
+    pub amount: u32,
+}
+
+#[test]
+fn test_raw_record_field() {
+    let record: Audited = fixed_width::from_str("AB  000042  ").unwrap();
+
+    assert_eq!(
+        record,
+        Audited {
+            code: "AB".to_string(),
+            raw: "AB  000042  ".to_string(),
+            amount: 42,
+        }
+    );
+
+    assert_eq!(fixed_width::to_string(&record).unwrap(), "AB  000042");
+
+    let edited = Audited {
+        amount: 7,
+        ..record
+    };
+    assert_eq!(fixed_width::to_string(&edited).unwrap(), "AB  000007");
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Enriched {
+    #[fixed_width(range = "0..4")]
+    pub code: String,
+    #[fixed_width(range = "4..8", blank_on_write)]
+    pub legacy_code: String,
+    #[fixed_width(range = "8..14", occurs = "2", blank_on_write)]
+    pub legacy_totals: [u32; 2],
+    #[fixed_width(range = "14..16")]
+    pub region: String,
+}
+
+#[test]
+fn test_blank_on_write_field() {
+    let record: Enriched = fixed_width::from_str("ABCDOLD1001002EU").unwrap();
+
+    assert_eq!(
+        record,
+        Enriched {
+            code: "ABCD".to_string(),
+            legacy_code: "OLD1".to_string(),
+            legacy_totals: [1, 2],
+            region: "EU".to_string(),
+        }
+    );
+
+    assert_eq!(fixed_width::to_string(&record).unwrap(), "ABCD          EU");
+}
+
+#[test]
+fn test_fields_built_once() {
+    let first = Stuff::fields();
+    let second = Stuff::fields();
+    assert_eq!(first, second);
+    assert_eq!(first.iter_flat().count(), 6);
+
+    assert_eq!(Occurs::fields(), Occurs::fields());
+    assert_eq!(Wrapper::<u32>::fields(), Wrapper::<u32>::fields());
+    assert_eq!(Readings::<3>::fields(), Readings::<3>::fields());
+}
+
+#[test]
+fn test_cached_flattened_fields() {
+    let first = Literals::flattened_fields().unwrap();
+    let second = Literals::flattened_fields().unwrap();
+
+    assert_eq!(first.as_ptr(), second.as_ptr());
+    assert_eq!(first, Literals::fields().flatten());
+
+    // Nested fields and fields from a `field_def` function are not cached.
+    assert!(Occurs::flattened_fields().is_none());
+    assert!(ByFieldDef::flattened_fields().is_none());
+
+    let s = "01ab  ***007";
+    let cached: Literals = fixed_width::from_str(s).unwrap();
+    let uncached: Literals = fixed_width::from_str_with_fields(s, Literals::fields()).unwrap();
+    assert_eq!(cached, uncached);
+
+    let mut w = fixed_width::Writer::from_memory();
+    fixed_width::to_writer_with_fields(&mut w, &cached, Literals::fields()).unwrap();
+    assert_eq!(fixed_width::to_string(&cached).unwrap(), String::from(w));
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Centered {
+    #[fixed_width(range = "0..8", justify = "center", pad_with = "-")]
+    pub title: String,
+    #[fixed_width(range = "8..11", justify = "centre")]
+    pub status: String,
+}
+
+#[test]
+fn test_center_justified() {
+    let record = Centered {
+        title: "Sales".to_string(),
+        status: "OK".to_string(),
+    };
+
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "-Sales--OK ");
+
+    let read: Centered = fixed_width::from_str("  Sales  OK ").unwrap();
+    assert_eq!(read, record);
+}
+
+#[test]
+fn test_record_width() {
+    // A gap between 19 and 21.
+    assert_eq!(Stuff::record_width(), 31);
+    // An open ended occurs range, computed from its elements.
+    assert_eq!(Occurs::record_width(), 24);
+    // A literal after the last field.
+    assert_eq!(Literals::record_width(), 12);
+    // A raw record field has no range of its own.
+    assert_eq!(Audited::record_width(), 10);
+    // A field that runs to the end of the record only counts up to its start.
+    assert_eq!(Ranges::record_width(), 6);
+    // Fields from a `field_def` function fall back to `total_width`.
+    assert_eq!(ByFieldDef::record_width(), 9);
+
+    assert_eq!(Stuff::record_width(), Stuff::fields().total_width());
+    assert_eq!(Occurs::record_width(), Occurs::fields().total_width());
+    assert_eq!(Literals::record_width(), Literals::fields().total_width());
+    assert_eq!(Audited::record_width(), Audited::fields().total_width());
+    assert_eq!(Ranges::record_width(), Ranges::fields().total_width());
+}
+
+#[test]
+fn test_enum_write_mixed() {
+    let detail = |name: &str, amount| {
+        TaggedRecord::Detail(DetailRec {
+            kind: "D".to_string(),
+            name: name.to_string(),
+            amount,
+        })
+    };
+    let records = vec![
+        TaggedRecord::Header(HeaderRec {
+            kind: "H".to_string(),
+            date: "20240101".to_string(),
+        }),
+        detail("foo", 12),
+        detail("ba", 3456),
+        TaggedRecord::Trailer(TrailerRec {
+            kind: "T".to_string(),
+            count: 2,
+        }),
+    ];
+
+    let mut wrtr = fixed_width::Writer::from_memory().linebreak(fixed_width::LineBreak::CRLF);
+    wrtr.write_mixed(records.iter()).unwrap();
+    assert_eq!(wrtr.stats().records, 4);
+
+    let s: String = wrtr.into();
+    assert_eq!(s, "H20240101\r\nDfoo 0012\r\nDba  3456\r\nT00000002");
+
+    let read = s
+        .split("\r\n")
+        .map(|r| TaggedRecord::from_bytes(r.as_bytes()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(read, records);
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Priced {
+    #[fixed_width(range = "0..10", decimals = "2", justify = "right")]
+    pub price: f64,
+    #[fixed_width(
+        range = "10..22",
+        occurs = "2",
+        decimals = "1",
+        pad_with = "0",
+        justify = "right"
+    )]
+    pub rates: [f32; 2],
+}
+
+#[test]
+fn test_float_decimals() {
+    let record = Priced {
+        price: 12345.678,
+        rates: [0.26, 1e-7],
+    };
+
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "  12345.680000.30000.0");
+
+    let read: Priced = fixed_width::from_str(&s).unwrap();
+    assert_eq!(fixed_width::to_string(&read).unwrap(), s);
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Totals {
+    #[fixed_width(range = "0..4", blank_when_zero, pad_with = "0", justify = "right")]
+    pub count: u32,
+    #[fixed_width(range = "4..10", blank_when_zero, decimals = "2", justify = "right")]
+    pub amount: Option<f64>,
+}
+
+#[test]
+fn test_blank_when_zero() {
+    let zero = Totals {
+        count: 0,
+        amount: Some(0.0),
+    };
+    assert_eq!(fixed_width::to_string(&zero).unwrap(), "          ");
+
+    let read: Totals = fixed_width::from_str("          ").unwrap();
+    assert_eq!(
+        read,
+        Totals {
+            count: 0,
+            amount: None,
+        }
+    );
+
+    let record = Totals {
+        count: 12,
+        amount: Some(3.5),
+    };
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "0012  3.50");
+    assert_eq!(fixed_width::from_str::<Totals>(&s).unwrap(), record);
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Address {
+    #[fixed_width(range = "0..8", case = "upper")]
+    pub street: String,
+    #[fixed_width(range = "8..10", case = "upper", case_on_read)]
+    pub state: String,
+}
+
+#[test]
+fn test_case_transform() {
+    let record = Address {
+        street: "Straße".to_string(),
+        state: "ny".to_string(),
+    };
+
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "STRASSE NY");
+
+    let read: Address = fixed_width::from_str("Main St ny").unwrap();
+    assert_eq!(read.street, "Main St");
+    assert_eq!(read.state, "NY");
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum AccountKind {
+    Checking,
+    Savings,
+    Other,
+}
+
+fn account_codes() -> &'static [(&'static str, &'static str)] {
+    &[("01", "Checking"), ("02", "Savings"), ("99", "Other")]
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Account {
+    #[fixed_width(range = "0..2", variants = "01=Checking, 02=Savings")]
+    pub kind: AccountKind,
+    #[fixed_width(range = "2..4", variants_with = "account_codes", catch_all = "Other")]
+    pub previous_kind: AccountKind,
+}
+
+#[test]
+fn test_enum_codes() {
+    let record = Account {
+        kind: AccountKind::Savings,
+        previous_kind: AccountKind::Other,
+    };
+
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "0299");
+    assert_eq!(fixed_width::from_str::<Account>(&s).unwrap(), record);
+
+    let read: Account = fixed_width::from_str("0142").unwrap();
+    assert_eq!(read.kind, AccountKind::Checking);
+    assert_eq!(read.previous_kind, AccountKind::Other);
+
+    let err = fixed_width::from_str::<Account>("0701").unwrap_err();
+    assert!(err.to_string().contains(r#"unknown code "07""#), "{}", err);
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Borrowed<'a> {
+    #[fixed_width(range = "0..4")]
+    pub code: &'a str,
+    #[fixed_width(range = "4..12", justify = "right")]
+    pub name: &'a str,
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Wrapper<T>
+where
+    T: Clone,
+{
+    #[fixed_width(range = "0..2")]
+    pub kind: String,
+    #[fixed_width(range = "2..8", pad_with = "0", justify = "right")]
+    pub value: T,
+}
+
+#[derive(FixedWidth)]
+#[allow(dead_code)]
+struct Readings<const N: usize> {
+    #[fixed_width(range = "0..4")]
+    pub id: String,
+    #[fixed_width(range = "4..10", occurs = "3")]
+    pub readings: [u16; N],
+}
+
+#[test]
+fn test_borrowed_lifetime() {
+    let bytes = b"AB12    Carl";
+    let record: Borrowed = fixed_width::from_bytes_borrowed(bytes, &Borrowed::fields()).unwrap();
+
+    assert_eq!(
+        record,
+        Borrowed {
+            code: "AB12",
+            name: "Carl"
+        }
+    );
+    assert_eq!(record.name.as_ptr(), bytes[8..].as_ptr());
+    assert_eq!(Borrowed::record_width(), 12);
+    assert_eq!(fixed_width::to_string(&record).unwrap(), "AB12    Carl");
+}
+
+#[test]
+fn test_generic_wrapper() {
+    let number: Wrapper<u32> = fixed_width::from_str("NU000042").unwrap();
+    assert_eq!(
+        number,
+        Wrapper {
+            kind: "NU".to_string(),
+            value: 42
+        }
+    );
+
+    let text: Wrapper<String> = fixed_width::from_str("TX000042").unwrap();
+    assert_eq!(text.value, "000042");
+
+    assert_eq!(fixed_width::to_string(&number).unwrap(), "NU000042");
+    assert_eq!(Wrapper::<u32>::fields(), Wrapper::<String>::fields());
+}
+
+#[test]
+fn test_const_generic_array() {
+    let fields: Vec<_> = Readings::<3>::fields()
+        .iter_flat()
+        .map(|conf| conf.range())
+        .collect();
+
+    assert_eq!(fields, vec![0..4, 4..6, 6..8, 8..10]);
+    assert_eq!(Readings::<3>::record_width(), 10);
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Pair(
+    #[fixed_width(range = "0..3", pad_with = "0", justify = "right")] u16,
+    #[fixed_width(range = "3..6")] String,
+);
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+#[fixed_width(unnamed_fields)]
+struct UnnamedPair(
+    #[fixed_width(range = "0..2")] String,
+    #[fixed_width(range = "2..4", name = "second")] String,
+);
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+struct Code(#[fixed_width(range = "0..4", justify = "right")] String);
+
+#[derive(FixedWidth, Serialize, Deserialize)]
+struct StuffRecord(Stuff);
+
+#[derive(FixedWidth, Serialize, Deserialize)]
+struct Newtype<T>(T);
+
+#[test]
+fn test_tuple_struct_round_trip() {
+    let pair: Pair = fixed_width::from_str("042abc").unwrap();
+    assert_eq!(pair, Pair(42, "abc".to_string()));
+    assert_eq!(fixed_width::to_string(&pair).unwrap(), "042abc");
+    assert_eq!(Pair::record_width(), 6);
+
+    assert_eq!(field_names::<Pair>("042abc"), vec!["0", "1"]);
+    assert_eq!(field_names::<UnnamedPair>("aabb"), vec!["0..2", "second"]);
+}
+
+#[test]
+fn test_newtype_struct_with_attribute() {
+    let code: Code = fixed_width::from_str("  AB").unwrap();
+    assert_eq!(code, Code("AB".to_string()));
+    assert_eq!(fixed_width::to_string(&code).unwrap(), "  AB");
+}
+
+#[test]
+fn test_newtype_struct_takes_inner_fields() {
+    assert_eq!(StuffRecord::fields(), Stuff::fields());
+    assert_eq!(StuffRecord::record_width(), Stuff::record_width());
+    assert_eq!(StuffRecord::flattened_fields(), Stuff::flattened_fields());
+
+    let record: StuffRecord = fixed_width::from_str("   foo000bar234   9  foobar123 ").unwrap();
+    assert_eq!(record.0.stuff1, "foo");
+    assert_eq!(record.0.stuff3, 234);
+    assert_eq!(
+        fixed_width::to_string(&record).unwrap(),
+        "foo   000bar2349   foobar 123"
+    );
+}
+
+#[test]
+fn test_generic_newtype_takes_inner_fields() {
+    assert_eq!(Newtype::<Stuff>::fields(), Stuff::fields());
+    assert_eq!(Newtype::<Code>::record_width(), 4);
+
+    let record: Newtype<Code> = fixed_width::from_str("  42").unwrap();
+    assert_eq!(record.0, Code("42".to_string()));
+    assert_eq!(fixed_width::to_string(&record).unwrap(), "  42");
+}
+
+#[derive(FixedWidth, Serialize, Deserialize, Debug, PartialEq)]
+#[fixed_width(
+    default_pad_with = "0",
+    default_justify = "right",
+    default_case = "upper"
+)]
+struct Numerics {
+    #[fixed_width(range = "0..4")]
+    pub count: usize,
+    #[fixed_width(range = "4..10")]
+    pub amount: usize,
+    #[fixed_width(range = "10..16", pad_with = " ", justify = "left", case = "none")]
+    pub label: String,
+    #[fixed_width(range = "16..18")]
+    pub code: String,
+}
+
+#[test]
+fn test_container_defaults() {
+    use fixed_width::{Case, FieldSet, Justify};
+
+    let expected = FieldSet::Seq(vec![
+        FieldSet::new_field(0..4)
+            .name("count")
+            .pad_with('0')
+            .justify(Justify::Right)
+            .transform(Case::Upper),
+        FieldSet::new_field(4..10)
+            .name("amount")
+            .pad_with('0')
+            .justify(Justify::Right)
+            .transform(Case::Upper),
+        FieldSet::new_field(10..16).name("label"),
+        FieldSet::new_field(16..18)
+            .name("code")
+            .pad_with('0')
+            .justify(Justify::Right)
+            .transform(Case::Upper),
+    ]);
+    assert_eq!(Numerics::fields(), expected);
+
+    let record = Numerics {
+        count: 3,
+        amount: 1250,
+        label: "fees".to_string(),
+        code: "x".to_string(),
+    };
+    let s = fixed_width::to_string(&record).unwrap();
+    assert_eq!(s, "0003001250fees  0X");
+    assert_eq!(
+        fixed_width::from_str::<Numerics>(&s).unwrap(),
+        Numerics {
+            code: "0X".to_string(),
+            ..record
+        }
+    );
+}